@@ -19,6 +19,7 @@ use std::{error, fmt, fs, io};
 use clarity::vm::types::PrincipalData;
 use serde::Deserialize;
 use serde_json::json;
+use stacks_common::codec::{Error as codec_error, StacksMessageCodec};
 use stacks_common::types::chainstate::{
     BlockHeaderHash, BurnchainHeaderHash, StacksAddress, StacksBlockId, TrieHash, VRFSeed,
 };
@@ -439,6 +440,22 @@ impl BlockstackOperationType {
         }
     }
 
+    /// Serialize the operation's data: its opcode and payload, as they appear after the magic
+    /// bytes in its `OP_RETURN` output
+    pub fn serialize_op_data(&self) -> Result<Vec<u8>, codec_error> {
+        let mut bytes = vec![];
+        match self {
+            BlockstackOperationType::LeaderKeyRegister(op) => op.consensus_serialize(&mut bytes),
+            BlockstackOperationType::LeaderBlockCommit(op) => op.consensus_serialize(&mut bytes),
+            BlockstackOperationType::StackStx(op) => op.consensus_serialize(&mut bytes),
+            BlockstackOperationType::PreStx(op) => op.consensus_serialize(&mut bytes),
+            BlockstackOperationType::TransferStx(op) => op.consensus_serialize(&mut bytes),
+            BlockstackOperationType::DelegateStx(op) => op.consensus_serialize(&mut bytes),
+            BlockstackOperationType::VoteForAggregateKey(op) => op.consensus_serialize(&mut bytes),
+        }?;
+        Ok(bytes)
+    }
+
     #[cfg(test)]
     pub fn set_block_height(&mut self, height: u64) {
         match self {
@@ -55,12 +55,13 @@ use crate::chainstate::burn::{BlockSnapshot, ConsensusHash};
 use crate::chainstate::coordinator::comm::{
     ArcCounterCoordinatorNotices, CoordinatorEvents, CoordinatorNotices, CoordinatorReceivers,
 };
+use crate::chainstate::nakamoto::NakamotoChainState;
 use crate::chainstate::stacks::address::PoxAddress;
 use crate::chainstate::stacks::boot::{POX_3_NAME, POX_4_NAME};
 use crate::chainstate::stacks::db::accounts::MinerReward;
 use crate::chainstate::stacks::db::{
-    ChainStateBootData, ClarityTx, MinerRewardInfo, StacksChainState, StacksEpochReceipt,
-    StacksHeaderInfo,
+    BlockProcessingTimings, ChainStateBootData, ClarityTx, MinerRewardInfo, StacksChainState,
+    StacksEpochReceipt, StacksHeaderInfo,
 };
 use crate::chainstate::stacks::events::{
     StacksBlockEventData, StacksTransactionEvent, StacksTransactionReceipt, TransactionOrigin,
@@ -161,6 +162,27 @@ impl RewardCycleInfo {
     }
 }
 
+/// What the coordinator was processing when it announced the canonical Stacks chain tip
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TipChangeCause {
+    /// New Stacks blocks
+    NewBlock,
+    /// New burnchain blocks on the same burnchain fork
+    Sortition,
+    /// New burnchain blocks on a different burnchain fork than the last canonical burn block
+    BurnReorg,
+}
+
+impl std::fmt::Display for TipChangeCause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TipChangeCause::NewBlock => write!(f, "new_block"),
+            TipChangeCause::Sortition => write!(f, "sortition"),
+            TipChangeCause::BurnReorg => write!(f, "burn_reorg"),
+        }
+    }
+}
+
 pub trait BlockEventDispatcher {
     fn announce_block(
         &self,
@@ -181,6 +203,7 @@ pub trait BlockEventDispatcher {
         signer_bitvec: &Option<BitVec<4000>>,
         block_timestamp: Option<u64>,
         coinbase_height: u64,
+        timings: &BlockProcessingTimings,
     );
 
     /// called whenever a burn block is about to be
@@ -195,6 +218,17 @@ pub trait BlockEventDispatcher {
         burns: u64,
         reward_recipients: Vec<PoxAddress>,
     );
+
+    /// called whenever the coordinator has processed new
+    ///  Stacks or burnchain blocks, with the canonical Stacks
+    ///  chain tip afterwards. note, the tip may not have changed.
+    fn announce_canonical_tip(&self, tip: &StacksBlockId, cause: TipChangeCause);
+
+    /// Whether anything wants `announce_canonical_tip()`. Looking up the tip takes DB reads,
+    /// so the coordinator skips them unless the dispatcher asks for it.
+    fn wants_tip_changes(&self) -> bool {
+        false
+    }
 }
 
 pub struct ChainsCoordinatorConfig {
@@ -590,12 +624,14 @@ impl<
                     warn!("Error processing new stacks block: {:?}", e);
                 }
             }
+            self.announce_canonical_tip(TipChangeCause::NewBlock);
 
             signal_mining_ready(miner_status.clone());
         }
         if (bits & (CoordinatorEvents::NEW_BURN_BLOCK as u8)) != 0 {
             signal_mining_blocked(miner_status.clone());
             debug!("Received new burn block notice");
+            let prior_burn_tip = self.prior_burn_tip();
             match self.handle_new_burnchain_block() {
                 Ok(burn_block_status) => match burn_block_status {
                     NewBurnchainBlockStatus::Ready => {}
@@ -610,6 +646,9 @@ impl<
                     warn!("Error processing new burn block: {:?}", e);
                 }
             }
+            if self.wants_tip_changes() {
+                self.announce_canonical_tip(self.burn_tip_change_cause(prior_burn_tip.as_ref()));
+            }
             signal_mining_ready(miner_status.clone());
         }
         if (bits & (CoordinatorEvents::STOP as u8)) != 0 {
@@ -1109,6 +1148,70 @@ impl<
         B: BurnchainHeaderReader,
     > ChainsCoordinator<'a, T, N, U, CE, FE, B>
 {
+    /// Does the event dispatcher (if there is one) want to hear about the canonical Stacks chain
+    /// tip?
+    pub fn wants_tip_changes(&self) -> bool {
+        self.dispatcher
+            .is_some_and(|dispatcher| dispatcher.wants_tip_changes())
+    }
+
+    /// The canonical burnchain tip from before processing new burnchain blocks, for
+    /// `burn_tip_change_cause()`. Only looked up if the dispatcher wants tip changes.
+    pub fn prior_burn_tip(&self) -> Option<BlockSnapshot> {
+        if !self.wants_tip_changes() {
+            return None;
+        }
+        SortitionDB::get_canonical_burn_chain_tip(self.sortition_db.conn()).ok()
+    }
+
+    /// Tell the event dispatcher (if there is one, and it wants tip changes) what the canonical
+    /// Stacks chain tip is, after processing new blocks.
+    pub fn announce_canonical_tip(&self, cause: TipChangeCause) {
+        let Some(dispatcher) = self.dispatcher else {
+            return;
+        };
+        if !dispatcher.wants_tip_changes() {
+            return;
+        }
+        match NakamotoChainState::get_canonical_block_header(
+            self.chain_state_db.db(),
+            &self.sortition_db,
+        ) {
+            Ok(Some(tip)) => dispatcher.announce_canonical_tip(&tip.index_block_hash(), cause),
+            Ok(None) => {}
+            Err(e) => {
+                warn!("Failed to load canonical Stacks tip to announce: {:?}", &e);
+            }
+        }
+    }
+
+    /// Work out why the canonical Stacks chain tip may have changed after processing new
+    /// burnchain blocks, given the canonical burnchain tip from before.  It's a burnchain reorg
+    /// if that burn block is no longer on the canonical burnchain fork.
+    pub fn burn_tip_change_cause(&self, prior_burn_tip: Option<&BlockSnapshot>) -> TipChangeCause {
+        let Some(prior_burn_tip) = prior_burn_tip else {
+            return TipChangeCause::Sortition;
+        };
+        let ancestor = SortitionDB::get_canonical_burn_chain_tip(self.sortition_db.conn())
+            .and_then(|burn_tip| {
+                SortitionDB::get_ancestor_snapshot(
+                    &self.sortition_db.index_conn(),
+                    prior_burn_tip.block_height,
+                    &burn_tip.sortition_id,
+                )
+            });
+        match ancestor {
+            Ok(Some(ancestor)) if ancestor.burn_header_hash != prior_burn_tip.burn_header_hash => {
+                TipChangeCause::BurnReorg
+            }
+            Ok(_) => TipChangeCause::Sortition,
+            Err(e) => {
+                warn!("Failed to check for a burnchain reorg: {:?}", &e);
+                TipChangeCause::Sortition
+            }
+        }
+    }
+
     /// Process new Stacks blocks.  If we get stuck for want of a missing PoX anchor block, return
     /// its hash.
     pub fn handle_new_stacks_block(&mut self) -> Result<Option<BlockHeaderHash>, Error> {
@@ -61,7 +61,9 @@ use crate::chainstate::stacks::boot::{
     PoxStartCycleInfo, COSTS_2_NAME, POX_1_NAME, POX_2_NAME, POX_3_NAME,
 };
 use crate::chainstate::stacks::db::accounts::MinerReward;
-use crate::chainstate::stacks::db::{ClarityTx, StacksChainState, StacksHeaderInfo};
+use crate::chainstate::stacks::db::{
+    BlockProcessingTimings, ClarityTx, StacksChainState, StacksHeaderInfo,
+};
 use crate::chainstate::stacks::miner::BlockBuilder;
 use crate::chainstate::stacks::*;
 use crate::clarity_vm::clarity::ClarityConnection;
@@ -432,6 +434,7 @@ impl BlockEventDispatcher for NullEventDispatcher {
         _signer_bitvec: &Option<BitVec<4000>>,
         _block_timestamp: Option<u64>,
         _coinbase_height: u64,
+        _timings: &BlockProcessingTimings,
     ) {
         assert!(
             false,
@@ -448,6 +451,8 @@ impl BlockEventDispatcher for NullEventDispatcher {
         _slot_holders: Vec<PoxAddress>,
     ) {
     }
+
+    fn announce_canonical_tip(&self, _tip: &StacksBlockId, _cause: TipChangeCause) {}
 }
 
 pub fn make_coordinator<'a>(
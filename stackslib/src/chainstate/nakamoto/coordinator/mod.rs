@@ -42,7 +42,7 @@ use crate::chainstate::coordinator::comm::{
 use crate::chainstate::coordinator::{
     calculate_paid_rewards, dispatcher_announce_burn_ops, BlockEventDispatcher, ChainsCoordinator,
     Error, OnChainRewardSetProvider, PaidRewards, PoxAnchorBlockStatus, RewardCycleInfo,
-    RewardSetProvider,
+    RewardSetProvider, TipChangeCause,
 };
 use crate::chainstate::nakamoto::NakamotoChainState;
 use crate::chainstate::stacks::boot::{RewardSet, SIGNERS_NAME};
@@ -730,12 +730,14 @@ impl<
                     warn!("Error processing new stacks block: {:?}", e);
                 }
             }
+            self.announce_canonical_tip(TipChangeCause::NewBlock);
 
             signal_mining_ready(miner_status.clone());
         }
         if (bits & (CoordinatorEvents::NEW_BURN_BLOCK as u8)) != 0 {
             signal_mining_blocked(miner_status.clone());
             debug!("Received new burn block notice");
+            let prior_burn_tip = self.prior_burn_tip();
             match self.handle_new_nakamoto_burnchain_block() {
                 Ok(can_proceed) => {
                     if !can_proceed {
@@ -746,6 +748,9 @@ impl<
                     warn!("Error processing new burn block: {:?}", e);
                 }
             }
+            if self.wants_tip_changes() {
+                self.announce_canonical_tip(self.burn_tip_change_cause(prior_burn_tip.as_ref()));
+            }
             signal_mining_ready(miner_status.clone());
         }
         if (bits & (CoordinatorEvents::STOP as u8)) != 0 {
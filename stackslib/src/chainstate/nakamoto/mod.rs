@@ -18,6 +18,7 @@ use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::ops::{Deref, DerefMut, Range};
 use std::path::PathBuf;
+use std::time::Instant;
 
 use clarity::util::secp256k1::Secp256k1PublicKey;
 use clarity::vm::ast::ASTRules;
@@ -92,7 +93,8 @@ use crate::chainstate::stacks::address::PoxAddress;
 use crate::chainstate::stacks::boot::{POX_4_NAME, SIGNERS_UPDATE_STATE};
 use crate::chainstate::stacks::db::blocks::DummyEventDispatcher;
 use crate::chainstate::stacks::db::{
-    DBConfig as ChainstateConfig, StacksChainState, StacksDBConn, StacksDBTx,
+    BlockProcessingTimings, DBConfig as ChainstateConfig, StacksChainState, StacksDBConn,
+    StacksDBTx,
 };
 use crate::chainstate::stacks::index::marf::MarfConnection;
 use crate::chainstate::stacks::{
@@ -2119,6 +2121,7 @@ impl NakamotoChainState {
                 &Some(signer_bitvec),
                 Some(block_timestamp),
                 receipt.coinbase_height,
+                &receipt.timings,
             );
         }
 
@@ -2713,6 +2716,89 @@ impl NakamotoChainState {
         Ok(StacksChainState::get_stacks_block_header_info_by_consensus_hash(db, consensus_hash)?)
     }
 
+    /// DO NOT USE IN CONSENSUS CODE.  Different nodes can have different blocks for the same
+    /// tenure.
+    ///
+    /// Get the highest known block in the tenure of the given Nakamoto block which is either that
+    /// block or one of its descendants.  Ties will be broken by timestamp.
+    /// Returns Ok(None) if the block is not a known Nakamoto block.
+    pub fn get_highest_known_descendant_in_tenure<SDBI: StacksDBIndexed>(
+        chainstate_conn: &mut SDBI,
+        block_id: &StacksBlockId,
+    ) -> Result<Option<StacksHeaderInfo>, ChainstateError> {
+        let Some(header) = Self::get_block_header_nakamoto(chainstate_conn.sqlite(), block_id)?
+        else {
+            return Ok(None);
+        };
+        let Some(nakamoto_header) = header.anchored_header.as_stacks_nakamoto() else {
+            return Ok(None);
+        };
+
+        let qry = "SELECT * FROM nakamoto_block_headers WHERE consensus_hash = ?1 AND block_height >= ?2 ORDER BY block_height DESC, timestamp DESC";
+        let args = params![
+            nakamoto_header.consensus_hash,
+            u64_to_sql(header.stacks_block_height)?
+        ];
+        let candidates: Vec<StacksHeaderInfo> = query_rows(chainstate_conn.sqlite(), qry, args)?;
+        for candidate in candidates.into_iter() {
+            let ancestor_id = chainstate_conn
+                .get_ancestor_block_id(header.stacks_block_height, &candidate.index_block_hash())?;
+            if ancestor_id.as_ref() == Some(block_id) {
+                return Ok(Some(candidate));
+            }
+        }
+        Ok(Some(header))
+    }
+
+    /// DO NOT USE IN CONSENSUS CODE.  Different nodes can have different blocks for the same
+    /// tenure.
+    ///
+    /// Get the known descendants of the given Nakamoto block in its tenure, oldest first, on the
+    /// way to the highest of them.  Ties will be broken by timestamp.
+    /// Returns an empty list if the block has no known descendants in its tenure, or is not a
+    /// known Nakamoto block.
+    pub fn get_known_descendants_in_tenure(
+        db: &Connection,
+        block_id: &StacksBlockId,
+    ) -> Result<Vec<NakamotoBlockHeader>, ChainstateError> {
+        let Some(header) = Self::get_block_header_nakamoto(db, block_id)? else {
+            return Ok(vec![]);
+        };
+        let Some(nakamoto_header) = header.anchored_header.as_stacks_nakamoto() else {
+            return Ok(vec![]);
+        };
+
+        let qry = "SELECT * FROM nakamoto_block_headers WHERE consensus_hash = ?1 AND block_height > ?2 ORDER BY block_height DESC, timestamp DESC";
+        let args = params![
+            nakamoto_header.consensus_hash,
+            u64_to_sql(header.stacks_block_height)?
+        ];
+        let candidates: Vec<StacksHeaderInfo> = query_rows(db, qry, args)?;
+        for candidate in candidates.into_iter() {
+            // walk back to the given block's height, to see if the candidate descends from it
+            let mut descendants = vec![];
+            let mut cursor = Some(candidate);
+            while let Some(cursor_header) = cursor.take() {
+                let Some(cursor_nakamoto_header) =
+                    cursor_header.anchored_header.as_stacks_nakamoto()
+                else {
+                    break;
+                };
+                if cursor_header.stacks_block_height <= header.stacks_block_height {
+                    if &cursor_nakamoto_header.block_id() == block_id {
+                        descendants.reverse();
+                        return Ok(descendants);
+                    }
+                    break;
+                }
+                descendants.push(cursor_nakamoto_header.clone());
+                cursor =
+                    Self::get_block_header_nakamoto(db, &cursor_nakamoto_header.parent_block_id)?;
+            }
+        }
+        Ok(vec![])
+    }
+
     /// Get the VRF proof for a Stacks block.
     /// For Nakamoto blocks, this is the VRF proof contained in the coinbase of the tenure-start
     /// block of the given tenure identified by the consensus hash.
@@ -3915,6 +4001,7 @@ impl NakamotoChainState {
             &block.header.block_hash().to_hex(),
             block.txs.len()
         );
+        let validation_start = Instant::now();
 
         let ast_rules = ASTRules::PrecheckSize;
         let next_block_height = block.header.chain_length;
@@ -4152,6 +4239,7 @@ impl NakamotoChainState {
             &tenure_block_commit,
             active_reward_set,
         )?;
+        let execution_start = Instant::now();
 
         let starting_cost = clarity_tx.cost_so_far();
 
@@ -4247,6 +4335,10 @@ impl NakamotoChainState {
 
         debug!("Reached state root {}", root_hash;
                "block_cost" => %block_execution_cost);
+        let timings = BlockProcessingTimings {
+            validation: execution_start.duration_since(validation_start),
+            execution: execution_start.elapsed(),
+        };
 
         // good to go!
         let block_limit = clarity_tx
@@ -4384,6 +4476,7 @@ impl NakamotoChainState {
             epoch_transition: applied_epoch_transition,
             signers_updated,
             coinbase_height,
+            timings,
         };
 
         Ok((epoch_receipt, clarity_commit, reward_set_data))
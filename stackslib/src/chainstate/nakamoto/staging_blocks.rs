@@ -45,6 +45,8 @@ pub enum NakamotoBlockObtainMethod {
     Pushed,
     Mined,
     Uploaded,
+    /// imported from a tenure archive
+    Imported,
 }
 
 impl fmt::Display for NakamotoBlockObtainMethod {
@@ -53,6 +55,42 @@ impl fmt::Display for NakamotoBlockObtainMethod {
     }
 }
 
+/// Somewhere outside the staging DB to look for Nakamoto blocks, such as cold storage that old
+/// blocks have been moved to
+pub trait ArchivalBlockSource: Send + Sync {
+    /// Get the serialized block with the given index block hash, if the archive has it
+    fn get_block_bytes(&self, block_id: &StacksBlockId) -> Option<Vec<u8>>;
+}
+
+/// An archive kept in a local (or mounted) directory, with one file per block named by the
+/// block's index block hash in hex
+pub struct DirectoryBlockSource {
+    dir: PathBuf,
+}
+
+impl DirectoryBlockSource {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+}
+
+impl ArchivalBlockSource for DirectoryBlockSource {
+    fn get_block_bytes(&self, block_id: &StacksBlockId) -> Option<Vec<u8>> {
+        let path = self.dir.join(block_id.to_hex());
+        match fs::read(&path) {
+            Ok(bytes) => Some(bytes),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => {
+                warn!(
+                    "Failed to read archived block";
+                    "path" => %path.display(), "error" => ?e
+                );
+                None
+            }
+        }
+    }
+}
+
 pub const NAKAMOTO_STAGING_DB_SCHEMA_1: &'static [&'static str] = &[
     r#"
   -- Table for staging nakamoto blocks
@@ -302,6 +340,27 @@ impl<'a> NakamotoStagingBlocksConnRef<'a> {
             .optional()?)
     }
 
+    /// Given a block ID, determine if it has children in a different tenure that have been
+    /// processed and accepted (i.e. its tenure has ended with it)
+    pub fn has_children_in_other_tenure(
+        &self,
+        index_block_hash: &StacksBlockId,
+        consensus_hash: &ConsensusHash,
+    ) -> Result<bool, ChainstateError> {
+        let qry = "SELECT 1 FROM nakamoto_staging_blocks WHERE parent_block_id = ?1 AND consensus_hash != ?2 AND processed = 1 AND orphaned = 0 LIMIT 1";
+        let args = params![index_block_hash, consensus_hash];
+        let children_flags: Option<u32> = query_row(self, qry, args)?;
+        Ok(children_flags.is_some())
+    }
+
+    /// Get SQLite's `data_version` for this connection.  It changes whenever another connection
+    /// commits to the staging DB (e.g. when a block is stored or processed), so callers can poll
+    /// it cheaply to learn when to look for new blocks.
+    pub fn get_data_version(&self) -> Result<i64, ChainstateError> {
+        let res: Option<i64> = query_row(self, "PRAGMA data_version", NO_PARAMS)?;
+        res.ok_or_else(|| ChainstateError::DBError(DBError::NotFoundError))
+    }
+
     /// Get a Nakamoto block by index block hash, as well as its size.
     /// Verifies its integrity.
     /// Returns Ok(Some(block, size)) if the block was present
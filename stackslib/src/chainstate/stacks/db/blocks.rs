@@ -18,6 +18,7 @@ use std::collections::{HashMap, HashSet};
 use std::io::prelude::*;
 use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
+use std::time::Instant;
 use std::{cmp, fmt, fs, io};
 
 pub use clarity::vm::analysis::errors::{CheckError, CheckErrors};
@@ -55,7 +56,7 @@ use crate::burnchains::db::{BurnchainDB, BurnchainHeaderReader};
 use crate::chainstate::burn::db::sortdb::*;
 use crate::chainstate::burn::operations::*;
 use crate::chainstate::burn::BlockSnapshot;
-use crate::chainstate::coordinator::BlockEventDispatcher;
+use crate::chainstate::coordinator::{BlockEventDispatcher, TipChangeCause};
 use crate::chainstate::nakamoto::signer_set::{NakamotoSigners, SignerCalculation};
 use crate::chainstate::nakamoto::NakamotoChainState;
 use crate::chainstate::stacks::address::{PoxAddress, StacksAddressExtensions};
@@ -192,6 +193,7 @@ impl BlockEventDispatcher for DummyEventDispatcher {
         _signer_bitvec: &Option<BitVec<4000>>,
         _block_timestamp: Option<u64>,
         _coinbase_height: u64,
+        _timings: &BlockProcessingTimings,
     ) {
         assert!(
             false,
@@ -212,6 +214,13 @@ impl BlockEventDispatcher for DummyEventDispatcher {
             "We should never try to announce to the dummy dispatcher"
         );
     }
+
+    fn announce_canonical_tip(&self, _tip: &StacksBlockId, _cause: TipChangeCause) {
+        assert!(
+            false,
+            "We should never try to announce to the dummy dispatcher"
+        );
+    }
 }
 
 impl MemPoolRejection {
@@ -5443,6 +5452,7 @@ impl StacksChainState {
             &block.block_hash().to_hex(),
             block.txs.len()
         );
+        let validation_start = Instant::now();
 
         let ast_rules =
             SortitionDB::get_ast_rules(burn_dbconn.tx(), chain_tip_burn_header_height.into())?;
@@ -5555,6 +5565,7 @@ impl StacksChainState {
             mainnet,
             None,
         )?;
+        let execution_start = Instant::now();
 
         let block_limit = clarity_tx.block_limit().unwrap_or_else(|| {
             warn!("Failed to read transaction block limit");
@@ -5800,6 +5811,11 @@ impl StacksChainState {
             )
         };
 
+        let timings = BlockProcessingTimings {
+            validation: execution_start.duration_since(validation_start),
+            execution: execution_start.elapsed(),
+        };
+
         let microblock_tail_opt = match microblocks.len() {
             0 => None,
             x => Some(microblocks[x - 1].header.clone()),
@@ -5826,6 +5842,7 @@ impl StacksChainState {
                 epoch_transition: applied_epoch_transition,
                 signers_updated: false,
                 coinbase_height,
+                timings,
             };
 
             return Ok((epoch_receipt, clarity_commit, None));
@@ -5919,6 +5936,7 @@ impl StacksChainState {
             epoch_transition: applied_epoch_transition,
             signers_updated,
             coinbase_height,
+            timings,
         };
 
         Ok((epoch_receipt, clarity_commit, reward_set_data))
@@ -6420,6 +6438,7 @@ impl StacksChainState {
                 &None,
                 None,
                 next_staging_block.height,
+                &epoch_receipt.timings,
             );
         }
 
@@ -19,6 +19,7 @@ use std::collections::{BTreeMap, HashSet};
 use std::io::prelude::*;
 use std::ops::{Deref, DerefMut};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use std::{fmt, fs, io};
 
 use clarity::vm::analysis::analysis_db::AnalysisDatabase;
@@ -211,6 +212,15 @@ pub struct MinerRewardInfo {
     pub from_parent_stacks_block_hash: BlockHeaderHash,
 }
 
+/// How long the stages of appending a block took
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlockProcessingTimings {
+    /// Checking the block against its parent and the burnchain, and setting up its execution
+    pub validation: Duration,
+    /// Running the block's transactions and checking the resulting state root
+    pub execution: Duration,
+}
+
 /// This is the block receipt for a Stacks block
 #[derive(Debug, Clone, PartialEq)]
 pub struct StacksEpochReceipt {
@@ -231,6 +241,8 @@ pub struct StacksEpochReceipt {
     /// Was .signers updated during this block?
     pub signers_updated: bool,
     pub coinbase_height: u64,
+    /// How long appending the block took
+    pub timings: BlockProcessingTimings,
 }
 
 /// Headers we serve over the network
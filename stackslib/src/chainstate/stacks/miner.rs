@@ -1406,8 +1406,16 @@ impl<'a> StacksMicroblockBuilder<'a> {
         self.runtime.num_mined = num_txs;
 
         mem_pool.drop_txs(&invalidated_txs)?;
-        event_dispatcher.mempool_txs_dropped(invalidated_txs, MemPoolDropReason::TOO_EXPENSIVE);
-        event_dispatcher.mempool_txs_dropped(to_drop_and_blacklist, MemPoolDropReason::PROBLEMATIC);
+        event_dispatcher.mempool_txs_dropped(
+            invalidated_txs,
+            MemPoolDropReason::TOO_EXPENSIVE,
+            None,
+        );
+        event_dispatcher.mempool_txs_dropped(
+            to_drop_and_blacklist,
+            MemPoolDropReason::PROBLEMATIC,
+            None,
+        );
 
         if blocked {
             debug!(
@@ -2505,8 +2513,12 @@ impl StacksBlockBuilder {
         mempool.drop_txs(&invalidated_txs)?;
 
         if let Some(observer) = event_observer {
-            observer.mempool_txs_dropped(invalidated_txs, MemPoolDropReason::TOO_EXPENSIVE);
-            observer.mempool_txs_dropped(to_drop_and_blacklist, MemPoolDropReason::PROBLEMATIC);
+            observer.mempool_txs_dropped(invalidated_txs, MemPoolDropReason::TOO_EXPENSIVE, None);
+            observer.mempool_txs_dropped(
+                to_drop_and_blacklist,
+                MemPoolDropReason::PROBLEMATIC,
+                None,
+            );
         }
 
         if let Err(e) = result {
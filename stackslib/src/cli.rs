@@ -30,7 +30,7 @@ use stacks_common::types::chainstate::{BlockHeaderHash, BurnchainHeaderHash, Sta
 use stacks_common::types::sqlite::NO_PARAMS;
 
 use crate::burnchains::db::BurnchainDB;
-use crate::burnchains::PoxConstants;
+use crate::burnchains::{Burnchain, PoxConstants};
 use crate::chainstate::burn::db::sortdb::{
     get_ancestor_sort_id, SortitionDB, SortitionHandle, SortitionHandleContext,
 };
@@ -43,6 +43,7 @@ use crate::chainstate::stacks::miner::*;
 use crate::chainstate::stacks::{Error as ChainstateError, *};
 use crate::clarity_vm::clarity::ClarityInstance;
 use crate::core::*;
+use crate::net::api::tenure_archive::{decode_tenure_archive, import_tenure_archive};
 use crate::util_lib::db::IndexDBTx;
 
 /// Can be used with CLI commands to support non-mainnet chainstate
@@ -245,6 +246,68 @@ pub fn command_replay_mock_mining(argv: &[String], conf: Option<&StacksChainConf
     }
 }
 
+/// Import a tenure archive, as served by `/v3/tenures/<block-id>?format=archive`, into the
+/// staging DB of a stopped node. The node processes the imported blocks once it is restarted.
+/// Terminates on error using `process::exit()`
+///
+/// Arguments:
+///  - `argv`: Args in CLI format: `<command-name> [args...]`
+///  - `conf`: Optional config for running on non-mainnet chainstate
+pub fn command_import_tenure_archive(argv: &[String], conf: Option<&StacksChainConfig>) {
+    let print_help_and_exit = || -> ! {
+        let n = &argv[0];
+        eprintln!("Usage:");
+        eprintln!("  {n} <database-path> <archive-path>");
+        process::exit(1);
+    };
+    let db_path = argv.get(1).unwrap_or_else(|| print_help_and_exit());
+    let archive_path = argv.get(2).unwrap_or_else(|| print_help_and_exit());
+
+    let archive_bytes = fs::read(archive_path).unwrap_or_else(|e| {
+        eprintln!("Failed to read {archive_path}: {e}");
+        process::exit(1);
+    });
+    let archive = decode_tenure_archive(&archive_bytes).unwrap_or_else(|e| {
+        eprintln!("Failed to decode tenure archive {archive_path}: {e:?}");
+        process::exit(1);
+    });
+
+    let default_conf = STACKS_CHAIN_CONFIG_DEFAULT_MAINNET;
+    let conf = conf.unwrap_or(&default_conf);
+
+    let mainnet = conf.chain_id == CHAIN_ID_MAINNET;
+    let chain_state_path = format!("{db_path}/chainstate/");
+    let sort_db_path = format!("{db_path}/burnchain/sortition");
+    let (mut chainstate, _) =
+        StacksChainState::open(mainnet, conf.chain_id, &chain_state_path, None).unwrap();
+    let sortdb = SortitionDB::open(&sort_db_path, false, conf.pox_constants.clone()).unwrap();
+
+    let mut burnchain =
+        Burnchain::new(&format!("{db_path}/burnchain"), "bitcoin", "mainnet").unwrap();
+    burnchain.first_block_height = conf.first_block_height;
+    burnchain.first_block_hash = conf.first_burn_header_hash.clone();
+    burnchain.first_block_timestamp = conf.first_burn_header_timestamp.try_into().unwrap();
+    burnchain.pox_constants = conf.pox_constants.clone();
+
+    let imported = import_tenure_archive(&burnchain, &sortdb, &mut chainstate, &archive, None)
+        .unwrap_or_else(|e| {
+            eprintln!("Failed to import tenure archive {archive_path}: {e:?}");
+            process::exit(1);
+        });
+    for (block_id, accepted) in imported.iter() {
+        println!("{block_id}: {accepted:?}");
+    }
+    println!(
+        "Imported {} of {} blocks of tenure {}",
+        imported
+            .iter()
+            .filter(|(_, accepted)| accepted.is_accepted())
+            .count(),
+        imported.len(),
+        &archive.manifest.consensus_hash
+    );
+}
+
 /// Fetch and process a `StagingBlock` from database and call `replay_block()` to validate
 fn replay_staging_block(
     db_path: &str,
@@ -61,13 +61,15 @@ use crate::core::{
 use crate::cost_estimates::metrics::{CostMetric, UnitMetric};
 use crate::cost_estimates::{CostEstimator, EstimatorError, UnitEstimator};
 use crate::monitoring::increment_stx_mempool_gc;
+use crate::net::api::geteventhealth::RPCEventHealthData;
+use crate::net::api::getmempooldrops::RPCMempoolDropsData;
 use crate::net::api::postblock_proposal::{BlockValidateOk, BlockValidateReject};
 use crate::net::Error as net_error;
 use crate::util_lib::bloom::{BloomCounter, BloomFilter, BloomNodeHasher};
 use crate::util_lib::db::{
-    query_int, query_row, query_row_columns, query_rows, sql_pragma, sqlite_open, table_exists,
-    tx_begin_immediate, tx_busy_handler, u64_to_sql, DBConn, DBTx, Error as db_error, Error,
-    FromColumn, FromRow,
+    query_count, query_int, query_row, query_row_columns, query_rows, sql_pragma, sqlite_open,
+    table_exists, tx_begin_immediate, tx_busy_handler, u64_to_sql, DBConn, DBTx, Error as db_error,
+    Error, FromColumn, FromRow,
 };
 use crate::{cost_estimates, monitoring};
 
@@ -357,6 +359,25 @@ pub enum MemPoolDropReason {
     PROBLEMATIC,
 }
 
+impl MemPoolDropReason {
+    /// Was the transaction dropped over its fee (outbid, or never picked up by miners)?
+    pub fn is_fee_related(&self) -> bool {
+        matches!(
+            self,
+            MemPoolDropReason::REPLACE_BY_FEE | MemPoolDropReason::STALE_COLLECT
+        )
+    }
+}
+
+/// The fee rates of the transactions in the mempool at a few percentiles, sent with fee-related
+/// drop events so that wallets can tell what fee would have been enough
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MemPoolFeeRatePercentiles {
+    pub p10: f64,
+    pub p50: f64,
+    pub p90: f64,
+}
+
 pub struct ConsiderTransaction {
     /// Transaction to consider in block assembly
     pub tx: MemPoolTxInfo,
@@ -390,7 +411,19 @@ pub trait ProposalCallbackReceiver: Send {
 
 pub trait MemPoolEventDispatcher {
     fn get_proposal_callback_receiver(&self) -> Option<Box<dyn ProposalCallbackReceiver>>;
-    fn mempool_txs_dropped(&self, txids: Vec<Txid>, reason: MemPoolDropReason);
+    /// Announce that `txids` were dropped from the mempool. `fee_rates` is only given for
+    /// fee-related drops, and only if `wants_drop_fee_rates()` is true.
+    fn mempool_txs_dropped(
+        &self,
+        txids: Vec<Txid>,
+        reason: MemPoolDropReason,
+        fee_rates: Option<MemPoolFeeRatePercentiles>,
+    );
+    /// Whether the mempool should compute its fee rate percentiles for fee-related drops.
+    /// This scans the mempool, so it is off unless the dispatcher asks for it.
+    fn wants_drop_fee_rates(&self) -> bool {
+        false
+    }
     fn mined_block_event(
         &self,
         target_burn_height: u64,
@@ -415,6 +448,16 @@ pub trait MemPoolEventDispatcher {
         consumed: &ExecutionCost,
         tx_results: Vec<TransactionEvent>,
     );
+    /// Report how event delivery to each of the node's event observers is doing, for
+    /// `GET /v2/debug/event_health`. Returns None if this dispatcher doesn't track it.
+    fn get_event_delivery_health(&self) -> Option<RPCEventHealthData> {
+        None
+    }
+    /// Report the transactions most recently dropped from the mempool, for
+    /// `GET /v2/debug/mempool_drops`. Returns None if this dispatcher doesn't keep them.
+    fn get_mempool_drop_history(&self) -> Option<RPCMempoolDropsData> {
+        None
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -2222,12 +2265,63 @@ impl MemPoolDB {
 
         // broadcast drop event if a tx is being replaced
         if let (Some(prior_tx), Some(event_observer)) = (prior_tx, event_observer) {
-            event_observer.mempool_txs_dropped(vec![prior_tx.txid], replace_reason);
+            MemPoolDB::announce_dropped_txs(
+                tx,
+                event_observer,
+                vec![prior_tx.txid],
+                replace_reason,
+            );
         };
 
         Ok(())
     }
 
+    /// Get the fee rates of the mempool's transactions at the 10th, 50th and 90th percentiles
+    /// (by nearest rank). Transactions whose fee rate hasn't been estimated yet are left out.
+    /// Returns None if there are no transactions with a fee rate.
+    pub fn get_fee_rate_percentiles(
+        conn: &DBConn,
+    ) -> Result<Option<MemPoolFeeRatePercentiles>, db_error> {
+        let sql = "SELECT COUNT(*) FROM mempool WHERE fee_rate IS NOT NULL";
+        let num_rated =
+            u64::try_from(query_count(conn, sql, NO_PARAMS)?).map_err(|_| db_error::ParseError)?;
+        if num_rated == 0 {
+            return Ok(None);
+        }
+
+        let fee_rate_at = |percentile: u64| -> Result<f64, db_error> {
+            let rank = (num_rated * percentile).div_ceil(100).max(1);
+            let sql = "SELECT fee_rate FROM mempool WHERE fee_rate IS NOT NULL
+                       ORDER BY fee_rate ASC LIMIT 1 OFFSET ?1";
+            conn.query_row(sql, params![u64_to_sql(rank - 1)?], |row| row.get(0))
+                .map_err(db_error::SqliteError)
+        };
+        Ok(Some(MemPoolFeeRatePercentiles {
+            p10: fee_rate_at(10)?,
+            p50: fee_rate_at(50)?,
+            p90: fee_rate_at(90)?,
+        }))
+    }
+
+    /// Tell `event_observer` that `txids` were dropped, along with the mempool's fee rate
+    /// percentiles if the drop was fee-related and the observer wants them
+    fn announce_dropped_txs(
+        conn: &DBConn,
+        event_observer: &dyn MemPoolEventDispatcher,
+        txids: Vec<Txid>,
+        reason: MemPoolDropReason,
+    ) {
+        let fee_rates = if reason.is_fee_related() && event_observer.wants_drop_fee_rates() {
+            MemPoolDB::get_fee_rate_percentiles(conn).unwrap_or_else(|e| {
+                warn!("Failed to compute mempool fee rate percentiles: {e:?}");
+                None
+            })
+        } else {
+            None
+        };
+        event_observer.mempool_txs_dropped(txids, reason, fee_rates);
+    }
+
     /// Garbage-collect the mempool according to the behavior specified in `behavior`.
     pub fn garbage_collect(
         &mut self,
@@ -2265,16 +2359,28 @@ impl MemPoolDB {
     ) -> Result<(), db_error> {
         let threshold_time = get_epoch_time_secs().saturating_sub(age.as_secs());
         let args = params![u64_to_sql(threshold_time)?];
-        if let Some(event_observer) = event_observer {
-            let sql = "SELECT txid FROM mempool WHERE accept_time < ?1";
-            let txids = query_rows(tx, sql, args)?;
-            event_observer.mempool_txs_dropped(txids, MemPoolDropReason::STALE_COLLECT);
-        }
+        let dropped_txids = match event_observer {
+            Some(_) => {
+                let sql = "SELECT txid FROM mempool WHERE accept_time < ?1";
+                query_rows(tx, sql, args)?
+            }
+            None => vec![],
+        };
 
         let sql = "DELETE FROM mempool WHERE accept_time < ?1";
 
         tx.execute(sql, args)?;
         increment_stx_mempool_gc();
+
+        // announced after the delete, so that any fee rates are those of the remaining txs
+        if let Some(event_observer) = event_observer {
+            MemPoolDB::announce_dropped_txs(
+                tx,
+                event_observer,
+                dropped_txids,
+                MemPoolDropReason::STALE_COLLECT,
+            );
+        }
         Ok(())
     }
 
@@ -2286,17 +2392,28 @@ impl MemPoolDB {
         event_observer: Option<&dyn MemPoolEventDispatcher>,
     ) -> Result<(), db_error> {
         let args = params![u64_to_sql(min_coinbase_height)?];
-
-        if let Some(event_observer) = event_observer {
-            let sql = "SELECT txid FROM mempool WHERE height < ?1";
-            let txids = query_rows(tx, sql, args)?;
-            event_observer.mempool_txs_dropped(txids, MemPoolDropReason::STALE_COLLECT);
-        }
+        let dropped_txids = match event_observer {
+            Some(_) => {
+                let sql = "SELECT txid FROM mempool WHERE height < ?1";
+                query_rows(tx, sql, args)?
+            }
+            None => vec![],
+        };
 
         let sql = "DELETE FROM mempool WHERE height < ?1";
 
         tx.execute(sql, args)?;
         increment_stx_mempool_gc();
+
+        // announced after the delete, so that any fee rates are those of the remaining txs
+        if let Some(event_observer) = event_observer {
+            MemPoolDB::announce_dropped_txs(
+                tx,
+                event_observer,
+                dropped_txids,
+                MemPoolDropReason::STALE_COLLECT,
+            );
+        }
         Ok(())
     }
 
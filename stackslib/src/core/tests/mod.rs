@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 use std::{cmp, io};
@@ -42,6 +43,7 @@ use stacks_common::util::{get_epoch_time_ms, get_epoch_time_secs, log, sleep_ms}
 use super::MemPoolDB;
 use crate::burnchains::{Address, Txid};
 use crate::chainstate::burn::ConsensusHash;
+use crate::chainstate::nakamoto::NakamotoBlock;
 use crate::chainstate::stacks::db::blocks::MemPoolRejection;
 use crate::chainstate::stacks::db::test::{
     chainstate_path, instantiate_chainstate, instantiate_chainstate_with_balances,
@@ -49,19 +51,20 @@ use crate::chainstate::stacks::db::test::{
 use crate::chainstate::stacks::db::{StacksChainState, StacksHeaderInfo};
 use crate::chainstate::stacks::events::StacksTransactionReceipt;
 use crate::chainstate::stacks::index::{MarfTrieId, TrieHashExtension};
-use crate::chainstate::stacks::miner::TransactionResult;
+use crate::chainstate::stacks::miner::{TransactionEvent, TransactionResult};
 use crate::chainstate::stacks::test::codec_all_transactions;
 use crate::chainstate::stacks::{
     CoinbasePayload, Error as ChainstateError, SinglesigHashMode, SinglesigSpendingCondition,
-    StacksBlockHeader, StacksMicroblockHeader, StacksPrivateKey, StacksPublicKey,
-    StacksTransaction, StacksTransactionSigner, TokenTransferMemo, TransactionAnchorMode,
-    TransactionAuth, TransactionContractCall, TransactionPayload, TransactionPostConditionMode,
-    TransactionPublicKeyEncoding, TransactionSmartContract, TransactionSpendingCondition,
-    TransactionVersion, C32_ADDRESS_VERSION_MAINNET_SINGLESIG,
+    StacksBlock, StacksBlockHeader, StacksMicroblock, StacksMicroblockHeader, StacksPrivateKey,
+    StacksPublicKey, StacksTransaction, StacksTransactionSigner, TokenTransferMemo,
+    TransactionAnchorMode, TransactionAuth, TransactionContractCall, TransactionPayload,
+    TransactionPostConditionMode, TransactionPublicKeyEncoding, TransactionSmartContract,
+    TransactionSpendingCondition, TransactionVersion, C32_ADDRESS_VERSION_MAINNET_SINGLESIG,
     C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
 };
 use crate::core::mempool::{
-    db_get_all_nonces, MemPoolSyncData, MemPoolWalkSettings, MemPoolWalkTxTypes, TxTag,
+    db_get_all_nonces, MemPoolDropReason, MemPoolEventDispatcher, MemPoolFeeRatePercentiles,
+    MemPoolSyncData, MemPoolWalkSettings, MemPoolWalkTxTypes, ProposalCallbackReceiver, TxTag,
     BLOOM_COUNTER_DEPTH, BLOOM_COUNTER_ERROR_RATE, MAX_BLOOM_COUNTER_TXS,
 };
 use crate::core::{FIRST_BURNCHAIN_CONSENSUS_HASH, FIRST_STACKS_BLOCK_HASH};
@@ -2839,3 +2842,191 @@ fn test_filter_txs_by_type() {
         },
     );
 }
+
+/// Records the drop events it is sent
+struct DropRecorder {
+    wants_fee_rates: bool,
+    drops: RefCell<Vec<(Vec<Txid>, String, Option<MemPoolFeeRatePercentiles>)>>,
+}
+
+impl MemPoolEventDispatcher for DropRecorder {
+    fn get_proposal_callback_receiver(&self) -> Option<Box<dyn ProposalCallbackReceiver>> {
+        None
+    }
+
+    fn mempool_txs_dropped(
+        &self,
+        txids: Vec<Txid>,
+        reason: MemPoolDropReason,
+        fee_rates: Option<MemPoolFeeRatePercentiles>,
+    ) {
+        self.drops
+            .borrow_mut()
+            .push((txids, reason.to_string(), fee_rates));
+    }
+
+    fn wants_drop_fee_rates(&self) -> bool {
+        self.wants_fee_rates
+    }
+
+    fn mined_block_event(
+        &self,
+        _target_burn_height: u64,
+        _block: &StacksBlock,
+        _block_size_bytes: u64,
+        _consumed: &ExecutionCost,
+        _confirmed_microblock_cost: &ExecutionCost,
+        _tx_results: Vec<TransactionEvent>,
+    ) {
+    }
+
+    fn mined_microblock_event(
+        &self,
+        _microblock: &StacksMicroblock,
+        _tx_results: Vec<TransactionEvent>,
+        _anchor_block_consensus_hash: ConsensusHash,
+        _anchor_block: BlockHeaderHash,
+    ) {
+    }
+
+    fn mined_nakamoto_block_event(
+        &self,
+        _target_burn_height: u64,
+        _block: &NakamotoBlock,
+        _block_size_bytes: u64,
+        _consumed: &ExecutionCost,
+        _tx_results: Vec<TransactionEvent>,
+    ) {
+    }
+}
+
+#[test]
+fn test_drop_event_fee_rate_percentiles() {
+    let mut chainstate =
+        instantiate_chainstate_with_balances(false, 0x80000000, function_name!(), vec![]);
+    let chainstate_path = chainstate_path(function_name!());
+    let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+    let b_1 = make_block(
+        &mut chainstate,
+        ConsensusHash([0x1; 20]),
+        &(
+            FIRST_BURNCHAIN_CONSENSUS_HASH.clone(),
+            FIRST_STACKS_BLOCK_HASH.clone(),
+        ),
+        1,
+        1,
+    );
+
+    assert_eq!(
+        MemPoolDB::get_fee_rate_percentiles(mempool.conn()).unwrap(),
+        None
+    );
+
+    let mut txs = codec_all_transactions(
+        &TransactionVersion::Testnet,
+        0x80000000,
+        &TransactionAnchorMode::Any,
+        &TransactionPostConditionMode::Allow,
+        StacksEpochId::latest(),
+    );
+
+    // 10 stale txs with a high fee rate, then 10 fresh ones with fee rates 1 through 10, and
+    // one fresh one with no fee rate yet
+    let mut stale_txids = vec![];
+    for nonce in 0..21 {
+        let mut tx = txs.pop().unwrap();
+        let mut mempool_tx = mempool.tx_begin().unwrap();
+
+        let origin_address = tx.origin_address();
+        let sponsor_address = tx.sponsor_address().unwrap_or(origin_address);
+
+        tx.set_tx_fee(100);
+        let txid = tx.txid();
+        let tx_bytes = tx.serialize_to_vec();
+        let tx_fee = tx.get_tx_fee();
+        let (height, fee_rate) = match nonce {
+            0..=9 => (100, Some(1000.0)),
+            10..=19 => (200, Some((nonce - 9) as f64)),
+            _ => (200, None),
+        };
+        if height == 100 {
+            stale_txids.push(txid.clone());
+        }
+
+        MemPoolDB::try_add_tx(
+            &mut mempool_tx,
+            &mut chainstate,
+            &b_1.0,
+            &b_1.1,
+            true,
+            txid,
+            tx_bytes,
+            tx_fee,
+            height,
+            &origin_address,
+            nonce,
+            &sponsor_address,
+            nonce,
+            None,
+        )
+        .unwrap();
+
+        mempool_tx
+            .execute(
+                "UPDATE mempool SET fee_rate = ? WHERE txid = ?",
+                params![fee_rate, txid],
+            )
+            .unwrap();
+        mempool_tx.commit().unwrap();
+    }
+
+    // nearest-rank percentiles of the 20 fee rates 1 through 10 and 10 times 1000; the tx
+    // without a fee rate is left out
+    assert_eq!(
+        MemPoolDB::get_fee_rate_percentiles(mempool.conn()).unwrap(),
+        Some(MemPoolFeeRatePercentiles {
+            p10: 2.0,
+            p50: 10.0,
+            p90: 1000.0,
+        })
+    );
+    stale_txids.sort();
+
+    // an observer that doesn't ask for fee rates doesn't get them
+    let recorder = DropRecorder {
+        wants_fee_rates: false,
+        drops: RefCell::new(vec![]),
+    };
+    let mempool_tx = mempool.tx_begin().unwrap();
+    MemPoolDB::garbage_collect_by_coinbase_height(&mempool_tx, 150, Some(&recorder)).unwrap();
+    drop(mempool_tx);
+    let mut drops = recorder.drops.into_inner();
+    drops.iter_mut().for_each(|(txids, ..)| txids.sort());
+    assert_eq!(
+        drops,
+        vec![(stale_txids.clone(), "StaleGarbageCollect".to_string(), None)]
+    );
+
+    // an observer that does gets the fee rates of the txs left after the collection
+    let recorder = DropRecorder {
+        wants_fee_rates: true,
+        drops: RefCell::new(vec![]),
+    };
+    let mempool_tx = mempool.tx_begin().unwrap();
+    MemPoolDB::garbage_collect_by_coinbase_height(&mempool_tx, 150, Some(&recorder)).unwrap();
+    mempool_tx.commit().unwrap();
+    let mut drops = recorder.drops.into_inner();
+    drops.iter_mut().for_each(|(txids, ..)| txids.sort());
+    assert_eq!(
+        drops,
+        vec![(
+            stale_txids,
+            "StaleGarbageCollect".to_string(),
+            Some(MemPoolFeeRatePercentiles {
+                p10: 1.0,
+                p50: 5.0,
+                p90: 9.0,
+            })
+        )]
+    );
+}
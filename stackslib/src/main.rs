@@ -1475,6 +1475,11 @@ simulating a miner.
         process::exit(0);
     }
 
+    if argv[1] == "import-tenure-archive" {
+        cli::command_import_tenure_archive(&argv[1..], None);
+        process::exit(0);
+    }
+
     if argv.len() < 4 {
         eprintln!("Usage: {} blockchain network working_dir", argv[0]);
         process::exit(1);
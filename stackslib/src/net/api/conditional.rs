@@ -0,0 +1,233 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Conditional (`If-None-Match`) and byte-range (`Range`) request support for endpoints that
+//! stream immutable block data, such as `/v3/tenures/:block_id` and `/v3/blocks/range`.
+//!
+//! The content these endpoints serve is fully determined by the block IDs in the request, so
+//! the ETag is derived from the request itself and never requires reading the blocks.  Only
+//! single byte ranges are supported.  A malformed `Range` header is ignored (and the full
+//! content is served), as RFC 9110 permits.
+
+use stacks_common::util::hash::{to_hex, Sha512Trunc256Sum};
+
+use crate::net::http::{
+    http_reason, HttpChunkGenerator, HttpContentType, HttpError, HttpRequestPreamble,
+    HttpResponseContents, HttpResponsePreamble, HttpServerError,
+};
+use crate::net::httpcore::StacksHttpResponse;
+use crate::net::Error as NetError;
+
+/// A single byte range from a `Range: bytes=...` header
+#[derive(Debug, Clone, PartialEq)]
+pub enum ByteRange {
+    /// `bytes=start-end`, with `end` inclusive
+    Bounded(u64, u64),
+    /// `bytes=start-`
+    From(u64),
+    /// `bytes=-len`, i.e. the last `len` bytes
+    Suffix(u64),
+}
+
+impl ByteRange {
+    /// Parse the value of a `Range` header.
+    /// Returns None if the header is malformed, uses a unit other than `bytes`, or asks for
+    /// more than one range.
+    pub fn parse(value: &str) -> Option<ByteRange> {
+        let spec = value.trim().strip_prefix("bytes=")?.trim();
+        if spec.contains(',') {
+            return None;
+        }
+        let (start, end) = spec.split_once('-')?;
+        let (start, end) = (start.trim(), end.trim());
+        match (start.is_empty(), end.is_empty()) {
+            (true, true) => None,
+            (true, false) => Some(ByteRange::Suffix(end.parse().ok()?)),
+            (false, true) => Some(ByteRange::From(start.parse().ok()?)),
+            (false, false) => {
+                let start = start.parse().ok()?;
+                let end = end.parse().ok()?;
+                if end < start {
+                    return None;
+                }
+                Some(ByteRange::Bounded(start, end))
+            }
+        }
+    }
+
+    /// Resolve this range against content of length `len`.
+    /// Returns the inclusive (first, last) byte offsets, or None if the range is unsatisfiable.
+    pub fn resolve(&self, len: u64) -> Option<(u64, u64)> {
+        if len == 0 {
+            return None;
+        }
+        match self {
+            ByteRange::Bounded(start, end) => {
+                if *start >= len {
+                    return None;
+                }
+                Some((*start, (*end).min(len - 1)))
+            }
+            ByteRange::From(start) => {
+                if *start >= len {
+                    return None;
+                }
+                Some((*start, len - 1))
+            }
+            ByteRange::Suffix(suffix_len) => {
+                if *suffix_len == 0 {
+                    return None;
+                }
+                Some((len.saturating_sub(*suffix_len), len - 1))
+            }
+        }
+    }
+}
+
+/// Make a strong ETag from the values that determine a response's content.
+/// Each value is length-prefixed so that different splits of the same bytes differ.
+pub fn make_etag(parts: &[&[u8]]) -> String {
+    let mut preimage = vec![];
+    for part in parts.iter() {
+        preimage.extend_from_slice(&(part.len() as u64).to_be_bytes());
+        preimage.extend_from_slice(part);
+    }
+    format!(
+        "\"{}\"",
+        to_hex(Sha512Trunc256Sum::from_data(&preimage).as_bytes())
+    )
+}
+
+/// Does an `If-None-Match` header value match the given ETag?
+/// Uses the weak comparison that RFC 9110 requires for `If-None-Match`.
+pub fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let etag = etag.trim_start_matches("W/");
+    if_none_match
+        .split(',')
+        .map(|tag| tag.trim())
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+/// Read out all of a stream's content
+fn drain_stream(stream: &mut dyn HttpChunkGenerator) -> Result<Vec<u8>, String> {
+    let mut content = vec![];
+    loop {
+        let mut chunk = stream.generate_next_chunk()?;
+        if chunk.is_empty() {
+            return Ok(content);
+        }
+        content.append(&mut chunk);
+    }
+}
+
+/// Make a response with a definite, possibly empty, body and the given extra headers
+fn make_ram_response(
+    preamble: &HttpRequestPreamble,
+    status: u16,
+    content_type: HttpContentType,
+    headers: Vec<(&str, String)>,
+    body: Vec<u8>,
+) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+    let content_length = u32::try_from(body.len())
+        .map_err(|_| NetError::SendError("Response body is too long".into()))?;
+    let mut resp_preamble = HttpResponsePreamble::from_http_request_preamble(
+        preamble,
+        status,
+        http_reason(status),
+        Some(content_length),
+        content_type,
+    );
+    for (key, value) in headers.into_iter() {
+        resp_preamble.add_header(key.into(), value);
+    }
+    Ok((resp_preamble, HttpResponseContents::from_ram(body)))
+}
+
+/// Respond to a request for immutable, streamed content identified by `etag`, honoring the
+/// request's `If-None-Match` and `Range` headers:
+/// * 304 if `If-None-Match` matches `etag`
+/// * 206 with the requested bytes if `Range` is satisfiable
+/// * 416 if `Range` is unsatisfiable
+/// * 200 with the streamed content otherwise
+///
+/// Range requests are served from RAM, since the total length must be known up front.  The
+/// streamed endpoints cap their responses at `MAX_PAYLOAD_LEN`, so this is bounded.
+pub fn make_conditional_response(
+    preamble: &HttpRequestPreamble,
+    etag: String,
+    content_type: HttpContentType,
+    mut stream: Box<dyn HttpChunkGenerator>,
+) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+    if let Some(if_none_match) = preamble.get_header("if-none-match".into()) {
+        if etag_matches(&if_none_match, &etag) {
+            return make_ram_response(preamble, 304, content_type, vec![("ETag", etag)], vec![]);
+        }
+    }
+
+    let Some(range) = preamble
+        .get_header("range".into())
+        .and_then(|value| ByteRange::parse(&value))
+    else {
+        let mut resp_preamble = HttpResponsePreamble::from_http_request_preamble(
+            preamble,
+            200,
+            "OK",
+            None,
+            content_type,
+        );
+        resp_preamble.add_header("ETag".into(), etag);
+        resp_preamble.add_header("Accept-Ranges".into(), "bytes".into());
+        return Ok((resp_preamble, HttpResponseContents::from_stream(stream)));
+    };
+
+    let content = match drain_stream(stream.as_mut()) {
+        Ok(content) => content,
+        Err(e) => {
+            let msg = format!("Failed to load content for range request: {}\n", &e);
+            warn!("{}", &msg);
+            return StacksHttpResponse::new_error(preamble, &HttpServerError::new(msg))
+                .try_into_contents()
+                .map_err(NetError::from);
+        }
+    };
+    let len = content.len() as u64;
+
+    let Some((first, last)) = range.resolve(len) else {
+        let (mut resp_preamble, contents) = StacksHttpResponse::new_error(
+            preamble,
+            &HttpError::new(
+                416,
+                format!("Range not satisfiable: content is {} bytes\n", len),
+            ),
+        )
+        .try_into_contents()?;
+        resp_preamble.add_header("Content-Range".into(), format!("bytes */{}", len));
+        return Ok((resp_preamble, contents));
+    };
+
+    // `first` and `last` are bounded by `content.len()`, so they fit in a usize
+    let body = content[(first as usize)..=(last as usize)].to_vec();
+    make_ram_response(
+        preamble,
+        206,
+        content_type,
+        vec![
+            ("ETag", etag),
+            ("Content-Range", format!("bytes {}-{}/{}", first, last, len)),
+        ],
+        body,
+    )
+}
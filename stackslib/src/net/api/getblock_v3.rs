@@ -15,6 +15,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::io::{Read, Seek, SeekFrom, Write};
+use std::sync::Arc;
 use std::{fs, io};
 
 use regex::{Captures, Regex};
@@ -26,6 +27,7 @@ use stacks_common::types::net::PeerHost;
 use stacks_common::util::hash::to_hex;
 use {serde, serde_json};
 
+use crate::chainstate::nakamoto::staging_blocks::ArchivalBlockSource;
 use crate::chainstate::nakamoto::{NakamotoBlock, NakamotoChainState, NakamotoStagingBlocksConn};
 use crate::chainstate::stacks::db::StacksChainState;
 use crate::chainstate::stacks::Error as ChainError;
@@ -65,8 +67,30 @@ pub struct NakamotoBlockStream {
     pub total_bytes: u64,
     /// Connection to the staging DB
     pub staging_db_conn: NakamotoStagingBlocksConn,
-    /// rowid of the block
+    /// rowid of the block (unused if the block came from `archive`)
     pub rowid: i64,
+    /// where to look for blocks that aren't in the staging DB
+    pub archive: Option<Arc<dyn ArchivalBlockSource>>,
+    /// the block most recently fetched from `archive`, and its bytes
+    pub archived_block: Option<(StacksBlockId, Vec<u8>)>,
+}
+
+/// Fetch a block from an archive, checking that it's the block that was asked for
+pub(crate) fn load_archived_block(
+    archive: &dyn ArchivalBlockSource,
+    block_id: &StacksBlockId,
+) -> Option<Vec<u8>> {
+    let bytes = archive.get_block_bytes(block_id)?;
+    match NakamotoBlock::consensus_deserialize(&mut &bytes[..]) {
+        Ok(block) if &block.block_id() == block_id => Some(bytes),
+        _ => {
+            warn!(
+                "Archived block is not a valid Nakamoto block with the requested ID";
+                "block_id" => %block_id
+            );
+            None
+        }
+    }
 }
 
 impl NakamotoBlockStream {
@@ -75,23 +99,33 @@ impl NakamotoBlockStream {
         block_id: StacksBlockId,
         consensus_hash: ConsensusHash,
         parent_block_id: StacksBlockId,
+    ) -> Result<Self, ChainError> {
+        Self::new_with_archive(chainstate, block_id, consensus_hash, parent_block_id, None)
+    }
+
+    /// Stream a block from the staging DB, or from `archive` if the staging DB doesn't have it
+    pub fn new_with_archive(
+        chainstate: &StacksChainState,
+        block_id: StacksBlockId,
+        consensus_hash: ConsensusHash,
+        parent_block_id: StacksBlockId,
+        archive: Option<Arc<dyn ArchivalBlockSource>>,
     ) -> Result<Self, ChainError> {
         let staging_db_path = chainstate.get_nakamoto_staging_blocks_path()?;
         let db_conn = StacksChainState::open_nakamoto_staging_blocks(&staging_db_path, false)?;
-        let rowid = db_conn
-            .conn()
-            .get_nakamoto_block_rowid(&block_id)?
-            .ok_or(ChainError::NoSuchBlockError)?;
-
-        Ok(NakamotoBlockStream {
-            index_block_hash: block_id,
+        let mut stream = NakamotoBlockStream {
+            index_block_hash: block_id.clone(),
             consensus_hash,
-            parent_block_id,
+            parent_block_id: parent_block_id.clone(),
             offset: 0,
             total_bytes: 0,
             staging_db_conn: db_conn,
-            rowid,
-        })
+            rowid: 0,
+            archive,
+            archived_block: None,
+        };
+        stream.reset(block_id, parent_block_id)?;
+        Ok(stream)
     }
 
     /// reset the stream to send another block.
@@ -101,11 +135,18 @@ impl NakamotoBlockStream {
         block_id: StacksBlockId,
         parent_block_id: StacksBlockId,
     ) -> Result<(), ChainError> {
-        let rowid = self
+        let rowid = match self
             .staging_db_conn
             .conn()
             .get_nakamoto_block_rowid(&block_id)?
-            .ok_or(ChainError::NoSuchBlockError)?;
+        {
+            Some(rowid) => rowid,
+            None => {
+                self.fetch_archived_block(&block_id)
+                    .ok_or(ChainError::NoSuchBlockError)?;
+                0
+            }
+        };
 
         self.index_block_hash = block_id;
         self.parent_block_id = parent_block_id;
@@ -114,6 +155,69 @@ impl NakamotoBlockStream {
         self.rowid = rowid;
         Ok(())
     }
+
+    /// Get the size of a block in the staging DB, or failing that, in the archive.  A block
+    /// found in the archive is kept, so that a `reset()` to it doesn't fetch it again.
+    pub fn get_block_size(&mut self, block_id: &StacksBlockId) -> Result<Option<u64>, ChainError> {
+        if let Some(size) = self
+            .staging_db_conn
+            .conn()
+            .get_nakamoto_block_size(block_id)?
+        {
+            return Ok(Some(size));
+        }
+        Ok(self
+            .fetch_archived_block(block_id)
+            .map(|bytes| bytes.len() as u64))
+    }
+
+    /// Fetch a block from the archive, unless it's the one fetched last
+    fn fetch_archived_block(&mut self, block_id: &StacksBlockId) -> Option<&[u8]> {
+        let fetched = self
+            .archived_block
+            .as_ref()
+            .is_some_and(|(archived_id, _)| archived_id == block_id);
+        if !fetched {
+            let bytes = load_archived_block(self.archive.as_deref()?, block_id)?;
+            self.archived_block = Some((block_id.clone(), bytes));
+        }
+        self.archived_block
+            .as_ref()
+            .map(|(_, bytes)| bytes.as_slice())
+    }
+
+    /// Check that the current block's stored bytes decode to the block that was asked for,
+    /// before any of them are sent.  A block that doesn't is logged as a storage-integrity
+    /// problem, and Ok(false) is returned.  Blocks from the archive were checked when they
+    /// were fetched.
+    pub fn is_block_intact(&self) -> Result<bool, ChainError> {
+        if self.current_archived_block().is_some() {
+            return Ok(true);
+        }
+        let mut blob_fd = self
+            .staging_db_conn
+            .open_nakamoto_block(self.rowid, false)?;
+        let problem = match NakamotoBlock::consensus_deserialize(&mut blob_fd) {
+            Ok(block) if block.block_id() == self.index_block_hash => return Ok(true),
+            Ok(block) => format!("decodes to block {}", &block.block_id()),
+            Err(e) => format!("fails to decode: {:?}", &e),
+        };
+        error!(
+            "Storage integrity problem: stored Nakamoto block is corrupt";
+            "block_id" => %self.index_block_hash,
+            "rowid" => self.rowid,
+            "problem" => %problem,
+        );
+        Ok(false)
+    }
+
+    /// The current block's bytes, if it came from the archive
+    fn current_archived_block(&self) -> Option<&[u8]> {
+        match self.archived_block.as_ref() {
+            Some((block_id, bytes)) if block_id == &self.index_block_hash => Some(bytes),
+            _ => None,
+        }
+    }
 }
 
 /// Decode the HTTP request
@@ -211,6 +315,29 @@ impl RPCRequestHandler for RPCNakamotoBlockRequestHandler {
             }
         };
 
+        // don't send a block that is corrupt in storage as if it were the block asked for
+        match stream.is_block_intact() {
+            Ok(true) => {}
+            Ok(false) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpServerError::new(format!(
+                        "Block {} is corrupt in this node's storage\n",
+                        &block_id
+                    )),
+                )
+                .try_into_contents()
+                .map_err(NetError::from)
+            }
+            Err(e) => {
+                let msg = format!("Failed to check block {}: {:?}\n", &block_id, &e);
+                warn!("{}", &msg);
+                return StacksHttpResponse::new_error(&preamble, &HttpServerError::new(msg))
+                    .try_into_contents()
+                    .map_err(NetError::from);
+            }
+        }
+
         let resp_preamble = HttpResponsePreamble::from_http_request_preamble(
             &preamble,
             200,
@@ -254,6 +381,19 @@ impl HttpChunkGenerator for NakamotoBlockStream {
     }
 
     fn generate_next_chunk(&mut self) -> Result<Vec<u8>, String> {
+        if let Some(bytes) = self.current_archived_block() {
+            let start = usize::try_from(self.offset)
+                .unwrap_or(usize::MAX)
+                .min(bytes.len());
+            let end = start
+                .saturating_add(self.hint_chunk_size())
+                .min(bytes.len());
+            let buf = bytes[start..end].to_vec();
+            self.offset += buf.len() as u64;
+            self.total_bytes += buf.len() as u64;
+            return Ok(buf);
+        }
+
         let mut blob_fd = self
             .staging_db_conn
             .open_nakamoto_block(self.rowid, false)
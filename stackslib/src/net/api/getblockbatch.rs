@@ -0,0 +1,482 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use regex::{Captures, Regex};
+use stacks_common::codec::{StacksMessageCodec, MAX_MESSAGE_LEN};
+use stacks_common::types::chainstate::{ConsensusHash, StacksBlockId};
+use stacks_common::types::net::PeerHost;
+
+use crate::chainstate::nakamoto::staging_blocks::ArchivalBlockSource;
+use crate::chainstate::nakamoto::{NakamotoBlock, NakamotoChainState, StacksDBIndexed};
+use crate::chainstate::stacks::db::StacksChainState;
+use crate::chainstate::stacks::Error as ChainError;
+use crate::net::api::getblock_v3::NakamotoBlockStream;
+use crate::net::http::{
+    parse_bytes, Error, HttpChunkGenerator, HttpContentType, HttpNotFound, HttpRequest,
+    HttpRequestContents, HttpRequestPreamble, HttpResponse, HttpResponseContents,
+    HttpResponsePayload, HttpResponsePreamble, HttpServerError,
+};
+use crate::net::httpcore::{
+    HttpRequestContentsExtensions, RPCRequestHandler, StacksHttpRequest, StacksHttpResponse,
+};
+use crate::net::{Error as NetError, StacksNodeState};
+
+/// Most blocks a `/v3/blocks/batch` request asks for, and the default
+pub const MAX_BLOCK_BATCH_COUNT: u64 = 1000;
+
+/// Response header with the height of the first block that the batch did not include, which
+/// is where the next request continues from
+pub const BLOCK_BATCH_NEXT_HEIGHT_HEADER: &str = "X-Stacks-Next-Height";
+
+/// Bytes of the length prefix ahead of each block in a batch
+const BLOCK_BATCH_FRAME_PREFIX_LEN: u64 = 4;
+
+#[derive(Clone)]
+pub struct RPCNakamotoBlockBatchRequestHandler {
+    /// Height of the first block to send.
+    /// Passed as `start_height=` query parameter
+    pub start_height: Option<u64>,
+    /// Most blocks to send.
+    /// Passed as `count=` query parameter
+    pub count: u64,
+}
+
+impl RPCNakamotoBlockBatchRequestHandler {
+    pub fn new() -> Self {
+        Self {
+            start_height: None,
+            count: MAX_BLOCK_BATCH_COUNT,
+        }
+    }
+}
+
+/// Errors from resolving a batch of blocks, before any bytes are streamed
+#[derive(Debug)]
+pub enum BlockBatchError {
+    /// The block at this height of the canonical fork is an epoch2 block
+    NotNakamoto(u64),
+    /// Failed to query the chainstate
+    ChainstateError(ChainError),
+}
+
+impl From<ChainError> for BlockBatchError {
+    fn from(e: ChainError) -> Self {
+        Self::ChainstateError(e)
+    }
+}
+
+impl From<crate::util_lib::db::Error> for BlockBatchError {
+    fn from(e: crate::util_lib::db::Error) -> Self {
+        Self::ChainstateError(e.into())
+    }
+}
+
+/// Stream of the canonical fork's Nakamoto blocks, in height order, starting at a given
+/// height.  Unlike `NakamotoTenureStream`, this does not stop at tenure boundaries.  Each block
+/// is preceded by its length, as a 4-byte big-endian integer.
+///
+/// The blocks to send are chosen up front, so that the response can say where the next batch
+/// starts.  The batch stops short of `count` blocks at the chain tip, at an epoch2 block, or
+/// before the block that would take it past MAX_MESSAGE_LEN bytes.
+pub struct NakamotoBlockBatchStream {
+    /// stream for the current block, or None if the batch is empty
+    pub block_stream: Option<NakamotoBlockStream>,
+    /// blocks still to send after the current one, as (block ID, consensus hash, parent block ID)
+    pub remaining: VecDeque<(StacksBlockId, ConsensusHash, StacksBlockId)>,
+    /// whether the current block's length prefix has been sent
+    pub prefix_sent: bool,
+    /// height of the first block after this batch
+    pub next_height: u64,
+}
+
+impl NakamotoBlockBatchStream {
+    /// Choose up to `count` blocks of the fork ending at `tip_block_id` (at `tip_height`),
+    /// starting at `start_height`.  If `start_height` is past the tip, the batch is empty.
+    /// Fails if the block at `start_height` is an epoch2 block.
+    pub fn new(
+        chainstate: &StacksChainState,
+        tip_block_id: &StacksBlockId,
+        tip_height: u64,
+        start_height: u64,
+        count: u64,
+        archive: Option<Arc<dyn ArchivalBlockSource>>,
+    ) -> Result<Self, BlockBatchError> {
+        let end_height = start_height
+            .saturating_add(count)
+            .min(tip_height.saturating_add(1));
+
+        let mut blocks = VecDeque::new();
+        let mut block_stream: Option<NakamotoBlockStream> = None;
+        let mut total_bytes = 0u64;
+        let mut next_height = start_height;
+        while next_height < end_height {
+            let block_id = chainstate
+                .index_conn()
+                .get_ancestor_block_id(next_height, tip_block_id)?
+                .ok_or(ChainError::NoSuchBlockError)?;
+            let header = NakamotoChainState::get_block_header(chainstate.db(), &block_id)?
+                .ok_or(ChainError::NoSuchBlockError)?;
+            let Some(nakamoto_header) = header.anchored_header.as_stacks_nakamoto() else {
+                if next_height == start_height {
+                    return Err(BlockBatchError::NotNakamoto(start_height));
+                }
+                // stop sending at an epoch2 block
+                break;
+            };
+
+            let stream = match block_stream.as_mut() {
+                Some(stream) => stream,
+                None => block_stream.insert(NakamotoBlockStream::new_with_archive(
+                    chainstate,
+                    block_id.clone(),
+                    nakamoto_header.consensus_hash.clone(),
+                    nakamoto_header.parent_block_id.clone(),
+                    archive.clone(),
+                )?),
+            };
+            let block_size = stream
+                .get_block_size(&block_id)?
+                .ok_or(ChainError::NoSuchBlockError)?;
+            let framed_size = block_size.saturating_add(BLOCK_BATCH_FRAME_PREFIX_LEN);
+            if !blocks.is_empty()
+                && total_bytes.saturating_add(framed_size) > MAX_MESSAGE_LEN.into()
+            {
+                // out of space to send this; the first block is always sent, so that the
+                // client always makes progress
+                break;
+            }
+            total_bytes = total_bytes.saturating_add(framed_size);
+            blocks.push_back((
+                block_id,
+                nakamoto_header.consensus_hash.clone(),
+                nakamoto_header.parent_block_id.clone(),
+            ));
+            next_height += 1;
+        }
+
+        // the block stream starts at the first block
+        blocks.pop_front();
+        Ok(Self {
+            block_stream,
+            remaining: blocks,
+            prefix_sent: false,
+            next_height,
+        })
+    }
+
+    /// Start streaming the next block of the batch.
+    /// Return Ok(true) if there is one, and Ok(false) if the whole batch has been sent.
+    /// Return Err(..) on DB error
+    pub fn next_block(&mut self) -> Result<bool, ChainError> {
+        let Some(block_stream) = self.block_stream.as_mut() else {
+            return Ok(false);
+        };
+        let Some((block_id, consensus_hash, parent_block_id)) = self.remaining.pop_front() else {
+            return Ok(false);
+        };
+        // the tenure may change from block to block
+        block_stream.consensus_hash = consensus_hash;
+        block_stream.reset(block_id, parent_block_id)?;
+        self.prefix_sent = false;
+        Ok(true)
+    }
+
+    /// The current block's length prefix, the first time this is called for the block.  A
+    /// block that is corrupt in the node's storage fails the stream, rather than letting it end
+    /// as if the batch had been sent.
+    fn frame_prefix(&mut self) -> Result<Vec<u8>, String> {
+        let Some(block_stream) = self.block_stream.as_mut() else {
+            return Ok(vec![]);
+        };
+        if std::mem::replace(&mut self.prefix_sent, true) {
+            return Ok(vec![]);
+        }
+        let block_id = block_stream.index_block_hash.clone();
+        let intact = block_stream
+            .is_block_intact()
+            .map_err(|e| format!("Failed to load block {}: {:?}", &block_id, &e))?;
+        if !intact {
+            return Err(format!(
+                "Nakamoto block {} is corrupt in this node's storage",
+                &block_id
+            ));
+        }
+        let block_size = block_stream
+            .get_block_size(&block_id)
+            .map_err(|e| format!("Failed to load block size: {:?}", &e))?
+            .ok_or_else(|| "No such block".to_string())?;
+        let block_size = u32::try_from(block_size)
+            .map_err(|_| format!("Nakamoto block {} is too big to send", &block_id))?;
+        Ok(block_size.to_be_bytes().to_vec())
+    }
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCNakamotoBlockBatchRequestHandler {
+    fn verb(&self) -> &'static str {
+        "GET"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(r#"^/v3/blocks/batch$"#).unwrap()
+    }
+
+    fn metrics_identifier(&self) -> &str {
+        "/v3/blocks/batch"
+    }
+
+    /// Try to decode this request.
+    /// There's nothing to load here, so just make sure the request is well-formed.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        _body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        if preamble.get_content_length() != 0 {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+
+        let req_contents = HttpRequestContents::new().query_string(query);
+        let start_height = req_contents
+            .get_query_arg("start_height")
+            .ok_or_else(|| {
+                Error::DecodeError("Missing required start_height= query parameter".to_string())
+            })?
+            .parse::<u64>()
+            .map_err(|e| {
+                Error::DecodeError(format!(
+                    "Failed to parse start_height= query parameter: {:?}",
+                    &e
+                ))
+            })?;
+        let count = req_contents
+            .get_query_arg("count")
+            .map(|count| count.parse::<u64>())
+            .transpose()
+            .map_err(|e| {
+                Error::DecodeError(format!("Failed to parse count= query parameter: {:?}", &e))
+            })?
+            .unwrap_or(MAX_BLOCK_BATCH_COUNT);
+        if count == 0 {
+            return Err(Error::DecodeError(
+                "count= query parameter must be at least 1".to_string(),
+            ));
+        }
+
+        self.start_height = Some(start_height);
+        self.count = count.min(MAX_BLOCK_BATCH_COUNT);
+        Ok(req_contents)
+    }
+}
+
+impl RPCRequestHandler for RPCNakamotoBlockBatchRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {
+        self.start_height = None;
+        self.count = MAX_BLOCK_BATCH_COUNT;
+    }
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        _contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let start_height = self
+            .start_height
+            .take()
+            .ok_or(NetError::SendError("Missing `start_height`".into()))?;
+
+        let stream_res =
+            node.with_node_state(|network, _sortdb, chainstate, _mempool, rpc_args| {
+                NakamotoBlockBatchStream::new(
+                    chainstate,
+                    &network.stacks_tip.block_id(),
+                    network.stacks_tip.height,
+                    start_height,
+                    self.count,
+                    rpc_args.archival_block_source.clone(),
+                )
+            });
+
+        let stream = match stream_res {
+            Ok(stream) => stream,
+            Err(BlockBatchError::NotNakamoto(height)) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpNotFound::new(format!("No Nakamoto block at height {}\n", height)),
+                )
+                .try_into_contents()
+                .map_err(NetError::from);
+            }
+            Err(BlockBatchError::ChainstateError(e)) => {
+                // nope -- error trying to check
+                let msg = format!(
+                    "Failed to load blocks from height {}: {:?}\n",
+                    start_height, &e
+                );
+                warn!("{}", &msg);
+                return StacksHttpResponse::new_error(&preamble, &HttpServerError::new(msg))
+                    .try_into_contents()
+                    .map_err(NetError::from);
+            }
+        };
+
+        // the canonical fork can change, so the content can't be cached or ranged
+        let mut resp_preamble = HttpResponsePreamble::from_http_request_preamble(
+            &preamble,
+            200,
+            "OK",
+            None,
+            HttpContentType::Bytes,
+        );
+        resp_preamble.add_header(
+            BLOCK_BATCH_NEXT_HEIGHT_HEADER.into(),
+            format!("{}", stream.next_height),
+        );
+        Ok((
+            resp_preamble,
+            HttpResponseContents::from_stream(Box::new(stream)),
+        ))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCNakamotoBlockBatchRequestHandler {
+    /// Decode this response from a byte stream.  This is called by the client to decode this
+    /// message
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let bytes = parse_bytes(preamble, body, MAX_MESSAGE_LEN.into())?;
+        Ok(HttpResponsePayload::Bytes(bytes))
+    }
+}
+
+/// Stream implementation for a batch of Nakamoto blocks
+impl HttpChunkGenerator for NakamotoBlockBatchStream {
+    #[cfg(test)]
+    fn hint_chunk_size(&self) -> usize {
+        // make this hurt
+        32
+    }
+
+    #[cfg(not(test))]
+    fn hint_chunk_size(&self) -> usize {
+        4096
+    }
+
+    fn generate_next_chunk(&mut self) -> Result<Vec<u8>, String> {
+        let Some(block_stream) = self.block_stream.as_mut() else {
+            // empty batch
+            return Ok(vec![]);
+        };
+        if self.prefix_sent {
+            let next_block_chunk = block_stream.generate_next_chunk()?;
+            if !next_block_chunk.is_empty() {
+                // have block data to send
+                return Ok(next_block_chunk);
+            }
+
+            // load up next block
+            let send_more = self.next_block().map_err(|e| {
+                let msg = format!("Failed to load next block in this batch: {:?}", &e);
+                warn!("{}", &msg);
+                msg
+            })?;
+
+            if !send_more {
+                return Ok(vec![]);
+            }
+        }
+
+        self.frame_prefix()
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a new request for up to `count` blocks of the canonical fork, starting at
+    /// `start_height`
+    pub fn new_get_nakamoto_block_batch(
+        host: PeerHost,
+        start_height: u64,
+        count: Option<u64>,
+    ) -> StacksHttpRequest {
+        let contents = HttpRequestContents::new()
+            .query_arg("start_height".into(), format!("{}", start_height));
+        let contents = match count {
+            Some(count) => contents.query_arg("count".into(), format!("{}", count)),
+            None => contents,
+        };
+        StacksHttpRequest::new_for_peer(host, "GET".into(), "/v3/blocks/batch".into(), contents)
+            .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    /// Decode an HTTP response into a batch of blocks, lowest block first, and the height that
+    /// the next batch starts at.
+    /// The bytes are a concatenation of Nakamoto blocks, each preceded by its 4-byte big-endian
+    /// length.
+    /// If it fails, return Self::Error(..)
+    pub fn decode_nakamoto_block_batch(self) -> Result<(Vec<NakamotoBlock>, u64), NetError> {
+        let next_height = self
+            .preamble()
+            .get_header(BLOCK_BATCH_NEXT_HEIGHT_HEADER.into())
+            .and_then(|next_height| next_height.parse::<u64>().ok())
+            .ok_or_else(|| {
+                Error::DecodeError(format!(
+                    "Missing or invalid {} header",
+                    BLOCK_BATCH_NEXT_HEIGHT_HEADER
+                ))
+            })?;
+        let contents = self.get_http_payload_ok()?;
+
+        // contents will be raw bytes
+        let batch_bytes: Vec<u8> = contents.try_into()?;
+        let mut ptr = batch_bytes.as_slice();
+
+        let mut blocks = vec![];
+        while !ptr.is_empty() {
+            let block_len = u32::consensus_deserialize(&mut ptr)?;
+            let block_len = usize::try_from(block_len).unwrap_or(usize::MAX);
+            if block_len > ptr.len() {
+                return Err(Error::DecodeError("Block batch is truncated".to_string()).into());
+            }
+            let (mut block_bytes, rest) = ptr.split_at(block_len);
+            let block = NakamotoBlock::consensus_deserialize(&mut block_bytes)?;
+            if !block_bytes.is_empty() {
+                return Err(
+                    Error::DecodeError("Block batch frame has trailing bytes".to_string()).into(),
+                );
+            }
+            blocks.push(block);
+            ptr = rest;
+        }
+
+        Ok((blocks, next_height))
+    }
+}
@@ -0,0 +1,399 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use regex::{Captures, Regex};
+use stacks_common::codec::{StacksMessageCodec, MAX_PAYLOAD_LEN};
+use stacks_common::types::chainstate::StacksBlockId;
+use stacks_common::types::net::PeerHost;
+
+use crate::chainstate::nakamoto::{NakamotoBlock, NakamotoChainState, StacksDBIndexed};
+use crate::chainstate::stacks::db::StacksChainState;
+use crate::chainstate::stacks::Error as ChainError;
+use crate::net::api::conditional::{make_conditional_response, make_etag};
+use crate::net::api::getblock_v3::NakamotoBlockStream;
+use crate::net::http::{
+    parse_bytes, Error, HttpBadRequest, HttpChunkGenerator, HttpContentType, HttpNotFound,
+    HttpRequest, HttpRequestContents, HttpRequestPreamble, HttpResponse, HttpResponseContents,
+    HttpResponsePayload, HttpResponsePreamble, HttpServerError,
+};
+use crate::net::httpcore::{
+    HttpRequestContentsExtensions, RPCRequestHandler, StacksHttpRequest, StacksHttpResponse,
+};
+use crate::net::{Error as NetError, StacksNodeState};
+use crate::util_lib::db::DBConn;
+
+#[derive(Clone)]
+pub struct RPCNakamotoBlockRangeRequestHandler {
+    /// One end of the range, passed as the `start=` query parameter.
+    pub start_block_id: Option<StacksBlockId>,
+    /// The other end of the range, passed as the `end=` query parameter.
+    pub end_block_id: Option<StacksBlockId>,
+}
+
+impl RPCNakamotoBlockRangeRequestHandler {
+    pub fn new() -> Self {
+        Self {
+            start_block_id: None,
+            end_block_id: None,
+        }
+    }
+}
+
+/// Errors from resolving a block range, before any bytes are streamed
+#[derive(Debug)]
+pub enum BlockRangeError {
+    /// One of the endpoints is unknown, or is not a Nakamoto block
+    NoSuchBlock(StacksBlockId),
+    /// Neither endpoint is an ancestor of the other
+    NotAncestor(StacksBlockId, StacksBlockId),
+    /// Failed to query the chainstate
+    ChainstateError(ChainError),
+}
+
+impl From<ChainError> for BlockRangeError {
+    fn from(e: ChainError) -> Self {
+        Self::ChainstateError(e)
+    }
+}
+
+impl From<crate::util_lib::db::Error> for BlockRangeError {
+    fn from(e: crate::util_lib::db::Error) -> Self {
+        Self::ChainstateError(e.into())
+    }
+}
+
+/// Stream of Nakamoto blocks along a single fork, from the highest block in the range down to
+/// (and including) the lowest block in the range. Unlike `NakamotoTenureStream`, this does not
+/// stop at tenure boundaries.
+pub struct NakamotoBlockRangeStream {
+    /// stream for the current block
+    pub block_stream: NakamotoBlockStream,
+    /// connection to the headers DB
+    pub headers_conn: DBConn,
+    /// total bytes sent so far
+    pub total_sent: u64,
+    /// lowest block in the range; streaming stops once this block has been sent
+    pub ancestor_block_id: StacksBlockId,
+}
+
+impl NakamotoBlockRangeStream {
+    /// Resolve the two endpoints of a range into a stream.
+    /// The endpoints may be given in either order; the higher block is streamed first.
+    /// Fails if either endpoint is not a Nakamoto block, or if the lower block is not an ancestor
+    /// of the higher block.
+    pub fn new(
+        chainstate: &StacksChainState,
+        start_block_id: &StacksBlockId,
+        end_block_id: &StacksBlockId,
+    ) -> Result<Self, BlockRangeError> {
+        let load_header = |block_id: &StacksBlockId| {
+            NakamotoChainState::get_block_header_nakamoto(chainstate.db(), block_id)?
+                .and_then(|header| {
+                    let height = header.stacks_block_height;
+                    header
+                        .anchored_header
+                        .as_stacks_nakamoto()
+                        .cloned()
+                        .map(|hdr| (hdr, height))
+                })
+                .ok_or_else(|| BlockRangeError::NoSuchBlock(block_id.clone()))
+        };
+        let (start_header, start_height) = load_header(start_block_id)?;
+        let (end_header, end_height) = load_header(end_block_id)?;
+
+        let ((descendant, _), (ancestor, ancestor_height)) = if start_height >= end_height {
+            ((start_header, start_height), (end_header, end_height))
+        } else {
+            ((end_header, end_height), (start_header, start_height))
+        };
+
+        let descendant_id = descendant.block_id();
+        let ancestor_id = ancestor.block_id();
+        let ancestor_on_fork = chainstate
+            .index_conn()
+            .get_ancestor_block_id(ancestor_height, &descendant_id)?;
+        if ancestor_on_fork.as_ref() != Some(&ancestor_id) {
+            return Err(BlockRangeError::NotAncestor(descendant_id, ancestor_id));
+        }
+
+        let block_stream = NakamotoBlockStream::new(
+            chainstate,
+            descendant_id,
+            descendant.consensus_hash.clone(),
+            descendant.parent_block_id.clone(),
+        )?;
+        let headers_conn = chainstate.reopen_db()?;
+        Ok(Self {
+            block_stream,
+            headers_conn,
+            total_sent: 0,
+            ancestor_block_id: ancestor_id,
+        })
+    }
+
+    /// Start streaming the next block (i.e. the parent of the block we last streamed).
+    /// Return Ok(true) if we can fit the block into the stream.
+    /// Return Ok(false) if we have sent the whole range, or if we're out of space. In the latter
+    /// case, the caller will need to call this RPC method again with `start=` set to the parent of
+    /// the last block it received.
+    /// Return Err(..) on DB error
+    pub fn next_block(&mut self) -> Result<bool, ChainError> {
+        if self.block_stream.index_block_hash == self.ancestor_block_id {
+            // sent the whole range
+            return Ok(false);
+        }
+
+        let parent_header = NakamotoChainState::get_block_header(
+            &self.headers_conn,
+            &self.block_stream.parent_block_id,
+        )?
+        .ok_or(ChainError::NoSuchBlockError)?;
+
+        // stop sending if the parent is an epoch2 block
+        let Some(parent_nakamoto_header) = parent_header.anchored_header.as_stacks_nakamoto()
+        else {
+            return Ok(false);
+        };
+
+        let parent_size = self
+            .block_stream
+            .staging_db_conn
+            .conn()
+            .get_nakamoto_block_size(&self.block_stream.parent_block_id)?
+            .ok_or(ChainError::NoSuchBlockError)?;
+
+        self.total_sent = self
+            .total_sent
+            .saturating_add(self.block_stream.total_bytes);
+        if self.total_sent.saturating_add(parent_size) > MAX_PAYLOAD_LEN.into() {
+            // out of space to send this
+            return Ok(false);
+        }
+
+        // the tenure may change from block to block
+        self.block_stream.consensus_hash = parent_nakamoto_header.consensus_hash.clone();
+        self.block_stream.reset(
+            parent_nakamoto_header.block_id(),
+            parent_nakamoto_header.parent_block_id.clone(),
+        )?;
+        Ok(true)
+    }
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCNakamotoBlockRangeRequestHandler {
+    fn verb(&self) -> &'static str {
+        "GET"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(r#"^/v3/blocks/range$"#).unwrap()
+    }
+
+    fn metrics_identifier(&self) -> &str {
+        "/v3/blocks/range"
+    }
+
+    /// Try to decode this request.
+    /// There's nothing to load here, so just make sure the request is well-formed.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        _body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        if preamble.get_content_length() != 0 {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+
+        let req_contents = HttpRequestContents::new().query_string(query);
+        let parse_block_id = |arg: &str| {
+            let block_id_hex = req_contents.get_query_arg(arg).ok_or_else(|| {
+                Error::DecodeError(format!("Missing required {}= query parameter", arg))
+            })?;
+            StacksBlockId::from_hex(block_id_hex).map_err(|e| {
+                Error::DecodeError(format!(
+                    "Failed to parse {}= query parameter: {:?}",
+                    arg, &e
+                ))
+            })
+        };
+
+        self.start_block_id = Some(parse_block_id("start")?);
+        self.end_block_id = Some(parse_block_id("end")?);
+
+        Ok(req_contents)
+    }
+}
+
+impl RPCRequestHandler for RPCNakamotoBlockRangeRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {
+        self.start_block_id = None;
+        self.end_block_id = None;
+    }
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        _contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let start_block_id = self
+            .start_block_id
+            .take()
+            .ok_or(NetError::SendError("Missing `start`".into()))?;
+        let end_block_id = self
+            .end_block_id
+            .take()
+            .ok_or(NetError::SendError("Missing `end`".into()))?;
+
+        let stream_res =
+            node.with_node_state(|_network, _sortdb, chainstate, _mempool, _rpc_args| {
+                NakamotoBlockRangeStream::new(chainstate, &start_block_id, &end_block_id)
+            });
+
+        let stream = match stream_res {
+            Ok(stream) => stream,
+            Err(BlockRangeError::NoSuchBlock(block_id)) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpNotFound::new(format!("No such Nakamoto block {}\n", &block_id)),
+                )
+                .try_into_contents()
+                .map_err(NetError::from);
+            }
+            Err(BlockRangeError::NotAncestor(descendant, ancestor)) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpBadRequest::new(format!(
+                        "Block {} is not an ancestor of block {}\n",
+                        &ancestor, &descendant
+                    )),
+                )
+                .try_into_contents()
+                .map_err(NetError::from);
+            }
+            Err(BlockRangeError::ChainstateError(e)) => {
+                // nope -- error trying to check
+                let msg = format!(
+                    "Failed to load block range {}..{}: {:?}\n",
+                    &start_block_id, &end_block_id, &e
+                );
+                warn!("{}", &msg);
+                return StacksHttpResponse::new_error(&preamble, &HttpServerError::new(msg))
+                    .try_into_contents()
+                    .map_err(NetError::from);
+            }
+        };
+
+        // blocks never change, so the content is determined by the request
+        let etag = make_etag(&[b"/v3/blocks/range", &start_block_id.0, &end_block_id.0]);
+        make_conditional_response(&preamble, etag, HttpContentType::Bytes, Box::new(stream))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCNakamotoBlockRangeRequestHandler {
+    /// Decode this response from a byte stream.  This is called by the client to decode this
+    /// message
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let bytes = parse_bytes(preamble, body, MAX_PAYLOAD_LEN.into())?;
+        Ok(HttpResponsePayload::Bytes(bytes))
+    }
+}
+
+/// Stream implementation for a range of Nakamoto blocks
+impl HttpChunkGenerator for NakamotoBlockRangeStream {
+    #[cfg(test)]
+    fn hint_chunk_size(&self) -> usize {
+        // make this hurt
+        32
+    }
+
+    #[cfg(not(test))]
+    fn hint_chunk_size(&self) -> usize {
+        4096
+    }
+
+    fn generate_next_chunk(&mut self) -> Result<Vec<u8>, String> {
+        let next_block_chunk = self.block_stream.generate_next_chunk()?;
+        if next_block_chunk.len() > 0 {
+            // have block data to send
+            return Ok(next_block_chunk);
+        }
+
+        // load up next block
+        let send_more = self.next_block().map_err(|e| {
+            let msg = format!("Failed to load next block in this range: {:?}", &e);
+            warn!("{}", &msg);
+            msg
+        })?;
+
+        if !send_more {
+            return Ok(vec![]);
+        }
+
+        self.block_stream.generate_next_chunk()
+    }
+}
+
+impl StacksHttpRequest {
+    pub fn new_get_nakamoto_block_range(
+        host: PeerHost,
+        start_block_id: StacksBlockId,
+        end_block_id: StacksBlockId,
+    ) -> StacksHttpRequest {
+        StacksHttpRequest::new_for_peer(
+            host,
+            "GET".into(),
+            "/v3/blocks/range".into(),
+            HttpRequestContents::new()
+                .query_arg("start".into(), format!("{}", &start_block_id))
+                .query_arg("end".into(), format!("{}", &end_block_id)),
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    /// Decode an HTTP response into a range of blocks, highest block first.
+    /// The bytes are a concatenation of Nakamoto blocks, with no length prefix.
+    /// If it fails, return Self::Error(..)
+    pub fn decode_nakamoto_block_range(self) -> Result<Vec<NakamotoBlock>, NetError> {
+        let contents = self.get_http_payload_ok()?;
+
+        // contents will be raw bytes
+        let range_bytes: Vec<u8> = contents.try_into()?;
+        let ptr = &mut range_bytes.as_slice();
+
+        let mut blocks = vec![];
+        while ptr.len() > 0 {
+            let block = NakamotoBlock::consensus_deserialize(ptr)?;
+            blocks.push(block);
+        }
+
+        Ok(blocks)
+    }
+}
@@ -0,0 +1,199 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use regex::{Captures, Regex};
+use stacks_common::bitvec::BitVec;
+use stacks_common::types::chainstate::StacksBlockId;
+use stacks_common::types::net::PeerHost;
+use stacks_common::util::hash::Sha512Trunc256Sum;
+use stacks_common::util::secp256k1::MessageSignature;
+use {serde, serde_json};
+
+use crate::chainstate::nakamoto::NakamotoChainState;
+use crate::net::http::{
+    parse_json, Error, HttpNotFound, HttpRequest, HttpRequestContents, HttpRequestPreamble,
+    HttpResponse, HttpResponseContents, HttpResponsePayload, HttpResponsePreamble, HttpServerError,
+};
+use crate::net::httpcore::{
+    request, HttpRequestContentsExtensions, RPCRequestHandler, StacksHttp, StacksHttpRequest,
+    StacksHttpResponse,
+};
+use crate::net::{Error as NetError, StacksNodeState};
+
+/// The signer signatures of a Nakamoto block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockSignaturesResponse {
+    pub block_id: StacksBlockId,
+    /// The hash of the block header that the signers signed
+    pub signer_signature_hash: Sha512Trunc256Sum,
+    /// The signatures over `signer_signature_hash`, in reward set order
+    pub signer_signature: Vec<MessageSignature>,
+    /// The block's PoX treatment bitvec
+    pub signer_bitvec: BitVec<4000>,
+}
+
+#[derive(Clone)]
+pub struct RPCNakamotoBlockSignaturesRequestHandler {
+    pub(crate) block_id: Option<StacksBlockId>,
+}
+
+impl RPCNakamotoBlockSignaturesRequestHandler {
+    pub fn new() -> Self {
+        Self { block_id: None }
+    }
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCNakamotoBlockSignaturesRequestHandler {
+    fn verb(&self) -> &'static str {
+        "GET"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(r#"^/v3/blocks/(?P<block_id>[0-9a-f]{64})/signatures$"#).unwrap()
+    }
+
+    fn metrics_identifier(&self) -> &str {
+        "/v3/blocks/:block_id/signatures"
+    }
+
+    /// Try to decode this request.
+    /// There's nothing to load here, so just make sure the request is well-formed.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        if preamble.get_content_length() != 0 {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+        let block_id = request::get_block_hash(captures, "block_id")?;
+        self.block_id = Some(block_id);
+        Ok(HttpRequestContents::new().query_string(query))
+    }
+}
+
+impl RPCRequestHandler for RPCNakamotoBlockSignaturesRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {
+        self.block_id = None;
+    }
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        _contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let block_id = self
+            .block_id
+            .take()
+            .ok_or(NetError::SendError("`block_id` not set".into()))?;
+
+        let signatures_resp =
+            node.with_node_state(|_network, _sortdb, chainstate, _mempool, _rpc_args| {
+                let header_info =
+                    match NakamotoChainState::get_block_header_nakamoto(chainstate.db(), &block_id)
+                    {
+                        Ok(Some(header)) => header,
+                        Ok(None) => {
+                            let msg = format!("No such Nakamoto block {}\n", &block_id);
+                            debug!("{}", &msg);
+                            return Err(StacksHttpResponse::new_error(
+                                &preamble,
+                                &HttpNotFound::new(msg),
+                            ));
+                        }
+                        Err(e) => {
+                            let msg =
+                                format!("Failed to query block header {}: {:?}\n", &block_id, &e);
+                            error!("{}", &msg);
+                            return Err(StacksHttpResponse::new_error(
+                                &preamble,
+                                &HttpServerError::new(msg),
+                            ));
+                        }
+                    };
+                let Some(header) = header_info.anchored_header.as_stacks_nakamoto() else {
+                    let msg = format!("No such Nakamoto block {}\n", &block_id);
+                    debug!("{}", &msg);
+                    return Err(StacksHttpResponse::new_error(
+                        &preamble,
+                        &HttpNotFound::new(msg),
+                    ));
+                };
+                Ok(BlockSignaturesResponse {
+                    block_id: block_id.clone(),
+                    signer_signature_hash: header.signer_signature_hash(),
+                    signer_signature: header.signer_signature.clone(),
+                    signer_bitvec: header.pox_treatment.clone(),
+                })
+            });
+
+        let signatures = match signatures_resp {
+            Ok(signatures) => signatures,
+            Err(response) => {
+                return response.try_into_contents().map_err(NetError::from);
+            }
+        };
+
+        let preamble = HttpResponsePreamble::ok_json(&preamble);
+        let body = HttpResponseContents::try_from_json(&signatures)?;
+        Ok((preamble, body))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCNakamotoBlockSignaturesRequestHandler {
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let signatures: BlockSignaturesResponse = parse_json(preamble, body)?;
+        Ok(HttpResponsePayload::try_from_json(signatures)?)
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a new request for the signer signatures of a Nakamoto block
+    pub fn new_get_nakamoto_block_signatures(
+        host: PeerHost,
+        block_id: &StacksBlockId,
+    ) -> StacksHttpRequest {
+        StacksHttpRequest::new_for_peer(
+            host,
+            "GET".into(),
+            format!("/v3/blocks/{}/signatures", block_id),
+            HttpRequestContents::new(),
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    pub fn decode_nakamoto_block_signatures(self) -> Result<BlockSignaturesResponse, NetError> {
+        let contents = self.get_http_payload_ok()?;
+        let response_json: serde_json::Value = contents.try_into()?;
+        let signatures: BlockSignaturesResponse = serde_json::from_value(response_json)
+            .map_err(|_e| Error::DecodeError("Failed to decode JSON".to_string()))?;
+        Ok(signatures)
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use regex::{Captures, Regex};
+use stacks_common::types::net::PeerHost;
+
+use crate::net::http::{
+    parse_json, Error, HttpNotFound, HttpRequest, HttpRequestContents, HttpRequestPreamble,
+    HttpResponse, HttpResponseContents, HttpResponsePayload, HttpResponsePreamble,
+};
+use crate::net::httpcore::{
+    HttpPreambleExtensions, RPCRequestHandler, StacksHttpRequest, StacksHttpResponse,
+};
+use crate::net::{Error as NetError, StacksNodeState};
+
+/// How payloads are currently reaching an event observer
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventDeliveryState {
+    /// The last delivery attempt succeeded, or none was made yet
+    Ok,
+    /// The observer's endpoint is refusing or timing out on payloads, which are being retried
+    Failing,
+    /// The observer's endpoint is unreachable, and payloads go to its fallback transport
+    Fallback,
+}
+
+/// Event delivery health of a single event observer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCEventObserverHealth {
+    /// The observer's endpoint, with any credentials redacted
+    pub endpoint: String,
+    /// Number of payloads waiting in the node's pending-payload database for this observer
+    pub queue_depth: u64,
+    /// Unix time (in seconds) of the last payload this observer took, if any since startup
+    pub last_success_time: Option<u64>,
+    /// Number of delivery attempts that failed since the last successful one
+    pub consecutive_failures: u64,
+    pub delivery_state: EventDeliveryState,
+    /// Number of payloads handed to the fallback transport instead of the observer since startup
+    pub fallback_deliveries: u64,
+}
+
+/// Event delivery health of all of the node's event observers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCEventHealthData {
+    pub observers: Vec<RPCEventObserverHealth>,
+    /// Sum of `queue_depth` across all observers
+    pub total_queue_depth: u64,
+    /// Sum of `fallback_deliveries` across all observers
+    pub total_fallback_deliveries: u64,
+}
+
+impl RPCEventHealthData {
+    pub fn new(observers: Vec<RPCEventObserverHealth>) -> Self {
+        Self {
+            total_queue_depth: observers.iter().map(|o| o.queue_depth).sum(),
+            total_fallback_deliveries: observers.iter().map(|o| o.fallback_deliveries).sum(),
+            observers,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RPCGetEventHealthRequestHandler {}
+
+impl RPCGetEventHealthRequestHandler {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCGetEventHealthRequestHandler {
+    fn verb(&self) -> &'static str {
+        "GET"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(r#"^/v2/debug/event_health$"#).unwrap()
+    }
+
+    fn metrics_identifier(&self) -> &str {
+        "/v2/debug/event_health"
+    }
+
+    /// Try to decode this request.
+    /// There's nothing to load here, so just make sure the request is well-formed.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        _body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        if preamble.get_content_length() != 0 {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+        Ok(HttpRequestContents::new().query_string(query))
+    }
+}
+
+impl RPCRequestHandler for RPCGetEventHealthRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {}
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        _contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let health = node.with_node_state(|_network, _sortdb, _chainstate, _mempool, rpc_args| {
+            rpc_args
+                .event_observer
+                .and_then(|observer| observer.get_event_delivery_health())
+        });
+
+        let Some(health) = health else {
+            return StacksHttpResponse::new_error(
+                &preamble,
+                &HttpNotFound::new("Event delivery health is not available on this node".into()),
+            )
+            .try_into_contents()
+            .map_err(NetError::from);
+        };
+
+        let mut preamble = HttpResponsePreamble::ok_json(&preamble);
+        preamble.set_canonical_stacks_tip_height(Some(node.canonical_stacks_tip_height()));
+        let body = HttpResponseContents::try_from_json(&health)?;
+        Ok((preamble, body))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCGetEventHealthRequestHandler {
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let health: RPCEventHealthData = parse_json(preamble, body)?;
+        Ok(HttpResponsePayload::try_from_json(health)?)
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a new request for the node's event delivery health
+    pub fn new_get_event_health(host: PeerHost) -> StacksHttpRequest {
+        StacksHttpRequest::new_for_peer(
+            host,
+            "GET".into(),
+            "/v2/debug/event_health".into(),
+            HttpRequestContents::new(),
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    pub fn decode_event_health(self) -> Result<RPCEventHealthData, NetError> {
+        let contents = self.get_http_payload_ok()?;
+        let response_json: serde_json::Value = contents.try_into()?;
+        let health: RPCEventHealthData = serde_json::from_value(response_json)
+            .map_err(|_e| Error::DecodeError("Failed to decode JSON".to_string()))?;
+        Ok(health)
+    }
+}
@@ -0,0 +1,156 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use regex::{Captures, Regex};
+use stacks_common::types::net::PeerHost;
+
+use crate::burnchains::Txid;
+use crate::net::http::{
+    parse_json, Error, HttpNotFound, HttpRequest, HttpRequestContents, HttpRequestPreamble,
+    HttpResponse, HttpResponseContents, HttpResponsePayload, HttpResponsePreamble,
+};
+use crate::net::httpcore::{
+    HttpPreambleExtensions, RPCRequestHandler, StacksHttpRequest, StacksHttpResponse,
+};
+use crate::net::{Error as NetError, StacksNodeState};
+
+/// A transaction that was dropped from the mempool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCMempoolDrop {
+    pub txid: Txid,
+    /// Why it was dropped, as given in the `/drop_mempool_tx` event (e.g. `ReplaceByFee`)
+    pub reason: String,
+    /// Unix time (in seconds) at which it was dropped
+    pub timestamp: u64,
+}
+
+/// The transactions most recently dropped from the mempool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCMempoolDropsData {
+    /// Dropped transactions, oldest first
+    pub drops: Vec<RPCMempoolDrop>,
+    /// Most dropped transactions that are remembered
+    pub capacity: u64,
+}
+
+#[derive(Clone)]
+pub struct RPCGetMempoolDropsRequestHandler {}
+
+impl RPCGetMempoolDropsRequestHandler {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCGetMempoolDropsRequestHandler {
+    fn verb(&self) -> &'static str {
+        "GET"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(r#"^/v2/debug/mempool_drops$"#).unwrap()
+    }
+
+    fn metrics_identifier(&self) -> &str {
+        "/v2/debug/mempool_drops"
+    }
+
+    /// Try to decode this request.
+    /// There's nothing to load here, so just make sure the request is well-formed.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        _body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        if preamble.get_content_length() != 0 {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+        Ok(HttpRequestContents::new().query_string(query))
+    }
+}
+
+impl RPCRequestHandler for RPCGetMempoolDropsRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {}
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        _contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let drops = node.with_node_state(|_network, _sortdb, _chainstate, _mempool, rpc_args| {
+            rpc_args
+                .event_observer
+                .and_then(|observer| observer.get_mempool_drop_history())
+        });
+
+        let Some(drops) = drops else {
+            return StacksHttpResponse::new_error(
+                &preamble,
+                &HttpNotFound::new("Mempool drop history is not kept on this node".into()),
+            )
+            .try_into_contents()
+            .map_err(NetError::from);
+        };
+
+        let mut preamble = HttpResponsePreamble::ok_json(&preamble);
+        preamble.set_canonical_stacks_tip_height(Some(node.canonical_stacks_tip_height()));
+        let body = HttpResponseContents::try_from_json(&drops)?;
+        Ok((preamble, body))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCGetMempoolDropsRequestHandler {
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let drops: RPCMempoolDropsData = parse_json(preamble, body)?;
+        Ok(HttpResponsePayload::try_from_json(drops)?)
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a new request for the node's history of dropped mempool transactions
+    pub fn new_get_mempool_drops(host: PeerHost) -> StacksHttpRequest {
+        StacksHttpRequest::new_for_peer(
+            host,
+            "GET".into(),
+            "/v2/debug/mempool_drops".into(),
+            HttpRequestContents::new(),
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    pub fn decode_mempool_drops(self) -> Result<RPCMempoolDropsData, NetError> {
+        let contents = self.get_http_payload_ok()?;
+        let response_json: serde_json::Value = contents.try_into()?;
+        let drops: RPCMempoolDropsData = serde_json::from_value(response_json)
+            .map_err(|_e| Error::DecodeError("Failed to decode JSON".to_string()))?;
+        Ok(drops)
+    }
+}
@@ -14,33 +14,66 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::VecDeque;
 use std::io::{Read, Seek, SeekFrom, Write};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use std::{fs, io};
 
 use regex::{Captures, Regex};
+use serde;
 use serde::de::Error as de_Error;
+use serde_json;
 use stacks_common::codec::{StacksMessageCodec, MAX_PAYLOAD_LEN};
 use stacks_common::types::chainstate::{ConsensusHash, StacksBlockId};
 use stacks_common::types::net::PeerHost;
 use stacks_common::util::hash::to_hex;
-use {serde, serde_json};
 
+use crate::burnchains::Burnchain;
+use crate::chainstate::burn::db::sortdb::{get_ancestor_sort_id, SortitionDB};
+use crate::chainstate::coordinator::OnChainRewardSetProvider;
+use crate::chainstate::nakamoto::staging_blocks::ArchivalBlockSource;
 use crate::chainstate::nakamoto::{NakamotoBlock, NakamotoChainState, NakamotoStagingBlocksConn};
 use crate::chainstate::stacks::db::StacksChainState;
 use crate::chainstate::stacks::Error as ChainError;
-use crate::net::api::getblock_v3::NakamotoBlockStream;
+use crate::net::api::conditional::{make_conditional_response, make_etag};
+use crate::net::api::getblock_v3::{load_archived_block, NakamotoBlockStream};
+#[cfg(feature = "protobuf")]
+use crate::net::api::protobuf::{accepts_protobuf, block_frame_prefix, decode_block_frames};
+use crate::net::api::tenure_archive::{
+    archive_entry, archive_entry_header, decode_tenure_archive, TenureArchive,
+    TenureArchiveManifest, TENURE_ARCHIVE_MAGIC, TENURE_ARCHIVE_MANIFEST_NAME,
+};
+use crate::net::http::common::parse_raw_bytes;
 use crate::net::http::{
-    parse_bytes, Error, HttpBadRequest, HttpChunkGenerator, HttpContentType, HttpNotFound,
-    HttpRequest, HttpRequestContents, HttpRequestPreamble, HttpResponse, HttpResponseContents,
-    HttpResponsePayload, HttpResponsePreamble, HttpServerError, HttpVersion,
+    parse_bytes, Error, HttpBadRequest, HttpChunkGenerator, HttpContentType, HttpError,
+    HttpForbidden, HttpNotFound, HttpRequest, HttpRequestContents, HttpRequestPreamble,
+    HttpResponse, HttpResponseContents, HttpResponsePayload, HttpResponsePreamble, HttpServerError,
+    HttpServiceUnavailable, HttpVersion,
 };
 use crate::net::httpcore::{
-    HttpRequestContentsExtensions, RPCRequestHandler, StacksHttp, StacksHttpRequest,
-    StacksHttpResponse,
+    send_http_request, HttpRequestContentsExtensions, RPCRequestHandler, SendRequestError,
+    StacksHttp, StacksHttpRequest, StacksHttpResponse,
 };
 use crate::net::{Error as NetError, StacksNodeState, TipRequest, MAX_HEADERS};
 use crate::util_lib::db::{DBConn, Error as DBError};
 
+/// Longest time a `follow=true` request holds its connection open, waiting for new blocks.
+/// It is further capped by the node's HTTP request timeout, so the connection is not closed
+/// as idle while it waits.
+pub const TENURE_FOLLOW_MAX_HOLD_SECS: u64 = 30;
+
+/// Default for the most `/v3/tenures` requests `fetch_tenure_paged()` makes for one tenure
+pub const DEFAULT_TENURE_MAX_PAGES: usize = 64;
+
+/// Bytes of a `format=archive` stream set aside for the archive's magic, and for its manifest
+/// entry apart from the manifest's block IDs
+const TENURE_ARCHIVE_RESERVED_BYTES: u64 = 256;
+
+/// Bytes that each block adds to an archive's manifest: its quoted hex ID, and a comma
+const TENURE_ARCHIVE_MANIFEST_BYTES_PER_BLOCK: u64 = 67;
+
 #[derive(Clone)]
 pub struct RPCNakamotoTenureRequestHandler {
     /// Block to start streaming from. It and its ancestors will be incrementally streamed until one of
@@ -51,6 +84,23 @@ pub struct RPCNakamotoTenureRequestHandler {
     /// What's the final block ID to stream from?
     /// Passed as `stop=` query parameter
     pub last_block_id: Option<StacksBlockId>,
+    /// Whether to frame each block as a length-delimited protobuf message.
+    /// Set by `Accept: application/x-protobuf`, if the `protobuf` feature is enabled.
+    pub protobuf: bool,
+    /// Whether to keep streaming the tenure's new blocks as they arrive.
+    /// Passed as `follow=` query parameter
+    pub follow: bool,
+    /// Whether to send the blocks as a tenure archive (see `tenure_archive`).
+    /// Passed as `format=archive` query parameter
+    pub archive: bool,
+    /// Whether to check the blocks' signer signatures before streaming them.
+    /// Passed as `verify=signatures` query parameter
+    pub verify: bool,
+    /// Most bytes to send, if fewer than MAX_PAYLOAD_LEN.
+    /// Passed as `max_bytes=` query parameter
+    pub max_bytes: Option<u64>,
+    /// Address of the caller, checked against the node's `tenure_stream_allowlist`
+    pub peer_addr: Option<SocketAddr>,
 }
 
 impl RPCNakamotoTenureRequestHandler {
@@ -58,6 +108,74 @@ impl RPCNakamotoTenureRequestHandler {
         Self {
             block_id: None,
             last_block_id: None,
+            protobuf: false,
+            follow: false,
+            archive: false,
+            verify: false,
+            max_bytes: None,
+            peer_addr: None,
+        }
+    }
+
+    /// Serve the caller at `peer_addr`
+    pub fn with_peer_addr(mut self, peer_addr: SocketAddr) -> Self {
+        self.peer_addr = Some(peer_addr);
+        self
+    }
+
+    /// Whether the caller may stream tenures (see `is_tenure_stream_caller_allowed()`)
+    pub fn is_caller_allowed(&self, allowlist: Option<&[IpAddr]>) -> bool {
+        is_tenure_stream_caller_allowed(self.peer_addr.as_ref(), allowlist)
+    }
+}
+
+/// Whether the caller at `peer_addr` may stream tenures. Without an allowlist, everyone may;
+/// with one, only callers whose IP address is on it may, so a caller of unknown address may not.
+pub fn is_tenure_stream_caller_allowed(
+    peer_addr: Option<&SocketAddr>,
+    allowlist: Option<&[IpAddr]>,
+) -> bool {
+    let Some(allowlist) = allowlist else {
+        return true;
+    };
+    peer_addr.is_some_and(|peer_addr| allowlist.contains(&peer_addr.ip()))
+}
+
+/// State for streaming a tenure's blocks as they arrive, once its existing blocks are sent
+pub struct TenureFollowState {
+    /// newest block of the tenure that the client has
+    pub tip: StacksBlockId,
+    /// stop waiting for new blocks at this time
+    pub deadline: Instant,
+    /// whether the tenure's existing blocks have all been sent
+    pub following: bool,
+    /// new blocks to send, as (block ID, parent block ID), in the order they will be sent
+    pub pending: VecDeque<(StacksBlockId, StacksBlockId)>,
+    /// whether the tenure is known to end with the last pending block
+    pub tenure_over: bool,
+    /// staging DB `data_version` when we last looked for a new block
+    pub data_version: Option<i64>,
+    /// set once the tenure ends, or there's no more room in the stream
+    pub done: bool,
+}
+
+/// State for sending a tenure's blocks as a tenure archive
+pub struct TenureArchiveState {
+    /// whether the archive's magic has been sent
+    pub started: bool,
+    /// the blocks sent so far, in the order they were sent
+    pub block_ids: Vec<StacksBlockId>,
+    /// whether the manifest has been sent, which ends the archive
+    pub manifest_sent: bool,
+}
+
+impl TenureArchiveState {
+    /// The archive's magic the first time this is called, and nothing after that
+    fn start(&mut self) -> Vec<u8> {
+        if std::mem::replace(&mut self.started, true) {
+            vec![]
+        } else {
+            TENURE_ARCHIVE_MAGIC.to_vec()
         }
     }
 }
@@ -71,6 +189,26 @@ pub struct NakamotoTenureStream {
     pub total_sent: u64,
     /// stop streaming if we reach this block
     pub last_block_id: Option<StacksBlockId>,
+    /// whether each block is preceded by a length-delimited protobuf frame header
+    pub protobuf_framing: bool,
+    /// if set, stream the tenure's new blocks as they arrive
+    pub follow: Option<TenureFollowState>,
+    /// if set, send the blocks as entries of a tenure archive
+    pub archive: Option<TenureArchiveState>,
+    /// if set, end the stream early if looking up the next block takes longer than this
+    pub db_budget: Option<Duration>,
+    /// when the lookup of the next block must be done by, if there's a `db_budget`
+    pub lookup_deadline: Option<Instant>,
+    /// set if the stream ended early because looking up the next block took too long
+    pub truncated: bool,
+    /// set if the stream ended early because this block, the next one to send, is corrupt in
+    /// the node's storage
+    pub corrupt_block_id: Option<StacksBlockId>,
+    /// most bytes to send; no block is started that would take the stream past this
+    pub max_bytes: u64,
+    /// how long each DB lookup is made to take, to simulate a slow DB
+    #[cfg(test)]
+    pub test_db_delay: Option<Duration>,
 }
 
 impl NakamotoTenureStream {
@@ -81,21 +219,217 @@ impl NakamotoTenureStream {
         parent_block_id: StacksBlockId,
         last_block_id: Option<StacksBlockId>,
     ) -> Result<Self, ChainError> {
-        let block_stream =
-            NakamotoBlockStream::new(chainstate, block_id, consensus_hash, parent_block_id)?;
+        Self::new_with_archive(
+            chainstate,
+            block_id,
+            consensus_hash,
+            parent_block_id,
+            last_block_id,
+            None,
+        )
+    }
+
+    /// Stream a tenure's blocks, looking for any that aren't in the staging DB in `archive`
+    pub fn new_with_archive(
+        chainstate: &StacksChainState,
+        block_id: StacksBlockId,
+        consensus_hash: ConsensusHash,
+        parent_block_id: StacksBlockId,
+        last_block_id: Option<StacksBlockId>,
+        archive: Option<Arc<dyn ArchivalBlockSource>>,
+    ) -> Result<Self, ChainError> {
+        let block_stream = NakamotoBlockStream::new_with_archive(
+            chainstate,
+            block_id,
+            consensus_hash,
+            parent_block_id,
+            archive,
+        )?;
         let headers_conn = chainstate.reopen_db()?;
         Ok(NakamotoTenureStream {
             block_stream,
             headers_conn,
             total_sent: 0,
             last_block_id,
+            protobuf_framing: false,
+            follow: None,
+            archive: None,
+            db_budget: None,
+            lookup_deadline: None,
+            truncated: false,
+            corrupt_block_id: None,
+            max_bytes: MAX_PAYLOAD_LEN.into(),
+            #[cfg(test)]
+            test_db_delay: None,
         })
     }
 
+    /// Once the tenure's blocks are sent, keep streaming the blocks that are added to it, until
+    /// the tenure ends, the stream would exceed MAX_PAYLOAD_LEN bytes, or `hold` elapses.
+    /// Must be called before streaming starts.
+    pub fn with_follow(mut self, hold: Duration) -> Self {
+        self.follow = Some(TenureFollowState {
+            tip: self.block_stream.index_block_hash.clone(),
+            deadline: Instant::now() + hold,
+            following: false,
+            pending: VecDeque::new(),
+            tenure_over: false,
+            data_version: None,
+            done: false,
+        });
+        self
+    }
+
+    /// End the stream early if looking up the next block to send ever takes longer than
+    /// `db_budget`, rather than holding up the RPC thread behind a slow DB.  The client sees a
+    /// stream that ends before the tenure's start, and asks for the rest of it starting from
+    /// the last block it got, as it does when the stream runs out of room.  An archive's
+    /// manifest is marked `truncated`.
+    pub fn with_db_budget(mut self, db_budget: Duration) -> Self {
+        self.db_budget = Some(db_budget);
+        self
+    }
+
+    /// Don't start sending a block that would take the stream past `max_bytes`, rather than
+    /// MAX_PAYLOAD_LEN, bytes.  It is clamped to MAX_PAYLOAD_LEN.  The block the stream
+    /// starts with is always sent, so that the client always makes progress.  As when the
+    /// stream runs out of room, the client asks for the rest of the tenure starting from the
+    /// parent of the last block it got.
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes.min(MAX_PAYLOAD_LEN.into());
+        self
+    }
+
+    /// Frame each block as a length-delimited protobuf message
+    #[cfg(feature = "protobuf")]
+    pub fn with_protobuf_framing(mut self, protobuf_framing: bool) -> Self {
+        self.protobuf_framing = protobuf_framing;
+        self
+    }
+
+    /// Send the blocks as a tenure archive: each block is an archive entry, and the stream
+    /// ends with the archive's manifest.  Must be called before streaming starts.
+    pub fn with_archive_format(mut self) -> Self {
+        self.archive = Some(TenureArchiveState {
+            started: false,
+            block_ids: vec![],
+            manifest_sent: false,
+        });
+        self.total_sent = TENURE_ARCHIVE_RESERVED_BYTES;
+        self
+    }
+
+    /// The bytes to send ahead of block `block_id` of `block_size` bytes
+    fn block_frame_prefix(&self, block_id: &StacksBlockId, block_size: u64) -> Vec<u8> {
+        if self.archive.is_some() {
+            archive_entry_header(&block_id.to_hex(), block_size)
+        } else {
+            self.protobuf_frame_prefix(block_size)
+        }
+    }
+
+    /// The protobuf frame header to send ahead of a block of `block_size` bytes
+    #[cfg(feature = "protobuf")]
+    fn protobuf_frame_prefix(&self, block_size: u64) -> Vec<u8> {
+        if self.protobuf_framing {
+            block_frame_prefix(block_size)
+        } else {
+            vec![]
+        }
+    }
+
+    /// The protobuf frame header to send ahead of a block of `block_size` bytes
+    #[cfg(not(feature = "protobuf"))]
+    fn protobuf_frame_prefix(&self, _block_size: u64) -> Vec<u8> {
+        vec![]
+    }
+
+    /// Prepend the current block's frame header to `chunk`, if `chunk` starts the block
+    fn frame_chunk(&mut self, starts_block: bool, chunk: Vec<u8>) -> Result<Vec<u8>, String> {
+        let framed_blocks = self.protobuf_framing || self.archive.is_some();
+        if !framed_blocks || !starts_block || chunk.is_empty() {
+            return Ok(chunk);
+        }
+        let block_id = self.block_stream.index_block_hash.clone();
+        let block_size = self
+            .block_stream
+            .get_block_size(&block_id)
+            .map_err(|e| format!("Failed to load block size: {:?}", &e))?
+            .ok_or_else(|| "No such block".to_string())?;
+        let prefix = self.block_frame_prefix(&block_id, block_size);
+        self.total_sent = self.total_sent.saturating_add(prefix.len() as u64);
+
+        // the archive's magic is counted in `TENURE_ARCHIVE_RESERVED_BYTES`
+        let mut framed = match self.archive.as_mut() {
+            Some(archive) => {
+                archive.block_ids.push(block_id);
+                archive.start()
+            }
+            None => vec![],
+        };
+        framed.extend(prefix);
+        framed.extend(chunk);
+        Ok(framed)
+    }
+
+    /// The manifest entry that ends the archive, if this is an archive stream whose manifest
+    /// hasn't been sent yet.  Its size is counted in `TENURE_ARCHIVE_RESERVED_BYTES` and
+    /// `TENURE_ARCHIVE_MANIFEST_BYTES_PER_BLOCK`.
+    fn finish_archive(&mut self) -> Result<Vec<u8>, String> {
+        let consensus_hash = self.block_stream.consensus_hash.clone();
+        let Some(archive) = self.archive.as_mut() else {
+            return Ok(vec![]);
+        };
+        if archive.manifest_sent {
+            return Ok(vec![]);
+        }
+        let manifest = TenureArchiveManifest {
+            consensus_hash,
+            block_ids: archive.block_ids.clone(),
+            truncated: self.truncated,
+            corrupt_block_id: self.corrupt_block_id.clone(),
+        };
+        let manifest_bytes = serde_json::to_vec(&manifest)
+            .map_err(|e| format!("Failed to serialize tenure archive manifest: {:?}", &e))?;
+        archive.manifest_sent = true;
+
+        let mut chunk = archive.start();
+        chunk.extend(archive_entry(TENURE_ARCHIVE_MANIFEST_NAME, &manifest_bytes));
+        Ok(chunk)
+    }
+
+    /// End a stream that found a corrupt block.  An archive ends with a manifest naming the
+    /// block.  Any other stream fails, rather than ending as if the tenure had been sent.
+    fn finish_corrupt(&mut self) -> Result<Vec<u8>, String> {
+        let Some(corrupt_block_id) = self.corrupt_block_id.as_ref() else {
+            return Ok(vec![]);
+        };
+        if self.archive.is_some() {
+            return self.finish_archive();
+        }
+        Err(format!(
+            "Nakamoto block {} is corrupt in this node's storage",
+            corrupt_block_id
+        ))
+    }
+
+    /// Check the block that the stream just moved to, before any of it is sent.  If it's
+    /// corrupt in storage, the stream sends no more blocks (see `finish_corrupt()`).
+    fn check_block_intact(&mut self) -> Result<bool, ChainError> {
+        if self.block_stream.is_block_intact()? {
+            return Ok(true);
+        }
+        self.corrupt_block_id = Some(self.block_stream.index_block_hash.clone());
+        if let Some(follow) = self.follow.as_mut() {
+            follow.done = true;
+        }
+        Ok(false)
+    }
+
     /// Start streaming the next block (i.e. the parent of the block we last streamed).
     /// Return Ok(true) if we can fit the block into the stream.
     /// Return Ok(false) if not. The caller will need to call this RPC method again with the block
-    /// ID of the last block it received.
+    /// ID of the last block it received.  Also return Ok(false) if the block is corrupt.
     /// Return Err(..) on DB error
     pub fn next_block(&mut self) -> Result<bool, ChainError> {
         let parent_header = NakamotoChainState::get_block_header(
@@ -103,6 +437,9 @@ impl NakamotoTenureStream {
             &self.block_stream.parent_block_id,
         )?
         .ok_or(ChainError::NoSuchBlockError)?;
+        if self.lookup_overran() {
+            return Ok(false);
+        }
 
         // stop sending if the parent is an epoch2 block
         let Some(parent_nakamoto_header) = parent_header.anchored_header.as_stacks_nakamoto()
@@ -122,27 +459,371 @@ impl NakamotoTenureStream {
             return Ok(false);
         }
 
+        let parent_block_id = self.block_stream.parent_block_id.clone();
         let parent_size = self
             .block_stream
-            .staging_db_conn
-            .conn()
-            .get_nakamoto_block_size(&self.block_stream.parent_block_id)?
+            .get_block_size(&parent_block_id)?
             .ok_or(ChainError::NoSuchBlockError)?;
+        if self.lookup_overran() {
+            return Ok(false);
+        }
 
-        self.total_sent = self
-            .total_sent
-            .saturating_add(self.block_stream.total_bytes);
-        if self.total_sent.saturating_add(parent_size) > MAX_PAYLOAD_LEN.into() {
+        if !self.has_room_for(&parent_block_id, parent_size) {
             // out of space to send this
             return Ok(false);
         }
 
+        self.total_sent = self
+            .total_sent
+            .saturating_add(self.block_stream.total_bytes);
         self.block_stream.reset(
             parent_nakamoto_header.block_id(),
             parent_nakamoto_header.parent_block_id.clone(),
         )?;
-        Ok(true)
+        self.check_block_intact()
+    }
+
+    /// Can we send block `block_id` of `block_size` bytes after the current block, without
+    /// exceeding the stream's `max_bytes`?
+    fn has_room_for(&self, block_id: &StacksBlockId, block_size: u64) -> bool {
+        let mut frame_len = self.block_frame_prefix(block_id, block_size).len() as u64;
+        if self.archive.is_some() {
+            frame_len += TENURE_ARCHIVE_MANIFEST_BYTES_PER_BLOCK;
+        }
+        self.total_sent
+            .saturating_add(self.block_stream.total_bytes)
+            .saturating_add(frame_len)
+            .saturating_add(block_size)
+            <= self.max_bytes
+    }
+
+    /// Whether looking up the next block has taken longer than the stream's `db_budget`.  If
+    /// so, the stream is marked truncated, and stops following the tenure.
+    fn lookup_overran(&mut self) -> bool {
+        #[cfg(test)]
+        if let Some(delay) = self.test_db_delay {
+            std::thread::sleep(delay);
+        }
+        let Some(deadline) = self.lookup_deadline else {
+            return false;
+        };
+        if Instant::now() < deadline {
+            return false;
+        }
+        warn!(
+            "Tenure stream: looking up the next block took too long, ending the stream early";
+            "consensus_hash" => %self.block_stream.consensus_hash,
+            "block_id" => %self.block_stream.index_block_hash,
+        );
+        self.truncated = true;
+        if let Some(follow) = self.follow.as_mut() {
+            follow.done = true;
+        }
+        true
+    }
+
+    /// Start streaming the next block of the tenure that was processed after the last one the
+    /// client has, if there is one.  New blocks are only looked for if the staging DB has
+    /// changed since the last time we looked.
+    /// Return Ok(true) if there's a new block to send.
+    /// Return Ok(false) if not.  The stream is either done, or waiting (see `is_waiting()`).
+    /// It is done if the new block is corrupt.
+    /// Return Err(..) on DB error
+    pub fn next_followed_block(&mut self) -> Result<bool, ChainError> {
+        let Some(follow) = self.follow.as_mut() else {
+            return Ok(false);
+        };
+        if follow.done {
+            return Ok(false);
+        }
+        if Instant::now() >= follow.deadline {
+            follow.done = true;
+            return Ok(false);
+        }
+        follow.following = true;
+
+        if follow.pending.is_empty() && follow.tenure_over {
+            follow.done = true;
+            return Ok(false);
+        }
+        if follow.pending.is_empty() {
+            let staging_conn = self.block_stream.staging_db_conn.conn();
+            let data_version = staging_conn.get_data_version()?;
+            if follow.data_version == Some(data_version) {
+                // nothing new
+                return Ok(false);
+            }
+            follow.data_version = Some(data_version);
+
+            follow.pending = NakamotoChainState::get_known_descendants_in_tenure(
+                &self.headers_conn,
+                &follow.tip,
+            )?
+            .into_iter()
+            .map(|header| (header.block_id(), header.parent_block_id))
+            .collect();
+            let last_block_id = follow
+                .pending
+                .back()
+                .map(|(block_id, _)| block_id)
+                .unwrap_or(&follow.tip);
+            follow.tenure_over = staging_conn
+                .has_children_in_other_tenure(last_block_id, &self.block_stream.consensus_hash)?;
+            if follow.pending.is_empty() {
+                follow.done = follow.tenure_over;
+                return Ok(false);
+            }
+        }
+
+        let Some((block_id, parent_block_id)) = follow.pending.pop_front() else {
+            return Ok(false);
+        };
+        let block_size = self
+            .block_stream
+            .get_block_size(&block_id)?
+            .ok_or(ChainError::NoSuchBlockError)?;
+        if self.lookup_overran() {
+            return Ok(false);
+        }
+        if !self.has_room_for(&block_id, block_size) {
+            // out of space to send this.  The client can ask for the rest of the tenure
+            // starting from the last block it got.
+            if let Some(follow) = self.follow.as_mut() {
+                follow.done = true;
+            }
+            return Ok(false);
+        }
+
+        if let Some(follow) = self.follow.as_mut() {
+            follow.tip = block_id.clone();
+        }
+        self.total_sent = self
+            .total_sent
+            .saturating_add(self.block_stream.total_bytes);
+        self.block_stream.reset(block_id, parent_block_id)?;
+        self.check_block_intact()
+    }
+}
+
+/// Why a tenure's blocks failed `verify=signatures`
+#[derive(Debug)]
+pub enum TenureVerifyError {
+    /// This block's signer signatures don't meet the signing threshold of its reward set
+    InvalidSignatures(StacksBlockId, String),
+    /// The reward set of this reward cycle can't be loaded, so the blocks can't be checked
+    NoRewardSet(u64, String),
+    /// A block couldn't be loaded
+    ChainError(ChainError),
+}
+
+impl From<ChainError> for TenureVerifyError {
+    fn from(e: ChainError) -> Self {
+        Self::ChainError(e)
+    }
+}
+
+/// Check the signer signatures of the blocks that `stream` sends: the block it starts with and
+/// its ancestors in the same tenure, back to the tenure's first block or to the stream's
+/// `last_block_id` (which isn't sent).  Each block is checked as it would be sent, so it's
+/// loaded from the staging DB, or from `archive` if the staging DB doesn't have it.  All of
+/// the blocks are checked against the reward set of the tenure's reward cycle, as they are
+/// when they're relayed.
+/// Only the blocks that fit in the stream's `max_bytes` are checked, and once checking has
+/// taken longer than the stream's `db_budget`, no more blocks are, though the first block
+/// always is.  The stream must then end before the first block that wasn't checked, and the
+/// client asks for the rest of the tenure starting from the last block it got.
+/// Returns Ok(None) if every block the stream sends is signed by enough of the reward set's
+/// signers.
+/// Returns Ok(Some(block_id)) if they are, but checking stopped at `block_id`, which must not
+/// be sent.
+pub fn verify_tenure_signatures(
+    burnchain: &Burnchain,
+    sortdb: &SortitionDB,
+    chainstate: &mut StacksChainState,
+    archive: Option<&dyn ArchivalBlockSource>,
+    stream: &NakamotoTenureStream,
+) -> Result<Option<StacksBlockId>, TenureVerifyError> {
+    let deadline = stream.db_budget.map(|db_budget| Instant::now() + db_budget);
+    let block_id = &stream.block_stream.index_block_hash;
+    let consensus_hash = &stream.block_stream.consensus_hash;
+
+    let sn = SortitionDB::get_block_snapshot_consensus(sortdb.conn(), consensus_hash)
+        .map_err(ChainError::from)?
+        .ok_or(ChainError::NoSuchBlockError)?;
+    let reward_cycle = burnchain
+        .block_height_to_reward_cycle(sn.block_height)
+        .expect("FATAL: sortition has no reward cycle");
+    let reward_set = OnChainRewardSetProvider::new()
+        .read_reward_set_nakamoto(chainstate, reward_cycle, sortdb, block_id, true)
+        .map_err(|e| TenureVerifyError::NoRewardSet(reward_cycle, format!("{:?}", &e)))?;
+
+    let mut total_bytes = stream.total_sent;
+    let mut first_block = true;
+    let mut next_block_id = Some(block_id.clone());
+    while let Some(block_id) = next_block_id.take() {
+        if !first_block && deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            warn!(
+                "Tenure stream: checking signer signatures took too long, ending the stream early";
+                "consensus_hash" => %consensus_hash,
+                "block_id" => %block_id,
+            );
+            return Ok(Some(block_id));
+        }
+        let (block, block_size) = match chainstate
+            .nakamoto_blocks_db()
+            .get_nakamoto_block(&block_id)?
+        {
+            Some(block) => block,
+            None => {
+                let bytes = archive
+                    .and_then(|archive| load_archived_block(archive, &block_id))
+                    .ok_or(ChainError::NoSuchBlockError)?;
+                let block = NakamotoBlock::consensus_deserialize(&mut &bytes[..])
+                    .map_err(ChainError::from)?;
+                (block, bytes.len() as u64)
+            }
+        };
+
+        // the stream always sends its first block, and then only the blocks that fit
+        let mut frame_len = stream.block_frame_prefix(&block_id, block_size).len() as u64;
+        if stream.archive.is_some() {
+            frame_len += TENURE_ARCHIVE_MANIFEST_BYTES_PER_BLOCK;
+        }
+        total_bytes = total_bytes
+            .saturating_add(frame_len)
+            .saturating_add(block_size);
+        if !first_block && total_bytes > stream.max_bytes {
+            return Ok(Some(block_id));
+        }
+
+        block
+            .header
+            .verify_signer_signatures(&reward_set)
+            .map_err(|e| TenureVerifyError::InvalidSignatures(block_id, format!("{:?}", &e)))?;
+        first_block = false;
+
+        // stop at the same blocks that `NakamotoTenureStream::next_block()` stops at
+        let parent_block_id = block.header.parent_block_id;
+        if stream.last_block_id.as_ref() == Some(&parent_block_id) {
+            break;
+        }
+        let Some(parent_header) =
+            NakamotoChainState::get_block_header(chainstate.db(), &parent_block_id)?
+        else {
+            break;
+        };
+        if parent_header
+            .anchored_header
+            .as_stacks_nakamoto()
+            .is_some_and(|parent| &parent.consensus_hash == consensus_hash)
+        {
+            next_block_id = Some(parent_block_id);
+        }
+    }
+    Ok(None)
+}
+
+/// Response header with whether a `/v3/tenures` tenure's sortition selected a PoX anchor block
+pub const TENURE_POX_ANCHOR_HEADER: &str = "X-Stacks-Is-Pox-Anchor";
+
+/// Response header with the reward cycle that a `/v3/tenures` tenure is the PoX anchor of
+pub const TENURE_POX_ANCHOR_REWARD_CYCLE_HEADER: &str = "X-Stacks-Pox-Anchor-Reward-Cycle";
+
+/// Whether a tenure's sortition selected a PoX anchor block
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TenurePoxAnchorStatus {
+    /// Whether the tenure's block-commit is the PoX anchor block of a reward cycle
+    pub is_pox_anchor: bool,
+    /// The reward cycle whose reward set the anchor block selects, if it is one
+    pub pox_anchor_reward_cycle: Option<u64>,
+}
+
+impl TenurePoxAnchorStatus {
+    /// Add this status to a `/v3/tenures` response's headers
+    pub fn add_headers(&self, preamble: &mut HttpResponsePreamble) {
+        preamble.add_header(
+            TENURE_POX_ANCHOR_HEADER.into(),
+            self.is_pox_anchor.to_string(),
+        );
+        if let Some(reward_cycle) = self.pox_anchor_reward_cycle {
+            preamble.add_header(
+                TENURE_POX_ANCHOR_REWARD_CYCLE_HEADER.into(),
+                reward_cycle.to_string(),
+            );
+        }
+    }
+
+    /// Read this status from a `/v3/tenures` response's headers, if it has it
+    pub fn from_headers(preamble: &HttpResponsePreamble) -> Option<Self> {
+        let is_pox_anchor = preamble
+            .get_header(TENURE_POX_ANCHOR_HEADER.into())?
+            .parse()
+            .ok()?;
+        let pox_anchor_reward_cycle = preamble
+            .get_header(TENURE_POX_ANCHOR_REWARD_CYCLE_HEADER.into())
+            .and_then(|reward_cycle| reward_cycle.parse().ok());
+        Some(Self {
+            is_pox_anchor,
+            pox_anchor_reward_cycle,
+        })
+    }
+}
+
+/// Find whether the sortition of the Nakamoto tenure `consensus_hash` selected a PoX anchor
+/// block.  A Nakamoto tenure's anchor block for reward cycle R is chosen from the sortitions of
+/// the prepare phase that ends as R starts, so a tenure can only be the anchor of the reward
+/// cycle after its own, or of its own reward cycle if its sortition is the last of that prepare
+/// phase.  The tenure is the anchor if its block-commit is the one that the canonical burnchain
+/// fork's reward set for that cycle was computed from.  Until that reward set is computed, no
+/// tenure is its anchor.
+pub fn get_tenure_pox_anchor_status(
+    sortdb: &SortitionDB,
+    consensus_hash: &ConsensusHash,
+) -> Result<TenurePoxAnchorStatus, DBError> {
+    let sn = SortitionDB::get_block_snapshot_consensus(sortdb.conn(), consensus_hash)?
+        .ok_or(DBError::NotFoundError)?;
+    let Some(reward_cycle) = sortdb
+        .pox_constants
+        .block_height_to_reward_cycle(sortdb.first_block_height, sn.block_height)
+    else {
+        return Ok(TenurePoxAnchorStatus::default());
+    };
+    if !sn.sortition {
+        return Ok(TenurePoxAnchorStatus::default());
+    }
+
+    let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn())?;
+    for anchored_cycle in [reward_cycle + 1, reward_cycle] {
+        let Some(prepare_cycle) = anchored_cycle.checked_sub(1) else {
+            continue;
+        };
+        let prepare_phase_start = sortdb
+            .pox_constants
+            .prepare_phase_start(sortdb.first_block_height, prepare_cycle);
+        if prepare_phase_start > tip.block_height {
+            continue;
+        }
+        let Some(prepare_phase_start_id) =
+            get_ancestor_sort_id(&sortdb.index_conn(), prepare_phase_start, &tip.sortition_id)?
+        else {
+            continue;
+        };
+        let Some(rc_info) =
+            SortitionDB::get_preprocessed_reward_set(sortdb.conn(), &prepare_phase_start_id)?
+        else {
+            continue;
+        };
+        if rc_info
+            .selected_anchor_block()
+            .is_some_and(|(_, txid)| *txid == sn.winning_block_txid)
+        {
+            return Ok(TenurePoxAnchorStatus {
+                is_pox_anchor: true,
+                pox_anchor_reward_cycle: Some(anchored_cycle),
+            });
+        }
     }
+    Ok(TenurePoxAnchorStatus::default())
 }
 
 /// Decode the HTTP request
@@ -194,8 +875,71 @@ impl HttpRequest for RPCNakamotoTenureRequestHandler {
                 Error::DecodeError(format!("Failed to parse stop= query parameter: {:?}", &e))
             })?;
 
+        let follow = match req_contents.get_query_arg("follow").map(|s| s.as_str()) {
+            None | Some("false") | Some("0") => false,
+            Some("true") | Some("1") => true,
+            Some(follow) => {
+                return Err(Error::DecodeError(format!(
+                    "Failed to parse follow= query parameter: {}",
+                    follow
+                )));
+            }
+        };
+
+        let archive = match req_contents.get_query_arg("format").map(|s| s.as_str()) {
+            None | Some("raw") => false,
+            Some("archive") => true,
+            Some(format) => {
+                return Err(Error::DecodeError(format!(
+                    "Failed to parse format= query parameter: {}",
+                    format
+                )));
+            }
+        };
+
+        let verify = match req_contents.get_query_arg("verify").map(|s| s.as_str()) {
+            None | Some("none") => false,
+            Some("signatures") => true,
+            Some(verify) => {
+                return Err(Error::DecodeError(format!(
+                    "Failed to parse verify= query parameter: {}",
+                    verify
+                )));
+            }
+        };
+        let max_bytes = req_contents
+            .get_query_arg("max_bytes")
+            .map(|max_bytes| max_bytes.parse::<u64>())
+            .transpose()
+            .map_err(|e| {
+                Error::DecodeError(format!(
+                    "Failed to parse max_bytes= query parameter: {:?}",
+                    &e
+                ))
+            })?
+            .map(|max_bytes| max_bytes.min(MAX_PAYLOAD_LEN.into()));
+
+        if verify && follow {
+            // blocks that haven't arrived yet can't be checked before the stream starts
+            return Err(Error::DecodeError(
+                "verify=signatures can't be combined with follow=true".to_string(),
+            ));
+        }
+
         self.last_block_id = last_block_id;
         self.block_id = Some(block_id);
+        self.follow = follow;
+        self.archive = archive;
+        self.verify = verify;
+        self.max_bytes = max_bytes;
+        #[cfg(feature = "protobuf")]
+        {
+            // an archive holds raw blocks, whatever the caller accepts
+            self.protobuf = !archive
+                && preamble
+                    .get_header("accept".into())
+                    .is_some_and(|accept| accepts_protobuf(&accept));
+        }
 
         Ok(req_contents)
     }
@@ -206,6 +950,11 @@ impl RPCRequestHandler for RPCNakamotoTenureRequestHandler {
     fn restart(&mut self) {
         self.block_id = None;
         self.last_block_id = None;
+        self.protobuf = false;
+        self.follow = false;
+        self.archive = false;
+        self.verify = false;
+        self.max_bytes = None;
     }
 
     /// Make the response
@@ -215,33 +964,88 @@ impl RPCRequestHandler for RPCNakamotoTenureRequestHandler {
         _contents: HttpRequestContents,
         node: &mut StacksNodeState,
     ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let allowed = node.with_node_state(|network, _sortdb, _chainstate, _mempool, _rpc_args| {
+            self.is_caller_allowed(
+                network
+                    .get_connection_opts()
+                    .tenure_stream_allowlist
+                    .as_deref(),
+            )
+        });
+        if !allowed {
+            return StacksHttpResponse::new_error(
+                &preamble,
+                &HttpForbidden::new("Tenure streaming is not allowed from this address\n".into()),
+            )
+            .try_into_contents()
+            .map_err(NetError::from);
+        }
+
         let block_id = self
             .block_id
             .take()
             .ok_or(NetError::SendError("Missing `block_id`".into()))?;
 
-        let stream_res =
-            node.with_node_state(|_network, _sortdb, chainstate, _mempool, _rpc_args| {
-                let Some(header) =
-                    NakamotoChainState::get_block_header_nakamoto(chainstate.db(), &block_id)?
-                else {
-                    return Err(ChainError::NoSuchBlockError);
-                };
-                let Some(nakamoto_header) = header.anchored_header.as_stacks_nakamoto() else {
-                    return Err(ChainError::NoSuchBlockError);
-                };
-                NakamotoTenureStream::new(
-                    chainstate,
-                    block_id,
-                    nakamoto_header.consensus_hash.clone(),
-                    nakamoto_header.parent_block_id.clone(),
-                    self.last_block_id.clone(),
-                )
-            });
+        let stream_res = node.with_node_state(|network, sortdb, chainstate, _mempool, rpc_args| {
+            let Some(header) = NakamotoChainState::get_block_header(chainstate.db(), &block_id)?
+            else {
+                return Err(ChainError::NoSuchBlockError);
+            };
+            let Some(nakamoto_header) = header.anchored_header.as_stacks_nakamoto() else {
+                // the block exists, but it's an epoch2 block, so it has no tenure to stream
+                return Ok(None);
+            };
+            let pox_anchor_status =
+                get_tenure_pox_anchor_status(sortdb, &nakamoto_header.consensus_hash)?;
+            let stream = NakamotoTenureStream::new_with_archive(
+                chainstate,
+                block_id,
+                nakamoto_header.consensus_hash.clone(),
+                nakamoto_header.parent_block_id.clone(),
+                self.last_block_id.clone(),
+                rpc_args.archival_block_source.clone(),
+            )?;
+            #[cfg(feature = "protobuf")]
+            let stream = stream.with_protobuf_framing(self.protobuf);
+            let stream = if self.archive {
+                stream.with_archive_format()
+            } else {
+                stream
+            };
+            let stream = stream.with_db_budget(Duration::from_millis(
+                network.get_connection_opts().tenure_stream_db_budget_ms,
+            ));
+            let stream = match self.max_bytes {
+                Some(max_bytes) => stream.with_max_bytes(max_bytes),
+                None => stream,
+            };
+            if !self.follow {
+                return Ok(Some((stream, pox_anchor_status)));
+            }
+            let hold_secs = TENURE_FOLLOW_MAX_HOLD_SECS.min(network.get_connection_opts().timeout);
+            Ok(Some((
+                stream.with_follow(Duration::from_secs(hold_secs)),
+                pox_anchor_status,
+            )))
+        });
 
         // start loading up the block
-        let stream = match stream_res {
-            Ok(stream) => stream,
+        let (mut stream, pox_anchor_status) = match stream_res {
+            Ok(Some(stream)) => stream,
+            Ok(None) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpError::new(
+                        409,
+                        format!(
+                            "Block {} is an epoch2 block, not a Nakamoto block; fetch it from /v2/blocks/{}\n",
+                            &block_id, &block_id
+                        ),
+                    ),
+                )
+                .try_into_contents()
+                .map_err(NetError::from)
+            }
             Err(ChainError::NoSuchBlockError) => {
                 return StacksHttpResponse::new_error(
                     &preamble,
@@ -260,18 +1064,125 @@ impl RPCRequestHandler for RPCNakamotoTenureRequestHandler {
             }
         };
 
-        let resp_preamble = HttpResponsePreamble::from_http_request_preamble(
-            &preamble,
-            200,
-            "OK",
-            None,
-            HttpContentType::Bytes,
-        );
+        // a tenure whose first block to send is corrupt can't be streamed at all
+        match stream.block_stream.is_block_intact() {
+            Ok(true) => {}
+            Ok(false) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpServerError::new(format!(
+                        "Block {} is corrupt in this node's storage\n",
+                        &block_id
+                    )),
+                )
+                .try_into_contents()
+                .map_err(NetError::from)
+            }
+            Err(e) => {
+                let msg = format!("Failed to check block {}: {:?}\n", &block_id, &e);
+                warn!("{}", &msg);
+                return StacksHttpResponse::new_error(&preamble, &HttpServerError::new(msg))
+                    .try_into_contents()
+                    .map_err(NetError::from);
+            }
+        }
 
-        Ok((
-            resp_preamble,
-            HttpResponseContents::from_stream(Box::new(stream)),
-        ))
+        if self.verify {
+            let verify_res =
+                node.with_node_state(|network, sortdb, chainstate, _mempool, rpc_args| {
+                    verify_tenure_signatures(
+                        network.get_burnchain(),
+                        sortdb,
+                        chainstate,
+                        rpc_args.archival_block_source.as_deref(),
+                        &stream,
+                    )
+                });
+            match verify_res {
+                Ok(None) => {}
+                // don't send the blocks that weren't checked
+                Ok(Some(unchecked_block_id)) => stream.last_block_id = Some(unchecked_block_id),
+                Err(TenureVerifyError::InvalidSignatures(bad_block_id, e)) => {
+                    return StacksHttpResponse::new_error(
+                        &preamble,
+                        &HttpError::new(
+                            409,
+                            format!(
+                                "Block {} failed signer signature verification: {}\n",
+                                &bad_block_id, &e
+                            ),
+                        ),
+                    )
+                    .try_into_contents()
+                    .map_err(NetError::from)
+                }
+                Err(TenureVerifyError::NoRewardSet(reward_cycle, e)) => {
+                    return StacksHttpResponse::new_error(
+                        &preamble,
+                        &HttpServiceUnavailable::new(format!(
+                            "Failed to load the reward set of reward cycle {}: {}\n",
+                            reward_cycle, &e
+                        )),
+                    )
+                    .try_into_contents()
+                    .map_err(NetError::from)
+                }
+                Err(TenureVerifyError::ChainError(e)) => {
+                    let msg = format!("Failed to verify tenure of block {}: {:?}\n", &block_id, &e);
+                    warn!("{}", &msg);
+                    return StacksHttpResponse::new_error(&preamble, &HttpServerError::new(msg))
+                        .try_into_contents()
+                        .map_err(NetError::from);
+                }
+            }
+        }
+
+        let content_type = if self.protobuf {
+            HttpContentType::Protobuf
+        } else {
+            HttpContentType::Bytes
+        };
+        if self.follow {
+            // new blocks may arrive while streaming, so the content can't be cached or ranged
+            let mut resp_preamble = HttpResponsePreamble::from_http_request_preamble(
+                &preamble,
+                200,
+                "OK",
+                None,
+                content_type,
+            );
+            pox_anchor_status.add_headers(&mut resp_preamble);
+            return Ok((
+                resp_preamble,
+                HttpResponseContents::from_stream(Box::new(stream)),
+            ));
+        }
+
+        // a tenure's blocks never change, so the content is determined by the request
+        let last_block_id = self
+            .last_block_id
+            .as_ref()
+            .map(|last_block_id| &last_block_id.0[..])
+            .unwrap_or(&[]);
+        let mut etag_parts: Vec<&[u8]> = vec![b"/v3/tenures", &block_id.0, last_block_id];
+        if self.archive {
+            etag_parts.push(b"archive");
+        } else if self.protobuf {
+            etag_parts.push(b"protobuf");
+        }
+        // a smaller byte budget may end the stream at an earlier block
+        let max_bytes = self.max_bytes.map(u64::to_be_bytes);
+        if let Some(max_bytes) = max_bytes.as_ref() {
+            etag_parts.push(b"max_bytes");
+            etag_parts.push(max_bytes);
+        }
+        let etag = make_etag(&etag_parts);
+        let (mut resp_preamble, body) =
+            make_conditional_response(&preamble, etag, content_type, Box::new(stream))?;
+        // whether the tenure is an anchor is settled after its blocks are, so it's sent even
+        // when the blocks aren't
+        pox_anchor_status.add_headers(&mut resp_preamble);
+        Ok((resp_preamble, body))
     }
 }
 
@@ -284,7 +1195,16 @@ impl HttpResponse for RPCNakamotoTenureRequestHandler {
         preamble: &HttpResponsePreamble,
         body: &[u8],
     ) -> Result<HttpResponsePayload, Error> {
-        let bytes = parse_bytes(preamble, body, MAX_PAYLOAD_LEN.into())?;
+        let bytes = if preamble.content_type == HttpContentType::Protobuf {
+            parse_raw_bytes(
+                preamble,
+                body,
+                MAX_PAYLOAD_LEN.into(),
+                HttpContentType::Protobuf,
+            )?
+        } else {
+            parse_bytes(preamble, body, MAX_PAYLOAD_LEN.into())?
+        };
         Ok(HttpResponsePayload::Bytes(bytes))
     }
 }
@@ -302,25 +1222,60 @@ impl HttpChunkGenerator for NakamotoTenureStream {
         4096
     }
 
+    fn is_waiting(&self) -> bool {
+        self.follow.as_ref().is_some_and(|follow| {
+            follow.following && !follow.done && Instant::now() < follow.deadline
+        })
+    }
+
     fn generate_next_chunk(&mut self) -> Result<Vec<u8>, String> {
+        // none of a corrupt block is sent
+        if self.corrupt_block_id.is_some() {
+            return self.finish_corrupt();
+        }
+
+        let starts_block = self.block_stream.offset == 0;
         let next_block_chunk = self.block_stream.generate_next_chunk()?;
         if next_block_chunk.len() > 0 {
             // have block data to send
-            return Ok(next_block_chunk);
+            return self.frame_chunk(starts_block, next_block_chunk);
         }
 
-        // load up next block
-        let send_more = self.next_block().map_err(|e| {
+        // a truncated stream sends no more blocks
+        if self.truncated {
+            return self.finish_archive();
+        }
+
+        // load up next block -- first the tenure's existing blocks, then any new ones
+        self.lookup_deadline = self.db_budget.map(|db_budget| Instant::now() + db_budget);
+        let following = self.follow.as_ref().is_some_and(|follow| follow.following);
+        let send_more_res = if following {
+            self.next_followed_block()
+        } else {
+            match self.next_block() {
+                Ok(false) if self.follow.is_some() => self.next_followed_block(),
+                res => res,
+            }
+        };
+        let send_more = send_more_res.map_err(|e| {
             let msg = format!("Failed to load next block in this tenure: {:?}", &e);
             warn!("{}", &msg);
             msg
         })?;
 
         if !send_more {
+            if self.corrupt_block_id.is_some() {
+                return self.finish_corrupt();
+            }
+            // an archive ends with its manifest, once there are no more blocks to wait for
+            if !self.is_waiting() {
+                return self.finish_archive();
+            }
             return Ok(vec![]);
         }
 
-        self.block_stream.generate_next_chunk()
+        let next_block_chunk = self.block_stream.generate_next_chunk()?;
+        self.frame_chunk(true, next_block_chunk)
     }
 }
 
@@ -344,6 +1299,79 @@ impl StacksHttpRequest {
         )
         .expect("FATAL: failed to construct request from infallible data")
     }
+
+    /// Make a request for a tenure that, once the tenure's blocks up to `block_id` are sent,
+    /// keeps streaming the blocks that are added to it (`follow=true`)
+    pub fn new_get_nakamoto_tenure_follow(
+        host: PeerHost,
+        block_id: StacksBlockId,
+    ) -> StacksHttpRequest {
+        StacksHttpRequest::new_for_peer(
+            host,
+            "GET".into(),
+            format!("/v3/tenures/{}", &block_id),
+            HttpRequestContents::new().query_arg("follow".into(), "true".into()),
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+
+    /// Make a request for a tenure as a tenure archive (`format=archive`). Decode the response
+    /// with `decode_nakamoto_tenure_archive()`.
+    pub fn new_get_nakamoto_tenure_archive(
+        host: PeerHost,
+        block_id: StacksBlockId,
+        last_block_id: Option<StacksBlockId>,
+    ) -> StacksHttpRequest {
+        let mut contents = HttpRequestContents::new().query_arg("format".into(), "archive".into());
+        if let Some(last_block_id) = last_block_id {
+            contents = contents.query_arg("stop".into(), last_block_id.to_hex());
+        }
+        StacksHttpRequest::new_for_peer(
+            host,
+            "GET".into(),
+            format!("/v3/tenures/{}", &block_id),
+            contents,
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+
+    /// Make a request for a tenure whose blocks' signer signatures are checked by the node
+    /// before it sends them (`verify=signatures`). Decode the response with
+    /// `decode_nakamoto_tenure()`.
+    pub fn new_get_nakamoto_tenure_verified(
+        host: PeerHost,
+        block_id: StacksBlockId,
+        last_block_id: Option<StacksBlockId>,
+    ) -> StacksHttpRequest {
+        let mut contents =
+            HttpRequestContents::new().query_arg("verify".into(), "signatures".into());
+        if let Some(last_block_id) = last_block_id {
+            contents = contents.query_arg("stop".into(), last_block_id.to_hex());
+        }
+        StacksHttpRequest::new_for_peer(
+            host,
+            "GET".into(),
+            format!("/v3/tenures/{}", &block_id),
+            contents,
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+
+    /// Make a request for a tenure whose blocks are framed as length-delimited protobuf
+    /// messages. Decode the response with `decode_nakamoto_tenure_protobuf()`.
+    #[cfg(feature = "protobuf")]
+    pub fn new_get_nakamoto_tenure_protobuf(
+        host: PeerHost,
+        block_id: StacksBlockId,
+        last_block_id: Option<StacksBlockId>,
+    ) -> StacksHttpRequest {
+        let mut request = Self::new_get_nakamoto_tenure(host, block_id, last_block_id);
+        request.add_header(
+            "Accept".into(),
+            HttpContentType::Protobuf.as_str().to_string(),
+        );
+        request
+    }
 }
 
 impl StacksHttpResponse {
@@ -365,4 +1393,107 @@ impl StacksHttpResponse {
 
         Ok(blocks)
     }
+
+    /// Decode an HTTP response into a tenure archive, checking that its blocks match its
+    /// manifest.
+    /// If it fails, return Self::Error(..)
+    pub fn decode_nakamoto_tenure_archive(self) -> Result<TenureArchive, NetError> {
+        let contents = self.get_http_payload_ok()?;
+        let archive_bytes: Vec<u8> = contents.try_into()?;
+        Ok(decode_tenure_archive(&archive_bytes)?)
+    }
+
+    /// Decode an HTTP response into a tenure, whose blocks are framed as length-delimited
+    /// `NakamotoBlockFrame` protobuf messages.
+    /// If it fails, return Self::Error(..)
+    #[cfg(feature = "protobuf")]
+    pub fn decode_nakamoto_tenure_protobuf(self) -> Result<Vec<NakamotoBlock>, NetError> {
+        if self.preamble().content_type != HttpContentType::Protobuf {
+            return Err(NetError::DeserializeError(
+                "Invalid content-type: expected application/x-protobuf".to_string(),
+            ));
+        }
+        let contents = self.get_http_payload_ok()?;
+        let tenure_bytes: Vec<u8> = contents.try_into()?;
+        Ok(decode_block_frames(&tenure_bytes)?)
+    }
+}
+
+/// Options for `fetch_tenure_paged()`
+#[derive(Debug, Clone)]
+pub struct TenurePagingOpts {
+    /// Most requests to make before giving up on the tenure
+    pub max_pages: usize,
+    /// Timeout for each request
+    pub timeout: Duration,
+}
+
+impl Default for TenurePagingOpts {
+    fn default() -> Self {
+        Self {
+            max_pages: DEFAULT_TENURE_MAX_PAGES,
+            timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Fetch the block `block_id` and all of its ancestors in the same tenure from the node at
+/// `host:port`, paging through `/v3/tenures` as many times as the tenure's size requires.
+/// Each page starts at the parent of the deepest block of the page before it, and the last
+/// page is the one that ends with the tenure-start block.
+/// Returns the blocks newest first, as `/v3/tenures` does.
+/// Returns Err(SendRequestError::Protocol(..)) if a page can't be decoded, or if the tenure
+/// isn't complete after `opts.max_pages` requests.
+pub fn fetch_tenure_paged(
+    host: &str,
+    port: u16,
+    block_id: StacksBlockId,
+    opts: &TenurePagingOpts,
+) -> Result<Vec<NakamotoBlock>, SendRequestError> {
+    let mut blocks: Vec<NakamotoBlock> = vec![];
+    let mut next_block_id = block_id;
+    for _ in 0..opts.max_pages {
+        let request = StacksHttpRequest::new_get_nakamoto_tenure(
+            PeerHost::from_host_port(host.to_string(), port),
+            next_block_id.clone(),
+            None,
+        )
+        .with_header("Connection".to_string(), "close".to_string());
+        let page = send_http_request(host, port, request, opts.timeout)?
+            .decode_nakamoto_tenure()
+            .map_err(|e| {
+                SendRequestError::Protocol(format!("Failed to decode tenure page: {:?}", &e))
+            })?;
+
+        let Some(first) = page.first() else {
+            return Err(SendRequestError::Protocol(format!(
+                "No blocks returned for {}",
+                &next_block_id
+            )));
+        };
+        if first.block_id() != next_block_id {
+            return Err(SendRequestError::Protocol(format!(
+                "Tenure page starts at {} instead of {}",
+                &first.block_id(),
+                &next_block_id
+            )));
+        }
+        if let Some(last) = blocks.last() {
+            if first.header.consensus_hash != last.header.consensus_hash {
+                // the previous page ended at the tenure's first block
+                return Ok(blocks);
+            }
+        }
+
+        blocks.extend(page);
+        let deepest = blocks.last().expect("FATAL: pushed a nonempty page");
+        if deepest.is_wellformed_tenure_start_block() == Ok(true) {
+            return Ok(blocks);
+        }
+        next_block_id = deepest.header.parent_block_id.clone();
+    }
+    Err(SendRequestError::Protocol(format!(
+        "Tenure not complete after {} pages",
+        opts.max_pages
+    )))
 }
@@ -0,0 +1,256 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use regex::{Captures, Regex};
+use stacks_common::types::chainstate::{ConsensusHash, StacksBlockId};
+use stacks_common::types::net::PeerHost;
+
+use crate::chainstate::nakamoto::NakamotoChainState;
+use crate::chainstate::stacks::db::StacksChainState;
+use crate::chainstate::stacks::Error as ChainError;
+use crate::net::http::{
+    parse_json, Error, HttpNotFound, HttpRequest, HttpRequestContents, HttpRequestPreamble,
+    HttpResponse, HttpResponseContents, HttpResponsePayload, HttpResponsePreamble, HttpServerError,
+};
+use crate::net::httpcore::{request, RPCRequestHandler, StacksHttpRequest, StacksHttpResponse};
+use crate::net::{Error as NetError, StacksNodeState};
+
+/// Most block IDs that one `/v3/tenures/:block_id/block_ids` response lists
+pub const MAX_TENURE_BLOCK_IDS: u64 = 1000;
+
+/// The IDs of a block and its ancestors in the same tenure
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCTenureBlockIds {
+    /// The tenure's consensus hash
+    pub consensus_hash: ConsensusHash,
+    /// The requested block's ID, then the IDs of its ancestors in the tenure, highest first
+    pub block_ids: Vec<StacksBlockId>,
+    /// If the listed blocks don't reach the tenure's first block, the ID of the block to list
+    /// the rest from
+    pub next_block_id: Option<StacksBlockId>,
+}
+
+#[derive(Clone)]
+pub struct RPCNakamotoTenureBlockIdsRequestHandler {
+    /// Highest block in the tenure to list
+    pub block_id: Option<StacksBlockId>,
+    /// Most block IDs to list
+    pub limit: u64,
+}
+
+impl RPCNakamotoTenureBlockIdsRequestHandler {
+    pub fn new() -> Self {
+        Self {
+            block_id: None,
+            limit: MAX_TENURE_BLOCK_IDS,
+        }
+    }
+}
+
+/// List the IDs of `block_id` and of its ancestors in the same tenure, highest first, by
+/// walking the blocks' headers back to the tenure's first block.  At most `limit` IDs are
+/// listed, along with the ID of the next block to list if there are more.
+/// Fails with ChainError::NoSuchBlockError if `block_id` is not a Nakamoto block.
+pub fn load_tenure_block_ids(
+    chainstate: &StacksChainState,
+    block_id: &StacksBlockId,
+    limit: u64,
+) -> Result<RPCTenureBlockIds, ChainError> {
+    let header = NakamotoChainState::get_block_header_nakamoto(chainstate.db(), block_id)?
+        .ok_or(ChainError::NoSuchBlockError)?;
+    let header = header
+        .anchored_header
+        .as_stacks_nakamoto()
+        .ok_or(ChainError::NoSuchBlockError)?
+        .clone();
+    let consensus_hash = header.consensus_hash.clone();
+
+    let mut block_ids = vec![];
+    let mut next_block_id = None;
+    let mut cursor = Some(header);
+    while let Some(header) = cursor.take() {
+        if u64::try_from(block_ids.len()).unwrap_or(u64::MAX) >= limit {
+            next_block_id = Some(header.block_id());
+            break;
+        }
+        block_ids.push(header.block_id());
+
+        let Some(parent_header) = NakamotoChainState::get_block_header_nakamoto(
+            chainstate.db(),
+            &header.parent_block_id,
+        )?
+        else {
+            // parent is an epoch2 block
+            break;
+        };
+        // stop once the parent is in a different tenure
+        cursor = parent_header
+            .anchored_header
+            .as_stacks_nakamoto()
+            .filter(|parent| parent.consensus_hash == consensus_hash)
+            .cloned();
+    }
+    Ok(RPCTenureBlockIds {
+        consensus_hash,
+        block_ids,
+        next_block_id,
+    })
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCNakamotoTenureBlockIdsRequestHandler {
+    fn verb(&self) -> &'static str {
+        "GET"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(r#"^/v3/tenures/(?P<block_id>[0-9a-f]{64})/block_ids$"#).unwrap()
+    }
+
+    fn metrics_identifier(&self) -> &str {
+        "/v3/tenures/:block_id/block_ids"
+    }
+
+    /// Try to decode this request.
+    /// There's nothing to load here, so just make sure the request is well-formed.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        if preamble.get_content_length() != 0 {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+        let block_id = request::get_block_hash(captures, "block_id")?;
+        let req_contents = HttpRequestContents::new().query_string(query);
+        let limit = req_contents
+            .get_query_arg("limit")
+            .map(|limit| limit.parse::<u64>())
+            .transpose()
+            .map_err(|e| {
+                Error::DecodeError(format!("Failed to parse limit= query parameter: {:?}", &e))
+            })?
+            .unwrap_or(MAX_TENURE_BLOCK_IDS);
+        if limit == 0 {
+            return Err(Error::DecodeError(
+                "limit= query parameter must be at least 1".to_string(),
+            ));
+        }
+
+        self.block_id = Some(block_id);
+        self.limit = limit.min(MAX_TENURE_BLOCK_IDS);
+        Ok(req_contents)
+    }
+}
+
+impl RPCRequestHandler for RPCNakamotoTenureBlockIdsRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {
+        self.block_id = None;
+        self.limit = MAX_TENURE_BLOCK_IDS;
+    }
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        _contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let block_id = self
+            .block_id
+            .take()
+            .ok_or(NetError::SendError("Missing `block_id`".into()))?;
+
+        let block_ids_res =
+            node.with_node_state(|_network, _sortdb, chainstate, _mempool, _rpc_args| {
+                load_tenure_block_ids(chainstate, &block_id, self.limit)
+            });
+
+        let block_ids = match block_ids_res {
+            Ok(block_ids) => block_ids,
+            Err(ChainError::NoSuchBlockError) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpNotFound::new(format!("No such Nakamoto block {}\n", &block_id)),
+                )
+                .try_into_contents()
+                .map_err(NetError::from);
+            }
+            Err(e) => {
+                // nope -- error trying to check
+                let msg = format!("Failed to load tenure of block {}: {:?}\n", &block_id, &e);
+                warn!("{}", &msg);
+                return StacksHttpResponse::new_error(&preamble, &HttpServerError::new(msg))
+                    .try_into_contents()
+                    .map_err(NetError::from);
+            }
+        };
+
+        let preamble = HttpResponsePreamble::ok_json(&preamble);
+        let body = HttpResponseContents::try_from_json(&block_ids)?;
+        Ok((preamble, body))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCNakamotoTenureBlockIdsRequestHandler {
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let block_ids: RPCTenureBlockIds = parse_json(preamble, body)?;
+        Ok(HttpResponsePayload::try_from_json(block_ids)?)
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a new request for the IDs of a block and its ancestors in the same tenure
+    pub fn new_get_nakamoto_tenure_block_ids(
+        host: PeerHost,
+        block_id: &StacksBlockId,
+        limit: Option<u64>,
+    ) -> StacksHttpRequest {
+        let contents = match limit {
+            Some(limit) => {
+                HttpRequestContents::new().query_arg("limit".into(), format!("{}", limit))
+            }
+            None => HttpRequestContents::new(),
+        };
+        StacksHttpRequest::new_for_peer(
+            host,
+            "GET".into(),
+            format!("/v3/tenures/{}/block_ids", block_id),
+            contents,
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    pub fn decode_nakamoto_tenure_block_ids(self) -> Result<RPCTenureBlockIds, NetError> {
+        let contents = self.get_http_payload_ok()?;
+        let response_json: serde_json::Value = contents.try_into()?;
+        let block_ids: RPCTenureBlockIds = serde_json::from_value(response_json)
+            .map_err(|_e| Error::DecodeError("Failed to decode JSON".to_string()))?;
+        Ok(block_ids)
+    }
+}
@@ -0,0 +1,259 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use regex::{Captures, Regex};
+use stacks_common::codec::{StacksMessageCodec, MAX_PAYLOAD_LEN};
+use stacks_common::types::chainstate::{ConsensusHash, StacksBlockId};
+use stacks_common::types::net::PeerHost;
+
+use crate::chainstate::nakamoto::NakamotoChainState;
+use crate::chainstate::stacks::{StacksTransaction, TransactionPayload};
+use crate::net::http::{
+    parse_bytes, Error, HttpContentType, HttpNotFound, HttpRequest, HttpRequestContents,
+    HttpRequestPreamble, HttpResponse, HttpResponseContents, HttpResponsePayload,
+    HttpResponsePreamble, HttpServerError,
+};
+use crate::net::httpcore::{request, RPCRequestHandler, StacksHttpRequest, StacksHttpResponse};
+use crate::net::{Error as NetError, StacksNodeState};
+
+/// How a tenure is identified in a `/v3/tenures/tenure_change` request
+#[derive(Debug, Clone, PartialEq)]
+pub enum TenureChangeTenureId {
+    /// The tenure's consensus hash. The tenure is looked up on the canonical fork.
+    ConsensusHash(ConsensusHash),
+    /// Any block in the tenure
+    BlockId(StacksBlockId),
+}
+
+#[derive(Clone)]
+pub struct RPCNakamotoTenureChangeRequestHandler {
+    pub(crate) tenure_id: Option<TenureChangeTenureId>,
+}
+
+impl RPCNakamotoTenureChangeRequestHandler {
+    pub fn new() -> Self {
+        Self { tenure_id: None }
+    }
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCNakamotoTenureChangeRequestHandler {
+    fn verb(&self) -> &'static str {
+        "GET"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(r#"^/v3/tenures/tenure_change/(?P<tenure_id>[0-9a-f]{40}|[0-9a-f]{64})$"#)
+            .unwrap()
+    }
+
+    fn metrics_identifier(&self) -> &str {
+        "/v3/tenures/tenure_change/:tenure_id"
+    }
+
+    /// Try to decode this request.
+    /// There's nothing to load here, so just make sure the request is well-formed.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        if preamble.get_content_length() != 0 {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+        let tenure_id = match captures.name("tenure_id").map(|m| m.as_str().len()) {
+            Some(40) => TenureChangeTenureId::ConsensusHash(request::get_consensus_hash(
+                captures,
+                "tenure_id",
+            )?),
+            _ => TenureChangeTenureId::BlockId(request::get_block_hash(captures, "tenure_id")?),
+        };
+        self.tenure_id = Some(tenure_id);
+        Ok(HttpRequestContents::new().query_string(query))
+    }
+}
+
+impl RPCRequestHandler for RPCNakamotoTenureChangeRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {
+        self.tenure_id = None;
+    }
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        _contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let tenure_id = self
+            .tenure_id
+            .take()
+            .ok_or(NetError::SendError("`tenure_id` not set".into()))?;
+
+        let tx_resp = node.with_node_state(|network, _sortdb, chainstate, _mempool, _rpc_args| {
+            let not_found = |msg: String| {
+                debug!("{}", &msg);
+                StacksHttpResponse::new_error(&preamble, &HttpNotFound::new(msg))
+            };
+            let server_error = |msg: String| {
+                error!("{}", &msg);
+                StacksHttpResponse::new_error(&preamble, &HttpServerError::new(msg))
+            };
+
+            // find the tenure, and a tip from which to find its first block
+            let (consensus_hash, tip_block_id) = match &tenure_id {
+                TenureChangeTenureId::ConsensusHash(consensus_hash) => {
+                    (consensus_hash.clone(), network.stacks_tip.block_id())
+                }
+                TenureChangeTenureId::BlockId(block_id) => {
+                    match NakamotoChainState::get_block_header_nakamoto_tenure_id(
+                        chainstate.db(),
+                        block_id,
+                    ) {
+                        Ok(Some(consensus_hash)) => (consensus_hash, block_id.clone()),
+                        Ok(None) => {
+                            return Err(not_found(format!("No such Nakamoto block {}", block_id)))
+                        }
+                        Err(e) => {
+                            return Err(server_error(format!(
+                                "Failed to load Nakamoto block {}: {:?}",
+                                block_id, &e
+                            )))
+                        }
+                    }
+                }
+            };
+
+            let tenure_start_header =
+                match NakamotoChainState::get_nakamoto_tenure_start_block_header(
+                    &mut chainstate.index_conn(),
+                    &tip_block_id,
+                    &consensus_hash,
+                ) {
+                    Ok(Some(header)) => header,
+                    Ok(None) => {
+                        return Err(not_found(format!(
+                            "No start block for tenure {} from {}",
+                            &consensus_hash, &tip_block_id
+                        )))
+                    }
+                    Err(e) => {
+                        return Err(server_error(format!(
+                            "Failed to query start block of tenure {}: {:?}",
+                            &consensus_hash, &e
+                        )))
+                    }
+                };
+
+            let tenure_start_block_id = tenure_start_header.index_block_hash();
+            let block = match chainstate
+                .nakamoto_blocks_db()
+                .get_nakamoto_block(&tenure_start_block_id)
+            {
+                Ok(Some((block, _size))) => block,
+                Ok(None) => {
+                    return Err(not_found(format!(
+                        "No such Nakamoto block {}",
+                        &tenure_start_block_id
+                    )))
+                }
+                Err(e) => {
+                    return Err(server_error(format!(
+                        "Failed to load Nakamoto block {}: {:?}",
+                        &tenure_start_block_id, &e
+                    )))
+                }
+            };
+
+            // if present, the tenure-change is the first transaction
+            let Some(tenure_change_tx) = block
+                .txs
+                .first()
+                .filter(|tx| matches!(tx.payload, TransactionPayload::TenureChange(..)))
+            else {
+                return Err(not_found(format!(
+                    "No tenure-change transaction in block {}",
+                    &tenure_start_block_id
+                )));
+            };
+            Ok(tenure_change_tx.serialize_to_vec())
+        });
+
+        let tx_bytes = match tx_resp {
+            Ok(tx_bytes) => tx_bytes,
+            Err(response) => {
+                return response.try_into_contents().map_err(NetError::from);
+            }
+        };
+
+        let content_length = u32::try_from(tx_bytes.len())
+            .map_err(|_| NetError::SendError("Transaction is too long".into()))?;
+        let preamble = HttpResponsePreamble::from_http_request_preamble(
+            &preamble,
+            200,
+            "OK",
+            Some(content_length),
+            HttpContentType::Bytes,
+        );
+        Ok((preamble, HttpResponseContents::from_ram(tx_bytes)))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCNakamotoTenureChangeRequestHandler {
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let bytes = parse_bytes(preamble, body, MAX_PAYLOAD_LEN.into())?;
+        Ok(HttpResponsePayload::Bytes(bytes))
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a new request for the tenure-change transaction of a tenure
+    pub fn new_get_tenure_change_tx(
+        host: PeerHost,
+        tenure_id: &TenureChangeTenureId,
+    ) -> StacksHttpRequest {
+        let tenure_id = match tenure_id {
+            TenureChangeTenureId::ConsensusHash(consensus_hash) => consensus_hash.to_string(),
+            TenureChangeTenureId::BlockId(block_id) => block_id.to_string(),
+        };
+        StacksHttpRequest::new_for_peer(
+            host,
+            "GET".into(),
+            format!("/v3/tenures/tenure_change/{}", &tenure_id),
+            HttpRequestContents::new(),
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    /// Decode an HTTP response into a tenure-change transaction
+    pub fn decode_tenure_change_tx(self) -> Result<StacksTransaction, NetError> {
+        let contents = self.get_http_payload_ok()?;
+        let tx_bytes: Vec<u8> = contents.try_into()?;
+        let tx = StacksTransaction::consensus_deserialize(&mut &tx_bytes[..])?;
+        Ok(tx)
+    }
+}
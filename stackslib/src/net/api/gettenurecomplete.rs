@@ -0,0 +1,216 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use regex::{Captures, Regex};
+use stacks_common::types::chainstate::{ConsensusHash, StacksBlockId};
+use stacks_common::types::net::PeerHost;
+use {serde, serde_json};
+
+use crate::chainstate::nakamoto::NakamotoChainState;
+use crate::chainstate::stacks::db::StacksBlockHeaderTypes;
+use crate::chainstate::stacks::Error as ChainError;
+use crate::net::http::{
+    parse_json, Error, HttpNotFound, HttpRequest, HttpRequestContents, HttpRequestPreamble,
+    HttpResponse, HttpResponseContents, HttpResponsePayload, HttpResponsePreamble, HttpServerError,
+};
+use crate::net::httpcore::{
+    HttpRequestContentsExtensions, RPCRequestHandler, StacksHttp, StacksHttpRequest,
+    StacksHttpResponse,
+};
+use crate::net::{Error as NetError, StacksNodeState};
+
+#[derive(Clone)]
+pub struct RPCNakamotoHighestCompleteTenureRequestHandler {}
+
+impl RPCNakamotoHighestCompleteTenureRequestHandler {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+/// The highest tenure whose blocks this node has all processed, and can serve.
+/// A Nakamoto tenure is complete once the next tenure has started on top of it, since only
+/// then is its last block known. The tenure in progress at the node's tip is never complete.
+/// In epoch 2.x, each block is its own tenure, so the tip's tenure is complete.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct RPCHighestCompleteTenure {
+    /// The consensus hash of the highest complete tenure
+    pub consensus_hash: ConsensusHash,
+    /// The block ID of the first block in that tenure
+    pub tenure_start_block_id: StacksBlockId,
+    /// The block ID of the last block in that tenure
+    pub last_block_id: StacksBlockId,
+    /// The height of the last block in that tenure
+    pub last_block_height: u64,
+    /// Whether that tenure is a Nakamoto tenure
+    pub is_nakamoto: bool,
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCNakamotoHighestCompleteTenureRequestHandler {
+    fn verb(&self) -> &'static str {
+        "GET"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(r#"^/v3/tenures/complete$"#).unwrap()
+    }
+
+    fn metrics_identifier(&self) -> &str {
+        "/v3/tenures/complete"
+    }
+
+    /// Try to decode this request.
+    /// There's nothing to load here, so just make sure the request is well-formed.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        _body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        if preamble.get_content_length() != 0 {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+        Ok(HttpRequestContents::new().query_string(query))
+    }
+}
+
+impl RPCRequestHandler for RPCNakamotoHighestCompleteTenureRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {}
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        _contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let tenure_resp: Result<Option<RPCHighestCompleteTenure>, ChainError> = node
+            .with_node_state(|network, _sortdb, chainstate, _mempool, _rpc_args| {
+                let tip_block_id = StacksBlockId::new(
+                    &network.stacks_tip.consensus_hash,
+                    &network.stacks_tip.block_hash,
+                );
+                if !network.stacks_tip.is_nakamoto {
+                    return Ok(Some(RPCHighestCompleteTenure {
+                        consensus_hash: network.stacks_tip.consensus_hash.clone(),
+                        tenure_start_block_id: tip_block_id.clone(),
+                        last_block_id: tip_block_id,
+                        last_block_height: network.stacks_tip.height,
+                        is_nakamoto: false,
+                    }));
+                }
+
+                // the ongoing tenure's first block builds on the last block of the tenure
+                // before it
+                let Some(tenure_start_header) = NakamotoChainState::get_block_header(
+                    chainstate.db(),
+                    &network.tenure_start_block_id,
+                )?
+                else {
+                    return Ok(None);
+                };
+                let StacksBlockHeaderTypes::Nakamoto(tenure_start_header) =
+                    tenure_start_header.anchored_header
+                else {
+                    return Ok(None);
+                };
+                let last_block_id = tenure_start_header.parent_block_id;
+                let Some(last_header) =
+                    NakamotoChainState::get_block_header(chainstate.db(), &last_block_id)?
+                else {
+                    return Ok(None);
+                };
+                let Some(first_header) = NakamotoChainState::get_tenure_start_block_header(
+                    &mut chainstate.index_conn(),
+                    &last_block_id,
+                    &last_header.consensus_hash,
+                )?
+                else {
+                    return Ok(None);
+                };
+                Ok(Some(RPCHighestCompleteTenure {
+                    consensus_hash: last_header.consensus_hash.clone(),
+                    tenure_start_block_id: first_header.index_block_hash(),
+                    last_block_id,
+                    last_block_height: last_header.stacks_block_height,
+                    is_nakamoto: last_header.anchored_header.as_stacks_nakamoto().is_some(),
+                }))
+            });
+
+        let tenure = match tenure_resp {
+            Ok(Some(tenure)) => tenure,
+            Ok(None) => {
+                let msg = "No complete tenure is available\n".to_string();
+                debug!("{}", &msg);
+                return StacksHttpResponse::new_error(&preamble, &HttpNotFound::new(msg))
+                    .try_into_contents()
+                    .map_err(NetError::from);
+            }
+            Err(e) => {
+                let msg = format!("Failed to query highest complete tenure: {:?}\n", &e);
+                error!("{}", &msg);
+                return StacksHttpResponse::new_error(&preamble, &HttpServerError::new(msg))
+                    .try_into_contents()
+                    .map_err(NetError::from);
+            }
+        };
+
+        let preamble = HttpResponsePreamble::ok_json(&preamble);
+        let body = HttpResponseContents::try_from_json(&tenure)?;
+        Ok((preamble, body))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCNakamotoHighestCompleteTenureRequestHandler {
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let tenure: RPCHighestCompleteTenure = parse_json(preamble, body)?;
+        Ok(HttpResponsePayload::try_from_json(tenure)?)
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a new request for the highest complete tenure
+    pub fn new_get_nakamoto_highest_complete_tenure(host: PeerHost) -> StacksHttpRequest {
+        StacksHttpRequest::new_for_peer(
+            host,
+            "GET".into(),
+            "/v3/tenures/complete".into(),
+            HttpRequestContents::new(),
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    pub fn decode_nakamoto_highest_complete_tenure(
+        self,
+    ) -> Result<RPCHighestCompleteTenure, NetError> {
+        let contents = self.get_http_payload_ok()?;
+        let response_json: serde_json::Value = contents.try_into()?;
+        let tenure: RPCHighestCompleteTenure = serde_json::from_value(response_json)
+            .map_err(|_e| Error::DecodeError("Failed to decode JSON".to_string()))?;
+        Ok(tenure)
+    }
+}
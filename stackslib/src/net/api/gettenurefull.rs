@@ -0,0 +1,328 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::VecDeque;
+
+use regex::{Captures, Regex};
+use stacks_common::codec::{StacksMessageCodec, MAX_PAYLOAD_LEN};
+use stacks_common::types::chainstate::StacksBlockId;
+use stacks_common::types::net::PeerHost;
+
+use crate::chainstate::nakamoto::{NakamotoBlock, NakamotoChainState};
+use crate::chainstate::stacks::db::StacksChainState;
+use crate::chainstate::stacks::Error as ChainError;
+use crate::net::api::getblock_v3::NakamotoBlockStream;
+use crate::net::http::{
+    parse_bytes, Error, HttpChunkGenerator, HttpContentType, HttpNotFound, HttpRequest,
+    HttpRequestContents, HttpRequestPreamble, HttpResponse, HttpResponseContents,
+    HttpResponsePayload, HttpResponsePreamble, HttpServerError,
+};
+use crate::net::httpcore::{request, RPCRequestHandler, StacksHttpRequest, StacksHttpResponse};
+use crate::net::{Error as NetError, StacksNodeState};
+
+#[derive(Clone)]
+pub struct RPCNakamotoFullTenureRequestHandler {
+    /// Any block in the tenure to stream
+    pub block_id: Option<StacksBlockId>,
+}
+
+impl RPCNakamotoFullTenureRequestHandler {
+    pub fn new() -> Self {
+        Self { block_id: None }
+    }
+}
+
+/// Stream of the Nakamoto blocks in a tenure, from the tenure's first block up to (and
+/// including) the highest known block in the tenure that descends from a given block.
+/// Unlike `NakamotoTenureStream`, this streams blocks oldest first.
+pub struct NakamotoFullTenureStream {
+    /// stream for the current block
+    pub block_stream: NakamotoBlockStream,
+    /// (block ID, parent block ID) of each block left to send after the current one, in the
+    /// order they will be sent
+    pub remaining: VecDeque<(StacksBlockId, StacksBlockId)>,
+}
+
+impl NakamotoFullTenureStream {
+    /// Resolve the tenure containing `block_id` into a stream of all of its blocks.
+    /// The blocks are found by walking back from the tenure's highest known descendant of
+    /// `block_id` to the tenure's first block.  If they don't all fit into MAX_PAYLOAD_LEN bytes,
+    /// then only the first blocks that do fit are streamed (but at least one block).
+    /// Fails with ChainError::NoSuchBlockError if `block_id` is not a Nakamoto block.
+    pub fn new(
+        chainstate: &StacksChainState,
+        block_id: &StacksBlockId,
+    ) -> Result<Self, ChainError> {
+        let tip_header = NakamotoChainState::get_highest_known_descendant_in_tenure(
+            &mut chainstate.index_conn(),
+            block_id,
+        )?
+        .ok_or(ChainError::NoSuchBlockError)?;
+        let tip_header = tip_header
+            .anchored_header
+            .as_stacks_nakamoto()
+            .ok_or(ChainError::NoSuchBlockError)?
+            .clone();
+
+        let staging_db_path = chainstate.get_nakamoto_staging_blocks_path()?;
+        let staging_db_conn =
+            StacksChainState::open_nakamoto_staging_blocks(&staging_db_path, false)?;
+
+        // walk back from the tenure tip to the tenure's first block
+        let mut blocks = vec![];
+        let mut cursor = tip_header;
+        loop {
+            let size = staging_db_conn
+                .conn()
+                .get_nakamoto_block_size(&cursor.block_id())?
+                .ok_or(ChainError::NoSuchBlockError)?;
+            blocks.push((cursor.block_id(), cursor.parent_block_id.clone(), size));
+
+            let Some(parent_header) = NakamotoChainState::get_block_header_nakamoto(
+                chainstate.db(),
+                &cursor.parent_block_id,
+            )?
+            else {
+                // parent is an epoch2 block
+                break;
+            };
+            let Some(parent_nakamoto_header) = parent_header.anchored_header.as_stacks_nakamoto()
+            else {
+                break;
+            };
+            if parent_nakamoto_header.consensus_hash != cursor.consensus_hash {
+                // parent is in a different tenure
+                break;
+            }
+            cursor = parent_nakamoto_header.clone();
+        }
+        blocks.reverse();
+
+        // only send as many blocks as fit
+        let mut total_size: u64 = 0;
+        let mut remaining = VecDeque::new();
+        for (block_id, parent_block_id, size) in blocks.into_iter() {
+            total_size = total_size.saturating_add(size);
+            if !remaining.is_empty() && total_size > MAX_PAYLOAD_LEN.into() {
+                break;
+            }
+            remaining.push_back((block_id, parent_block_id));
+        }
+
+        let (first_block_id, first_parent_block_id) = remaining
+            .pop_front()
+            .expect("FATAL: tenure stream has no blocks");
+        let block_stream = NakamotoBlockStream::new(
+            chainstate,
+            first_block_id,
+            cursor.consensus_hash,
+            first_parent_block_id,
+        )?;
+        Ok(Self {
+            block_stream,
+            remaining,
+        })
+    }
+
+    /// Start streaming the next block (i.e. the child of the block we last streamed).
+    /// Return Ok(true) if there is such a block to send.
+    /// Return Ok(false) if we have sent every block.
+    /// Return Err(..) on DB error
+    pub fn next_block(&mut self) -> Result<bool, ChainError> {
+        let Some((block_id, parent_block_id)) = self.remaining.pop_front() else {
+            return Ok(false);
+        };
+        self.block_stream.reset(block_id, parent_block_id)?;
+        Ok(true)
+    }
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCNakamotoFullTenureRequestHandler {
+    fn verb(&self) -> &'static str {
+        "GET"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(r#"^/v3/tenures/(?P<block_id>[0-9a-f]{64})/full$"#).unwrap()
+    }
+
+    fn metrics_identifier(&self) -> &str {
+        "/v3/tenures/:block_id/full"
+    }
+
+    /// Try to decode this request.
+    /// There's nothing to load here, so just make sure the request is well-formed.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        if preamble.get_content_length() != 0 {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+        self.block_id = Some(request::get_block_hash(captures, "block_id")?);
+        Ok(HttpRequestContents::new().query_string(query))
+    }
+}
+
+impl RPCRequestHandler for RPCNakamotoFullTenureRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {
+        self.block_id = None;
+    }
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        _contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let block_id = self
+            .block_id
+            .take()
+            .ok_or(NetError::SendError("Missing `block_id`".into()))?;
+
+        let stream_res =
+            node.with_node_state(|_network, _sortdb, chainstate, _mempool, _rpc_args| {
+                NakamotoFullTenureStream::new(chainstate, &block_id)
+            });
+
+        let stream = match stream_res {
+            Ok(stream) => stream,
+            Err(ChainError::NoSuchBlockError) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpNotFound::new(format!("No such Nakamoto block {}\n", &block_id)),
+                )
+                .try_into_contents()
+                .map_err(NetError::from);
+            }
+            Err(e) => {
+                // nope -- error trying to check
+                let msg = format!("Failed to load tenure of block {}: {:?}\n", &block_id, &e);
+                warn!("{}", &msg);
+                return StacksHttpResponse::new_error(&preamble, &HttpServerError::new(msg))
+                    .try_into_contents()
+                    .map_err(NetError::from);
+            }
+        };
+
+        // the tenure can grow, so this response is not cacheable
+        let resp_preamble = HttpResponsePreamble::from_http_request_preamble(
+            &preamble,
+            200,
+            "OK",
+            None,
+            HttpContentType::Bytes,
+        );
+        Ok((
+            resp_preamble,
+            HttpResponseContents::from_stream(Box::new(stream)),
+        ))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCNakamotoFullTenureRequestHandler {
+    /// Decode this response from a byte stream.  This is called by the client to decode this
+    /// message
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let bytes = parse_bytes(preamble, body, MAX_PAYLOAD_LEN.into())?;
+        Ok(HttpResponsePayload::Bytes(bytes))
+    }
+}
+
+/// Stream implementation for a whole Nakamoto tenure
+impl HttpChunkGenerator for NakamotoFullTenureStream {
+    #[cfg(test)]
+    fn hint_chunk_size(&self) -> usize {
+        // make this hurt
+        32
+    }
+
+    #[cfg(not(test))]
+    fn hint_chunk_size(&self) -> usize {
+        4096
+    }
+
+    fn generate_next_chunk(&mut self) -> Result<Vec<u8>, String> {
+        let next_block_chunk = self.block_stream.generate_next_chunk()?;
+        if next_block_chunk.len() > 0 {
+            // have block data to send
+            return Ok(next_block_chunk);
+        }
+
+        // load up next block
+        let send_more = self.next_block().map_err(|e| {
+            let msg = format!("Failed to load next block in this tenure: {:?}", &e);
+            warn!("{}", &msg);
+            msg
+        })?;
+
+        if !send_more {
+            return Ok(vec![]);
+        }
+
+        self.block_stream.generate_next_chunk()
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a new request for the whole tenure containing a block
+    pub fn new_get_nakamoto_full_tenure(
+        host: PeerHost,
+        block_id: &StacksBlockId,
+    ) -> StacksHttpRequest {
+        StacksHttpRequest::new_for_peer(
+            host,
+            "GET".into(),
+            format!("/v3/tenures/{}/full", block_id),
+            HttpRequestContents::new(),
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    /// Decode an HTTP response into a tenure, first block first.
+    /// The bytes are a concatenation of Nakamoto blocks, with no length prefix.
+    /// If it fails, return Self::Error(..)
+    pub fn decode_nakamoto_full_tenure(self) -> Result<Vec<NakamotoBlock>, NetError> {
+        let contents = self.get_http_payload_ok()?;
+
+        // contents will be raw bytes
+        let tenure_bytes: Vec<u8> = contents.try_into()?;
+        let ptr = &mut tenure_bytes.as_slice();
+
+        let mut blocks = vec![];
+        while ptr.len() > 0 {
+            let block = NakamotoBlock::consensus_deserialize(ptr)?;
+            blocks.push(block);
+        }
+
+        Ok(blocks)
+    }
+}
@@ -29,6 +29,7 @@ use crate::chainstate::nakamoto::{NakamotoBlock, NakamotoChainState, NakamotoSta
 use crate::chainstate::stacks::db::StacksChainState;
 use crate::chainstate::stacks::Error as ChainError;
 use crate::net::api::getblock_v3::NakamotoBlockStream;
+use crate::net::api::gettenure::get_tenure_pox_anchor_status;
 use crate::net::http::{
     parse_bytes, parse_json, Error, HttpBadRequest, HttpChunkGenerator, HttpContentType,
     HttpNotFound, HttpRequest, HttpRequestContents, HttpRequestPreamble, HttpResponse,
@@ -51,8 +52,8 @@ impl RPCNakamotoTenureInfoRequestHandler {
 }
 
 /// The view of this node's current tenure.
-/// All of this information can be found from the PeerNetwork struct, so loading this up should
-/// incur zero disk I/O.
+/// All of this information but the tenure's PoX anchor status can be found from the PeerNetwork
+/// struct, so loading this up only reads the sortition DB.
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct RPCGetTenureInfo {
     /// The highest known consensus hash (identifies the current tenure)
@@ -69,6 +70,12 @@ pub struct RPCGetTenureInfo {
     pub tip_height: u64,
     /// Which reward cycle we're in
     pub reward_cycle: u64,
+    /// Whether the current tenure's sortition selected a PoX anchor block
+    #[serde(default)]
+    pub is_pox_anchor: bool,
+    /// The reward cycle that the current tenure is the PoX anchor of, if it is one
+    #[serde(default)]
+    pub pox_anchor_reward_cycle: Option<u64>,
 }
 
 /// Decode the HTTP request
@@ -114,8 +121,10 @@ impl RPCRequestHandler for RPCNakamotoTenureInfoRequestHandler {
         _contents: HttpRequestContents,
         node: &mut StacksNodeState,
     ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
-        let info = node.with_node_state(|network, _sortdb, _chainstate, _mempool, _rpc_args| {
-            RPCGetTenureInfo {
+        let info_res = node.with_node_state(|network, sortdb, _chainstate, _mempool, _rpc_args| {
+            let pox_anchor_status =
+                get_tenure_pox_anchor_status(sortdb, &network.stacks_tip.consensus_hash)?;
+            Ok::<_, DBError>(RPCGetTenureInfo {
                 consensus_hash: network.stacks_tip.consensus_hash.clone(),
                 tenure_start_block_id: network.tenure_start_block_id.clone(),
                 parent_consensus_hash: network.parent_stacks_tip.consensus_hash.clone(),
@@ -132,9 +141,25 @@ impl RPCRequestHandler for RPCNakamotoTenureInfoRequestHandler {
                     .burnchain
                     .block_height_to_reward_cycle(network.burnchain_tip.block_height)
                     .expect("FATAL: burnchain tip before system start"),
-            }
+                is_pox_anchor: pox_anchor_status.is_pox_anchor,
+                pox_anchor_reward_cycle: pox_anchor_status.pox_anchor_reward_cycle,
+            })
         });
 
+        let info = match info_res {
+            Ok(info) => info,
+            Err(e) => {
+                let msg = format!(
+                    "Failed to load PoX anchor status of current tenure: {:?}\n",
+                    &e
+                );
+                warn!("{}", &msg);
+                return StacksHttpResponse::new_error(&preamble, &HttpServerError::new(msg))
+                    .try_into_contents()
+                    .map_err(NetError::from);
+            }
+        };
+
         let preamble = HttpResponsePreamble::ok_json(&preamble);
         let body = HttpResponseContents::try_from_json(&info)?;
         Ok((preamble, body))
@@ -0,0 +1,241 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use regex::{Captures, Regex};
+use stacks_common::codec::MAX_PAYLOAD_LEN;
+use stacks_common::types::chainstate::ConsensusHash;
+use stacks_common::types::net::PeerHost;
+
+use crate::chainstate::nakamoto::{NakamotoBlock, NakamotoChainState};
+use crate::chainstate::stacks::Error as ChainError;
+use crate::net::api::gettenure::{is_tenure_stream_caller_allowed, NakamotoTenureStream};
+use crate::net::http::{
+    parse_bytes, Error, HttpContentType, HttpError, HttpForbidden, HttpNotFound, HttpRequest,
+    HttpRequestContents, HttpRequestPreamble, HttpResponse, HttpResponseContents,
+    HttpResponsePayload, HttpResponsePreamble, HttpServerError,
+};
+use crate::net::httpcore::{request, RPCRequestHandler, StacksHttpRequest, StacksHttpResponse};
+use crate::net::{Error as NetError, StacksNodeState};
+
+#[derive(Clone)]
+pub struct RPCNakamotoTenureTipBlocksRequestHandler {
+    /// Tenure to stream, from its highest known block down to its first block
+    pub consensus_hash: Option<ConsensusHash>,
+    /// Address of the caller, checked against the node's `tenure_stream_allowlist`
+    pub peer_addr: Option<SocketAddr>,
+}
+
+impl RPCNakamotoTenureTipBlocksRequestHandler {
+    pub fn new() -> Self {
+        Self {
+            consensus_hash: None,
+            peer_addr: None,
+        }
+    }
+
+    /// Serve the caller at `peer_addr`
+    pub fn with_peer_addr(mut self, peer_addr: SocketAddr) -> Self {
+        self.peer_addr = Some(peer_addr);
+        self
+    }
+}
+
+/// Decode the HTTP request
+impl HttpRequest for RPCNakamotoTenureTipBlocksRequestHandler {
+    fn verb(&self) -> &'static str {
+        "GET"
+    }
+
+    fn path_regex(&self) -> Regex {
+        Regex::new(r#"^/v3/tenures/tip/(?P<consensus_hash>[0-9a-f]{40})/blocks$"#).unwrap()
+    }
+
+    fn metrics_identifier(&self) -> &str {
+        "/v3/tenures/tip/:consensus_hash/blocks"
+    }
+
+    /// Try to decode this request.
+    /// There's nothing to load here, so just make sure the request is well-formed.
+    fn try_parse_request(
+        &mut self,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _body: &[u8],
+    ) -> Result<HttpRequestContents, Error> {
+        if preamble.get_content_length() != 0 {
+            return Err(Error::DecodeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+        self.consensus_hash = Some(request::get_consensus_hash(captures, "consensus_hash")?);
+        Ok(HttpRequestContents::new().query_string(query))
+    }
+}
+
+impl RPCRequestHandler for RPCNakamotoTenureTipBlocksRequestHandler {
+    /// Reset internal state
+    fn restart(&mut self) {
+        self.consensus_hash = None;
+    }
+
+    /// Make the response
+    fn try_handle_request(
+        &mut self,
+        preamble: HttpRequestPreamble,
+        _contents: HttpRequestContents,
+        node: &mut StacksNodeState,
+    ) -> Result<(HttpResponsePreamble, HttpResponseContents), NetError> {
+        let allowed = node.with_node_state(|network, _sortdb, _chainstate, _mempool, _rpc_args| {
+            is_tenure_stream_caller_allowed(
+                self.peer_addr.as_ref(),
+                network
+                    .get_connection_opts()
+                    .tenure_stream_allowlist
+                    .as_deref(),
+            )
+        });
+        if !allowed {
+            return StacksHttpResponse::new_error(
+                &preamble,
+                &HttpForbidden::new("Tenure streaming is not allowed from this address\n".into()),
+            )
+            .try_into_contents()
+            .map_err(NetError::from);
+        }
+
+        let consensus_hash = self
+            .consensus_hash
+            .take()
+            .ok_or(NetError::SendError("Missing `consensus_hash`".into()))?;
+
+        let stream_res =
+            node.with_node_state(|network, _sortdb, chainstate, _mempool, rpc_args| {
+                let Some(header) = NakamotoChainState::get_highest_known_block_header_in_tenure(
+                    chainstate.db(),
+                    &consensus_hash,
+                )?
+                else {
+                    return Err(ChainError::NoSuchBlockError);
+                };
+                let Some(nakamoto_header) = header.anchored_header.as_stacks_nakamoto() else {
+                    // the tenure exists, but it's an epoch2 tenure, with no Nakamoto blocks
+                    return Ok(None);
+                };
+                let stream = NakamotoTenureStream::new_with_archive(
+                    chainstate,
+                    nakamoto_header.block_id(),
+                    consensus_hash.clone(),
+                    nakamoto_header.parent_block_id.clone(),
+                    None,
+                    rpc_args.archival_block_source.clone(),
+                )?
+                .with_db_budget(Duration::from_millis(
+                    network.get_connection_opts().tenure_stream_db_budget_ms,
+                ));
+                Ok(Some(stream))
+            });
+
+        let stream = match stream_res {
+            Ok(Some(stream)) => stream,
+            Ok(None) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpError::new(
+                        409,
+                        format!(
+                            "Tenure {} is an epoch2 tenure, not a Nakamoto tenure\n",
+                            &consensus_hash
+                        ),
+                    ),
+                )
+                .try_into_contents()
+                .map_err(NetError::from)
+            }
+            Err(ChainError::NoSuchBlockError) => {
+                return StacksHttpResponse::new_error(
+                    &preamble,
+                    &HttpNotFound::new(format!("No blocks in tenure {}\n", &consensus_hash)),
+                )
+                .try_into_contents()
+                .map_err(NetError::from)
+            }
+            Err(e) => {
+                // nope -- error trying to check
+                let msg = format!("Failed to load tenure {}: {:?}\n", &consensus_hash, &e);
+                warn!("{}", &msg);
+                return StacksHttpResponse::new_error(&preamble, &HttpServerError::new(msg))
+                    .try_into_contents()
+                    .map_err(NetError::from);
+            }
+        };
+
+        // the tenure can grow, so this response is not cacheable
+        let resp_preamble = HttpResponsePreamble::from_http_request_preamble(
+            &preamble,
+            200,
+            "OK",
+            None,
+            HttpContentType::Bytes,
+        );
+        Ok((
+            resp_preamble,
+            HttpResponseContents::from_stream(Box::new(stream)),
+        ))
+    }
+}
+
+/// Decode the HTTP response
+impl HttpResponse for RPCNakamotoTenureTipBlocksRequestHandler {
+    /// Decode this response from a byte stream.  This is called by the client to decode this
+    /// message
+    fn try_parse_response(
+        &self,
+        preamble: &HttpResponsePreamble,
+        body: &[u8],
+    ) -> Result<HttpResponsePayload, Error> {
+        let bytes = parse_bytes(preamble, body, MAX_PAYLOAD_LEN.into())?;
+        Ok(HttpResponsePayload::Bytes(bytes))
+    }
+}
+
+impl StacksHttpRequest {
+    /// Make a new request for the blocks of a tenure, starting from its highest known block
+    pub fn new_get_nakamoto_tenure_tip_blocks(
+        host: PeerHost,
+        consensus_hash: &ConsensusHash,
+    ) -> StacksHttpRequest {
+        StacksHttpRequest::new_for_peer(
+            host,
+            "GET".into(),
+            format!("/v3/tenures/tip/{}/blocks", consensus_hash),
+            HttpRequestContents::new(),
+        )
+        .expect("FATAL: failed to construct request from infallible data")
+    }
+}
+
+impl StacksHttpResponse {
+    /// Decode an HTTP response into a tenure, highest block first.
+    /// The bytes are a concatenation of Nakamoto blocks, with no length prefix.
+    /// If it fails, return Self::Error(..)
+    pub fn decode_nakamoto_tenure_tip_blocks(self) -> Result<Vec<NakamotoBlock>, NetError> {
+        self.decode_nakamoto_tenure()
+    }
+}
@@ -36,20 +36,26 @@ use crate::net::Error as NetError;
 use crate::stacks_common::codec::StacksMessageCodec;
 
 pub mod callreadonly;
+pub mod conditional;
 pub mod get_tenures_fork_info;
 pub mod getaccount;
 pub mod getattachment;
 pub mod getattachmentsinv;
 pub mod getblock;
 pub mod getblock_v3;
+pub mod getblockbatch;
+pub mod getblockrange;
+pub mod getblocksignatures;
 pub mod getconstantval;
 pub mod getcontractabi;
 pub mod getcontractsrc;
 pub mod getdatavar;
+pub mod geteventhealth;
 pub mod getheaders;
 pub mod getinfo;
 pub mod getistraitimplemented;
 pub mod getmapentry;
+pub mod getmempooldrops;
 pub mod getmicroblocks_confirmed;
 pub mod getmicroblocks_indexed;
 pub mod getmicroblocks_unconfirmed;
@@ -62,8 +68,13 @@ pub mod getstackerdbmetadata;
 pub mod getstackers;
 pub mod getstxtransfercost;
 pub mod gettenure;
+pub mod gettenureblockids;
+pub mod gettenurechange;
+pub mod gettenurecomplete;
+pub mod gettenurefull;
 pub mod gettenureinfo;
 pub mod gettenuretip;
+pub mod gettenuretipblocks;
 pub mod gettransaction_unconfirmed;
 pub mod liststackerdbreplicas;
 pub mod postblock;
@@ -75,6 +86,9 @@ pub mod postmempoolquery;
 pub mod postmicroblock;
 pub mod poststackerdbchunk;
 pub mod posttransaction;
+#[cfg(feature = "protobuf")]
+pub mod protobuf;
+pub mod tenure_archive;
 
 #[cfg(test)]
 mod tests;
@@ -92,16 +106,23 @@ impl StacksHttp {
         self.register_rpc_endpoint(getattachmentsinv::RPCGetAttachmentsInvRequestHandler::new());
         self.register_rpc_endpoint(getblock::RPCBlocksRequestHandler::new());
         self.register_rpc_endpoint(getblock_v3::RPCNakamotoBlockRequestHandler::new());
+        self.register_rpc_endpoint(getblockbatch::RPCNakamotoBlockBatchRequestHandler::new());
+        self.register_rpc_endpoint(getblockrange::RPCNakamotoBlockRangeRequestHandler::new());
+        self.register_rpc_endpoint(
+            getblocksignatures::RPCNakamotoBlockSignaturesRequestHandler::new(),
+        );
         self.register_rpc_endpoint(getconstantval::RPCGetConstantValRequestHandler::new());
         self.register_rpc_endpoint(getcontractabi::RPCGetContractAbiRequestHandler::new());
         self.register_rpc_endpoint(getcontractsrc::RPCGetContractSrcRequestHandler::new());
         self.register_rpc_endpoint(getdatavar::RPCGetDataVarRequestHandler::new());
+        self.register_rpc_endpoint(geteventhealth::RPCGetEventHealthRequestHandler::new());
         self.register_rpc_endpoint(getheaders::RPCHeadersRequestHandler::new());
         self.register_rpc_endpoint(getinfo::RPCPeerInfoRequestHandler::new());
         self.register_rpc_endpoint(
             getistraitimplemented::RPCGetIsTraitImplementedRequestHandler::new(),
         );
         self.register_rpc_endpoint(getmapentry::RPCGetMapEntryRequestHandler::new());
+        self.register_rpc_endpoint(getmempooldrops::RPCGetMempoolDropsRequestHandler::new());
         self.register_rpc_endpoint(
             getmicroblocks_confirmed::RPCMicroblocksConfirmedRequestHandler::new(),
         );
@@ -120,9 +141,24 @@ impl StacksHttp {
         );
         self.register_rpc_endpoint(getstackers::GetStackersRequestHandler::default());
         self.register_rpc_endpoint(getsortition::GetSortitionHandler::new());
-        self.register_rpc_endpoint(gettenure::RPCNakamotoTenureRequestHandler::new());
+        self.register_rpc_endpoint(
+            gettenure::RPCNakamotoTenureRequestHandler::new()
+                .with_peer_addr(self.get_peer_addr().clone()),
+        );
+        self.register_rpc_endpoint(
+            gettenureblockids::RPCNakamotoTenureBlockIdsRequestHandler::new(),
+        );
+        self.register_rpc_endpoint(gettenurechange::RPCNakamotoTenureChangeRequestHandler::new());
+        self.register_rpc_endpoint(
+            gettenurecomplete::RPCNakamotoHighestCompleteTenureRequestHandler::new(),
+        );
+        self.register_rpc_endpoint(gettenurefull::RPCNakamotoFullTenureRequestHandler::new());
         self.register_rpc_endpoint(gettenureinfo::RPCNakamotoTenureInfoRequestHandler::new());
         self.register_rpc_endpoint(gettenuretip::RPCNakamotoTenureTipRequestHandler::new());
+        self.register_rpc_endpoint(
+            gettenuretipblocks::RPCNakamotoTenureTipBlocksRequestHandler::new()
+                .with_peer_addr(self.get_peer_addr().clone()),
+        );
         self.register_rpc_endpoint(get_tenures_fork_info::GetTenuresForkInfo::default());
         self.register_rpc_endpoint(
             gettransaction_unconfirmed::RPCGetTransactionUnconfirmedRequestHandler::new(),
@@ -0,0 +1,95 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Protobuf framing for streamed Nakamoto blocks, served by `/v3/tenures/:block_id` when the
+//! request has `Accept: application/x-protobuf`.
+//!
+//! Each block is sent as a length-delimited `NakamotoBlockFrame` message, i.e. a varint of the
+//! message's length followed by the message, which holds the block's consensus-serialized
+//! bytes.  This is the framing protobuf's own `writeDelimitedTo()` / `parseDelimitedFrom()`
+//! use, so clients need no Stacks-specific code to split the stream into blocks.  The message
+//! definition is:
+//!
+//! ```protobuf
+//! message NakamotoBlockFrame {
+//!   // consensus-serialized Nakamoto block
+//!   bytes block = 1;
+//! }
+//! ```
+//!
+//! Frames are written ahead of each block's bytes, so blocks are still streamed from the
+//! staging DB without being buffered.
+
+use prost::encoding::{encode_key, encode_varint, encoded_len_varint, key_len, WireType};
+use prost::Message;
+use stacks_common::codec::StacksMessageCodec;
+
+use crate::chainstate::nakamoto::NakamotoBlock;
+use crate::net::http::{Error, HttpContentType};
+
+/// Tag of `NakamotoBlockFrame::block`
+const BLOCK_FIELD_TAG: u32 = 1;
+
+/// A Nakamoto block, as sent in a protobuf-framed stream
+#[derive(Clone, PartialEq, Message)]
+pub struct NakamotoBlockFrame {
+    /// The consensus-serialized block
+    #[prost(bytes = "vec", tag = "1")]
+    pub block: Vec<u8>,
+}
+
+/// The bytes to send ahead of a block of `block_len` bytes, so that the block's bytes complete
+/// a length-delimited `NakamotoBlockFrame`
+pub fn block_frame_prefix(block_len: u64) -> Vec<u8> {
+    let frame_len =
+        key_len(BLOCK_FIELD_TAG) as u64 + encoded_len_varint(block_len) as u64 + block_len;
+    let mut prefix = vec![];
+    encode_varint(frame_len, &mut prefix);
+    encode_key(BLOCK_FIELD_TAG, WireType::LengthDelimited, &mut prefix);
+    encode_varint(block_len, &mut prefix);
+    prefix
+}
+
+/// Decode a stream of length-delimited `NakamotoBlockFrame`s into the blocks they hold
+pub fn decode_block_frames(mut bytes: &[u8]) -> Result<Vec<NakamotoBlock>, Error> {
+    let mut blocks = vec![];
+    while !bytes.is_empty() {
+        let frame = NakamotoBlockFrame::decode_length_delimited(&mut bytes).map_err(|e| {
+            Error::DecodeError(format!("Failed to decode protobuf block frame: {}", &e))
+        })?;
+        let block_bytes = &mut frame.block.as_slice();
+        let block = NakamotoBlock::consensus_deserialize(block_bytes)
+            .map_err(|e| Error::DecodeError(format!("Failed to decode framed block: {:?}", &e)))?;
+        if !block_bytes.is_empty() {
+            return Err(Error::DecodeError(
+                "Protobuf block frame has trailing bytes".to_string(),
+            ));
+        }
+        blocks.push(block);
+    }
+    Ok(blocks)
+}
+
+/// Whether an `Accept` header value asks for protobuf
+pub fn accepts_protobuf(accept: &str) -> bool {
+    accept.split(',').any(|media_range| {
+        media_range.split(';').next().is_some_and(|media_type| {
+            media_type
+                .trim()
+                .eq_ignore_ascii_case(HttpContentType::Protobuf.as_str())
+        })
+    })
+}
@@ -0,0 +1,215 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Tenure archives, served by `/v3/tenures/:block_id?format=archive`, so that a tenure can be
+//! copied from one node and imported into another offline.
+//!
+//! An archive is `TENURE_ARCHIVE_MAGIC` followed by a sequence of entries. Each entry is a
+//! 4-byte big-endian name length, the UTF-8 name, a 4-byte big-endian data length, and the
+//! data. There is one entry per block, named by the block's index block hash in hex and holding
+//! the consensus-serialized block, in the order `/v3/tenures` streams them (newest first). The
+//! last entry is `manifest.json`, a `TenureArchiveManifest` listing them. It comes last because
+//! which blocks fit in one response is only known once they have been sent.
+
+use stacks_common::codec::StacksMessageCodec;
+use stacks_common::types::chainstate::{ConsensusHash, StacksBlockId};
+
+use crate::burnchains::Burnchain;
+use crate::chainstate::burn::db::sortdb::{SortitionDB, SortitionHandle};
+use crate::chainstate::coordinator::comm::CoordinatorChannels;
+use crate::chainstate::nakamoto::staging_blocks::NakamotoBlockObtainMethod;
+use crate::chainstate::nakamoto::NakamotoBlock;
+use crate::chainstate::stacks::db::StacksChainState;
+use crate::chainstate::stacks::Error as ChainError;
+use crate::net::http::Error;
+use crate::net::relay::{BlockAcceptResponse, Relayer};
+
+/// The bytes every tenure archive starts with
+pub const TENURE_ARCHIVE_MAGIC: &[u8; 8] = b"STXTNRA1";
+
+/// The name of the archive's manifest entry
+pub const TENURE_ARCHIVE_MANIFEST_NAME: &str = "manifest.json";
+
+/// What a tenure archive holds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TenureArchiveManifest {
+    /// The consensus hash of the tenure the blocks belong to
+    pub consensus_hash: ConsensusHash,
+    /// The archived blocks, newest first. Each block is the parent of the one before it.
+    pub block_ids: Vec<StacksBlockId>,
+    /// Set if the node ended the archive early, because looking up its next block took too
+    /// long. The rest of the tenure can be fetched starting from the last archived block.
+    #[serde(default)]
+    pub truncated: bool,
+    /// Set if the node ended the archive early, because the next block it would have archived
+    /// is corrupt in its storage. That block can't be fetched from this node.
+    #[serde(default)]
+    pub corrupt_block_id: Option<StacksBlockId>,
+}
+
+/// A decoded tenure archive
+#[derive(Debug, Clone, PartialEq)]
+pub struct TenureArchive {
+    pub manifest: TenureArchiveManifest,
+    /// The archived blocks, in manifest order
+    pub blocks: Vec<NakamotoBlock>,
+}
+
+/// The bytes that precede the data of an archive entry. `data_len` must fit in 4 bytes, which
+/// it always does for a block, since blocks are at most `MAX_PAYLOAD_LEN` bytes.
+pub fn archive_entry_header(name: &str, data_len: u64) -> Vec<u8> {
+    let mut header = Vec::with_capacity(name.len() + 8);
+    header.extend_from_slice(&(name.len() as u32).to_be_bytes());
+    header.extend_from_slice(name.as_bytes());
+    header.extend_from_slice(&(data_len as u32).to_be_bytes());
+    header
+}
+
+/// Encode a whole archive entry
+pub fn archive_entry(name: &str, data: &[u8]) -> Vec<u8> {
+    let mut entry = archive_entry_header(name, data.len() as u64);
+    entry.extend_from_slice(data);
+    entry
+}
+
+/// Take the next `len` bytes off the front of `bytes`
+fn take<'a>(bytes: &mut &'a [u8], len: usize, what: &str) -> Result<&'a [u8], Error> {
+    if bytes.len() < len {
+        return Err(Error::DecodeError(format!(
+            "Truncated tenure archive: expected {} bytes of {}, got {}",
+            len,
+            what,
+            bytes.len()
+        )));
+    }
+    let (taken, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Ok(taken)
+}
+
+/// Take a 4-byte big-endian length off the front of `bytes`
+fn take_len(bytes: &mut &[u8], what: &str) -> Result<usize, Error> {
+    let len_bytes = take(bytes, 4, what)?;
+    let len = u32::from_be_bytes(len_bytes.try_into().expect("FATAL: took 4 bytes"));
+    Ok(len as usize)
+}
+
+/// Decode a tenure archive, and check that its blocks are the ones its manifest lists, in
+/// order, and that they all belong to the manifest's tenure
+pub fn decode_tenure_archive(mut bytes: &[u8]) -> Result<TenureArchive, Error> {
+    let magic = take(&mut bytes, TENURE_ARCHIVE_MAGIC.len(), "magic")?;
+    if magic != TENURE_ARCHIVE_MAGIC {
+        return Err(Error::DecodeError("Not a tenure archive".to_string()));
+    }
+
+    let mut blocks = vec![];
+    let mut manifest = None;
+    while !bytes.is_empty() {
+        if manifest.is_some() {
+            return Err(Error::DecodeError(
+                "Tenure archive has entries after its manifest".to_string(),
+            ));
+        }
+        let name_len = take_len(&mut bytes, "entry name length")?;
+        let name = std::str::from_utf8(take(&mut bytes, name_len, "entry name")?)
+            .map_err(|_| Error::DecodeError("Tenure archive entry name is not UTF-8".into()))?;
+        let data_len = take_len(&mut bytes, "entry data length")?;
+        let data = take(&mut bytes, data_len, name)?;
+
+        if name == TENURE_ARCHIVE_MANIFEST_NAME {
+            let parsed: TenureArchiveManifest = serde_json::from_slice(data).map_err(|e| {
+                Error::DecodeError(format!("Failed to decode tenure archive manifest: {}", &e))
+            })?;
+            manifest = Some(parsed);
+            continue;
+        }
+
+        let block_bytes = &mut &data[..];
+        let block = NakamotoBlock::consensus_deserialize(block_bytes).map_err(|e| {
+            Error::DecodeError(format!(
+                "Failed to decode archived block {}: {:?}",
+                name, &e
+            ))
+        })?;
+        if !block_bytes.is_empty() {
+            return Err(Error::DecodeError(format!(
+                "Archived block {} has trailing bytes",
+                name
+            )));
+        }
+        if block.block_id().to_hex() != name {
+            return Err(Error::DecodeError(format!(
+                "Archive entry {} holds block {}",
+                name,
+                &block.block_id()
+            )));
+        }
+        blocks.push(block);
+    }
+
+    let manifest =
+        manifest.ok_or_else(|| Error::DecodeError("Tenure archive has no manifest".to_string()))?;
+    let block_ids: Vec<_> = blocks.iter().map(|block| block.block_id()).collect();
+    if block_ids != manifest.block_ids {
+        return Err(Error::DecodeError(
+            "Tenure archive's blocks don't match its manifest".to_string(),
+        ));
+    }
+    if let Some(block) = blocks
+        .iter()
+        .find(|block| block.header.consensus_hash != manifest.consensus_hash)
+    {
+        return Err(Error::DecodeError(format!(
+            "Archived block {} is not in tenure {}",
+            &block.block_id(),
+            &manifest.consensus_hash
+        )));
+    }
+    Ok(TenureArchive { manifest, blocks })
+}
+
+/// Ingest the blocks of `archive` into the staging DB, oldest first, the same way as a block
+/// that is uploaded to `/v3/blocks/upload`. The blocks are then processed by the coordinator
+/// as usual, which is told about each accepted block through `coord_comms`, if given.
+/// Returns what became of each block, oldest first.
+pub fn import_tenure_archive(
+    burnchain: &Burnchain,
+    sortdb: &SortitionDB,
+    chainstate: &mut StacksChainState,
+    archive: &TenureArchive,
+    coord_comms: Option<&CoordinatorChannels>,
+) -> Result<Vec<(StacksBlockId, BlockAcceptResponse)>, ChainError> {
+    let mut sort_handle = sortdb.index_handle_at_tip();
+    let stacks_tip = sort_handle
+        .get_nakamoto_tip_block_id()?
+        .ok_or(ChainError::NoSuchBlockError)?;
+
+    let mut imported = vec![];
+    for block in archive.blocks.iter().rev() {
+        let accepted = Relayer::process_new_nakamoto_block(
+            burnchain,
+            sortdb,
+            &mut sort_handle,
+            chainstate,
+            &stacks_tip,
+            block,
+            coord_comms,
+            NakamotoBlockObtainMethod::Imported,
+        )?;
+        imported.push((block.block_id(), accepted));
+    }
+    Ok(imported)
+}
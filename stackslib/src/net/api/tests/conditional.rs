@@ -0,0 +1,166 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use super::TestRPC;
+use crate::net::api::conditional::{etag_matches, make_etag, ByteRange};
+use crate::net::httpcore::StacksHttpRequest;
+use crate::net::test::TestEventObserver;
+
+#[test]
+fn test_parse_byte_range() {
+    assert_eq!(
+        ByteRange::parse("bytes=0-9"),
+        Some(ByteRange::Bounded(0, 9))
+    );
+    assert_eq!(ByteRange::parse(" bytes= 10 - "), Some(ByteRange::From(10)));
+    assert_eq!(ByteRange::parse("bytes=-5"), Some(ByteRange::Suffix(5)));
+
+    // malformed ranges are ignored
+    for malformed in [
+        "",
+        "bytes=",
+        "bytes=-",
+        "bytes=abc-",
+        "bytes=1-abc",
+        "bytes=10-9",
+        "bytes=--1",
+        "items=0-9",
+        "bytes=0-1,5-9",
+        "bytes=0-99999999999999999999999",
+    ] {
+        assert_eq!(ByteRange::parse(malformed), None, "{}", malformed);
+    }
+}
+
+#[test]
+fn test_resolve_byte_range() {
+    assert_eq!(ByteRange::Bounded(0, 9).resolve(100), Some((0, 9)));
+    assert_eq!(ByteRange::Bounded(90, 200).resolve(100), Some((90, 99)));
+    assert_eq!(ByteRange::From(99).resolve(100), Some((99, 99)));
+    assert_eq!(ByteRange::Suffix(10).resolve(100), Some((90, 99)));
+    assert_eq!(ByteRange::Suffix(200).resolve(100), Some((0, 99)));
+
+    // unsatisfiable ranges
+    assert_eq!(ByteRange::Bounded(100, 200).resolve(100), None);
+    assert_eq!(ByteRange::From(100).resolve(100), None);
+    assert_eq!(ByteRange::Suffix(0).resolve(100), None);
+    assert_eq!(ByteRange::From(0).resolve(0), None);
+}
+
+#[test]
+fn test_etags() {
+    let etag = make_etag(&[b"ab", b"c"]);
+    assert_eq!(etag, make_etag(&[b"ab", b"c"]));
+    assert_ne!(etag, make_etag(&[b"a", b"bc"]));
+    assert!(etag.starts_with('"') && etag.ends_with('"'));
+
+    assert!(etag_matches(&etag, &etag));
+    assert!(etag_matches(&format!("W/{}", &etag), &etag));
+    assert!(etag_matches(&format!("\"other\", {}", &etag), &etag));
+    assert!(etag_matches("*", &etag));
+    assert!(!etag_matches("\"other\"", &etag));
+}
+
+#[test]
+fn test_try_make_conditional_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let test_observer = TestEventObserver::new();
+    let rpc_test = TestRPC::setup_nakamoto(function_name!(), &test_observer);
+
+    let nakamoto_chain_tip = rpc_test.canonical_tip.clone();
+    let etag = make_etag(&[b"/v3/tenures", &nakamoto_chain_tip.0, &[]]);
+
+    let make_request = |header: Option<(&str, &str)>| {
+        let mut request = StacksHttpRequest::new_get_nakamoto_tenure(
+            addr.into(),
+            nakamoto_chain_tip.clone(),
+            None,
+        );
+        if let Some((key, value)) = header {
+            request.add_header(key.into(), value.into());
+        }
+        request
+    };
+
+    let requests = vec![
+        make_request(None),
+        make_request(Some(("If-None-Match", &etag))),
+        make_request(Some(("If-None-Match", "\"something-else\""))),
+        make_request(Some(("Range", "bytes=10-19"))),
+        make_request(Some(("Range", "bytes=-5"))),
+        make_request(Some(("Range", "bytes=999999999-"))),
+        make_request(Some(("Range", "bytes=abc"))),
+    ];
+
+    let mut responses = rpc_test.run(requests);
+
+    // full content, with validators
+    let (preamble, body) = responses.remove(0).destruct();
+    assert_eq!(preamble.status_code, 200);
+    assert_eq!(preamble.get_header("etag".into()), Some(etag.clone()));
+    assert_eq!(
+        preamble.get_header("accept-ranges".into()),
+        Some("bytes".into())
+    );
+    let full: Vec<u8> = body.try_into().unwrap();
+    assert!(full.len() > 20);
+
+    // matching ETag
+    let (preamble, body) = responses.remove(0).destruct();
+    assert_eq!(preamble.status_code, 304);
+    assert_eq!(preamble.get_header("etag".into()), Some(etag.clone()));
+    let empty: Vec<u8> = body.try_into().unwrap();
+    assert!(empty.is_empty());
+
+    // non-matching ETag
+    let (preamble, body) = responses.remove(0).destruct();
+    assert_eq!(preamble.status_code, 200);
+    let content: Vec<u8> = body.try_into().unwrap();
+    assert_eq!(content, full);
+
+    // bounded range
+    let (preamble, body) = responses.remove(0).destruct();
+    assert_eq!(preamble.status_code, 206);
+    assert_eq!(
+        preamble.get_header("content-range".into()),
+        Some(format!("bytes 10-19/{}", full.len()))
+    );
+    let content: Vec<u8> = body.try_into().unwrap();
+    assert_eq!(content, full[10..20].to_vec());
+
+    // suffix range
+    let (preamble, body) = responses.remove(0).destruct();
+    assert_eq!(preamble.status_code, 206);
+    let content: Vec<u8> = body.try_into().unwrap();
+    assert_eq!(content, full[(full.len() - 5)..].to_vec());
+
+    // unsatisfiable range
+    let (preamble, _body) = responses.remove(0).destruct();
+    assert_eq!(preamble.status_code, 416);
+    assert_eq!(
+        preamble.get_header("content-range".into()),
+        Some(format!("bytes */{}", full.len()))
+    );
+
+    // malformed range is ignored
+    let (preamble, body) = responses.remove(0).destruct();
+    assert_eq!(preamble.status_code, 200);
+    let content: Vec<u8> = body.try_into().unwrap();
+    assert_eq!(content, full);
+}
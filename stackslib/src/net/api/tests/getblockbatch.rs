@@ -0,0 +1,289 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use stacks_common::codec::StacksMessageCodec;
+use stacks_common::types::chainstate::StacksBlockId;
+
+use super::TestRPC;
+use crate::chainstate::burn::db::sortdb::{SortitionDB, SortitionHandle};
+use crate::chainstate::nakamoto::{NakamotoBlock, NakamotoChainState, StacksDBIndexed};
+use crate::net::api::getblockbatch::{
+    NakamotoBlockBatchStream, BLOCK_BATCH_NEXT_HEIGHT_HEADER, MAX_BLOCK_BATCH_COUNT,
+};
+use crate::net::api::*;
+use crate::net::connection::ConnectionOptions;
+use crate::net::http::HttpChunkGenerator;
+use crate::net::httpcore::{RPCRequestHandler, StacksHttp, StacksHttpRequest};
+use crate::net::test::TestEventObserver;
+use crate::net::tests::inv::nakamoto::make_nakamoto_peer_from_invs;
+use crate::net::ProtocolFamily;
+
+#[test]
+fn test_try_parse_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let request = StacksHttpRequest::new_get_nakamoto_block_batch(addr.into(), 100, Some(5));
+    let bytes = request.try_serialize().unwrap();
+
+    debug!("Request:\n{}\n", std::str::from_utf8(&bytes).unwrap());
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = getblockbatch::RPCNakamotoBlockBatchRequestHandler::new();
+    let mut parsed_request = http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .unwrap();
+
+    // parsed request consumes headers that would not be in a constructed reqeuest
+    parsed_request.clear_headers();
+    let (preamble, _contents) = parsed_request.destruct();
+
+    // consumed query args
+    assert_eq!(handler.start_height, Some(100));
+    assert_eq!(handler.count, 5);
+
+    assert_eq!(&preamble, request.preamble());
+
+    handler.restart();
+    assert!(handler.start_height.is_none());
+    assert_eq!(handler.count, MAX_BLOCK_BATCH_COUNT);
+
+    // the count is clamped to the most blocks a batch holds
+    let request = StacksHttpRequest::new_get_nakamoto_block_batch(
+        addr.into(),
+        100,
+        Some(MAX_BLOCK_BATCH_COUNT + 1),
+    );
+    let bytes = request.try_serialize().unwrap();
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    http.handle_try_parse_request(
+        &mut handler,
+        &parsed_preamble.expect_request(),
+        &bytes[offset..],
+    )
+    .unwrap();
+    assert_eq!(handler.count, MAX_BLOCK_BATCH_COUNT);
+
+    // the start height is required, and a count of 0 would never make progress
+    for query_args in [
+        vec![],
+        vec![("count", "5")],
+        vec![("start_height", "abc")],
+        vec![("start_height", "100"), ("count", "0")],
+    ] {
+        let mut contents = HttpRequestContents::new();
+        for (arg, value) in query_args.into_iter() {
+            contents = contents.query_arg(arg.into(), value.into());
+        }
+        let request = StacksHttpRequest::new_for_peer(
+            addr.into(),
+            "GET".into(),
+            "/v3/blocks/batch".into(),
+            contents,
+        )
+        .unwrap();
+        let bytes = request.try_serialize().unwrap();
+        let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+        let mut handler = getblockbatch::RPCNakamotoBlockBatchRequestHandler::new();
+        assert!(http
+            .handle_try_parse_request(
+                &mut handler,
+                &parsed_preamble.expect_request(),
+                &bytes[offset..],
+            )
+            .is_err());
+    }
+}
+
+#[test]
+fn test_try_make_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let test_observer = TestEventObserver::new();
+    let mut rpc_test = TestRPC::setup_nakamoto(function_name!(), &test_observer);
+
+    let nakamoto_chain_tip = rpc_test.canonical_tip.clone();
+    let tip_height = NakamotoChainState::get_block_header_nakamoto(
+        rpc_test.peer_1.chainstate().db(),
+        &nakamoto_chain_tip,
+    )
+    .unwrap()
+    .unwrap()
+    .stacks_block_height;
+
+    // an epoch2 block
+    let all_sortitions = rpc_test.peer_1.sortdb().get_all_snapshots().unwrap();
+    assert!(all_sortitions[30].sortition);
+    let epoch2_block_id = StacksBlockId::new(
+        &all_sortitions[30].consensus_hash,
+        &all_sortitions[30].winning_stacks_block_hash,
+    );
+    let epoch2_height =
+        NakamotoChainState::get_block_header(rpc_test.peer_1.chainstate().db(), &epoch2_block_id)
+            .unwrap()
+            .unwrap()
+            .stacks_block_height;
+
+    let requests = vec![
+        // the chain's last blocks; the batch stops at the tip
+        StacksHttpRequest::new_get_nakamoto_block_batch(addr.into(), tip_height - 3, Some(10)),
+        // the first two of them
+        StacksHttpRequest::new_get_nakamoto_block_batch(addr.into(), tip_height - 3, Some(2)),
+        // past the tip
+        StacksHttpRequest::new_get_nakamoto_block_batch(addr.into(), tip_height + 1, None),
+        // an epoch2 block
+        StacksHttpRequest::new_get_nakamoto_block_batch(addr.into(), epoch2_height, None),
+    ];
+    let mut responses = rpc_test.run(requests);
+
+    let (blocks, next_height) = responses.remove(0).decode_nakamoto_block_batch().unwrap();
+    assert_eq!(blocks.len(), 4);
+    assert_eq!(next_height, tip_height + 1);
+    assert_eq!(blocks.last().unwrap().header.block_id(), nakamoto_chain_tip);
+    for pair in blocks.windows(2) {
+        assert_eq!(pair[1].header.parent_block_id, pair[0].header.block_id());
+    }
+
+    // a batch that doesn't reach the tip says where to continue from
+    let (first_blocks, next_height) = responses.remove(0).decode_nakamoto_block_batch().unwrap();
+    assert_eq!(first_blocks, blocks[..2]);
+    assert_eq!(next_height, tip_height - 1);
+
+    // nothing to send yet, so the client asks again from the same height
+    let (blocks, next_height) = responses.remove(0).decode_nakamoto_block_batch().unwrap();
+    assert!(blocks.is_empty());
+    assert_eq!(next_height, tip_height + 1);
+
+    let response = responses.remove(0);
+    assert!(response
+        .preamble()
+        .get_header(BLOCK_BATCH_NEXT_HEIGHT_HEADER.into())
+        .is_none());
+    let (preamble, _body) = response.destruct();
+    assert_eq!(preamble.status_code, 404);
+}
+
+fn read_batch(stream: &mut NakamotoBlockBatchStream) -> Vec<NakamotoBlock> {
+    let mut all_block_bytes = vec![];
+    loop {
+        let mut next_bytes = stream.generate_next_chunk().unwrap();
+        if next_bytes.is_empty() {
+            break;
+        }
+        all_block_bytes.append(&mut next_bytes);
+    }
+
+    let mut ptr = all_block_bytes.as_slice();
+    let mut blocks = vec![];
+    while !ptr.is_empty() {
+        let block_len = u32::consensus_deserialize(&mut ptr).unwrap() as usize;
+        let (mut block_bytes, rest) = ptr.split_at(block_len);
+        let block = NakamotoBlock::consensus_deserialize(&mut block_bytes).unwrap();
+        assert!(block_bytes.is_empty());
+        blocks.push(block);
+        ptr = rest;
+    }
+    blocks
+}
+
+#[test]
+fn test_stream_nakamoto_block_batch() {
+    let test_observer = TestEventObserver::new();
+    let bitvecs = vec![vec![
+        true, true, true, true, true, true, true, true, true, true,
+    ]];
+
+    let mut peer =
+        make_nakamoto_peer_from_invs(function_name!(), &test_observer, 10, 3, bitvecs.clone());
+
+    let nakamoto_tip = {
+        let sortdb = peer.sortdb.take().unwrap();
+        let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn()).unwrap();
+        let ih = sortdb.index_handle(&tip.sortition_id);
+        let nakamoto_tip = ih.get_nakamoto_tip().unwrap().unwrap();
+        peer.sortdb = Some(sortdb);
+        nakamoto_tip
+    };
+    let nakamoto_tip_block_id = StacksBlockId::new(&nakamoto_tip.0, &nakamoto_tip.1);
+    let tip_height = NakamotoChainState::get_block_header_nakamoto(
+        peer.chainstate().db(),
+        &nakamoto_tip_block_id,
+    )
+    .unwrap()
+    .unwrap()
+    .stacks_block_height;
+
+    // a batch that crosses at least one tenure boundary
+    let start_height = tip_height - 15;
+    let start_block_id = peer
+        .chainstate()
+        .index_conn()
+        .get_ancestor_block_id(start_height, &nakamoto_tip_block_id)
+        .unwrap()
+        .unwrap();
+
+    let mut stream = NakamotoBlockBatchStream::new(
+        peer.chainstate(),
+        &nakamoto_tip_block_id,
+        tip_height,
+        start_height,
+        10,
+        None,
+    )
+    .unwrap();
+    assert_eq!(stream.next_height, start_height + 10);
+    let blocks = read_batch(&mut stream);
+
+    assert_eq!(blocks.len(), 10);
+    assert_eq!(blocks.first().unwrap().header.block_id(), start_block_id);
+    for pair in blocks.windows(2) {
+        assert_eq!(pair[1].header.parent_block_id, pair[0].header.block_id());
+    }
+    let tenures: HashSet<_> = blocks
+        .iter()
+        .map(|blk| blk.header.consensus_hash.clone())
+        .collect();
+    assert!(tenures.len() > 1);
+
+    // the next batch picks up where this one left off, and stops at the tip
+    let mut stream = NakamotoBlockBatchStream::new(
+        peer.chainstate(),
+        &nakamoto_tip_block_id,
+        tip_height,
+        start_height + 10,
+        10,
+        None,
+    )
+    .unwrap();
+    assert_eq!(stream.next_height, tip_height + 1);
+    let next_blocks = read_batch(&mut stream);
+    assert_eq!(next_blocks.len(), 6);
+    assert_eq!(
+        next_blocks.first().unwrap().header.parent_block_id,
+        blocks.last().unwrap().header.block_id()
+    );
+    assert_eq!(
+        next_blocks.last().unwrap().header.block_id(),
+        nakamoto_tip_block_id
+    );
+}
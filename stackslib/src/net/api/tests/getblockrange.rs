@@ -0,0 +1,242 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use stacks_common::codec::StacksMessageCodec;
+use stacks_common::types::chainstate::StacksBlockId;
+
+use super::TestRPC;
+use crate::chainstate::burn::db::sortdb::{SortitionDB, SortitionHandle};
+use crate::chainstate::nakamoto::{NakamotoBlock, NakamotoChainState, StacksDBIndexed};
+use crate::net::api::getblockrange::{BlockRangeError, NakamotoBlockRangeStream};
+use crate::net::api::*;
+use crate::net::connection::ConnectionOptions;
+use crate::net::http::HttpChunkGenerator;
+use crate::net::httpcore::{
+    HttpPreambleExtensions, RPCRequestHandler, StacksHttp, StacksHttpRequest,
+};
+use crate::net::test::TestEventObserver;
+use crate::net::tests::inv::nakamoto::make_nakamoto_peer_from_invs;
+use crate::net::ProtocolFamily;
+
+#[test]
+fn test_try_parse_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let request = StacksHttpRequest::new_get_nakamoto_block_range(
+        addr.into(),
+        StacksBlockId([0x11; 32]),
+        StacksBlockId([0x22; 32]),
+    );
+    let bytes = request.try_serialize().unwrap();
+
+    debug!("Request:\n{}\n", std::str::from_utf8(&bytes).unwrap());
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = getblockrange::RPCNakamotoBlockRangeRequestHandler::new();
+    let mut parsed_request = http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .unwrap();
+
+    // parsed request consumes headers that would not be in a constructed reqeuest
+    parsed_request.clear_headers();
+    let (preamble, _contents) = parsed_request.destruct();
+
+    // consumed query args
+    assert_eq!(handler.start_block_id, Some(StacksBlockId([0x11; 32])));
+    assert_eq!(handler.end_block_id, Some(StacksBlockId([0x22; 32])));
+
+    assert_eq!(&preamble, request.preamble());
+
+    handler.restart();
+    assert!(handler.start_block_id.is_none());
+    assert!(handler.end_block_id.is_none());
+
+    // both endpoints are required, and must be block IDs
+    let block_id_hex = format!("{}", &StacksBlockId([0x11; 32]));
+    for query_args in [
+        vec![],
+        vec![("start", block_id_hex.clone())],
+        vec![("start", "11".to_string()), ("end", block_id_hex.clone())],
+    ] {
+        let mut contents = HttpRequestContents::new();
+        for (arg, value) in query_args.into_iter() {
+            contents = contents.query_arg(arg.into(), value);
+        }
+        let request = StacksHttpRequest::new_for_peer(
+            addr.into(),
+            "GET".into(),
+            "/v3/blocks/range".into(),
+            contents,
+        )
+        .unwrap();
+        let bytes = request.try_serialize().unwrap();
+        let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+        let mut handler = getblockrange::RPCNakamotoBlockRangeRequestHandler::new();
+        assert!(http
+            .handle_try_parse_request(
+                &mut handler,
+                &parsed_preamble.expect_request(),
+                &bytes[offset..],
+            )
+            .is_err());
+    }
+}
+
+#[test]
+fn test_try_make_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let test_observer = TestEventObserver::new();
+    let rpc_test = TestRPC::setup_nakamoto(function_name!(), &test_observer);
+
+    let nakamoto_chain_tip = rpc_test.canonical_tip.clone();
+
+    let mut requests = vec![];
+
+    // a one-block range
+    let request = StacksHttpRequest::new_get_nakamoto_block_range(
+        addr.into(),
+        nakamoto_chain_tip.clone(),
+        nakamoto_chain_tip.clone(),
+    );
+    requests.push(request);
+
+    // query non-existant block
+    let request = StacksHttpRequest::new_get_nakamoto_block_range(
+        addr.into(),
+        StacksBlockId([0x11; 32]),
+        nakamoto_chain_tip.clone(),
+    );
+    requests.push(request);
+
+    let mut responses = rpc_test.run(requests);
+
+    let response = responses.remove(0);
+    let resp = response.decode_nakamoto_block_range().unwrap();
+    assert_eq!(resp.len(), 1);
+    assert_eq!(resp[0].header.block_id(), nakamoto_chain_tip);
+
+    // no block
+    let response = responses.remove(0);
+    let (preamble, _body) = response.destruct();
+    assert_eq!(preamble.status_code, 404);
+}
+
+fn read_range(stream: &mut NakamotoBlockRangeStream) -> Vec<NakamotoBlock> {
+    let mut all_block_bytes = vec![];
+    loop {
+        let mut next_bytes = stream.generate_next_chunk().unwrap();
+        if next_bytes.is_empty() {
+            break;
+        }
+        all_block_bytes.append(&mut next_bytes);
+    }
+
+    let ptr = &mut all_block_bytes.as_slice();
+    let mut blocks = vec![];
+    while ptr.len() > 0 {
+        let block = NakamotoBlock::consensus_deserialize(ptr).unwrap();
+        blocks.push(block);
+    }
+    blocks
+}
+
+#[test]
+fn test_stream_nakamoto_block_range() {
+    let test_observer = TestEventObserver::new();
+    let bitvecs = vec![vec![
+        true, true, true, true, true, true, true, true, true, true,
+    ]];
+
+    let mut peer =
+        make_nakamoto_peer_from_invs(function_name!(), &test_observer, 10, 3, bitvecs.clone());
+
+    let nakamoto_tip = {
+        let sortdb = peer.sortdb.take().unwrap();
+        let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn()).unwrap();
+        let ih = sortdb.index_handle(&tip.sortition_id);
+        let nakamoto_tip = ih.get_nakamoto_tip().unwrap().unwrap();
+        peer.sortdb = Some(sortdb);
+        nakamoto_tip
+    };
+    let nakamoto_tip_block_id = StacksBlockId::new(&nakamoto_tip.0, &nakamoto_tip.1);
+    let tip_height = NakamotoChainState::get_block_header_nakamoto(
+        peer.chainstate().db(),
+        &nakamoto_tip_block_id,
+    )
+    .unwrap()
+    .unwrap()
+    .stacks_block_height;
+
+    // a range that crosses at least one tenure boundary
+    let ancestor_block_id = peer
+        .chainstate()
+        .index_conn()
+        .get_ancestor_block_id(tip_height - 15, &nakamoto_tip_block_id)
+        .unwrap()
+        .unwrap();
+
+    let mut stream = NakamotoBlockRangeStream::new(
+        peer.chainstate(),
+        &nakamoto_tip_block_id,
+        &ancestor_block_id,
+    )
+    .unwrap();
+    let blocks = read_range(&mut stream);
+
+    assert_eq!(blocks.len(), 16);
+    assert_eq!(
+        blocks.first().unwrap().header.block_id(),
+        nakamoto_tip_block_id
+    );
+    assert_eq!(blocks.last().unwrap().header.block_id(), ancestor_block_id);
+    for pair in blocks.windows(2) {
+        assert_eq!(pair[0].header.parent_block_id, pair[1].header.block_id());
+    }
+    let tenures: HashSet<_> = blocks
+        .iter()
+        .map(|blk| blk.header.consensus_hash.clone())
+        .collect();
+    assert!(tenures.len() > 1);
+
+    // endpoints can be given in either order
+    let mut stream = NakamotoBlockRangeStream::new(
+        peer.chainstate(),
+        &ancestor_block_id,
+        &nakamoto_tip_block_id,
+    )
+    .unwrap();
+    let reversed_blocks = read_range(&mut stream);
+    assert_eq!(blocks, reversed_blocks);
+
+    // can't stream a range with an unknown endpoint
+    assert!(matches!(
+        NakamotoBlockRangeStream::new(
+            peer.chainstate(),
+            &nakamoto_tip_block_id,
+            &StacksBlockId([0x11; 32]),
+        ),
+        Err(BlockRangeError::NoSuchBlock(_))
+    ));
+}
@@ -0,0 +1,149 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use stacks_common::types::chainstate::StacksBlockId;
+
+use crate::chainstate::nakamoto::NakamotoChainState;
+use crate::net::api::tests::TestRPC;
+use crate::net::api::*;
+use crate::net::connection::ConnectionOptions;
+use crate::net::httpcore::{
+    HttpPreambleExtensions, HttpRequestContentsExtensions, RPCRequestHandler, StacksHttp,
+    StacksHttpRequest,
+};
+use crate::net::test::TestEventObserver;
+use crate::net::ProtocolFamily;
+
+#[test]
+fn test_try_parse_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let request = StacksHttpRequest::new_get_nakamoto_block_signatures(
+        addr.into(),
+        &StacksBlockId([0x11; 32]),
+    );
+
+    let bytes = request.try_serialize().unwrap();
+
+    debug!("Request:\n{}\n", std::str::from_utf8(&bytes).unwrap());
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+
+    let mut handler = getblocksignatures::RPCNakamotoBlockSignaturesRequestHandler::new();
+    let mut parsed_request = http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .unwrap();
+    assert_eq!(handler.block_id, Some(StacksBlockId([0x11; 32])));
+
+    // parsed request consumes headers that would not be in a constructed reqeuest
+    parsed_request.clear_headers();
+    let (preamble, contents) = parsed_request.destruct();
+
+    assert_eq!(&preamble, request.preamble());
+
+    handler.restart();
+    assert!(handler.block_id.is_none());
+}
+
+#[test]
+fn test_try_make_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let test_observer = TestEventObserver::new();
+    let mut rpc_test = TestRPC::setup_nakamoto(function_name!(), &test_observer);
+
+    let nakamoto_chain_tip = rpc_test.canonical_tip.clone();
+    let header_info = NakamotoChainState::get_block_header_nakamoto(
+        rpc_test.peer_1.chainstate().db(),
+        &nakamoto_chain_tip,
+    )
+    .unwrap()
+    .unwrap();
+    let header = header_info
+        .anchored_header
+        .as_stacks_nakamoto()
+        .unwrap()
+        .clone();
+
+    let all_sortitions = rpc_test.peer_1.sortdb().get_all_snapshots().unwrap();
+    assert!(all_sortitions.len() > 30);
+    assert!(all_sortitions[30].sortition);
+    let epoch2_block_id = StacksBlockId::new(
+        &all_sortitions[30].consensus_hash,
+        &all_sortitions[30].winning_stacks_block_hash,
+    );
+
+    let mut requests = vec![];
+
+    // query existing Nakamoto block
+    let request =
+        StacksHttpRequest::new_get_nakamoto_block_signatures(addr.into(), &nakamoto_chain_tip);
+    requests.push(request);
+
+    // query non-existant block
+    let request = StacksHttpRequest::new_get_nakamoto_block_signatures(
+        addr.into(),
+        &StacksBlockId([0x11; 32]),
+    );
+    requests.push(request);
+
+    // query existing epoch2 block
+    let request =
+        StacksHttpRequest::new_get_nakamoto_block_signatures(addr.into(), &epoch2_block_id);
+    requests.push(request);
+
+    let mut responses = rpc_test.run(requests);
+
+    // got the signatures
+    let response = responses.remove(0);
+    debug!(
+        "Response:\n{}\n",
+        std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+    );
+
+    let resp = response.decode_nakamoto_block_signatures().unwrap();
+    assert_eq!(resp.block_id, nakamoto_chain_tip);
+    assert_eq!(resp.signer_signature_hash, header.signer_signature_hash());
+    assert_eq!(resp.signer_signature, header.signer_signature);
+    assert!(!resp.signer_signature.is_empty());
+    assert_eq!(resp.signer_bitvec, header.pox_treatment);
+
+    // no such block
+    let response = responses.remove(0);
+    debug!(
+        "Response:\n{}\n",
+        std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+    );
+
+    let (preamble, body) = response.destruct();
+    assert_eq!(preamble.status_code, 404);
+
+    // epoch2 blocks have no signer signatures
+    let response = responses.remove(0);
+    debug!(
+        "Response:\n{}\n",
+        std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+    );
+
+    let (preamble, body) = response.destruct();
+    assert_eq!(preamble.status_code, 404);
+}
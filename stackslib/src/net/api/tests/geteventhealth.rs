@@ -0,0 +1,172 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2023 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use clarity::vm::costs::ExecutionCost;
+use stacks_common::types::chainstate::{BlockHeaderHash, ConsensusHash};
+
+use super::{test_rpc, TestRPC};
+use crate::burnchains::Txid;
+use crate::chainstate::nakamoto::NakamotoBlock;
+use crate::chainstate::stacks::miner::TransactionEvent;
+use crate::chainstate::stacks::{StacksBlock, StacksMicroblock};
+use crate::core::mempool::{
+    MemPoolDropReason, MemPoolEventDispatcher, MemPoolFeeRatePercentiles, ProposalCallbackReceiver,
+};
+use crate::net::api::geteventhealth::{
+    EventDeliveryState, RPCEventHealthData, RPCEventObserverHealth,
+};
+use crate::net::api::*;
+use crate::net::connection::ConnectionOptions;
+use crate::net::httpcore::{
+    HttpPreambleExtensions, RPCRequestHandler, StacksHttp, StacksHttpRequest,
+};
+use crate::net::ProtocolFamily;
+
+/// An event dispatcher that reports fixed event delivery health
+struct HealthTestObserver {
+    health: RPCEventHealthData,
+}
+
+impl MemPoolEventDispatcher for HealthTestObserver {
+    fn get_proposal_callback_receiver(&self) -> Option<Box<dyn ProposalCallbackReceiver>> {
+        None
+    }
+
+    fn mempool_txs_dropped(
+        &self,
+        _txids: Vec<Txid>,
+        _reason: MemPoolDropReason,
+        _fee_rates: Option<MemPoolFeeRatePercentiles>,
+    ) {
+    }
+
+    fn mined_block_event(
+        &self,
+        _target_burn_height: u64,
+        _block: &StacksBlock,
+        _block_size_bytes: u64,
+        _consumed: &ExecutionCost,
+        _confirmed_microblock_cost: &ExecutionCost,
+        _tx_results: Vec<TransactionEvent>,
+    ) {
+    }
+
+    fn mined_microblock_event(
+        &self,
+        _microblock: &StacksMicroblock,
+        _tx_results: Vec<TransactionEvent>,
+        _anchor_block_consensus_hash: ConsensusHash,
+        _anchor_block: BlockHeaderHash,
+    ) {
+    }
+
+    fn mined_nakamoto_block_event(
+        &self,
+        _target_burn_height: u64,
+        _block: &NakamotoBlock,
+        _block_size_bytes: u64,
+        _consumed: &ExecutionCost,
+        _tx_results: Vec<TransactionEvent>,
+    ) {
+    }
+
+    fn get_event_delivery_health(&self) -> Option<RPCEventHealthData> {
+        Some(self.health.clone())
+    }
+}
+
+#[test]
+fn test_try_parse_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let request = StacksHttpRequest::new_get_event_health(addr.into());
+    let bytes = request.try_serialize().unwrap();
+
+    debug!("Request:\n{}\n", std::str::from_utf8(&bytes).unwrap());
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = geteventhealth::RPCGetEventHealthRequestHandler::new();
+    let mut parsed_request = http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .unwrap();
+
+    // parsed request consumes headers that would not be in a constructed reqeuest
+    parsed_request.clear_headers();
+    let (preamble, _contents) = parsed_request.destruct();
+
+    assert_eq!(&preamble, request.preamble());
+}
+
+#[test]
+fn test_try_make_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    // no event dispatcher to ask
+    let request = StacksHttpRequest::new_get_event_health(addr.into());
+    let mut responses = test_rpc(function_name!(), vec![request]);
+    assert_eq!(responses.len(), 1);
+
+    let response = responses.pop().unwrap();
+    let (preamble, _body) = response.destruct();
+    assert_eq!(preamble.status_code, 404);
+
+    let health = RPCEventHealthData::new(vec![
+        RPCEventObserverHealth {
+            endpoint: "localhost:3700".into(),
+            queue_depth: 0,
+            last_success_time: Some(1_700_000_000),
+            consecutive_failures: 0,
+            delivery_state: EventDeliveryState::Ok,
+            fallback_deliveries: 0,
+        },
+        RPCEventObserverHealth {
+            endpoint: "REDACTED@indexer:3999/events".into(),
+            queue_depth: 3,
+            last_success_time: None,
+            consecutive_failures: 12,
+            delivery_state: EventDeliveryState::Fallback,
+            fallback_deliveries: 7,
+        },
+    ]);
+    assert_eq!(health.total_queue_depth, 3);
+    assert_eq!(health.total_fallback_deliveries, 7);
+
+    let observer = HealthTestObserver {
+        health: health.clone(),
+    };
+    let request = StacksHttpRequest::new_get_event_health(addr.into());
+    let mut responses = TestRPC::setup(&format!("{}-observer", function_name!()))
+        .run_with_observer(vec![request], Some(&observer));
+    assert_eq!(responses.len(), 1);
+
+    let response = responses.pop().unwrap();
+    debug!(
+        "Response:\n{}\n",
+        std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+    );
+    assert_eq!(
+        response.preamble().get_canonical_stacks_tip_height(),
+        Some(1)
+    );
+    assert_eq!(response.decode_event_health().unwrap(), health);
+}
@@ -14,11 +14,17 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 
 use clarity::vm::types::{QualifiedContractIdentifier, StacksAddressExtensions};
 use clarity::vm::{ClarityName, ContractName};
-use stacks_common::codec::StacksMessageCodec;
+use rusqlite::params;
+use stacks_common::codec::{StacksMessageCodec, MAX_PAYLOAD_LEN};
 use stacks_common::types::chainstate::{
     ConsensusHash, StacksAddress, StacksBlockId, StacksPrivateKey,
 };
@@ -27,20 +33,29 @@ use stacks_common::types::Address;
 
 use super::TestRPC;
 use crate::chainstate::burn::db::sortdb::{SortitionDB, SortitionHandle};
-use crate::chainstate::nakamoto::{NakamotoBlock, NakamotoChainState};
+use crate::chainstate::nakamoto::staging_blocks::ArchivalBlockSource;
+use crate::chainstate::nakamoto::{NakamotoBlock, NakamotoBlockHeader, NakamotoChainState};
 use crate::chainstate::stacks::db::blocks::test::*;
 use crate::chainstate::stacks::db::StacksChainState;
 use crate::chainstate::stacks::{
     Error as chainstate_error, StacksBlock, StacksBlockHeader, StacksMicroblock,
 };
-use crate::net::api::gettenure::NakamotoTenureStream;
+use crate::net::api::gettenure::{
+    fetch_tenure_paged, get_tenure_pox_anchor_status, verify_tenure_signatures,
+    NakamotoTenureStream, TenurePagingOpts, TenurePoxAnchorStatus,
+};
+use crate::net::api::tenure_archive::{
+    archive_entry, archive_entry_header, decode_tenure_archive, import_tenure_archive,
+    TenureArchiveManifest, TENURE_ARCHIVE_MAGIC, TENURE_ARCHIVE_MANIFEST_NAME,
+};
 use crate::net::api::*;
 use crate::net::connection::ConnectionOptions;
-use crate::net::http::HttpChunkGenerator;
+use crate::net::http::{HttpChunkGenerator, HttpResponsePayload};
 use crate::net::httpcore::{
-    HttpPreambleExtensions, HttpRequestContentsExtensions, RPCRequestHandler, StacksHttp,
-    StacksHttpRequest,
+    HttpPreambleExtensions, HttpRequestContentsExtensions, RPCRequestHandler, SendRequestError,
+    StacksHttp, StacksHttpRequest,
 };
+use crate::net::relay::BlockAcceptResponse;
 use crate::net::test::TestEventObserver;
 use crate::net::tests::inv::nakamoto::make_nakamoto_peer_from_invs;
 use crate::net::{ProtocolFamily, TipRequest};
@@ -73,6 +88,7 @@ fn test_try_parse_request() {
 
     // consumed path args
     assert_eq!(handler.block_id, Some(StacksBlockId([0x11; 32])));
+    assert!(!handler.protobuf);
 
     assert_eq!(&preamble, request.preamble());
 
@@ -80,12 +96,253 @@ fn test_try_parse_request() {
     assert!(handler.block_id.is_none());
 }
 
+#[test]
+fn test_try_parse_follow_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let request =
+        StacksHttpRequest::new_get_nakamoto_tenure_follow(addr.into(), StacksBlockId([0x11; 32]));
+    let bytes = request.try_serialize().unwrap();
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = gettenure::RPCNakamotoTenureRequestHandler::new();
+    http.handle_try_parse_request(
+        &mut handler,
+        &parsed_preamble.expect_request(),
+        &bytes[offset..],
+    )
+    .unwrap();
+
+    assert_eq!(handler.block_id, Some(StacksBlockId([0x11; 32])));
+    assert!(handler.follow);
+
+    handler.restart();
+    assert!(!handler.follow);
+
+    // follow= must be a boolean
+    let request = StacksHttpRequest::new_for_peer(
+        addr.into(),
+        "GET".into(),
+        format!("/v3/tenures/{}", &StacksBlockId([0x11; 32])),
+        HttpRequestContents::new().query_arg("follow".into(), "maybe".into()),
+    )
+    .unwrap();
+    let bytes = request.try_serialize().unwrap();
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    assert!(http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .is_err());
+}
+
+#[test]
+fn test_try_parse_archive_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let request = StacksHttpRequest::new_get_nakamoto_tenure_archive(
+        addr.into(),
+        StacksBlockId([0x11; 32]),
+        Some(StacksBlockId([0x22; 32])),
+    );
+    let bytes = request.try_serialize().unwrap();
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = gettenure::RPCNakamotoTenureRequestHandler::new();
+    http.handle_try_parse_request(
+        &mut handler,
+        &parsed_preamble.expect_request(),
+        &bytes[offset..],
+    )
+    .unwrap();
+
+    assert_eq!(handler.block_id, Some(StacksBlockId([0x11; 32])));
+    assert_eq!(handler.last_block_id, Some(StacksBlockId([0x22; 32])));
+    assert!(handler.archive);
+
+    handler.restart();
+    assert!(!handler.archive);
+
+    // only raw and archive formats are supported
+    let request = StacksHttpRequest::new_for_peer(
+        addr.into(),
+        "GET".into(),
+        format!("/v3/tenures/{}", &StacksBlockId([0x11; 32])),
+        HttpRequestContents::new().query_arg("format".into(), "tar".into()),
+    )
+    .unwrap();
+    let bytes = request.try_serialize().unwrap();
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = gettenure::RPCNakamotoTenureRequestHandler::new();
+    assert!(http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .is_err());
+}
+
+#[test]
+fn test_try_parse_verify_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let request = StacksHttpRequest::new_get_nakamoto_tenure_verified(
+        addr.into(),
+        StacksBlockId([0x11; 32]),
+        Some(StacksBlockId([0x22; 32])),
+    );
+    let bytes = request.try_serialize().unwrap();
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = gettenure::RPCNakamotoTenureRequestHandler::new();
+    http.handle_try_parse_request(
+        &mut handler,
+        &parsed_preamble.expect_request(),
+        &bytes[offset..],
+    )
+    .unwrap();
+
+    assert_eq!(handler.block_id, Some(StacksBlockId([0x11; 32])));
+    assert_eq!(handler.last_block_id, Some(StacksBlockId([0x22; 32])));
+    assert!(handler.verify);
+
+    handler.restart();
+    assert!(!handler.verify);
+
+    // only signatures can be verified, and not while following the tenure
+    for contents in [
+        HttpRequestContents::new().query_arg("verify".into(), "everything".into()),
+        HttpRequestContents::new()
+            .query_arg("verify".into(), "signatures".into())
+            .query_arg("follow".into(), "true".into()),
+    ] {
+        let request = StacksHttpRequest::new_for_peer(
+            addr.into(),
+            "GET".into(),
+            format!("/v3/tenures/{}", &StacksBlockId([0x11; 32])),
+            contents,
+        )
+        .unwrap();
+        let bytes = request.try_serialize().unwrap();
+        let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+        let mut handler = gettenure::RPCNakamotoTenureRequestHandler::new();
+        assert!(http
+            .handle_try_parse_request(
+                &mut handler,
+                &parsed_preamble.expect_request(),
+                &bytes[offset..],
+            )
+            .is_err());
+    }
+}
+
+#[test]
+fn test_try_parse_max_bytes_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let parse = |http: &mut StacksHttp, max_bytes: &str| {
+        let request = StacksHttpRequest::new_for_peer(
+            addr.into(),
+            "GET".into(),
+            format!("/v3/tenures/{}", &StacksBlockId([0x11; 32])),
+            HttpRequestContents::new().query_arg("max_bytes".into(), max_bytes.into()),
+        )
+        .unwrap();
+        let bytes = request.try_serialize().unwrap();
+        let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+        let mut handler = gettenure::RPCNakamotoTenureRequestHandler::new();
+        http.handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .map(|_| handler)
+    };
+
+    let mut handler = parse(&mut http, "65536").unwrap();
+    assert_eq!(handler.max_bytes, Some(65536));
+    handler.restart();
+    assert!(handler.max_bytes.is_none());
+
+    // the budget can only be made smaller than the default
+    let handler = parse(&mut http, &u64::MAX.to_string()).unwrap();
+    assert_eq!(handler.max_bytes, Some(MAX_PAYLOAD_LEN.into()));
+
+    assert!(parse(&mut http, "lots").is_err());
+    assert!(parse(&mut http, "-1").is_err());
+}
+
+#[cfg(feature = "protobuf")]
+#[test]
+fn test_try_parse_protobuf_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let request = StacksHttpRequest::new_get_nakamoto_tenure_protobuf(
+        addr.into(),
+        StacksBlockId([0x11; 32]),
+        None,
+    );
+    let bytes = request.try_serialize().unwrap();
+
+    debug!("Request:\n{}\n", std::str::from_utf8(&bytes).unwrap());
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = gettenure::RPCNakamotoTenureRequestHandler::new();
+    http.handle_try_parse_request(
+        &mut handler,
+        &parsed_preamble.expect_request(),
+        &bytes[offset..],
+    )
+    .unwrap();
+
+    assert_eq!(handler.block_id, Some(StacksBlockId([0x11; 32])));
+    assert!(handler.protobuf);
+
+    handler.restart();
+    assert!(!handler.protobuf);
+}
+
+#[cfg(feature = "protobuf")]
+#[test]
+fn test_block_frame_prefix() {
+    use prost::Message;
+
+    use crate::net::api::protobuf::{accepts_protobuf, block_frame_prefix, NakamotoBlockFrame};
+
+    for block_len in [1, 127, 128, 300, 16_383, 16_384, 2_000_000] {
+        let block = vec![0xab; block_len];
+        let mut framed = block_frame_prefix(block_len as u64);
+        framed.extend_from_slice(&block);
+        assert_eq!(
+            framed,
+            NakamotoBlockFrame { block }.encode_length_delimited_to_vec()
+        );
+    }
+
+    assert!(accepts_protobuf("application/x-protobuf"));
+    assert!(accepts_protobuf(
+        "application/json, Application/X-Protobuf;q=0.9"
+    ));
+    assert!(!accepts_protobuf(
+        "application/octet-stream, application/json"
+    ));
+    assert!(!accepts_protobuf("application/x-protobuffer"));
+}
+
 #[test]
 fn test_try_make_response() {
     let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
 
     let test_observer = TestEventObserver::new();
-    let rpc_test = TestRPC::setup_nakamoto(function_name!(), &test_observer);
+    let mut rpc_test = TestRPC::setup_nakamoto(function_name!(), &test_observer);
 
     let nakamoto_chain_tip = rpc_test.canonical_tip.clone();
     let consensus_hash = rpc_test.consensus_hash.clone();
@@ -105,6 +362,17 @@ fn test_try_make_response() {
         StacksHttpRequest::new_get_nakamoto_tenure(addr.into(), StacksBlockId([0x11; 32]), None);
     requests.push(request);
 
+    // query an epoch2 block
+    let all_sortitions = rpc_test.peer_1.sortdb().get_all_snapshots().unwrap();
+    assert!(all_sortitions[30].sortition);
+    let epoch2_block_id = StacksBlockId::new(
+        &all_sortitions[30].consensus_hash,
+        &all_sortitions[30].winning_stacks_block_hash,
+    );
+    let request =
+        StacksHttpRequest::new_get_nakamoto_tenure(addr.into(), epoch2_block_id.clone(), None);
+    requests.push(request);
+
     let mut responses = rpc_test.run(requests);
 
     // got the block
@@ -120,6 +388,368 @@ fn test_try_make_response() {
     let (preamble, body) = response.destruct();
 
     assert_eq!(preamble.status_code, 404);
+
+    // the block exists, but has no tenure to stream
+    let response = responses.remove(0);
+    let (preamble, body) = response.destruct();
+
+    assert_eq!(preamble.status_code, 409);
+    let HttpResponsePayload::Text(msg) = body else {
+        panic!("Expected a text error, got {:?}", &body);
+    };
+    assert!(msg.contains(&format!("/v2/blocks/{}", &epoch2_block_id)));
+}
+
+#[test]
+fn test_tenure_stream_allowlist() {
+    let peer_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), 20443);
+    let handler = gettenure::RPCNakamotoTenureRequestHandler::new().with_peer_addr(peer_addr);
+    let allowed = [IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))];
+    let others = [IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2))];
+
+    assert!(handler.is_caller_allowed(None));
+    assert!(handler.is_caller_allowed(Some(&allowed)));
+    assert!(!handler.is_caller_allowed(Some(&others)));
+    assert!(!handler.is_caller_allowed(Some(&[])));
+
+    // a caller of unknown address is only served without an allowlist
+    let handler = gettenure::RPCNakamotoTenureRequestHandler::new();
+    assert!(handler.is_caller_allowed(None));
+    assert!(!handler.is_caller_allowed(Some(&allowed)));
+}
+
+/// Run a `/v3/tenures` request against nodes whose allowlist is `allowlist`, and return the
+/// response's status code
+fn tenure_stream_status(test_name: &str, allowlist: Vec<IpAddr>) -> u16 {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let test_observer = TestEventObserver::new();
+    let mut rpc_test = TestRPC::setup_nakamoto(test_name, &test_observer);
+    rpc_test
+        .peer_1
+        .network
+        .connection_opts
+        .tenure_stream_allowlist = Some(allowlist.clone());
+    rpc_test
+        .peer_2
+        .network
+        .connection_opts
+        .tenure_stream_allowlist = Some(allowlist);
+
+    let request = StacksHttpRequest::new_get_nakamoto_tenure(
+        addr.into(),
+        rpc_test.canonical_tip.clone(),
+        None,
+    );
+    let mut responses = rpc_test.run(vec![request]);
+    responses.remove(0).preamble().status_code
+}
+
+#[test]
+fn test_try_make_response_allowed_caller() {
+    // the test harness's connections are all from localhost
+    let status_code = tenure_stream_status(
+        function_name!(),
+        vec![IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1))],
+    );
+    assert_eq!(status_code, 200);
+}
+
+#[test]
+fn test_try_make_response_denied_caller() {
+    let status_code = tenure_stream_status(
+        function_name!(),
+        vec![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))],
+    );
+    assert_eq!(status_code, 403);
+}
+
+#[cfg(feature = "protobuf")]
+#[test]
+fn test_try_make_protobuf_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let test_observer = TestEventObserver::new();
+    let rpc_test = TestRPC::setup_nakamoto(function_name!(), &test_observer);
+
+    let nakamoto_chain_tip = rpc_test.canonical_tip.clone();
+
+    let requests = vec![
+        StacksHttpRequest::new_get_nakamoto_tenure(addr.into(), nakamoto_chain_tip.clone(), None),
+        StacksHttpRequest::new_get_nakamoto_tenure_protobuf(
+            addr.into(),
+            nakamoto_chain_tip.clone(),
+            None,
+        ),
+    ];
+    let mut responses = rpc_test.run(requests);
+
+    let raw_response = responses.remove(0);
+    let raw_etag = raw_response.preamble().get_header("etag".into()).unwrap();
+    let raw_blocks = raw_response.decode_nakamoto_tenure().unwrap();
+
+    // same blocks, in the same order, but each in its own frame
+    let response = responses.remove(0);
+    assert_eq!(
+        response.preamble().content_type,
+        crate::net::http::HttpContentType::Protobuf
+    );
+    assert_ne!(
+        response.preamble().get_header("etag".into()).unwrap(),
+        raw_etag
+    );
+    let blocks = response.decode_nakamoto_tenure_protobuf().unwrap();
+    assert_eq!(blocks.len(), 10);
+    assert_eq!(blocks, raw_blocks);
+}
+
+#[test]
+fn test_try_make_archive_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let test_observer = TestEventObserver::new();
+    let rpc_test = TestRPC::setup_nakamoto(function_name!(), &test_observer);
+
+    let nakamoto_chain_tip = rpc_test.canonical_tip.clone();
+
+    let requests = vec![
+        StacksHttpRequest::new_get_nakamoto_tenure(addr.into(), nakamoto_chain_tip.clone(), None),
+        StacksHttpRequest::new_get_nakamoto_tenure_archive(
+            addr.into(),
+            nakamoto_chain_tip.clone(),
+            None,
+        ),
+    ];
+    let mut responses = rpc_test.run(requests);
+
+    let raw_response = responses.remove(0);
+    let raw_etag = raw_response.preamble().get_header("etag".into()).unwrap();
+    let raw_blocks = raw_response.decode_nakamoto_tenure().unwrap();
+
+    // same blocks, in the same order, each in its own entry, and listed in the manifest
+    let response = responses.remove(0);
+    assert_ne!(
+        response.preamble().get_header("etag".into()).unwrap(),
+        raw_etag
+    );
+    let archive = response.decode_nakamoto_tenure_archive().unwrap();
+    assert_eq!(archive.blocks.len(), 10);
+    assert_eq!(archive.blocks, raw_blocks);
+    assert_eq!(
+        archive.manifest,
+        TenureArchiveManifest {
+            consensus_hash: raw_blocks[0].header.consensus_hash.clone(),
+            block_ids: raw_blocks.iter().map(|block| block.block_id()).collect(),
+            truncated: false,
+            corrupt_block_id: None,
+        }
+    );
+}
+
+#[test]
+fn test_try_make_verified_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let test_observer = TestEventObserver::new();
+    let rpc_test = TestRPC::setup_nakamoto(function_name!(), &test_observer);
+
+    let nakamoto_chain_tip = rpc_test.canonical_tip.clone();
+
+    let requests = vec![
+        StacksHttpRequest::new_get_nakamoto_tenure(addr.into(), nakamoto_chain_tip.clone(), None),
+        StacksHttpRequest::new_get_nakamoto_tenure_verified(
+            addr.into(),
+            nakamoto_chain_tip.clone(),
+            None,
+        ),
+    ];
+    let mut responses = rpc_test.run(requests);
+
+    // the tenure's blocks are all properly signed, so they're all sent
+    let raw_blocks = responses.remove(0).decode_nakamoto_tenure().unwrap();
+    let blocks = responses.remove(0).decode_nakamoto_tenure().unwrap();
+    assert_eq!(blocks.len(), 10);
+    assert_eq!(blocks, raw_blocks);
+}
+
+#[test]
+fn test_try_make_verified_response_bad_signatures() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let test_observer = TestEventObserver::new();
+    let mut rpc_test = TestRPC::setup_nakamoto(function_name!(), &test_observer);
+
+    let nakamoto_chain_tip = rpc_test.canonical_tip.clone();
+
+    // strip the signer signatures from the tip's parent, which is in the same tenure
+    let mut bad_block_id = None;
+    for peer in [&mut rpc_test.peer_1, &mut rpc_test.peer_2] {
+        let chainstate = peer.chainstate();
+        let (tip, _) = chainstate
+            .nakamoto_blocks_db()
+            .get_nakamoto_block(&nakamoto_chain_tip)
+            .unwrap()
+            .unwrap();
+        let (mut parent, _) = chainstate
+            .nakamoto_blocks_db()
+            .get_nakamoto_block(&tip.header.parent_block_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(parent.header.consensus_hash, tip.header.consensus_hash);
+        parent.header.signer_signature.clear();
+
+        let staging_db_path = chainstate.get_nakamoto_staging_blocks_path().unwrap();
+        let staging_conn =
+            StacksChainState::open_nakamoto_staging_blocks(&staging_db_path, true).unwrap();
+        staging_conn
+            .conn()
+            .execute(
+                "UPDATE nakamoto_staging_blocks SET data = ?1 WHERE index_block_hash = ?2",
+                params![parent.serialize_to_vec(), parent.block_id()],
+            )
+            .unwrap();
+        bad_block_id = Some(parent.block_id());
+    }
+    let bad_block_id = bad_block_id.unwrap();
+
+    let requests = vec![
+        StacksHttpRequest::new_get_nakamoto_tenure(addr.into(), nakamoto_chain_tip.clone(), None),
+        StacksHttpRequest::new_get_nakamoto_tenure_verified(
+            addr.into(),
+            nakamoto_chain_tip.clone(),
+            None,
+        ),
+        // the blocks before the bad one are fine
+        StacksHttpRequest::new_get_nakamoto_tenure_verified(
+            addr.into(),
+            nakamoto_chain_tip.clone(),
+            Some(bad_block_id.clone()),
+        ),
+        // only the blocks that fit in `max_bytes` are checked, and the bad one doesn't
+        StacksHttpRequest::new_for_peer(
+            addr.into(),
+            "GET".into(),
+            format!("/v3/tenures/{}", &nakamoto_chain_tip),
+            HttpRequestContents::new()
+                .query_arg("verify".into(), "signatures".into())
+                .query_arg("max_bytes".into(), "1".into()),
+        )
+        .unwrap(),
+    ];
+    let mut responses = rpc_test.run(requests);
+
+    // without verification, the tenure is sent as it is stored
+    let raw_blocks = responses.remove(0).decode_nakamoto_tenure().unwrap();
+    assert_eq!(raw_blocks.len(), 10);
+    assert!(raw_blocks[1].header.signer_signature.is_empty());
+
+    // with verification, nothing is sent, and the bad block is named
+    let (preamble, body) = responses.remove(0).destruct();
+    assert_eq!(preamble.status_code, 409);
+    let HttpResponsePayload::Text(msg) = body else {
+        panic!("Expected a text error, got {:?}", &body);
+    };
+    assert!(msg.contains(&bad_block_id.to_string()));
+
+    let blocks = responses.remove(0).decode_nakamoto_tenure().unwrap();
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].block_id(), nakamoto_chain_tip);
+
+    let blocks = responses.remove(0).decode_nakamoto_tenure().unwrap();
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].block_id(), nakamoto_chain_tip);
+}
+
+#[test]
+fn test_tenure_pox_anchor_status() {
+    let test_observer = TestEventObserver::new();
+    let bitvecs = vec![vec![true; 10]; 3];
+    let mut peer = make_nakamoto_peer_from_invs(function_name!(), &test_observer, 10, 3, bitvecs);
+    let sortdb = peer.sortdb.take().unwrap();
+
+    // each reward set is stored under the first sortition of the prepare phase that chose its
+    // anchor block, so the anchor block-commits can be read back from them
+    let all_sortitions = sortdb.get_all_snapshots().unwrap();
+    let mut anchors = HashMap::new();
+    for sn in all_sortitions.iter() {
+        let Some(rc_info) =
+            SortitionDB::get_preprocessed_reward_set(sortdb.conn(), &sn.sortition_id).unwrap()
+        else {
+            continue;
+        };
+        if let Some((_, txid)) = rc_info.selected_anchor_block() {
+            anchors.insert(txid.clone(), rc_info.reward_cycle);
+        }
+    }
+
+    let mut nakamoto_anchors = 0;
+    for sn in all_sortitions.iter() {
+        let epoch = SortitionDB::get_stacks_epoch(sortdb.conn(), sn.block_height)
+            .unwrap()
+            .unwrap();
+        if !epoch.epoch_id.uses_nakamoto_blocks() {
+            continue;
+        }
+        let status = get_tenure_pox_anchor_status(&sortdb, &sn.consensus_hash).unwrap();
+        match anchors.get(&sn.winning_block_txid) {
+            Some(reward_cycle) if sn.sortition => {
+                assert_eq!(
+                    status,
+                    TenurePoxAnchorStatus {
+                        is_pox_anchor: true,
+                        pox_anchor_reward_cycle: Some(*reward_cycle),
+                    }
+                );
+                nakamoto_anchors += 1;
+            }
+            _ => assert_eq!(status, TenurePoxAnchorStatus::default()),
+        }
+    }
+    // three reward cycles of Nakamoto tenures span at least two Nakamoto prepare phases
+    assert!(nakamoto_anchors >= 2);
+
+    // a tenure that the sortition DB doesn't know about has no status
+    assert!(get_tenure_pox_anchor_status(&sortdb, &ConsensusHash([0x22; 20])).is_err());
+    peer.sortdb = Some(sortdb);
+}
+
+#[test]
+fn test_try_make_response_pox_anchor_status() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let test_observer = TestEventObserver::new();
+    let mut rpc_test = TestRPC::setup_nakamoto(function_name!(), &test_observer);
+
+    let nakamoto_chain_tip = rpc_test.canonical_tip.clone();
+    let expected_status =
+        get_tenure_pox_anchor_status(rpc_test.peer_1.sortdb(), &rpc_test.consensus_hash).unwrap();
+
+    let requests = vec![
+        StacksHttpRequest::new_get_nakamoto_tenure(addr.into(), nakamoto_chain_tip.clone(), None),
+        StacksHttpRequest::new_get_nakamoto_tenure_archive(
+            addr.into(),
+            nakamoto_chain_tip.clone(),
+            None,
+        ),
+        StacksHttpRequest::new_get_nakamoto_tenure_info(addr.into()),
+    ];
+    let mut responses = rpc_test.run(requests);
+
+    // the tenure's status is the same whether its blocks are sent as bytes or as an archive,
+    // or its metadata as JSON
+    for response in responses.drain(0..2) {
+        assert_eq!(response.preamble().status_code, 200);
+        assert_eq!(
+            TenurePoxAnchorStatus::from_headers(response.preamble()).unwrap(),
+            expected_status
+        );
+    }
+    let info = responses.remove(0).decode_nakamoto_tenure_info().unwrap();
+    assert_eq!(info.is_pox_anchor, expected_status.is_pox_anchor);
+    assert_eq!(
+        info.pox_anchor_reward_cycle,
+        expected_status.pox_anchor_reward_cycle
+    );
 }
 
 #[test]
@@ -203,3 +833,858 @@ fn test_stream_nakamoto_tenure() {
         nakamoto_tip_block_id
     );
 }
+
+/// An archive that holds a fixed set of blocks
+struct TestArchive(HashMap<StacksBlockId, Vec<u8>>);
+
+impl ArchivalBlockSource for TestArchive {
+    fn get_block_bytes(&self, block_id: &StacksBlockId) -> Option<Vec<u8>> {
+        self.0.get(block_id).cloned()
+    }
+}
+
+#[test]
+fn test_stream_nakamoto_tenure_from_archive() {
+    let test_observer = TestEventObserver::new();
+    let bitvecs = vec![vec![
+        true, true, true, true, true, true, true, true, true, true,
+    ]];
+
+    let mut peer =
+        make_nakamoto_peer_from_invs(function_name!(), &test_observer, 10, 3, bitvecs.clone());
+
+    let nakamoto_tip = {
+        let sortdb = peer.sortdb.take().unwrap();
+        let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn()).unwrap();
+        let ih = sortdb.index_handle(&tip.sortition_id);
+        let nakamoto_tip = ih.get_nakamoto_tip().unwrap().unwrap();
+        peer.sortdb = Some(sortdb);
+        nakamoto_tip
+    };
+    let nakamoto_tip_block_id = StacksBlockId::new(&nakamoto_tip.0, &nakamoto_tip.1);
+    let nakamoto_header = NakamotoChainState::get_block_header_nakamoto(
+        peer.chainstate().db(),
+        &nakamoto_tip_block_id,
+    )
+    .unwrap()
+    .unwrap()
+    .anchored_header
+    .as_stacks_nakamoto()
+    .cloned()
+    .unwrap();
+    let make_stream = |chainstate: &StacksChainState,
+                       archive: Option<Arc<dyn ArchivalBlockSource>>| {
+        NakamotoTenureStream::new_with_archive(
+            chainstate,
+            nakamoto_tip_block_id.clone(),
+            nakamoto_header.consensus_hash.clone(),
+            nakamoto_header.parent_block_id.clone(),
+            None,
+            archive,
+        )
+    };
+
+    let blocks = drain_tenure_stream(&mut make_stream(peer.chainstate(), None).unwrap());
+    assert_eq!(blocks.len(), 10);
+
+    // move the tip and every other block of the tenure to the archive
+    let archived: HashMap<_, _> = blocks
+        .iter()
+        .step_by(2)
+        .map(|block| (block.block_id(), block.serialize_to_vec()))
+        .collect();
+    let staging_db_path = peer
+        .chainstate()
+        .get_nakamoto_staging_blocks_path()
+        .unwrap();
+    let staging_conn =
+        StacksChainState::open_nakamoto_staging_blocks(&staging_db_path, true).unwrap();
+    for block_id in archived.keys() {
+        staging_conn
+            .conn()
+            .execute(
+                "DELETE FROM nakamoto_staging_blocks WHERE index_block_hash = ?1",
+                params![block_id],
+            )
+            .unwrap();
+    }
+
+    // the blocks can't be served without the archive
+    assert!(make_stream(peer.chainstate(), None).is_err());
+
+    let archive: Arc<dyn ArchivalBlockSource> = Arc::new(TestArchive(archived.clone()));
+    let archived_blocks =
+        drain_tenure_stream(&mut make_stream(peer.chainstate(), Some(archive)).unwrap());
+    assert_eq!(archived_blocks, blocks);
+
+    // an archive that has the wrong bytes for a block doesn't have it
+    let mislabeled = archived
+        .keys()
+        .map(|block_id| (block_id.clone(), blocks[1].serialize_to_vec()))
+        .collect();
+    let archive: Arc<dyn ArchivalBlockSource> = Arc::new(TestArchive(mislabeled));
+    assert!(make_stream(peer.chainstate(), Some(archive)).is_err());
+}
+
+/// Serve `/v3/tenures` requests for the given tenure (newest block first) from a mock node
+/// that returns at most `page_len` blocks per page. Returns the node's port.
+fn start_mock_tenure_server(blocks: Vec<NakamotoBlock>, page_len: usize) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut request = vec![];
+            let mut buf = [0u8; 1024];
+            while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                let num_read = stream.read(&mut buf).unwrap();
+                if num_read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..num_read]);
+            }
+            let request = String::from_utf8(request).unwrap();
+            let path = request.split_whitespace().nth(1).unwrap();
+            let block_id =
+                StacksBlockId::from_hex(path.trim_start_matches("/v3/tenures/")).unwrap();
+
+            let start = blocks
+                .iter()
+                .position(|block| block.block_id() == block_id)
+                .unwrap();
+            let mut body = vec![];
+            for block in blocks.iter().skip(start).take(page_len) {
+                body.extend(block.serialize_to_vec());
+            }
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/octet-stream\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            stream.write_all(head.as_bytes()).unwrap();
+            stream.write_all(&body).unwrap();
+        }
+    });
+    port
+}
+
+#[test]
+fn test_fetch_tenure_paged() {
+    let test_observer = TestEventObserver::new();
+    let bitvecs = vec![vec![
+        true, true, true, true, true, true, true, true, true, true,
+    ]];
+
+    let mut peer =
+        make_nakamoto_peer_from_invs(function_name!(), &test_observer, 10, 3, bitvecs.clone());
+
+    let nakamoto_tip = {
+        let sortdb = peer.sortdb.take().unwrap();
+        let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn()).unwrap();
+        let ih = sortdb.index_handle(&tip.sortition_id);
+        let nakamoto_tip = ih.get_nakamoto_tip().unwrap().unwrap();
+        peer.sortdb = Some(sortdb);
+        nakamoto_tip
+    };
+    let nakamoto_header = NakamotoChainState::get_block_header_nakamoto(
+        peer.chainstate().db(),
+        &StacksBlockId::new(&nakamoto_tip.0, &nakamoto_tip.1),
+    )
+    .unwrap()
+    .unwrap()
+    .anchored_header
+    .as_stacks_nakamoto()
+    .cloned()
+    .unwrap();
+    let mut stream = NakamotoTenureStream::new(
+        peer.chainstate(),
+        nakamoto_header.block_id(),
+        nakamoto_header.consensus_hash.clone(),
+        nakamoto_header.parent_block_id.clone(),
+        None,
+    )
+    .unwrap();
+    let blocks = drain_tenure_stream(&mut stream);
+    assert_eq!(blocks.len(), 10);
+    assert_eq!(
+        blocks.last().unwrap().is_wellformed_tenure_start_block(),
+        Ok(true)
+    );
+
+    // 3 blocks per page takes 4 pages
+    let port = start_mock_tenure_server(blocks.clone(), 3);
+    let opts = TenurePagingOpts {
+        max_pages: 4,
+        timeout: Duration::from_secs(10),
+    };
+    let fetched = fetch_tenure_paged("127.0.0.1", port, nakamoto_header.block_id(), &opts).unwrap();
+    assert_eq!(fetched, blocks);
+
+    // starting part-way through the tenure
+    let fetched = fetch_tenure_paged("127.0.0.1", port, blocks[5].block_id(), &opts).unwrap();
+    assert_eq!(fetched, blocks[5..]);
+
+    // not enough pages
+    let opts = TenurePagingOpts {
+        max_pages: 3,
+        timeout: Duration::from_secs(10),
+    };
+    assert!(matches!(
+        fetch_tenure_paged("127.0.0.1", port, nakamoto_header.block_id(), &opts),
+        Err(SendRequestError::Protocol(_))
+    ));
+}
+
+#[test]
+fn test_stream_and_import_tenure_archive() {
+    let test_observer = TestEventObserver::new();
+    let bitvecs = vec![vec![
+        true, true, true, true, true, true, true, true, true, true,
+    ]];
+
+    let mut peer =
+        make_nakamoto_peer_from_invs(function_name!(), &test_observer, 10, 3, bitvecs.clone());
+
+    let nakamoto_tip = {
+        let sortdb = peer.sortdb.take().unwrap();
+        let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn()).unwrap();
+        let ih = sortdb.index_handle(&tip.sortition_id);
+        let nakamoto_tip = ih.get_nakamoto_tip().unwrap().unwrap();
+        peer.sortdb = Some(sortdb);
+        nakamoto_tip
+    };
+    let nakamoto_tip_block_id = StacksBlockId::new(&nakamoto_tip.0, &nakamoto_tip.1);
+    let nakamoto_header = NakamotoChainState::get_block_header_nakamoto(
+        peer.chainstate().db(),
+        &nakamoto_tip_block_id,
+    )
+    .unwrap()
+    .unwrap()
+    .anchored_header
+    .as_stacks_nakamoto()
+    .cloned()
+    .unwrap();
+    let make_stream = |chainstate: &StacksChainState| {
+        NakamotoTenureStream::new(
+            chainstate,
+            nakamoto_tip_block_id.clone(),
+            nakamoto_header.consensus_hash.clone(),
+            nakamoto_header.parent_block_id.clone(),
+            None,
+        )
+        .unwrap()
+    };
+
+    let blocks = drain_tenure_stream(&mut make_stream(peer.chainstate()));
+    assert_eq!(blocks.len(), 10);
+
+    let mut stream = make_stream(peer.chainstate()).with_archive_format();
+    let mut archive_bytes = vec![];
+    loop {
+        let next_bytes = stream.generate_next_chunk().unwrap();
+        if next_bytes.is_empty() {
+            break;
+        }
+        archive_bytes.extend(next_bytes);
+    }
+    let archive = decode_tenure_archive(&archive_bytes).unwrap();
+    assert_eq!(archive.blocks, blocks);
+    assert_eq!(
+        archive.manifest.consensus_hash,
+        nakamoto_header.consensus_hash
+    );
+
+    // the node already has these blocks, so importing them is a no-op, but each block is
+    // offered, oldest first
+    let sortdb = peer.sortdb.take().unwrap();
+    let burnchain = peer.config.burnchain.clone();
+    let imported =
+        import_tenure_archive(&burnchain, &sortdb, peer.chainstate(), &archive, None).unwrap();
+    peer.sortdb = Some(sortdb);
+    let imported_ids: Vec<_> = imported
+        .iter()
+        .map(|(block_id, _)| block_id.clone())
+        .collect();
+    let mut oldest_first: Vec<_> = blocks.iter().map(|block| block.block_id()).collect();
+    oldest_first.reverse();
+    assert_eq!(imported_ids, oldest_first);
+    assert!(imported
+        .iter()
+        .all(|(_, accepted)| matches!(accepted, BlockAcceptResponse::AlreadyStored)));
+
+    // archives that don't hold what they claim to are rejected
+    let manifest = TenureArchiveManifest {
+        consensus_hash: blocks[0].header.consensus_hash.clone(),
+        block_ids: blocks.iter().map(|block| block.block_id()).collect(),
+        truncated: false,
+        corrupt_block_id: None,
+    };
+
+    let make_archive = |entries: &[(String, Vec<u8>)]| {
+        let mut archive = TENURE_ARCHIVE_MAGIC.to_vec();
+        for (name, data) in entries {
+            archive.extend(archive_entry(name, data));
+        }
+        archive
+    };
+    let mut entries: Vec<_> = blocks
+        .iter()
+        .map(|block| (block.block_id().to_hex(), block.serialize_to_vec()))
+        .collect();
+    entries.push((
+        TENURE_ARCHIVE_MANIFEST_NAME.to_string(),
+        serde_json::to_vec(&manifest).unwrap(),
+    ));
+    assert_eq!(
+        decode_tenure_archive(&make_archive(&entries)).unwrap(),
+        archive
+    );
+
+    // not an archive
+    let mut bad_magic = make_archive(&entries);
+    bad_magic[0] = b'X';
+    assert!(decode_tenure_archive(&bad_magic).is_err());
+
+    // truncated
+    let archive_bytes = make_archive(&entries);
+    assert!(decode_tenure_archive(&archive_bytes[..archive_bytes.len() - 1]).is_err());
+
+    // no manifest
+    assert!(decode_tenure_archive(&make_archive(&entries[..entries.len() - 1])).is_err());
+
+    // an entry that isn't named by its block's ID
+    let mut misnamed = entries.clone();
+    misnamed[0].0 = blocks[1].block_id().to_hex();
+    assert!(decode_tenure_archive(&make_archive(&misnamed)).is_err());
+
+    // a block that the manifest doesn't list
+    let mut unlisted = entries.clone();
+    unlisted.remove(1);
+    assert!(decode_tenure_archive(&make_archive(&unlisted)).is_err());
+
+    // an entry after the manifest
+    let mut trailing = entries.clone();
+    trailing.push(entries[0].clone());
+    assert!(decode_tenure_archive(&make_archive(&trailing)).is_err());
+}
+
+/// Streaming a tenure as an archive never builds the archive in memory: each chunk is at most
+/// one chunk of one block, behind that block's entry header, however many blocks the tenure
+/// has.  Only the manifest, which lists the blocks sent, grows with the tenure.
+#[test]
+fn test_tenure_archive_stream_memory_bounded() {
+    let test_observer = TestEventObserver::new();
+    let bitvecs = vec![vec![
+        true, true, true, true, true, true, true, true, true, true,
+    ]];
+
+    let mut peer =
+        make_nakamoto_peer_from_invs(function_name!(), &test_observer, 10, 3, bitvecs.clone());
+
+    let nakamoto_tip = {
+        let sortdb = peer.sortdb.take().unwrap();
+        let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn()).unwrap();
+        let ih = sortdb.index_handle(&tip.sortition_id);
+        let nakamoto_tip = ih.get_nakamoto_tip().unwrap().unwrap();
+        peer.sortdb = Some(sortdb);
+        nakamoto_tip
+    };
+    let nakamoto_tip_block_id = StacksBlockId::new(&nakamoto_tip.0, &nakamoto_tip.1);
+    let nakamoto_header = NakamotoChainState::get_block_header_nakamoto(
+        peer.chainstate().db(),
+        &nakamoto_tip_block_id,
+    )
+    .unwrap()
+    .unwrap()
+    .anchored_header
+    .as_stacks_nakamoto()
+    .cloned()
+    .unwrap();
+    let make_stream = |chainstate: &StacksChainState, last_block_id: Option<StacksBlockId>| {
+        NakamotoTenureStream::new(
+            chainstate,
+            nakamoto_tip_block_id.clone(),
+            nakamoto_header.consensus_hash.clone(),
+            nakamoto_header.parent_block_id.clone(),
+            last_block_id,
+        )
+        .unwrap()
+        .with_archive_format()
+    };
+    let blocks = drain_tenure_stream(
+        &mut NakamotoTenureStream::new(
+            peer.chainstate(),
+            nakamoto_tip_block_id.clone(),
+            nakamoto_header.consensus_hash.clone(),
+            nakamoto_header.parent_block_id.clone(),
+            None,
+        )
+        .unwrap(),
+    );
+    assert_eq!(blocks.len(), 10);
+
+    // stream ever-longer runs of the tenure, and record the largest chunk each one produces
+    let mut largest_chunks = vec![];
+    for num_blocks in 1..=blocks.len() {
+        let last_block_id = blocks.get(num_blocks).map(|block| block.block_id());
+        let mut stream = make_stream(peer.chainstate(), last_block_id);
+        let chunk_bound = stream.hint_chunk_size()
+            + TENURE_ARCHIVE_MAGIC.len()
+            + archive_entry_header(&nakamoto_tip_block_id.to_hex(), 0).len();
+
+        let mut chunks = vec![];
+        loop {
+            let chunk = stream.generate_next_chunk().unwrap();
+            if chunk.is_empty() {
+                break;
+            }
+            chunks.push(chunk);
+        }
+
+        // the last chunk is the manifest, and every chunk before it is part of a block entry
+        let (manifest_chunk, block_chunks) = chunks.split_last().unwrap();
+        let manifest = TenureArchiveManifest {
+            consensus_hash: nakamoto_header.consensus_hash.clone(),
+            block_ids: blocks[..num_blocks]
+                .iter()
+                .map(|block| block.block_id())
+                .collect(),
+            truncated: false,
+            corrupt_block_id: None,
+        };
+        assert_eq!(
+            manifest_chunk,
+            &archive_entry(
+                TENURE_ARCHIVE_MANIFEST_NAME,
+                &serde_json::to_vec(&manifest).unwrap()
+            )
+        );
+        assert!(block_chunks.len() > num_blocks);
+        assert!(block_chunks.iter().all(|chunk| chunk.len() <= chunk_bound));
+        largest_chunks.push(block_chunks.iter().map(|chunk| chunk.len()).max().unwrap());
+
+        let archive = decode_tenure_archive(&chunks.concat()).unwrap();
+        assert_eq!(archive.blocks, blocks[..num_blocks].to_vec());
+    }
+
+    // the largest chunk doesn't grow with the number of blocks streamed
+    assert!(largest_chunks.iter().all(|len| *len == largest_chunks[0]));
+}
+
+#[test]
+fn test_tenure_stream_db_budget() {
+    let test_observer = TestEventObserver::new();
+    let bitvecs = vec![vec![
+        true, true, true, true, true, true, true, true, true, true,
+    ]];
+
+    let mut peer =
+        make_nakamoto_peer_from_invs(function_name!(), &test_observer, 10, 3, bitvecs.clone());
+
+    let nakamoto_tip = {
+        let sortdb = peer.sortdb.take().unwrap();
+        let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn()).unwrap();
+        let ih = sortdb.index_handle(&tip.sortition_id);
+        let nakamoto_tip = ih.get_nakamoto_tip().unwrap().unwrap();
+        peer.sortdb = Some(sortdb);
+        nakamoto_tip
+    };
+    let nakamoto_header = NakamotoChainState::get_block_header_nakamoto(
+        peer.chainstate().db(),
+        &StacksBlockId::new(&nakamoto_tip.0, &nakamoto_tip.1),
+    )
+    .unwrap()
+    .unwrap()
+    .anchored_header
+    .as_stacks_nakamoto()
+    .cloned()
+    .unwrap();
+    let make_stream = |chainstate: &StacksChainState, db_budget: Duration| {
+        NakamotoTenureStream::new(
+            chainstate,
+            nakamoto_header.block_id(),
+            nakamoto_header.consensus_hash.clone(),
+            nakamoto_header.parent_block_id.clone(),
+            None,
+        )
+        .unwrap()
+        .with_db_budget(db_budget)
+    };
+
+    // a fast DB streams the whole tenure
+    let mut stream = make_stream(peer.chainstate(), Duration::from_secs(10));
+    let blocks = drain_tenure_stream(&mut stream);
+    assert_eq!(blocks.len(), 10);
+    assert!(!stream.truncated);
+
+    // a slow DB ends the stream after the block it started with
+    let mut stream = make_stream(peer.chainstate(), Duration::from_millis(10));
+    stream.test_db_delay = Some(Duration::from_millis(50));
+    assert_eq!(drain_tenure_stream(&mut stream), blocks[..1]);
+    assert!(stream.truncated);
+
+    // ...and says so in an archive's manifest
+    let mut stream =
+        make_stream(peer.chainstate(), Duration::from_millis(10)).with_archive_format();
+    stream.test_db_delay = Some(Duration::from_millis(50));
+    let mut archive_bytes = vec![];
+    loop {
+        let next_bytes = stream.generate_next_chunk().unwrap();
+        if next_bytes.is_empty() {
+            break;
+        }
+        archive_bytes.extend(next_bytes);
+    }
+    let archive = decode_tenure_archive(&archive_bytes).unwrap();
+    assert_eq!(archive.blocks, blocks[..1]);
+    assert_eq!(
+        archive.manifest,
+        TenureArchiveManifest {
+            consensus_hash: nakamoto_header.consensus_hash.clone(),
+            block_ids: vec![blocks[0].block_id()],
+            truncated: true,
+            corrupt_block_id: None,
+        }
+    );
+
+    // checking the blocks' signer signatures stops at the budget too, after the first block
+    let sortdb = peer.sortdb.take().unwrap();
+    let burnchain = peer.config.burnchain.clone();
+    let stream = make_stream(peer.chainstate(), Duration::from_secs(10));
+    let unchecked_block_id =
+        verify_tenure_signatures(&burnchain, &sortdb, peer.chainstate(), None, &stream).unwrap();
+    assert!(unchecked_block_id.is_none());
+
+    let stream = make_stream(peer.chainstate(), Duration::ZERO);
+    let unchecked_block_id =
+        verify_tenure_signatures(&burnchain, &sortdb, peer.chainstate(), None, &stream).unwrap();
+    assert_eq!(unchecked_block_id, Some(blocks[1].block_id()));
+    peer.sortdb = Some(sortdb);
+}
+
+#[test]
+fn test_tenure_stream_max_bytes() {
+    let test_observer = TestEventObserver::new();
+    let bitvecs = vec![vec![
+        true, true, true, true, true, true, true, true, true, true,
+    ]];
+
+    let mut peer =
+        make_nakamoto_peer_from_invs(function_name!(), &test_observer, 10, 3, bitvecs.clone());
+
+    let nakamoto_tip = {
+        let sortdb = peer.sortdb.take().unwrap();
+        let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn()).unwrap();
+        let ih = sortdb.index_handle(&tip.sortition_id);
+        let nakamoto_tip = ih.get_nakamoto_tip().unwrap().unwrap();
+        peer.sortdb = Some(sortdb);
+        nakamoto_tip
+    };
+    let nakamoto_header = NakamotoChainState::get_block_header_nakamoto(
+        peer.chainstate().db(),
+        &StacksBlockId::new(&nakamoto_tip.0, &nakamoto_tip.1),
+    )
+    .unwrap()
+    .unwrap()
+    .anchored_header
+    .as_stacks_nakamoto()
+    .cloned()
+    .unwrap();
+    let make_stream = |chainstate: &StacksChainState, header: &NakamotoBlockHeader| {
+        NakamotoTenureStream::new(
+            chainstate,
+            header.block_id(),
+            header.consensus_hash.clone(),
+            header.parent_block_id.clone(),
+            None,
+        )
+        .unwrap()
+    };
+
+    let blocks = drain_tenure_stream(&mut make_stream(peer.chainstate(), &nakamoto_header));
+    assert_eq!(blocks.len(), 10);
+    let block_sizes: Vec<u64> = blocks
+        .iter()
+        .map(|block| block.serialize_to_vec().len() as u64)
+        .collect();
+
+    // the budget is clamped to the default
+    let stream = make_stream(peer.chainstate(), &nakamoto_header).with_max_bytes(u64::MAX);
+    assert_eq!(stream.max_bytes, u64::from(MAX_PAYLOAD_LEN));
+
+    // a budget of the first three blocks stops the stream after them
+    let max_bytes: u64 = block_sizes[..3].iter().sum();
+    let mut stream = make_stream(peer.chainstate(), &nakamoto_header).with_max_bytes(max_bytes);
+    assert_eq!(drain_tenure_stream(&mut stream), blocks[..3]);
+
+    // paging through the tenure from the parent of each page's last block gets every block
+    // once, with no page over the budget
+    let mut paged_blocks: Vec<NakamotoBlock> = vec![];
+    let mut header = nakamoto_header.clone();
+    loop {
+        let mut stream = make_stream(peer.chainstate(), &header).with_max_bytes(max_bytes);
+        let page = drain_tenure_stream(&mut stream);
+        assert!(!page.is_empty());
+        let page_bytes: u64 = page
+            .iter()
+            .map(|block| block.serialize_to_vec().len() as u64)
+            .sum();
+        assert!(page_bytes <= max_bytes);
+        paged_blocks.extend(page);
+        if paged_blocks.len() == blocks.len() {
+            break;
+        }
+        let parent_block_id = &paged_blocks.last().unwrap().header.parent_block_id;
+        header = blocks
+            .iter()
+            .find(|block| &block.block_id() == parent_block_id)
+            .unwrap()
+            .header
+            .clone();
+    }
+    assert_eq!(paged_blocks, blocks);
+
+    // a budget too small for any block still sends the block the stream starts with
+    let mut stream = make_stream(peer.chainstate(), &nakamoto_header).with_max_bytes(1);
+    assert_eq!(drain_tenure_stream(&mut stream), blocks[..1]);
+}
+
+#[test]
+fn test_tenure_stream_corrupt_block() {
+    let test_observer = TestEventObserver::new();
+    let bitvecs = vec![vec![
+        true, true, true, true, true, true, true, true, true, true,
+    ]];
+
+    let mut peer =
+        make_nakamoto_peer_from_invs(function_name!(), &test_observer, 10, 3, bitvecs.clone());
+
+    let nakamoto_tip = {
+        let sortdb = peer.sortdb.take().unwrap();
+        let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn()).unwrap();
+        let ih = sortdb.index_handle(&tip.sortition_id);
+        let nakamoto_tip = ih.get_nakamoto_tip().unwrap().unwrap();
+        peer.sortdb = Some(sortdb);
+        nakamoto_tip
+    };
+    let nakamoto_header = NakamotoChainState::get_block_header_nakamoto(
+        peer.chainstate().db(),
+        &StacksBlockId::new(&nakamoto_tip.0, &nakamoto_tip.1),
+    )
+    .unwrap()
+    .unwrap()
+    .anchored_header
+    .as_stacks_nakamoto()
+    .cloned()
+    .unwrap();
+    let make_stream = |chainstate: &StacksChainState| {
+        NakamotoTenureStream::new(
+            chainstate,
+            nakamoto_header.block_id(),
+            nakamoto_header.consensus_hash.clone(),
+            nakamoto_header.parent_block_id.clone(),
+            None,
+        )
+        .unwrap()
+    };
+    let blocks = drain_tenure_stream(&mut make_stream(peer.chainstate()));
+    assert_eq!(blocks.len(), 10);
+
+    // cut off the stored bytes of the tenure's fourth-newest block
+    let corrupt_block = blocks[3].clone();
+    let chainstate = peer.chainstate();
+    let staging_db_path = chainstate.get_nakamoto_staging_blocks_path().unwrap();
+    let staging_conn =
+        StacksChainState::open_nakamoto_staging_blocks(&staging_db_path, true).unwrap();
+    let block_bytes = corrupt_block.serialize_to_vec();
+    staging_conn
+        .conn()
+        .execute(
+            "UPDATE nakamoto_staging_blocks SET data = ?1 WHERE index_block_hash = ?2",
+            params![
+                &block_bytes[..block_bytes.len() / 2],
+                corrupt_block.block_id()
+            ],
+        )
+        .unwrap();
+
+    // the blocks before the corrupt one are sent, and then the stream fails, naming it,
+    // rather than ending as if the tenure had been sent
+    let mut stream = make_stream(peer.chainstate());
+    let mut sent_bytes = vec![];
+    let err = loop {
+        match stream.generate_next_chunk() {
+            Ok(chunk) => {
+                assert!(!chunk.is_empty(), "stream ended without an error");
+                sent_bytes.extend(chunk);
+            }
+            Err(e) => break e,
+        }
+    };
+    assert!(err.contains(&corrupt_block.block_id().to_string()));
+    assert_eq!(stream.corrupt_block_id, Some(corrupt_block.block_id()));
+    let ptr = &mut sent_bytes.as_slice();
+    let mut sent_blocks = vec![];
+    while !ptr.is_empty() {
+        sent_blocks.push(NakamotoBlock::consensus_deserialize(ptr).unwrap());
+    }
+    assert_eq!(sent_blocks, blocks[..3]);
+
+    // ...and again, without sending any more of the tenure
+    assert!(stream.generate_next_chunk().is_err());
+
+    // an archive ends with a manifest naming the corrupt block
+    let mut stream = make_stream(peer.chainstate()).with_archive_format();
+    let mut archive_bytes = vec![];
+    loop {
+        let next_bytes = stream.generate_next_chunk().unwrap();
+        if next_bytes.is_empty() {
+            break;
+        }
+        archive_bytes.extend(next_bytes);
+    }
+    let archive = decode_tenure_archive(&archive_bytes).unwrap();
+    assert_eq!(archive.blocks, blocks[..3]);
+    assert_eq!(
+        archive.manifest,
+        TenureArchiveManifest {
+            consensus_hash: nakamoto_header.consensus_hash.clone(),
+            block_ids: blocks[..3].iter().map(|block| block.block_id()).collect(),
+            truncated: false,
+            corrupt_block_id: Some(corrupt_block.block_id()),
+        }
+    );
+
+    // a stream that would start with the corrupt block finds it before sending anything
+    let stream = NakamotoTenureStream::new(
+        peer.chainstate(),
+        corrupt_block.block_id(),
+        corrupt_block.header.consensus_hash.clone(),
+        corrupt_block.header.parent_block_id.clone(),
+        None,
+    )
+    .unwrap();
+    assert!(!stream.block_stream.is_block_intact().unwrap());
+    assert!(make_stream(peer.chainstate())
+        .block_stream
+        .is_block_intact()
+        .unwrap());
+}
+
+/// Read everything a tenure stream has to send right now, and decode it into blocks
+fn drain_tenure_stream(stream: &mut NakamotoTenureStream) -> Vec<NakamotoBlock> {
+    let mut all_block_bytes = vec![];
+    loop {
+        let mut next_bytes = stream.generate_next_chunk().unwrap();
+        if next_bytes.is_empty() {
+            break;
+        }
+        all_block_bytes.append(&mut next_bytes);
+    }
+
+    let ptr = &mut all_block_bytes.as_slice();
+    let mut blocks = vec![];
+    while ptr.len() > 0 {
+        blocks.push(NakamotoBlock::consensus_deserialize(ptr).unwrap());
+    }
+    blocks
+}
+
+/// Stream a tenure from the given block, and then follow it for up to `hold`
+fn make_follow_stream(
+    chainstate: &StacksChainState,
+    header: &NakamotoBlockHeader,
+    hold: Duration,
+) -> NakamotoTenureStream {
+    NakamotoTenureStream::new(
+        chainstate,
+        header.block_id(),
+        header.consensus_hash.clone(),
+        header.parent_block_id.clone(),
+        None,
+    )
+    .unwrap()
+    .with_follow(hold)
+}
+
+#[test]
+fn test_stream_nakamoto_tenure_follow() {
+    let test_observer = TestEventObserver::new();
+    let bitvecs = vec![vec![
+        true, true, true, true, true, true, true, true, true, true,
+    ]];
+
+    let mut peer =
+        make_nakamoto_peer_from_invs(function_name!(), &test_observer, 10, 3, bitvecs.clone());
+
+    let nakamoto_tip = {
+        let sortdb = peer.sortdb.take().unwrap();
+        let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn()).unwrap();
+        let ih = sortdb.index_handle(&tip.sortition_id);
+        let nakamoto_tip = ih.get_nakamoto_tip().unwrap().unwrap();
+        peer.sortdb = Some(sortdb);
+        nakamoto_tip
+    };
+
+    // the last two tenures' block headers, newest first
+    let mut headers = vec![];
+    let mut block_id = StacksBlockId::new(&nakamoto_tip.0, &nakamoto_tip.1);
+    let mut tenures = vec![];
+    while tenures.len() <= 2 {
+        let header =
+            NakamotoChainState::get_block_header_nakamoto(peer.chainstate().db(), &block_id)
+                .unwrap()
+                .unwrap()
+                .anchored_header
+                .as_stacks_nakamoto()
+                .cloned()
+                .unwrap();
+        if tenures.last() != Some(&header.consensus_hash) {
+            tenures.push(header.consensus_hash.clone());
+        }
+        if tenures.len() <= 2 {
+            block_id = header.parent_block_id.clone();
+            headers.push(header);
+        }
+    }
+    let (tip_tenure, prev_tenure): (Vec<_>, Vec<_>) = headers
+        .into_iter()
+        .partition(|header| header.consensus_hash == tenures[0]);
+    assert!(tip_tenure.len() > 2);
+    assert!(prev_tenure.len() > 2);
+
+    // following from the middle of the ongoing tenure sends the blocks before it, then the
+    // blocks after it, and then waits for more
+    let mid = tip_tenure.len() / 2;
+    let mut stream =
+        make_follow_stream(peer.chainstate(), &tip_tenure[mid], Duration::from_secs(2));
+    let blocks = drain_tenure_stream(&mut stream);
+    let block_ids: Vec<_> = blocks.iter().map(|block| block.header.block_id()).collect();
+    let expected: Vec<_> = tip_tenure[mid..]
+        .iter()
+        .chain(tip_tenure[..mid].iter().rev())
+        .map(|header| header.block_id())
+        .collect();
+    assert_eq!(block_ids, expected);
+    assert!(stream.is_waiting());
+
+    // nothing new arrives, so the stream ends once the hold time is up
+    assert!(drain_tenure_stream(&mut stream).is_empty());
+    thread::sleep(Duration::from_secs(3));
+    assert!(drain_tenure_stream(&mut stream).is_empty());
+    assert!(!stream.is_waiting());
+
+    // following a tenure that's over ends once its last block is sent
+    let mid = prev_tenure.len() / 2;
+    let mut stream = make_follow_stream(
+        peer.chainstate(),
+        &prev_tenure[mid],
+        Duration::from_secs(60),
+    );
+    let blocks = drain_tenure_stream(&mut stream);
+    assert_eq!(blocks.len(), prev_tenure.len());
+    assert_eq!(
+        blocks.last().unwrap().header.block_id(),
+        prev_tenure[0].block_id()
+    );
+    assert!(!stream.is_waiting());
+}
@@ -0,0 +1,197 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use stacks_common::types::chainstate::StacksBlockId;
+
+use super::TestRPC;
+use crate::chainstate::nakamoto::NakamotoChainState;
+use crate::net::api::gettenureblockids::MAX_TENURE_BLOCK_IDS;
+use crate::net::api::*;
+use crate::net::connection::ConnectionOptions;
+use crate::net::httpcore::{RPCRequestHandler, StacksHttp, StacksHttpRequest};
+use crate::net::test::TestEventObserver;
+use crate::net::ProtocolFamily;
+
+#[test]
+fn test_try_parse_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let request = StacksHttpRequest::new_get_nakamoto_tenure_block_ids(
+        addr.into(),
+        &StacksBlockId([0x11; 32]),
+        Some(5),
+    );
+    let bytes = request.try_serialize().unwrap();
+
+    debug!("Request:\n{}\n", std::str::from_utf8(&bytes).unwrap());
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = gettenureblockids::RPCNakamotoTenureBlockIdsRequestHandler::new();
+    let mut parsed_request = http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .unwrap();
+
+    // parsed request consumes headers that would not be in a constructed reqeuest
+    parsed_request.clear_headers();
+    let (preamble, _contents) = parsed_request.destruct();
+
+    // consumed path args and query
+    assert_eq!(handler.block_id, Some(StacksBlockId([0x11; 32])));
+    assert_eq!(handler.limit, 5);
+
+    assert_eq!(&preamble, request.preamble());
+
+    handler.restart();
+    assert!(handler.block_id.is_none());
+    assert_eq!(handler.limit, MAX_TENURE_BLOCK_IDS);
+
+    // the limit is clamped to the most IDs a response lists
+    let request = StacksHttpRequest::new_get_nakamoto_tenure_block_ids(
+        addr.into(),
+        &StacksBlockId([0x11; 32]),
+        Some(MAX_TENURE_BLOCK_IDS + 1),
+    );
+    let bytes = request.try_serialize().unwrap();
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    http.handle_try_parse_request(
+        &mut handler,
+        &parsed_preamble.expect_request(),
+        &bytes[offset..],
+    )
+    .unwrap();
+    assert_eq!(handler.limit, MAX_TENURE_BLOCK_IDS);
+
+    // but a limit of 0 would never make progress
+    let request = StacksHttpRequest::new_get_nakamoto_tenure_block_ids(
+        addr.into(),
+        &StacksBlockId([0x11; 32]),
+        Some(0),
+    );
+    let bytes = request.try_serialize().unwrap();
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    assert!(http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .is_err());
+}
+
+#[test]
+fn test_try_make_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let test_observer = TestEventObserver::new();
+    let mut rpc_test = TestRPC::setup_nakamoto(function_name!(), &test_observer);
+
+    let nakamoto_chain_tip = rpc_test.canonical_tip.clone();
+    let consensus_hash = rpc_test.consensus_hash.clone();
+
+    // the tenure's blocks, highest first, as found from their headers
+    let mut expected_block_ids = vec![];
+    let mut cursor = Some(nakamoto_chain_tip.clone());
+    while let Some(block_id) = cursor.take() {
+        let header = NakamotoChainState::get_block_header_nakamoto(
+            rpc_test.peer_1.chainstate().db(),
+            &block_id,
+        )
+        .unwrap()
+        .unwrap();
+        let header = header.anchored_header.as_stacks_nakamoto().unwrap().clone();
+        if header.consensus_hash != consensus_hash {
+            break;
+        }
+        expected_block_ids.push(block_id);
+        cursor = Some(header.parent_block_id);
+    }
+    assert_eq!(expected_block_ids.len(), 10);
+
+    // an epoch2 block
+    let all_sortitions = rpc_test.peer_1.sortdb().get_all_snapshots().unwrap();
+    assert!(all_sortitions[30].sortition);
+    let epoch2_block_id = StacksBlockId::new(
+        &all_sortitions[30].consensus_hash,
+        &all_sortitions[30].winning_stacks_block_hash,
+    );
+
+    let requests = vec![
+        // the whole tenure
+        StacksHttpRequest::new_get_nakamoto_tenure_block_ids(
+            addr.into(),
+            &nakamoto_chain_tip,
+            None,
+        ),
+        // the first page of the tenure
+        StacksHttpRequest::new_get_nakamoto_tenure_block_ids(
+            addr.into(),
+            &nakamoto_chain_tip,
+            Some(4),
+        ),
+        // the last page of the tenure
+        StacksHttpRequest::new_get_nakamoto_tenure_block_ids(
+            addr.into(),
+            &expected_block_ids[8],
+            Some(4),
+        ),
+        // a non-existant block
+        StacksHttpRequest::new_get_nakamoto_tenure_block_ids(
+            addr.into(),
+            &StacksBlockId([0x11; 32]),
+            None,
+        ),
+        StacksHttpRequest::new_get_nakamoto_tenure_block_ids(addr.into(), &epoch2_block_id, None),
+    ];
+    let mut responses = rpc_test.run(requests);
+
+    let resp = responses
+        .remove(0)
+        .decode_nakamoto_tenure_block_ids()
+        .unwrap();
+    assert_eq!(resp.consensus_hash, consensus_hash);
+    assert_eq!(resp.block_ids, expected_block_ids);
+    assert_eq!(resp.next_block_id, None);
+
+    // a page that doesn't reach the tenure's first block says where to continue from
+    let resp = responses
+        .remove(0)
+        .decode_nakamoto_tenure_block_ids()
+        .unwrap();
+    assert_eq!(resp.block_ids, expected_block_ids[..4]);
+    assert_eq!(resp.next_block_id.as_ref(), Some(&expected_block_ids[4]));
+
+    let resp = responses
+        .remove(0)
+        .decode_nakamoto_tenure_block_ids()
+        .unwrap();
+    assert_eq!(resp.block_ids, expected_block_ids[8..]);
+    assert_eq!(resp.next_block_id, None);
+
+    // no block
+    let (preamble, _body) = responses.remove(0).destruct();
+    assert_eq!(preamble.status_code, 404);
+
+    // the block exists, but it has no tenure
+    let (preamble, _body) = responses.remove(0).destruct();
+    assert_eq!(preamble.status_code, 404);
+}
@@ -0,0 +1,136 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use stacks_common::codec::StacksMessageCodec;
+use stacks_common::types::chainstate::{ConsensusHash, StacksBlockId};
+
+use crate::chainstate::stacks::TransactionPayload;
+use crate::net::api::gettenurechange::{
+    RPCNakamotoTenureChangeRequestHandler, TenureChangeTenureId,
+};
+use crate::net::api::tests::TestRPC;
+use crate::net::connection::ConnectionOptions;
+use crate::net::httpcore::{StacksHttp, StacksHttpRequest};
+use crate::net::test::TestEventObserver;
+use crate::net::ProtocolFamily;
+
+#[test]
+fn test_try_parse_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    for tenure_id in [
+        TenureChangeTenureId::ConsensusHash(ConsensusHash([0x01; 20])),
+        TenureChangeTenureId::BlockId(StacksBlockId([0x02; 32])),
+    ] {
+        let request = StacksHttpRequest::new_get_tenure_change_tx(addr.into(), &tenure_id);
+        let bytes = request.try_serialize().unwrap();
+
+        debug!("Request:\n{}\n", std::str::from_utf8(&bytes).unwrap());
+
+        let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+
+        let mut handler = RPCNakamotoTenureChangeRequestHandler::new();
+        let mut parsed_request = http
+            .handle_try_parse_request(
+                &mut handler,
+                &parsed_preamble.expect_request(),
+                &bytes[offset..],
+            )
+            .unwrap();
+        assert_eq!(handler.tenure_id, Some(tenure_id));
+
+        // parsed request consumes headers that would not be in a constructed reqeuest
+        parsed_request.clear_headers();
+        let (preamble, _contents) = parsed_request.destruct();
+
+        assert_eq!(&preamble, request.preamble());
+    }
+}
+
+#[test]
+fn test_try_make_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let test_observer = TestEventObserver::new();
+    let rpc_test = TestRPC::setup_nakamoto(function_name!(), &test_observer);
+
+    let nakamoto_chain_tip = rpc_test.canonical_tip.clone();
+    let consensus_hash = rpc_test.consensus_hash.clone();
+
+    let mut requests = vec![];
+
+    // query the tenure by consensus hash
+    let request = StacksHttpRequest::new_get_tenure_change_tx(
+        addr.clone().into(),
+        &TenureChangeTenureId::ConsensusHash(consensus_hash.clone()),
+    );
+    requests.push(request);
+
+    // query the tenure by one of its blocks
+    let request = StacksHttpRequest::new_get_tenure_change_tx(
+        addr.clone().into(),
+        &TenureChangeTenureId::BlockId(nakamoto_chain_tip.clone()),
+    );
+    requests.push(request);
+
+    // query non-existant tenure
+    let request = StacksHttpRequest::new_get_tenure_change_tx(
+        addr.clone().into(),
+        &TenureChangeTenureId::ConsensusHash(ConsensusHash([0x01; 20])),
+    );
+    requests.push(request);
+
+    // query non-existant block
+    let request = StacksHttpRequest::new_get_tenure_change_tx(
+        addr.clone().into(),
+        &TenureChangeTenureId::BlockId(StacksBlockId([0x02; 32])),
+    );
+    requests.push(request);
+
+    let mut responses = rpc_test.run(requests);
+
+    // got the tenure-change by consensus hash
+    let response = responses.remove(0);
+    let content_length = response.preamble().content_length;
+    let tx = response.decode_tenure_change_tx().unwrap();
+    let TransactionPayload::TenureChange(payload) = &tx.payload else {
+        panic!("Not a tenure-change transaction: {:?}", &tx);
+    };
+    assert_eq!(payload.tenure_consensus_hash, consensus_hash);
+    assert_eq!(
+        content_length,
+        Some(u32::try_from(tx.serialize_to_vec().len()).unwrap())
+    );
+
+    // got the same tenure-change by block ID
+    let response = responses.remove(0);
+    let tx_by_block_id = response.decode_tenure_change_tx().unwrap();
+    assert_eq!(tx_by_block_id, tx);
+
+    // got failures
+    for _ in 0..2 {
+        let response = responses.remove(0);
+        debug!(
+            "Response:\n{}\n",
+            std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+        );
+
+        let (preamble, _body) = response.destruct();
+        assert_eq!(preamble.status_code, 404);
+    }
+}
@@ -0,0 +1,132 @@
+// Copyright (C) 2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use crate::chainstate::nakamoto::NakamotoChainState;
+use crate::net::api::tests::TestRPC;
+use crate::net::api::*;
+use crate::net::connection::ConnectionOptions;
+use crate::net::httpcore::{
+    HttpPreambleExtensions, HttpRequestContentsExtensions, RPCRequestHandler, StacksHttp,
+    StacksHttpRequest,
+};
+use crate::net::test::TestEventObserver;
+use crate::net::ProtocolFamily;
+
+#[test]
+fn test_try_parse_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let request = StacksHttpRequest::new_get_nakamoto_highest_complete_tenure(addr.into());
+
+    let bytes = request.try_serialize().unwrap();
+
+    debug!("Request:\n{}\n", std::str::from_utf8(&bytes).unwrap());
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut parsed_request = http
+        .try_parse_request(&parsed_preamble.expect_request(), &bytes[offset..])
+        .unwrap();
+
+    // parsed request consumes headers that would not be in a constructed reqeuest
+    parsed_request.clear_headers();
+    let (preamble, contents) = parsed_request.destruct();
+
+    assert_eq!(&preamble, request.preamble());
+}
+
+#[test]
+fn test_try_make_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let test_observer = TestEventObserver::new();
+    let mut rpc_test = TestRPC::setup_nakamoto(function_name!(), &test_observer);
+
+    // the tip's tenure is still in progress, so the highest complete tenure is the one that its
+    // first block builds on
+    let chainstate = rpc_test.peer_1.chainstate();
+    let tip_tenure_start = NakamotoChainState::get_tenure_start_block_header(
+        &mut chainstate.index_conn(),
+        &rpc_test.canonical_tip,
+        &rpc_test.consensus_hash,
+    )
+    .unwrap()
+    .unwrap();
+    let last_block_id = tip_tenure_start
+        .anchored_header
+        .as_stacks_nakamoto()
+        .unwrap()
+        .parent_block_id
+        .clone();
+    let last_header = NakamotoChainState::get_block_header(chainstate.db(), &last_block_id)
+        .unwrap()
+        .unwrap();
+    let first_header = NakamotoChainState::get_tenure_start_block_header(
+        &mut chainstate.index_conn(),
+        &last_block_id,
+        &last_header.consensus_hash,
+    )
+    .unwrap()
+    .unwrap();
+    assert_ne!(last_header.consensus_hash, rpc_test.consensus_hash);
+    assert!(first_header.stacks_block_height <= last_header.stacks_block_height);
+
+    let requests = vec![StacksHttpRequest::new_get_nakamoto_highest_complete_tenure(
+        addr.into(),
+    )];
+    let mut responses = rpc_test.run(requests);
+
+    let response = responses.remove(0);
+    debug!(
+        "Response:\n{}\n",
+        std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+    );
+
+    let resp = response.decode_nakamoto_highest_complete_tenure().unwrap();
+    assert_eq!(
+        resp,
+        gettenurecomplete::RPCHighestCompleteTenure {
+            consensus_hash: last_header.consensus_hash.clone(),
+            tenure_start_block_id: first_header.index_block_hash(),
+            last_block_id,
+            last_block_height: last_header.stacks_block_height,
+            is_nakamoto: true,
+        }
+    );
+}
+
+#[test]
+fn test_try_make_response_epoch2() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let rpc_test = TestRPC::setup(function_name!());
+    let requests = vec![StacksHttpRequest::new_get_nakamoto_highest_complete_tenure(
+        addr.into(),
+    )];
+    let mut responses = rpc_test.run(requests);
+
+    let response = responses.remove(0);
+    debug!(
+        "Response:\n{}\n",
+        std::str::from_utf8(&response.try_serialize().unwrap()).unwrap()
+    );
+
+    // every epoch2 block is a complete tenure of its own
+    let resp = response.decode_nakamoto_highest_complete_tenure().unwrap();
+    assert!(!resp.is_nakamoto);
+    assert_eq!(resp.tenure_start_block_id, resp.last_block_id);
+}
@@ -0,0 +1,190 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use stacks_common::codec::StacksMessageCodec;
+use stacks_common::types::chainstate::StacksBlockId;
+
+use super::TestRPC;
+use crate::chainstate::burn::db::sortdb::{SortitionDB, SortitionHandle};
+use crate::chainstate::nakamoto::{NakamotoBlock, NakamotoChainState};
+use crate::chainstate::stacks::Error as ChainError;
+use crate::net::api::gettenurefull::NakamotoFullTenureStream;
+use crate::net::api::*;
+use crate::net::connection::ConnectionOptions;
+use crate::net::http::HttpChunkGenerator;
+use crate::net::httpcore::{
+    HttpPreambleExtensions, RPCRequestHandler, StacksHttp, StacksHttpRequest,
+};
+use crate::net::test::TestEventObserver;
+use crate::net::tests::inv::nakamoto::make_nakamoto_peer_from_invs;
+use crate::net::ProtocolFamily;
+
+#[test]
+fn test_try_parse_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let request =
+        StacksHttpRequest::new_get_nakamoto_full_tenure(addr.into(), &StacksBlockId([0x11; 32]));
+    let bytes = request.try_serialize().unwrap();
+
+    debug!("Request:\n{}\n", std::str::from_utf8(&bytes).unwrap());
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = gettenurefull::RPCNakamotoFullTenureRequestHandler::new();
+    let mut parsed_request = http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .unwrap();
+
+    // parsed request consumes headers that would not be in a constructed reqeuest
+    parsed_request.clear_headers();
+    let (preamble, _contents) = parsed_request.destruct();
+
+    // consumed path args
+    assert_eq!(handler.block_id, Some(StacksBlockId([0x11; 32])));
+
+    assert_eq!(&preamble, request.preamble());
+
+    handler.restart();
+    assert!(handler.block_id.is_none());
+}
+
+#[test]
+fn test_try_make_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let test_observer = TestEventObserver::new();
+    let rpc_test = TestRPC::setup_nakamoto(function_name!(), &test_observer);
+
+    let nakamoto_chain_tip = rpc_test.canonical_tip.clone();
+    let consensus_hash = rpc_test.consensus_hash.clone();
+
+    let mut requests = vec![];
+
+    // query existing tenure
+    let request = StacksHttpRequest::new_get_nakamoto_full_tenure(addr.into(), &nakamoto_chain_tip);
+    requests.push(request);
+
+    // query non-existant block
+    let request =
+        StacksHttpRequest::new_get_nakamoto_full_tenure(addr.into(), &StacksBlockId([0x11; 32]));
+    requests.push(request);
+
+    let mut responses = rpc_test.run(requests);
+
+    // got the tenure, ending at the tip
+    let response = responses.remove(0);
+    let resp = response.decode_nakamoto_full_tenure().unwrap();
+    assert!(!resp.is_empty());
+    assert_eq!(resp.last().unwrap().header.block_id(), nakamoto_chain_tip);
+    for block in resp.iter() {
+        assert_eq!(block.header.consensus_hash, consensus_hash);
+    }
+
+    // no block
+    let response = responses.remove(0);
+    let (preamble, _body) = response.destruct();
+    assert_eq!(preamble.status_code, 404);
+}
+
+fn read_tenure(stream: &mut NakamotoFullTenureStream) -> Vec<NakamotoBlock> {
+    let mut all_block_bytes = vec![];
+    loop {
+        let mut next_bytes = stream.generate_next_chunk().unwrap();
+        if next_bytes.is_empty() {
+            break;
+        }
+        all_block_bytes.append(&mut next_bytes);
+    }
+
+    let ptr = &mut all_block_bytes.as_slice();
+    let mut blocks = vec![];
+    while ptr.len() > 0 {
+        let block = NakamotoBlock::consensus_deserialize(ptr).unwrap();
+        blocks.push(block);
+    }
+    blocks
+}
+
+#[test]
+fn test_stream_nakamoto_full_tenure() {
+    let test_observer = TestEventObserver::new();
+    let bitvecs = vec![vec![
+        true, true, true, true, true, true, true, true, true, true,
+    ]];
+
+    let mut peer =
+        make_nakamoto_peer_from_invs(function_name!(), &test_observer, 10, 3, bitvecs.clone());
+
+    // can't stream the tenure of a nonexistant block
+    assert!(matches!(
+        NakamotoFullTenureStream::new(peer.chainstate(), &StacksBlockId([0x11; 32])),
+        Err(ChainError::NoSuchBlockError)
+    ));
+
+    let nakamoto_tip = {
+        let sortdb = peer.sortdb.take().unwrap();
+        let tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn()).unwrap();
+        let ih = sortdb.index_handle(&tip.sortition_id);
+        let nakamoto_tip = ih.get_nakamoto_tip().unwrap().unwrap();
+        peer.sortdb = Some(sortdb);
+        nakamoto_tip
+    };
+    let nakamoto_tip_block_id = StacksBlockId::new(&nakamoto_tip.0, &nakamoto_tip.1);
+
+    let mut stream =
+        NakamotoFullTenureStream::new(peer.chainstate(), &nakamoto_tip_block_id).unwrap();
+    let blocks = read_tenure(&mut stream);
+
+    // the whole tenure, oldest block first
+    assert_eq!(blocks.len(), 10);
+    assert_eq!(
+        blocks.last().unwrap().header.block_id(),
+        nakamoto_tip_block_id
+    );
+    assert!(blocks
+        .first()
+        .unwrap()
+        .is_wellformed_tenure_start_block()
+        .unwrap());
+    for pair in blocks.windows(2) {
+        assert_eq!(pair[1].header.parent_block_id, pair[0].header.block_id());
+        assert_eq!(pair[1].header.consensus_hash, pair[0].header.consensus_hash);
+    }
+    let first_parent = NakamotoChainState::get_block_header(
+        peer.chainstate().db(),
+        &blocks.first().unwrap().header.parent_block_id,
+    )
+    .unwrap()
+    .unwrap();
+    assert_ne!(
+        first_parent.consensus_hash,
+        blocks.first().unwrap().header.consensus_hash
+    );
+
+    // the same tenure is streamed given any of its blocks
+    for block in blocks.iter() {
+        let mut stream =
+            NakamotoFullTenureStream::new(peer.chainstate(), &block.header.block_id()).unwrap();
+        assert_eq!(read_tenure(&mut stream), blocks);
+    }
+}
@@ -0,0 +1,119 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+use stacks_common::types::chainstate::ConsensusHash;
+
+use super::TestRPC;
+use crate::net::api::*;
+use crate::net::connection::ConnectionOptions;
+use crate::net::httpcore::{RPCRequestHandler, StacksHttp, StacksHttpRequest};
+use crate::net::test::TestEventObserver;
+use crate::net::ProtocolFamily;
+
+#[test]
+fn test_try_parse_request() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+    let mut http = StacksHttp::new(addr.clone(), &ConnectionOptions::default());
+
+    let request = StacksHttpRequest::new_get_nakamoto_tenure_tip_blocks(
+        addr.into(),
+        &ConsensusHash([0x01; 20]),
+    );
+    let bytes = request.try_serialize().unwrap();
+
+    debug!("Request:\n{}\n", std::str::from_utf8(&bytes).unwrap());
+
+    let (parsed_preamble, offset) = http.read_preamble(&bytes).unwrap();
+    let mut handler = gettenuretipblocks::RPCNakamotoTenureTipBlocksRequestHandler::new();
+    let mut parsed_request = http
+        .handle_try_parse_request(
+            &mut handler,
+            &parsed_preamble.expect_request(),
+            &bytes[offset..],
+        )
+        .unwrap();
+
+    // parsed request consumes headers that would not be in a constructed reqeuest
+    parsed_request.clear_headers();
+    let (preamble, _contents) = parsed_request.destruct();
+
+    // consumed path args
+    assert_eq!(handler.consensus_hash, Some(ConsensusHash([0x01; 20])));
+
+    assert_eq!(&preamble, request.preamble());
+
+    handler.restart();
+    assert!(handler.consensus_hash.is_none());
+}
+
+#[test]
+fn test_try_make_response() {
+    let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 33333);
+
+    let test_observer = TestEventObserver::new();
+    let mut rpc_test = TestRPC::setup_nakamoto(function_name!(), &test_observer);
+
+    let nakamoto_chain_tip = rpc_test.canonical_tip.clone();
+    let consensus_hash = rpc_test.consensus_hash.clone();
+
+    // an epoch2 tenure
+    let all_sortitions = rpc_test.peer_1.sortdb().get_all_snapshots().unwrap();
+    assert!(all_sortitions[30].sortition);
+    let epoch2_consensus_hash = all_sortitions[30].consensus_hash.clone();
+
+    let requests = vec![
+        // the tenure, from its highest block
+        StacksHttpRequest::new_get_nakamoto_tenure_tip_blocks(addr.into(), &consensus_hash),
+        // the same tenure, from the block known to be its highest
+        StacksHttpRequest::new_get_nakamoto_tenure(addr.into(), nakamoto_chain_tip.clone(), None),
+        // an unknown tenure
+        StacksHttpRequest::new_get_nakamoto_tenure_tip_blocks(
+            addr.into(),
+            &ConsensusHash([0x11; 20]),
+        ),
+        StacksHttpRequest::new_get_nakamoto_tenure_tip_blocks(addr.into(), &epoch2_consensus_hash),
+    ];
+    let mut responses = rpc_test.run(requests);
+
+    let blocks = responses
+        .remove(0)
+        .decode_nakamoto_tenure_tip_blocks()
+        .unwrap();
+    assert_eq!(
+        blocks.first().unwrap().header.block_id(),
+        nakamoto_chain_tip
+    );
+    for block in blocks.iter() {
+        assert_eq!(block.header.consensus_hash, consensus_hash);
+    }
+    let expected_blocks = responses.remove(0).decode_nakamoto_tenure().unwrap();
+    assert_eq!(blocks, expected_blocks);
+    assert!(blocks
+        .last()
+        .unwrap()
+        .is_wellformed_tenure_start_block()
+        .unwrap());
+
+    // no such tenure
+    let (preamble, _body) = responses.remove(0).destruct();
+    assert_eq!(preamble.status_code, 404);
+
+    // the tenure exists, but it has no Nakamoto blocks to stream
+    let (preamble, _body) = responses.remove(0).destruct();
+    assert_eq!(preamble.status_code, 409);
+}
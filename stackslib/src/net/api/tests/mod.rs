@@ -54,20 +54,26 @@ use crate::net::{
 };
 
 mod callreadonly;
+mod conditional;
 mod get_tenures_fork_info;
 mod getaccount;
 mod getattachment;
 mod getattachmentsinv;
 mod getblock;
 mod getblock_v3;
+mod getblockbatch;
+mod getblockrange;
+mod getblocksignatures;
 mod getconstantval;
 mod getcontractabi;
 mod getcontractsrc;
 mod getdatavar;
+mod geteventhealth;
 mod getheaders;
 mod getinfo;
 mod getistraitimplemented;
 mod getmapentry;
+mod getmempooldrops;
 mod getmicroblocks_confirmed;
 mod getmicroblocks_indexed;
 mod getmicroblocks_unconfirmed;
@@ -79,8 +85,13 @@ mod getstackerdbchunk;
 mod getstackerdbmetadata;
 mod getstxtransfercost;
 mod gettenure;
+mod gettenureblockids;
+mod gettenurechange;
+mod gettenurecomplete;
+mod gettenurefull;
 mod gettenureinfo;
 mod gettenuretip;
+mod gettenuretipblocks;
 mod gettransaction_unconfirmed;
 mod liststackerdbreplicas;
 mod postblock;
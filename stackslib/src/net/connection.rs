@@ -328,6 +328,9 @@ pub struct ConnectionOptions {
     pub handshake_timeout: u64,
     pub timeout: u64,
     pub idle_timeout: u64,
+    /// How long (in seconds) an HTTP reply may wait for the client to make room for its next
+    /// chunk before the connection is dropped
+    pub stream_stall_timeout: u64,
     pub heartbeat: u32,
     pub private_key_lifetime: u64,
     pub num_neighbors: u64,
@@ -433,6 +436,11 @@ pub struct ConnectionOptions {
     pub nakamoto_unconfirmed_downloader_interval_ms: u128,
     /// The authorization token to enable privileged RPC endpoints
     pub auth_token: Option<String>,
+    /// If set, only callers from these IP addresses may stream tenures from `/v3/tenures`
+    pub tenure_stream_allowlist: Option<Vec<net::IpAddr>>,
+    /// How long (in milliseconds) a `/v3/tenures` stream may spend looking up the next block
+    /// to send before it ends early. Bounds how long a slow DB can hold up the RPC thread.
+    pub tenure_stream_db_budget_ms: u64,
 
     // fault injection
     /// Disable neighbor walk and discovery
@@ -490,6 +498,7 @@ impl std::default::Default for ConnectionOptions {
             handshake_timeout: 30, // how long before a peer must send a handshake, after connecting
             timeout: 30,         // how long to wait for a reply to a request
             idle_timeout: 15, // how long a non-request HTTP connection can be idle before it's closed
+            stream_stall_timeout: 30, // how long a streamed HTTP reply can wait for a slow client
             heartbeat: 3600,  // send a heartbeat once an hour by default
             private_key_lifetime: 4302, // key expires after ~1 month
             num_neighbors: 32, // how many outbound connections we can have, full-stop
@@ -563,6 +572,8 @@ impl std::default::Default for ConnectionOptions {
             nakamoto_inv_sync_burst_interval_ms: 1_000, // wait 1 second after a sortition before running inventory sync
             nakamoto_unconfirmed_downloader_interval_ms: 5_000, // run unconfirmed downloader once every 5 seconds
             auth_token: None,
+            tenure_stream_allowlist: None,
+            tenure_stream_db_budget_ms: 10_000, // how long a tenure stream may spend looking up its next block
 
             // no faults on by default
             disable_neighbor_walk: false,
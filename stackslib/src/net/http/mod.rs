@@ -153,6 +153,7 @@ pub enum HttpContentType {
     Bytes,
     Text,
     JSON,
+    Protobuf,
 }
 
 impl fmt::Display for HttpContentType {
@@ -167,6 +168,7 @@ impl HttpContentType {
             HttpContentType::Bytes => "application/octet-stream",
             HttpContentType::Text => "text/plain",
             HttpContentType::JSON => "application/json",
+            HttpContentType::Protobuf => "application/x-protobuf",
         }
     }
 }
@@ -182,6 +184,8 @@ impl FromStr for HttpContentType {
             Ok(HttpContentType::Text)
         } else if s == "application/json" || s.starts_with("application/json;") {
             Ok(HttpContentType::JSON)
+        } else if s == "application/x-protobuf" {
+            Ok(HttpContentType::Protobuf)
         } else {
             Err(CodecError::DeserializeError(format!(
                 "Unsupported HTTP content type: {header}"
@@ -314,8 +314,11 @@ impl StacksMessageCodec for HttpRequestPreamble {
             }
         }
 
-        fd.write_all(default_accept_header().as_bytes())
-            .map_err(CodecError::WriteError)?;
+        // unless the request asks for something else
+        if !self.headers.contains_key("accept") {
+            fd.write_all(default_accept_header().as_bytes())
+                .map_err(CodecError::WriteError)?;
+        }
 
         // other headers
         write_headers(fd, &self.headers)?;
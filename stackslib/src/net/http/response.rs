@@ -61,6 +61,8 @@ pub struct HttpResponsePreamble {
 pub struct HttpStreamState {
     encoder_state: Option<HttpChunkedTransferWriterState>,
     generator: Box<dyn HttpChunkGenerator>,
+    /// how many bytes the generator produces at a time
+    chunk_size: usize,
 }
 
 /// HTTP response body generated by the request handler.  It implements a means of streaming data from disk
@@ -87,6 +89,7 @@ impl HttpResponseContents {
         HttpResponseContents::Stream(HttpStreamState {
             generator,
             encoder_state: Some(HttpChunkedTransferWriterState::new(chunk_size)),
+            chunk_size,
         })
     }
 
@@ -111,6 +114,29 @@ impl HttpResponseContents {
         }
     }
 
+    /// Is this a stream that has no data to send right now, but may have more later?
+    pub fn is_waiting(&self) -> bool {
+        match self {
+            HttpResponseContents::Stream(ref inner_stream) => inner_stream.generator.is_waiting(),
+            HttpResponseContents::RAM(..) => false,
+        }
+    }
+
+    /// Is there room to write the next chunk of this response to a pipe that already holds
+    /// `backlog` bytes the client hasn't taken, without buffering more than `buffer_size` bytes?
+    /// A stream only generates its next chunk once there is room for the whole chunk, so a
+    /// client that reads slowly holds up generation instead of making the pipe grow. There is
+    /// always room in an empty pipe.
+    pub fn has_room(&self, backlog: usize, buffer_size: u32) -> bool {
+        let next_chunk_size = match self {
+            HttpResponseContents::Stream(ref inner_stream) => inner_stream.chunk_size,
+            HttpResponseContents::RAM(ref buf) => buf.len(),
+        };
+        backlog == 0
+            || backlog.saturating_add(next_chunk_size)
+                <= usize::try_from(buffer_size).unwrap_or(usize::MAX)
+    }
+
     /// Write data for this to a pipe writer, which buffers it up.
     /// Return Ok(Some(..)) if there is mroe data to send.
     /// Once all data is sent, return Ok(None)
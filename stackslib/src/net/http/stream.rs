@@ -36,9 +36,16 @@ pub trait HttpChunkGenerator: Send {
     fn generate_next_chunk(&mut self) -> Result<Vec<u8>, String>;
     fn hint_chunk_size(&self) -> usize;
 
+    /// Is the generator waiting for more data to become available?
+    /// If so, an empty chunk does not end the stream, and the caller should try again later.
+    fn is_waiting(&self) -> bool {
+        false
+    }
+
     /// Stream one chunk to the pipe writer.  This never blocks.
     /// Returns Ok(num-bytes > 0) if there are more chunks (i.e. the caller should call this again)
-    /// Returns Ok(0) if there are no more chunks (i.e. the caller should not call this again)
+    /// Returns Ok(0) if there are no more chunks (i.e. the caller should not call this again,
+    /// unless `is_waiting()` is true)
     /// Returns Err(..) on irrecoverable I/O error
     #[cfg_attr(test, mutants::skip)]
     fn stream_to(
@@ -54,8 +61,11 @@ pub trait HttpChunkGenerator: Send {
         let mut encoder = HttpChunkedTransferWriter::from_writer_state(fd, encoder_state);
 
         if chunk.is_empty() {
-            // no more chunks, but be sure to cork the stream
-            if !encoder.corked() {
+            if self.is_waiting() {
+                // no more chunks yet, so send what we have without ending the stream
+                encoder.flush_buffered()?;
+            } else if !encoder.corked() {
+                // no more chunks, but be sure to cork the stream
                 encoder.flush()?;
                 encoder.cork();
             }
@@ -18,6 +18,8 @@ use stacks_common::codec::StacksMessageCodec;
 use stacks_common::types::net::{PeerAddress, PeerHost};
 
 use crate::net::http::common::{HTTP_PREAMBLE_MAX_ENCODED_SIZE, HTTP_PREAMBLE_MAX_NUM_HEADERS};
+use crate::net::http::response::HttpResponseContents;
+use crate::net::http::stream::HttpChunkGenerator;
 use crate::net::http::{
     HttpContentType, HttpRequestPreamble, HttpReservedHeader, HttpResponsePreamble, HttpVersion,
 };
@@ -664,3 +666,33 @@ fn test_http_live_headers() {
         );
     }
 }
+
+struct FixedChunkGenerator(usize);
+
+impl HttpChunkGenerator for FixedChunkGenerator {
+    fn generate_next_chunk(&mut self) -> Result<Vec<u8>, String> {
+        Ok(vec![0u8; self.0])
+    }
+    fn hint_chunk_size(&self) -> usize {
+        self.0
+    }
+}
+
+#[test]
+fn test_http_response_contents_has_room() {
+    let stream = HttpResponseContents::from_stream(Box::new(FixedChunkGenerator(32)));
+
+    // an empty backlog always has room, even for a chunk bigger than the buffer
+    assert!(stream.has_room(0, 64));
+    assert!(stream.has_room(0, 16));
+
+    // otherwise, the next chunk must fit alongside the backlog
+    assert!(stream.has_room(32, 64));
+    assert!(!stream.has_room(33, 64));
+    assert!(!stream.has_room(64, 64));
+
+    let ram = HttpResponseContents::from_ram(vec![0u8; 10]);
+    assert!(ram.has_room(0, 8));
+    assert!(ram.has_room(54, 64));
+    assert!(!ram.has_room(55, 64));
+}
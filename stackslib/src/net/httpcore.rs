@@ -54,6 +54,7 @@ use crate::net::http::{
 };
 use crate::net::p2p::PeerNetwork;
 use crate::net::server::HttpPeer;
+use crate::net::tls::TlsClientConfig;
 use crate::net::{Error as NetError, MessageSequence, ProtocolFamily, StacksNodeState, UrlString};
 
 const CHUNK_BUF_LEN: usize = 32768;
@@ -897,6 +898,15 @@ impl HttpResponse for RPCArbitraryResponseHandler {
                 let text = String::from_utf8_lossy(&text_bytes).to_string();
                 Ok(HttpResponsePayload::Text(text))
             }
+            HttpContentType::Protobuf => {
+                let bytes = parse_raw_bytes(
+                    preamble,
+                    body,
+                    MAX_MESSAGE_LEN.into(),
+                    HttpContentType::Protobuf,
+                )?;
+                Ok(HttpResponsePayload::Bytes(bytes))
+            }
         }
     }
 }
@@ -981,6 +991,11 @@ impl StacksHttp {
         }
     }
 
+    /// Address of the peer on the other end of this connection
+    pub fn get_peer_addr(&self) -> &SocketAddr {
+        &self.peer_addr
+    }
+
     /// Register an API RPC endpoint
     pub fn register_rpc_endpoint<Handler: RPCRequestHandler + 'static>(
         &mut self,
@@ -1775,34 +1790,139 @@ pub fn decode_request_path(path: &str) -> Result<(String, String), NetError> {
     ))
 }
 
-/// Convert a NetError into an io::Error if appropriate.
-fn handle_net_error(e: NetError, msg: &str) -> io::Error {
-    match e {
-        NetError::ReadError(ioe) | NetError::WriteError(ioe) => ioe,
-        NetError::RecvTimeout => io::Error::new(io::ErrorKind::WouldBlock, "recv timeout"),
-        _ => io::Error::new(io::ErrorKind::Other, format!("{}: {:?}", &e, msg).as_str()),
+/// Why `send_http_request()` failed
+#[derive(Debug)]
+pub enum SendRequestError {
+    /// Failed to connect to the host, or the connection failed while in use
+    Connect(io::Error),
+    /// The connection was made, but the request and response did not complete in time
+    Timeout,
+    /// Failed to encode the request or decode the response
+    Protocol(String),
+    /// The host responded with an HTTP error status code, described by the message
+    HttpStatus(u16, String),
+    /// The host sent something other than an HTTP response
+    NonResponse,
+}
+
+impl fmt::Display for SendRequestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SendRequestError::Connect(ioe) => write!(f, "Connection failed: {}", ioe),
+            SendRequestError::Timeout => write!(f, "Timed out while receiving request"),
+            SendRequestError::Protocol(msg) => fmt::Display::fmt(msg, f),
+            SendRequestError::HttpStatus(_, msg) => fmt::Display::fmt(msg, f),
+            SendRequestError::NonResponse => write!(f, "Did not receive an HTTP response"),
+        }
+    }
+}
+
+impl std::error::Error for SendRequestError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SendRequestError::Connect(ioe) => Some(ioe),
+            _ => None,
+        }
+    }
+}
+
+impl From<SendRequestError> for io::Error {
+    fn from(e: SendRequestError) -> Self {
+        match e {
+            SendRequestError::Connect(ioe) => ioe,
+            SendRequestError::Timeout => io::Error::new(io::ErrorKind::WouldBlock, e.to_string()),
+            _ => io::Error::new(io::ErrorKind::Other, e.to_string()),
+        }
+    }
+}
+
+impl SendRequestError {
+    /// Classify a NetError from the connection that carries the request
+    fn from_net_error(e: NetError, msg: &str) -> Self {
+        match e {
+            NetError::ReadError(ioe) | NetError::WriteError(ioe) => match ioe.kind() {
+                // the socket's read or write timeout expired
+                io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => SendRequestError::Timeout,
+                _ => SendRequestError::Connect(ioe),
+            },
+            NetError::RecvTimeout => SendRequestError::Timeout,
+            // the host closed the connection before sending a whole response
+            NetError::PermanentlyDrained => SendRequestError::Connect(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "{}: connection closed before a full response was received",
+                    msg
+                ),
+            )),
+            _ => SendRequestError::Protocol(format!("{}: {:?}", &e, msg)),
+        }
+    }
+}
+
+/// Format a host and port as `host:port`, so that it can be parsed as a `SocketAddr` or
+/// `PeerHost` (or resolved with `to_socket_addrs()`).  An IPv6 literal host is put in brackets,
+/// e.g. `[::1]:3700`, unless it is already bracketed (as `Url::host_str()` returns it).
+pub fn format_host_port(host: &str, port: u16) -> String {
+    if host.contains(':') && !host.starts_with('[') {
+        format!("[{host}]:{port}")
+    } else {
+        format!("{host}:{port}")
     }
 }
 
 /// Send an HTTP request to the given host:port.  Returns the decoded response.
+/// `host` may be a DNS name or an IP address; IPv6 addresses may be given with or without
+/// brackets.
 /// Internally, this creates a socket, connects it, sends the HTTP request, and decodes the HTTP
 /// response.  It is a blocking operation.
 ///
 /// If the request encounters a network error, then return an error.  Don't retry.
 /// If the request times out after `timeout`, then return an error.
+/// If the response has an HTTP error status code, then return `SendRequestError::HttpStatus`.
 pub fn send_http_request(
     host: &str,
     port: u16,
     request: StacksHttpRequest,
     timeout: Duration,
-) -> Result<StacksHttpResponse, io::Error> {
+) -> Result<StacksHttpResponse, SendRequestError> {
+    let (stream, addr) = connect_http_host(host, port, timeout)?;
+    send_http_request_on(stream, addr, request, timeout)
+}
+
+/// Send an HTTP request to the given host:port over TLS, like `send_http_request()`.  The TLS
+/// session is set up as `tls` says, with `host` as its server name (SNI), so `host` is what the
+/// server's certificate must be for if `tls` verifies certificates.  A failed TLS handshake,
+/// including a certificate that fails verification, is a `SendRequestError::Connect` error.
+pub fn send_https_request(
+    host: &str,
+    port: u16,
+    request: StacksHttpRequest,
+    timeout: Duration,
+    tls: &TlsClientConfig,
+) -> Result<StacksHttpResponse, SendRequestError> {
+    let (stream, addr) = connect_http_host(host, port, timeout)?;
+    let stream = tls
+        .connect(host, stream)
+        .map_err(SendRequestError::Connect)?;
+    send_http_request_on(stream, addr, request, timeout)
+}
+
+/// Connect to the given host:port, with `timeout` as the socket's read and write timeouts
+fn connect_http_host(
+    host: &str,
+    port: u16,
+    timeout: Duration,
+) -> Result<(TcpStream, SocketAddr), SendRequestError> {
     // Find the host:port that works.
     // This is sometimes necessary because `localhost` can resolve to both its ipv4 and ipv6
     // addresses, but usually, Stacks services like event observers are only bound to ipv4
     // addresses.  So, be sure to use an address that will lead to a socket connection!
     let mut stream_and_addr = None;
     let mut last_err = None;
-    for addr in format!("{host}:{port}").to_socket_addrs()? {
+    for addr in format_host_port(host, port)
+        .to_socket_addrs()
+        .map_err(SendRequestError::Connect)?
+    {
         debug!("send_request: connect to {}", &addr);
         match TcpStream::connect_timeout(&addr, timeout) {
             Ok(sock) => {
@@ -1815,17 +1935,31 @@ pub fn send_http_request(
         }
     }
 
-    let Some((mut stream, addr)) = stream_and_addr else {
-        return Err(last_err.unwrap_or(io::Error::new(
-            io::ErrorKind::Other,
-            "Unable to connect to {host}:{port}",
+    let Some((stream, addr)) = stream_and_addr else {
+        return Err(SendRequestError::Connect(last_err.unwrap_or(
+            io::Error::new(io::ErrorKind::Other, "Unable to connect to {host}:{port}"),
         )));
     };
 
-    stream.set_read_timeout(Some(timeout))?;
-    stream.set_write_timeout(Some(timeout))?;
-    stream.set_nodelay(true)?;
+    stream
+        .set_read_timeout(Some(timeout))
+        .map_err(SendRequestError::Connect)?;
+    stream
+        .set_write_timeout(Some(timeout))
+        .map_err(SendRequestError::Connect)?;
+    stream
+        .set_nodelay(true)
+        .map_err(SendRequestError::Connect)?;
+    Ok((stream, addr))
+}
 
+/// Send an HTTP request over `stream`, a connection to `addr`, and decode the response
+fn send_http_request_on<S: Read + Write>(
+    mut stream: S,
+    addr: SocketAddr,
+    request: StacksHttpRequest,
+    timeout: Duration,
+) -> Result<StacksHttpResponse, SendRequestError> {
     let start = Instant::now();
 
     debug!("send_request: Sending request"; "request" => %request.request_path());
@@ -1871,10 +2005,7 @@ pub fn send_http_request(
     let mut request_handle = connection
         .make_request_handle(0, get_epoch_time_secs() + timeout.as_secs(), 0)
         .map_err(|e| {
-            io::Error::new(
-                io::ErrorKind::Other,
-                format!("Failed to create request handle: {:?}", &e).as_str(),
-            )
+            SendRequestError::Protocol(format!("Failed to create request handle: {:?}", &e))
         })?;
 
     // Step 3: load up the request with the message we're gonna send, and iteratively dump its
@@ -1883,18 +2014,18 @@ pub fn send_http_request(
     // anymore because the socket buffer is currently full).
     request
         .send(&mut request_handle)
-        .map_err(|e| handle_net_error(e, "Failed to serialize request body"))?;
+        .map_err(|e| SendRequestError::from_net_error(e, "Failed to serialize request body"))?;
 
     debug!("send_request(sending data)");
     loop {
         let flushed = request_handle
             .try_flush()
-            .map_err(|e| handle_net_error(e, "Failed to flush request body"))?;
+            .map_err(|e| SendRequestError::from_net_error(e, "Failed to flush request body"))?;
 
         // send it out
         let num_sent = connection
             .send_data(&mut stream)
-            .map_err(|e| handle_net_error(e, "Failed to send socket data"))?;
+            .map_err(|e| SendRequestError::from_net_error(e, "Failed to send socket data"))?;
 
         debug!(
             "send_request(sending data): flushed = {}, num_sent = {}",
@@ -1905,10 +2036,7 @@ pub fn send_http_request(
         }
 
         if Instant::now().saturating_duration_since(start) > timeout {
-            return Err(io::Error::new(
-                io::ErrorKind::WouldBlock,
-                "Timed out while receiving request",
-            ));
+            return Err(SendRequestError::Timeout);
         }
     }
 
@@ -1924,7 +2052,10 @@ pub fn send_http_request(
                 debug!("send_request(receiving data): received {} bytes", nr);
             }
             Err(e) => {
-                return Err(handle_net_error(e, "Failed to receive socket data"));
+                return Err(SendRequestError::from_net_error(
+                    e,
+                    "Failed to receive socket data",
+                ));
             }
         }
 
@@ -1940,7 +2071,7 @@ pub fn send_http_request(
             }
             Err(Ok(handle)) => handle,
             Err(Err(e)) => {
-                return Err(handle_net_error(
+                return Err(SendRequestError::from_net_error(
                     e,
                     "Failed to receive message after socket has been drained",
                 ));
@@ -1949,10 +2080,7 @@ pub fn send_http_request(
         request_handle = rh;
 
         if Instant::now().saturating_duration_since(start) > timeout {
-            return Err(io::Error::new(
-                io::ErrorKind::WouldBlock,
-                "Timed out while receiving request",
-            ));
+            return Err(SendRequestError::Timeout);
         }
     };
 
@@ -1964,18 +2092,15 @@ pub fn send_http_request(
             let path = &request.preamble().path_and_query_str;
             let resp_status_code = response.preamble().status_code;
             let resp_body = response.body();
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
+            return Err(SendRequestError::HttpStatus(
+                resp_status_code,
                 format!(
                     "HTTP '{verb} {path}' did not succeed ({resp_status_code} != 200). Response body = {resp_body:?}"
                 ),
             ));
         }
         _ => {
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
-                "Did not receive an HTTP response",
-            ));
+            return Err(SendRequestError::NonResponse);
         }
     };
 
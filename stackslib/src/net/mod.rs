@@ -24,6 +24,7 @@ use std::io::{Read, Write};
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 use std::ops::Deref;
 use std::str::FromStr;
+use std::sync::Arc;
 use std::{error, fmt, io};
 
 use clarity::vm::analysis::contract_interface_builder::ContractInterface;
@@ -68,6 +69,7 @@ use crate::chainstate::burn::db::sortdb::SortitionDB;
 use crate::chainstate::burn::{ConsensusHash, Opcodes};
 use crate::chainstate::coordinator::comm::CoordinatorChannels;
 use crate::chainstate::coordinator::Error as coordinator_error;
+use crate::chainstate::nakamoto::staging_blocks::ArchivalBlockSource;
 use crate::chainstate::nakamoto::{NakamotoBlock, NakamotoChainState};
 use crate::chainstate::stacks::boot::{
     BOOT_TEST_POX_4_AGG_KEY_CONTRACT, BOOT_TEST_POX_4_AGG_KEY_FNAME,
@@ -140,6 +142,8 @@ pub mod relay;
 pub mod rpc;
 pub mod server;
 pub mod stackerdb;
+/// TLS sessions for outbound HTTP requests
+pub mod tls;
 pub mod unsolicited;
 
 pub use crate::net::neighbors::{NeighborComms, PeerNetworkComms};
@@ -636,6 +640,8 @@ pub struct RPCHandlerArgs<'a> {
     pub cost_metric: Option<&'a dyn CostMetric>,
     /// coordinator channels
     pub coord_comms: Option<&'a CoordinatorChannels>,
+    /// where to look for Nakamoto blocks that are no longer in the staging DB
+    pub archival_block_source: Option<Arc<dyn ArchivalBlockSource>>,
 }
 
 impl<'a> RPCHandlerArgs<'a> {
@@ -2038,6 +2044,7 @@ pub mod test {
             _signer_bitvec: &Option<BitVec<4000>>,
             _block_timestamp: Option<u64>,
             _coinbase_height: u64,
+            _timings: &BlockProcessingTimings,
         ) {
             self.blocks.lock().unwrap().push(TestEventObserverBlock {
                 block: block.clone(),
@@ -2061,6 +2068,10 @@ pub mod test {
         ) {
             // pass
         }
+
+        fn announce_canonical_tip(&self, _tip: &StacksBlockId, _cause: TipChangeCause) {
+            // pass
+        }
     }
 
     // describes a peer's initial configuration
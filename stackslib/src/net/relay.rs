@@ -2491,6 +2491,9 @@ impl Relayer {
 
         let mut all_events: HashMap<QualifiedContractIdentifier, Vec<StackerDBChunkData>> =
             HashMap::new();
+        // chunks refused because we already have a chunk at the same slot version
+        let mut stale_events: HashMap<QualifiedContractIdentifier, Vec<StackerDBChunkData>> =
+            HashMap::new();
 
         for (sc, sync_results) in sync_results_map.into_iter() {
             if let Some(config) = stackerdb_configs.get(&sc) {
@@ -2507,6 +2510,18 @@ impl Relayer {
                                 "num_bytes" => chunk.data.len(),
                                 "error" => %e
                             );
+                            if let net_error::StaleChunk {
+                                latest_version,
+                                supplied_version,
+                            } = e
+                            {
+                                if latest_version == supplied_version {
+                                    stale_events
+                                        .entry(sync_result.contract_id.clone())
+                                        .or_default()
+                                        .push(chunk);
+                                }
+                            }
                             continue;
                         } else {
                             debug!("Stored chunk"; "stackerdb_contract_id" => &format!("{}", &sync_result.contract_id), "slot_id" => md.slot_id, "slot_version" => md.slot_version);
@@ -2537,6 +2552,9 @@ impl Relayer {
             for (contract_id, new_chunks) in all_events.into_iter() {
                 observer.new_stackerdb_chunks(contract_id, new_chunks);
             }
+            for (contract_id, stale_chunks) in stale_events.into_iter() {
+                observer.stale_stackerdb_chunks(contract_id, stale_chunks);
+            }
         }
         Ok(())
     }
@@ -119,6 +119,12 @@ pub struct ConversationHttp {
     pending_error_response: bool,
     /// how much data to buffer (i.e. the socket's send buffer size)
     socket_send_buffer_size: u32,
+    /// absolute timestamp since which the reply being sent has been held up because the client
+    /// hasn't taken enough of it to make room for the next chunk, and how many bytes it had
+    /// left to take at the last check
+    reply_stalled_since: Option<(u64, usize)>,
+    /// time (in seconds) a reply may be held up this way before the connection is dropped
+    stream_stall_timeout: u64,
 }
 
 impl fmt::Display for ConversationHttp {
@@ -173,6 +179,8 @@ impl ConversationHttp {
             last_request_timestamp: 0,
             last_response_timestamp: 0,
             socket_send_buffer_size,
+            reply_stalled_since: None,
+            stream_stall_timeout: conn_opts.stream_stall_timeout,
             connection_time: get_epoch_time_secs(),
         }
     }
@@ -304,6 +312,7 @@ impl ConversationHttp {
         // send out streamed responses in the order they were requested
         let mut drained_handle = false;
         let mut drained_stream = false;
+        let mut stalled_backlog = None;
         let mut broken = false;
         let mut do_keep_alive = true;
 
@@ -322,18 +331,25 @@ impl ConversationHttp {
             while !drained_stream {
                 // write out the last-generated data into the write-end of the reply handle's pipe
                 if let Some(pipe_fd) = reply.inner_pipe_out() {
+                    let backlog = pipe_fd.pending();
+                    if !http_response.has_room(backlog, self.socket_send_buffer_size) {
+                        // the client hasn't taken enough of what we've already generated to make
+                        // room for the next chunk, so don't generate it yet -- we'll get an edge
+                        // trigger next time the data drains from this socket.
+                        stalled_backlog = Some(backlog);
+                        break;
+                    }
                     let num_written = http_response.pipe_out(pipe_fd)?;
                     if num_written == 0 {
+                        if http_response.is_waiting() {
+                            // no more chunks yet -- try again on the next pass
+                            test_debug!("{}: Stream is waiting for more data", &_self_str);
+                            break;
+                        }
                         // no more chunks
                         drained_stream = true;
                     }
                     test_debug!("{}: Wrote {} bytes", &_self_str, num_written);
-                    if (pipe_fd.pending() as u32) >= self.socket_send_buffer_size {
-                        // we've written more data than can be dumped into the socket buffer, so
-                        // we're good to go for now -- we'll get an edge trigger next time the data
-                        // drains from this socket.
-                        break;
-                    }
                 } else {
                     test_debug!("{}: No inner pipe", &_self_str);
                     drained_stream = true;
@@ -379,6 +395,33 @@ impl ConversationHttp {
             }
         }
 
+        match stalled_backlog.filter(|_| !broken) {
+            Some(backlog) => {
+                let now = get_epoch_time_secs();
+                let stalled_since = match self.reply_stalled_since {
+                    Some((since, last_backlog)) if backlog >= last_backlog => since,
+                    // newly stalled, or the client took some of its reply since we last checked
+                    // and so is still reading it
+                    _ => now,
+                };
+                self.reply_stalled_since = Some((stalled_since, backlog));
+                if now.saturating_sub(stalled_since) >= self.stream_stall_timeout {
+                    // don't hold on to this reply (and the data behind it) for a client that isn't
+                    // reading it. The status line is already sent, so all we can do is hang up.
+                    warn!(
+                        "{}: Client has not read any of its reply in {} seconds; dropping connection",
+                        &_self_str,
+                        now.saturating_sub(stalled_since)
+                    );
+                    self.reply_stalled_since = None;
+                    return Err(net_error::ConnectionBroken);
+                }
+            }
+            None => {
+                self.reply_stalled_since = None;
+            }
+        }
+
         test_debug!(
             "broken = {}, drained_handle = {}, drained_stream = {}",
             broken,
@@ -470,6 +470,17 @@ pub trait StackerDBEventDispatcher {
         contract_id: QualifiedContractIdentifier,
         chunk_info: Vec<StackerDBChunkData>,
     );
+
+    /// One or more chunks were refused by this replica because it already has a chunk at their
+    /// slot versions. Their signatures were checked before they were refused, so a refused chunk
+    /// whose data differs from the stored chunk means that the slot's signer signed two chunks
+    /// for the same version.
+    fn stale_stackerdb_chunks(
+        &self,
+        _contract_id: QualifiedContractIdentifier,
+        _chunk_info: Vec<StackerDBChunkData>,
+    ) {
+    }
 }
 
 impl PeerNetwork {
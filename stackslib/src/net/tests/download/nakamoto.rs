@@ -563,6 +563,8 @@ fn test_nakamoto_unconfirmed_tenure_downloader() {
             ),
             tip_height: peer.network.stacks_tip.height,
             reward_cycle: tip_rc,
+            is_pox_anchor: false,
+            pox_anchor_reward_cycle: None,
         };
 
         let sortdb = peer.sortdb.take().unwrap();
@@ -638,6 +640,8 @@ fn test_nakamoto_unconfirmed_tenure_downloader() {
             ),
             tip_height: peer.network.stacks_tip.height,
             reward_cycle: tip_rc,
+            is_pox_anchor: false,
+            pox_anchor_reward_cycle: None,
         };
 
         let sortdb = peer.sortdb.take().unwrap();
@@ -737,6 +741,8 @@ fn test_nakamoto_unconfirmed_tenure_downloader() {
             ),
             tip_height: peer.network.stacks_tip.height,
             reward_cycle: tip_rc,
+            is_pox_anchor: false,
+            pox_anchor_reward_cycle: None,
         };
 
         let sortdb = peer.sortdb.take().unwrap();
@@ -835,6 +841,8 @@ fn test_nakamoto_unconfirmed_tenure_downloader() {
             ),
             tip_height: peer.network.stacks_tip.height,
             reward_cycle: tip_rc,
+            is_pox_anchor: false,
+            pox_anchor_reward_cycle: None,
         };
 
         let sortdb = peer.sortdb.take().unwrap();
@@ -912,6 +920,8 @@ fn test_nakamoto_unconfirmed_tenure_downloader() {
             ),
             tip_height: peer.network.stacks_tip.height,
             reward_cycle: tip_rc,
+            is_pox_anchor: false,
+            pox_anchor_reward_cycle: None,
         };
 
         let sortdb = peer.sortdb.take().unwrap();
@@ -975,6 +985,8 @@ fn test_nakamoto_unconfirmed_tenure_downloader() {
             ),
             tip_height: peer.network.stacks_tip.height,
             reward_cycle: tip_rc,
+            is_pox_anchor: false,
+            pox_anchor_reward_cycle: None,
         };
 
         let sortdb = peer.sortdb.take().unwrap();
@@ -27,7 +27,7 @@ use stacks_common::types::StacksEpochId;
 use stacks_common::util::chunked_encoding::{
     HttpChunkedTransferWriter, HttpChunkedTransferWriterState,
 };
-use stacks_common::util::hash::{hex_bytes, to_hex, Hash160};
+use stacks_common::util::hash::{hex_bytes, to_hex, Hash160, Sha256Sum};
 
 use crate::burnchains::Txid;
 use crate::chainstate::stacks::db::blocks::test::make_sample_microblock_stream;
@@ -44,10 +44,16 @@ use crate::net::http::{
     HttpResponsePreamble, HttpVersion, HTTP_PREAMBLE_MAX_NUM_HEADERS,
 };
 use crate::net::httpcore::{
-    send_http_request, HttpPreambleExtensions, HttpRequestContentsExtensions, StacksHttp,
-    StacksHttpMessage, StacksHttpPreamble, StacksHttpRequest, StacksHttpResponse,
+    format_host_port, send_http_request, send_https_request, HttpPreambleExtensions,
+    HttpRequestContentsExtensions, SendRequestError, StacksHttp, StacksHttpMessage,
+    StacksHttpPreamble, StacksHttpRequest, StacksHttpResponse,
 };
 use crate::net::rpc::ConversationHttp;
+use crate::net::tls::test::{
+    make_self_signed_server_config, spawn_test_tls_server, spawn_test_tls_server_with_config,
+    TEST_CA_CERT, TEST_SELF_SIGNED_CERT, TEST_SERVER_CERT,
+};
+use crate::net::tls::{parse_certificate_fingerprint, TlsClientConfig};
 use crate::net::{ProtocolFamily, TipRequest};
 
 #[test]
@@ -1137,6 +1143,25 @@ fn json_body(host: &str, port: u16, path: &str, json_bytes: &[u8]) -> StacksHttp
     request
 }
 
+#[test]
+fn test_format_host_port() {
+    assert_eq!(format_host_port("127.0.0.1", 3700), "127.0.0.1:3700");
+    assert_eq!(format_host_port("localhost", 3700), "localhost:3700");
+    assert_eq!(format_host_port("::1", 3700), "[::1]:3700");
+    assert_eq!(format_host_port("[::1]", 3700), "[::1]:3700");
+    assert_eq!(format_host_port("fe80::1:2", 80), "[fe80::1:2]:80");
+
+    // both forms of an IPv6 literal parse to the same PeerHost
+    let expected = PeerHost::IP(
+        PeerAddress::from_socketaddr(&"[::1]:3700".parse::<SocketAddr>().unwrap()),
+        3700,
+    );
+    for host in ["::1", "[::1]"] {
+        let peerhost: PeerHost = format_host_port(host, 3700).parse().unwrap();
+        assert_eq!(peerhost, expected);
+    }
+}
+
 #[test]
 fn test_send_request_timeout() {
     // Set up a TcpListener that accepts a connection but delays response
@@ -1169,14 +1194,18 @@ fn test_send_request_timeout() {
         result
     );
 
-    if let Err(err) = result {
-        assert_eq!(
-            err.kind(),
-            std::io::ErrorKind::WouldBlock,
-            "Expected TimedOut error, got: {:?}",
-            err
-        );
-    }
+    let err = result.unwrap_err();
+    assert!(
+        matches!(err, SendRequestError::Timeout),
+        "Expected a Timeout error, got: {:?}",
+        err
+    );
+
+    // still an io::Error, for callers that want one
+    assert_eq!(
+        std::io::Error::from(err).kind(),
+        std::io::ErrorKind::WouldBlock
+    );
 }
 
 fn start_mock_server(response: String, client_done_signal: Receiver<()>) -> String {
@@ -1274,3 +1303,210 @@ fn test_send_request_success() {
         result
     );
 }
+
+#[test]
+fn test_send_request_http_status() {
+    let mock_response = "HTTP/1.1 404 Not Found\r\nContent-Length: 9\r\n\r\nNot found";
+
+    let (tx_client_done, rx_client_done) = channel();
+    let server_addr = start_mock_server(mock_response.to_string(), rx_client_done);
+
+    let parts = server_addr.split(':').collect::<Vec<&str>>();
+    let host = parts[0];
+    let port = parts[1].parse().unwrap();
+
+    let result = send_http_request(
+        host,
+        port,
+        json_body(host, port, "/", b"{}"),
+        Duration::from_secs(5),
+    );
+    tx_client_done
+        .send(())
+        .expect("Failed to send close signal");
+
+    match result {
+        Err(SendRequestError::HttpStatus(status_code, msg)) => {
+            assert_eq!(status_code, 404);
+            assert!(msg.contains("404 != 200"), "Unexpected message: {}", msg);
+        }
+        res => panic!("Expected an HttpStatus error, got {:?}", res),
+    }
+}
+
+#[test]
+fn test_send_request_closed_mid_response() {
+    for partial_response in [
+        // closed in the middle of the preamble
+        "HTTP/1.1 200 OK\r\nContent-Le",
+        // closed in the middle of the body
+        "HTTP/1.1 200 OK\r\nContent-Length: 13\r\n\r\nHello",
+    ] {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind test listener");
+        let addr = listener.local_addr().unwrap();
+
+        // Send part of a response, then hang up
+        thread::spawn(move || {
+            let (mut stream, _addr) = listener.accept().unwrap();
+            let mut buffer = [0; 512];
+            let _ = stream.read(&mut buffer);
+            stream.write_all(partial_response.as_bytes()).unwrap();
+            stream.flush().unwrap();
+            drop(stream);
+        });
+
+        let timeout = Duration::from_secs(10);
+        let start = Instant::now();
+        let result = send_http_request(
+            "127.0.0.1",
+            addr.port(),
+            json_body("127.0.0.1", addr.port(), "/", b"{}"),
+            timeout,
+        );
+
+        // fails as soon as the connection closes, rather than when the timeout expires
+        assert!(
+            start.elapsed() < timeout / 2,
+            "Took {:?} to notice the closed connection",
+            start.elapsed()
+        );
+        match result {
+            Err(SendRequestError::Connect(e)) => {
+                assert_eq!(e.kind(), std::io::ErrorKind::UnexpectedEof)
+            }
+            res => panic!("Expected an UnexpectedEof error, got {:?}", res),
+        }
+    }
+}
+
+#[test]
+fn test_send_https_request() {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind test listener");
+    let port = listener.local_addr().unwrap().port();
+    let (tx, rx) = channel();
+    spawn_test_tls_server(listener, tx);
+
+    let verified = TlsClientConfig::with_root_certificates(&[TEST_CA_CERT.to_vec()]).unwrap();
+    let unverified = TlsClientConfig::new_unverified();
+    let timeout = Duration::from_secs(5);
+
+    // the server's certificate is for both its name and its address, and each one is sent as
+    // the server name
+    for (host, tls) in [
+        ("localhost", &verified),
+        ("127.0.0.1", &verified),
+        ("localhost", &unverified),
+    ] {
+        let body = format!(r#"{{"host":"{}"}}"#, host);
+        let response = send_https_request(
+            host,
+            port,
+            json_body(host, port, "/new_block", body.as_bytes()),
+            timeout,
+            tls,
+        )
+        .unwrap();
+
+        // the payload went over the TLS session, and came back
+        let (request_line, request_body) = rx.recv_timeout(timeout).unwrap();
+        assert_eq!(request_line, "POST /new_block HTTP/1.1");
+        assert_eq!(request_body, body.as_bytes());
+        assert_eq!(parse_http_response(response), body);
+    }
+
+    // a certificate that isn't from a trusted root fails the handshake, and nothing is sent
+    let result = send_https_request(
+        "localhost",
+        port,
+        json_body("localhost", port, "/new_block", b"{}"),
+        timeout,
+        &TlsClientConfig::new(),
+    );
+    assert!(
+        matches!(result, Err(SendRequestError::Connect(_))),
+        "Expected a Connect error, got {:?}",
+        result
+    );
+    assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+}
+
+#[test]
+fn test_send_https_request_pinned_certificate() {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind test listener");
+    let self_signed_port = listener.local_addr().unwrap().port();
+    let (tx, rx) = channel();
+    spawn_test_tls_server_with_config(listener, make_self_signed_server_config(), tx.clone());
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind test listener");
+    let ca_issued_port = listener.local_addr().unwrap().port();
+    spawn_test_tls_server(listener, tx);
+
+    let timeout = Duration::from_secs(5);
+    let self_signed = Sha256Sum::from_data(TEST_SELF_SIGNED_CERT);
+    let ca_issued = Sha256Sum::from_data(TEST_SERVER_CERT);
+    let send = |port: u16, tls: &TlsClientConfig| {
+        send_https_request(
+            "localhost",
+            port,
+            json_body("localhost", port, "/new_block", b"{}"),
+            timeout,
+            tls,
+        )
+    };
+
+    // a self-signed certificate is only accepted if it is the pinned one
+    for tls in [
+        TlsClientConfig::new(),
+        TlsClientConfig::with_root_certificates(&[TEST_CA_CERT.to_vec()]).unwrap(),
+        TlsClientConfig::with_pinned_certificate(ca_issued, None).unwrap(),
+    ] {
+        let result = send(self_signed_port, &tls);
+        assert!(
+            matches!(result, Err(SendRequestError::Connect(_))),
+            "Expected a Connect error, got {:?}",
+            result
+        );
+        assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+    }
+    let pinned = TlsClientConfig::with_pinned_certificate(self_signed, None).unwrap();
+    assert!(pinned.verifies_certificates());
+    send(self_signed_port, &pinned).unwrap();
+    assert_eq!(rx.recv_timeout(timeout).unwrap().1, b"{}");
+
+    // with roots, the pinned certificate must also be issued by one of them
+    let roots = [TEST_CA_CERT.to_vec()];
+    let pinned = TlsClientConfig::with_pinned_certificate(ca_issued, Some(&roots)).unwrap();
+    send(ca_issued_port, &pinned).unwrap();
+    assert_eq!(rx.recv_timeout(timeout).unwrap().1, b"{}");
+    let pinned = TlsClientConfig::with_pinned_certificate(self_signed, Some(&roots)).unwrap();
+    assert!(send(self_signed_port, &pinned).is_err());
+
+    // pinning the CA doesn't pin the certificates it issued
+    let ca = Sha256Sum::from_data(TEST_CA_CERT);
+    let pinned = TlsClientConfig::with_pinned_certificate(ca, Some(&roots)).unwrap();
+    assert!(send(ca_issued_port, &pinned).is_err());
+    assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+}
+
+#[test]
+fn test_parse_certificate_fingerprint() {
+    let fingerprint = Sha256Sum::from_data(TEST_CA_CERT);
+    let hex = to_hex(fingerprint.as_bytes());
+    assert_eq!(parse_certificate_fingerprint(&hex).unwrap(), fingerprint);
+
+    // as `openssl x509 -fingerprint -sha256` prints it
+    let openssl = hex
+        .to_uppercase()
+        .as_bytes()
+        .chunks(2)
+        .map(|byte| str::from_utf8(byte).unwrap())
+        .collect::<Vec<_>>()
+        .join(":");
+    assert_eq!(
+        parse_certificate_fingerprint(&openssl).unwrap(),
+        fingerprint
+    );
+
+    for bad in ["", "00", &hex[2..], &format!("{}zz", &hex[2..])] {
+        assert!(parse_certificate_fingerprint(bad).is_err(), "{}", bad);
+    }
+}
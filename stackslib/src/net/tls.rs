@@ -0,0 +1,387 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! TLS sessions for the blocking HTTP requests made by `send_https_request()`.
+
+use std::fs;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use rustls::client::{ServerCertVerified, ServerCertVerifier, WebPkiVerifier};
+use rustls::{
+    Certificate, ClientConfig, ClientConnection, OwnedTrustAnchor, RootCertStore, ServerName,
+    StreamOwned,
+};
+use stacks_common::util::hash::{hex_bytes, Sha256Sum};
+
+/// How the TLS sessions of outbound requests are set up
+#[derive(Clone)]
+pub struct TlsClientConfig {
+    config: Arc<ClientConfig>,
+    /// Whether the server's certificate is verified
+    verify_certificates: bool,
+}
+
+impl std::fmt::Debug for TlsClientConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TlsClientConfig")
+            .field("verify_certificates", &self.verify_certificates)
+            .finish()
+    }
+}
+
+/// Accepts whatever certificate the server presents. The handshake's signatures are still
+/// checked against it, but anyone can pose as the server.
+struct NoCertificateVerification;
+
+impl ServerCertVerifier for NoCertificateVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+/// Only accepts the certificate with a given SHA-256 fingerprint, which needn't be issued by a
+/// trusted root, or be for the server's name. If `roots` is set, the certificate must also be
+/// verified against them.
+struct PinnedCertificateVerification {
+    fingerprint: Sha256Sum,
+    roots: Option<WebPkiVerifier>,
+}
+
+impl ServerCertVerifier for PinnedCertificateVerification {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        intermediates: &[Certificate],
+        server_name: &ServerName,
+        scts: &mut dyn Iterator<Item = &[u8]>,
+        ocsp_response: &[u8],
+        now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        if Sha256Sum::from_data(&end_entity.0) != self.fingerprint {
+            return Err(rustls::Error::General(
+                "Server certificate does not match the pinned fingerprint".into(),
+            ));
+        }
+        match self.roots.as_ref() {
+            Some(roots) => roots.verify_server_cert(
+                end_entity,
+                intermediates,
+                server_name,
+                scts,
+                ocsp_response,
+                now,
+            ),
+            None => Ok(ServerCertVerified::assertion()),
+        }
+    }
+}
+
+/// Read the certificates of the PEM file at `path`, DER-encoded. A file that can't be read, or
+/// that holds no certificates, is an error.
+pub fn read_pem_certificates(path: &str) -> Result<Vec<Vec<u8>>, String> {
+    let pem = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let certs = rustls_pemfile::certs(&mut pem.as_slice())
+        .map_err(|e| format!("Invalid PEM in {}: {}", path, e))?;
+    if certs.is_empty() {
+        return Err(format!("No PEM certificates in {}", path));
+    }
+    Ok(certs)
+}
+
+/// Parse a certificate's SHA-256 fingerprint, given as 64 hex digits, optionally separated by
+/// colons as `openssl x509 -fingerprint -sha256` prints them
+pub fn parse_certificate_fingerprint(fingerprint: &str) -> Result<Sha256Sum, String> {
+    let hex: String = fingerprint.chars().filter(|c| *c != ':').collect();
+    hex_bytes(&hex)
+        .ok()
+        .and_then(|bytes| Sha256Sum::from_bytes(&bytes))
+        .ok_or_else(|| {
+            format!(
+                "Invalid SHA-256 certificate fingerprint '{}': expected 32 hex-encoded bytes",
+                fingerprint
+            )
+        })
+}
+
+impl TlsClientConfig {
+    /// Verify servers' certificates against the Mozilla root certificates
+    pub fn new() -> Self {
+        let mut roots = RootCertStore::empty();
+        roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
+            OwnedTrustAnchor::from_subject_spki_name_constraints(
+                ta.subject,
+                ta.spki,
+                ta.name_constraints,
+            )
+        }));
+        Self::with_roots(roots)
+    }
+
+    /// Verify servers' certificates against the DER-encoded root certificates `roots` only
+    pub fn with_root_certificates(roots: &[Vec<u8>]) -> Result<Self, String> {
+        Ok(Self::with_roots(Self::make_root_store(roots)?))
+    }
+
+    /// Verify servers' certificates against the root certificates of the PEM file at `path` only,
+    /// such as an internal CA bundle
+    pub fn with_root_certificates_file(path: &str) -> Result<Self, String> {
+        Self::with_root_certificates(&read_pem_certificates(path)?)
+    }
+
+    /// Only accept the server certificate whose SHA-256 fingerprint is `fingerprint`, such as a
+    /// self-signed one. If `roots` (DER-encoded) are given, the certificate must also be
+    /// verified against them, and be for the server's name.
+    pub fn with_pinned_certificate(
+        fingerprint: Sha256Sum,
+        roots: Option<&[Vec<u8>]>,
+    ) -> Result<Self, String> {
+        let roots = roots
+            .map(|roots| Self::make_root_store(roots).map(|store| WebPkiVerifier::new(store, None)))
+            .transpose()?;
+        let config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(Arc::new(PinnedCertificateVerification {
+                fingerprint,
+                roots,
+            }))
+            .with_no_client_auth();
+        Ok(Self {
+            config: Arc::new(config),
+            verify_certificates: true,
+        })
+    }
+
+    fn make_root_store(roots: &[Vec<u8>]) -> Result<RootCertStore, String> {
+        let mut root_store = RootCertStore::empty();
+        for root in roots.iter() {
+            root_store
+                .add(&Certificate(root.clone()))
+                .map_err(|e| format!("Invalid root certificate: {}", e))?;
+        }
+        Ok(root_store)
+    }
+
+    /// Accept any certificate, such as a self-signed one. This is only fit for testing.
+    pub fn new_unverified() -> Self {
+        let config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(Arc::new(NoCertificateVerification))
+            .with_no_client_auth();
+        Self {
+            config: Arc::new(config),
+            verify_certificates: false,
+        }
+    }
+
+    fn with_roots(roots: RootCertStore) -> Self {
+        let config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        Self {
+            config: Arc::new(config),
+            verify_certificates: true,
+        }
+    }
+
+    /// Whether servers' certificates are verified
+    pub fn verifies_certificates(&self) -> bool {
+        self.verify_certificates
+    }
+
+    /// Start a TLS session with `host` over `stream`, and complete its handshake. `host` is sent
+    /// as the session's server name (SNI), and is what the server's certificate must be for.
+    /// IPv6 addresses may be given with or without brackets. A certificate that fails
+    /// verification is an `InvalidData` error.
+    pub fn connect(&self, host: &str, mut stream: TcpStream) -> io::Result<TlsStream> {
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let server_name = ServerName::try_from(host).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid TLS server name '{}': {}", host, e),
+            )
+        })?;
+        let mut conn = ClientConnection::new(self.config.clone(), server_name)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        while conn.is_handshaking() {
+            conn.complete_io(&mut stream)?;
+        }
+        Ok(TlsStream(StreamOwned::new(conn, stream)))
+    }
+}
+
+/// A TLS session over a TCP stream
+pub struct TlsStream(StreamOwned<ClientConnection, TcpStream>);
+
+impl Read for TlsStream {
+    /// A server that closes the connection without ending the TLS session reads as the end of
+    /// the stream, as it would without TLS. A truncated response is still caught by the HTTP
+    /// decoder, which knows how long the response should be.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.0.read(buf) {
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(0),
+            res => res,
+        }
+    }
+}
+
+impl Write for TlsStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// TLS servers for tests, with a certificate authority and a certificate that it issued for
+/// `localhost` and `127.0.0.1`, and a self-signed certificate for the same names. All of the
+/// certificates are valid until 2126.
+#[cfg(any(test, feature = "testing"))]
+pub mod test {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc::Sender;
+    use std::sync::Arc;
+    use std::thread;
+
+    use rustls::{Certificate, PrivateKey, ServerConfig, ServerConnection, StreamOwned};
+
+    /// The test certificate authority's certificate, DER-encoded
+    pub const TEST_CA_CERT: &[u8] = include_bytes!("tests/tls/ca.der");
+    /// The certificate for `localhost` and `127.0.0.1`, DER-encoded
+    pub const TEST_SERVER_CERT: &[u8] = include_bytes!("tests/tls/localhost.der");
+    /// The key of `TEST_SERVER_CERT`, as DER-encoded PKCS#8
+    pub const TEST_SERVER_KEY: &[u8] = include_bytes!("tests/tls/localhost.key.der");
+    /// `TEST_CA_CERT`, as a PEM file
+    pub const TEST_CA_CERT_PEM: &[u8] = include_bytes!("tests/tls/ca.pem");
+    /// A self-signed certificate for `localhost` and `127.0.0.1`, DER-encoded
+    pub const TEST_SELF_SIGNED_CERT: &[u8] = include_bytes!("tests/tls/self_signed.der");
+    /// The key of `TEST_SELF_SIGNED_CERT`, as DER-encoded PKCS#8
+    pub const TEST_SELF_SIGNED_KEY: &[u8] = include_bytes!("tests/tls/self_signed.key.der");
+
+    /// A TLS server config that presents `TEST_SERVER_CERT`
+    pub fn make_test_server_config() -> Arc<ServerConfig> {
+        let config = ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![
+                    Certificate(TEST_SERVER_CERT.to_vec()),
+                    Certificate(TEST_CA_CERT.to_vec()),
+                ],
+                PrivateKey(TEST_SERVER_KEY.to_vec()),
+            )
+            .expect("FATAL: invalid test TLS certificate");
+        Arc::new(config)
+    }
+
+    /// A TLS server config that presents `TEST_SELF_SIGNED_CERT`
+    pub fn make_self_signed_server_config() -> Arc<ServerConfig> {
+        let config = ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![Certificate(TEST_SELF_SIGNED_CERT.to_vec())],
+                PrivateKey(TEST_SELF_SIGNED_KEY.to_vec()),
+            )
+            .expect("FATAL: invalid self-signed test TLS certificate");
+        Arc::new(config)
+    }
+
+    /// Read an HTTP/1.1 request with a `Content-Length` from `stream`, and return its request line
+    /// and body
+    fn read_test_request<R: Read>(stream: &mut R) -> Option<(String, Vec<u8>)> {
+        let mut request = vec![];
+        let mut buf = [0u8; 4096];
+        let headers_len = loop {
+            if let Some(pos) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+            let nr = stream.read(&mut buf).ok().filter(|nr| *nr > 0)?;
+            request.extend_from_slice(&buf[..nr]);
+        };
+        let headers = String::from_utf8_lossy(&request[..headers_len]).to_string();
+        let content_length: usize = headers
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+            .and_then(|(_, value)| value.trim().parse().ok())
+            .unwrap_or(0);
+        while request.len() < headers_len + content_length {
+            let nr = stream.read(&mut buf).ok().filter(|nr| *nr > 0)?;
+            request.extend_from_slice(&buf[..nr]);
+        }
+        let request_line = headers.lines().next().unwrap_or_default().to_string();
+        Some((request_line, request[headers_len..].to_vec()))
+    }
+
+    /// Serve HTTPS on `listener` with `TEST_SERVER_CERT`, in a background thread. Each request
+    /// is answered with a `200` that echoes its body back as JSON, and its request line and body
+    /// are sent to `requests`. Connections that fail their TLS handshake are dropped.
+    pub fn spawn_test_tls_server(listener: TcpListener, requests: Sender<(String, Vec<u8>)>) {
+        spawn_test_tls_server_with_config(listener, make_test_server_config(), requests)
+    }
+
+    /// Serve HTTPS on `listener` as `spawn_test_tls_server()` does, with the certificate of
+    /// `config`
+    pub fn spawn_test_tls_server_with_config(
+        listener: TcpListener,
+        config: Arc<ServerConfig>,
+        requests: Sender<(String, Vec<u8>)>,
+    ) {
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                let Ok(conn) = ServerConnection::new(config.clone()) else {
+                    continue;
+                };
+                let mut stream = StreamOwned::new(conn, stream);
+                let Some((request_line, body)) = read_test_request(&mut stream) else {
+                    continue;
+                };
+                let mut response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                )
+                .into_bytes();
+                response.extend_from_slice(&body);
+                if stream.write_all(&response).is_err() {
+                    continue;
+                }
+                stream.conn.send_close_notify();
+                let _ = stream.flush();
+                if requests.send((request_line, body)).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+}
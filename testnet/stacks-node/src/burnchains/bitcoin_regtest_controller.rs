@@ -51,7 +51,7 @@ use stacks::chainstate::stacks::address::PoxAddress;
 use stacks::core::{StacksEpoch, StacksEpochId};
 use stacks::monitoring::{increment_btc_blocks_received_counter, increment_btc_ops_sent_counter};
 use stacks::net::http::{HttpRequestContents, HttpResponsePayload};
-use stacks::net::httpcore::{send_http_request, StacksHttpRequest};
+use stacks::net::httpcore::{send_http_request, SendRequestError, StacksHttpRequest};
 use stacks::net::Error as NetError;
 use stacks_common::codec::StacksMessageCodec;
 use stacks_common::deps_common::bitcoin::blockdata::opcodes;
@@ -2479,6 +2479,12 @@ impl From<NetError> for RPCError {
     }
 }
 
+impl From<SendRequestError> for RPCError {
+    fn from(e: SendRequestError) -> Self {
+        Self::Network(format!("Send Request Error: {:?}", &e))
+    }
+}
+
 impl BitcoinRPCRequest {
     fn build_rpc_request(config: &Config, payload: &BitcoinRPCRequest) -> StacksHttpRequest {
         let url = {
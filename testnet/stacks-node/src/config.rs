@@ -14,8 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::{HashMap, HashSet};
-use std::net::{Ipv4Addr, SocketAddr, ToSocketAddrs};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, ToSocketAddrs};
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
@@ -23,7 +23,9 @@ use std::time::Duration;
 use std::{cmp, fs, thread};
 
 use clarity::vm::costs::ExecutionCost;
-use clarity::vm::types::{AssetIdentifier, PrincipalData, QualifiedContractIdentifier};
+use clarity::vm::types::{
+    AssetIdentifier, PrincipalData, QualifiedContractIdentifier, StandardPrincipalData,
+};
 use lazy_static::lazy_static;
 use rand::RngCore;
 use serde::Deserialize;
@@ -31,6 +33,7 @@ use stacks::burnchains::affirmation::AffirmationMap;
 use stacks::burnchains::bitcoin::BitcoinNetworkType;
 use stacks::burnchains::{Burnchain, MagicBytes, PoxConstants, BLOCKSTACK_MAGIC_MAINNET};
 use stacks::chainstate::nakamoto::signer_set::NakamotoSigners;
+use stacks::chainstate::nakamoto::staging_blocks::{ArchivalBlockSource, DirectoryBlockSource};
 use stacks::chainstate::stacks::boot::MINERS_NAME;
 use stacks::chainstate::stacks::index::marf::MARFOpenOpts;
 use stacks::chainstate::stacks::index::storage::TrieHashCalculationMode;
@@ -63,6 +66,12 @@ use stacks_common::util::hash::hex_bytes;
 use stacks_common::util::secp256k1::{Secp256k1PrivateKey, Secp256k1PublicKey};
 
 use crate::chain_data::MinerStats;
+use crate::event_dispatcher::{
+    is_redis_endpoint, make_observer_tls_config, DeliveryPolicy, DeliveryQueue, FallbackTransport,
+    FieldPath, PayloadPartitioner, StaticHeaders, WireCapture, DEFAULT_COST_NEAR_LIMIT_THRESHOLD,
+    DEFAULT_MAX_EVENT_OBSERVERS, DEFAULT_MAX_IN_FLIGHT_PER_HOST, DEFAULT_MEMPOOL_DROP_HISTORY_SIZE,
+    MAX_EVENT_OBSERVERS, STDOUT_ENDPOINT,
+};
 
 pub const DEFAULT_SATS_PER_VB: u64 = 50;
 pub const OP_TX_BLOCK_COMMIT_ESTIM_SIZE: u64 = 380;
@@ -899,11 +908,99 @@ impl Config {
                         .map(|e| EventKeyType::from_string(e).unwrap())
                         .collect();
 
-                    observers.insert(EventObserverConfig {
+                    let include_fields = observer.include_fields.unwrap_or_default();
+                    let exclude_fields = observer.exclude_fields.unwrap_or_default();
+                    for path in include_fields.iter().chain(exclude_fields.iter()) {
+                        FieldPath::parse(path).map_err(|e| {
+                            format!("Event observer {}: {}", &observer.endpoint, &e)
+                        })?;
+                    }
+                    if let Some(transport) = observer.fallback_transport.as_ref() {
+                        FallbackTransport::parse(transport).map_err(|e| {
+                            format!("Event observer {}: {}", &observer.endpoint, &e)
+                        })?;
+                    }
+                    if let Some(partition) = observer.partition.as_ref() {
+                        PayloadPartitioner::parse(partition).map_err(|e| {
+                            format!("Event observer {}: {}", &observer.endpoint, &e)
+                        })?;
+                    }
+                    let delivery_policy = observer.delivery_policy.unwrap_or_default();
+                    DeliveryPolicy::from_config(&delivery_policy)
+                        .map_err(|e| format!("Event observer {}: {}", &observer.endpoint, &e))?;
+                    if let Some(delivery_queue) = observer.delivery_queue.as_ref() {
+                        DeliveryQueue::from_config(delivery_queue).map_err(|e| {
+                            format!("Event observer {}: {}", &observer.endpoint, &e)
+                        })?;
+                    }
+                    if let Some(wire_capture) = observer.wire_capture.as_ref() {
+                        WireCapture::parse(wire_capture).map_err(|e| {
+                            format!("Event observer {}: {}", &observer.endpoint, &e)
+                        })?;
+                    }
+                    let headers = observer.headers.unwrap_or_default();
+                    StaticHeaders::parse(&headers)
+                        .map_err(|e| format!("Event observer {}: {}", &observer.endpoint, &e))?;
+                    if observer.redis_stream_prefix.is_some()
+                        && !is_redis_endpoint(&observer.endpoint)
+                    {
+                        return Err(format!(
+                            "Event observer {}: redis_stream_prefix can only be used with a redis:// endpoint",
+                            &observer.endpoint
+                        ));
+                    }
+
+                    let observer_conf = EventObserverConfig {
                         endpoint: observer.endpoint,
                         events_keys,
                         timeout_ms: observer.timeout_ms.unwrap_or(1_000),
-                    });
+                        parallel_connections: observer.parallel_connections.unwrap_or(1),
+                        include_winner_commit: observer.include_winner_commit.unwrap_or(false),
+                        include_sortition_info: observer.include_sortition_info.unwrap_or(false),
+                        include_raw_burnchain_op: observer
+                            .include_raw_burnchain_op
+                            .unwrap_or(false),
+                        split_array_events: observer.split_array_events.unwrap_or(false),
+                        stringify_large_ints: observer.stringify_large_ints.unwrap_or(false),
+                        flatten_execution_costs: observer.flatten_execution_costs.unwrap_or(false),
+                        include_drop_fee_rates: observer.include_drop_fee_rates.unwrap_or(false),
+                        http2: observer.http2.unwrap_or(false),
+                        strict_block_ordering: observer.strict_block_ordering.unwrap_or(false),
+                        include_block_timings: observer.include_block_timings.unwrap_or(false),
+                        payload_envelope: observer.payload_envelope.unwrap_or(false),
+                        include_block_header_event: observer
+                            .include_block_header_event
+                            .unwrap_or(false),
+                        include_fields,
+                        exclude_fields,
+                        max_payload_bytes: observer.max_payload_bytes,
+                        fallback_transport: observer.fallback_transport,
+                        fallback_after_attempts: observer.fallback_after_attempts,
+                        success_body_marker: observer.success_body_marker,
+                        stackerdb_batch_window_ms: observer.stackerdb_batch_window_ms,
+                        partition: observer.partition,
+                        send_startup_event: observer.send_startup_event.unwrap_or(false),
+                        include_miner_signature_valid: observer
+                            .include_miner_signature_valid
+                            .unwrap_or(false),
+                        include_stx_supply_delta: observer
+                            .include_stx_supply_delta
+                            .unwrap_or(false),
+                        include_raw_header: observer.include_raw_header.unwrap_or(false),
+                        delivery_policy,
+                        delivery_queue: observer.delivery_queue,
+                        wire_capture: observer.wire_capture,
+                        tls_skip_verify: observer.tls_skip_verify.unwrap_or(false),
+                        tls_ca_file: observer.tls_ca_file,
+                        tls_pinned_cert_sha256: observer.tls_pinned_cert_sha256,
+                        headers,
+                        redis_stream_prefix: observer.redis_stream_prefix,
+                    };
+                    // a bad CA bundle or fingerprint stops the node here, not at its first event
+                    make_observer_tls_config(&observer_conf).map_err(|e| {
+                        format!("Event observer {}: {}", &observer_conf.endpoint, &e)
+                    })?;
+                    observers.insert(observer_conf);
                 }
                 observers
             }
@@ -917,12 +1014,75 @@ impl Config {
                     endpoint: val,
                     events_keys: vec![EventKeyType::AnyEvent],
                     timeout_ms: 1_000,
+                    parallel_connections: 1,
+                    include_winner_commit: false,
+                    include_sortition_info: false,
+                    include_raw_burnchain_op: false,
+                    split_array_events: false,
+                    stringify_large_ints: false,
+                    http2: false,
+                    strict_block_ordering: false,
+                    include_block_timings: false,
+                    payload_envelope: false,
+                    flatten_execution_costs: false,
+                    include_drop_fee_rates: false,
+                    include_block_header_event: false,
+                    include_fields: vec![],
+                    exclude_fields: vec![],
+                    max_payload_bytes: None,
+                    fallback_transport: None,
+                    fallback_after_attempts: None,
+                    success_body_marker: None,
+                    stackerdb_batch_window_ms: None,
+                    partition: None,
+                    send_startup_event: false,
+                    include_miner_signature_valid: false,
+                    include_stx_supply_delta: false,
+                    include_raw_header: false,
+                    delivery_policy: Default::default(),
+                    delivery_queue: None,
+                    wire_capture: None,
+                    tls_skip_verify: false,
+                    tls_ca_file: None,
+                    tls_pinned_cert_sha256: None,
+                    headers: Default::default(),
+                    redis_stream_prefix: None,
                 });
                 ()
             }
             _ => (),
         };
 
+        if is_mainnet
+            && events_observers
+                .iter()
+                .any(|observer| observer.endpoint == STDOUT_ENDPOINT)
+        {
+            return Err(format!(
+                "Event observer {} is for development only, and is not allowed on mainnet",
+                STDOUT_ENDPOINT
+            ));
+        }
+        if is_mainnet {
+            if let Some(observer) = events_observers
+                .iter()
+                .find(|observer| observer.tls_skip_verify)
+            {
+                return Err(format!(
+                    "Event observer {}: tls_skip_verify is for testing only, and is not allowed on mainnet",
+                    &observer.endpoint
+                ));
+            }
+        }
+
+        if events_observers.len() > node.max_event_observers {
+            return Err(format!(
+                "{} event observers are configured, but node.max_event_observers is {}",
+                events_observers.len(),
+                node.max_event_observers
+            ));
+        }
+
         let connection_options = match config_file.connection_options {
             Some(opts) => opts.into_config(is_mainnet)?,
             None => HELIUM_DEFAULT_CONNECTION_OPTIONS.clone(),
@@ -1685,6 +1845,46 @@ pub struct NodeConfig {
     pub chain_liveness_poll_time_secs: u64,
     /// stacker DBs we replicate
     pub stacker_dbs: Vec<QualifiedContractIdentifier>,
+    /// Total time, in milliseconds, that the event dispatcher may spend retrying failed
+    /// `new_block` deliveries for one block, across all event observers. Deliveries that
+    /// still fail are left in the observers' pending-payload database, and are retried before
+    /// each observer's next event. If unset, every delivery is retried until it succeeds.
+    pub block_event_retry_budget_ms: Option<u64>,
+    /// Fraction of the block limit, in any cost dimension, above which a transaction's execution
+    /// cost is reported to `cost_near_limit` event observers. Must be in (0, 1]. Defaults to 0.9.
+    pub cost_near_limit_threshold: f64,
+    /// Minimum time, in milliseconds, between two `proposal_response` events sent to the same
+    /// event observer. A response still waiting to be sent is dropped if a later response for
+    /// the same block proposal replaces it. If unset, every response is sent immediately.
+    pub proposal_response_min_interval_ms: Option<u64>,
+    /// Directory of Nakamoto blocks that have been moved out of the staging DB, with one file
+    /// per block named by its index block hash. `/v3/tenures` serves blocks it can't find in
+    /// the staging DB from here.
+    pub archival_blocks_dir: Option<String>,
+    /// Most event observers that may be configured. Defaults to 1024, and can be at most
+    /// 65536.
+    pub max_event_observers: usize,
+    /// If set, every block this node mines is recorded in this directory, as gzipped
+    /// newline-delimited JSON with one file per target burn block height, whether or not any
+    /// event observer receives `mined_block` events.
+    pub mined_block_archive_dir: Option<String>,
+    /// If set, only the mined-block archive files of this many most recent burn block heights
+    /// are kept. If unset, every file is kept.
+    pub mined_block_archive_retention: Option<u64>,
+    /// Number of threads over which the serialization of each block's `new_block` event
+    /// payloads is spread. Helps blocks with many contract deployments or event observers.
+    /// Defaults to 1, which serializes them on the block processing thread.
+    pub event_serialization_threads: usize,
+    /// Number of the most recently dropped mempool transactions that the event dispatcher
+    /// remembers, with the reason and time of each drop, so that they can be listed with
+    /// `GET /v2/debug/mempool_drops` and sent again to an event observer. 0 remembers none.
+    pub mempool_drop_history_size: usize,
+    /// Most event deliveries that may be in flight to one event observer host at once, across
+    /// all of the observers with endpoints on that host. Deliveries beyond it wait for a slot.
+    pub event_observer_max_in_flight_per_host: usize,
+    /// Overrides of `event_observer_max_in_flight_per_host` for specific observer hosts, keyed
+    /// by host name or address (without a port)
+    pub event_observer_host_limits: HashMap<String, usize>,
 }
 
 #[derive(Clone, Debug)]
@@ -1858,6 +2058,13 @@ impl Config {
 
         Some(fee_estimator)
     }
+
+    /// Make the archive that `/v3/tenures` falls back to for blocks that are no longer in the
+    /// staging DB, if one is configured
+    pub fn make_archival_block_source(&self) -> Option<Arc<dyn ArchivalBlockSource>> {
+        let dir = self.node.archival_blocks_dir.as_ref()?;
+        Some(Arc::new(DirectoryBlockSource::new(PathBuf::from(dir))))
+    }
 }
 
 impl FeeEstimationConfig {
@@ -1968,6 +2175,17 @@ impl Default for NodeConfig {
             fault_injection_hide_blocks: false,
             chain_liveness_poll_time_secs: 300,
             stacker_dbs: vec![],
+            block_event_retry_budget_ms: None,
+            cost_near_limit_threshold: DEFAULT_COST_NEAR_LIMIT_THRESHOLD,
+            proposal_response_min_interval_ms: None,
+            archival_blocks_dir: None,
+            max_event_observers: DEFAULT_MAX_EVENT_OBSERVERS,
+            mined_block_archive_dir: None,
+            mined_block_archive_retention: None,
+            event_serialization_threads: 1,
+            mempool_drop_history_size: DEFAULT_MEMPOOL_DROP_HISTORY_SIZE,
+            event_observer_max_in_flight_per_host: DEFAULT_MAX_IN_FLIGHT_PER_HOST,
+            event_observer_host_limits: HashMap::new(),
         }
     }
 }
@@ -2226,6 +2444,9 @@ pub struct ConnectionOptionsFile {
     pub handshake_timeout: Option<u64>,
     pub timeout: Option<u64>,
     pub idle_timeout: Option<u64>,
+    /// How long (in seconds) a streamed reply, such as a tenure from `/v3/tenures`, may wait
+    /// for a slow client to make room for its next chunk before the connection is dropped.
+    pub stream_stall_timeout: Option<u64>,
     pub heartbeat: Option<u32>,
     pub private_key_lifetime: Option<u64>,
     pub num_neighbors: Option<u64>,
@@ -2264,6 +2485,12 @@ pub struct ConnectionOptionsFile {
     pub private_neighbors: Option<bool>,
     pub auth_token: Option<String>,
     pub antientropy_retry: Option<u64>,
+    /// If set, only callers from these IP addresses may stream tenures from `/v3/tenures`.
+    /// Everyone may by default.
+    pub tenure_stream_allowlist: Option<Vec<String>>,
+    /// How long (in milliseconds) a `/v3/tenures` stream may spend looking up the next block
+    /// to send, before it ends early. Defaults to 10 seconds.
+    pub tenure_stream_db_budget_ms: Option<u64>,
 }
 
 impl ConnectionOptionsFile {
@@ -2277,6 +2504,22 @@ impl ConnectionOptionsFile {
                     .map_err(|e| format!("Invalid connection_option.public_ip_address: {}", e))
             })
             .transpose()?;
+        let tenure_stream_allowlist = self
+            .tenure_stream_allowlist
+            .map(|allowlist| {
+                allowlist
+                    .iter()
+                    .map(|ip| {
+                        ip.parse::<IpAddr>().map_err(|e| {
+                            format!(
+                                "Invalid connection_option.tenure_stream_allowlist entry {}: {}",
+                                ip, e
+                            )
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()?;
         let mut read_only_call_limit = HELIUM_DEFAULT_CONNECTION_OPTIONS
             .read_only_call_limit
             .clone();
@@ -2310,6 +2553,9 @@ impl ConnectionOptionsFile {
             idle_timeout: self
                 .idle_timeout
                 .unwrap_or_else(|| HELIUM_DEFAULT_CONNECTION_OPTIONS.idle_timeout),
+            stream_stall_timeout: self
+                .stream_stall_timeout
+                .unwrap_or_else(|| HELIUM_DEFAULT_CONNECTION_OPTIONS.stream_stall_timeout),
             heartbeat: self
                 .heartbeat
                 .unwrap_or_else(|| HELIUM_DEFAULT_CONNECTION_OPTIONS.heartbeat),
@@ -2395,6 +2641,10 @@ impl ConnectionOptionsFile {
             antientropy_public: self.antientropy_public.unwrap_or(true),
             private_neighbors: self.private_neighbors.unwrap_or(true),
             auth_token: self.auth_token,
+            tenure_stream_allowlist,
+            tenure_stream_db_budget_ms: self
+                .tenure_stream_db_budget_ms
+                .unwrap_or(default.tenure_stream_db_budget_ms),
             antientropy_retry: self.antientropy_retry.unwrap_or(default.antientropy_retry),
             ..default
         })
@@ -2438,6 +2688,31 @@ pub struct NodeConfigFile {
     pub stacker_dbs: Option<Vec<String>>,
     /// fault injection: fail to push blocks with this probability (0-100)
     pub fault_injection_block_push_fail_probability: Option<u8>,
+    /// Total time, in milliseconds, that may be spent retrying failed `new_block` event
+    /// deliveries for one block, across all event observers
+    pub block_event_retry_budget_ms: Option<u64>,
+    /// Fraction of the block limit above which a transaction's execution cost is reported to
+    /// `cost_near_limit` event observers
+    pub cost_near_limit_threshold: Option<f64>,
+    /// Minimum time, in milliseconds, between two `proposal_response` events sent to the same
+    /// event observer
+    pub proposal_response_min_interval_ms: Option<u64>,
+    /// Directory of Nakamoto blocks that have been moved out of the staging DB
+    pub archival_blocks_dir: Option<String>,
+    /// Most event observers that may be configured
+    pub max_event_observers: Option<usize>,
+    /// Directory in which to record every block this node mines
+    pub mined_block_archive_dir: Option<String>,
+    /// Number of most recent burn block heights whose mined-block archive files are kept
+    pub mined_block_archive_retention: Option<u64>,
+    /// Number of threads over which `new_block` event payloads are serialized
+    pub event_serialization_threads: Option<usize>,
+    /// Number of the most recently dropped mempool transactions to remember
+    pub mempool_drop_history_size: Option<usize>,
+    /// Most event deliveries in flight to one event observer host at once
+    pub event_observer_max_in_flight_per_host: Option<usize>,
+    /// Most event deliveries in flight at once to specific event observer hosts
+    pub event_observer_host_limits: Option<HashMap<String, usize>>,
 }
 
 impl NodeConfigFile {
@@ -2445,6 +2720,48 @@ impl NodeConfigFile {
         let rpc_bind = self.rpc_bind.unwrap_or(default_node_config.rpc_bind);
         let miner = self.miner.unwrap_or(default_node_config.miner);
         let stacker = self.stacker.unwrap_or(default_node_config.stacker);
+        let cost_near_limit_threshold = self
+            .cost_near_limit_threshold
+            .unwrap_or(default_node_config.cost_near_limit_threshold);
+        if !(cost_near_limit_threshold > 0.0 && cost_near_limit_threshold <= 1.0) {
+            return Err(format!(
+                "node.cost_near_limit_threshold must be greater than 0 and at most 1, got {}",
+                cost_near_limit_threshold
+            ));
+        }
+        let max_event_observers = self
+            .max_event_observers
+            .unwrap_or(default_node_config.max_event_observers);
+        if max_event_observers > MAX_EVENT_OBSERVERS {
+            return Err(format!(
+                "node.max_event_observers must be at most {}, got {}",
+                MAX_EVENT_OBSERVERS, max_event_observers
+            ));
+        }
+        let event_serialization_threads = self
+            .event_serialization_threads
+            .unwrap_or(default_node_config.event_serialization_threads);
+        if event_serialization_threads == 0 {
+            return Err("node.event_serialization_threads must be at least 1".into());
+        }
+        let event_observer_max_in_flight_per_host = self
+            .event_observer_max_in_flight_per_host
+            .unwrap_or(default_node_config.event_observer_max_in_flight_per_host);
+        if event_observer_max_in_flight_per_host == 0 {
+            return Err("node.event_observer_max_in_flight_per_host must be at least 1".into());
+        }
+        let event_observer_host_limits = self
+            .event_observer_host_limits
+            .unwrap_or(default_node_config.event_observer_host_limits);
+        if let Some(host) = event_observer_host_limits
+            .iter()
+            .find_map(|(host, max_in_flight)| (*max_in_flight == 0).then_some(host))
+        {
+            return Err(format!(
+                "node.event_observer_host_limits must be at least 1, got 0 for {}",
+                host
+            ));
+        }
         let node_config = NodeConfig {
             name: self.name.unwrap_or(default_node_config.name),
             seed: match self.seed {
@@ -2531,6 +2848,29 @@ impl NodeConfigFile {
             } else {
                 default_node_config.fault_injection_block_push_fail_probability
             },
+            block_event_retry_budget_ms: self
+                .block_event_retry_budget_ms
+                .or(default_node_config.block_event_retry_budget_ms),
+            cost_near_limit_threshold,
+            proposal_response_min_interval_ms: self
+                .proposal_response_min_interval_ms
+                .or(default_node_config.proposal_response_min_interval_ms),
+            archival_blocks_dir: self
+                .archival_blocks_dir
+                .or(default_node_config.archival_blocks_dir),
+            max_event_observers,
+            mined_block_archive_dir: self
+                .mined_block_archive_dir
+                .or(default_node_config.mined_block_archive_dir),
+            mined_block_archive_retention: self
+                .mined_block_archive_retention
+                .or(default_node_config.mined_block_archive_retention),
+            event_serialization_threads,
+            mempool_drop_history_size: self
+                .mempool_drop_history_size
+                .unwrap_or(default_node_config.mempool_drop_history_size),
+            event_observer_max_in_flight_per_host,
+            event_observer_host_limits,
         };
         Ok(node_config)
     }
@@ -2723,9 +3063,173 @@ impl AtlasConfigFile {
 #[derive(Clone, Deserialize, Default, Debug, Hash, PartialEq, Eq, PartialOrd)]
 #[serde(deny_unknown_fields)]
 pub struct EventObserverConfigFile {
+    /// Where events are POSTed, as `host:port` with an optional path. With an `https://`
+    /// prefix, events are sent over TLS, and the host's certificate is verified.
     pub endpoint: String,
     pub events_keys: Vec<String>,
     pub timeout_ms: Option<u64>,
+    /// Number of concurrent connections used to deliver queued events to this observer.
+    /// Values greater than 1 trade delivery order for throughput.
+    pub parallel_connections: Option<u32>,
+    /// Whether to add the winning block-commit's details to `new_block` events, as
+    /// `winner_commit`. This costs a sortition DB lookup per block.
+    pub include_winner_commit: Option<bool>,
+    /// Whether to add the `sortition_id` of the sortition that started each block's tenure, and
+    /// its winning block-commit's `vrf_seed`, to `new_block` events. This costs a sortition DB
+    /// lookup per block.
+    pub include_sortition_info: Option<bool>,
+    /// Whether to add `raw_burnchain_op`, the serialized burnchain operation, to the
+    /// burn-origin transactions in `new_block` events
+    pub include_raw_burnchain_op: Option<bool>,
+    /// Whether to send each element of the array-style `new_mempool_tx` and `attachments/new`
+    /// events as its own request, in order, instead of sending the whole array in one request
+    pub split_array_events: Option<bool>,
+    /// Whether to send potentially-large integer fields (e.g. `block_height`,
+    /// `burn_block_height`, `amt`) as decimal strings, so that JavaScript consumers don't lose
+    /// precision parsing them
+    pub stringify_large_ints: Option<bool>,
+    /// Whether to send events over cleartext HTTP/2 (with prior knowledge), sharing one
+    /// connection between concurrent deliveries, instead of over HTTP/1.1. Needs a node built
+    /// with the `http2-observers` feature.
+    pub http2: Option<bool>,
+    /// Whether to confirm the delivery of each `new_block` event before sending this observer
+    /// any event of another topic (e.g. `stackerdb_chunks` or `proposal_response`). Those events
+    /// wait for the block's delivery, for up to `block_event_retry_budget_ms`, or however long
+    /// it takes without one. Cannot be used with `parallel_connections`.
+    pub strict_block_ordering: Option<bool>,
+    /// Whether to add a `timings` object to `new_block` events, with how long the node took to
+    /// validate and execute the block, and to serialize its events, in microseconds
+    pub include_block_timings: Option<bool>,
+    /// Whether to wrap each payload in an envelope, `{"type": <event path>, "data": <payload>}`,
+    /// for consumers that route events by a field rather than by URL path
+    pub payload_envelope: Option<bool>,
+    /// Whether to send each execution cost as one field per cost dimension of the object that
+    /// holds it (e.g. `anchored_cost_runtime`), instead of as a nested object (`anchored_cost`)
+    pub flatten_execution_costs: Option<bool>,
+    /// Whether to add the mempool's current fee rate percentiles (`fee_rates`) to
+    /// `drop_mempool_tx` events for transactions dropped over their fee, so that wallets can
+    /// suggest a fee bump. Computing them scans the mempool, so this is off by default.
+    pub include_drop_fee_rates: Option<bool>,
+    /// Whether to send a compact `new_block_header` event before each `new_block` event, so
+    /// that this observer learns of new blocks as early as possible
+    pub include_block_header_event: Option<bool>,
+    /// If set, only these payload fields are sent to this observer. Fields are named by
+    /// dot-separated paths, with `[]` for every item of an array, e.g. `transactions[].txid`.
+    pub include_fields: Option<Vec<String>>,
+    /// Payload fields that are not sent to this observer, named as in `include_fields`
+    pub exclude_fields: Option<Vec<String>>,
+    /// If set, a `new_block` payload larger than this many bytes is split into several
+    /// `new_block` requests, each carrying part of its `transactions` and `events`
+    pub max_payload_bytes: Option<u64>,
+    /// If set, payloads that this observer's endpoint fails to take are handed to this
+    /// transport instead of being retried indefinitely. Only `file:<path>` is supported, which
+    /// appends each payload to a local file as a line of JSON.
+    pub fallback_transport: Option<String>,
+    /// Number of delivery attempts the endpoint gets for each payload before it is handed to
+    /// `fallback_transport`. Defaults to 5.
+    pub fallback_after_attempts: Option<u32>,
+    /// If set, a `200` response from this observer only counts as a successful delivery if its
+    /// body contains this string (e.g. `"ok":true`). Other responses are retried.
+    pub success_body_marker: Option<String>,
+    /// If set, the `stackerdb_chunks` events for each StackerDB contract are collected for this
+    /// many milliseconds after the first one, and sent as one payload. Off by default.
+    pub stackerdb_batch_window_ms: Option<u64>,
+    /// If set, each payload is sent with an `X-Partition-Key` header, so that sharded consumers
+    /// can route related payloads to the same shard. Given as `<function>:<partitions>`, where
+    /// the function is `by_address`, `by_contract` or `by_block`, e.g. `by_address:16`.
+    pub partition: Option<String>,
+    /// Whether to send this observer a `new_observer` event when it is registered, describing
+    /// the node and this observer's subscriptions, so that the observer can confirm that it
+    /// can be reached before any real events are sent
+    pub send_startup_event: Option<bool>,
+    /// Whether to add `miner_signature_valid` to Nakamoto blocks' `new_block` events, saying
+    /// whether the block's miner signature was made by the key that the tenure's winning
+    /// block-commit registered. This costs a sortition DB lookup and a key recovery per block.
+    pub include_miner_signature_valid: Option<bool>,
+    /// Whether to add `stx_supply_delta` to `new_block` events, with the STX minted, burned and
+    /// locked by the block's committed transactions. This walks every event of every block,
+    /// even if the observer isn't subscribed to STX events.
+    pub include_stx_supply_delta: Option<bool>,
+    /// Whether to add `raw_header` to `new_block` events, with the block's header as it is
+    /// consensus-serialized, hex-encoded, so that consumers can verify it without
+    /// re-serializing its decoded fields
+    pub include_raw_header: Option<bool>,
+    /// How this observer's payloads are retried, and what becomes of those it gives up on.
+    /// Without it, payloads are retried until they are delivered.
+    pub delivery_policy: Option<DeliveryPolicyConfig>,
+    /// If set, payloads are queued for a thread of this observer's own to deliver, rather than
+    /// delivered by the thread that produced them, so that a slow observer doesn't hold up
+    /// block processing
+    pub delivery_queue: Option<DeliveryQueueConfig>,
+    /// If set, every request sent to this observer is recorded as it goes on the wire, with
+    /// credentials redacted, for debugging. Given as `log`, to log each request at trace level,
+    /// or `file:<path>`, to append each one to a file. Costs a copy and a write of every request.
+    pub wire_capture: Option<String>,
+    /// Whether to accept any certificate from an `https://` endpoint, such as a self-signed one,
+    /// instead of verifying it against the Mozilla root certificates. Anyone on the network path
+    /// can then pose as the observer, so this is not allowed on mainnet.
+    pub tls_skip_verify: Option<bool>,
+    /// PEM file of the CA certificates that an `https://` endpoint's certificate is verified
+    /// against, instead of the Mozilla root certificates, such as an internal CA bundle
+    pub tls_ca_file: Option<String>,
+    /// SHA-256 fingerprint of the certificate that an `https://` endpoint must present, as 64 hex
+    /// digits, which may be separated by colons. Without `tls_ca_file`, a certificate with this
+    /// fingerprint is accepted as is, such as a self-signed one; with it, the certificate must
+    /// also be issued by one of its CAs for the endpoint's host.
+    pub tls_pinned_cert_sha256: Option<String>,
+    /// Headers to send with every request to the observer, such as the `Authorization` of a
+    /// service that requires a bearer token. Their values are never logged.
+    pub headers: Option<BTreeMap<String, String>>,
+    /// Prefix of the names of the streams that a `redis://` observer's payloads are added to,
+    /// followed by each event's path. Defaults to `stacks:`.
+    pub redis_stream_prefix: Option<String>,
+}
+
+/// An event observer's delivery policy, as set in its `delivery_policy` table.
+/// Every field is optional, and the defaults retry each payload until it is delivered.
+#[derive(Clone, Deserialize, Serialize, Default, Debug, Hash, PartialEq, Eq, PartialOrd)]
+#[serde(deny_unknown_fields)]
+pub struct DeliveryPolicyConfig {
+    /// Most attempts at delivering each payload, after which it is given up on
+    pub max_attempts: Option<u32>,
+    /// Wait before the first retry of a payload, in milliseconds. Each retry after it waits
+    /// `backoff_multiplier` times as long as the one before, plus some jitter. Defaults to 100.
+    pub backoff_ms: Option<u64>,
+    /// Factor that the wait grows by from each retry to the next. Defaults to 2; 1 keeps the
+    /// wait at `backoff_ms` (plus jitter).
+    pub backoff_multiplier: Option<u32>,
+    /// Longest wait between retries, in milliseconds. Defaults to 3 times `timeout_ms`.
+    pub max_backoff_ms: Option<u64>,
+    /// If set, only failed responses with these HTTP statuses are retried, and a payload that
+    /// gets any other failed status is given up on at once. Connection failures are always
+    /// retried.
+    pub retryable_statuses: Option<Vec<u16>>,
+    /// If set, each payload that is given up on is POSTed once to this endpoint, at
+    /// `/dead_letter`, along with the URL it was meant for and why it was given up on.
+    /// Otherwise, it is dropped. Like an observer's `endpoint`, it is either a `host:port`, or a
+    /// full `http://` or `https://` URL, whose certificate is verified against the Mozilla root
+    /// certificates.
+    pub dead_letter_endpoint: Option<String>,
+    /// If set, a payload that hasn't been delivered this many milliseconds after it was
+    /// produced is given up on
+    pub max_event_age_ms: Option<u64>,
+    /// If set, a payload is only given up on after `max_attempts`, or handed to the observer's
+    /// `fallback_transport`, once the observer has been failing for this many milliseconds
+    /// without a successful delivery. Until then, its payloads keep being retried, so that a
+    /// brief outage doesn't cost any of them.
+    pub failure_grace_period_ms: Option<u64>,
+}
+
+/// An event observer's delivery queue, as set in its `delivery_queue` table
+#[derive(Clone, Deserialize, Serialize, Default, Debug, Hash, PartialEq, Eq, PartialOrd)]
+#[serde(deny_unknown_fields)]
+pub struct DeliveryQueueConfig {
+    /// Most payloads waiting to be delivered. Defaults to 1024.
+    pub capacity: Option<usize>,
+    /// What happens to a payload produced while the queue is full: `block` waits for room (the
+    /// default), `drop_oldest` drops the oldest queued payload, and `drop_newest` drops the
+    /// new payload
+    pub when_full: Option<String>,
 }
 
 #[derive(Clone, Default, Debug, Hash, PartialEq, Eq, PartialOrd)]
@@ -2733,21 +3237,70 @@ pub struct EventObserverConfig {
     pub endpoint: String,
     pub events_keys: Vec<EventKeyType>,
     pub timeout_ms: u64,
+    pub parallel_connections: u32,
+    pub include_winner_commit: bool,
+    pub include_sortition_info: bool,
+    pub include_raw_burnchain_op: bool,
+    pub split_array_events: bool,
+    pub stringify_large_ints: bool,
+    pub http2: bool,
+    pub strict_block_ordering: bool,
+    pub include_block_timings: bool,
+    pub payload_envelope: bool,
+    pub flatten_execution_costs: bool,
+    pub include_drop_fee_rates: bool,
+    pub include_block_header_event: bool,
+    pub include_fields: Vec<String>,
+    pub exclude_fields: Vec<String>,
+    pub max_payload_bytes: Option<u64>,
+    pub fallback_transport: Option<String>,
+    pub fallback_after_attempts: Option<u32>,
+    pub success_body_marker: Option<String>,
+    pub stackerdb_batch_window_ms: Option<u64>,
+    pub partition: Option<String>,
+    pub send_startup_event: bool,
+    pub include_miner_signature_valid: bool,
+    pub include_stx_supply_delta: bool,
+    pub include_raw_header: bool,
+    pub delivery_policy: DeliveryPolicyConfig,
+    pub delivery_queue: Option<DeliveryQueueConfig>,
+    pub wire_capture: Option<String>,
+    pub tls_skip_verify: bool,
+    pub tls_ca_file: Option<String>,
+    pub tls_pinned_cert_sha256: Option<String>,
+    pub headers: BTreeMap<String, String>,
+    pub redis_stream_prefix: Option<String>,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd)]
 pub enum EventKeyType {
     SmartContractEvent((QualifiedContractIdentifier, String)),
     AssetEvent(AssetIdentifier),
+    /// Contract and asset events of every contract deployed by this principal
+    DeployerEvent(StandardPrincipalData),
+    /// Contract and asset events of every boot contract (e.g. `pox-4` or `signers`)
+    BootContractEvent,
     STXEvent,
     MemPoolTransactions,
     Microblocks,
+    /// Compact notifications of the latest microblock of each microblock stream
+    MicroblockTip,
     AnyEvent,
     BurnchainBlocks,
     MinedBlocks,
     MinedMicroblocks,
     StackerDBChunks,
     BlockProposal,
+    SignerParticipation,
+    StackerSetDiff,
+    CostNearLimit,
+    Rewards,
+    BurnBlockReorg,
+    BlockFork,
+    TipChanged,
+    StackerDBEquivocation,
+    /// One-shot events about the node itself, such as `node_synced`
+    Lifecycle,
 }
 
 impl EventKeyType {
@@ -2772,6 +3325,10 @@ impl EventKeyType {
             return Some(EventKeyType::Microblocks);
         }
 
+        if raw_key == "microblock_tip" {
+            return Some(EventKeyType::MicroblockTip);
+        }
+
         if raw_key == "stackerdb" {
             return Some(EventKeyType::StackerDBChunks);
         }
@@ -2780,6 +3337,52 @@ impl EventKeyType {
             return Some(EventKeyType::BlockProposal);
         }
 
+        if raw_key == "signer_participation" {
+            return Some(EventKeyType::SignerParticipation);
+        }
+
+        if raw_key == "stacker_set_diff" {
+            return Some(EventKeyType::StackerSetDiff);
+        }
+
+        if raw_key == "cost_near_limit" {
+            return Some(EventKeyType::CostNearLimit);
+        }
+
+        if raw_key == "rewards" {
+            return Some(EventKeyType::Rewards);
+        }
+
+        if raw_key == "burn_block_reorg" {
+            return Some(EventKeyType::BurnBlockReorg);
+        }
+
+        if raw_key == "block_fork" {
+            return Some(EventKeyType::BlockFork);
+        }
+
+        if raw_key == "tip_changed" {
+            return Some(EventKeyType::TipChanged);
+        }
+
+        if raw_key == "stackerdb_equivocation" {
+            return Some(EventKeyType::StackerDBEquivocation);
+        }
+
+        if raw_key == "boot_contracts" {
+            return Some(EventKeyType::BootContractEvent);
+        }
+
+        if raw_key == "lifecycle" {
+            return Some(EventKeyType::Lifecycle);
+        }
+
+        if let Some(deployer) = raw_key.strip_suffix(".*") {
+            return PrincipalData::parse_standard_principal(deployer)
+                .ok()
+                .map(EventKeyType::DeployerEvent);
+        }
+
         let comps: Vec<_> = raw_key.split("::").collect();
         if comps.len() == 1 {
             let split: Vec<_> = comps[0].split('.').collect();
@@ -2815,6 +3418,53 @@ impl EventKeyType {
             None
         }
     }
+
+    /// Parse a key written by `to_key_string()`.
+    /// Unlike `from_string()`, this also accepts the miner event keys, which cannot be set in
+    /// the config file.
+    pub fn from_key_string(raw_key: &str) -> Option<EventKeyType> {
+        match raw_key {
+            "mined_blocks" => Some(EventKeyType::MinedBlocks),
+            "mined_microblocks" => Some(EventKeyType::MinedMicroblocks),
+            _ => Self::from_string(raw_key),
+        }
+    }
+
+    /// Render this key the way it is written in the config file's `events_keys`
+    pub fn to_key_string(&self) -> String {
+        match self {
+            EventKeyType::SmartContractEvent((contract_identifier, event_name)) => {
+                format!("{}::{}", contract_identifier, event_name)
+            }
+            EventKeyType::AssetEvent(asset_identifier) => format!(
+                "{}.{}.{}",
+                asset_identifier.contract_identifier.issuer,
+                asset_identifier.contract_identifier.name,
+                asset_identifier.asset_name
+            ),
+            EventKeyType::DeployerEvent(deployer) => format!("{}.*", deployer),
+            EventKeyType::BootContractEvent => "boot_contracts".into(),
+            EventKeyType::STXEvent => "stx".into(),
+            EventKeyType::MemPoolTransactions => "memtx".into(),
+            EventKeyType::Microblocks => "microblocks".into(),
+            EventKeyType::MicroblockTip => "microblock_tip".into(),
+            EventKeyType::AnyEvent => "*".into(),
+            EventKeyType::BurnchainBlocks => "burn_blocks".into(),
+            EventKeyType::MinedBlocks => "mined_blocks".into(),
+            EventKeyType::MinedMicroblocks => "mined_microblocks".into(),
+            EventKeyType::StackerDBChunks => "stackerdb".into(),
+            EventKeyType::BlockProposal => "block_proposal".into(),
+            EventKeyType::SignerParticipation => "signer_participation".into(),
+            EventKeyType::StackerSetDiff => "stacker_set_diff".into(),
+            EventKeyType::CostNearLimit => "cost_near_limit".into(),
+            EventKeyType::Rewards => "rewards".into(),
+            EventKeyType::BurnBlockReorg => "burn_block_reorg".into(),
+            EventKeyType::BlockFork => "block_fork".into(),
+            EventKeyType::TipChanged => "tip_changed".into(),
+            EventKeyType::StackerDBEquivocation => "stackerdb_equivocation".into(),
+            EventKeyType::Lifecycle => "lifecycle".into(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -3106,6 +3756,209 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_load_tenure_stream_allowlist() {
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [connection_options]
+                tenure_stream_allowlist = ["10.0.0.1", "::1"]
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .expect("Expected to be able to parse tenure stream allowlist from file");
+        assert_eq!(
+            config.connection_options.tenure_stream_allowlist,
+            Some(vec![
+                "10.0.0.1".parse::<IpAddr>().unwrap(),
+                "::1".parse::<IpAddr>().unwrap()
+            ])
+        );
+
+        let err = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [connection_options]
+                tenure_stream_allowlist = ["10.0.0.1:20443"]
+                "#,
+            )
+            .unwrap(),
+            false,
+        )
+        .unwrap_err();
+        assert!(err.contains("tenure_stream_allowlist"), "{}", err);
+    }
+
+    #[test]
+    fn should_reject_more_event_observers_than_max() {
+        let observers: HashSet<_> = (0..3)
+            .map(|i| EventObserverConfigFile {
+                endpoint: format!("localhost:{}", 3700 + i),
+                events_keys: vec!["*".to_string()],
+                ..EventObserverConfigFile::default()
+            })
+            .collect();
+
+        let mut config_file = ConfigFile::mocknet();
+        config_file.events_observer = Some(observers);
+        let config = Config::from_config_file(config_file.clone(), false).unwrap();
+        assert_eq!(config.node.max_event_observers, DEFAULT_MAX_EVENT_OBSERVERS);
+        assert_eq!(config.events_observers.len(), 3);
+
+        config_file.node.as_mut().unwrap().max_event_observers = Some(2);
+        let err = Config::from_config_file(config_file.clone(), false).unwrap_err();
+        assert!(err.contains("node.max_event_observers is 2"), "{}", err);
+
+        config_file.node.as_mut().unwrap().max_event_observers = Some(MAX_EVENT_OBSERVERS + 1);
+        let err = Config::from_config_file(config_file, false).unwrap_err();
+        assert!(err.contains("must be at most"), "{}", err);
+    }
+
+    #[test]
+    fn should_reject_stdout_event_observer_on_mainnet() {
+        let stdout_observer = EventObserverConfigFile {
+            endpoint: STDOUT_ENDPOINT.to_string(),
+            events_keys: vec!["*".to_string()],
+            ..EventObserverConfigFile::default()
+        };
+
+        let mut mocknet = ConfigFile::mocknet();
+        mocknet.events_observer = Some(HashSet::from([stdout_observer.clone()]));
+        let config = Config::from_config_file(mocknet, false).unwrap();
+        assert!(config
+            .events_observers
+            .iter()
+            .any(|observer| observer.endpoint == STDOUT_ENDPOINT));
+
+        let mut mainnet = ConfigFile::mainnet();
+        // don't resolve the default bitcoin peer
+        mainnet.burnchain.as_mut().unwrap().peer_host = Some("127.0.0.1".to_string());
+        mainnet.events_observer = Some(HashSet::from([stdout_observer]));
+        let err = Config::from_config_file(mainnet, false).unwrap_err();
+        assert!(err.contains("not allowed on mainnet"), "{}", err);
+    }
+
+    #[test]
+    fn should_reject_redis_stream_prefix_without_redis_endpoint() {
+        let observer = EventObserverConfigFile {
+            endpoint: "redis://127.0.0.1:6379".to_string(),
+            events_keys: vec!["*".to_string()],
+            redis_stream_prefix: Some("testnet:".to_string()),
+            ..EventObserverConfigFile::default()
+        };
+
+        let mut config_file = ConfigFile::mocknet();
+        config_file.events_observer = Some(HashSet::from([observer.clone()]));
+        let config = Config::from_config_file(config_file.clone(), false).unwrap();
+        assert!(config
+            .events_observers
+            .iter()
+            .all(|o| o.redis_stream_prefix.as_deref() == Some("testnet:")));
+
+        config_file.events_observer = Some(HashSet::from([EventObserverConfigFile {
+            endpoint: "localhost:3700".to_string(),
+            ..observer
+        }]));
+        let err = Config::from_config_file(config_file, false).unwrap_err();
+        assert!(err.contains("redis_stream_prefix"), "{}", err);
+    }
+
+    #[test]
+    fn should_reject_tls_skip_verify_on_mainnet() {
+        let observer = EventObserverConfigFile {
+            endpoint: "https://localhost:3700".to_string(),
+            events_keys: vec!["*".to_string()],
+            tls_skip_verify: Some(true),
+            ..EventObserverConfigFile::default()
+        };
+
+        let mut mocknet = ConfigFile::mocknet();
+        mocknet.events_observer = Some(HashSet::from([observer.clone()]));
+        let config = Config::from_config_file(mocknet, false).unwrap();
+        assert!(config.events_observers.iter().all(|o| o.tls_skip_verify));
+
+        let mut mainnet = ConfigFile::mainnet();
+        // don't resolve the default bitcoin peer
+        mainnet.burnchain.as_mut().unwrap().peer_host = Some("127.0.0.1".to_string());
+        mainnet.events_observer = Some(HashSet::from([observer]));
+        let err = Config::from_config_file(mainnet, false).unwrap_err();
+        assert!(err.contains("tls_skip_verify"), "{}", err);
+    }
+
+    #[test]
+    fn should_reject_bad_tls_verification_options() {
+        use stacks::net::tls::test::{TEST_CA_CERT_PEM, TEST_SERVER_CERT};
+        use stacks_common::util::hash::Sha256Sum;
+
+        let dir = tempfile::tempdir().unwrap();
+        let ca_file = dir.path().join("ca.pem").to_str().unwrap().to_string();
+        std::fs::write(&ca_file, TEST_CA_CERT_PEM).unwrap();
+        let bad_ca_file = dir.path().join("bad.pem").to_str().unwrap().to_string();
+        std::fs::write(&bad_ca_file, "not a certificate").unwrap();
+        let fingerprint = Sha256Sum::from_data(TEST_SERVER_CERT).to_hex();
+
+        let observer = EventObserverConfigFile {
+            endpoint: "https://localhost:3700".to_string(),
+            events_keys: vec!["*".to_string()],
+            tls_ca_file: Some(ca_file.clone()),
+            tls_pinned_cert_sha256: Some(fingerprint.clone()),
+            ..EventObserverConfigFile::default()
+        };
+        let load = |observer: EventObserverConfigFile| {
+            let mut config_file = ConfigFile::mocknet();
+            config_file.events_observer = Some(HashSet::from([observer]));
+            Config::from_config_file(config_file, false)
+        };
+        let config = load(observer.clone()).unwrap();
+        assert!(config.events_observers.iter().all(|o| {
+            o.tls_ca_file.as_deref() == Some(ca_file.as_str())
+                && o.tls_pinned_cert_sha256.as_deref() == Some(fingerprint.as_str())
+        }));
+
+        for (bad_observer, expected_err) in [
+            (
+                EventObserverConfigFile {
+                    tls_ca_file: Some(bad_ca_file.clone()),
+                    ..observer.clone()
+                },
+                bad_ca_file.as_str(),
+            ),
+            (
+                EventObserverConfigFile {
+                    tls_ca_file: Some(dir.path().join("missing.pem").to_str().unwrap().into()),
+                    ..observer.clone()
+                },
+                "missing.pem",
+            ),
+            (
+                EventObserverConfigFile {
+                    tls_pinned_cert_sha256: Some("00:11".into()),
+                    ..observer.clone()
+                },
+                "fingerprint",
+            ),
+            (
+                EventObserverConfigFile {
+                    endpoint: "localhost:3700".into(),
+                    ..observer.clone()
+                },
+                "https://",
+            ),
+            (
+                EventObserverConfigFile {
+                    tls_skip_verify: Some(true),
+                    ..observer.clone()
+                },
+                "tls_skip_verify",
+            ),
+        ] {
+            let err = load(bad_observer).unwrap_err();
+            assert!(err.contains(expected_err), "{}", err);
+        }
+    }
+
     #[test]
     fn should_load_affirmation_map() {
         let affirmation_string = "nnnnnnnnnnnnnnnnnnnnnnnnnnnnnnnnppnnnnnnnnnnnnnnnnnnnnnnnnpppppnnnnnnnnnnnnnnnnnnnnnnnpppppppppppppppnnnnnnnnnnnnnnnnnnnnnnnppppppppppnnnnnnnnnnnnnnnnnnnppppnnnnnnnnnnnnnnnnnnnnnnnppppppppnnnnnnnnnnnnnnnnnnnnnnnppnppnnnnnnnnnnnnnnnnnnnnnnnppppnnnnnnnnnnnnnnnnnnnnnnnnnppppppnnnnnnnnnnnnnnnnnnnnnnnnnppnnnnnnnnnnnnnnnnnnnnnnnnnpppppppnnnnnnnnnnnnnnnnnnnnnnnnnnpnnnnnnnnnnnnnnnnnnnnnnnnnpppnppppppppppppppnnppppnpa";
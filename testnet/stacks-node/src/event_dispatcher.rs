@@ -15,32 +15,42 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::hash_map::Entry;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::PathBuf;
-use std::sync::mpsc::{channel, Receiver, Sender};
-use std::sync::Mutex;
-use std::thread::sleep;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, SendError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, sleep};
+use std::time::{Duration, Instant};
 
 use clarity::vm::analysis::contract_interface_builder::build_contract_interface;
 use clarity::vm::costs::ExecutionCost;
-use clarity::vm::events::{FTEventType, NFTEventType, STXEventType};
-use clarity::vm::types::{AssetIdentifier, QualifiedContractIdentifier, Value};
+use clarity::vm::events::{FTEventType, NFTEventType};
+use clarity::vm::types::{
+    AssetIdentifier, QualifiedContractIdentifier, StacksAddressExtensions, StandardPrincipalData,
+    Value,
+};
 use rand::Rng;
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OpenFlags};
 use serde_json::json;
 use stacks::burnchains::{PoxConstants, Txid};
-use stacks::chainstate::burn::operations::BlockstackOperationType;
-use stacks::chainstate::burn::ConsensusHash;
-use stacks::chainstate::coordinator::BlockEventDispatcher;
-use stacks::chainstate::nakamoto::NakamotoBlock;
+use stacks::chainstate::burn::db::sortdb::{get_block_commit_by_txid, SortitionDB};
+use stacks::chainstate::burn::operations::{
+    BlockstackOperationType, LeaderBlockCommitOp, LeaderKeyRegisterOp,
+};
+use stacks::chainstate::burn::{BlockSnapshot, ConsensusHash};
+use stacks::chainstate::coordinator::{BlockEventDispatcher, TipChangeCause};
+use stacks::chainstate::nakamoto::{NakamotoBlock, NakamotoBlockHeader, NakamotoChainState};
 use stacks::chainstate::stacks::address::PoxAddress;
 use stacks::chainstate::stacks::boot::{
     NakamotoSignerEntry, PoxStartCycleInfo, RewardSet, RewardSetData, SIGNERS_NAME,
 };
 use stacks::chainstate::stacks::db::accounts::MinerReward;
 use stacks::chainstate::stacks::db::unconfirmed::ProcessedUnconfirmedState;
-use stacks::chainstate::stacks::db::{MinerRewardInfo, StacksBlockHeaderTypes, StacksHeaderInfo};
+use stacks::chainstate::stacks::db::{
+    BlockProcessingTimings, MinerRewardInfo, StacksBlockHeaderTypes, StacksChainState,
+    StacksHeaderInfo,
+};
 use stacks::chainstate::stacks::events::{
     StackerDBChunksEvent, StacksBlockEventData, StacksTransactionEvent, StacksTransactionReceipt,
     TransactionOrigin,
@@ -48,28 +58,106 @@ use stacks::chainstate::stacks::events::{
 use stacks::chainstate::stacks::miner::TransactionEvent;
 use stacks::chainstate::stacks::{
     StacksBlock, StacksMicroblock, StacksTransaction, TransactionPayload,
+    TransactionPostConditionMode,
+};
+use stacks::core::mempool::{
+    MemPoolDropReason, MemPoolEventDispatcher, MemPoolFeeRatePercentiles, ProposalCallbackReceiver,
 };
-use stacks::core::mempool::{MemPoolDropReason, MemPoolEventDispatcher, ProposalCallbackReceiver};
 use stacks::libstackerdb::StackerDBChunkData;
+use stacks::net::api::geteventhealth::{RPCEventHealthData, RPCEventObserverHealth};
+use stacks::net::api::getmempooldrops::RPCMempoolDropsData;
 use stacks::net::api::postblock_proposal::{
     BlockValidateOk, BlockValidateReject, BlockValidateResponse,
 };
 use stacks::net::atlas::{Attachment, AttachmentInstance};
-use stacks::net::http::HttpRequestContents;
-use stacks::net::httpcore::{send_http_request, StacksHttpRequest};
+use stacks::net::http::{HttpRequestContents, HttpResponsePayload};
+use stacks::net::httpcore::{
+    format_host_port, send_http_request, send_https_request, SendRequestError, StacksHttpRequest,
+};
 use stacks::net::stackerdb::StackerDBEventDispatcher;
+use stacks::net::tls::{parse_certificate_fingerprint, read_pem_certificates, TlsClientConfig};
 use stacks::util::hash::to_hex;
+use stacks::util::{get_epoch_time_ms, get_epoch_time_secs};
 use stacks::util_lib::db::Error as db_error;
 use stacks_common::bitvec::BitVec;
 use stacks_common::codec::StacksMessageCodec;
-use stacks_common::types::chainstate::{BlockHeaderHash, BurnchainHeaderHash, StacksBlockId};
+use stacks_common::types::chainstate::{
+    BlockHeaderHash, BurnchainHeaderHash, StacksBlockId, VRFSeed,
+};
 use stacks_common::types::net::PeerHost;
-use stacks_common::util::hash::{bytes_to_hex, Sha512Trunc256Sum};
+use stacks_common::util::hash::{bytes_to_hex, Hash160, Sha512Trunc256Sum};
 use stacks_common::util::secp256k1::MessageSignature;
 use url::Url;
 
+use self::block_forks::BlockForkTracker;
+use self::block_order::BlockOrderGate;
+use self::block_payload_parts::split_block_payload;
+use self::burn_block_history::{BurnBlockAnnouncement, BurnBlockHistory};
+use self::cost_near_limit::find_near_limit_transactions;
+use self::delivery_health::ObserverHealth;
+pub use self::delivery_policy::DeliveryPolicy;
+use self::delivery_policy::{make_dead_letter_payload, DeliveryOutcome};
+pub use self::delivery_queue::DeliveryQueue;
+use self::delivery_queue::QueuedPayload;
+use self::fallback::ObserverFallback;
+pub use self::fallback::{FallbackTransport, DEFAULT_FALLBACK_AFTER_ATTEMPTS, STDOUT_ENDPOINT};
+use self::flat_costs::flatten_execution_costs;
+pub use self::host_limits::DEFAULT_MAX_IN_FLIGHT_PER_HOST;
+use self::host_limits::{HostDeliveryLimit, HostDeliveryLimits};
+use self::http2::Http2Client;
+use self::large_ints::stringify_large_ints;
+use self::mempool_drop_history::MempoolDropHistory;
+pub use self::mined_block_archive::MinedBlockArchive;
+use self::observer_registry::redact_endpoint;
+use self::parallel_serialization::{parallel_map, NewBlockPayloadParts};
+pub use self::partition::PayloadPartitioner;
+use self::partition::PARTITION_KEY_HEADER;
+pub use self::payload_projection::{FieldPath, PayloadProjection};
+pub use self::payload_transform::PayloadTransform;
+use self::proposal_throttle::{ProposalResponseSlot, ProposalResponseThrottle};
+pub use self::redis_stream::{is_redis_endpoint, RedisStream, DEFAULT_REDIS_STREAM_PREFIX};
+use self::signer_participation::SignerParticipationTracker;
+use self::stacker_set_diff::StackerSetDiffTracker;
+use self::stackerdb_batch::StackerDBChunkBatcher;
+use self::stackerdb_equivocation::StackerDBEquivocationTracker;
+use self::startup_event::make_startup_payload;
+pub use self::startup_event::NodeIdentity;
+pub use self::static_headers::StaticHeaders;
+use self::stx_supply_delta::StxSupplyDelta;
+pub use self::wire_capture::WireCapture;
 use super::config::{EventKeyType, EventObserverConfig};
 
+mod block_forks;
+mod block_order;
+mod block_payload_parts;
+mod burn_block_history;
+mod cost_near_limit;
+mod delivery_health;
+mod delivery_policy;
+mod delivery_queue;
+mod fallback;
+mod flat_costs;
+mod host_limits;
+mod http2;
+mod large_ints;
+mod mempool_drop_history;
+mod mined_block_archive;
+mod observer_registry;
+mod parallel_serialization;
+mod partition;
+mod payload_projection;
+mod payload_transform;
+mod proposal_throttle;
+mod redis_stream;
+mod signer_participation;
+mod stacker_set_diff;
+mod stackerdb_batch;
+mod stackerdb_equivocation;
+mod startup_event;
+mod static_headers;
+mod stx_supply_delta;
+mod wire_capture;
+
 #[derive(Debug, Clone)]
 struct EventObserver {
     /// Path to the database where pending payloads are stored. If `None`, then
@@ -79,6 +167,113 @@ struct EventObserver {
     endpoint: String,
     /// Timeout for sending events to this observer
     timeout: Duration,
+    /// Maximum number of concurrent connections used to deliver this observer's queued
+    /// payloads. If greater than 1, payloads may be delivered out of order.
+    parallel_connections: usize,
+    /// Whether to add the winning block-commit's details to this observer's `new_block` payloads
+    include_winner_commit: bool,
+    /// Whether to add the block's tenure's sortition ID and VRF seed to this observer's
+    /// `new_block` payloads
+    include_sortition_info: bool,
+    /// Whether to add whether each Nakamoto block's miner signature is valid for its tenure's
+    /// miner to this observer's `new_block` payloads
+    include_miner_signature_valid: bool,
+    /// Whether to add the STX minted, burned and locked by each block to this observer's
+    /// `new_block` payloads
+    include_stx_supply_delta: bool,
+    /// Whether to add each block's consensus-serialized header to this observer's `new_block`
+    /// payloads
+    include_raw_header: bool,
+    /// Whether to add the serialized burnchain operation to the burn-origin transactions in
+    /// this observer's `new_block` payloads
+    include_raw_burnchain_op: bool,
+    /// Whether to send each element of this observer's array payloads (`new_mempool_tx` and
+    /// `attachments/new`) as its own request, rather than sending the whole array at once
+    split_array_events: bool,
+    /// Whether to send the potentially-large integer fields of this observer's payloads as
+    /// strings (see `large_ints::LARGE_INT_FIELDS`)
+    stringify_large_ints: bool,
+    /// Whether to send the execution costs in this observer's payloads as prefixed fields of
+    /// the objects that hold them, rather than as nested objects
+    flatten_execution_costs: bool,
+    /// Whether to add the mempool's fee rate percentiles to this observer's
+    /// `drop_mempool_tx` payloads for fee-related drops
+    include_drop_fee_rates: bool,
+    /// Whether to send this observer a `new_block_header` event before each `new_block` event
+    include_block_header_event: bool,
+    /// Whether to add how long appending and serializing each block took to this observer's
+    /// `new_block` payloads
+    include_block_timings: bool,
+    /// Whether to wrap each of this observer's payloads in a `{"type", "data"}` envelope
+    payload_envelope: bool,
+    /// Which fields of each payload this observer receives
+    projection: PayloadProjection,
+    /// If set, `new_block` payloads larger than this many bytes are split into several parts
+    max_payload_bytes: Option<u64>,
+    /// How this observer's payloads are delivered
+    delivery: DeliverySettings,
+    /// If set, payloads are written to this transport instead of being POSTed to `endpoint`
+    direct_transport: Option<FallbackTransport>,
+    /// If set, this observer's `new_block` deliveries are confirmed before any of its events
+    /// of other topics are released
+    block_order: Option<Arc<BlockOrderGate>>,
+    /// If set, the `stackerdb_chunks` events for each contract that arrive within a window are
+    /// merged and sent as one payload
+    stackerdb_batch: Option<Arc<StackerDBChunkBatcher>>,
+    /// If set, every payload is passed through this closure before any of the options above
+    /// are applied to it
+    transform: Option<PayloadTransform>,
+    /// If set, payloads are queued here for this observer's delivery thread, instead of being
+    /// delivered by the thread that sends them
+    delivery_queue: Option<Arc<DeliveryQueue>>,
+}
+
+/// Everything about an attempt at delivering one of an observer's payloads that is particular
+/// to the observer, other than the URL it goes to. A spooled payload is always sent with the
+/// settings of the observer that spooled it.
+#[derive(Debug, Clone)]
+struct DeliverySettings {
+    /// How payloads are retried, and what becomes of those given up on
+    policy: DeliveryPolicy,
+    /// How TLS sessions are set up, for `https://` URLs. Without it, the host's certificate is
+    /// verified against the Mozilla root certificates.
+    tls: Option<TlsClientConfig>,
+    /// Headers sent with every request, such as an `Authorization`. They are not sent to a
+    /// dead-letter endpoint, which is not the observer's.
+    headers: StaticHeaders,
+    /// If set, payloads are POSTed over HTTP/2 with this client, instead of over HTTP/1.1
+    http2: Option<Arc<Http2Client>>,
+    /// If set, payloads that the endpoint fails to take are handed to this fallback transport
+    /// instead of being retried indefinitely
+    fallback: Option<ObserverFallback>,
+    /// Running delivery statistics, for `GET /v2/debug/event_health`
+    health: Arc<ObserverHealth>,
+    /// If set, each payload is sent with its partition key, for sharded consumers
+    partition: Option<PayloadPartitioner>,
+    /// If set, the limit on delivery attempts in flight to the observer's host, shared with
+    /// every other observer of that host
+    host_limit: Option<Arc<HostDeliveryLimit>>,
+    /// If set, every request is recorded here as it goes on the wire
+    wire_capture: Option<WireCapture>,
+    /// If set, a `200` response only counts as a delivery if its body contains this string
+    success_marker: Option<String>,
+}
+
+impl Default for DeliverySettings {
+    fn default() -> Self {
+        Self {
+            policy: DeliveryPolicy::default(),
+            tls: None,
+            headers: StaticHeaders::default(),
+            http2: None,
+            fallback: None,
+            health: Arc::new(ObserverHealth::new()),
+            partition: None,
+            host_limit: None,
+            wire_capture: None,
+            success_marker: None,
+        }
+    }
 }
 
 struct ReceiptPayloadInfo<'a> {
@@ -94,8 +289,10 @@ const STATUS_RESP_TRUE: &str = "success";
 const STATUS_RESP_NOT_COMMITTED: &str = "abort_by_response";
 const STATUS_RESP_POST_CONDITION: &str = "abort_by_post_condition";
 
-/// Update `serve()` in `neon_integrations.rs` with any new paths that need to be tested
+/// Update `serve()` in `neon_integrations.rs` with any new paths that need to be tested, and
+/// `test_dispatcher_event_paths()` with any new paths that `process_*` methods send to
 pub const PATH_MICROBLOCK_SUBMIT: &str = "new_microblocks";
+pub const PATH_MICROBLOCK_TIP: &str = "microblock_tip";
 pub const PATH_MEMPOOL_TX_SUBMIT: &str = "new_mempool_tx";
 pub const PATH_MEMPOOL_TX_DROP: &str = "drop_mempool_tx";
 pub const PATH_MINED_BLOCK: &str = "mined_block";
@@ -104,8 +301,32 @@ pub const PATH_MINED_NAKAMOTO_BLOCK: &str = "mined_nakamoto_block";
 pub const PATH_STACKERDB_CHUNKS: &str = "stackerdb_chunks";
 pub const PATH_BURN_BLOCK_SUBMIT: &str = "new_burn_block";
 pub const PATH_BLOCK_PROCESSED: &str = "new_block";
+pub const PATH_BLOCK_HEADER: &str = "new_block_header";
 pub const PATH_ATTACHMENT_PROCESSED: &str = "attachments/new";
 pub const PATH_PROPOSAL_RESPONSE: &str = "proposal_response";
+pub const PATH_SIGNER_PARTICIPATION: &str = "signer_participation";
+pub const PATH_STACKER_SET_DIFF: &str = "new_stacker_set_diff";
+pub const PATH_COST_NEAR_LIMIT: &str = "cost_near_limit";
+pub const PATH_REWARDS: &str = "new_rewards";
+pub const PATH_BURN_BLOCK_REORG: &str = "burn_block_reorg";
+pub const PATH_BLOCK_FORK: &str = "block_fork";
+pub const PATH_TIP_CHANGED: &str = "tip_changed";
+pub const PATH_STACKERDB_EQUIVOCATION: &str = "stackerdb_equivocation";
+pub const PATH_NODE_SYNCED: &str = "node_synced";
+pub const PATH_NEW_OBSERVER: &str = "new_observer";
+
+/// Default fraction of the block limit above which `cost_near_limit` observers are told about a
+/// transaction's execution cost
+pub const DEFAULT_COST_NEAR_LIMIT_THRESHOLD: f64 = 0.9;
+
+/// Default cap on the number of event observers that may be registered
+pub const DEFAULT_MAX_EVENT_OBSERVERS: usize = 1024;
+
+/// Default number of dropped mempool transactions remembered for replay
+pub const DEFAULT_MEMPOOL_DROP_HISTORY_SIZE: usize = 100;
+
+/// Observers are indexed by `u16`, so no more than this many can ever be registered
+pub const MAX_EVENT_OBSERVERS: usize = u16::MAX as usize + 1;
 
 pub static STACKER_DB_CHANNEL: StackerDBChannel = StackerDBChannel::new();
 
@@ -247,6 +468,27 @@ impl StackerDBChannel {
         }
         None
     }
+
+    /// Handle a failed send on a sender obtained from `is_active()`, which means that its
+    /// receiver was dropped (e.g. the miner thread exited). The event is re-sent on the currently
+    /// registered sender, in case a new receiver was registered in the meantime. If that fails
+    /// too, the registered sender is disconnected as well, so it is cleared so that subsequent
+    /// events do not attempt doomed sends.
+    /// Returns true if the channel was deactivated.
+    pub fn deactivate_if_disconnected(&self, event: StackerDBChunksEvent) -> bool {
+        let mut guard = self
+            .sender_info
+            .lock()
+            .expect("FATAL: poisoned StackerDBChannel lock");
+        let Some(sender_info) = guard.as_ref() else {
+            return false;
+        };
+        if sender_info.sender.send(event).is_ok() {
+            return false;
+        }
+        guard.take();
+        true
+    }
 }
 
 fn serialize_u128_as_string<S>(value: &u128, serializer: S) -> Result<S::Ok, S::Error>
@@ -280,6 +522,201 @@ fn hex_serialize<S: serde::Serializer>(addr: &[u8; 33], s: S) -> Result<S::Ok, S
     s.serialize_str(&to_hex(addr))
 }
 
+/// Normalize a JSON number so that logically-equal numbers have one textual form.
+/// Integer literals are kept verbatim (they may exceed 64 bits, e.g. u128 amounts), and all other
+/// numbers are reduced to the shortest round-trippable `f64` representation, with integral
+/// values written without a fractional part.
+fn canonicalize_json_number(number: &serde_json::Number) -> serde_json::Number {
+    let repr = number.to_string();
+    let digits = repr.strip_prefix('-').unwrap_or(&repr);
+    if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+        if digits.bytes().all(|b| b == b'0') {
+            return serde_json::Number::from(0u64);
+        }
+        return number.clone();
+    }
+
+    let Ok(value) = repr.parse::<f64>() else {
+        return number.clone();
+    };
+    // 2^53 is the largest range in which every integer is exactly representable as an f64
+    if value.fract() == 0.0 && value.abs() < 9_007_199_254_740_992.0 {
+        return serde_json::Number::from(value as i64);
+    }
+    serde_json::Number::from_f64(value).unwrap_or_else(|| number.clone())
+}
+
+/// Produce the canonical form of an event payload: object keys are sorted bytewise at every
+/// level of nesting, and numbers are normalized (see `canonicalize_json_number`).
+/// Serializing the canonical form with `serde_json` yields the same bytes for the same logical
+/// payload, so anything that hashes or signs a payload should do so over
+/// `canonical_json_bytes()`, which doesn't need this copy of the payload.
+pub fn canonicalize_payload(payload: &serde_json::Value) -> serde_json::Value {
+    match payload {
+        serde_json::Value::Object(map) => {
+            let mut keys: Vec<_> = map.keys().collect();
+            keys.sort();
+            let mut canonical = serde_json::Map::new();
+            for key in keys {
+                canonical.insert(key.clone(), canonicalize_payload(&map[key]));
+            }
+            serde_json::Value::Object(canonical)
+        }
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.iter().map(canonicalize_payload).collect())
+        }
+        serde_json::Value::Number(number) => {
+            serde_json::Value::Number(canonicalize_json_number(number))
+        }
+        other => other.clone(),
+    }
+}
+
+/// Serializes the `serde_json::Value` it borrows in its canonical form, as
+/// `canonicalize_payload()` describes, without copying it
+struct CanonicalJson<'a>(&'a serde_json::Value);
+
+impl serde::Serialize for CanonicalJson<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        match self.0 {
+            serde_json::Value::Object(map) => {
+                let mut keys: Vec<_> = map.keys().collect();
+                keys.sort();
+                let mut object = serializer.serialize_map(Some(keys.len()))?;
+                for key in keys {
+                    object.serialize_entry(key, &CanonicalJson(&map[key]))?;
+                }
+                object.end()
+            }
+            serde_json::Value::Array(items) => {
+                serializer.collect_seq(items.iter().map(CanonicalJson))
+            }
+            serde_json::Value::Number(number) => {
+                serde::Serialize::serialize(&canonicalize_json_number(number), serializer)
+            }
+            other => serde::Serialize::serialize(other, serializer),
+        }
+    }
+}
+
+/// Serialize an event payload to its canonical byte representation, which is what its
+/// idempotency key hashes, and what a verifier should reproduce. The payload itself is sent
+/// as it serializes, which may differ from this in its number formatting.
+pub fn canonical_json_bytes(payload: &serde_json::Value) -> Vec<u8> {
+    serde_json::to_vec(&CanonicalJson(payload))
+        .expect("FATAL: failed to serialize canonical JSON payload")
+}
+
+/// The `Idempotency-Key` header sent with an event: a hash of the event's path and canonical
+/// payload. Retries of an event carry the same key. Distinct events differ in their payloads
+/// (e.g. in the block they describe, or in their part number), and so in their keys.
+pub fn idempotency_key(path: &str, payload: &serde_json::Value) -> String {
+    let mut data = path.as_bytes().to_vec();
+    data.push(0);
+    data.extend(canonical_json_bytes(payload));
+    Sha512Trunc256Sum::from_data(&data).to_hex()
+}
+
+/// Wrap `payload` in an envelope that names the event it carries: `{"type": <path>, "data":
+/// <payload>}`. `path` is the event's path without its leading slash, e.g. `new_block`.
+fn wrap_in_envelope(payload: &serde_json::Value, path: &str) -> serde_json::Value {
+    json!({
+        "type": path.trim_start_matches('/'),
+        "data": payload,
+    })
+}
+
+/// The URL that an observer's endpoint, `[https://]host:port[/path]`, stands for. An endpoint
+/// without a scheme is plain HTTP.
+fn observer_base_url(endpoint: &str) -> String {
+    if endpoint.starts_with("https://") || endpoint.starts_with("http://") {
+        endpoint.to_string()
+    } else {
+        format!("http://{}", endpoint)
+    }
+}
+
+/// Whether an observer's endpoint is sent its events over TLS
+fn is_https_endpoint(endpoint: &str) -> bool {
+    endpoint.starts_with("https://")
+}
+
+/// Make the TLS config of an observer with an `https://` endpoint. Its certificate is verified
+/// against the CAs of its `tls_ca_file` if it has one, or else the Mozilla root certificates,
+/// unless it has a pinned certificate fingerprint without a `tls_ca_file`, or `tls_skip_verify`.
+/// Other endpoints have no TLS config, and can't have any of these options.
+pub fn make_observer_tls_config(
+    conf: &EventObserverConfig,
+) -> Result<Option<TlsClientConfig>, String> {
+    let has_custom_verification =
+        conf.tls_ca_file.is_some() || conf.tls_pinned_cert_sha256.is_some();
+    if !is_https_endpoint(&conf.endpoint) {
+        if conf.tls_skip_verify {
+            return Err("tls_skip_verify can only be used with an https:// endpoint".into());
+        }
+        if has_custom_verification {
+            return Err(
+                "tls_ca_file and tls_pinned_cert_sha256 can only be used with an https:// endpoint"
+                    .into(),
+            );
+        }
+        return Ok(None);
+    }
+    if conf.tls_skip_verify {
+        if has_custom_verification {
+            return Err(
+                "tls_skip_verify cannot be used with tls_ca_file or tls_pinned_cert_sha256".into(),
+            );
+        }
+        return Ok(Some(TlsClientConfig::new_unverified()));
+    }
+    let roots = conf
+        .tls_ca_file
+        .as_deref()
+        .map(read_pem_certificates)
+        .transpose()?;
+    let tls = match (conf.tls_pinned_cert_sha256.as_deref(), roots) {
+        (Some(fingerprint), roots) => TlsClientConfig::with_pinned_certificate(
+            parse_certificate_fingerprint(fingerprint)?,
+            roots.as_deref(),
+        ),
+        (None, Some(roots)) => TlsClientConfig::with_root_certificates(&roots),
+        (None, None) => Ok(TlsClientConfig::new()),
+    };
+    tls.map(Some)
+        .map_err(|e| match conf.tls_ca_file.as_deref() {
+            Some(path) => format!("{}: {}", path, e),
+            None => e,
+        })
+}
+
+/// Parse an observer's URL, and find the host and port to connect to. IPv6 hosts are
+/// bracketed, e.g. `[::1]`.
+fn parse_observer_url(full_url: &str) -> Result<(Url, String, u16), String> {
+    let url = Url::parse(full_url).map_err(|e| format!("invalid URL: {}", e))?;
+    let host = url
+        .host_str()
+        .ok_or_else(|| "URL has no host".to_string())?
+        .to_string();
+    let port = url.port_or_known_default().unwrap_or(80);
+    Ok((url, host, port))
+}
+
+/// Whether an observer's response body contains `marker`. A JSON body is matched against its
+/// compact serialization, e.g. `"ok":true`.
+fn response_body_contains(body: &HttpResponsePayload, marker: &str) -> bool {
+    match body {
+        HttpResponsePayload::Empty => marker.is_empty(),
+        HttpResponsePayload::JSON(json) => {
+            serde_json::to_string(json).is_ok_and(|serialized| serialized.contains(marker))
+        }
+        HttpResponsePayload::Bytes(bytes) => String::from_utf8_lossy(bytes).contains(marker),
+        HttpResponsePayload::Text(text) => text.contains(marker),
+    }
+}
+
 #[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct RewardSetEventPayload {
     #[serde(serialize_with = "serialize_pox_addresses")]
@@ -322,9 +759,75 @@ impl RewardSetEventPayload {
     }
 }
 
+/// A spooled payload that hasn't been sent yet
+const PAYLOAD_STATE_PENDING: i64 = 0;
+/// A spooled payload whose delivery has started, which the observer may or may not have taken
+const PAYLOAD_STATE_IN_FLIGHT: i64 = 1;
+/// A spooled payload that the observer has acknowledged, which is never sent again
+const PAYLOAD_STATE_CONFIRMED: i64 = 2;
+
 #[cfg(test)]
 static TEST_EVENT_OBSERVER_SKIP_RETRY: std::sync::Mutex<Option<bool>> = std::sync::Mutex::new(None);
 
+/// A processed block, and what the node knew about it, for the tests to make `new_block`
+/// payloads of with `EventObserver::make_new_block_processed_payload()`. The default is the
+/// genesis block, with nothing else to report.
+#[cfg(test)]
+struct TestNewBlock<'a> {
+    filtered_events: Vec<(usize, &'a (bool, Txid, &'a StacksTransactionEvent))>,
+    block: StacksBlockEventData,
+    metadata: StacksHeaderInfo,
+    receipts: &'a [StacksTransactionReceipt],
+    parent_index_hash: StacksBlockId,
+    winner_txid: Txid,
+    mature_rewards: serde_json::Value,
+    parent_burn_block_hash: BurnchainHeaderHash,
+    parent_burn_block_height: u32,
+    parent_burn_block_timestamp: u64,
+    anchored_consumed: ExecutionCost,
+    mblock_confirmed_consumed: ExecutionCost,
+    pox_constants: PoxConstants,
+    reward_set_data: Option<RewardSetData>,
+    signer_bitvec: Option<BitVec<4000>>,
+    block_timestamp: Option<u64>,
+    coinbase_height: u64,
+    processed_at: u64,
+    winner_commit: Option<serde_json::Value>,
+    sortition_info: Option<serde_json::Value>,
+    miner_signature_valid: Option<bool>,
+    parent_tenure_consensus_hash: Option<ConsensusHash>,
+}
+
+#[cfg(test)]
+impl Default for TestNewBlock<'_> {
+    fn default() -> Self {
+        Self {
+            filtered_events: vec![],
+            block: StacksBlock::genesis_block().into(),
+            metadata: StacksHeaderInfo::regtest_genesis(),
+            receipts: &[],
+            parent_index_hash: StacksBlockId([0; 32]),
+            winner_txid: Txid([0; 32]),
+            mature_rewards: serde_json::Value::Array(vec![]),
+            parent_burn_block_hash: BurnchainHeaderHash([0; 32]),
+            parent_burn_block_height: 0,
+            parent_burn_block_timestamp: 0,
+            anchored_consumed: ExecutionCost::zero(),
+            mblock_confirmed_consumed: ExecutionCost::zero(),
+            pox_constants: PoxConstants::testnet_default(),
+            reward_set_data: None,
+            signer_bitvec: None,
+            block_timestamp: None,
+            coinbase_height: 0,
+            processed_at: 0,
+            winner_commit: None,
+            sortition_info: None,
+            miner_signature_valid: None,
+            parent_tenure_consensus_hash: None,
+        }
+    }
+}
+
 impl EventObserver {
     fn init_db(db_path: &str) -> Result<Connection, db_error> {
         let conn = Connection::open(db_path)?;
@@ -333,24 +836,61 @@ impl EventObserver {
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 url TEXT NOT NULL,
                 payload TEXT NOT NULL,
-                timeout INTEGER NOT NULL
+                timeout INTEGER NOT NULL,
+                state INTEGER NOT NULL DEFAULT 0,
+                created_at INTEGER,
+                observer TEXT
             )",
             [],
         )?;
+        // databases made before payloads had a delivery state only hold unsent payloads
+        let has_state = conn
+            .prepare("SELECT 1 FROM pragma_table_info('pending_payloads') WHERE name = 'state'")?
+            .exists([])?;
+        if !has_state {
+            conn.execute(
+                "ALTER TABLE pending_payloads ADD COLUMN state INTEGER NOT NULL DEFAULT 0",
+                [],
+            )?;
+        }
+        // nor did they record when each payload was produced, so their payloads are of unknown age
+        let has_created_at = conn
+            .prepare(
+                "SELECT 1 FROM pragma_table_info('pending_payloads') WHERE name = 'created_at'",
+            )?
+            .exists([])?;
+        if !has_created_at {
+            conn.execute(
+                "ALTER TABLE pending_payloads ADD COLUMN created_at INTEGER",
+                [],
+            )?;
+        }
+        // nor which observer each payload was for, so their payloads are matched to observers
+        // by URL
+        let has_observer = conn
+            .prepare("SELECT 1 FROM pragma_table_info('pending_payloads') WHERE name = 'observer'")?
+            .exists([])?;
+        if !has_observer {
+            conn.execute("ALTER TABLE pending_payloads ADD COLUMN observer TEXT", [])?;
+        }
+        // a drain that was interrupted may have left acknowledged payloads behind
+        Self::purge_confirmed_payloads(&conn)?;
         Ok(conn)
     }
 
     fn insert_payload(
         conn: &Connection,
+        observer: &str,
         url: &str,
         payload: &serde_json::Value,
         timeout: Duration,
     ) -> Result<(), db_error> {
-        let payload_text = payload.to_string();
+        let payload_text = serde_json::to_string(payload)?;
         let timeout_ms: u64 = timeout.as_millis().try_into().expect("Timeout too large");
+        let created_at_ms = u64::try_from(get_epoch_time_ms()).unwrap_or(u64::MAX);
         conn.execute(
-            "INSERT INTO pending_payloads (url, payload, timeout) VALUES (?1, ?2, ?3)",
-            params![url, payload_text, timeout_ms],
+            "INSERT INTO pending_payloads (url, payload, timeout, state, created_at, observer) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![url, payload_text, timeout_ms, PAYLOAD_STATE_PENDING, created_at_ms, observer],
         )?;
         Ok(())
     }
@@ -358,6 +898,7 @@ impl EventObserver {
     /// Insert a payload into the database, retrying on failure.
     fn insert_payload_with_retry(
         conn: &Connection,
+        observer: &str,
         url: &str,
         payload: &serde_json::Value,
         timeout: Duration,
@@ -367,7 +908,7 @@ impl EventObserver {
         let max_backoff = Duration::from_secs(5); // Cap the backoff duration
 
         loop {
-            match Self::insert_payload(conn, url, payload, timeout) {
+            match Self::insert_payload(conn, observer, url, payload, timeout) {
                 Ok(_) => {
                     // Successful insert, break the loop
                     return;
@@ -391,13 +932,15 @@ impl EventObserver {
         }
     }
 
+    #[cfg(test)]
     fn get_pending_payloads(
         conn: &Connection,
     ) -> Result<Vec<(i64, String, serde_json::Value, u64)>, db_error> {
-        let mut stmt =
-            conn.prepare("SELECT id, url, payload, timeout FROM pending_payloads ORDER BY id")?;
+        let mut stmt = conn.prepare(
+            "SELECT id, url, payload, timeout FROM pending_payloads WHERE state != ?1 ORDER BY id",
+        )?;
         let payload_iter = stmt.query_and_then(
-            [],
+            params![PAYLOAD_STATE_CONFIRMED],
             |row| -> Result<(i64, String, serde_json::Value, u64), db_error> {
                 let id: i64 = row.get(0)?;
                 let url: String = row.get(1)?;
@@ -411,26 +954,227 @@ impl EventObserver {
         payload_iter.collect()
     }
 
+    /// Whether a pending payload that was spooled before payloads recorded their observer, and
+    /// was meant for `url`, is one of `observer`'s. Its URL must be one of `observer`'s event
+    /// paths, and not the path of an observer whose endpoint is under `observer`'s.
+    fn is_legacy_payload_for(observer: &str, url: &str) -> bool {
+        url.strip_prefix(observer)
+            .and_then(|rest| rest.strip_prefix('/'))
+            .is_some_and(|path| path == PATH_ATTACHMENT_PROCESSED || !path.contains('/'))
+    }
+
+    /// Get the pending payloads in the database that were spooled for `observer`, in the order
+    /// they were produced. `observer` is the observer's base URL.
+    fn get_observer_pending_payloads(
+        conn: &Connection,
+        observer: &str,
+    ) -> Result<Vec<(i64, String, serde_json::Value, u64)>, db_error> {
+        let mut stmt = conn.prepare(
+            "SELECT id, url, payload, timeout, observer FROM pending_payloads
+             WHERE state != ?1 AND (observer = ?2 OR observer IS NULL) ORDER BY id",
+        )?;
+        let payload_iter = stmt.query_and_then(
+            params![PAYLOAD_STATE_CONFIRMED, observer],
+            |row| -> Result<Option<(i64, String, serde_json::Value, u64)>, db_error> {
+                let id: i64 = row.get(0)?;
+                let url: String = row.get(1)?;
+                let owner: Option<String> = row.get(4)?;
+                if owner.is_none() && !Self::is_legacy_payload_for(observer, &url) {
+                    return Ok(None);
+                }
+                let payload_text: String = row.get(2)?;
+                let payload: serde_json::Value = serde_json::from_str(&payload_text)
+                    .map_err(|e| db_error::SerializationError(e))?;
+                let timeout_ms: u64 = row.get(3)?;
+                Ok(Some((id, url, payload, timeout_ms)))
+            },
+        )?;
+        payload_iter
+            .filter_map(|payload| payload.transpose())
+            .collect()
+    }
+
+    /// When a pending payload was produced, in milliseconds since the epoch, if that was recorded
+    fn get_payload_created_at(conn: &Connection, id: i64) -> Result<Option<u64>, db_error> {
+        let created_at_ms = conn.query_row(
+            "SELECT created_at FROM pending_payloads WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        )?;
+        Ok(created_at_ms)
+    }
+
+    /// When a pending payload becomes too old for `policy` to deliver, if it has a
+    /// `max_event_age`. A payload whose age can't be found is taken to be new.
+    fn payload_expires_at(conn: &Connection, id: i64, policy: &DeliveryPolicy) -> Option<Instant> {
+        if policy.max_event_age.is_none() {
+            return None;
+        }
+        let created_at_ms = Self::get_payload_created_at(conn, id).unwrap_or_else(|e| {
+            error!(
+                "Event observer: failed to read when a pending payload was produced";
+                "error" => ?e
+            );
+            None
+        });
+        policy.expires_at(created_at_ms)
+    }
+
+    #[cfg(test)]
     fn delete_payload(conn: &Connection, id: i64) -> Result<(), db_error> {
         conn.execute("DELETE FROM pending_payloads WHERE id = ?1", params![id])?;
         Ok(())
     }
 
-    fn process_pending_payloads(conn: &Connection) {
-        let pending_payloads = match Self::get_pending_payloads(conn) {
+    /// Record that the delivery of each of `ids` has started. A payload that is still in flight
+    /// when the node stops is sent again, with the same idempotency key, when the node restarts.
+    fn mark_payloads_in_flight(conn: &Connection, ids: &[i64]) -> Result<(), db_error> {
+        let mut stmt =
+            conn.prepare("UPDATE pending_payloads SET state = ?1 WHERE id = ?2 AND state != ?3")?;
+        for id in ids {
+            stmt.execute(params![
+                PAYLOAD_STATE_IN_FLIGHT,
+                id,
+                PAYLOAD_STATE_CONFIRMED
+            ])?;
+        }
+        Ok(())
+    }
+
+    /// Record that the observer acknowledged a payload, so that it is never sent again
+    fn confirm_payload(conn: &Connection, id: i64) -> Result<(), db_error> {
+        conn.execute(
+            "UPDATE pending_payloads SET state = ?1 WHERE id = ?2",
+            params![PAYLOAD_STATE_CONFIRMED, id],
+        )?;
+        Ok(())
+    }
+
+    /// Delete every acknowledged payload from the database
+    fn purge_confirmed_payloads(conn: &Connection) -> Result<(), db_error> {
+        conn.execute(
+            "DELETE FROM pending_payloads WHERE state = ?1",
+            params![PAYLOAD_STATE_CONFIRMED],
+        )?;
+        Ok(())
+    }
+
+    /// Log a failure to record the delivery state of a pending payload. The payload is then
+    /// only at risk of being sent again, which its idempotency key makes harmless.
+    fn log_payload_state_error(e: db_error) {
+        error!(
+            "Event observer: failed to record the delivery state of a pending payload";
+            "error" => ?e
+        );
+    }
+
+    /// Count the pending payloads in the database that were spooled for `observer`
+    fn count_pending_payloads(conn: &Connection, observer: &str) -> Result<u64, db_error> {
+        let mut stmt = conn.prepare(
+            "SELECT url, observer FROM pending_payloads
+             WHERE state != ?1 AND (observer = ?2 OR observer IS NULL)",
+        )?;
+        let mut rows = stmt.query(params![PAYLOAD_STATE_CONFIRMED, observer])?;
+        let mut count = 0;
+        while let Some(row) = rows.next()? {
+            let url: String = row.get(0)?;
+            let owner: Option<String> = row.get(1)?;
+            if owner.is_some() || Self::is_legacy_payload_for(observer, &url) {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// Report this observer's event delivery health, with any credentials in its endpoint
+    /// redacted. Queue depth is 0 if the observer has no pending-payload database.
+    fn delivery_health(&self) -> RPCEventObserverHealth {
+        let queue_depth = match &self.db_path {
+            Some(db_path) => Connection::open(db_path)
+                .map_err(db_error::from)
+                .and_then(|conn| {
+                    Self::count_pending_payloads(&conn, &observer_base_url(&self.endpoint))
+                })
+                .unwrap_or_else(|e| {
+                    warn!(
+                        "Event observer: failed to count pending payloads";
+                        "endpoint" => redact_endpoint(&self.endpoint), "error" => ?e
+                    );
+                    0
+                }),
+            None => 0,
+        };
+        let fallback_active = self
+            .delivery
+            .fallback
+            .as_ref()
+            .is_some_and(|fallback| fallback.is_active());
+        self.delivery.health.report(
+            redact_endpoint(&self.endpoint),
+            queue_depth,
+            fallback_active,
+        )
+    }
+
+    /// Send `observer`'s pending payloads in the database, with its delivery `settings`,
+    /// confirming each one once it is delivered. Only the payloads spooled for `observer` are
+    /// sent, so that none goes out with another observer's headers, TLS or delivery policy.
+    /// Each payload is marked in flight before it is sent, and confirmed payloads are deleted
+    /// once the drain is over, so that if the node stops mid-drain, only the payloads that
+    /// weren't acknowledged are sent again when it restarts.
+    /// If `parallel_connections` is more than 1, the payloads are sent over up to that many
+    /// concurrent connections; otherwise, they are sent one at a time, in order, and a payload
+    /// that isn't delivered stops the drain, leaving it and the payloads after it for next time.
+    /// If `retry_deadline` is given, failed deliveries are not retried past it. A payload that
+    /// isn't delivered by then stays in the database, and is sent again the next time the
+    /// observer's pending payloads are processed.
+    fn process_pending_payloads(
+        conn: &Connection,
+        observer: &str,
+        parallel_connections: usize,
+        retry_deadline: Option<Instant>,
+        settings: &DeliverySettings,
+    ) {
+        let pending_payloads = match Self::get_observer_pending_payloads(conn, observer) {
             Ok(payloads) => payloads,
             Err(e) => {
                 error!(
                     "Event observer: failed to retrieve pending payloads from database";
-                    "error" => ?e
+                    "endpoint" => redact_endpoint(observer), "error" => ?e
                 );
                 return;
             }
         };
 
+        if parallel_connections > 1 {
+            Self::send_payloads_in_parallel(
+                conn,
+                pending_payloads,
+                parallel_connections,
+                retry_deadline,
+                settings,
+            );
+            Self::purge_confirmed_payloads(conn).unwrap_or_else(Self::log_payload_state_error);
+            return;
+        }
+
         for (id, url, payload, timeout_ms) in pending_payloads {
             let timeout = Duration::from_millis(timeout_ms);
-            Self::send_payload_directly(&payload, &url, timeout);
+            let expires_at = Self::payload_expires_at(conn, id, &settings.policy);
+            if let Err(e) = Self::mark_payloads_in_flight(conn, &[id]) {
+                Self::log_payload_state_error(e);
+            }
+            if !Self::send_payload_with_fallback(
+                &payload,
+                &url,
+                timeout,
+                retry_deadline,
+                expires_at,
+                settings,
+            ) {
+                // leave this and all later payloads for next time, to keep them in order
+                break;
+            }
 
             #[cfg(test)]
             if TEST_EVENT_OBSERVER_SKIP_RETRY
@@ -438,63 +1182,364 @@ impl EventObserver {
                 .unwrap()
                 .unwrap_or(false)
             {
-                warn!("Fault injection: delete_payload");
+                warn!("Fault injection: confirm_payload");
                 return;
             }
 
-            if let Err(e) = Self::delete_payload(conn, id) {
-                error!(
-                    "Event observer: failed to delete pending payload from database";
-                    "error" => ?e
-                );
+            if let Err(e) = Self::confirm_payload(conn, id) {
+                Self::log_payload_state_error(e);
             }
         }
+        Self::purge_confirmed_payloads(conn).unwrap_or_else(Self::log_payload_state_error);
     }
 
-    fn send_payload_directly(payload: &serde_json::Value, full_url: &str, timeout: Duration) {
-        debug!(
-            "Event dispatcher: Sending payload"; "url" => %full_url, "payload" => ?payload
+    /// Send payloads over up to `connections` concurrent connections, with the delivery
+    /// `settings` of the observer they were spooled for, confirming each one in the database
+    /// once it is delivered. They are all marked in flight before any is sent. Each connection
+    /// takes the next undelivered payload as soon as it is free, so payloads may be delivered
+    /// (and retried) in any order. Payloads that can't be delivered before `retry_deadline` are
+    /// left in the database, unless the observer's fallback takes them. With HTTP/2, the
+    /// connections are concurrent streams of one connection, and with a host limit, each
+    /// delivery attempt waits for a slot of it, whatever the number of connections.
+    fn send_payloads_in_parallel(
+        conn: &Connection,
+        payloads: Vec<(i64, String, serde_json::Value, u64)>,
+        connections: usize,
+        retry_deadline: Option<Instant>,
+        settings: &DeliverySettings,
+    ) {
+        let ids: Vec<_> = payloads.iter().map(|(id, ..)| *id).collect();
+        if let Err(e) = Self::mark_payloads_in_flight(conn, &ids) {
+            Self::log_payload_state_error(e);
+        }
+        let expiries: Vec<_> = ids
+            .iter()
+            .map(|id| Self::payload_expires_at(conn, *id, &settings.policy))
+            .collect();
+        let next_payload = AtomicUsize::new(0);
+        let (delivered_tx, delivered_rx) = channel();
+        thread::scope(|s| {
+            for _ in 0..connections.min(payloads.len()) {
+                let delivered_tx = delivered_tx.clone();
+                let payloads = &payloads;
+                let expiries = &expiries;
+                let next_payload = &next_payload;
+                s.spawn(move || loop {
+                    let index = next_payload.fetch_add(1, Ordering::SeqCst);
+                    let Some((id, url, payload, timeout_ms)) = payloads.get(index) else {
+                        return;
+                    };
+                    let delivered = Self::send_payload_with_fallback(
+                        payload,
+                        url,
+                        Duration::from_millis(*timeout_ms),
+                        retry_deadline,
+                        expiries[index],
+                        settings,
+                    );
+
+                    #[cfg(test)]
+                    if TEST_EVENT_OBSERVER_SKIP_RETRY
+                        .lock()
+                        .unwrap()
+                        .unwrap_or(false)
+                    {
+                        warn!("Fault injection: confirm_payload");
+                        return;
+                    }
+
+                    if !delivered {
+                        continue;
+                    }
+                    if delivered_tx.send(*id).is_err() {
+                        return;
+                    }
+                });
+            }
+            drop(delivered_tx);
+
+            // the database connection cannot be shared, so confirmations happen on this thread
+            for id in delivered_rx.iter() {
+                if let Err(e) = Self::confirm_payload(conn, id) {
+                    Self::log_payload_state_error(e);
+                }
+            }
+        });
+    }
+
+    /// Send a payload with `send_payload_directly()`. If `settings` has a fallback, then the
+    /// payload gets `fallback.after_attempts` attempts, after which it is handed to the fallback
+    /// transport. Returns `true` if either one took the payload.
+    /// The payload is retried as `settings.policy` says, until `expires_at`, if given. If the
+    /// policy gives up on it, then it goes to `send_dead_letter()` instead, even if there is a
+    /// fallback, and this returns what that returns.
+    fn send_payload_with_fallback(
+        payload: &serde_json::Value,
+        full_url: &str,
+        timeout: Duration,
+        retry_deadline: Option<Instant>,
+        expires_at: Option<Instant>,
+        settings: &DeliverySettings,
+    ) -> bool {
+        let fallback = settings.fallback.as_ref();
+        let outcome = Self::send_payload_directly(
+            payload,
+            full_url,
+            timeout,
+            retry_deadline,
+            fallback.map(|fallback| fallback.after_attempts),
+            expires_at,
+            settings,
+        );
+        match outcome {
+            DeliveryOutcome::Delivered => {
+                if let Some(fallback) = fallback {
+                    fallback.primary_delivered(full_url);
+                }
+                true
+            }
+            DeliveryOutcome::GaveUp(reason) => {
+                Self::send_dead_letter(payload, full_url, timeout, &settings.policy, &reason)
+            }
+            DeliveryOutcome::Deferred => {
+                let Some(fallback) = fallback else {
+                    return false;
+                };
+                if !fallback.send(full_url, payload) {
+                    return false;
+                }
+                settings.health.record_fallback_delivery();
+                true
+            }
+        }
+    }
+
+    /// Hand a payload meant for `full_url`, which `policy` gave up on for `reason`, to the
+    /// policy's dead-letter endpoint, which gets one attempt at it, with the default delivery
+    /// settings rather than the observer's. If the policy has no dead-letter endpoint, then the
+    /// payload is dropped. Returns `false` if the dead-letter endpoint didn't take the payload,
+    /// in which case it is left to be sent again.
+    fn send_dead_letter(
+        payload: &serde_json::Value,
+        full_url: &str,
+        timeout: Duration,
+        policy: &DeliveryPolicy,
+        reason: &str,
+    ) -> bool {
+        let Some(dead_letter_url) = policy.dead_letter_url.as_deref() else {
+            error!(
+                "Event dispatcher: dropping payload that the delivery policy gave up on";
+                "url" => %full_url, "reason" => reason
+            );
+            return true;
+        };
+        let dead_letter = make_dead_letter_payload(full_url, payload, reason);
+        let outcome = Self::send_payload_directly(
+            &dead_letter,
+            dead_letter_url,
+            timeout,
+            None,
+            Some(1),
+            None,
+            &DeliverySettings::default(),
         );
+        if outcome != DeliveryOutcome::Delivered {
+            warn!(
+                "Event dispatcher: dead-letter endpoint did not take payload, keeping it";
+                "url" => %full_url, "dead_letter_url" => dead_letter_url
+            );
+            return false;
+        }
+        true
+    }
 
-        let url = Url::parse(full_url)
-            .unwrap_or_else(|_| panic!("Event dispatcher: unable to parse {} as a URL", full_url));
+    /// Send a payload with the delivery `settings` of the observer it is for, retrying with
+    /// backoff until it is delivered.
+    /// If `retry_deadline` is given, then retries stop at that time, and if `max_attempts` is
+    /// given, then retries stop after that many attempts. Either way, this returns
+    /// `DeliveryOutcome::Deferred` if the payload was not delivered.
+    /// The policy's backoff is used, only the failed statuses it deems retryable are retried,
+    /// and this returns `DeliveryOutcome::GaveUp` once its `max_attempts` have failed, or once
+    /// it is too late to retry before `expires_at`. Otherwise, this only returns once the
+    /// payload is delivered.
+    /// Neither the policy's `max_attempts` nor `max_attempts` stop the retries while the
+    /// observer is within the policy's failure grace period.
+    /// Each attempt's outcome is recorded in the settings' health.
+    /// With a success marker, a `200` response only counts as a delivery if its body contains
+    /// it; otherwise, the attempt failed and is retried.
+    /// With an HTTP/2 client, each attempt is a request over its HTTP/2 connection, rather than
+    /// a new HTTP/1.1 connection. The timeout applies to each attempt either way.
+    /// With a partitioner, each attempt carries the payload's partition key in the
+    /// `X-Partition-Key` header.
+    /// With a host limit, each attempt first waits for one of its slots, which is given back
+    /// before any backoff, so that a failing payload doesn't hold up other deliveries to the
+    /// same host.
+    /// With a wire capture, each attempt's request is recorded in it.
+    /// An `https://` payload is sent over a TLS session with the URL's host, set up as the
+    /// settings' TLS config says.
+    /// Each attempt carries the settings' headers. Their values are never logged.
+    /// A payload for a malformed `full_url` can never be delivered, so it is given up on.
+    fn send_payload_directly(
+        payload: &serde_json::Value,
+        full_url: &str,
+        timeout: Duration,
+        retry_deadline: Option<Instant>,
+        max_attempts: Option<u32>,
+        expires_at: Option<Instant>,
+        settings: &DeliverySettings,
+    ) -> DeliveryOutcome {
+        let DeliverySettings {
+            policy,
+            tls,
+            headers,
+            http2,
+            health,
+            partition,
+            host_limit,
+            wire_capture,
+            success_marker,
+            ..
+        } = settings;
+        debug!(
+            "Event dispatcher: Sending payload";
+            "url" => %full_url, "headers" => ?headers, "payload" => ?payload
+        );
 
-        let host = url.host_str().expect("Invalid URL: missing host");
-        let port = url.port_or_known_default().unwrap_or(80);
-        let peerhost: PeerHost = format!("{host}:{port}")
+        let (url, host, port) = match parse_observer_url(full_url) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                error!(
+                    "Event dispatcher: cannot send payload to a malformed URL, giving up on it";
+                    "url" => redact_endpoint(full_url), "error" => %e
+                );
+                return DeliveryOutcome::GaveUp(format!("malformed URL: {}", e));
+            }
+        };
+        if expires_at.is_some_and(|expires_at| Instant::now() >= expires_at) {
+            warn!(
+                "Event dispatcher: payload is older than the delivery policy allows, giving up on it";
+                "url" => %url
+            );
+            return DeliveryOutcome::GaveUp("payload is older than max_event_age_ms".into());
+        }
+        let host = host.as_str();
+        let peerhost: PeerHost = format_host_port(host, port)
             .parse()
             .unwrap_or(PeerHost::DNS(host.to_string(), port));
 
-        let mut backoff = Duration::from_millis(100);
+        let idempotency_key = idempotency_key(url.path(), payload);
+        let extra_headers: Vec<_> = partition
+            .as_ref()
+            .map(|partitioner| {
+                (
+                    PARTITION_KEY_HEADER,
+                    partitioner.partition_key(url.path(), payload).to_string(),
+                )
+            })
+            .into_iter()
+            .chain(
+                headers
+                    .iter()
+                    .map(|(name, value)| (name, value.to_string())),
+            )
+            .collect();
+        let default_tls;
+        let tls = match (url.scheme(), tls.as_ref()) {
+            ("https", Some(tls)) => Some(tls),
+            ("https", None) => {
+                default_tls = TlsClientConfig::new();
+                Some(&default_tls)
+            }
+            _ => None,
+        };
+        let mut backoff = policy.initial_backoff;
         let mut attempts: i32 = 0;
-        // Cap the backoff at 3x the timeout
-        let max_backoff = timeout.saturating_mul(3);
 
         loop {
-            let mut request = StacksHttpRequest::new_for_peer(
-                peerhost.clone(),
-                "POST".into(),
-                url.path().into(),
-                HttpRequestContents::new().payload_json(payload.clone()),
-            )
-            .unwrap_or_else(|_| panic!("FATAL: failed to encode infallible data as HTTP request"));
-            request.add_header("Connection".into(), "close".into());
-            match send_http_request(host, port, request, timeout) {
-                Ok(response) => {
-                    if response.preamble().status_code == 200 {
-                        debug!(
-                            "Event dispatcher: Successful POST"; "url" => %url
+            let permit = host_limit.as_deref().map(HostDeliveryLimit::acquire);
+            let result = match http2.as_deref() {
+                Some(client) => {
+                    if let Some(capture) = wire_capture.as_ref() {
+                        let mut captured_headers = vec![
+                            ("Content-Type", "application/json".to_string()),
+                            ("Idempotency-Key", idempotency_key.clone()),
+                        ];
+                        captured_headers.extend(extra_headers.iter().cloned());
+                        let body = serde_json::to_vec(payload).unwrap_or_default();
+                        capture.record_http2_request(&url, &captured_headers, &body, Some(headers));
+                    }
+                    client
+                        .post(&url, payload, &idempotency_key, &extra_headers, timeout)
+                        .map_err(|e| format!("HTTP/2 request failed: {}", e))
+                }
+                None => {
+                    let mut request = StacksHttpRequest::new_for_peer(
+                        peerhost.clone(),
+                        "POST".into(),
+                        url.path().into(),
+                        HttpRequestContents::new().payload_json(payload.clone()),
+                    )
+                    .unwrap_or_else(|_| {
+                        panic!("FATAL: failed to encode infallible data as HTTP request")
+                    });
+                    request.add_header("Connection".into(), "close".into());
+                    request.add_header("Idempotency-Key".into(), idempotency_key.clone());
+                    for (name, value) in extra_headers.iter() {
+                        request.add_header(name.to_string(), value.clone());
+                    }
+                    if let Some(capture) = wire_capture.as_ref() {
+                        capture.record_request(&url, &request, Some(headers));
+                    }
+                    match tls {
+                        Some(tls) => send_https_request(host, port, request, timeout, tls),
+                        None => send_http_request(host, port, request, timeout),
+                    }
+                    .map(|response| {
+                        let (preamble, body) = response.destruct();
+                        (preamble.status_code, body)
+                    })
+                    .or_else(|e| match e {
+                        // an error status is the delivery policy's to judge, as over HTTP/2
+                        SendRequestError::HttpStatus(status_code, msg) => {
+                            Ok((status_code, HttpResponsePayload::Text(msg)))
+                        }
+                        e => Err(format!("{:?}", &e)),
+                    })
+                }
+            };
+            drop(permit);
+            match result {
+                Ok((status_code, body)) => {
+                    if status_code != 200 {
+                        error!(
+                            "Event dispatcher: Failed POST"; "url" => %url, "status_code" => status_code, "response" => ?body
+                        );
+                        if !policy.is_retryable(status_code) {
+                            health.record_failure();
+                            return DeliveryOutcome::GaveUp(format!(
+                                "observer responded with status {}, which is not retryable",
+                                status_code
+                            ));
+                        }
+                    } else if let Some(marker) = success_marker
+                        .as_deref()
+                        .filter(|marker| !response_body_contains(&body, marker))
+                    {
+                        warn!(
+                            "Event dispatcher: POST response is missing the success marker";
+                            "url" => %url, "marker" => marker, "response" => ?body
                         );
-                        break;
                     } else {
-                        error!(
-                            "Event dispatcher: Failed POST"; "url" => %url, "response" => ?response.preamble()
+                        debug!(
+                            "Event dispatcher: Successful POST"; "url" => %url
                         );
+                        health.record_success();
+                        return DeliveryOutcome::Delivered;
                     }
                 }
                 Err(err) => {
                     warn!(
-                        "Event dispatcher: connection or request failed to {}:{} - {:?}",
+                        "Event dispatcher: connection or request failed to {}:{} - {}",
                         &host, &port, err;
                         "backoff" => ?backoff,
                         "attempts" => attempts
@@ -502,6 +1547,8 @@ impl EventObserver {
                 }
             }
 
+            health.record_failure();
+
             #[cfg(test)]
             if TEST_EVENT_OBSERVER_SKIP_RETRY
                 .lock()
@@ -509,20 +1556,63 @@ impl EventObserver {
                 .unwrap_or(false)
             {
                 warn!("Fault injection: skipping retry of payload");
-                return;
+                return DeliveryOutcome::Deferred;
             }
 
-            sleep(backoff);
-            let jitter: u64 = rand::thread_rng().gen_range(0..100);
-            backoff = std::cmp::min(
-                backoff.saturating_mul(2) + Duration::from_millis(jitter),
-                max_backoff,
-            );
-            attempts = attempts.saturating_add(1);
-        }
-    }
+            if retry_deadline.is_some_and(|deadline| Instant::now() + backoff >= deadline) {
+                warn!(
+                    "Event dispatcher: retry budget exhausted, deferring payload";
+                    "url" => %url,
+                    "attempts" => attempts
+                );
+                return DeliveryOutcome::Deferred;
+            }
 
-    fn new(working_dir: Option<PathBuf>, endpoint: String, timeout: Duration) -> Self {
+            // until the observer has been failing for the grace period, its payloads are
+            // retried however many attempts they took
+            let in_grace_period = policy.is_in_grace_period(health.failing_for());
+
+            if policy.is_exhausted(attempts.saturating_add(1) as u32) && !in_grace_period {
+                warn!(
+                    "Event dispatcher: delivery policy's max_attempts reached, giving up on payload";
+                    "url" => %url,
+                    "attempts" => attempts.saturating_add(1)
+                );
+                return DeliveryOutcome::GaveUp(format!(
+                    "delivery failed after {} attempts",
+                    attempts.saturating_add(1)
+                ));
+            }
+
+            if expires_at.is_some_and(|expires_at| Instant::now() + backoff >= expires_at) {
+                warn!(
+                    "Event dispatcher: payload will be older than the delivery policy allows before its next retry, giving up on it";
+                    "url" => %url,
+                    "attempts" => attempts.saturating_add(1)
+                );
+                return DeliveryOutcome::GaveUp("payload is older than max_event_age_ms".into());
+            }
+
+            if max_attempts
+                .is_some_and(|max_attempts| attempts.saturating_add(1) as u32 >= max_attempts)
+                && !in_grace_period
+            {
+                warn!(
+                    "Event dispatcher: retries exhausted, giving up on payload";
+                    "url" => %url,
+                    "attempts" => attempts.saturating_add(1)
+                );
+                return DeliveryOutcome::Deferred;
+            }
+
+            sleep(backoff);
+            let jitter: u64 = rand::thread_rng().gen_range(0..100);
+            backoff = policy.next_backoff(backoff, Duration::from_millis(jitter), timeout);
+            attempts = attempts.saturating_add(1);
+        }
+    }
+
+    fn new(working_dir: Option<PathBuf>, endpoint: String, timeout: Duration) -> Self {
         let db_path = if let Some(mut db_path) = working_dir {
             db_path.push("event_observers.sqlite");
 
@@ -541,33 +1631,484 @@ impl EventObserver {
             db_path,
             endpoint,
             timeout,
+            parallel_connections: 1,
+            include_winner_commit: false,
+            include_sortition_info: false,
+            include_miner_signature_valid: false,
+            include_stx_supply_delta: false,
+            include_raw_header: false,
+            include_raw_burnchain_op: false,
+            split_array_events: false,
+            stringify_large_ints: false,
+            flatten_execution_costs: false,
+            include_drop_fee_rates: false,
+            include_block_header_event: false,
+            include_block_timings: false,
+            payload_envelope: false,
+            projection: PayloadProjection::default(),
+            max_payload_bytes: None,
+            delivery: DeliverySettings::default(),
+            direct_transport: None,
+            block_order: None,
+            stackerdb_batch: None,
+            transform: None,
+            delivery_queue: None,
         }
     }
 
+    /// Deliver this observer's queued payloads over up to `connections` concurrent
+    /// connections, trading delivery order for throughput.
+    fn with_parallel_connections(mut self, connections: usize) -> Self {
+        self.parallel_connections = connections.max(1);
+        self
+    }
+
+    /// Add (or don't add) the winning block-commit's details to this observer's `new_block`
+    /// payloads
+    fn with_winner_commit(mut self, include_winner_commit: bool) -> Self {
+        self.include_winner_commit = include_winner_commit;
+        self
+    }
+
+    /// Add (or don't add) the block's tenure's sortition ID and VRF seed to this observer's
+    /// `new_block` payloads
+    fn with_sortition_info(mut self, include_sortition_info: bool) -> Self {
+        self.include_sortition_info = include_sortition_info;
+        self
+    }
+
+    /// Add (or don't add) the validity of each Nakamoto block's miner signature to this
+    /// observer's `new_block` payloads
+    fn with_miner_signature_valid(mut self, include_miner_signature_valid: bool) -> Self {
+        self.include_miner_signature_valid = include_miner_signature_valid;
+        self
+    }
+
+    /// Add (or don't add) the STX minted, burned and locked by each block to this observer's
+    /// `new_block` payloads
+    fn with_stx_supply_delta(mut self, include_stx_supply_delta: bool) -> Self {
+        self.include_stx_supply_delta = include_stx_supply_delta;
+        self
+    }
+
+    /// Add (or don't add) each block's consensus-serialized header to this observer's
+    /// `new_block` payloads
+    fn with_raw_header(mut self, include_raw_header: bool) -> Self {
+        self.include_raw_header = include_raw_header;
+        self
+    }
+
+    /// Add (or don't add) the serialized burnchain operation to the burn-origin transactions in
+    /// this observer's `new_block` payloads
+    fn with_raw_burnchain_op(mut self, include_raw_burnchain_op: bool) -> Self {
+        self.include_raw_burnchain_op = include_raw_burnchain_op;
+        self
+    }
+
+    /// Send this observer's array payloads one element per request, or as whole arrays
+    fn with_split_array_events(mut self, split_array_events: bool) -> Self {
+        self.split_array_events = split_array_events;
+        self
+    }
+
+    /// Send this observer's potentially-large integer fields as strings, or as numbers
+    fn with_stringify_large_ints(mut self, stringify_large_ints: bool) -> Self {
+        self.stringify_large_ints = stringify_large_ints;
+        self
+    }
+
+    /// Send this observer's execution costs as flattened fields, or as nested objects
+    fn with_flatten_execution_costs(mut self, flatten_execution_costs: bool) -> Self {
+        self.flatten_execution_costs = flatten_execution_costs;
+        self
+    }
+
+    /// Add (or don't add) the mempool's fee rate percentiles to this observer's fee-related
+    /// `drop_mempool_tx` events
+    fn with_drop_fee_rates(mut self, include_drop_fee_rates: bool) -> Self {
+        self.include_drop_fee_rates = include_drop_fee_rates;
+        self
+    }
+
+    /// Send (or don't send) this observer a `new_block_header` event ahead of each `new_block`
+    /// event
+    fn with_block_header_event(mut self, include_block_header_event: bool) -> Self {
+        self.include_block_header_event = include_block_header_event;
+        self
+    }
+
+    /// Add (or don't add) the block's processing timings to this observer's `new_block` payloads
+    fn with_block_timings(mut self, include_block_timings: bool) -> Self {
+        self.include_block_timings = include_block_timings;
+        self
+    }
+
+    /// Wrap (or don't wrap) each of this observer's payloads in a `{"type", "data"}` envelope
+    fn with_payload_envelope(mut self, payload_envelope: bool) -> Self {
+        self.payload_envelope = payload_envelope;
+        self
+    }
+
+    /// Only deliver the fields of each payload that `projection` selects
+    fn with_payload_projection(mut self, projection: PayloadProjection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    /// Split this observer's `new_block` payloads that are larger than `max_payload_bytes`
+    fn with_max_payload_bytes(mut self, max_payload_bytes: Option<u64>) -> Self {
+        self.max_payload_bytes = max_payload_bytes;
+        self
+    }
+
+    /// Retry this observer's payloads as `delivery_policy` says
+    fn with_delivery_policy(mut self, delivery_policy: DeliveryPolicy) -> Self {
+        self.delivery.policy = delivery_policy;
+        self
+    }
+
+    /// Record every request sent to this observer in `wire_capture`, as it goes on the wire
+    fn with_wire_capture(mut self, wire_capture: Option<WireCapture>) -> Self {
+        self.delivery.wire_capture = wire_capture;
+        self
+    }
+
+    /// Set up the TLS sessions with this observer's `https://` endpoint as `tls` says
+    fn with_tls(mut self, tls: Option<TlsClientConfig>) -> Self {
+        self.delivery.tls = tls;
+        self
+    }
+
+    /// Send `headers` with every request to this observer
+    fn with_headers(mut self, headers: StaticHeaders) -> Self {
+        self.delivery.headers = headers;
+        self
+    }
+
+    /// Queue this observer's payloads for a delivery thread of its own, which is started here,
+    /// rather than delivering each one before `send_payload()` returns
+    fn with_delivery_queue(
+        mut self,
+        delivery_queue: Option<DeliveryQueue>,
+    ) -> Result<Self, String> {
+        let Some(delivery_queue) = delivery_queue else {
+            self.delivery_queue = None;
+            return Ok(self);
+        };
+        info!(
+            "Event dispatcher: observer delivers from a queue";
+            "endpoint" => redact_endpoint(&self.endpoint),
+            "capacity" => delivery_queue.capacity(),
+            "when_full" => %delivery_queue.when_full()
+        );
+        let delivery_queue = Arc::new(delivery_queue);
+        // the thread's copy of this observer delivers its payloads itself
+        let observer = EventObserver {
+            delivery_queue: None,
+            ..self.clone()
+        };
+        let thread_queue = delivery_queue.clone();
+        thread::Builder::new()
+            .name(format!(
+                "event-observer-queue-{}",
+                redact_endpoint(&self.endpoint)
+            ))
+            .spawn(move || {
+                DeliveryQueue::run(thread_queue, |queued| {
+                    observer.deliver_payload(&queued.payload, &queued.path, queued.retry_deadline)
+                })
+            })
+            .map_err(|e| format!("failed to spawn delivery thread: {:?}", e))?;
+        self.delivery_queue = Some(delivery_queue);
+        Ok(self)
+    }
+
+    /// Hand payloads that this observer's endpoint fails to take to a fallback transport
+    fn with_fallback(mut self, fallback: Option<ObserverFallback>) -> Self {
+        self.delivery.fallback = fallback;
+        self
+    }
+
+    /// Only count this observer's `200` responses as deliveries if their bodies contain
+    /// `success_body_marker`
+    fn with_success_body_marker(mut self, success_body_marker: Option<String>) -> Self {
+        self.delivery.success_marker = success_body_marker;
+        self
+    }
+
+    /// Write this observer's payloads to `direct_transport` instead of POSTing them
+    fn with_direct_transport(mut self, direct_transport: Option<FallbackTransport>) -> Self {
+        self.direct_transport = direct_transport;
+        self
+    }
+
+    /// POST this observer's payloads over HTTP/2 with `http2`, or over HTTP/1.1
+    fn with_http2(mut self, http2: Option<Arc<Http2Client>>) -> Self {
+        self.delivery.http2 = http2;
+        self
+    }
+
+    /// Confirm (or don't confirm) each of this observer's `new_block` deliveries before
+    /// releasing its events of other topics, which wait for up to `max_wait`
+    fn with_strict_block_ordering(
+        mut self,
+        strict_block_ordering: bool,
+        max_wait: Option<Duration>,
+    ) -> Self {
+        self.block_order = strict_block_ordering.then(|| {
+            let gate = BlockOrderGate::new();
+            gate.set_max_wait(max_wait);
+            Arc::new(gate)
+        });
+        self
+    }
+
+    /// Merge the `stackerdb_chunks` events for each contract that arrive within `window` into
+    /// one payload
+    fn with_stackerdb_batch_window(mut self, window: Option<Duration>) -> Self {
+        self.stackerdb_batch = window.map(|window| Arc::new(StackerDBChunkBatcher::new(window)));
+        self
+    }
+
+    /// Pass each of this observer's payloads through `transform` before sending it
+    fn with_payload_transform(mut self, transform: Option<PayloadTransform>) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    /// Send each of this observer's payloads with its partition key from `partition`
+    fn with_partition(mut self, partition: Option<PayloadPartitioner>) -> Self {
+        self.delivery.partition = partition;
+        self
+    }
+
+    /// Wait for a slot of `host_limit` before each delivery attempt to this observer
+    fn with_host_limit(mut self, host_limit: Option<Arc<HostDeliveryLimit>>) -> Self {
+        self.delivery.host_limit = host_limit;
+        self
+    }
+
     /// Send the payload to the given URL.
     /// Before sending this payload, any pending payloads in the database will be sent first.
+    /// The payload is projected to the fields this observer wants before it is stored or sent.
     pub fn send_payload(&self, payload: &serde_json::Value, path: &str) {
+        self.send_payload_with_retry_deadline(payload, path, None);
+    }
+
+    /// Like `send_payload()`, but failed deliveries are not retried past `retry_deadline`. Only
+    /// this observer's pending payloads are sent, and any that aren't delivered in time stay in
+    /// the database until this observer's next payload. Without a database, there's nowhere
+    /// to defer a payload to, so the deadline is ignored.
+    /// A projected `new_block` payload larger than this observer's `max_payload_bytes` is sent
+    /// as several parts, in order.
+    /// With strict block ordering, payloads of topics other than `new_block` and
+    /// `new_block_header` first wait for any block being delivered, for up to the block retry
+    /// budget. Without a budget, they wait until the block is delivered, and nothing has a
+    /// deadline.
+    /// With a delivery queue, the payload is only queued here, and delivered later, in order,
+    /// by this observer's delivery thread.
+    fn send_payload_with_retry_deadline(
+        &self,
+        payload: &serde_json::Value,
+        path: &str,
+        retry_deadline: Option<Instant>,
+    ) {
+        let retry_deadline = if let Some(block_order) = self.block_order.as_ref() {
+            // a queued block is delivered before anything queued after it, so once the block is
+            // queued, there's nothing more to wait for
+            if path != PATH_BLOCK_PROCESSED
+                && path != PATH_BLOCK_HEADER
+                && !block_order.wait_for_blocks()
+            {
+                warn!(
+                    "Event dispatcher: block delivery is taking longer than the block retry budget, sending a held-back payload anyway";
+                    "endpoint" => redact_endpoint(&self.endpoint),
+                    "path" => path
+                );
+            }
+            // without a budget, blocks are retried until they're delivered, and so is
+            // everything held back behind them
+            block_order.max_wait().and(retry_deadline)
+        } else {
+            retry_deadline
+        };
+        let Some(delivery_queue) = self.delivery_queue.as_ref() else {
+            self.deliver_payload(payload, path, retry_deadline);
+            return;
+        };
+        let dropped = delivery_queue.push(QueuedPayload {
+            payload: payload.clone(),
+            path: path.to_string(),
+            retry_deadline,
+        });
+        if let Some(dropped) = dropped {
+            warn!(
+                "Event dispatcher: delivery queue is full, dropping a payload";
+                "endpoint" => redact_endpoint(&self.endpoint),
+                "path" => &dropped.path,
+                "when_full" => %delivery_queue.when_full(),
+                "total_dropped" => delivery_queue.dropped()
+            );
+        }
+    }
+
+    /// Deliver a payload to this observer now, as `send_payload_with_retry_deadline()`
+    /// describes
+    fn deliver_payload(
+        &self,
+        payload: &serde_json::Value,
+        path: &str,
+        retry_deadline: Option<Instant>,
+    ) {
+        let transformed;
+        let payload = if let Some(transform) = self.transform.as_ref() {
+            let Some(payload) = transform.apply(path, payload) else {
+                return;
+            };
+            transformed = payload;
+            &transformed
+        } else {
+            payload
+        };
+        let projected;
+        let payload = if self.projection.is_identity() {
+            payload
+        } else {
+            projected = self.projection.apply(payload);
+            &projected
+        };
+        let stringified;
+        let payload = if self.stringify_large_ints {
+            stringified = stringify_large_ints(payload);
+            &stringified
+        } else {
+            payload
+        };
+        let flattened;
+        let payload = if self.flatten_execution_costs {
+            flattened = flatten_execution_costs(payload);
+            &flattened
+        } else {
+            payload
+        };
+
+        if let Some(max_payload_bytes) = self
+            .max_payload_bytes
+            .filter(|_| path == PATH_BLOCK_PROCESSED)
+        {
+            if let Some(parts) = split_block_payload(payload, max_payload_bytes) {
+                debug!(
+                    "Event dispatcher: splitting {} payload into {} parts", path, parts.len();
+                    "endpoint" => &self.endpoint, "max_payload_bytes" => max_payload_bytes
+                );
+                for part in parts.iter() {
+                    self.send_projected_payload(part, path, retry_deadline);
+                }
+                return;
+            }
+        }
+        self.send_projected_payload(payload, path, retry_deadline);
+    }
+
+    /// Send a payload that has already been projected to this observer's fields. If this
+    /// observer wants envelopes, this is where the payload is wrapped in one.
+    fn send_projected_payload(
+        &self,
+        payload: &serde_json::Value,
+        path: &str,
+        retry_deadline: Option<Instant>,
+    ) {
+        let enveloped;
+        let payload = if self.payload_envelope {
+            enveloped = wrap_in_envelope(payload, path);
+            &enveloped
+        } else {
+            payload
+        };
+
+        if let Some(transport) = self.direct_transport.as_ref() {
+            if let Err(e) = transport.send_event(path, payload) {
+                error!(
+                    "Event observer: failed to write payload";
+                    "path" => path, "transport" => %transport, "error" => e
+                );
+            }
+            return;
+        }
+
         // Construct the full URL
         let url_str = if path.starts_with('/') {
             format!("{}{}", &self.endpoint, path)
         } else {
             format!("{}/{}", &self.endpoint, path)
         };
-        let full_url = format!("http://{}", url_str);
+        let full_url = observer_base_url(&url_str);
 
         if let Some(db_path) = &self.db_path {
             let conn =
                 Connection::open(db_path).expect("Failed to open database for event observer");
 
             // Insert the new payload into the database
-            Self::insert_payload_with_retry(&conn, &full_url, payload, self.timeout);
+            Self::insert_payload_with_retry(
+                &conn,
+                &observer_base_url(&self.endpoint),
+                &full_url,
+                payload,
+                self.timeout,
+            );
 
             // Process all pending payloads
-            Self::process_pending_payloads(&conn);
+            self.send_pending_payloads(&conn, retry_deadline);
         } else {
             // No database, just send the payload
-            Self::send_payload_directly(payload, &full_url, self.timeout);
+            Self::send_payload_with_fallback(
+                payload,
+                &full_url,
+                self.timeout,
+                None,
+                self.delivery.policy.expires_at(None),
+                &self.delivery,
+            );
+        }
+    }
+
+    /// Send this observer's pending payloads in the database that `conn` is open on, with its
+    /// delivery settings. Failed deliveries are not retried past `retry_deadline`, if given.
+    fn send_pending_payloads(&self, conn: &Connection, retry_deadline: Option<Instant>) {
+        Self::process_pending_payloads(
+            conn,
+            &observer_base_url(&self.endpoint),
+            self.parallel_connections,
+            retry_deadline,
+            &self.delivery,
+        );
+    }
+
+    /// Send the payloads that are still pending for this observer in its pending-payload
+    /// database, such as those it hadn't acknowledged when the node last stopped, in the order
+    /// they were produced. An observer that can't take them within its timeout doesn't hold up
+    /// the node's startup: they stay in the database, and are delivered ahead of its next event.
+    fn replay_pending_payloads(&self) {
+        let Some(db_path) = &self.db_path else {
+            return;
+        };
+        let conn = Connection::open(db_path).expect("Failed to open database for event observer");
+        let observer = observer_base_url(&self.endpoint);
+        let pending = Self::count_pending_payloads(&conn, &observer).unwrap_or_else(|e| {
+            warn!(
+                "Event observer: failed to count pending payloads";
+                "endpoint" => redact_endpoint(&self.endpoint), "error" => ?e
+            );
+            0
+        });
+        if pending == 0 {
+            return;
         }
+        info!(
+            "Event observer: replaying undelivered payloads";
+            "endpoint" => redact_endpoint(&self.endpoint), "payloads" => pending
+        );
+        self.send_pending_payloads(&conn, Some(Instant::now() + self.timeout));
     }
 
     fn make_new_mempool_txs_payload(transactions: Vec<StacksTransaction>) -> serde_json::Value {
@@ -612,6 +2153,46 @@ impl EventObserver {
         })
     }
 
+    /// Make a transaction's `new_block` payload, with the optional fields this observer wants.
+    /// Burn-origin transactions have no raw Stacks transaction, so if `include_raw_burnchain_op`
+    /// is set, they carry their serialized burnchain operation in `raw_burnchain_op`.
+    #[cfg(test)]
+    fn make_observer_block_txs_payload(
+        &self,
+        receipt: &StacksTransactionReceipt,
+        tx_index: u32,
+    ) -> serde_json::Value {
+        let mut payload = EventObserver::make_new_block_txs_payload(receipt, tx_index);
+        if !self.include_raw_burnchain_op {
+            return payload;
+        }
+        if let Some(raw_burnchain_op) = EventObserver::make_raw_burnchain_op_payload(receipt) {
+            payload["raw_burnchain_op"] = raw_burnchain_op;
+        }
+        payload
+    }
+
+    /// Serialize a burn-origin transaction's burnchain operation, for `raw_burnchain_op`.
+    /// Returns `None` for Stacks transactions.
+    fn make_raw_burnchain_op_payload(
+        receipt: &StacksTransactionReceipt,
+    ) -> Option<serde_json::Value> {
+        let TransactionOrigin::Burn(op) = &receipt.transaction else {
+            return None;
+        };
+        let payload = match op.serialize_op_data() {
+            Ok(bytes) => json!(format!("0x{}", bytes_to_hex(&bytes))),
+            Err(e) => {
+                warn!(
+                    "Event dispatcher: failed to serialize burnchain operation";
+                    "txid" => %op.txid(), "error" => ?e
+                );
+                json!(null)
+            }
+        };
+        Some(payload)
+    }
+
     /// Returns tuple of (txid, success, raw_result, raw_tx, contract_interface_json)
     fn generate_payload_info_for_receipt(receipt: &StacksTransactionReceipt) -> ReceiptPayloadInfo {
         let tx = &receipt.transaction;
@@ -682,6 +2263,28 @@ impl EventObserver {
     ) -> serde_json::Value {
         let receipt_payload_info = EventObserver::generate_payload_info_for_receipt(receipt);
 
+        // burnchain operations are never sponsored. In a sponsored transaction, the sponsor's
+        // spending condition carries the fee.
+        let sponsor = match &receipt.transaction {
+            TransactionOrigin::Stacks(tx) => tx
+                .sponsor_address()
+                .map(|address| (address, tx.get_tx_fee())),
+            TransactionOrigin::Burn(_) => None,
+        };
+
+        // the receipt doesn't record which post-condition failed, so describe the checks that
+        // were made. In deny mode, an unlisted asset transfer also aborts the transaction.
+        let post_condition_failure = match &receipt.transaction {
+            TransactionOrigin::Stacks(tx) if receipt.post_condition_aborted => json!({
+                "post_condition_mode": match tx.post_condition_mode {
+                    TransactionPostConditionMode::Allow => "allow",
+                    TransactionPostConditionMode::Deny => "deny",
+                },
+                "post_condition_count": tx.post_conditions.len(),
+            }),
+            _ => json!(null),
+        };
+
         json!({
             "txid": format!("0x{}", &receipt_payload_info.txid),
             "tx_index": tx_index,
@@ -694,6 +2297,10 @@ impl EventObserver {
             "microblock_sequence": receipt.microblock_header.as_ref().map(|x| x.sequence),
             "microblock_hash": receipt.microblock_header.as_ref().map(|x| format!("0x{}", x.block_hash())),
             "microblock_parent_hash": receipt.microblock_header.as_ref().map(|x| format!("0x{}", x.prev_block)),
+            "sponsored": sponsor.is_some(),
+            "sponsor_address": sponsor.as_ref().map(|(address, _)| address.to_string()),
+            "sponsor_fee": sponsor.as_ref().map(|(_, fee)| fee),
+            "post_condition_failure": post_condition_failure,
         })
     }
 
@@ -713,11 +2320,24 @@ impl EventObserver {
     }
 
     fn send_new_attachments(&self, payload: &serde_json::Value) {
-        self.send_payload(payload, PATH_ATTACHMENT_PROCESSED);
+        self.send_array_payload(payload, PATH_ATTACHMENT_PROCESSED);
     }
 
     fn send_new_mempool_txs(&self, payload: &serde_json::Value) {
-        self.send_payload(payload, PATH_MEMPOOL_TX_SUBMIT);
+        self.send_array_payload(payload, PATH_MEMPOOL_TX_SUBMIT);
+    }
+
+    /// Send an array payload. If this observer has `split_array_events` set, then each element
+    /// is sent as its own request, in order.
+    fn send_array_payload(&self, payload: &serde_json::Value, path: &str) {
+        match payload.as_array() {
+            Some(items) if self.split_array_events => {
+                for item in items.iter() {
+                    self.send_payload(item, path);
+                }
+            }
+            _ => self.send_payload(payload, path),
+        }
     }
 
     /// Serializes new microblocks data into a JSON payload and sends it off to the correct path
@@ -752,6 +2372,10 @@ impl EventObserver {
         self.send_payload(&payload, PATH_MICROBLOCK_SUBMIT);
     }
 
+    fn send_microblock_tip(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_MICROBLOCK_TIP);
+    }
+
     fn send_dropped_mempool_txs(&self, payload: &serde_json::Value) {
         self.send_payload(payload, PATH_MEMPOOL_TX_DROP);
     }
@@ -772,16 +2396,203 @@ impl EventObserver {
         self.send_payload(payload, PATH_STACKERDB_CHUNKS);
     }
 
+    /// Send a `stackerdb_chunks` payload for `contract_id`, or add it to this observer's batch
+    /// for the contract. The first payload of a batch starts a thread that sends the batch once
+    /// its window has passed.
+    fn send_or_batch_stackerdb_chunks(
+        &self,
+        contract_id: &QualifiedContractIdentifier,
+        payload: &serde_json::Value,
+    ) {
+        let Some(batcher) = self.stackerdb_batch.as_ref() else {
+            self.send_stackerdb_chunks(payload);
+            return;
+        };
+        if !batcher.add(contract_id, payload) {
+            return;
+        }
+
+        let observer = self.clone();
+        let thread_batcher = batcher.clone();
+        let batch_contract_id = contract_id.clone();
+        let spawn_res = thread::Builder::new()
+            .name(format!(
+                "stackerdb-batch-{}",
+                redact_endpoint(&self.endpoint)
+            ))
+            .spawn(move || {
+                sleep(thread_batcher.window());
+                if let Some(payload) = thread_batcher.take(&batch_contract_id) {
+                    observer.send_stackerdb_chunks(&payload);
+                }
+            });
+        if let Err(e) = spawn_res {
+            warn!(
+                "Event dispatcher: failed to spawn a StackerDB batch thread, sending the batch now";
+                "endpoint" => redact_endpoint(&self.endpoint), "error" => ?e
+            );
+            if let Some(payload) = batcher.take(contract_id) {
+                self.send_stackerdb_chunks(&payload);
+            }
+        }
+    }
+
     fn send_new_burn_block(&self, payload: &serde_json::Value) {
         self.send_payload(payload, PATH_BURN_BLOCK_SUBMIT);
     }
 
-    fn make_new_block_processed_payload(
-        &self,
-        filtered_events: Vec<(usize, &(bool, Txid, &StacksTransactionEvent))>,
+    fn send_signer_participation(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_SIGNER_PARTICIPATION);
+    }
+
+    fn send_stacker_set_diff(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_STACKER_SET_DIFF);
+    }
+
+    fn send_cost_near_limit(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_COST_NEAR_LIMIT);
+    }
+
+    fn send_new_rewards(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_REWARDS);
+    }
+
+    fn send_burn_block_reorg(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_BURN_BLOCK_REORG);
+    }
+
+    fn send_block_fork(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_BLOCK_FORK);
+    }
+
+    fn send_tip_changed(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_TIP_CHANGED);
+    }
+
+    fn send_stackerdb_equivocation(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_STACKERDB_EQUIVOCATION);
+    }
+
+    fn send_node_synced(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_NODE_SYNCED);
+    }
+
+    /// Send the `new_observer` event. An observer that can't take it within its timeout
+    /// doesn't hold up the node's startup; the event stays in its pending payloads, and is
+    /// delivered ahead of its next event.
+    fn send_startup_event(&self, payload: &serde_json::Value) {
+        self.send_payload_with_retry_deadline(
+            payload,
+            PATH_NEW_OBSERVER,
+            Some(Instant::now() + self.timeout),
+        );
+    }
+
+    /// Make this observer's `new_block` payload for `new_block` on its own.
+    /// `process_chain_tip()` makes every observer's payload from one set of
+    /// `NewBlockPayloadParts` instead.
+    #[cfg(test)]
+    fn make_new_block_processed_payload(&self, new_block: &TestNewBlock) -> serde_json::Value {
+        let common = EventObserver::make_new_block_common_payload(
+            &new_block.block,
+            &new_block.metadata,
+            &new_block.parent_index_hash,
+            &new_block.winner_txid,
+            &new_block.mature_rewards,
+            new_block.parent_burn_block_hash,
+            new_block.parent_burn_block_height,
+            new_block.parent_burn_block_timestamp,
+            &new_block.anchored_consumed,
+            &new_block.mblock_confirmed_consumed,
+            &new_block.pox_constants,
+            &new_block.reward_set_data,
+            &new_block.signer_bitvec,
+            new_block.block_timestamp,
+            new_block.coinbase_height,
+            new_block.processed_at,
+            new_block.parent_tenure_consensus_hash.as_ref(),
+        );
+        let filtered_events: Vec<_> = new_block
+            .filtered_events
+            .iter()
+            .map(|(event_index, event)| (*event_index, **event))
+            .collect();
+        let parts = EventObserver::make_new_block_payload_parts(
+            common,
+            &filtered_events,
+            new_block.receipts,
+            self.include_raw_burnchain_op,
+            1,
+        );
+        let event_ids: Vec<_> = filtered_events
+            .iter()
+            .map(|(event_index, _)| *event_index)
+            .collect();
+        parts.assemble(
+            &event_ids,
+            self.include_raw_burnchain_op,
+            new_block.winner_commit.as_ref(),
+            new_block.sortition_info.as_ref(),
+            new_block.miner_signature_valid,
+        )
+    }
+
+    /// Serialize the parts of a block's `new_block` payloads, on up to `threads` threads.
+    /// `filtered_events` are the events that any observer receives, and burnchain operations
+    /// are only serialized if `include_raw_burnchain_ops` is set.
+    fn make_new_block_payload_parts(
+        common: serde_json::Map<String, serde_json::Value>,
+        filtered_events: &[(usize, (bool, Txid, &StacksTransactionEvent))],
+        receipts: &[StacksTransactionReceipt],
+        include_raw_burnchain_ops: bool,
+        threads: usize,
+    ) -> NewBlockPayloadParts {
+        let serialized_events = parallel_map(
+            filtered_events,
+            threads,
+            |_, (event_index, (committed, txid, event))| {
+                event
+                    .json_serialize(*event_index, txid, *committed)
+                    .unwrap()
+            },
+        );
+        let events = filtered_events
+            .iter()
+            .map(|(event_index, _)| *event_index)
+            .zip(serialized_events)
+            .collect();
+
+        let transactions = parallel_map(receipts, threads, |tx_index, receipt| {
+            EventObserver::make_new_block_txs_payload(receipt, tx_index as u32)
+        });
+
+        let raw_burnchain_ops = if include_raw_burnchain_ops {
+            receipts
+                .iter()
+                .enumerate()
+                .filter_map(|(tx_index, receipt)| {
+                    EventObserver::make_raw_burnchain_op_payload(receipt).map(|op| (tx_index, op))
+                })
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
+        NewBlockPayloadParts {
+            common,
+            events,
+            transactions,
+            raw_burnchain_ops,
+        }
+    }
+
+    /// Make the fields of a block's `new_block` payload that are the same for every observer,
+    /// i.e. all of them except `events`, `transactions`, and the optional fields that only
+    /// some observers want. `parent_tenure_consensus_hash` is the consensus hash of the tenure
+    /// of the block's parent, and is only reported for Nakamoto blocks.
+    fn make_new_block_common_payload(
         block: &StacksBlockEventData,
         metadata: &StacksHeaderInfo,
-        receipts: &[StacksTransactionReceipt],
         parent_index_hash: &StacksBlockId,
         winner_txid: &Txid,
         mature_rewards: &serde_json::Value,
@@ -795,25 +2606,9 @@ impl EventObserver {
         signer_bitvec_opt: &Option<BitVec<4000>>,
         block_timestamp: Option<u64>,
         coinbase_height: u64,
-    ) -> serde_json::Value {
-        // Serialize events to JSON
-        let serialized_events: Vec<serde_json::Value> = filtered_events
-            .iter()
-            .map(|(event_index, (committed, txid, event))| {
-                event
-                    .json_serialize(*event_index, txid, *committed)
-                    .unwrap()
-            })
-            .collect();
-
-        let mut tx_index: u32 = 0;
-        let mut serialized_txs = vec![];
-        for receipt in receipts.iter() {
-            let payload = EventObserver::make_new_block_txs_payload(receipt, tx_index);
-            serialized_txs.push(payload);
-            tx_index += 1;
-        }
-
+        processed_at: u64,
+        parent_tenure_consensus_hash: Option<&ConsensusHash>,
+    ) -> serde_json::Map<String, serde_json::Value> {
         let signer_bitvec_value = signer_bitvec_opt
             .as_ref()
             .map(|bitvec| serde_json::to_value(bitvec).unwrap_or_default())
@@ -828,8 +2623,7 @@ impl EventObserver {
             None => (serde_json::Value::Null, serde_json::Value::Null),
         };
 
-        // Wrap events
-        let mut payload = json!({
+        let serde_json::Value::Object(mut payload) = json!({
             "block_hash": format!("0x{}", block.block_hash),
             "block_height": metadata.stacks_block_height,
             "block_time": block_timestamp,
@@ -843,8 +2637,6 @@ impl EventObserver {
             "parent_microblock": format!("0x{}", block.parent_microblock_hash),
             "parent_microblock_sequence": block.parent_microblock_sequence,
             "matured_miner_rewards": mature_rewards.clone(),
-            "events": serialized_events,
-            "transactions": serialized_txs,
             "parent_burn_block_hash":  format!("0x{}", parent_burn_block_hash),
             "parent_burn_block_height": parent_burn_block_height,
             "parent_burn_block_timestamp": parent_burn_block_timestamp,
@@ -857,27 +2649,124 @@ impl EventObserver {
             "reward_set": reward_set_value,
             "cycle_number": cycle_number_value,
             "tenure_height": coinbase_height,
-        });
-
-        let as_object_mut = payload.as_object_mut().unwrap();
+            "burn_to_block_latency_secs": processed_at.saturating_sub(metadata.burn_header_timestamp),
+        }) else {
+            unreachable!("FATAL: new_block payload is not an object");
+        };
 
         if let StacksBlockHeaderTypes::Nakamoto(ref header) = &metadata.anchored_header {
-            as_object_mut.insert(
+            payload.insert(
                 "signer_signature_hash".into(),
                 format!("0x{}", header.signer_signature_hash()).into(),
             );
-            as_object_mut.insert(
+            payload.insert(
                 "miner_signature".into(),
                 format!("0x{}", &header.miner_signature).into(),
             );
-            as_object_mut.insert(
+            payload.insert(
                 "signer_signature".into(),
                 serde_json::to_value(&header.signer_signature).unwrap_or_default(),
             );
+            payload.insert(
+                "parent_tenure_consensus_hash".into(),
+                parent_tenure_consensus_hash
+                    .map(|consensus_hash| format!("0x{}", consensus_hash))
+                    .into(),
+            );
         }
 
         payload
     }
+
+    /// Identify the sortition that a block's tenure started in, for `new_block` payloads.
+    /// `vrf_seed` is the new VRF seed of the sortition's winning block-commit, if it had one.
+    fn make_sortition_info_payload(
+        snapshot: &BlockSnapshot,
+        vrf_seed: Option<&VRFSeed>,
+    ) -> serde_json::Value {
+        json!({
+            "sortition_id": format!("0x{}", &snapshot.sortition_id),
+            "vrf_seed": vrf_seed.map(|seed| format!("0x{}", seed.to_hex())),
+        })
+    }
+
+    /// Make the `timings` object of a `new_block` payload, in microseconds
+    fn make_block_timings_payload(
+        timings: &BlockProcessingTimings,
+        event_serialization: Duration,
+    ) -> serde_json::Value {
+        let micros = |duration: Duration| u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+        json!({
+            "validation_us": micros(timings.validation),
+            "execution_us": micros(timings.execution),
+            "event_serialization_us": micros(event_serialization),
+        })
+    }
+
+    /// Make the compact `new_block_header` payload, which identifies a newly-processed block
+    /// without any of its transactions or events
+    fn make_new_block_header_payload(
+        block: &StacksBlockEventData,
+        metadata: &StacksHeaderInfo,
+        parent_index_hash: &StacksBlockId,
+    ) -> serde_json::Value {
+        json!({
+            "block_hash": format!("0x{}", block.block_hash),
+            "block_height": metadata.stacks_block_height,
+            "index_block_hash": format!("0x{}", metadata.index_block_hash()),
+            "consensus_hash": format!("0x{}", metadata.consensus_hash),
+            "parent_index_block_hash": format!("0x{}", parent_index_hash),
+            "burn_block_hash": format!("0x{}", metadata.burn_header_hash),
+            "burn_block_height": metadata.burn_header_height,
+        })
+    }
+
+    /// The consensus serialization of a block's header, hex-encoded, for `new_block` payloads.
+    /// This is a `NakamotoBlockHeader` for Nakamoto blocks, and a `StacksBlockHeader` for
+    /// epoch 2.x blocks.
+    fn make_raw_header_payload(header: &StacksBlockHeaderTypes) -> serde_json::Value {
+        let bytes = match header {
+            StacksBlockHeaderTypes::Epoch2(header) => header.serialize_to_vec(),
+            StacksBlockHeaderTypes::Nakamoto(header) => header.serialize_to_vec(),
+        };
+        json!(format!("0x{}", bytes_to_hex(&bytes)))
+    }
+
+    /// Whether a Nakamoto block's miner signature was made by the signing key that its tenure's
+    /// miner registered in `leader_key`. `None` if the leader key holds no signing key.
+    fn check_miner_signature(
+        header: &NakamotoBlockHeader,
+        leader_key: &LeaderKeyRegisterOp,
+    ) -> Option<bool> {
+        let miner_pubkey_hash160 = leader_key.interpret_nakamoto_signing_key()?;
+        let valid = header
+            .recover_miner_pk()
+            .map(|miner_pubkey| Hash160::from_node_public_key(&miner_pubkey))
+            .is_some_and(|recovered_hash160| recovered_hash160 == miner_pubkey_hash160);
+        Some(valid)
+    }
+
+    /// Describe a winning block-commit for `new_block` payloads.
+    /// `sortition_burn` is the total burn of all the sortition's block-commits, and
+    /// `leader_key` is the commit's VRF key registration, if it could be found.
+    fn make_winner_commit_payload(
+        commit: &LeaderBlockCommitOp,
+        sortition_burn: u64,
+        leader_key: Option<&LeaderKeyRegisterOp>,
+    ) -> serde_json::Value {
+        json!({
+            "txid": format!("0x{}", &commit.txid),
+            "burn_block_height": commit.block_height,
+            "vtxindex": commit.vtxindex,
+            "burn_fee": commit.burn_fee,
+            "sunset_burn": commit.sunset_burn,
+            "sortition_burn": sortition_burn,
+            "apparent_sender": commit.apparent_sender.to_string(),
+            "key_block_ptr": commit.key_block_ptr,
+            "key_vtxindex": commit.key_vtxindex,
+            "vrf_public_key": leader_key.map(|key| format!("0x{}", key.public_key.to_hex())),
+        })
+    }
 }
 
 /// Events received from block-processing.
@@ -886,44 +2775,190 @@ impl EventObserver {
 #[derive(Clone)]
 pub struct EventDispatcher {
     /// List of configured event observers to which events will be posted.
-    /// The fields below this contain indexes into this list.
+    /// The fields below this contain indexes into this list. They are ordered sets, so that
+    /// observers are always visited, and sent their payloads, in registration order.
     registered_observers: Vec<EventObserver>,
+    /// The configs the observers in `registered_observers` were registered with, in the same
+    /// order. Used to export the observer registry.
+    registered_observer_configs: Vec<EventObserverConfig>,
     /// Smart contract-specific events, keyed by (contract-id, event-name). Values are indexes into `registered_observers`.
-    contract_events_observers_lookup: HashMap<(QualifiedContractIdentifier, String), HashSet<u16>>,
+    contract_events_observers_lookup: HashMap<(QualifiedContractIdentifier, String), BTreeSet<u16>>,
     /// Asset event observers, keyed by fully-qualified asset identifier. Values are indexes into
     /// `registered_observers.
-    assets_observers_lookup: HashMap<AssetIdentifier, HashSet<u16>>,
+    assets_observers_lookup: HashMap<AssetIdentifier, BTreeSet<u16>>,
+    /// Observers of every contract and asset event of a deployer's contracts, keyed by the
+    /// deployer. Values are indexes into `registered_observers`.
+    deployer_observers_lookup: HashMap<StandardPrincipalData, BTreeSet<u16>>,
+    /// Index into `registered_observers` that will receive every contract and asset event of
+    /// the boot contracts
+    boot_contract_observers_lookup: BTreeSet<u16>,
     /// Index into `registered_observers` that will receive burn block events
-    burn_block_observers_lookup: HashSet<u16>,
+    burn_block_observers_lookup: BTreeSet<u16>,
     /// Index into `registered_observers` that will receive mempool events
-    mempool_observers_lookup: HashSet<u16>,
+    mempool_observers_lookup: BTreeSet<u16>,
     /// Index into `registered_observers` that will receive microblock events
-    microblock_observers_lookup: HashSet<u16>,
+    microblock_observers_lookup: BTreeSet<u16>,
+    /// Index into `registered_observers` that will receive compact microblock stream tip events
+    microblock_tip_observers_lookup: BTreeSet<u16>,
     /// Index into `registered_observers` that will receive STX events
-    stx_observers_lookup: HashSet<u16>,
+    stx_observers_lookup: BTreeSet<u16>,
     /// Index into `registered_observers` that will receive all events
-    any_event_observers_lookup: HashSet<u16>,
+    any_event_observers_lookup: BTreeSet<u16>,
     /// Index into `registered_observers` that will receive block miner events (Stacks 2.5 and
     /// lower)
-    miner_observers_lookup: HashSet<u16>,
+    miner_observers_lookup: BTreeSet<u16>,
     /// Index into `registered_observers` that will receive microblock miner events (Stacks 2.5 and
     /// lower)
-    mined_microblocks_observers_lookup: HashSet<u16>,
+    mined_microblocks_observers_lookup: BTreeSet<u16>,
     /// Index into `registered_observers` that will receive StackerDB events
-    stackerdb_observers_lookup: HashSet<u16>,
+    stackerdb_observers_lookup: BTreeSet<u16>,
     /// Index into `registered_observers` that will receive block proposal events (Nakamoto and
     /// later)
-    block_proposal_observers_lookup: HashSet<u16>,
+    block_proposal_observers_lookup: BTreeSet<u16>,
+    /// Index into `registered_observers` that will receive per-tenure signer participation
+    /// summaries (Nakamoto and later)
+    signer_participation_observers_lookup: BTreeSet<u16>,
+    /// Signer signatures seen so far in the current tenure
+    signer_participation: Arc<Mutex<SignerParticipationTracker>>,
+    /// Index into `registered_observers` that will receive stacker set diffs
+    stacker_set_diff_observers_lookup: BTreeSet<u16>,
+    /// Recently computed reward sets, to diff new ones against
+    stacker_set_diff: Arc<Mutex<StackerSetDiffTracker>>,
+    /// Index into `registered_observers` that will receive warnings about transactions whose
+    /// execution cost came close to the block limit
+    cost_near_limit_observers_lookup: BTreeSet<u16>,
+    /// Fraction of the block limit, in any cost dimension, above which a transaction's
+    /// execution cost is reported to `cost_near_limit_observers_lookup`
+    cost_near_limit_threshold: f64,
+    /// Index into `registered_observers` that will receive each block's coinbase and matured
+    /// miner rewards
+    rewards_observers_lookup: BTreeSet<u16>,
+    /// Index into `registered_observers` that will be told when a burn block replaces one
+    /// that was already announced at the same height
+    burn_block_reorg_observers_lookup: BTreeSet<u16>,
+    /// The burn blocks announced at recent heights, to detect re-announcements
+    burn_block_history: Arc<Mutex<BurnBlockHistory>>,
+    /// Index into `registered_observers` that will be told when a Nakamoto block competes with
+    /// an already-processed block for the same height in the same tenure
+    block_fork_observers_lookup: BTreeSet<u16>,
+    /// The Nakamoto blocks processed at recent heights, to detect forks
+    block_forks: Arc<Mutex<BlockForkTracker>>,
+    /// Index into `registered_observers` that will be told when the canonical Stacks chain tip
+    /// changes
+    tip_changed_observers_lookup: BTreeSet<u16>,
+    /// The last canonical Stacks chain tip announced by the coordinator
+    canonical_tip: Arc<Mutex<Option<StacksBlockId>>>,
+    /// Index into `registered_observers` that will be told when a StackerDB slot's signer signs
+    /// two different chunks for the same slot version
+    stackerdb_equivocation_observers_lookup: BTreeSet<u16>,
+    /// The StackerDB chunks stored at recent slot versions, to detect equivocation
+    stackerdb_equivocation: Arc<Mutex<StackerDBEquivocationTracker>>,
+    /// Index into `registered_observers` that will be sent lifecycle events, such as
+    /// `node_synced`
+    lifecycle_observers_lookup: BTreeSet<u16>,
+    /// Whether the node has caught up to the chain tip since it started, in which case
+    /// `node_synced` was already sent
+    node_synced: Arc<AtomicBool>,
+    /// Path to the sortition DB, used to look up winning block-commits for observers that
+    /// want them, and the epoch cost limits for `cost_near_limit` observers
+    sortdb_path: Option<String>,
+    /// Path to the chainstate, used to look up the tenures of Nakamoto blocks' parents
+    chainstate_path: Option<String>,
+    /// Total time that may be spent retrying failed `new_block` deliveries for one block, across
+    /// all observers. Once it is spent, undelivered payloads are left in the observers' spool.
+    block_retry_budget: Option<Duration>,
+    /// If set, spaces out the `proposal_response` events sent to each observer, and drops
+    /// responses that are replaced before they are sent
+    proposal_response_throttle: Option<Arc<Mutex<ProposalResponseThrottle>>>,
+    /// The `new_block` payload most recently built for each observer, keyed by index into
+    /// `registered_observers`, so that it can be sent again on request. Replaced as a whole
+    /// for each block.
+    last_block_payloads: Arc<Mutex<HashMap<u16, Arc<serde_json::Value>>>>,
+    /// Most observers that `register_observer()` will register
+    max_observers: usize,
+    /// If set, every mined-block payload is also written to this local archive, whether or
+    /// not any observer receives it
+    mined_block_archive: Option<MinedBlockArchive>,
+    /// If set, applied to every payload sent to every observer
+    payload_transform: Option<PayloadTransform>,
+    /// Number of threads over which each block's `new_block` payloads are serialized
+    event_serialization_threads: usize,
+    /// The transactions most recently dropped from the mempool, so that their drop events can
+    /// be listed and sent again
+    mempool_drop_history: Arc<Mutex<MempoolDropHistory>>,
+    /// Who this node is, as told to observers in `new_observer` events
+    node_identity: Option<NodeIdentity>,
+    /// Limits on the delivery attempts in flight to each observer host, shared by all of the
+    /// host's observers
+    host_delivery_limits: Arc<HostDeliveryLimits>,
 }
 
 /// This struct is used specifically for receiving proposal responses.
 /// It's constructed separately to play nicely with threading.
 struct ProposalCallbackHandler {
     observers: Vec<EventObserver>,
+    throttle: Option<Arc<Mutex<ProposalResponseThrottle>>>,
+}
+
+impl ProposalCallbackHandler {
+    /// Send a response for the proposal with `signer_signature_hash` to each observer, waiting
+    /// out each observer's minimum interval. This runs on the proposal's validation thread,
+    /// so waiting here holds up nothing else.
+    fn send_throttled(
+        &self,
+        throttle: &Mutex<ProposalResponseThrottle>,
+        signer_signature_hash: &Sha512Trunc256Sum,
+        response: &serde_json::Value,
+    ) {
+        let Ok(mut guard) = throttle.lock() else {
+            error!("Proposal response throttle lock poisoned");
+            return;
+        };
+        let seq = guard.begin(signer_signature_hash);
+        drop(guard);
+
+        'observers: for observer in self.observers.iter() {
+            loop {
+                let Ok(mut guard) = throttle.lock() else {
+                    error!("Proposal response throttle lock poisoned");
+                    return;
+                };
+                let slot = guard.try_send(
+                    signer_signature_hash,
+                    seq,
+                    &observer.endpoint,
+                    Instant::now(),
+                );
+                drop(guard);
+                match slot {
+                    ProposalResponseSlot::Send => {
+                        observer.send_payload(response, PATH_PROPOSAL_RESPONSE);
+                        continue 'observers;
+                    }
+                    ProposalResponseSlot::Wait(wait) => sleep(wait),
+                    ProposalResponseSlot::Superseded => {
+                        debug!(
+                            "Dropping proposal response replaced by a later one";
+                            "signer_signature_hash" => %signer_signature_hash
+                        );
+                        return;
+                    }
+                }
+            }
+        }
+
+        if let Ok(mut guard) = throttle.lock() {
+            guard.finish(signer_signature_hash, seq);
+        }
+    }
 }
 
 impl ProposalCallbackReceiver for ProposalCallbackHandler {
     fn notify_proposal_result(&self, result: Result<BlockValidateOk, BlockValidateReject>) {
+        let signer_signature_hash = match &result {
+            Ok(ok) => ok.signer_signature_hash.clone(),
+            Err(reject) => reject.signer_signature_hash.clone(),
+        };
         let response = match serde_json::to_value(BlockValidateResponse::from(result)) {
             Ok(x) => x,
             Err(e) => {
@@ -934,6 +2969,10 @@ impl ProposalCallbackReceiver for ProposalCallbackHandler {
                 return;
             }
         };
+        if let Some(throttle) = self.throttle.as_ref() {
+            self.send_throttled(throttle, &signer_signature_hash, &response);
+            return;
+        }
         for observer in self.observers.iter() {
             observer.send_payload(&response, PATH_PROPOSAL_RESPONSE);
         }
@@ -941,12 +2980,36 @@ impl ProposalCallbackReceiver for ProposalCallbackHandler {
 }
 
 impl MemPoolEventDispatcher for EventDispatcher {
-    fn mempool_txs_dropped(&self, txids: Vec<Txid>, reason: MemPoolDropReason) {
+    fn get_event_delivery_health(&self) -> Option<RPCEventHealthData> {
+        Some(self.event_delivery_health())
+    }
+
+    fn get_mempool_drop_history(&self) -> Option<RPCMempoolDropsData> {
+        Some(
+            self.mempool_drop_history
+                .lock()
+                .expect("FATAL: failed to lock mempool drop history")
+                .snapshot(),
+        )
+    }
+
+    fn mempool_txs_dropped(
+        &self,
+        txids: Vec<Txid>,
+        reason: MemPoolDropReason,
+        fee_rates: Option<MemPoolFeeRatePercentiles>,
+    ) {
         if !txids.is_empty() {
-            self.process_dropped_mempool_txs(txids, reason)
+            self.process_dropped_mempool_txs(txids, reason, fee_rates)
         }
     }
 
+    fn wants_drop_fee_rates(&self) -> bool {
+        self.filter_observers(&self.mempool_observers_lookup, true)
+            .iter()
+            .any(|observer| observer.include_drop_fee_rates)
+    }
+
     fn mined_block_event(
         &self,
         target_burn_height: u64,
@@ -1021,6 +3084,7 @@ impl MemPoolEventDispatcher for EventDispatcher {
         }
         let handler = ProposalCallbackHandler {
             observers: callback_receivers,
+            throttle: self.proposal_response_throttle.clone(),
         };
         Some(Box::new(handler))
     }
@@ -1035,6 +3099,15 @@ impl StackerDBEventDispatcher for EventDispatcher {
     ) {
         self.process_new_stackerdb_chunks(contract_id, chunks);
     }
+
+    /// Check StackerDB chunks refused at already-stored slot versions for equivocation
+    fn stale_stackerdb_chunks(
+        &self,
+        contract_id: QualifiedContractIdentifier,
+        chunks: Vec<StackerDBChunkData>,
+    ) {
+        self.process_stale_stackerdb_chunks(contract_id, chunks);
+    }
 }
 
 impl BlockEventDispatcher for EventDispatcher {
@@ -1057,6 +3130,7 @@ impl BlockEventDispatcher for EventDispatcher {
         signer_bitvec: &Option<BitVec<4000>>,
         block_timestamp: Option<u64>,
         coinbase_height: u64,
+        timings: &BlockProcessingTimings,
     ) {
         self.process_chain_tip(
             block,
@@ -1076,6 +3150,7 @@ impl BlockEventDispatcher for EventDispatcher {
             signer_bitvec,
             block_timestamp,
             coinbase_height,
+            timings,
         );
     }
 
@@ -1095,23 +3170,66 @@ impl BlockEventDispatcher for EventDispatcher {
             recipient_info,
         )
     }
+
+    fn announce_canonical_tip(&self, tip: &StacksBlockId, cause: TipChangeCause) {
+        self.process_canonical_tip(tip, cause)
+    }
+
+    fn wants_tip_changes(&self) -> bool {
+        !self.tip_changed_observers_lookup.is_empty()
+    }
 }
 
 impl EventDispatcher {
     pub fn new() -> EventDispatcher {
         EventDispatcher {
             registered_observers: vec![],
+            registered_observer_configs: vec![],
             contract_events_observers_lookup: HashMap::new(),
             assets_observers_lookup: HashMap::new(),
-            stx_observers_lookup: HashSet::new(),
-            any_event_observers_lookup: HashSet::new(),
-            burn_block_observers_lookup: HashSet::new(),
-            mempool_observers_lookup: HashSet::new(),
-            microblock_observers_lookup: HashSet::new(),
-            miner_observers_lookup: HashSet::new(),
-            mined_microblocks_observers_lookup: HashSet::new(),
-            stackerdb_observers_lookup: HashSet::new(),
-            block_proposal_observers_lookup: HashSet::new(),
+            deployer_observers_lookup: HashMap::new(),
+            boot_contract_observers_lookup: BTreeSet::new(),
+            stx_observers_lookup: BTreeSet::new(),
+            any_event_observers_lookup: BTreeSet::new(),
+            burn_block_observers_lookup: BTreeSet::new(),
+            mempool_observers_lookup: BTreeSet::new(),
+            microblock_observers_lookup: BTreeSet::new(),
+            microblock_tip_observers_lookup: BTreeSet::new(),
+            miner_observers_lookup: BTreeSet::new(),
+            mined_microblocks_observers_lookup: BTreeSet::new(),
+            stackerdb_observers_lookup: BTreeSet::new(),
+            block_proposal_observers_lookup: BTreeSet::new(),
+            signer_participation_observers_lookup: BTreeSet::new(),
+            signer_participation: Arc::new(Mutex::new(SignerParticipationTracker::new())),
+            stacker_set_diff_observers_lookup: BTreeSet::new(),
+            stacker_set_diff: Arc::new(Mutex::new(StackerSetDiffTracker::new())),
+            cost_near_limit_observers_lookup: BTreeSet::new(),
+            cost_near_limit_threshold: DEFAULT_COST_NEAR_LIMIT_THRESHOLD,
+            rewards_observers_lookup: BTreeSet::new(),
+            burn_block_reorg_observers_lookup: BTreeSet::new(),
+            burn_block_history: Arc::new(Mutex::new(BurnBlockHistory::new())),
+            block_fork_observers_lookup: BTreeSet::new(),
+            block_forks: Arc::new(Mutex::new(BlockForkTracker::new())),
+            tip_changed_observers_lookup: BTreeSet::new(),
+            canonical_tip: Arc::new(Mutex::new(None)),
+            stackerdb_equivocation_observers_lookup: BTreeSet::new(),
+            stackerdb_equivocation: Arc::new(Mutex::new(StackerDBEquivocationTracker::new())),
+            lifecycle_observers_lookup: BTreeSet::new(),
+            node_synced: Arc::new(AtomicBool::new(false)),
+            sortdb_path: None,
+            chainstate_path: None,
+            block_retry_budget: None,
+            proposal_response_throttle: None,
+            last_block_payloads: Arc::new(Mutex::new(HashMap::new())),
+            max_observers: DEFAULT_MAX_EVENT_OBSERVERS,
+            mined_block_archive: None,
+            payload_transform: None,
+            event_serialization_threads: 1,
+            mempool_drop_history: Arc::new(Mutex::new(MempoolDropHistory::new(
+                DEFAULT_MEMPOOL_DROP_HISTORY_SIZE,
+            ))),
+            node_identity: None,
+            host_delivery_limits: Arc::new(HostDeliveryLimits::default()),
         }
     }
 
@@ -1125,11 +3243,11 @@ impl EventDispatcher {
     ) {
         // lazily assemble payload only if we have observers
         let interested_observers = self.filter_observers(&self.burn_block_observers_lookup, true);
-        if interested_observers.len() < 1 {
+        if interested_observers.len() < 1 && self.burn_block_reorg_observers_lookup.is_empty() {
             return;
         }
 
-        let payload = EventObserver::make_new_burn_block_payload(
+        let mut payload = EventObserver::make_new_burn_block_payload(
             burn_block,
             burn_block_height,
             rewards,
@@ -1137,35 +3255,145 @@ impl EventDispatcher {
             recipient_info,
         );
 
+        let announcement = self
+            .burn_block_history
+            .lock()
+            .expect("FATAL: failed to lock burn block history")
+            .record(
+                burn_block,
+                burn_block_height,
+                &canonical_json_bytes(&payload),
+            );
+        let is_reorg = match announcement {
+            BurnBlockAnnouncement::Duplicate => {
+                debug!("Event dispatcher: not re-announcing burn block";
+                       "burn_block_hash" => %burn_block, "burn_block_height" => burn_block_height);
+                return;
+            }
+            BurnBlockAnnouncement::Reorg(previous_burn_block) => {
+                info!("Event dispatcher: burn block replaces one already announced at its height";
+                      "burn_block_height" => burn_block_height,
+                      "old_burn_block_hash" => %previous_burn_block,
+                      "new_burn_block_hash" => %burn_block);
+                self.process_burn_block_reorg(&previous_burn_block, burn_block, burn_block_height);
+                true
+            }
+            BurnBlockAnnouncement::New | BurnBlockAnnouncement::Updated => false,
+        };
+        payload
+            .as_object_mut()
+            .expect("FATAL: burn block payload is not an object")
+            .insert("is_reorg".into(), is_reorg.into());
+
         for observer in interested_observers.iter() {
             observer.send_new_burn_block(&payload);
         }
     }
 
+    /// Tell interested observers that `new_burn_block` replaces `old_burn_block`, which was
+    /// already announced at `burn_block_height`
+    fn process_burn_block_reorg(
+        &self,
+        old_burn_block: &BurnchainHeaderHash,
+        new_burn_block: &BurnchainHeaderHash,
+        burn_block_height: u64,
+    ) {
+        let interested_observers =
+            self.filter_observers(&self.burn_block_reorg_observers_lookup, false);
+        if interested_observers.is_empty() {
+            return;
+        }
+
+        let payload = json!({
+            "burn_block_height": burn_block_height,
+            "old_burn_block_hash": format!("0x{}", old_burn_block),
+            "new_burn_block_hash": format!("0x{}", new_burn_block),
+        });
+        for observer in interested_observers.iter() {
+            observer.send_burn_block_reorg(&payload);
+        }
+    }
+
+    /// Do any observers receive transaction events in their `new_block` payloads?
+    fn has_transaction_event_observers(&self) -> bool {
+        !self.any_event_observers_lookup.is_empty()
+            || !self.stx_observers_lookup.is_empty()
+            || !self.contract_events_observers_lookup.is_empty()
+            || !self.assets_observers_lookup.is_empty()
+            || !self.deployer_observers_lookup.is_empty()
+            || !self.boot_contract_observers_lookup.is_empty()
+    }
+
+    /// Like `create_dispatch_matrix()`, but skips walking the receipts' events if no observer
+    /// would receive any of them, and there's no `stx_supply_delta` to add them to. Every
+    /// observer still gets a (possibly empty) row in the dispatch matrix, since every observer
+    /// is sent `new_block` payloads.
+    fn make_dispatch_matrix(
+        &self,
+        receipts: &[StacksTransactionReceipt],
+        stx_supply_delta: Option<&mut StxSupplyDelta>,
+    ) -> Vec<BTreeSet<usize>> {
+        if !self.has_transaction_event_observers() && stx_supply_delta.is_none() {
+            return vec![BTreeSet::new(); self.registered_observers.len()];
+        }
+        self.create_dispatch_matrix(receipts, stx_supply_delta)
+    }
+
+    /// The events of all of `receipts`, in the order they happened, with whether their
+    /// transaction took effect and its ID. An event's index in the dispatch matrix is its
+    /// position here.
+    fn receipt_events<'a>(
+        receipts: impl IntoIterator<Item = &'a StacksTransactionReceipt>,
+    ) -> impl Iterator<Item = (bool, Txid, &'a StacksTransactionEvent)> {
+        receipts.into_iter().flat_map(|receipt| {
+            let committed = !receipt.post_condition_aborted;
+            let txid = receipt.transaction.txid();
+            receipt
+                .events
+                .iter()
+                .map(move |event| (committed, txid, event))
+        })
+    }
+
     /// Iterates through tx receipts, and then the events corresponding to each receipt to
     /// generate a dispatch matrix & event vector.
     ///
     /// # Returns
-    /// - dispatch_matrix: a vector where each index corresponds to the hashset of event indexes
-    ///     that each respective event observer is subscribed to
+    /// - dispatch_matrix: a vector where each index corresponds to the set of event indexes
+    ///     that each respective event observer is subscribed to, in the order the events
+    ///     happened
     /// - events: a vector of all events from all the tx receipts
+    #[cfg(test)]
     fn create_dispatch_matrix_and_event_vector<'a>(
         &self,
-        receipts: &'a Vec<StacksTransactionReceipt>,
+        receipts: &'a [StacksTransactionReceipt],
+        stx_supply_delta: Option<&mut StxSupplyDelta>,
     ) -> (
-        Vec<HashSet<usize>>,
+        Vec<BTreeSet<usize>>,
         Vec<(bool, Txid, &'a StacksTransactionEvent)>,
     ) {
-        let mut dispatch_matrix: Vec<HashSet<usize>> = self
+        let dispatch_matrix = self.create_dispatch_matrix(receipts, stx_supply_delta);
+        (dispatch_matrix, Self::receipt_events(receipts).collect())
+    }
+
+    /// Make the dispatch matrix of `receipts`: for each observer, the indexes of the events it
+    /// is subscribed to, in the order the events happened. The events themselves are not
+    /// collected, so a block with many events that few observers want costs little memory.
+    /// The STX events of the transactions that took effect are also added to `stx_supply_delta`,
+    /// if given.
+    fn create_dispatch_matrix<'a>(
+        &self,
+        receipts: impl IntoIterator<Item = &'a StacksTransactionReceipt>,
+        mut stx_supply_delta: Option<&mut StxSupplyDelta>,
+    ) -> Vec<BTreeSet<usize>> {
+        let mut dispatch_matrix: Vec<BTreeSet<usize>> = self
             .registered_observers
             .iter()
-            .map(|_| HashSet::new())
+            .map(|_| BTreeSet::new())
             .collect();
-        let mut events: Vec<(bool, Txid, &StacksTransactionEvent)> = vec![];
         let mut i: usize = 0;
 
         for receipt in receipts {
-            let tx_hash = receipt.transaction.txid();
             for event in receipt.events.iter() {
                 match event {
                     StacksTransactionEvent::SmartContractEvent(event_data) => {
@@ -1176,14 +3404,22 @@ impl EventDispatcher {
                                 dispatch_matrix[*o_i as usize].insert(i);
                             }
                         }
+                        self.update_dispatch_matrix_if_deployer_subscribed(
+                            &event_data.key.0,
+                            i,
+                            &mut dispatch_matrix,
+                        );
                     }
-                    StacksTransactionEvent::STXEvent(STXEventType::STXTransferEvent(_))
-                    | StacksTransactionEvent::STXEvent(STXEventType::STXMintEvent(_))
-                    | StacksTransactionEvent::STXEvent(STXEventType::STXBurnEvent(_))
-                    | StacksTransactionEvent::STXEvent(STXEventType::STXLockEvent(_)) => {
+                    StacksTransactionEvent::STXEvent(stx_event) => {
                         for o_i in &self.stx_observers_lookup {
                             dispatch_matrix[*o_i as usize].insert(i);
                         }
+                        // a transaction that failed its post-conditions changed no balances
+                        if let Some(stx_supply_delta) = stx_supply_delta.as_deref_mut() {
+                            if !receipt.post_condition_aborted {
+                                stx_supply_delta.add_event(stx_event);
+                            }
+                        }
                     }
                     StacksTransactionEvent::NFTEvent(NFTEventType::NFTTransferEvent(
                         event_data,
@@ -1230,7 +3466,6 @@ impl EventDispatcher {
                         );
                     }
                 }
-                events.push((!receipt.post_condition_aborted, tx_hash, event));
                 for o_i in &self.any_event_observers_lookup {
                     dispatch_matrix[*o_i as usize].insert(i);
                 }
@@ -1238,7 +3473,7 @@ impl EventDispatcher {
             }
         }
 
-        (dispatch_matrix, events)
+        dispatch_matrix
     }
 
     pub fn process_chain_tip(
@@ -1260,1091 +3495,7859 @@ impl EventDispatcher {
         signer_bitvec: &Option<BitVec<4000>>,
         block_timestamp: Option<u64>,
         coinbase_height: u64,
+        timings: &BlockProcessingTimings,
     ) {
-        let all_receipts = receipts.to_owned();
-        let (dispatch_matrix, events) = self.create_dispatch_matrix_and_event_vector(&all_receipts);
-
-        if dispatch_matrix.len() > 0 {
-            let mature_rewards_vec = if let Some(rewards_info) = mature_rewards_info {
-                mature_rewards
-                    .iter()
-                    .map(|reward| {
-                        json!({
-                            "recipient": reward.recipient.to_string(),
-                            "miner_address": reward.address.to_string(),
-                            "coinbase_amount": reward.coinbase.to_string(),
-                            "tx_fees_anchored": reward.tx_fees_anchored.to_string(),
-                            "tx_fees_streamed_confirmed": reward.tx_fees_streamed_confirmed.to_string(),
-                            "tx_fees_streamed_produced": reward.tx_fees_streamed_produced.to_string(),
-                            "from_stacks_block_hash": format!("0x{}", rewards_info.from_stacks_block_hash),
-                            "from_index_consensus_hash": format!("0x{}", StacksBlockId::new(&rewards_info.from_block_consensus_hash,
-                                                                                            &rewards_info.from_stacks_block_hash)),
-                        })
-                    })
-                    .collect()
-            } else {
-                vec![]
-            };
+        // capture this before any payloads are assembled
+        let processed_at = get_epoch_time_secs();
 
-            let mature_rewards = serde_json::Value::Array(mature_rewards_vec);
+        // all observers share one retry budget for this block
+        let retry_deadline = self
+            .block_retry_budget
+            .map(|budget| Instant::now() + budget);
 
-            for (observer_id, filtered_events_ids) in dispatch_matrix.iter().enumerate() {
-                let filtered_events: Vec<_> = filtered_events_ids
-                    .iter()
-                    .map(|event_id| (*event_id, &events[*event_id]))
-                    .collect();
-
-                let payload = self.registered_observers[observer_id]
-                    .make_new_block_processed_payload(
-                        filtered_events,
-                        &block,
-                        metadata,
-                        receipts,
-                        parent_index_hash,
-                        &winner_txid,
-                        &mature_rewards,
-                        parent_burn_block_hash,
-                        parent_burn_block_height,
-                        parent_burn_block_timestamp,
-                        anchored_consumed,
-                        mblock_confirmed_consumed,
-                        pox_constants,
-                        reward_set_data,
-                        signer_bitvec,
-                        block_timestamp,
-                        coinbase_height,
-                    );
+        // hold back the strictly-ordered observers' other events until this block is delivered
+        let block_order_holds: Vec<_> = self
+            .registered_observers
+            .iter()
+            .filter_map(|observer| observer.block_order.as_ref().map(|gate| gate.hold()))
+            .collect();
 
-                // Send payload
-                self.registered_observers[observer_id].send_payload(&payload, PATH_BLOCK_PROCESSED);
-            }
+        // announce the block before doing any of the work to assemble the full payloads
+        let mut header_payload = None;
+        for observer in self
+            .registered_observers
+            .iter()
+            .filter(|observer| observer.include_block_header_event)
+        {
+            let payload = header_payload.get_or_insert_with(|| {
+                EventObserver::make_new_block_header_payload(block, metadata, parent_index_hash)
+            });
+            observer.send_payload_with_retry_deadline(payload, PATH_BLOCK_HEADER, retry_deadline);
         }
-    }
 
-    /// Creates a list of observers that are interested in the new microblocks event,
-    /// creates a mapping from observers to the event ids that are relevant to each, and then
-    /// sends the event to each interested observer.
-    pub fn process_new_microblocks(
-        &self,
-        parent_index_block_hash: StacksBlockId,
-        processed_unconfirmed_state: ProcessedUnconfirmedState,
-    ) {
-        // lazily assemble payload only if we have observers
-        let interested_observers: Vec<_> = self
+        let serialization_start = Instant::now();
+        let mut stx_supply_delta = self
             .registered_observers
             .iter()
-            .enumerate()
-            .filter(|(obs_id, _observer)| {
-                self.microblock_observers_lookup
-                    .contains(&(u16::try_from(*obs_id).expect("FATAL: more than 2^16 observers")))
-                    || self.any_event_observers_lookup.contains(
-                        &(u16::try_from(*obs_id).expect("FATAL: more than 2^16 observers")),
+            .any(|observer| observer.include_stx_supply_delta)
+            .then(StxSupplyDelta::default);
+        let dispatch_matrix = self.make_dispatch_matrix(receipts, stx_supply_delta.as_mut());
+        let mature_rewards = Self::make_mature_rewards_payload(mature_rewards, mature_rewards_info);
+
+        if dispatch_matrix.len() > 0 {
+            let winner_commit = if self
+                .registered_observers
+                .iter()
+                .any(|observer| observer.include_winner_commit)
+            {
+                self.get_winner_commit(&metadata.consensus_hash, &winner_txid, pox_constants)
+            } else {
+                None
+            };
+            let sortition_info = if self
+                .registered_observers
+                .iter()
+                .any(|observer| observer.include_sortition_info)
+            {
+                self.get_sortition_info(&metadata.consensus_hash, pox_constants)
+            } else {
+                None
+            };
+            let miner_signature_valid = match &metadata.anchored_header {
+                StacksBlockHeaderTypes::Nakamoto(header)
+                    if self
+                        .registered_observers
+                        .iter()
+                        .any(|observer| observer.include_miner_signature_valid) =>
+                {
+                    self.get_miner_signature_valid(header, pox_constants)
+                }
+                _ => None,
+            };
+            let parent_tenure_consensus_hash = match &metadata.anchored_header {
+                StacksBlockHeaderTypes::Nakamoto(_) => {
+                    self.get_parent_tenure_consensus_hash(parent_index_hash)
+                }
+                StacksBlockHeaderTypes::Epoch2(_) => None,
+            };
+
+            // serialize the parts that the observers' payloads share once, then assemble each
+            // observer's payload from them. Only the events that some observer receives are
+            // collected from the receipts.
+            let threads = self.event_serialization_threads;
+            let event_ids: BTreeSet<usize> = dispatch_matrix.iter().flatten().copied().collect();
+            let filtered_events: Vec<_> = Self::receipt_events(receipts)
+                .enumerate()
+                .filter(|(event_id, _)| event_ids.contains(event_id))
+                .collect();
+            let common = EventObserver::make_new_block_common_payload(
+                block,
+                metadata,
+                parent_index_hash,
+                &winner_txid,
+                &mature_rewards,
+                parent_burn_block_hash,
+                parent_burn_block_height,
+                parent_burn_block_timestamp,
+                anchored_consumed,
+                mblock_confirmed_consumed,
+                pox_constants,
+                reward_set_data,
+                signer_bitvec,
+                block_timestamp,
+                coinbase_height,
+                processed_at,
+                parent_tenure_consensus_hash.as_ref(),
+            );
+            let parts = EventObserver::make_new_block_payload_parts(
+                common,
+                &filtered_events,
+                receipts,
+                self.registered_observers
+                    .iter()
+                    .any(|observer| observer.include_raw_burnchain_op),
+                threads,
+            );
+
+            let observer_options: Vec<_> = self
+                .registered_observers
+                .iter()
+                .map(|observer| {
+                    (
+                        observer.include_raw_burnchain_op,
+                        winner_commit
+                            .as_ref()
+                            .filter(|_| observer.include_winner_commit),
+                        sortition_info
+                            .as_ref()
+                            .filter(|_| observer.include_sortition_info),
+                        miner_signature_valid.filter(|_| observer.include_miner_signature_valid),
                     )
-            })
-            .collect();
-        if interested_observers.len() < 1 {
-            return;
-        }
-        let flattened_receipts = processed_unconfirmed_state
-            .receipts
-            .iter()
-            .flat_map(|(_, _, r)| r.clone())
+                })
+                .collect();
+            let mut payloads: HashMap<u16, serde_json::Value> = parallel_map(
+                &observer_options,
+                threads,
+                |observer_id,
+                 (
+                    include_raw_burnchain_op,
+                    winner_commit,
+                    sortition_info,
+                    miner_signature_valid,
+                )| {
+                    parts.assemble(
+                        &dispatch_matrix[observer_id],
+                        *include_raw_burnchain_op,
+                        *winner_commit,
+                        *sortition_info,
+                        *miner_signature_valid,
+                    )
+                },
+            )
+            .into_iter()
+            .enumerate()
+            .map(|(observer_id, payload)| (observer_id as u16, payload))
             .collect();
-        let (dispatch_matrix, events) =
-            self.create_dispatch_matrix_and_event_vector(&flattened_receipts);
-
-        // Serialize receipts
-        let mut tx_index;
-        let mut serialized_txs = Vec::new();
 
-        for (_, _, receipts) in processed_unconfirmed_state.receipts.iter() {
-            tx_index = 0;
-            for receipt in receipts.iter() {
-                let payload = EventObserver::make_new_block_txs_payload(receipt, tx_index);
-                serialized_txs.push(payload);
-                tx_index += 1;
+            if let Some(stx_supply_delta) = stx_supply_delta.as_ref() {
+                let stx_supply_delta = stx_supply_delta.to_json();
+                for (observer_id, observer) in self.registered_observers.iter().enumerate() {
+                    if !observer.include_stx_supply_delta {
+                        continue;
+                    }
+                    if let Some(serde_json::Value::Object(payload)) =
+                        payloads.get_mut(&(observer_id as u16))
+                    {
+                        payload.insert("stx_supply_delta".into(), stx_supply_delta.clone());
+                    }
+                }
             }
-        }
 
-        for (obs_id, observer) in interested_observers.iter() {
-            let filtered_events_ids = &dispatch_matrix[*obs_id];
-            let filtered_events: Vec<_> = filtered_events_ids
+            if self
+                .registered_observers
                 .iter()
-                .map(|event_id| (*event_id, &events[*event_id]))
+                .any(|observer| observer.include_raw_header)
+            {
+                let raw_header = EventObserver::make_raw_header_payload(&metadata.anchored_header);
+                for (observer_id, observer) in self.registered_observers.iter().enumerate() {
+                    if !observer.include_raw_header {
+                        continue;
+                    }
+                    if let Some(serde_json::Value::Object(payload)) =
+                        payloads.get_mut(&(observer_id as u16))
+                    {
+                        payload.insert("raw_header".into(), raw_header.clone());
+                    }
+                }
+            }
+
+            let event_serialization = serialization_start.elapsed();
+            for (observer_id, observer) in self.registered_observers.iter().enumerate() {
+                if !observer.include_block_timings {
+                    continue;
+                }
+                if let Some(serde_json::Value::Object(payload)) =
+                    payloads.get_mut(&(observer_id as u16))
+                {
+                    payload.insert(
+                        "timings".into(),
+                        EventObserver::make_block_timings_payload(timings, event_serialization),
+                    );
+                }
+            }
+
+            // swap in this block's payloads for all observers at once, so a resend never mixes
+            // up payloads from different blocks. The payloads are shared with the remembered
+            // ones rather than copied, since a large block's payloads can be large.
+            let payloads: HashMap<u16, Arc<serde_json::Value>> = payloads
+                .into_iter()
+                .map(|(observer_id, payload)| (observer_id, Arc::new(payload)))
                 .collect();
+            *self
+                .last_block_payloads
+                .lock()
+                .expect("FATAL: failed to lock last block payloads") = payloads.clone();
+
+            for (observer_id, observer) in self.registered_observers.iter().enumerate() {
+                let Some(payload) = payloads.get(&(observer_id as u16)) else {
+                    continue;
+                };
+                observer.send_payload_with_retry_deadline(
+                    payload,
+                    PATH_BLOCK_PROCESSED,
+                    retry_deadline,
+                );
+            }
+        }
+        drop(block_order_holds);
 
-            observer.send_new_microblocks(
-                parent_index_block_hash,
-                filtered_events,
-                &serialized_txs,
-                processed_unconfirmed_state.burn_block_hash,
-                processed_unconfirmed_state.burn_block_height,
-                processed_unconfirmed_state.burn_block_timestamp,
+        if let StacksBlockHeaderTypes::Nakamoto(ref header) = metadata.anchored_header {
+            self.process_signer_participation(
+                header,
+                metadata.stacks_block_height,
+                reward_set_data,
             );
+            self.process_block_forks(header, metadata.stacks_block_height, pox_constants);
+        }
+
+        if let Some(reward_set_data) = reward_set_data {
+            self.process_stacker_set_diff(reward_set_data);
         }
+
+        self.process_cost_near_limit(block, metadata, receipts, pox_constants);
+
+        self.process_new_rewards(block, metadata, receipts, &mature_rewards);
     }
 
-    fn filter_observers(&self, lookup: &HashSet<u16>, include_any: bool) -> Vec<&EventObserver> {
-        self.registered_observers
+    /// Describe the miner rewards that matured in a block, as sent in `matured_miner_rewards`
+    fn make_mature_rewards_payload(
+        mature_rewards: &[MinerReward],
+        mature_rewards_info: Option<&MinerRewardInfo>,
+    ) -> serde_json::Value {
+        let Some(rewards_info) = mature_rewards_info else {
+            return serde_json::Value::Array(vec![]);
+        };
+        mature_rewards
             .iter()
-            .enumerate()
-            .filter_map(|(obs_id, observer)| {
-                let lookup_ix = u16::try_from(obs_id).expect("FATAL: more than 2^16 observers");
-                if lookup.contains(&lookup_ix) {
-                    return Some(observer);
-                } else if include_any && self.any_event_observers_lookup.contains(&lookup_ix) {
-                    return Some(observer);
-                } else {
-                    return None;
-                }
+            .map(|reward| {
+                json!({
+                    "recipient": reward.recipient.to_string(),
+                    "miner_address": reward.address.to_string(),
+                    "coinbase_amount": reward.coinbase.to_string(),
+                    "tx_fees_anchored": reward.tx_fees_anchored.to_string(),
+                    "tx_fees_streamed_confirmed": reward.tx_fees_streamed_confirmed.to_string(),
+                    "tx_fees_streamed_produced": reward.tx_fees_streamed_produced.to_string(),
+                    "from_stacks_block_hash": format!("0x{}", rewards_info.from_stacks_block_hash),
+                    "from_index_consensus_hash": format!("0x{}", StacksBlockId::new(&rewards_info.from_block_consensus_hash,
+                                                                                    &rewards_info.from_stacks_block_hash)),
+                })
             })
             .collect()
     }
 
-    pub fn process_new_mempool_txs(&self, txs: Vec<StacksTransaction>) {
-        // lazily assemble payload only if we have observers
-        let interested_observers = self.filter_observers(&self.mempool_observers_lookup, true);
+    /// Send the observer with `endpoint` the most recent `new_block` event again, as it was
+    /// built for that observer. This is cheaper than replaying the observer's history when it
+    /// only needs the current tip. Fails if there is no such observer, or no block has been
+    /// processed since the node started.
+    pub fn resend_last_block(&self, endpoint: &str) -> Result<(), String> {
+        let observer_id = self
+            .registered_observers
+            .iter()
+            .position(|observer| observer.endpoint == endpoint)
+            .ok_or_else(|| format!("No event observer with endpoint '{}'", endpoint))?;
+        let payload = self
+            .last_block_payloads
+            .lock()
+            .expect("FATAL: failed to lock last block payloads")
+            .get(&(observer_id as u16))
+            .cloned()
+            .ok_or_else(|| "No block has been processed yet".to_string())?;
 
-        if interested_observers.len() < 1 {
-            return;
-        }
+        info!(
+            "Event dispatcher: resending the last block";
+            "endpoint" => redact_endpoint(endpoint)
+        );
+        self.registered_observers[observer_id].send_payload(&payload, PATH_BLOCK_PROCESSED);
+        Ok(())
+    }
 
-        let payload = EventObserver::make_new_mempool_txs_payload(txs);
+    /// Send the observer with `endpoint` the remembered `drop_mempool_tx` events again, oldest
+    /// first, with the time and reason of each drop. Helps an observer that subscribed after
+    /// the drops were announced. Returns the number of events sent. Fails if there is no such
+    /// observer, or it isn't subscribed to mempool events.
+    pub fn replay_dropped_mempool_txs(&self, endpoint: &str) -> Result<usize, String> {
+        let observer = self
+            .filter_observers(&self.mempool_observers_lookup, true)
+            .into_iter()
+            .find(|observer| observer.endpoint == endpoint)
+            .ok_or_else(|| {
+                format!(
+                    "No event observer with endpoint '{}' subscribed to mempool events",
+                    endpoint
+                )
+            })?;
+        let payloads = self
+            .mempool_drop_history
+            .lock()
+            .expect("FATAL: failed to lock mempool drop history")
+            .make_replay_payloads();
 
-        for observer in interested_observers.iter() {
-            observer.send_new_mempool_txs(&payload);
+        info!(
+            "Event dispatcher: replaying dropped mempool transactions";
+            "endpoint" => redact_endpoint(endpoint),
+            "events" => payloads.len()
+        );
+        for payload in payloads.iter() {
+            observer.send_dropped_mempool_txs(payload);
         }
+        Ok(payloads.len())
     }
 
-    pub fn process_mined_block_event(
-        &self,
-        target_burn_height: u64,
-        block: &StacksBlock,
-        block_size_bytes: u64,
-        consumed: &ExecutionCost,
-        confirmed_microblock_cost: &ExecutionCost,
-        tx_events: Vec<TransactionEvent>,
-    ) {
-        let interested_observers = self.filter_observers(&self.miner_observers_lookup, false);
-
-        if interested_observers.len() < 1 {
-            return;
-        }
+    /// Set the path to the sortition DB, so that winning block-commits can be looked up for
+    /// observers with `include_winner_commit` set
+    pub fn set_sortdb_path(&mut self, sortdb_path: String) {
+        self.sortdb_path = Some(sortdb_path);
+    }
 
-        let payload = serde_json::to_value(MinedBlockEvent {
-            target_burn_height,
-            block_hash: block.block_hash().to_string(),
-            stacks_height: block.header.total_work.work,
-            block_size: block_size_bytes,
-            anchored_cost: consumed.clone(),
-            confirmed_microblocks_cost: confirmed_microblock_cost.clone(),
-            tx_events,
-        })
-        .unwrap();
+    /// Set the path to the chainstate, so that the tenure of each Nakamoto block's parent can
+    /// be reported in `new_block` payloads
+    pub fn set_chainstate_path(&mut self, chainstate_path: String) {
+        self.chainstate_path = Some(chainstate_path);
+    }
 
-        for observer in interested_observers.iter() {
-            observer.send_mined_block(&payload);
+    /// Cap the total time spent retrying `new_block` deliveries for each block. `None` (the
+    /// default) retries every delivery until it succeeds.
+    pub fn set_block_retry_budget(&mut self, block_retry_budget: Option<Duration>) {
+        self.block_retry_budget = block_retry_budget;
+        // strictly-ordered observers hold their other events back for no longer than the budget
+        for observer in self.registered_observers.iter() {
+            if let Some(block_order) = observer.block_order.as_ref() {
+                block_order.set_max_wait(block_retry_budget);
+            }
         }
     }
 
-    pub fn process_mined_microblock_event(
-        &self,
-        microblock: &StacksMicroblock,
-        tx_events: Vec<TransactionEvent>,
-        anchor_block_consensus_hash: ConsensusHash,
-        anchor_block: BlockHeaderHash,
-    ) {
-        let interested_observers =
-            self.filter_observers(&self.mined_microblocks_observers_lookup, false);
-        if interested_observers.len() < 1 {
-            return;
-        }
+    /// Enforce a minimum interval between the `proposal_response` events sent to each observer.
+    /// A response still waiting for its slot is dropped if a later response for the same
+    /// block proposal arrives. `None` (the default) sends every response immediately.
+    pub fn set_proposal_response_min_interval(&mut self, min_interval: Option<Duration>) {
+        self.proposal_response_throttle = min_interval
+            .map(|min_interval| Arc::new(Mutex::new(ProposalResponseThrottle::new(min_interval))));
+    }
 
-        let payload = serde_json::to_value(MinedMicroblockEvent {
-            block_hash: microblock.block_hash().to_string(),
-            sequence: microblock.header.sequence,
-            tx_events,
-            anchor_block_consensus_hash,
-            anchor_block,
-        })
-        .unwrap();
+    /// Write every mined-block payload to `mined_block_archive`, in addition to sending it to
+    /// the miner observers. `None` (the default) keeps no local record.
+    pub fn set_mined_block_archive(&mut self, mined_block_archive: Option<MinedBlockArchive>) {
+        self.mined_block_archive = mined_block_archive;
+    }
 
-        for observer in interested_observers.iter() {
-            observer.send_mined_microblock(&payload);
+    /// Pass every payload sent to any observer, registered before or after this call, through
+    /// `payload_transform`. It is applied before each observer's own payload options, so it
+    /// sees the full payload. `None` (the default) sends payloads as they are built.
+    /// The closure must not panic: a payload whose transform panics is dropped, not sent.
+    pub fn set_payload_transform(&mut self, payload_transform: Option<PayloadTransform>) {
+        for observer in self.registered_observers.iter_mut() {
+            observer.transform = payload_transform.clone();
         }
+        self.payload_transform = payload_transform;
     }
 
-    pub fn process_mined_nakamoto_block_event(
+    /// Write a mined-block payload to the local archive, if there is one
+    fn archive_mined_block(
         &self,
+        path: &str,
         target_burn_height: u64,
-        block: &NakamotoBlock,
-        block_size_bytes: u64,
-        consumed: &ExecutionCost,
-        tx_events: Vec<TransactionEvent>,
+        payload: &serde_json::Value,
     ) {
-        let interested_observers = self.filter_observers(&self.miner_observers_lookup, false);
-        if interested_observers.len() < 1 {
+        let Some(archive) = self.mined_block_archive.as_ref() else {
             return;
+        };
+        if let Err(e) = archive.archive(&format!("/{}", path), target_burn_height, payload) {
+            error!(
+                "Event dispatcher: failed to archive mined block";
+                "target_burn_height" => target_burn_height, "error" => e
+            );
         }
+    }
 
-        let signer_bitvec = serde_json::to_value(block.header.pox_treatment.clone())
-            .unwrap_or_default()
-            .as_str()
-            .unwrap_or_default()
-            .to_string();
+    /// Cap the number of observers that may be registered. The cap can't exceed
+    /// `MAX_EVENT_OBSERVERS`, since observers are indexed by `u16`.
+    pub fn set_max_observers(&mut self, max_observers: usize) {
+        self.max_observers = max_observers.min(MAX_EVENT_OBSERVERS);
+    }
 
-        let payload = serde_json::to_value(MinedNakamotoBlockEvent {
-            target_burn_height,
-            parent_block_id: block.header.parent_block_id.to_string(),
-            block_hash: block.header.block_hash().to_string(),
-            block_id: block.header.block_id().to_string(),
-            stacks_height: block.header.chain_length,
-            block_size: block_size_bytes,
-            cost: consumed.clone(),
-            tx_events,
-            miner_signature: block.header.miner_signature.clone(),
-            signer_signature_hash: block.header.signer_signature_hash(),
-            signer_signature: block.header.signer_signature.clone(),
-            signer_bitvec,
-        })
-        .unwrap();
+    /// Set the fraction of the block limit, in any cost dimension, above which a transaction's
+    /// execution cost is reported to `cost_near_limit` observers
+    pub fn set_cost_near_limit_threshold(&mut self, threshold: f64) {
+        self.cost_near_limit_threshold = threshold;
+    }
 
-        for observer in interested_observers.iter() {
-            observer.send_mined_nakamoto_block(&payload);
-        }
+    /// Spread the serialization of each block's `new_block` payloads over this many threads.
+    /// `1` (the default) serializes them on the block processing thread.
+    pub fn set_event_serialization_threads(&mut self, threads: usize) {
+        self.event_serialization_threads = threads.max(1);
     }
 
-    /// Forward newly-accepted StackerDB chunk metadata to downstream `stackerdb` observers.
-    /// Infallible.
-    pub fn process_new_stackerdb_chunks(
-        &self,
-        contract_id: QualifiedContractIdentifier,
-        modified_slots: Vec<StackerDBChunkData>,
+    /// Set how many dropped mempool transactions are remembered for
+    /// `GET /v2/debug/mempool_drops` and `replay_dropped_mempool_txs()`. `0` remembers none.
+    /// Any drops already remembered are forgotten.
+    pub fn set_mempool_drop_history_size(&mut self, size: usize) {
+        self.mempool_drop_history = Arc::new(Mutex::new(MempoolDropHistory::new(size)));
+    }
+
+    /// Set who this node is, as told to observers with `send_startup_event` when they're
+    /// registered. Must be called before they're registered.
+    pub fn set_node_identity(&mut self, node_identity: NodeIdentity) {
+        self.node_identity = Some(node_identity);
+    }
+
+    /// Cap the delivery attempts in flight to each observer host, across all of the host's
+    /// observers, at `default_max_in_flight`, or at the host's entry in `host_max_in_flight`.
+    /// Attempts beyond the cap wait for a slot. Must be called before the observers are
+    /// registered.
+    pub fn set_host_delivery_limits(
+        &mut self,
+        default_max_in_flight: usize,
+        host_max_in_flight: HashMap<String, usize>,
     ) {
-        debug!(
-            "event_dispatcher: New StackerDB chunk events for {}: {:?}",
-            contract_id, modified_slots
-        );
+        self.host_delivery_limits = Arc::new(HostDeliveryLimits::new(
+            default_max_in_flight,
+            host_max_in_flight,
+        ));
+    }
 
-        let interested_observers = self.filter_observers(&self.stackerdb_observers_lookup, false);
+    /// Look up the block-commit that won the sortition with the given consensus hash, and
+    /// describe it for `new_block` payloads. Returns `None` (after logging why) if there is
+    /// no sortition DB or the commit cannot be found.
+    fn get_winner_commit(
+        &self,
+        consensus_hash: &ConsensusHash,
+        winner_txid: &Txid,
+        pox_constants: &PoxConstants,
+    ) -> Option<serde_json::Value> {
+        let Some(sortdb_path) = self.sortdb_path.as_ref() else {
+            warn!("Event dispatcher: no sortition DB to look up winning block-commits in");
+            return None;
+        };
+        let sortdb = SortitionDB::open(sortdb_path, false, pox_constants.clone())
+            .map_err(|e| {
+                warn!("Event dispatcher: failed to open sortition DB"; "error" => ?e);
+            })
+            .ok()?;
+        let lookup = || -> Result<Option<serde_json::Value>, db_error> {
+            let Some(snapshot) =
+                SortitionDB::get_block_snapshot_consensus(sortdb.conn(), consensus_hash)?
+            else {
+                return Ok(None);
+            };
+            let Some(commit) =
+                get_block_commit_by_txid(sortdb.conn(), &snapshot.sortition_id, winner_txid)?
+            else {
+                return Ok(None);
+            };
+            let parent_total_burn =
+                SortitionDB::get_block_snapshot(sortdb.conn(), &snapshot.parent_sortition_id)?
+                    .map(|parent| parent.total_burn)
+                    .unwrap_or(0);
+            let leader_key = SortitionDB::get_leader_key_at(
+                &sortdb.index_conn(),
+                commit.key_block_ptr.into(),
+                commit.key_vtxindex.into(),
+                &snapshot.sortition_id,
+            )?;
+            Ok(Some(EventObserver::make_winner_commit_payload(
+                &commit,
+                snapshot.total_burn.saturating_sub(parent_total_burn),
+                leader_key.as_ref(),
+            )))
+        };
+        match lookup() {
+            Ok(Some(winner_commit)) => Some(winner_commit),
+            Ok(None) => {
+                warn!("Event dispatcher: winning block-commit not found";
+                      "consensus_hash" => %consensus_hash, "winner_txid" => %winner_txid);
+                None
+            }
+            Err(e) => {
+                warn!("Event dispatcher: failed to look up winning block-commit";
+                      "consensus_hash" => %consensus_hash, "winner_txid" => %winner_txid, "error" => ?e);
+                None
+            }
+        }
+    }
 
-        let interested_receiver = STACKER_DB_CHANNEL.is_active(&contract_id);
-        if interested_observers.is_empty() && interested_receiver.is_none() {
-            return;
+    /// Look up the sortition that the tenure with `consensus_hash` started in, and its winning
+    /// block-commit's VRF seed, for observers with `include_sortition_info` set
+    fn get_sortition_info(
+        &self,
+        consensus_hash: &ConsensusHash,
+        pox_constants: &PoxConstants,
+    ) -> Option<serde_json::Value> {
+        let Some(sortdb_path) = self.sortdb_path.as_ref() else {
+            warn!("Event dispatcher: no sortition DB to look up sortitions in");
+            return None;
+        };
+        let sortdb = SortitionDB::open(sortdb_path, false, pox_constants.clone())
+            .map_err(|e| {
+                warn!("Event dispatcher: failed to open sortition DB"; "error" => ?e);
+            })
+            .ok()?;
+        let lookup = || -> Result<Option<serde_json::Value>, db_error> {
+            let Some(snapshot) =
+                SortitionDB::get_block_snapshot_consensus(sortdb.conn(), consensus_hash)?
+            else {
+                return Ok(None);
+            };
+            let vrf_seed = if snapshot.sortition {
+                get_block_commit_by_txid(
+                    sortdb.conn(),
+                    &snapshot.sortition_id,
+                    &snapshot.winning_block_txid,
+                )?
+                .map(|commit| commit.new_seed)
+            } else {
+                None
+            };
+            Ok(Some(EventObserver::make_sortition_info_payload(
+                &snapshot,
+                vrf_seed.as_ref(),
+            )))
+        };
+        match lookup() {
+            Ok(Some(sortition_info)) => Some(sortition_info),
+            Ok(None) => {
+                warn!("Event dispatcher: sortition not found";
+                      "consensus_hash" => %consensus_hash);
+                None
+            }
+            Err(e) => {
+                warn!("Event dispatcher: failed to look up sortition";
+                      "consensus_hash" => %consensus_hash, "error" => ?e);
+                None
+            }
         }
+    }
 
-        let event = StackerDBChunksEvent {
-            contract_id,
-            modified_slots,
+    /// Look up the consensus hash of the tenure that the block `parent_index_hash` was mined in.
+    /// Returns `None` (after logging why) if there is no chainstate or the block is unknown.
+    fn get_parent_tenure_consensus_hash(
+        &self,
+        parent_index_hash: &StacksBlockId,
+    ) -> Option<ConsensusHash> {
+        let Some(chainstate_path) = self.chainstate_path.as_ref() else {
+            warn!("Event dispatcher: no chainstate to look up parent blocks in");
+            return None;
         };
-        let payload = serde_json::to_value(&event)
-            .expect("FATAL: failed to serialize StackerDBChunksEvent to JSON");
+        let headers_path = StacksChainState::header_index_root_path(chainstate_path.into());
+        let conn = Connection::open_with_flags(&headers_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(|e| {
+                warn!("Event dispatcher: failed to open chainstate headers DB"; "error" => ?e);
+            })
+            .ok()?;
+        match NakamotoChainState::get_block_header(&conn, parent_index_hash) {
+            Ok(Some(parent)) => Some(parent.consensus_hash),
+            Ok(None) => {
+                warn!("Event dispatcher: parent block not found";
+                      "parent_index_block_hash" => %parent_index_hash);
+                None
+            }
+            Err(e) => {
+                warn!("Event dispatcher: failed to look up parent block";
+                      "parent_index_block_hash" => %parent_index_hash, "error" => ?e);
+                None
+            }
+        }
+    }
 
-        if let Some(channel) = interested_receiver {
-            if let Err(send_err) = channel.send(event) {
-                warn!(
-                    "Failed to send StackerDB event to signer coordinator channel. Miner thread may have exited.";
-                    "err" => ?send_err
-                );
+    /// Check a Nakamoto block's miner signature against the signing key registered by the
+    /// winner of its tenure's sortition, for observers with `include_miner_signature_valid` set
+    fn get_miner_signature_valid(
+        &self,
+        header: &NakamotoBlockHeader,
+        pox_constants: &PoxConstants,
+    ) -> Option<bool> {
+        let Some(sortdb_path) = self.sortdb_path.as_ref() else {
+            warn!("Event dispatcher: no sortition DB to look up miner keys in");
+            return None;
+        };
+        let sortdb = SortitionDB::open(sortdb_path, false, pox_constants.clone())
+            .map_err(|e| {
+                warn!("Event dispatcher: failed to open sortition DB"; "error" => ?e);
+            })
+            .ok()?;
+        let lookup = || -> Result<Option<LeaderKeyRegisterOp>, db_error> {
+            let Some(snapshot) =
+                SortitionDB::get_block_snapshot_consensus(sortdb.conn(), &header.consensus_hash)?
+            else {
+                return Ok(None);
+            };
+            let Some(commit) = get_block_commit_by_txid(
+                sortdb.conn(),
+                &snapshot.sortition_id,
+                &snapshot.winning_block_txid,
+            )?
+            else {
+                return Ok(None);
+            };
+            SortitionDB::get_leader_key_at(
+                &sortdb.index_conn(),
+                commit.key_block_ptr.into(),
+                commit.key_vtxindex.into(),
+                &snapshot.sortition_id,
+            )
+        };
+        match lookup() {
+            Ok(Some(leader_key)) => {
+                let valid = EventObserver::check_miner_signature(header, &leader_key);
+                if valid.is_none() {
+                    warn!("Event dispatcher: tenure's leader key has no miner signing key";
+                          "consensus_hash" => %header.consensus_hash);
+                }
+                valid
+            }
+            Ok(None) => {
+                warn!("Event dispatcher: tenure's leader key not found";
+                      "consensus_hash" => %header.consensus_hash);
+                None
+            }
+            Err(e) => {
+                warn!("Event dispatcher: failed to look up tenure's leader key";
+                      "consensus_hash" => %header.consensus_hash, "error" => ?e);
+                None
             }
         }
+    }
+
+    /// Diff a newly-computed reward set against the previous one, and send the diff to
+    /// interested observers
+    fn process_stacker_set_diff(&self, reward_set_data: &RewardSetData) {
+        let interested_observers =
+            self.filter_observers(&self.stacker_set_diff_observers_lookup, false);
+        if interested_observers.is_empty() {
+            return;
+        }
+
+        let Some(diff) = self
+            .stacker_set_diff
+            .lock()
+            .expect("FATAL: failed to lock stacker set diff tracker")
+            .record_reward_set(reward_set_data)
+        else {
+            return;
+        };
 
+        let payload = serde_json::to_value(&diff)
+            .expect("FATAL: failed to serialize stacker set diff to JSON");
         for observer in interested_observers.iter() {
-            observer.send_stackerdb_chunks(&payload);
+            observer.send_stacker_set_diff(&payload);
         }
     }
 
-    pub fn process_dropped_mempool_txs(&self, txs: Vec<Txid>, reason: MemPoolDropReason) {
-        // lazily assemble payload only if we have observers
-        let interested_observers = self.filter_observers(&self.mempool_observers_lookup, true);
+    /// Look up the block cost limit of the epoch that the given burn block height is in.
+    /// Returns `None` (after logging why) if there is no sortition DB or the epoch cannot be
+    /// found.
+    fn get_epoch_block_limit(
+        &self,
+        burn_block_height: u64,
+        pox_constants: &PoxConstants,
+    ) -> Option<ExecutionCost> {
+        let Some(sortdb_path) = self.sortdb_path.as_ref() else {
+            warn!("Event dispatcher: no sortition DB to look up epoch cost limits in");
+            return None;
+        };
+        let sortdb = SortitionDB::open(sortdb_path, false, pox_constants.clone())
+            .map_err(|e| {
+                warn!("Event dispatcher: failed to open sortition DB"; "error" => ?e);
+            })
+            .ok()?;
+        match SortitionDB::get_stacks_epoch(sortdb.conn(), burn_block_height) {
+            Ok(Some(epoch)) => Some(epoch.block_limit),
+            Ok(None) => {
+                warn!("Event dispatcher: no epoch found for burn block";
+                      "burn_block_height" => burn_block_height);
+                None
+            }
+            Err(e) => {
+                warn!("Event dispatcher: failed to look up epoch";
+                      "burn_block_height" => burn_block_height, "error" => ?e);
+                None
+            }
+        }
+    }
 
-        if interested_observers.len() < 1 {
+    /// Find the transactions in a newly-processed block whose execution cost came close to the
+    /// epoch's block limit, and report them to interested observers
+    fn process_cost_near_limit(
+        &self,
+        block: &StacksBlockEventData,
+        metadata: &StacksHeaderInfo,
+        receipts: &[StacksTransactionReceipt],
+        pox_constants: &PoxConstants,
+    ) {
+        let interested_observers =
+            self.filter_observers(&self.cost_near_limit_observers_lookup, false);
+        if interested_observers.is_empty() {
             return;
         }
 
-        let dropped_txids: Vec<_> = txs
-            .into_iter()
-            .map(|tx| serde_json::Value::String(format!("0x{}", &tx)))
-            .collect();
+        let Some(block_limit) =
+            self.get_epoch_block_limit(metadata.burn_header_height.into(), pox_constants)
+        else {
+            return;
+        };
+        let transactions =
+            find_near_limit_transactions(receipts, &block_limit, self.cost_near_limit_threshold);
+        if transactions.is_empty() {
+            return;
+        }
 
         let payload = json!({
-            "dropped_txids": serde_json::Value::Array(dropped_txids),
-            "reason": reason.to_string(),
+            "block_hash": format!("0x{}", block.block_hash),
+            "index_block_hash": format!("0x{}", metadata.index_block_hash()),
+            "block_height": metadata.stacks_block_height,
+            "block_limit": block_limit,
+            "threshold": self.cost_near_limit_threshold,
+            "transactions": transactions,
         });
-
         for observer in interested_observers.iter() {
-            observer.send_dropped_mempool_txs(&payload);
+            observer.send_cost_near_limit(&payload);
         }
     }
 
-    pub fn process_new_attachments(&self, attachments: &Vec<(AttachmentInstance, Attachment)>) {
-        let interested_observers: Vec<_> = self.registered_observers.iter().enumerate().collect();
-        if interested_observers.len() < 1 {
+    /// Tell interested observers if a newly-processed Nakamoto block competes with blocks
+    /// already processed for the same height in the same tenure, and which of them the
+    /// canonical Stacks chain goes through
+    fn process_block_forks(
+        &self,
+        header: &NakamotoBlockHeader,
+        block_height: u64,
+        pox_constants: &PoxConstants,
+    ) {
+        let interested_observers = self.filter_observers(&self.block_fork_observers_lookup, false);
+        if interested_observers.is_empty() {
             return;
         }
 
-        let mut serialized_attachments = vec![];
-        for attachment in attachments.iter() {
-            let payload = EventObserver::make_new_attachment_payload(attachment);
-            serialized_attachments.push(payload);
+        let block_id = header.block_id();
+        let mut block_forks = self
+            .block_forks
+            .lock()
+            .expect("FATAL: failed to lock block fork tracker");
+        let siblings = block_forks.record(
+            &header.consensus_hash,
+            block_height,
+            &block_id,
+            &header.parent_block_id,
+        );
+        if siblings.is_empty() {
+            return;
         }
 
-        for (_, observer) in interested_observers.iter() {
-            observer.send_new_attachments(&json!(serialized_attachments));
+        let canonical_block_id = self
+            .get_canonical_stacks_tip(pox_constants)
+            .and_then(|(tip, tip_height)| block_forks.ancestor_at(&tip, tip_height, block_height));
+        drop(block_forks);
+
+        for sibling_block_id in siblings.iter() {
+            info!(
+                "Event dispatcher: block competes with an already-processed block";
+                "block_id" => %block_id,
+                "sibling_block_id" => %sibling_block_id,
+                "consensus_hash" => %header.consensus_hash,
+                "block_height" => block_height,
+            );
+            let canonical = canonical_block_id
+                .as_ref()
+                .filter(|canonical| *canonical == &block_id || *canonical == sibling_block_id)
+                .map(|canonical| format!("0x{}", canonical));
+            let payload = json!({
+                "consensus_hash": format!("0x{}", header.consensus_hash),
+                "block_height": block_height,
+                "block_id": format!("0x{}", block_id),
+                "sibling_block_id": format!("0x{}", sibling_block_id),
+                "canonical_block_id": canonical,
+            });
+            for observer in interested_observers.iter() {
+                observer.send_block_fork(&payload);
+            }
         }
     }
 
-    fn update_dispatch_matrix_if_observer_subscribed(
+    /// Tell interested observers if the canonical Stacks chain tip announced by the coordinator
+    /// differs from the last one it announced. The first announcement since the node started
+    /// only records the tip.
+    pub fn process_canonical_tip(&self, tip: &StacksBlockId, cause: TipChangeCause) {
+        let interested_observers = self.filter_observers(&self.tip_changed_observers_lookup, false);
+        if interested_observers.is_empty() {
+            return;
+        }
+
+        let Some(old_tip) = self
+            .canonical_tip
+            .lock()
+            .expect("FATAL: failed to lock canonical tip")
+            .replace(tip.clone())
+        else {
+            return;
+        };
+        if &old_tip == tip {
+            return;
+        }
+
+        debug!("Event dispatcher: canonical Stacks tip changed";
+               "old_tip" => %old_tip, "new_tip" => %tip, "cause" => %cause);
+        let payload = json!({
+            "old_tip": format!("0x{}", old_tip),
+            "new_tip": format!("0x{}", tip),
+            "cause": cause.to_string(),
+        });
+        for observer in interested_observers.iter() {
+            observer.send_tip_changed(&payload);
+        }
+    }
+
+    /// Tell interested observers that the node has caught up to the chain tip, and is now
+    /// following it, with the heights of its Stacks tip and burnchain tip. The run loop calls
+    /// this every time it finds the node caught up, but only the first call since the node
+    /// started sends anything.
+    pub fn process_node_synced(&self, stacks_tip_height: u64, burn_block_height: u64) {
+        if self.node_synced.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        info!("Event dispatcher: node caught up to the chain tip";
+              "stacks_tip_height" => stacks_tip_height, "burn_block_height" => burn_block_height);
+        let interested_observers = self.filter_observers(&self.lifecycle_observers_lookup, false);
+        if interested_observers.is_empty() {
+            return;
+        }
+
+        let payload = json!({
+            "stacks_tip_height": stacks_tip_height,
+            "burn_block_height": burn_block_height,
+        });
+        for observer in interested_observers.iter() {
+            observer.send_node_synced(&payload);
+        }
+    }
+
+    /// Whether `node_synced` was already sent, because the node caught up to the chain tip
+    pub fn is_node_synced(&self) -> bool {
+        self.node_synced.load(Ordering::SeqCst)
+    }
+
+    /// Record that the node already caught up to the chain tip, under another run loop, so
+    /// that `node_synced` isn't sent again
+    pub fn set_node_synced(&self) {
+        self.node_synced.store(true, Ordering::SeqCst);
+    }
+
+    /// Look up the canonical Stacks tip and its height in the sortition DB. Returns `None`
+    /// (after logging why) if there is no sortition DB or the lookup fails.
+    fn get_canonical_stacks_tip(
         &self,
-        asset_identifier: &AssetIdentifier,
-        event_index: usize,
-        dispatch_matrix: &mut Vec<HashSet<usize>>,
+        pox_constants: &PoxConstants,
+    ) -> Option<(StacksBlockId, u64)> {
+        let Some(sortdb_path) = self.sortdb_path.as_ref() else {
+            warn!("Event dispatcher: no sortition DB to look up the canonical Stacks tip in");
+            return None;
+        };
+        let sortdb = SortitionDB::open(sortdb_path, false, pox_constants.clone())
+            .map_err(|e| {
+                warn!("Event dispatcher: failed to open sortition DB"; "error" => ?e);
+            })
+            .ok()?;
+        let (consensus_hash, block_hash, height) =
+            SortitionDB::get_canonical_stacks_chain_tip_hash_and_height(sortdb.conn())
+                .map_err(|e| {
+                    warn!("Event dispatcher: failed to look up the canonical Stacks tip"; "error" => ?e);
+                })
+                .ok()?;
+        Some((StacksBlockId::new(&consensus_hash, &block_hash), height))
+    }
+
+    /// Send a newly-processed block's coinbase and matured miner rewards to interested observers.
+    /// The coinbase's recipient is its alternative recipient if it has one, and otherwise the
+    /// miner that sent it.
+    fn process_new_rewards(
+        &self,
+        block: &StacksBlockEventData,
+        metadata: &StacksHeaderInfo,
+        receipts: &[StacksTransactionReceipt],
+        mature_rewards: &serde_json::Value,
     ) {
-        if let Some(observer_indexes) = self.assets_observers_lookup.get(asset_identifier) {
-            for o_i in observer_indexes {
-                dispatch_matrix[*o_i as usize].insert(event_index);
+        let interested_observers = self.filter_observers(&self.rewards_observers_lookup, false);
+        if interested_observers.is_empty() {
+            return;
+        }
+
+        let coinbase = receipts.iter().find_map(|receipt| {
+            let TransactionOrigin::Stacks(tx) = &receipt.transaction else {
+                return None;
+            };
+            let (_, recipient_opt, _) = tx.try_as_coinbase()?;
+            let miner_address = tx.origin_address().to_account_principal();
+            Some(json!({
+                "txid": format!("0x{}", tx.txid()),
+                "miner_address": miner_address.to_string(),
+                "recipient": recipient_opt.unwrap_or(&miner_address).to_string(),
+            }))
+        });
+
+        let payload = json!({
+            "block_hash": format!("0x{}", block.block_hash),
+            "index_block_hash": format!("0x{}", metadata.index_block_hash()),
+            "block_height": metadata.stacks_block_height,
+            "burn_block_height": metadata.burn_header_height,
+            "coinbase": coinbase,
+            "matured_miner_rewards": mature_rewards,
+        });
+        for observer in interested_observers.iter() {
+            observer.send_new_rewards(&payload);
+        }
+    }
+
+    /// Tally the signers of a newly-processed Nakamoto block, and send the previous tenure's
+    /// signer participation summary to interested observers if this block starts a new tenure.
+    fn process_signer_participation(
+        &self,
+        header: &NakamotoBlockHeader,
+        block_height: u64,
+        reward_set_data: &Option<RewardSetData>,
+    ) {
+        let interested_observers =
+            self.filter_observers(&self.signer_participation_observers_lookup, false);
+        if interested_observers.is_empty() {
+            return;
+        }
+
+        let Some(summary) = self
+            .signer_participation
+            .lock()
+            .expect("FATAL: failed to lock signer participation tracker")
+            .record_block(header, block_height, reward_set_data)
+        else {
+            return;
+        };
+
+        let payload = serde_json::to_value(&summary)
+            .expect("FATAL: failed to serialize signer participation summary to JSON");
+        for observer in interested_observers.iter() {
+            observer.send_signer_participation(&payload);
+        }
+    }
+
+    /// Creates a list of observers that are interested in the new microblocks event,
+    /// creates a mapping from observers to the event ids that are relevant to each, and then
+    /// sends the event to each interested observer.
+    pub fn process_new_microblocks(
+        &self,
+        parent_index_block_hash: StacksBlockId,
+        processed_unconfirmed_state: ProcessedUnconfirmedState,
+    ) {
+        self.process_microblock_tip(&parent_index_block_hash, &processed_unconfirmed_state);
+
+        // lazily assemble payload only if we have observers
+        let interested_observers: Vec<_> = self
+            .registered_observers
+            .iter()
+            .enumerate()
+            .filter(|(obs_id, _observer)| {
+                self.microblock_observers_lookup
+                    .contains(&(u16::try_from(*obs_id).expect("FATAL: more than 2^16 observers")))
+                    || self.any_event_observers_lookup.contains(
+                        &(u16::try_from(*obs_id).expect("FATAL: more than 2^16 observers")),
+                    )
+            })
+            .collect();
+        if interested_observers.len() < 1 {
+            return;
+        }
+        // the microblocks' receipts are borrowed, and only the events that some interested
+        // observer receives are collected from them
+        let receipts = || {
+            processed_unconfirmed_state
+                .receipts
+                .iter()
+                .flat_map(|(_, _, receipts)| receipts.iter())
+        };
+        let dispatch_matrix = self.create_dispatch_matrix(receipts(), None);
+        let event_ids: BTreeSet<usize> = interested_observers
+            .iter()
+            .flat_map(|(obs_id, _)| dispatch_matrix[*obs_id].iter())
+            .copied()
+            .collect();
+        let events: BTreeMap<usize, (bool, Txid, &StacksTransactionEvent)> =
+            Self::receipt_events(receipts())
+                .enumerate()
+                .filter(|(event_id, _)| event_ids.contains(event_id))
+                .collect();
+
+        // Serialize receipts
+        let mut tx_index;
+        let mut serialized_txs = Vec::new();
+
+        for (_, _, receipts) in processed_unconfirmed_state.receipts.iter() {
+            tx_index = 0;
+            for receipt in receipts.iter() {
+                let payload = EventObserver::make_new_block_txs_payload(receipt, tx_index);
+                serialized_txs.push(payload);
+                tx_index += 1;
             }
         }
+
+        for (obs_id, observer) in interested_observers.iter() {
+            let filtered_events_ids = &dispatch_matrix[*obs_id];
+            let filtered_events: Vec<_> = filtered_events_ids
+                .iter()
+                .map(|event_id| (*event_id, &events[event_id]))
+                .collect();
+
+            observer.send_new_microblocks(
+                parent_index_block_hash,
+                filtered_events,
+                &serialized_txs,
+                processed_unconfirmed_state.burn_block_hash,
+                processed_unconfirmed_state.burn_block_height,
+                processed_unconfirmed_state.burn_block_timestamp,
+            );
+        }
+    }
+
+    /// Send the observers subscribed to `microblock_tip` the latest microblock of the stream
+    /// that builds on `parent_index_block_hash`, without any of the stream's transactions or
+    /// events
+    fn process_microblock_tip(
+        &self,
+        parent_index_block_hash: &StacksBlockId,
+        processed_unconfirmed_state: &ProcessedUnconfirmedState,
+    ) {
+        let interested_observers =
+            self.filter_observers(&self.microblock_tip_observers_lookup, false);
+        if interested_observers.is_empty() {
+            return;
+        }
+        let Some((sequence, header, _)) = processed_unconfirmed_state
+            .receipts
+            .iter()
+            .max_by_key(|(sequence, ..)| *sequence)
+        else {
+            return;
+        };
+
+        let payload = json!({
+            "parent_index_block_hash": format!("0x{}", parent_index_block_hash),
+            "microblock_sequence": sequence,
+            "microblock_hash": format!("0x{}", header.block_hash()),
+        });
+        for observer in interested_observers.iter() {
+            observer.send_microblock_tip(&payload);
+        }
+    }
+
+    fn filter_observers(&self, lookup: &BTreeSet<u16>, include_any: bool) -> Vec<&EventObserver> {
+        self.registered_observers
+            .iter()
+            .enumerate()
+            .filter_map(|(obs_id, observer)| {
+                let lookup_ix = u16::try_from(obs_id).expect("FATAL: more than 2^16 observers");
+                if lookup.contains(&lookup_ix) {
+                    return Some(observer);
+                } else if include_any && self.any_event_observers_lookup.contains(&lookup_ix) {
+                    return Some(observer);
+                } else {
+                    return None;
+                }
+            })
+            .collect()
+    }
+
+    pub fn process_new_mempool_txs(&self, txs: Vec<StacksTransaction>) {
+        // lazily assemble payload only if we have observers
+        let interested_observers = self.filter_observers(&self.mempool_observers_lookup, true);
+
+        if interested_observers.len() < 1 {
+            return;
+        }
+
+        let payload = EventObserver::make_new_mempool_txs_payload(txs);
+
+        for observer in interested_observers.iter() {
+            observer.send_new_mempool_txs(&payload);
+        }
+    }
+
+    pub fn process_mined_block_event(
+        &self,
+        target_burn_height: u64,
+        block: &StacksBlock,
+        block_size_bytes: u64,
+        consumed: &ExecutionCost,
+        confirmed_microblock_cost: &ExecutionCost,
+        tx_events: Vec<TransactionEvent>,
+    ) {
+        let interested_observers = self.filter_observers(&self.miner_observers_lookup, false);
+
+        if interested_observers.len() < 1 && self.mined_block_archive.is_none() {
+            return;
+        }
+
+        let payload = serde_json::to_value(MinedBlockEvent {
+            target_burn_height,
+            block_hash: block.block_hash().to_string(),
+            stacks_height: block.header.total_work.work,
+            block_size: block_size_bytes,
+            anchored_cost: consumed.clone(),
+            confirmed_microblocks_cost: confirmed_microblock_cost.clone(),
+            tx_events,
+        })
+        .unwrap();
+
+        self.archive_mined_block(PATH_MINED_BLOCK, target_burn_height, &payload);
+        for observer in interested_observers.iter() {
+            observer.send_mined_block(&payload);
+        }
+    }
+
+    pub fn process_mined_microblock_event(
+        &self,
+        microblock: &StacksMicroblock,
+        tx_events: Vec<TransactionEvent>,
+        anchor_block_consensus_hash: ConsensusHash,
+        anchor_block: BlockHeaderHash,
+    ) {
+        let interested_observers =
+            self.filter_observers(&self.mined_microblocks_observers_lookup, false);
+        if interested_observers.len() < 1 {
+            return;
+        }
+
+        let payload = serde_json::to_value(MinedMicroblockEvent {
+            block_hash: microblock.block_hash().to_string(),
+            sequence: microblock.header.sequence,
+            tx_events,
+            anchor_block_consensus_hash,
+            anchor_block,
+        })
+        .unwrap();
+
+        for observer in interested_observers.iter() {
+            observer.send_mined_microblock(&payload);
+        }
+    }
+
+    pub fn process_mined_nakamoto_block_event(
+        &self,
+        target_burn_height: u64,
+        block: &NakamotoBlock,
+        block_size_bytes: u64,
+        consumed: &ExecutionCost,
+        tx_events: Vec<TransactionEvent>,
+    ) {
+        let interested_observers = self.filter_observers(&self.miner_observers_lookup, false);
+        if interested_observers.len() < 1 && self.mined_block_archive.is_none() {
+            return;
+        }
+
+        let signer_bitvec = serde_json::to_value(block.header.pox_treatment.clone())
+            .unwrap_or_default()
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+
+        let payload = serde_json::to_value(MinedNakamotoBlockEvent {
+            target_burn_height,
+            parent_block_id: block.header.parent_block_id.to_string(),
+            block_hash: block.header.block_hash().to_string(),
+            block_id: block.header.block_id().to_string(),
+            stacks_height: block.header.chain_length,
+            block_size: block_size_bytes,
+            cost: consumed.clone(),
+            tx_events,
+            miner_signature: block.header.miner_signature.clone(),
+            signer_signature_hash: block.header.signer_signature_hash(),
+            signer_signature: block.header.signer_signature.clone(),
+            signer_bitvec,
+        })
+        .unwrap();
+
+        self.archive_mined_block(PATH_MINED_NAKAMOTO_BLOCK, target_burn_height, &payload);
+        for observer in interested_observers.iter() {
+            observer.send_mined_nakamoto_block(&payload);
+        }
+    }
+
+    /// Forward newly-accepted StackerDB chunk metadata to downstream `stackerdb` observers.
+    /// Infallible.
+    pub fn process_new_stackerdb_chunks(
+        &self,
+        contract_id: QualifiedContractIdentifier,
+        modified_slots: Vec<StackerDBChunkData>,
+    ) {
+        debug!(
+            "event_dispatcher: New StackerDB chunk events for {}: {:?}",
+            contract_id, modified_slots
+        );
+
+        if !self.stackerdb_equivocation_observers_lookup.is_empty() {
+            let mut tracker = self
+                .stackerdb_equivocation
+                .lock()
+                .expect("FATAL: failed to lock StackerDB equivocation tracker");
+            for chunk in modified_slots.iter() {
+                tracker.record_accepted(&contract_id, chunk);
+            }
+        }
+
+        let interested_observers = self.filter_observers(&self.stackerdb_observers_lookup, false);
+
+        let interested_receiver = STACKER_DB_CHANNEL.is_active(&contract_id);
+        if interested_observers.is_empty() && interested_receiver.is_none() {
+            return;
+        }
+
+        let event = StackerDBChunksEvent {
+            contract_id: contract_id.clone(),
+            modified_slots,
+        };
+        let payload = serde_json::to_value(&event)
+            .expect("FATAL: failed to serialize StackerDBChunksEvent to JSON");
+
+        // the miner's channel always gets each event as it arrives, even if observers batch them
+        if let Some(channel) = interested_receiver {
+            if let Err(SendError(event)) = channel.send(event) {
+                if STACKER_DB_CHANNEL.deactivate_if_disconnected(event) {
+                    warn!(
+                        "Failed to send StackerDB event to signer coordinator channel. Miner thread may have exited. Deactivating the channel."
+                    );
+                }
+            }
+        }
+
+        for observer in interested_observers.iter() {
+            observer.send_or_batch_stackerdb_chunks(&contract_id, &payload);
+        }
+    }
+
+    /// Tell interested observers about StackerDB chunks that were refused because a different
+    /// chunk was already stored at the same slot version, i.e. that the slot's signer signed two
+    /// different chunks for that version. Only chunks stored since the node started, and recently
+    /// enough to still be tracked, can be checked.
+    pub fn process_stale_stackerdb_chunks(
+        &self,
+        contract_id: QualifiedContractIdentifier,
+        stale_chunks: Vec<StackerDBChunkData>,
+    ) {
+        let interested_observers =
+            self.filter_observers(&self.stackerdb_equivocation_observers_lookup, false);
+        if interested_observers.is_empty() {
+            return;
+        }
+
+        let tracker = self
+            .stackerdb_equivocation
+            .lock()
+            .expect("FATAL: failed to lock StackerDB equivocation tracker");
+        let equivocations: Vec<_> = stale_chunks
+            .into_iter()
+            .filter_map(|chunk| {
+                let accepted = tracker.check_refused(&contract_id, &chunk)?;
+                Some((chunk, accepted))
+            })
+            .collect();
+        drop(tracker);
+
+        for (chunk, accepted) in equivocations.into_iter() {
+            warn!(
+                "Event dispatcher: StackerDB slot signer signed two chunks for the same version";
+                "contract_id" => %contract_id,
+                "slot_id" => chunk.slot_id,
+                "slot_version" => chunk.slot_version,
+            );
+            let payload = json!({
+                "contract_id": contract_id.to_string(),
+                "slot_id": chunk.slot_id,
+                "slot_version": chunk.slot_version,
+                "stored_chunk": {
+                    "data_hash": accepted.data_hash.to_hex(),
+                    "signature": accepted.sig,
+                },
+                "conflicting_chunk": {
+                    "slot_id": chunk.slot_id,
+                    "slot_version": chunk.slot_version,
+                    "signature": chunk.sig,
+                    "data": to_hex(&chunk.data),
+                    "data_hash": Sha512Trunc256Sum::from_data(&chunk.data).to_hex(),
+                },
+            });
+            for observer in interested_observers.iter() {
+                observer.send_stackerdb_equivocation(&payload);
+            }
+        }
+    }
+
+    pub fn process_dropped_mempool_txs(
+        &self,
+        txs: Vec<Txid>,
+        reason: MemPoolDropReason,
+        fee_rates: Option<MemPoolFeeRatePercentiles>,
+    ) {
+        // drops are remembered whether or not anyone is listening, so that they can be looked
+        // into later
+        self.mempool_drop_history
+            .lock()
+            .expect("FATAL: failed to lock mempool drop history")
+            .record(&txs, &reason, get_epoch_time_secs());
+
+        // lazily assemble payload only if we have observers
+        let interested_observers = self.filter_observers(&self.mempool_observers_lookup, true);
+
+        if interested_observers.len() < 1 {
+            return;
+        }
+
+        let dropped_txids: Vec<_> = txs
+            .into_iter()
+            .map(|tx| serde_json::Value::String(format!("0x{}", &tx)))
+            .collect();
+
+        let payload = json!({
+            "dropped_txids": serde_json::Value::Array(dropped_txids),
+            "reason": reason.to_string(),
+        });
+        // observers that asked for the mempool's fee rates get them alongside the drop
+        let payload_with_fee_rates = fee_rates.map(|fee_rates| {
+            let mut payload = payload.clone();
+            payload["fee_rates"] = json!(fee_rates);
+            payload
+        });
+
+        for observer in interested_observers.iter() {
+            match payload_with_fee_rates.as_ref() {
+                Some(payload_with_fee_rates) if observer.include_drop_fee_rates => {
+                    observer.send_dropped_mempool_txs(payload_with_fee_rates)
+                }
+                _ => observer.send_dropped_mempool_txs(&payload),
+            }
+        }
+    }
+
+    pub fn process_new_attachments(&self, attachments: &Vec<(AttachmentInstance, Attachment)>) {
+        let interested_observers: Vec<_> = self.registered_observers.iter().enumerate().collect();
+        if interested_observers.len() < 1 {
+            return;
+        }
+
+        let mut serialized_attachments = vec![];
+        for attachment in attachments.iter() {
+            let payload = EventObserver::make_new_attachment_payload(attachment);
+            serialized_attachments.push(payload);
+        }
+
+        for (_, observer) in interested_observers.iter() {
+            observer.send_new_attachments(&json!(serialized_attachments));
+        }
+    }
+
+    fn update_dispatch_matrix_if_observer_subscribed(
+        &self,
+        asset_identifier: &AssetIdentifier,
+        event_index: usize,
+        dispatch_matrix: &mut Vec<BTreeSet<usize>>,
+    ) {
+        if let Some(observer_indexes) = self.assets_observers_lookup.get(asset_identifier) {
+            for o_i in observer_indexes {
+                dispatch_matrix[*o_i as usize].insert(event_index);
+            }
+        }
+        self.update_dispatch_matrix_if_deployer_subscribed(
+            &asset_identifier.contract_identifier,
+            event_index,
+            dispatch_matrix,
+        );
+    }
+
+    /// Assign an event of the contract `contract_identifier` to the observers of its deployer,
+    /// and to the observers of boot contracts if it is one
+    fn update_dispatch_matrix_if_deployer_subscribed(
+        &self,
+        contract_identifier: &QualifiedContractIdentifier,
+        event_index: usize,
+        dispatch_matrix: &mut Vec<BTreeSet<usize>>,
+    ) {
+        if let Some(observer_indexes) = self
+            .deployer_observers_lookup
+            .get(&contract_identifier.issuer)
+        {
+            for o_i in observer_indexes {
+                dispatch_matrix[*o_i as usize].insert(event_index);
+            }
+        }
+        if contract_identifier.is_boot() {
+            for o_i in &self.boot_contract_observers_lookup {
+                dispatch_matrix[*o_i as usize].insert(event_index);
+            }
+        }
+    }
+
+    /// The endpoints of the observers that would receive an event matching `event_key`, in
+    /// registration order. This only consults the subscription lookups, so nothing is sent.
+    /// Contract and asset events also go to the observers of the contract's deployer (and of boot
+    /// contracts, if it is one), and every
+    /// topic that `*` covers also goes to the observers subscribed to all events.
+    pub fn observers_for(&self, event_key: &EventKeyType) -> Vec<String> {
+        let empty = BTreeSet::new();
+        let contract_lookup;
+        let (lookup, include_any) = match event_key {
+            EventKeyType::SmartContractEvent(event_key) => {
+                contract_lookup = self.with_deployer_observers(
+                    self.contract_events_observers_lookup.get(event_key),
+                    &event_key.0,
+                );
+                (&contract_lookup, true)
+            }
+            EventKeyType::AssetEvent(asset_identifier) => {
+                contract_lookup = self.with_deployer_observers(
+                    self.assets_observers_lookup.get(asset_identifier),
+                    &asset_identifier.contract_identifier,
+                );
+                (&contract_lookup, true)
+            }
+            EventKeyType::DeployerEvent(deployer) => (
+                self.deployer_observers_lookup
+                    .get(deployer)
+                    .unwrap_or(&empty),
+                true,
+            ),
+            EventKeyType::BootContractEvent => (&self.boot_contract_observers_lookup, true),
+            EventKeyType::STXEvent => (&self.stx_observers_lookup, true),
+            EventKeyType::MemPoolTransactions => (&self.mempool_observers_lookup, true),
+            EventKeyType::Microblocks => (&self.microblock_observers_lookup, true),
+            EventKeyType::MicroblockTip => (&self.microblock_tip_observers_lookup, false),
+            EventKeyType::AnyEvent => (&empty, true),
+            EventKeyType::BurnchainBlocks => (&self.burn_block_observers_lookup, true),
+            EventKeyType::MinedBlocks => (&self.miner_observers_lookup, false),
+            EventKeyType::MinedMicroblocks => (&self.mined_microblocks_observers_lookup, false),
+            EventKeyType::StackerDBChunks => (&self.stackerdb_observers_lookup, false),
+            EventKeyType::BlockProposal => (&self.block_proposal_observers_lookup, false),
+            EventKeyType::SignerParticipation => {
+                (&self.signer_participation_observers_lookup, false)
+            }
+            EventKeyType::StackerSetDiff => (&self.stacker_set_diff_observers_lookup, false),
+            EventKeyType::CostNearLimit => (&self.cost_near_limit_observers_lookup, false),
+            EventKeyType::Rewards => (&self.rewards_observers_lookup, false),
+            EventKeyType::BurnBlockReorg => (&self.burn_block_reorg_observers_lookup, false),
+            EventKeyType::BlockFork => (&self.block_fork_observers_lookup, false),
+            EventKeyType::TipChanged => (&self.tip_changed_observers_lookup, false),
+            EventKeyType::StackerDBEquivocation => {
+                (&self.stackerdb_equivocation_observers_lookup, false)
+            }
+            EventKeyType::Lifecycle => (&self.lifecycle_observers_lookup, false),
+        };
+        self.filter_observers(lookup, include_any)
+            .into_iter()
+            .map(|observer| observer.endpoint.clone())
+            .collect()
+    }
+
+    /// `lookup`, plus the observers of the deployer of `contract_identifier`, and the observers
+    /// of boot contracts if it is one
+    fn with_deployer_observers(
+        &self,
+        lookup: Option<&BTreeSet<u16>>,
+        contract_identifier: &QualifiedContractIdentifier,
+    ) -> BTreeSet<u16> {
+        let mut lookup = lookup.cloned().unwrap_or_default();
+        if let Some(deployer_observers) = self
+            .deployer_observers_lookup
+            .get(&contract_identifier.issuer)
+        {
+            lookup.extend(deployer_observers);
+        }
+        if contract_identifier.is_boot() {
+            lookup.extend(&self.boot_contract_observers_lookup);
+        }
+        lookup
+    }
+
+    /// Report how event delivery to each registered observer is doing, in registration order
+    pub fn event_delivery_health(&self) -> RPCEventHealthData {
+        RPCEventHealthData::new(
+            self.registered_observers
+                .iter()
+                .map(EventObserver::delivery_health)
+                .collect(),
+        )
+    }
+
+    /// Register an event observer. Fails if as many observers as the cap set by
+    /// `set_max_observers()` are already registered. The payloads left pending for the observer
+    /// in the pending-payload database, if any, are sent first, in the order they were produced.
+    /// An observer with `send_startup_event` is then sent a `new_observer` event.
+    pub fn register_observer(
+        &mut self,
+        conf: &EventObserverConfig,
+        working_dir: PathBuf,
+    ) -> Result<(), String> {
+        if self.registered_observers.len() >= self.max_observers {
+            return Err(format!(
+                "Cannot register event observer {}: at most {} event observers may be registered",
+                redact_endpoint(&conf.endpoint),
+                self.max_observers
+            ));
+        }
+        let event_observer = self.make_observer(conf, working_dir)?;
+        self.add_observer(conf, event_observer, conf.send_startup_event);
+        Ok(())
+    }
+
+    /// Replace every registered observer with the observers of `confs`, as if each had been
+    /// passed to `register_observer()` in turn. All of the new observers are set up before any
+    /// of them is swapped in, so if one of `confs` is invalid, or there are more of them than
+    /// the cap set by `set_max_observers()`, this fails and leaves the registered observers as
+    /// they were. Since this takes `&mut self`, no event is dispatched while the observers are
+    /// swapped: each event goes either to the old observers or to the new ones.
+    ///
+    /// An observer whose config is unchanged is kept as it is, along with its pending payloads,
+    /// its delivery queue and its delivery health, and it isn't sent another `new_observer`
+    /// event. The delivery threads of the observers that are dropped finish delivering what's
+    /// in their queues before they exit.
+    pub fn replace_observers(
+        &mut self,
+        confs: &[EventObserverConfig],
+        working_dir: PathBuf,
+    ) -> Result<(), String> {
+        if confs.len() > self.max_observers {
+            return Err(format!(
+                "Cannot replace event observers: at most {} event observers may be registered, but {} were given",
+                self.max_observers,
+                confs.len()
+            ));
+        }
+        let mut old_observers: Vec<_> = self
+            .registered_observer_configs
+            .iter()
+            .zip(self.registered_observers.iter())
+            .map(Some)
+            .collect();
+        let mut observers = Vec::with_capacity(confs.len());
+        for conf in confs.iter() {
+            let kept_observer = old_observers
+                .iter_mut()
+                .find(|old| old.is_some_and(|(old_conf, _)| old_conf == conf))
+                .and_then(Option::take)
+                .map(|(_, old_observer)| old_observer.clone());
+            let (observer, is_new) = match kept_observer {
+                Some(observer) => (observer, false),
+                None => (self.make_observer(conf, working_dir.clone())?, true),
+            };
+            observers.push((conf, observer, is_new));
+        }
+
+        // build the new lookups aside, then swap them all in at once
+        let mut replacement = EventDispatcher::new();
+        replacement.node_identity = self.node_identity.clone();
+        for (conf, observer, is_new) in observers.into_iter() {
+            replacement.add_observer(conf, observer, is_new && conf.send_startup_event);
+        }
+        info!(
+            "Event dispatcher: replaced event observers";
+            "old_observers" => self.registered_observers.len(),
+            "new_observers" => replacement.registered_observers.len()
+        );
+        self.registered_observers = replacement.registered_observers;
+        self.registered_observer_configs = replacement.registered_observer_configs;
+        self.contract_events_observers_lookup = replacement.contract_events_observers_lookup;
+        self.assets_observers_lookup = replacement.assets_observers_lookup;
+        self.deployer_observers_lookup = replacement.deployer_observers_lookup;
+        self.boot_contract_observers_lookup = replacement.boot_contract_observers_lookup;
+        self.burn_block_observers_lookup = replacement.burn_block_observers_lookup;
+        self.mempool_observers_lookup = replacement.mempool_observers_lookup;
+        self.microblock_observers_lookup = replacement.microblock_observers_lookup;
+        self.microblock_tip_observers_lookup = replacement.microblock_tip_observers_lookup;
+        self.stx_observers_lookup = replacement.stx_observers_lookup;
+        self.any_event_observers_lookup = replacement.any_event_observers_lookup;
+        self.miner_observers_lookup = replacement.miner_observers_lookup;
+        self.mined_microblocks_observers_lookup = replacement.mined_microblocks_observers_lookup;
+        self.stackerdb_observers_lookup = replacement.stackerdb_observers_lookup;
+        self.block_proposal_observers_lookup = replacement.block_proposal_observers_lookup;
+        self.signer_participation_observers_lookup =
+            replacement.signer_participation_observers_lookup;
+        self.stacker_set_diff_observers_lookup = replacement.stacker_set_diff_observers_lookup;
+        self.cost_near_limit_observers_lookup = replacement.cost_near_limit_observers_lookup;
+        self.rewards_observers_lookup = replacement.rewards_observers_lookup;
+        self.burn_block_reorg_observers_lookup = replacement.burn_block_reorg_observers_lookup;
+        self.block_fork_observers_lookup = replacement.block_fork_observers_lookup;
+        self.tip_changed_observers_lookup = replacement.tip_changed_observers_lookup;
+        self.stackerdb_equivocation_observers_lookup =
+            replacement.stackerdb_equivocation_observers_lookup;
+        self.lifecycle_observers_lookup = replacement.lifecycle_observers_lookup;
+        // the last block's payloads are kept by observer index, which no longer lines up
+        self.last_block_payloads
+            .lock()
+            .expect("FATAL: failed to lock last block payloads")
+            .clear();
+        Ok(())
+    }
+
+    /// Set up the observer of `conf`, without registering it
+    fn make_observer(
+        &self,
+        conf: &EventObserverConfig,
+        working_dir: PathBuf,
+    ) -> Result<EventObserver, String> {
+        info!("Registering event observer at: {}", conf.endpoint);
+        let is_stdout = conf.endpoint == STDOUT_ENDPOINT;
+        let is_redis = is_redis_endpoint(&conf.endpoint);
+        let host_limit = if is_stdout || is_redis {
+            None
+        } else {
+            let url = format!("{}/", observer_base_url(&conf.endpoint));
+            let (_, host, _) = parse_observer_url(&url).map_err(|e| {
+                format!(
+                    "Cannot register event observer {}: invalid endpoint: {}",
+                    redact_endpoint(&conf.endpoint),
+                    e
+                )
+            })?;
+            Some(self.host_delivery_limits.limit_for(&host))
+        };
+        if conf.strict_block_ordering && conf.parallel_connections > 1 {
+            return Err(format!(
+                "Cannot register event observer {}: strict_block_ordering cannot be used with parallel_connections",
+                redact_endpoint(&conf.endpoint)
+            ));
+        }
+        let is_https = is_https_endpoint(&conf.endpoint);
+        if is_https && conf.http2 {
+            // the HTTP/2 client only speaks cleartext HTTP/2
+            return Err(format!(
+                "Cannot register event observer {}: http2 cannot be used with an https:// endpoint",
+                redact_endpoint(&conf.endpoint)
+            ));
+        }
+        let tls = make_observer_tls_config(conf).map_err(|e| {
+            format!(
+                "Cannot register event observer {}: {}",
+                redact_endpoint(&conf.endpoint),
+                e
+            )
+        })?;
+        if conf.tls_skip_verify {
+            warn!(
+                "Event observer {}: its TLS certificate will not be verified. This is meant for testing only.",
+                redact_endpoint(&conf.endpoint)
+            );
+        }
+        let http2 = if conf.http2 && !is_stdout && !is_redis {
+            let client = Http2Client::new().map_err(|e| {
+                format!(
+                    "Cannot register event observer {}: {}",
+                    redact_endpoint(&conf.endpoint),
+                    e
+                )
+            })?;
+            Some(Arc::new(client))
+        } else {
+            None
+        };
+        let delivery_policy = DeliveryPolicy::from_config(&conf.delivery_policy).map_err(|e| {
+            format!(
+                "Cannot register event observer {}: {}",
+                redact_endpoint(&conf.endpoint),
+                e
+            )
+        })?;
+        let wire_capture = conf
+            .wire_capture
+            .as_deref()
+            .map(WireCapture::parse)
+            .transpose()
+            .map_err(|e| {
+                format!(
+                    "Cannot register event observer {}: {}",
+                    redact_endpoint(&conf.endpoint),
+                    e
+                )
+            })?;
+        let headers = StaticHeaders::parse(&conf.headers).map_err(|e| {
+            format!(
+                "Cannot register event observer {}: {}",
+                redact_endpoint(&conf.endpoint),
+                e
+            )
+        })?;
+        let delivery_queue = conf
+            .delivery_queue
+            .as_ref()
+            .map(DeliveryQueue::from_config)
+            .transpose()
+            .map_err(|e| {
+                format!(
+                    "Cannot register event observer {}: {}",
+                    redact_endpoint(&conf.endpoint),
+                    e
+                )
+            })?;
+        if is_stdout {
+            // nothing is retried or persisted, so there's no need for the pending-payload DB
+            warn!("Event observer {} is active: every event it subscribes to is written to stdout. This is meant for development only.", STDOUT_ENDPOINT);
+        }
+        let direct_transport = if is_stdout {
+            Some(FallbackTransport::Stdout)
+        } else if is_redis {
+            // the stream is retried until Redis takes each entry, so it needs no pending-payload DB either
+            let stream = RedisStream::new(
+                &conf.endpoint,
+                conf.redis_stream_prefix
+                    .as_deref()
+                    .unwrap_or(DEFAULT_REDIS_STREAM_PREFIX),
+                Duration::from_millis(conf.timeout_ms),
+            )
+            .map_err(|e| {
+                format!(
+                    "Cannot register event observer {}: {}",
+                    redact_endpoint(&conf.endpoint),
+                    e
+                )
+            })?;
+            Some(FallbackTransport::Redis(Arc::new(stream)))
+        } else {
+            None
+        };
+        let event_observer = EventObserver::new(
+            direct_transport.is_none().then_some(working_dir),
+            conf.endpoint.clone(),
+            Duration::from_millis(conf.timeout_ms),
+        )
+        .with_parallel_connections(conf.parallel_connections as usize)
+        .with_winner_commit(conf.include_winner_commit)
+        .with_sortition_info(conf.include_sortition_info)
+        .with_miner_signature_valid(conf.include_miner_signature_valid)
+        .with_stx_supply_delta(conf.include_stx_supply_delta)
+        .with_raw_header(conf.include_raw_header)
+        .with_raw_burnchain_op(conf.include_raw_burnchain_op)
+        .with_split_array_events(conf.split_array_events)
+        .with_stringify_large_ints(conf.stringify_large_ints)
+        .with_flatten_execution_costs(conf.flatten_execution_costs)
+        .with_drop_fee_rates(conf.include_drop_fee_rates)
+        .with_block_header_event(conf.include_block_header_event)
+        .with_block_timings(conf.include_block_timings)
+        .with_payload_envelope(conf.payload_envelope)
+        .with_payload_projection(
+            PayloadProjection::new(&conf.include_fields, &conf.exclude_fields)
+                .expect("FATAL: invalid payload field path in event observer config"),
+        )
+        .with_max_payload_bytes(conf.max_payload_bytes)
+        .with_delivery_policy(delivery_policy)
+        .with_wire_capture(wire_capture)
+        .with_tls(tls)
+        .with_headers(headers)
+        .with_fallback(conf.fallback_transport.as_ref().map(|transport| {
+            ObserverFallback::new(
+                FallbackTransport::parse(transport)
+                    .expect("FATAL: invalid fallback transport in event observer config"),
+                conf.fallback_after_attempts
+                    .unwrap_or(DEFAULT_FALLBACK_AFTER_ATTEMPTS),
+            )
+        }))
+        .with_success_body_marker(conf.success_body_marker.clone())
+        .with_direct_transport(direct_transport)
+        .with_http2(http2)
+        .with_strict_block_ordering(conf.strict_block_ordering, self.block_retry_budget)
+        .with_stackerdb_batch_window(
+            conf.stackerdb_batch_window_ms
+                .filter(|window_ms| *window_ms > 0)
+                .map(Duration::from_millis),
+        )
+        .with_payload_transform(self.payload_transform.clone())
+        .with_partition(conf.partition.as_ref().map(|partition| {
+            PayloadPartitioner::parse(partition)
+                .expect("FATAL: invalid partition in event observer config")
+        }))
+        .with_host_limit(host_limit)
+        .with_delivery_queue(delivery_queue)
+        .map_err(|e| {
+            format!(
+                "Cannot register event observer {}: {}",
+                redact_endpoint(&conf.endpoint),
+                e
+            )
+        })?;
+        // whatever the observer didn't take before the node last stopped goes ahead of new events
+        event_observer.replay_pending_payloads();
+        Ok(event_observer)
+    }
+
+    /// Route the events that `conf` subscribes to to `event_observer`, and send it a
+    /// `new_observer` event if `send_startup_event` is set
+    fn add_observer(
+        &mut self,
+        conf: &EventObserverConfig,
+        event_observer: EventObserver,
+        send_startup_event: bool,
+    ) {
+        let observer_index = self.registered_observers.len() as u16;
+
+        for event_key_type in conf.events_keys.iter() {
+            match event_key_type {
+                EventKeyType::SmartContractEvent(event_key) => {
+                    match self
+                        .contract_events_observers_lookup
+                        .entry(event_key.clone())
+                    {
+                        Entry::Occupied(observer_indexes) => {
+                            observer_indexes.into_mut().insert(observer_index);
+                        }
+                        Entry::Vacant(v) => {
+                            let mut observer_indexes = BTreeSet::new();
+                            observer_indexes.insert(observer_index);
+                            v.insert(observer_indexes);
+                        }
+                    };
+                }
+                EventKeyType::BurnchainBlocks => {
+                    self.burn_block_observers_lookup.insert(observer_index);
+                }
+                EventKeyType::MemPoolTransactions => {
+                    self.mempool_observers_lookup.insert(observer_index);
+                }
+                EventKeyType::Microblocks => {
+                    self.microblock_observers_lookup.insert(observer_index);
+                }
+                EventKeyType::MicroblockTip => {
+                    self.microblock_tip_observers_lookup.insert(observer_index);
+                }
+                EventKeyType::STXEvent => {
+                    self.stx_observers_lookup.insert(observer_index);
+                }
+                EventKeyType::AssetEvent(event_key) => {
+                    match self.assets_observers_lookup.entry(event_key.clone()) {
+                        Entry::Occupied(observer_indexes) => {
+                            observer_indexes.into_mut().insert(observer_index);
+                        }
+                        Entry::Vacant(v) => {
+                            let mut observer_indexes = BTreeSet::new();
+                            observer_indexes.insert(observer_index);
+                            v.insert(observer_indexes);
+                        }
+                    };
+                }
+                EventKeyType::DeployerEvent(deployer) => {
+                    match self.deployer_observers_lookup.entry(deployer.clone()) {
+                        Entry::Occupied(observer_indexes) => {
+                            observer_indexes.into_mut().insert(observer_index);
+                        }
+                        Entry::Vacant(v) => {
+                            let mut observer_indexes = BTreeSet::new();
+                            observer_indexes.insert(observer_index);
+                            v.insert(observer_indexes);
+                        }
+                    };
+                }
+                EventKeyType::BootContractEvent => {
+                    self.boot_contract_observers_lookup.insert(observer_index);
+                }
+                EventKeyType::AnyEvent => {
+                    self.any_event_observers_lookup.insert(observer_index);
+                }
+                EventKeyType::MinedBlocks => {
+                    self.miner_observers_lookup.insert(observer_index);
+                }
+                EventKeyType::MinedMicroblocks => {
+                    self.mined_microblocks_observers_lookup
+                        .insert(observer_index);
+                }
+                EventKeyType::StackerDBChunks => {
+                    self.stackerdb_observers_lookup.insert(observer_index);
+                }
+                EventKeyType::BlockProposal => {
+                    self.block_proposal_observers_lookup.insert(observer_index);
+                }
+                EventKeyType::SignerParticipation => {
+                    self.signer_participation_observers_lookup
+                        .insert(observer_index);
+                }
+                EventKeyType::StackerSetDiff => {
+                    self.stacker_set_diff_observers_lookup
+                        .insert(observer_index);
+                }
+                EventKeyType::CostNearLimit => {
+                    self.cost_near_limit_observers_lookup.insert(observer_index);
+                }
+                EventKeyType::Rewards => {
+                    self.rewards_observers_lookup.insert(observer_index);
+                }
+                EventKeyType::BurnBlockReorg => {
+                    self.burn_block_reorg_observers_lookup
+                        .insert(observer_index);
+                }
+                EventKeyType::BlockFork => {
+                    self.block_fork_observers_lookup.insert(observer_index);
+                }
+                EventKeyType::TipChanged => {
+                    self.tip_changed_observers_lookup.insert(observer_index);
+                }
+                EventKeyType::StackerDBEquivocation => {
+                    self.stackerdb_equivocation_observers_lookup
+                        .insert(observer_index);
+                }
+                EventKeyType::Lifecycle => {
+                    self.lifecycle_observers_lookup.insert(observer_index);
+                }
+            }
+        }
+
+        if send_startup_event {
+            event_observer
+                .send_startup_event(&make_startup_payload(self.node_identity.as_ref(), conf));
+        }
+        self.registered_observers.push(event_observer);
+        self.registered_observer_configs.push(conf.clone());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeMap, HashSet};
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::Instant;
+
+    use clarity::vm::costs::ExecutionCost;
+    use clarity::vm::events::STXEventType;
+    use stacks::burnchains::{PoxConstants, Txid};
+    use stacks::chainstate::nakamoto::{NakamotoBlock, NakamotoBlockHeader};
+    use stacks::chainstate::stacks::db::{StacksBlockHeaderTypes, StacksHeaderInfo};
+    use stacks::chainstate::stacks::events::StacksBlockEventData;
+    use stacks::chainstate::stacks::StacksBlock;
+    use stacks::net::api::geteventhealth::EventDeliveryState;
+    use stacks::net::api::postblock_proposal::ValidateRejectCode;
+    use stacks::net::tls::test::{
+        make_self_signed_server_config, spawn_test_tls_server, spawn_test_tls_server_with_config,
+        TEST_CA_CERT, TEST_CA_CERT_PEM, TEST_SELF_SIGNED_CERT, TEST_SERVER_CERT,
+    };
+    use stacks::types::chainstate::BlockHeaderHash;
+    use stacks::util::secp256k1::MessageSignature;
+    use stacks::util_lib::boot::boot_code_id;
+    use stacks_common::bitvec::BitVec;
+    use stacks_common::types::chainstate::{BurnchainHeaderHash, StacksBlockId};
+    use stacks_common::util::hash::Sha256Sum;
+    use tempfile::tempdir;
+    use tiny_http::{Method, Response, Server, StatusCode};
+
+    use super::*;
+    use crate::config::{Config, DeliveryPolicyConfig};
+
+    #[test]
+    fn build_block_processed_event() {
+        let observer = EventObserver::new(None, "nowhere".to_string(), Duration::from_secs(3));
+
+        let filtered_events = vec![];
+        let block = StacksBlock::genesis_block();
+        let metadata = StacksHeaderInfo::regtest_genesis();
+        let receipts = vec![];
+        let parent_index_hash = StacksBlockId([0; 32]);
+        let winner_txid = Txid([0; 32]);
+        let mature_rewards = serde_json::Value::Array(vec![]);
+        let parent_burn_block_hash = BurnchainHeaderHash([0; 32]);
+        let parent_burn_block_height = 0;
+        let parent_burn_block_timestamp = 0;
+        let anchored_consumed = ExecutionCost::zero();
+        let mblock_confirmed_consumed = ExecutionCost::zero();
+        let pox_constants = PoxConstants::testnet_default();
+        let signer_bitvec = BitVec::zeros(2).expect("Failed to create BitVec with length 2");
+        let block_timestamp = Some(123456);
+        let coinbase_height = 1234;
+        let processed_at = metadata.burn_header_timestamp + 42;
+
+        let payload = observer.make_new_block_processed_payload(&TestNewBlock {
+            filtered_events,
+            block: block.into(),
+            metadata,
+            receipts: &receipts,
+            parent_index_hash,
+            winner_txid,
+            mature_rewards,
+            parent_burn_block_hash,
+            parent_burn_block_height,
+            parent_burn_block_timestamp,
+            anchored_consumed,
+            mblock_confirmed_consumed,
+            pox_constants: pox_constants.clone(),
+            signer_bitvec: Some(signer_bitvec.clone()),
+            block_timestamp,
+            coinbase_height,
+            processed_at,
+            ..TestNewBlock::default()
+        });
+        assert_eq!(
+            payload
+                .get("pox_v1_unlock_height")
+                .unwrap()
+                .as_u64()
+                .unwrap(),
+            pox_constants.v1_unlock_height as u64
+        );
+        assert_eq!(
+            payload
+                .get("burn_to_block_latency_secs")
+                .unwrap()
+                .as_u64()
+                .unwrap(),
+            42
+        );
+
+        let expected_bitvec_str = serde_json::to_value(signer_bitvec)
+            .unwrap_or_default()
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert_eq!(
+            payload.get("signer_bitvec").unwrap().as_str().unwrap(),
+            expected_bitvec_str
+        );
+    }
+
+    #[test]
+    fn test_block_event_raw_values() {
+        use clarity::vm::events::{NFTMintEventData, SmartContractEventData};
+        use clarity::vm::types::{PrincipalData, TupleData};
+
+        let observer = EventObserver::new(None, "nowhere".to_string(), Duration::from_secs(3));
+        let contract_id = QualifiedContractIdentifier::new(
+            PrincipalData::parse_standard_principal("ST2CY5V39NHDPWSXMW9QDT3HC3GD6Q6XX4CFRK9AG")
+                .unwrap(),
+            "protocol-core".into(),
+        );
+
+        // a tuple holding a list, and a list of tuples, whose JSON forms are easily confused
+        let tuple = Value::from(
+            TupleData::from_data(vec![
+                (
+                    "amounts".into(),
+                    Value::cons_list_unsanitized(vec![Value::UInt(1), Value::UInt(2)]).unwrap(),
+                ),
+                (
+                    "memo".into(),
+                    Value::some(Value::string_ascii_from_bytes(b"hi".to_vec()).unwrap()).unwrap(),
+                ),
+            ])
+            .unwrap(),
+        );
+        let list_of_tuples = Value::cons_list_unsanitized(vec![
+            Value::from(TupleData::from_data(vec![("id".into(), Value::Int(-1))]).unwrap()),
+            Value::from(TupleData::from_data(vec![("id".into(), Value::Int(7))]).unwrap()),
+        ])
+        .unwrap();
+        let events = vec![
+            StacksTransactionEvent::SmartContractEvent(SmartContractEventData {
+                key: (contract_id.clone(), "print".into()),
+                value: tuple.clone(),
+            }),
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTMintEvent(NFTMintEventData {
+                asset_identifier: AssetIdentifier {
+                    contract_identifier: contract_id,
+                    asset_name: "badge".into(),
+                },
+                recipient: PrincipalData::parse("ST2JHG361ZXG51QTKY2NQCVBPPRRE2KZB1HR05NNC")
+                    .unwrap(),
+                value: list_of_tuples.clone(),
+            })),
+        ];
+        let events: Vec<_> = events
+            .iter()
+            .map(|event| (true, Txid([1; 32]), event))
+            .collect();
+
+        let payload = observer.make_new_block_processed_payload(&TestNewBlock {
+            filtered_events: events.iter().enumerate().collect(),
+            ..TestNewBlock::default()
+        });
+
+        // every event that carries a Clarity value carries its consensus serialization too,
+        // which decodes back to exactly that value
+        let events = payload["events"].as_array().unwrap();
+        let raw_value = |event: &serde_json::Value| {
+            Value::try_deserialize_hex_untyped(event.as_str().unwrap()).unwrap()
+        };
+        assert_eq!(raw_value(&events[0]["contract_event"]["raw_value"]), tuple);
+        assert_eq!(
+            raw_value(&events[1]["nft_mint_event"]["raw_value"]),
+            list_of_tuples
+        );
+    }
+
+    #[test]
+    fn test_block_timings() {
+        let port = get_random_port();
+        let dir = tempdir().unwrap();
+
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            for _ in 0..2 {
+                let mut request = server.recv().unwrap();
+                let mut payload = String::new();
+                request.as_reader().read_to_string(&mut payload).unwrap();
+                let url = request.url().to_string();
+                request.respond(Response::empty(200)).unwrap();
+                tx.send((url, payload)).unwrap();
+            }
+        });
+
+        let mut dispatcher = EventDispatcher::new();
+        for (path, include_block_timings) in [("with_timings", true), ("without", false)] {
+            dispatcher
+                .register_observer(
+                    &EventObserverConfig {
+                        endpoint: format!("127.0.0.1:{}/{}", port, path),
+                        events_keys: vec![EventKeyType::AnyEvent],
+                        timeout_ms: 3_000,
+                        include_block_timings,
+                        ..EventObserverConfig::default()
+                    },
+                    dir.path().to_path_buf(),
+                )
+                .unwrap();
+        }
+
+        let block = StacksBlock::genesis_block();
+        let metadata = StacksHeaderInfo::regtest_genesis();
+        dispatcher.process_chain_tip(
+            &block.into(),
+            &metadata,
+            &[],
+            &StacksBlockId([0; 32]),
+            Txid([0; 32]),
+            &[],
+            None,
+            BurnchainHeaderHash([0; 32]),
+            0,
+            0,
+            &ExecutionCost::zero(),
+            &ExecutionCost::zero(),
+            &PoxConstants::testnet_default(),
+            &None,
+            &None,
+            None,
+            0,
+            &BlockProcessingTimings {
+                validation: Duration::from_micros(1_500),
+                execution: Duration::from_millis(20),
+            },
+        );
+
+        let received: HashMap<_, _> = (0..2)
+            .map(|_| rx.recv_timeout(Duration::from_secs(5)).unwrap())
+            .collect();
+        let with_timings: serde_json::Value =
+            serde_json::from_str(&received["/with_timings/new_block"]).unwrap();
+        let timings = with_timings.get("timings").unwrap();
+        assert_eq!(timings["validation_us"], json!(1_500));
+        assert_eq!(timings["execution_us"], json!(20_000));
+        assert!(timings["event_serialization_us"].is_u64());
+
+        let without: serde_json::Value =
+            serde_json::from_str(&received["/without/new_block"]).unwrap();
+        assert!(without.get("timings").is_none());
+    }
+
+    #[test]
+    fn test_block_header_event() {
+        let port = get_random_port();
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+
+        // record the order in which the observers' events arrive
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            for _ in 0..3 {
+                let mut request = server.recv().unwrap();
+                let mut payload = String::new();
+                request.as_reader().read_to_string(&mut payload).unwrap();
+                let url = request.url().to_string();
+                request.respond(Response::empty(200)).unwrap();
+                tx.send((url, payload)).unwrap();
+            }
+        });
+
+        let mut dispatcher = EventDispatcher::new();
+        for (path, include_block_header_event) in [("with_header", true), ("without", false)] {
+            dispatcher
+                .register_observer(
+                    &EventObserverConfig {
+                        endpoint: format!("127.0.0.1:{}/{}", port, path),
+                        events_keys: vec![EventKeyType::AnyEvent],
+                        timeout_ms: 3_000,
+                        parallel_connections: 1,
+                        include_block_header_event,
+                        ..EventObserverConfig::default()
+                    },
+                    working_dir.clone(),
+                )
+                .unwrap();
+        }
+
+        let block = StacksBlock::genesis_block();
+        let metadata = StacksHeaderInfo::regtest_genesis();
+        dispatcher.process_chain_tip(
+            &block.clone().into(),
+            &metadata,
+            &[],
+            &StacksBlockId([0; 32]),
+            Txid([0; 32]),
+            &[],
+            None,
+            BurnchainHeaderHash([0; 32]),
+            0,
+            0,
+            &ExecutionCost::zero(),
+            &ExecutionCost::zero(),
+            &PoxConstants::testnet_default(),
+            &None,
+            &None,
+            None,
+            0,
+            &BlockProcessingTimings::default(),
+        );
+
+        let received: Vec<_> = (0..3)
+            .map(|_| rx.recv_timeout(Duration::from_secs(5)).unwrap())
+            .collect();
+        let urls: Vec<_> = received.iter().map(|(url, _)| url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                "/with_header/new_block_header",
+                "/with_header/new_block",
+                "/without/new_block"
+            ]
+        );
+
+        let header: serde_json::Value = serde_json::from_str(&received[0].1).unwrap();
+        assert_eq!(
+            header,
+            json!({
+                "block_hash": format!("0x{}", block.block_hash()),
+                "block_height": metadata.stacks_block_height,
+                "index_block_hash": format!("0x{}", metadata.index_block_hash()),
+                "consensus_hash": format!("0x{}", metadata.consensus_hash),
+                "parent_index_block_hash": format!("0x{}", StacksBlockId([0; 32])),
+                "burn_block_hash": format!("0x{}", metadata.burn_header_hash),
+                "burn_block_height": metadata.burn_header_height,
+            })
+        );
+    }
+
+    #[test]
+    fn test_resend_last_block() {
+        let port = get_random_port();
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            for _ in 0..3 {
+                let mut request = server.recv().unwrap();
+                let mut payload = String::new();
+                request.as_reader().read_to_string(&mut payload).unwrap();
+                let url = request.url().to_string();
+                request.respond(Response::empty(200)).unwrap();
+                tx.send((url, payload)).unwrap();
+            }
+        });
+
+        let mut dispatcher = EventDispatcher::new();
+        for (path, events_keys) in [
+            ("all", vec![EventKeyType::AnyEvent]),
+            ("burn_only", vec![EventKeyType::BurnchainBlocks]),
+        ] {
+            dispatcher
+                .register_observer(
+                    &EventObserverConfig {
+                        endpoint: format!("127.0.0.1:{}/{}", port, path),
+                        events_keys,
+                        timeout_ms: 3_000,
+                        parallel_connections: 1,
+                        ..EventObserverConfig::default()
+                    },
+                    working_dir.clone(),
+                )
+                .unwrap();
+        }
+
+        let all_endpoint = format!("127.0.0.1:{}/all", port);
+        assert_eq!(
+            dispatcher.resend_last_block(&all_endpoint),
+            Err("No block has been processed yet".to_string())
+        );
+        assert!(dispatcher.resend_last_block("127.0.0.1:1/unknown").is_err());
+
+        let receipts = make_stx_transfer_receipts(1, 1);
+        let block = StacksBlock::genesis_block();
+        let metadata = StacksHeaderInfo::regtest_genesis();
+        dispatcher.process_chain_tip(
+            &block.clone().into(),
+            &metadata,
+            &receipts,
+            &StacksBlockId([0; 32]),
+            Txid([0; 32]),
+            &[],
+            None,
+            BurnchainHeaderHash([0; 32]),
+            0,
+            0,
+            &ExecutionCost::zero(),
+            &ExecutionCost::zero(),
+            &PoxConstants::testnet_default(),
+            &None,
+            &None,
+            None,
+            0,
+            &BlockProcessingTimings::default(),
+        );
+        dispatcher.resend_last_block(&all_endpoint).unwrap();
+
+        let received: Vec<(String, serde_json::Value)> = (0..3)
+            .map(|_| {
+                let (url, payload) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+                (url, serde_json::from_str(&payload).unwrap())
+            })
+            .collect();
+        let urls: Vec<_> = received.iter().map(|(url, _)| url.as_str()).collect();
+        assert_eq!(
+            urls,
+            ["/all/new_block", "/burn_only/new_block", "/all/new_block"]
+        );
+
+        // the observer gets the same payload it was sent for the block, with its own events
+        assert_eq!(received[2].1, received[0].1);
+        assert_eq!(received[2].1["events"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_large_block_payloads_match_per_observer_payloads() {
+        let port = get_random_port();
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+
+        let observers = [
+            ("all", vec![EventKeyType::AnyEvent]),
+            ("stx", vec![EventKeyType::STXEvent]),
+            ("burn_only", vec![EventKeyType::BurnchainBlocks]),
+        ];
+        let num_observers = observers.len();
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            for _ in 0..num_observers {
+                let mut request = server.recv().unwrap();
+                let mut payload = String::new();
+                request.as_reader().read_to_string(&mut payload).unwrap();
+                let url = request.url().to_string();
+                request.respond(Response::empty(200)).unwrap();
+                tx.send((url, payload)).unwrap();
+            }
+        });
+
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher.set_event_serialization_threads(4);
+        for (path, events_keys) in observers.iter() {
+            dispatcher
+                .register_observer(
+                    &EventObserverConfig {
+                        endpoint: format!("127.0.0.1:{}/{}", port, path),
+                        events_keys: events_keys.clone(),
+                        timeout_ms: 10_000,
+                        ..EventObserverConfig::default()
+                    },
+                    working_dir.clone(),
+                )
+                .unwrap();
+        }
+
+        // a large block, in which some transactions failed their post-conditions
+        let mut receipts = make_stx_transfer_receipts(1_000, 10);
+        for receipt in receipts.iter_mut().step_by(7) {
+            receipt.post_condition_aborted = true;
+        }
+        let block: StacksBlockEventData = StacksBlock::genesis_block().into();
+        let metadata = StacksHeaderInfo::regtest_genesis();
+        let pox_constants = PoxConstants::testnet_default();
+        dispatcher.process_chain_tip(
+            &block,
+            &metadata,
+            &receipts,
+            &StacksBlockId([0; 32]),
+            Txid([0; 32]),
+            &[],
+            None,
+            BurnchainHeaderHash([0; 32]),
+            0,
+            0,
+            &ExecutionCost::zero(),
+            &ExecutionCost::zero(),
+            &pox_constants,
+            &None,
+            &None,
+            None,
+            0,
+            &BlockProcessingTimings::default(),
+        );
+        let mut received: HashMap<String, serde_json::Value> = (0..num_observers)
+            .map(|_| {
+                let (url, payload) = rx.recv_timeout(Duration::from_secs(30)).unwrap();
+                (url, serde_json::from_str(&payload).unwrap())
+            })
+            .collect();
+
+        // each observer's payload is what it would be if made on its own, from every event of
+        // the block
+        let (dispatch_matrix, events) =
+            dispatcher.create_dispatch_matrix_and_event_vector(&receipts, None);
+        assert_eq!(events.len(), 10_000);
+        for (observer_id, (path, _)) in observers.iter().enumerate() {
+            let mut payload = received.remove(&format!("/{}/new_block", path)).unwrap();
+            let expected = dispatcher.registered_observers[observer_id]
+                .make_new_block_processed_payload(&TestNewBlock {
+                    filtered_events: dispatch_matrix[observer_id]
+                        .iter()
+                        .map(|event_id| (*event_id, &events[*event_id]))
+                        .collect(),
+                    block: block.clone(),
+                    metadata: metadata.clone(),
+                    receipts: &receipts,
+                    pox_constants: pox_constants.clone(),
+                    ..TestNewBlock::default()
+                });
+            // depends on when the block was processed
+            payload["burn_to_block_latency_secs"] = expected["burn_to_block_latency_secs"].clone();
+            assert_eq!(payload, expected, "{}", path);
+
+            let expected_events = match *path {
+                "burn_only" => 0,
+                _ => 10_000,
+            };
+            assert_eq!(
+                payload["events"].as_array().unwrap().len(),
+                expected_events,
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn test_replay_dropped_mempool_txs() {
+        let port = get_random_port();
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            for _ in 0..2 {
+                let mut request = server.recv().unwrap();
+                let mut payload = String::new();
+                request.as_reader().read_to_string(&mut payload).unwrap();
+                let url = request.url().to_string();
+                request.respond(Response::empty(200)).unwrap();
+                tx.send((url, payload)).unwrap();
+            }
+        });
+
+        // drops are remembered before any observer subscribes
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher.set_mempool_drop_history_size(2);
+        dispatcher.process_dropped_mempool_txs(
+            vec![Txid([0x01; 32]), Txid([0x02; 32])],
+            MemPoolDropReason::STALE_COLLECT,
+            None,
+        );
+        dispatcher.process_dropped_mempool_txs(
+            vec![Txid([0x03; 32])],
+            MemPoolDropReason::REPLACE_BY_FEE,
+            None,
+        );
+        let history = dispatcher.get_mempool_drop_history().unwrap();
+        assert_eq!(history.capacity, 2);
+        let txids: Vec<_> = history.drops.iter().map(|drop| drop.txid.clone()).collect();
+        assert_eq!(txids, vec![Txid([0x02; 32]), Txid([0x03; 32])]);
+
+        for (path, events_keys) in [
+            ("mempool", vec![EventKeyType::MemPoolTransactions]),
+            ("burn_only", vec![EventKeyType::BurnchainBlocks]),
+        ] {
+            dispatcher
+                .register_observer(
+                    &EventObserverConfig {
+                        endpoint: format!("127.0.0.1:{}/{}", port, path),
+                        events_keys,
+                        timeout_ms: 3_000,
+                        ..EventObserverConfig::default()
+                    },
+                    working_dir.clone(),
+                )
+                .unwrap();
+        }
+
+        assert!(dispatcher
+            .replay_dropped_mempool_txs(&format!("127.0.0.1:{}/burn_only", port))
+            .is_err());
+        assert_eq!(
+            dispatcher.replay_dropped_mempool_txs(&format!("127.0.0.1:{}/mempool", port)),
+            Ok(2)
+        );
+
+        let received: Vec<(String, serde_json::Value)> = (0..2)
+            .map(|_| {
+                let (url, payload) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+                (url, serde_json::from_str(&payload).unwrap())
+            })
+            .collect();
+        assert_eq!(received[0].0, "/mempool/drop_mempool_tx");
+        assert_eq!(
+            received[0].1["dropped_txids"],
+            json!([format!("0x{}", Txid([0x02; 32]))])
+        );
+        assert_eq!(received[0].1["reason"], "StaleGarbageCollect");
+        assert_eq!(received[0].1["replayed"], true);
+        assert_eq!(received[1].0, "/mempool/drop_mempool_tx");
+        assert_eq!(
+            received[1].1["dropped_txids"],
+            json!([format!("0x{}", Txid([0x03; 32]))])
+        );
+        assert_eq!(received[1].1["reason"], "ReplaceByFee");
+        assert_eq!(received[1].1["dropped_at"], history.drops[1].timestamp);
+    }
+
+    #[test]
+    fn test_send_startup_event() {
+        let port = get_random_port();
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || loop {
+            let mut request = server.recv().unwrap();
+            let mut payload = String::new();
+            request.as_reader().read_to_string(&mut payload).unwrap();
+            let url = request.url().to_string();
+            request.respond(Response::empty(200)).unwrap();
+            tx.send((url, payload)).unwrap();
+        });
+
+        let mut config = Config::default();
+        config.node.local_peer_seed = vec![0x01; 32];
+        let identity = NodeIdentity::from_config(&config);
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher.set_node_identity(identity.clone());
+
+        // only the observer that asked for it is greeted, as soon as it is registered
+        for (path, send_startup_event) in [("quiet", false), ("hello", true)] {
+            dispatcher
+                .register_observer(
+                    &EventObserverConfig {
+                        endpoint: format!("127.0.0.1:{}/{}", port, path),
+                        events_keys: vec![EventKeyType::BurnchainBlocks],
+                        timeout_ms: 3_000,
+                        send_startup_event,
+                        ..EventObserverConfig::default()
+                    },
+                    working_dir.clone(),
+                )
+                .unwrap();
+        }
+
+        let (url, payload) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(url, "/hello/new_observer");
+        let payload: serde_json::Value = serde_json::from_str(&payload).unwrap();
+        assert_eq!(payload["node_id"], format!("0x{}", identity.node_id));
+        assert_eq!(payload["version"], identity.version);
+        assert_eq!(payload["network"], "testnet");
+        assert_eq!(
+            payload["observer"]["endpoint"],
+            format!("127.0.0.1:{}/hello", port)
+        );
+        assert_eq!(payload["observer"]["events_keys"], json!(["burn_blocks"]));
+        assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+    }
+
+    #[test]
+    fn test_max_observers() {
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+        let conf = |port: u16| EventObserverConfig {
+            endpoint: format!("127.0.0.1:{}", port),
+            events_keys: vec![EventKeyType::AnyEvent],
+            timeout_ms: 1_000,
+            ..EventObserverConfig::default()
+        };
+
+        let mut dispatcher = EventDispatcher::new();
+        assert_eq!(dispatcher.max_observers, DEFAULT_MAX_EVENT_OBSERVERS);
+        dispatcher.set_max_observers(usize::MAX);
+        assert_eq!(dispatcher.max_observers, MAX_EVENT_OBSERVERS);
+
+        dispatcher.set_max_observers(2);
+        dispatcher
+            .register_observer(&conf(3700), working_dir.clone())
+            .unwrap();
+        dispatcher
+            .register_observer(&conf(3701), working_dir.clone())
+            .unwrap();
+        let err = dispatcher
+            .register_observer(&conf(3702), working_dir.clone())
+            .unwrap_err();
+        assert!(err.contains("at most 2 event observers"), "{}", err);
+        assert_eq!(dispatcher.registered_observers.len(), 2);
+        assert_eq!(dispatcher.registered_observer_configs.len(), 2);
+    }
+
+    #[test]
+    fn test_malformed_endpoint() {
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+        let endpoint = "user:secret@127.0.0.1:99999";
+
+        let mut dispatcher = EventDispatcher::new();
+        let err = dispatcher
+            .register_observer(
+                &EventObserverConfig {
+                    endpoint: endpoint.into(),
+                    events_keys: vec![EventKeyType::AnyEvent],
+                    timeout_ms: 1_000,
+                    ..EventObserverConfig::default()
+                },
+                working_dir.clone(),
+            )
+            .unwrap_err();
+        assert!(err.contains("invalid endpoint"), "{}", err);
+        assert!(!err.contains("secret"), "{}", err);
+        assert!(dispatcher.registered_observers.is_empty());
+
+        // an observer that bypassed registration logs and gives up on its payloads instead of
+        // panicking, or leaving them to hold up the rest of its queue
+        let observer = EventObserver::new(None, endpoint.into(), Duration::from_secs(1));
+        observer.send_payload(&json!({"key": "value"}), "/test");
+        let observer =
+            EventObserver::new(Some(working_dir), endpoint.into(), Duration::from_secs(1));
+        observer.send_payload(&json!({"key": "value"}), "/test");
+        let conn = Connection::open(observer.db_path.as_ref().unwrap()).unwrap();
+        assert!(EventObserver::get_pending_payloads(&conn)
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            EventObserver::send_payload_directly(
+                &json!({"key": "value"}),
+                &format!("http://{}/test", endpoint),
+                Duration::from_secs(1),
+                None,
+                None,
+                None,
+                &DeliverySettings::default(),
+            ),
+            DeliveryOutcome::GaveUp(_)
+        ));
+    }
+
+    #[test]
+    #[cfg(not(feature = "http2-observers"))]
+    fn test_http2_needs_feature() {
+        let dir = tempdir().unwrap();
+
+        let mut dispatcher = EventDispatcher::new();
+        let err = dispatcher
+            .register_observer(
+                &EventObserverConfig {
+                    endpoint: "127.0.0.1:3700".into(),
+                    events_keys: vec![EventKeyType::AnyEvent],
+                    timeout_ms: 1_000,
+                    http2: true,
+                    ..EventObserverConfig::default()
+                },
+                dir.path().to_path_buf(),
+            )
+            .unwrap_err();
+        assert!(err.contains("http2-observers"), "{}", err);
+        assert!(dispatcher.registered_observers.is_empty());
+    }
+
+    #[test]
+    #[cfg(not(feature = "redis-observers"))]
+    fn test_redis_needs_feature() {
+        let dir = tempdir().unwrap();
+
+        let mut dispatcher = EventDispatcher::new();
+        let err = dispatcher
+            .register_observer(
+                &EventObserverConfig {
+                    endpoint: "redis://:hunter2@127.0.0.1:6379/0".into(),
+                    events_keys: vec![EventKeyType::AnyEvent],
+                    timeout_ms: 1_000,
+                    ..EventObserverConfig::default()
+                },
+                dir.path().to_path_buf(),
+            )
+            .unwrap_err();
+        assert!(err.contains("redis-observers"), "{}", err);
+        assert!(!err.contains("hunter2"), "{}", err);
+        assert!(dispatcher.registered_observers.is_empty());
+    }
+
+    #[test]
+    fn test_strict_block_ordering_needs_serial_delivery() {
+        let dir = tempdir().unwrap();
+
+        let mut dispatcher = EventDispatcher::new();
+        let err = dispatcher
+            .register_observer(
+                &EventObserverConfig {
+                    endpoint: "127.0.0.1:3700".into(),
+                    events_keys: vec![EventKeyType::AnyEvent],
+                    timeout_ms: 1_000,
+                    parallel_connections: 4,
+                    strict_block_ordering: true,
+                    ..EventObserverConfig::default()
+                },
+                dir.path().to_path_buf(),
+            )
+            .unwrap_err();
+        assert!(err.contains("parallel_connections"), "{}", err);
+        assert!(dispatcher.registered_observers.is_empty());
+    }
+
+    #[test]
+    fn test_strict_block_ordering() {
+        let port = get_random_port();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        let (tx, rx) = channel();
+        thread::spawn(move || {
+            // the first attempt at each payload fails
+            let mut attempted = HashSet::new();
+            while let Ok(request) = server.recv() {
+                let url = request.url().to_string();
+                let status = if attempted.insert(url.clone()) {
+                    500
+                } else {
+                    200
+                };
+                request.respond(Response::empty(status)).unwrap();
+                tx.send((url, status)).unwrap();
+            }
+        });
+
+        let dir = tempdir().unwrap();
+        let observer = EventObserver::new(
+            Some(dir.path().to_path_buf()),
+            format!("127.0.0.1:{}", port),
+            Duration::from_secs(3),
+        )
+        .with_strict_block_ordering(true, None);
+
+        let hold = observer.block_order.as_ref().unwrap().hold();
+        let chunks_sender = {
+            let observer = observer.clone();
+            thread::spawn(move || {
+                observer.send_payload(&json!({"chunks": []}), PATH_STACKERDB_CHUNKS);
+            })
+        };
+
+        // the chunks wait for the block
+        assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+
+        // the block is retried until it's delivered, even though its deadline has passed
+        observer.send_payload_with_retry_deadline(
+            &json!({"block_height": 1}),
+            PATH_BLOCK_PROCESSED,
+            Some(Instant::now()),
+        );
+        drop(hold);
+        chunks_sender.join().unwrap();
+
+        let received: Vec<_> = (0..4)
+            .map(|_| rx.recv_timeout(Duration::from_secs(5)).unwrap())
+            .collect();
+        assert_eq!(
+            received,
+            vec![
+                ("/new_block".to_string(), 500),
+                ("/new_block".to_string(), 200),
+                ("/stackerdb_chunks".to_string(), 500),
+                ("/stackerdb_chunks".to_string(), 200),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_strict_block_ordering_retry_budget() {
+        let port = get_random_port();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        let (tx, rx) = channel();
+        thread::spawn(move || {
+            while let Ok(request) = server.recv() {
+                let url = request.url().to_string();
+                request.respond(Response::empty(200)).unwrap();
+                tx.send(url).unwrap();
+            }
+        });
+
+        let dir = tempdir().unwrap();
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher
+            .register_observer(
+                &EventObserverConfig {
+                    endpoint: format!("127.0.0.1:{}", port),
+                    events_keys: vec![EventKeyType::AnyEvent],
+                    timeout_ms: 3_000,
+                    strict_block_ordering: true,
+                    ..EventObserverConfig::default()
+                },
+                dir.path().to_path_buf(),
+            )
+            .unwrap();
+        dispatcher.set_block_retry_budget(Some(Duration::from_millis(200)));
+
+        let observer = &dispatcher.registered_observers[0];
+        let gate = observer.block_order.as_ref().unwrap();
+        assert_eq!(gate.max_wait(), Some(Duration::from_millis(200)));
+
+        // the chunks stop waiting for a block that isn't delivered within the budget
+        let _hold = gate.hold();
+        observer.send_payload(&json!({"chunks": []}), PATH_STACKERDB_CHUNKS);
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+            "/stackerdb_chunks"
+        );
+    }
+
+    #[test]
+    fn test_split_array_events() {
+        let port = get_random_port();
+
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            for _ in 0..6 {
+                let mut request = server.recv().unwrap();
+                let mut payload = String::new();
+                request.as_reader().read_to_string(&mut payload).unwrap();
+                let url = request.url().to_string();
+                request.respond(Response::empty(200)).unwrap();
+                tx.send((url, payload)).unwrap();
+            }
+        });
+        let recv = || {
+            let (url, payload) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+            (
+                url,
+                serde_json::from_str::<serde_json::Value>(&payload).unwrap(),
+            )
+        };
+
+        let txs = json!(["0x01", "0x02", "0x03"]);
+        let attachments = json!([{"attachment_index": 1}, {"attachment_index": 2}]);
+
+        // by default, arrays are sent whole
+        let observer =
+            EventObserver::new(None, format!("127.0.0.1:{}", port), Duration::from_secs(3));
+        observer.send_new_mempool_txs(&txs);
+        assert_eq!(
+            recv(),
+            (format!("/{}", PATH_MEMPOOL_TX_SUBMIT), txs.clone())
+        );
+
+        // split, each element is sent on its own, in order
+        let observer = observer.with_split_array_events(true);
+        observer.send_new_mempool_txs(&txs);
+        for item in txs.as_array().unwrap() {
+            assert_eq!(
+                recv(),
+                (format!("/{}", PATH_MEMPOOL_TX_SUBMIT), item.clone())
+            );
+        }
+        observer.send_new_attachments(&attachments);
+        for item in attachments.as_array().unwrap() {
+            assert_eq!(
+                recv(),
+                (format!("/{}", PATH_ATTACHMENT_PROCESSED), item.clone())
+            );
+        }
+    }
+
+    #[test]
+    fn test_stringify_large_ints() {
+        let port = get_random_port();
+
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            for _ in 0..2 {
+                let mut request = server.recv().unwrap();
+                let mut payload = String::new();
+                request.as_reader().read_to_string(&mut payload).unwrap();
+                request.respond(Response::empty(200)).unwrap();
+                tx.send(payload).unwrap();
+            }
+        });
+        let recv = || {
+            let payload = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+            serde_json::from_str::<serde_json::Value>(&payload).unwrap()
+        };
+
+        let payload = json!({
+            "burn_block_hash": "0x01",
+            "burn_block_height": u64::MAX,
+            "reward_recipients": [{"recipient": "addr", "amt": 9007199254740993u64}],
+            "burn_amount": 10,
+            "consensus_hash": "0x02",
+        });
+
+        // by default, integers are sent as numbers
+        let observer =
+            EventObserver::new(None, format!("127.0.0.1:{}", port), Duration::from_secs(3));
+        observer.send_payload(&payload, PATH_BURN_BLOCK_SUBMIT);
+        assert_eq!(recv(), payload);
+
+        let observer = observer.with_stringify_large_ints(true);
+        observer.send_payload(&payload, PATH_BURN_BLOCK_SUBMIT);
+        assert_eq!(
+            recv(),
+            json!({
+                "burn_block_hash": "0x01",
+                "burn_block_height": "18446744073709551615",
+                "reward_recipients": [{"recipient": "addr", "amt": "9007199254740993"}],
+                "burn_amount": "10",
+                "consensus_hash": "0x02",
+            })
+        );
+    }
+
+    #[test]
+    fn test_mined_block_archive_without_observers() {
+        use std::io::Read;
+
+        use libflate::gzip::MultiDecoder;
+
+        let dir = tempdir().unwrap();
+        let archive = MinedBlockArchive::new(dir.path().to_path_buf(), None);
+
+        // no observer receives mined blocks, but they are still archived
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher.set_mined_block_archive(Some(archive.clone()));
+
+        let block = StacksBlock::genesis_block();
+        dispatcher.process_mined_block_event(
+            100,
+            &block,
+            10,
+            &ExecutionCost::zero(),
+            &ExecutionCost::zero(),
+            vec![],
+        );
+        let nakamoto_block = NakamotoBlock {
+            header: NakamotoBlockHeader::empty(),
+            txs: vec![],
+        };
+        dispatcher.process_mined_nakamoto_block_event(
+            100,
+            &nakamoto_block,
+            20,
+            &ExecutionCost::zero(),
+            vec![],
+        );
+
+        let file = std::fs::File::open(archive.file_path(100)).unwrap();
+        let mut contents = String::new();
+        MultiDecoder::new(file)
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        let lines: Vec<serde_json::Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["path"], format!("/{}", PATH_MINED_BLOCK));
+        assert_eq!(lines[0]["payload"]["block_size"], 10);
+        assert_eq!(lines[0]["payload"]["target_burn_height"], 100);
+        assert_eq!(lines[1]["path"], format!("/{}", PATH_MINED_NAKAMOTO_BLOCK));
+        assert_eq!(lines[1]["payload"]["block_size"], 20);
+        assert_eq!(
+            lines[1]["payload"]["block_id"],
+            nakamoto_block.header.block_id().to_string()
+        );
+    }
+
+    #[test]
+    fn test_payload_envelope() {
+        let port = get_random_port();
+
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            for _ in 0..3 {
+                let mut request = server.recv().unwrap();
+                let mut payload = String::new();
+                request.as_reader().read_to_string(&mut payload).unwrap();
+                let url = request.url().to_string();
+                request.respond(Response::empty(200)).unwrap();
+                tx.send((url, payload)).unwrap();
+            }
+        });
+        let recv = || {
+            let (url, payload) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+            (
+                url,
+                serde_json::from_str::<serde_json::Value>(&payload).unwrap(),
+            )
+        };
+
+        let payload = json!({
+            "burn_block_hash": "0x01",
+            "burn_block_height": 100,
+        });
+
+        // by default, payloads are sent as they are
+        let observer =
+            EventObserver::new(None, format!("127.0.0.1:{}", port), Duration::from_secs(3));
+        observer.send_payload(&payload, PATH_BURN_BLOCK_SUBMIT);
+        assert_eq!(recv(), ("/new_burn_block".to_string(), payload.clone()));
+
+        // enveloped payloads are still sent to the event's path
+        let observer = observer.with_payload_envelope(true);
+        observer.send_payload(&payload, PATH_BURN_BLOCK_SUBMIT);
+        assert_eq!(
+            recv(),
+            (
+                "/new_burn_block".to_string(),
+                json!({ "type": "new_burn_block", "data": payload }),
+            )
+        );
+        observer.send_payload(&json!([]), PATH_ATTACHMENT_PROCESSED);
+        assert_eq!(
+            recv(),
+            (
+                "/attachments/new".to_string(),
+                json!({ "type": "attachments/new", "data": [] }),
+            )
+        );
+    }
+
+    #[test]
+    fn test_stackerdb_chunks_batch_window() {
+        let port = get_random_port();
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            for _ in 0..3 {
+                let mut request = server.recv().unwrap();
+                let mut payload = String::new();
+                request.as_reader().read_to_string(&mut payload).unwrap();
+                let url = request.url().to_string();
+                request.respond(Response::empty(200)).unwrap();
+                tx.send((url, payload)).unwrap();
+            }
+        });
+
+        let mut dispatcher = EventDispatcher::new();
+        for (path, stackerdb_batch_window_ms) in [("batched", Some(500)), ("unbatched", None)] {
+            dispatcher
+                .register_observer(
+                    &EventObserverConfig {
+                        endpoint: format!("127.0.0.1:{}/{}", port, path),
+                        events_keys: vec![EventKeyType::StackerDBChunks],
+                        timeout_ms: 3_000,
+                        parallel_connections: 1,
+                        stackerdb_batch_window_ms,
+                        ..EventObserverConfig::default()
+                    },
+                    working_dir.clone(),
+                )
+                .unwrap();
+        }
+
+        let contract_id = boot_code_id("signers-0-0", false);
+        dispatcher.process_new_stackerdb_chunks(
+            contract_id.clone(),
+            vec![StackerDBChunkData::new(0, 1, vec![1])],
+        );
+        dispatcher.process_new_stackerdb_chunks(
+            contract_id.clone(),
+            vec![StackerDBChunkData::new(1, 1, vec![2])],
+        );
+
+        let received: Vec<(String, serde_json::Value)> = (0..3)
+            .map(|_| {
+                let (url, payload) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+                (url, serde_json::from_str(&payload).unwrap())
+            })
+            .collect();
+        let urls: Vec<_> = received.iter().map(|(url, _)| url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                "/unbatched/stackerdb_chunks",
+                "/unbatched/stackerdb_chunks",
+                "/batched/stackerdb_chunks"
+            ]
+        );
+
+        // the batched observer gets both events' slots in one payload
+        let batch = &received[2].1;
+        assert_eq!(batch["contract_id"], json!(contract_id));
+        let slot_ids: Vec<_> = batch["modified_slots"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|slot| slot["slot_id"].as_u64().unwrap())
+            .collect();
+        assert_eq!(slot_ids, [0, 1]);
+        assert_eq!(
+            batch["modified_slots"][0],
+            received[0].1["modified_slots"][0]
+        );
+    }
+
+    /// Drive each of the dispatcher's `process_*` methods against a live observer, and check
+    /// that every event arrives on its path, and only for the observers subscribed to it
+    #[test]
+    fn test_dispatcher_event_paths() {
+        let port = get_random_port();
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+        let num_events = 12;
+
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            for _ in 0..num_events {
+                let mut request = server.recv().unwrap();
+                let mut payload = String::new();
+                request.as_reader().read_to_string(&mut payload).unwrap();
+                let url = request.url().to_string();
+                request.respond(Response::empty(200)).unwrap();
+                tx.send((url, payload)).unwrap();
+            }
+        });
+
+        let mut dispatcher = EventDispatcher::new();
+        for (path, events_keys) in [
+            (
+                "all",
+                vec![
+                    EventKeyType::AnyEvent,
+                    EventKeyType::MinedBlocks,
+                    EventKeyType::StackerDBChunks,
+                    EventKeyType::BlockProposal,
+                    EventKeyType::Rewards,
+                ],
+            ),
+            ("burn_only", vec![EventKeyType::BurnchainBlocks]),
+        ] {
+            dispatcher
+                .register_observer(
+                    &EventObserverConfig {
+                        endpoint: format!("127.0.0.1:{}/{}", port, path),
+                        events_keys,
+                        timeout_ms: 3_000,
+                        parallel_connections: 1,
+                        ..EventObserverConfig::default()
+                    },
+                    working_dir.clone(),
+                )
+                .unwrap();
+        }
+
+        let receipts = make_stx_transfer_receipts(1, 1);
+        let TransactionOrigin::Stacks(stacks_tx) = receipts[0].transaction.clone() else {
+            panic!("Not a Stacks transaction");
+        };
+        let block = StacksBlock::genesis_block();
+        let metadata = StacksHeaderInfo::regtest_genesis();
+        dispatcher.process_chain_tip(
+            &block.clone().into(),
+            &metadata,
+            &receipts,
+            &StacksBlockId([0; 32]),
+            Txid([0; 32]),
+            &[],
+            None,
+            BurnchainHeaderHash([0; 32]),
+            0,
+            0,
+            &ExecutionCost::zero(),
+            &ExecutionCost::zero(),
+            &PoxConstants::testnet_default(),
+            &None,
+            &None,
+            None,
+            0,
+            &BlockProcessingTimings::default(),
+        );
+        dispatcher.process_burn_block(&BurnchainHeaderHash([1; 32]), 100, vec![], 0, vec![]);
+        dispatcher.process_new_mempool_txs(vec![stacks_tx.clone()]);
+        dispatcher.process_dropped_mempool_txs(
+            vec![stacks_tx.txid()],
+            MemPoolDropReason::STALE_COLLECT,
+            None,
+        );
+        dispatcher
+            .process_new_microblocks(StacksBlockId([2; 32]), ProcessedUnconfirmedState::default());
+        dispatcher.process_mined_block_event(
+            100,
+            &block,
+            0,
+            &ExecutionCost::zero(),
+            &ExecutionCost::zero(),
+            vec![],
+        );
+        let nakamoto_block = NakamotoBlock {
+            header: NakamotoBlockHeader::empty(),
+            txs: vec![],
+        };
+        dispatcher.process_mined_nakamoto_block_event(
+            100,
+            &nakamoto_block,
+            0,
+            &ExecutionCost::zero(),
+            vec![],
+        );
+        dispatcher.process_new_stackerdb_chunks(boot_code_id("signers-0-0", false), vec![]);
+        dispatcher
+            .get_proposal_callback_receiver()
+            .expect("No block proposal observers")
+            .notify_proposal_result(Err(BlockValidateReject {
+                signer_signature_hash: Sha512Trunc256Sum([3; 32]),
+                reason: "test".into(),
+                reason_code: ValidateRejectCode::InvalidBlock,
+            }));
+
+        let received: Vec<(String, serde_json::Value)> = (0..num_events)
+            .map(|_| {
+                let (url, payload) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+                (url, serde_json::from_str(&payload).unwrap())
+            })
+            .collect();
+        let urls: Vec<_> = received.iter().map(|(url, _)| url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                format!("/all/{}", PATH_BLOCK_PROCESSED),
+                format!("/burn_only/{}", PATH_BLOCK_PROCESSED),
+                format!("/all/{}", PATH_REWARDS),
+                format!("/all/{}", PATH_BURN_BLOCK_SUBMIT),
+                format!("/burn_only/{}", PATH_BURN_BLOCK_SUBMIT),
+                format!("/all/{}", PATH_MEMPOOL_TX_SUBMIT),
+                format!("/all/{}", PATH_MEMPOOL_TX_DROP),
+                format!("/all/{}", PATH_MICROBLOCK_SUBMIT),
+                format!("/all/{}", PATH_MINED_BLOCK),
+                format!("/all/{}", PATH_MINED_NAKAMOTO_BLOCK),
+                format!("/all/{}", PATH_STACKERDB_CHUNKS),
+                format!("/all/{}", PATH_PROPOSAL_RESPONSE),
+            ]
+        );
+        let payloads: Vec<_> = received.into_iter().map(|(_, payload)| payload).collect();
+
+        // every observer gets the block, but only with the events it subscribed to
+        assert_eq!(
+            payloads[0]["block_hash"],
+            json!(format!("0x{}", block.block_hash()))
+        );
+        assert_eq!(payloads[0]["events"].as_array().unwrap().len(), 1);
+        assert_eq!(payloads[1]["events"].as_array().unwrap().len(), 0);
+        assert_eq!(payloads[0]["transactions"], payloads[1]["transactions"]);
+        assert_eq!(
+            payloads[2]["index_block_hash"],
+            payloads[0]["index_block_hash"]
+        );
+
+        assert_eq!(payloads[3]["burn_block_height"], json!(100));
+        assert_eq!(payloads[3], payloads[4]);
+        assert_eq!(
+            payloads[5],
+            json!([format!("0x{}", bytes_to_hex(&stacks_tx.serialize_to_vec()))])
+        );
+        assert_eq!(
+            payloads[6]["dropped_txids"],
+            json!([format!("0x{}", stacks_tx.txid())])
+        );
+        assert_eq!(
+            payloads[7]["parent_index_block_hash"],
+            json!(format!("0x{}", StacksBlockId([2; 32])))
+        );
+        assert_eq!(
+            payloads[8]["block_hash"],
+            json!(block.block_hash().to_string())
+        );
+        assert_eq!(
+            payloads[9]["block_id"],
+            json!(nakamoto_block.header.block_id().to_string())
+        );
+        assert_eq!(
+            payloads[10]["contract_id"],
+            json!(boot_code_id("signers-0-0", false))
+        );
+        assert_eq!(payloads[11]["result"], json!("Reject"));
+    }
+
+    /// Observers are sent their payloads in registration order, and each observer's events in
+    /// the order they happened, however many times the dispatcher is set up
+    #[test]
+    fn test_dispatch_order_is_deterministic() {
+        let num_observers = 8;
+        let num_runs = 4;
+        let port = get_random_port();
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            for _ in 0..num_observers * num_runs {
+                let request = server.recv().unwrap();
+                let url = request.url().to_string();
+                request.respond(Response::empty(200)).unwrap();
+                tx.send(url).unwrap();
+            }
+        });
+
+        let receipts = make_stx_transfer_receipts(4, 8);
+        for _ in 0..num_runs {
+            let mut dispatcher = EventDispatcher::new();
+            for i in 0..num_observers {
+                dispatcher
+                    .register_observer(
+                        &EventObserverConfig {
+                            endpoint: format!("127.0.0.1:{}/{}", port, i),
+                            events_keys: vec![EventKeyType::BlockProposal, EventKeyType::STXEvent],
+                            timeout_ms: 3_000,
+                            parallel_connections: 1,
+                            ..EventObserverConfig::default()
+                        },
+                        working_dir.clone(),
+                    )
+                    .unwrap();
+            }
+
+            let dispatch_matrix = dispatcher.make_dispatch_matrix(&receipts, None);
+            assert_eq!(EventDispatcher::receipt_events(&receipts).count(), 32);
+            for event_ids in dispatch_matrix.iter() {
+                assert_eq!(
+                    event_ids.iter().copied().collect::<Vec<_>>(),
+                    (0..32).collect::<Vec<_>>()
+                );
+            }
+
+            dispatcher
+                .get_proposal_callback_receiver()
+                .expect("No block proposal observers")
+                .notify_proposal_result(Err(BlockValidateReject {
+                    signer_signature_hash: Sha512Trunc256Sum([3; 32]),
+                    reason: "test".into(),
+                    reason_code: ValidateRejectCode::InvalidBlock,
+                }));
+            let urls: Vec<_> = (0..num_observers)
+                .map(|_| rx.recv_timeout(Duration::from_secs(5)).unwrap())
+                .collect();
+            let expected: Vec<_> = (0..num_observers)
+                .map(|i| format!("/{}/{}", i, PATH_PROPOSAL_RESPONSE))
+                .collect();
+            assert_eq!(urls, expected);
+        }
+    }
+
+    #[test]
+    fn test_new_rewards_event() {
+        use clarity::vm::types::PrincipalData;
+        use stacks::chainstate::stacks::{CoinbasePayload, TransactionAuth, TransactionVersion};
+        use stacks_common::types::chainstate::StacksPrivateKey;
+
+        let port = get_random_port();
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+        let num_events = 3;
+
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            for _ in 0..num_events {
+                let mut request = server.recv().unwrap();
+                let mut payload = String::new();
+                request.as_reader().read_to_string(&mut payload).unwrap();
+                let url = request.url().to_string();
+                request.respond(Response::empty(200)).unwrap();
+                tx.send((url, payload)).unwrap();
+            }
+        });
+
+        // the unsubscribed observer is registered first, so that it would get the first
+        // `new_rewards` payload if it were sent one
+        let mut dispatcher = EventDispatcher::new();
+        for (path, events_keys) in [
+            ("other", vec![EventKeyType::AnyEvent]),
+            ("rewards", vec![EventKeyType::Rewards]),
+        ] {
+            dispatcher
+                .register_observer(
+                    &EventObserverConfig {
+                        endpoint: format!("127.0.0.1:{}/{}", port, path),
+                        events_keys,
+                        timeout_ms: 3_000,
+                        parallel_connections: 1,
+                        ..EventObserverConfig::default()
+                    },
+                    working_dir.clone(),
+                )
+                .unwrap();
+        }
+
+        let recipient = PrincipalData::from(boot_code_id("pox-4", false));
+        let coinbase_tx = StacksTransaction::new(
+            TransactionVersion::Testnet,
+            TransactionAuth::from_p2pkh(&StacksPrivateKey::new()).unwrap(),
+            TransactionPayload::Coinbase(CoinbasePayload([0; 32]), Some(recipient.clone()), None),
+        );
+        let mut receipts = vec![StacksTransactionReceipt::from_coinbase(coinbase_tx.clone())];
+        receipts.extend(make_stx_transfer_receipts(1, 1));
+
+        let miner_address = coinbase_tx.origin_address();
+        let mature_reward = MinerReward {
+            address: miner_address.clone(),
+            recipient: miner_address.to_account_principal(),
+            coinbase: 1_000,
+            tx_fees_anchored: 20,
+            tx_fees_streamed_produced: 0,
+            tx_fees_streamed_confirmed: 0,
+            vtxindex: 0,
+        };
+        let mature_rewards_info = MinerRewardInfo {
+            from_block_consensus_hash: ConsensusHash([1; 20]),
+            from_stacks_block_hash: BlockHeaderHash([2; 32]),
+            from_parent_block_consensus_hash: ConsensusHash([3; 20]),
+            from_parent_stacks_block_hash: BlockHeaderHash([4; 32]),
+        };
+
+        let block = StacksBlock::genesis_block();
+        let metadata = StacksHeaderInfo::regtest_genesis();
+        dispatcher.process_chain_tip(
+            &block.clone().into(),
+            &metadata,
+            &receipts,
+            &StacksBlockId([0; 32]),
+            Txid([0; 32]),
+            &[mature_reward],
+            Some(&mature_rewards_info),
+            BurnchainHeaderHash([0; 32]),
+            0,
+            0,
+            &ExecutionCost::zero(),
+            &ExecutionCost::zero(),
+            &PoxConstants::testnet_default(),
+            &None,
+            &None,
+            None,
+            0,
+            &BlockProcessingTimings::default(),
+        );
+
+        let received: Vec<(String, serde_json::Value)> = (0..num_events)
+            .map(|_| {
+                let (url, payload) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+                (url, serde_json::from_str(&payload).unwrap())
+            })
+            .collect();
+        let urls: Vec<_> = received.iter().map(|(url, _)| url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                format!("/other/{}", PATH_BLOCK_PROCESSED),
+                format!("/rewards/{}", PATH_BLOCK_PROCESSED),
+                format!("/rewards/{}", PATH_REWARDS),
+            ]
+        );
+
+        let block_payload = &received[0].1;
+        let rewards_payload = &received[2].1;
+        assert_eq!(
+            rewards_payload["index_block_hash"],
+            block_payload["index_block_hash"]
+        );
+        assert_eq!(
+            rewards_payload["block_height"],
+            block_payload["block_height"]
+        );
+        assert_eq!(
+            rewards_payload["matured_miner_rewards"],
+            block_payload["matured_miner_rewards"]
+        );
+        assert_eq!(
+            rewards_payload["matured_miner_rewards"][0]["coinbase_amount"],
+            json!("1000")
+        );
+        assert_eq!(
+            rewards_payload["coinbase"],
+            json!({
+                "txid": format!("0x{}", coinbase_tx.txid()),
+                "miner_address": miner_address.to_account_principal().to_string(),
+                "recipient": recipient.to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_burn_block_reorg_event() {
+        let port = get_random_port();
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+        let num_events = 4;
+
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            for _ in 0..num_events {
+                let mut request = server.recv().unwrap();
+                let mut payload = String::new();
+                request.as_reader().read_to_string(&mut payload).unwrap();
+                let url = request.url().to_string();
+                request.respond(Response::empty(200)).unwrap();
+                tx.send((url, payload)).unwrap();
+            }
+        });
+
+        let mut dispatcher = EventDispatcher::new();
+        for (path, events_keys) in [
+            ("burn", vec![EventKeyType::BurnchainBlocks]),
+            ("reorg", vec![EventKeyType::BurnBlockReorg]),
+        ] {
+            dispatcher
+                .register_observer(
+                    &EventObserverConfig {
+                        endpoint: format!("127.0.0.1:{}/{}", port, path),
+                        events_keys,
+                        timeout_ms: 3_000,
+                        parallel_connections: 1,
+                        ..EventObserverConfig::default()
+                    },
+                    working_dir.clone(),
+                )
+                .unwrap();
+        }
+
+        let block_a = BurnchainHeaderHash([0x0a; 32]);
+        let block_b = BurnchainHeaderHash([0x0b; 32]);
+        let block_c = BurnchainHeaderHash([0x0c; 32]);
+        dispatcher.process_burn_block(&block_a, 100, vec![], 0, vec![]);
+        // announcing the same burn block again sends nothing
+        dispatcher.process_burn_block(&block_a, 100, vec![], 0, vec![]);
+        // a burnchain reorg replaces it
+        dispatcher.process_burn_block(&block_b, 100, vec![], 0, vec![]);
+        dispatcher.process_burn_block(&block_c, 101, vec![], 0, vec![]);
+
+        let received: Vec<(String, serde_json::Value)> = (0..num_events)
+            .map(|_| {
+                let (url, payload) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+                (url, serde_json::from_str(&payload).unwrap())
+            })
+            .collect();
+        let urls: Vec<_> = received.iter().map(|(url, _)| url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                format!("/burn/{}", PATH_BURN_BLOCK_SUBMIT),
+                format!("/reorg/{}", PATH_BURN_BLOCK_REORG),
+                format!("/burn/{}", PATH_BURN_BLOCK_SUBMIT),
+                format!("/burn/{}", PATH_BURN_BLOCK_SUBMIT),
+            ]
+        );
+        let payloads: Vec<_> = received.into_iter().map(|(_, payload)| payload).collect();
+
+        assert_eq!(
+            payloads[0]["burn_block_hash"],
+            json!(format!("0x{}", block_a))
+        );
+        assert_eq!(payloads[0]["is_reorg"], json!(false));
+        assert_eq!(
+            payloads[1],
+            json!({
+                "burn_block_height": 100,
+                "old_burn_block_hash": format!("0x{}", block_a),
+                "new_burn_block_hash": format!("0x{}", block_b),
+            })
+        );
+        assert_eq!(
+            payloads[2]["burn_block_hash"],
+            json!(format!("0x{}", block_b))
+        );
+        assert_eq!(payloads[2]["is_reorg"], json!(true));
+        assert_eq!(
+            payloads[3]["burn_block_hash"],
+            json!(format!("0x{}", block_c))
+        );
+        assert_eq!(payloads[3]["is_reorg"], json!(false));
+    }
+
+    #[test]
+    fn test_tip_changed_event() {
+        let port = get_random_port();
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+        let num_events = 2;
+
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            for _ in 0..num_events {
+                let mut request = server.recv().unwrap();
+                let mut payload = String::new();
+                request.as_reader().read_to_string(&mut payload).unwrap();
+                let url = request.url().to_string();
+                request.respond(Response::empty(200)).unwrap();
+                tx.send((url, payload)).unwrap();
+            }
+        });
+
+        let mut dispatcher = EventDispatcher::new();
+        for (path, events_keys) in [
+            ("all", vec![EventKeyType::AnyEvent]),
+            ("tip", vec![EventKeyType::TipChanged]),
+        ] {
+            // `*` doesn't include tip changes, so the coordinator only looks the tip up once
+            // an observer asks for them
+            assert!(!dispatcher.wants_tip_changes());
+            dispatcher
+                .register_observer(
+                    &EventObserverConfig {
+                        endpoint: format!("127.0.0.1:{}/{}", port, path),
+                        events_keys,
+                        timeout_ms: 3_000,
+                        parallel_connections: 1,
+                        ..EventObserverConfig::default()
+                    },
+                    working_dir.clone(),
+                )
+                .unwrap();
+        }
+
+        assert!(dispatcher.wants_tip_changes());
+
+        let tip_a = StacksBlockId([0x0a; 32]);
+        let tip_b = StacksBlockId([0x0b; 32]);
+        let tip_c = StacksBlockId([0x0c; 32]);
+        // the first tip is only recorded
+        dispatcher.process_canonical_tip(&tip_a, TipChangeCause::NewBlock);
+        dispatcher.process_canonical_tip(&tip_b, TipChangeCause::NewBlock);
+        // an unchanged tip sends nothing
+        dispatcher.process_canonical_tip(&tip_b, TipChangeCause::Sortition);
+        dispatcher.process_canonical_tip(&tip_c, TipChangeCause::BurnReorg);
+
+        let received: Vec<(String, serde_json::Value)> = (0..num_events)
+            .map(|_| {
+                let (url, payload) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+                (url, serde_json::from_str(&payload).unwrap())
+            })
+            .collect();
+        assert_eq!(
+            received,
+            [
+                (
+                    format!("/tip/{}", PATH_TIP_CHANGED),
+                    json!({
+                        "old_tip": format!("0x{}", tip_a),
+                        "new_tip": format!("0x{}", tip_b),
+                        "cause": "new_block",
+                    })
+                ),
+                (
+                    format!("/tip/{}", PATH_TIP_CHANGED),
+                    json!({
+                        "old_tip": format!("0x{}", tip_b),
+                        "new_tip": format!("0x{}", tip_c),
+                        "cause": "burn_reorg",
+                    })
+                ),
+            ]
+        );
+    }
+
+    /// Serve on `port`, holding the response to the first request until `release` is sent
+    /// to. Each request's path and body are sent to `requests` as it arrives.
+    fn spawn_held_server(port: u16, requests: Sender<(String, serde_json::Value)>) -> Sender<()> {
+        let (release_tx, release_rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            let mut held = true;
+            loop {
+                let mut request = server.recv().unwrap();
+                let mut payload = String::new();
+                request.as_reader().read_to_string(&mut payload).unwrap();
+                let url = request.url().to_string();
+                if requests
+                    .send((url, serde_json::from_str(&payload).unwrap()))
+                    .is_err()
+                {
+                    return;
+                }
+                if held {
+                    held = false;
+                    let _ = release_rx.recv();
+                }
+                request.respond(Response::empty(200)).unwrap();
+            }
+        });
+        release_tx
+    }
+
+    #[test]
+    fn test_delivery_queue() {
+        use crate::config::DeliveryQueueConfig;
+
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+        for (when_full, capacity, sent, expected) in [
+            // nothing is dropped while there's room, and everything arrives in order
+            ("drop_newest", 10, 5, vec![0, 1, 2, 3, 4]),
+            // the first payload is being delivered, so the queue holds the next two
+            ("drop_newest", 2, 6, vec![0, 1, 2]),
+            ("drop_oldest", 2, 6, vec![0, 4, 5]),
+        ] {
+            let port = get_random_port();
+            let (tx, rx) = channel();
+            let release = spawn_held_server(port, tx);
+
+            let mut dispatcher = EventDispatcher::new();
+            dispatcher
+                .register_observer(
+                    &EventObserverConfig {
+                        endpoint: format!("127.0.0.1:{}", port),
+                        events_keys: vec![EventKeyType::AnyEvent],
+                        timeout_ms: 10_000,
+                        delivery_queue: Some(DeliveryQueueConfig {
+                            capacity: Some(capacity),
+                            when_full: Some(when_full.into()),
+                        }),
+                        ..EventObserverConfig::default()
+                    },
+                    working_dir.clone(),
+                )
+                .unwrap();
+            let observer = &dispatcher.registered_observers[0];
+
+            // the first payload is taken off the queue, and held up at the observer
+            let start = Instant::now();
+            observer.send_payload(&json!({ "n": 0 }), "test");
+            let (_, payload) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+            assert_eq!(payload, json!({ "n": 0 }));
+            // which doesn't hold up sending the rest
+            for n in 1..sent {
+                observer.send_payload(&json!({ "n": n }), "test");
+            }
+            assert!(start.elapsed() < Duration::from_secs(5));
+
+            release.send(()).unwrap();
+            let mut received = vec![0];
+            while let Ok((url, payload)) = rx.recv_timeout(Duration::from_millis(500)) {
+                assert_eq!(url, "/test");
+                received.push(payload["n"].as_u64().unwrap());
+            }
+            assert_eq!(received, expected, "{}", when_full);
+        }
+    }
+
+    #[test]
+    fn test_node_synced_event() {
+        let port = get_random_port();
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || loop {
+            let mut request = server.recv().unwrap();
+            let mut payload = String::new();
+            request.as_reader().read_to_string(&mut payload).unwrap();
+            let url = request.url().to_string();
+            request.respond(Response::empty(200)).unwrap();
+            if tx.send((url, payload)).is_err() {
+                return;
+            }
+        });
+
+        let mut dispatcher = EventDispatcher::new();
+        for (path, events_keys) in [
+            ("all", vec![EventKeyType::AnyEvent]),
+            ("lifecycle", vec![EventKeyType::Lifecycle]),
+        ] {
+            dispatcher
+                .register_observer(
+                    &EventObserverConfig {
+                        endpoint: format!("127.0.0.1:{}/{}", port, path),
+                        events_keys,
+                        timeout_ms: 3_000,
+                        parallel_connections: 1,
+                        ..EventObserverConfig::default()
+                    },
+                    working_dir.clone(),
+                )
+                .unwrap();
+        }
+
+        assert!(!dispatcher.is_node_synced());
+        dispatcher.process_node_synced(100, 200);
+        assert!(dispatcher.is_node_synced());
+        // the node staying caught up sends nothing more
+        dispatcher.process_node_synced(101, 201);
+
+        let (url, payload) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(url, format!("/lifecycle/{}", PATH_NODE_SYNCED));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&payload).unwrap(),
+            json!({
+                "stacks_tip_height": 100,
+                "burn_block_height": 200,
+            })
+        );
+        // the `any` observer isn't sent lifecycle events
+        assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+
+        // clones share whether it was sent
+        dispatcher.clone().process_node_synced(102, 202);
+        assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+
+        let fresh_dispatcher = EventDispatcher::new();
+        fresh_dispatcher.set_node_synced();
+        assert!(fresh_dispatcher.is_node_synced());
+    }
+
+    #[test]
+    fn test_replace_observers() {
+        let port = get_random_port();
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || loop {
+            let mut request = server.recv().unwrap();
+            let mut payload = String::new();
+            request.as_reader().read_to_string(&mut payload).unwrap();
+            let url = request.url().to_string();
+            request.respond(Response::empty(200)).unwrap();
+            if tx.send(url).is_err() {
+                return;
+            }
+        });
+
+        let make_conf = |path: &str| EventObserverConfig {
+            endpoint: format!("127.0.0.1:{}/{}", port, path),
+            events_keys: vec![EventKeyType::MemPoolTransactions],
+            timeout_ms: 3_000,
+            parallel_connections: 1,
+            send_startup_event: true,
+            ..EventObserverConfig::default()
+        };
+        // each observer is sent every drop that it's routed, in order, before the next one
+        let drop_tx = |dispatcher: &EventDispatcher| {
+            dispatcher.process_dropped_mempool_txs(
+                vec![Txid([1; 32])],
+                MemPoolDropReason::STALE_COLLECT,
+                None,
+            )
+        };
+        let recv_urls = |count| -> Vec<String> {
+            (0..count)
+                .map(|_| rx.recv_timeout(Duration::from_secs(5)).unwrap())
+                .collect()
+        };
+
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher
+            .replace_observers(&[make_conf("a"), make_conf("b")], working_dir.clone())
+            .unwrap();
+        drop_tx(&dispatcher);
+        assert_eq!(
+            recv_urls(4),
+            [
+                format!("/a/{}", PATH_NEW_OBSERVER),
+                format!("/b/{}", PATH_NEW_OBSERVER),
+                format!("/a/{}", PATH_MEMPOOL_TX_DROP),
+                format!("/b/{}", PATH_MEMPOOL_TX_DROP),
+            ]
+        );
+
+        // b is kept as it was, so only c is sent a new_observer event
+        dispatcher
+            .replace_observers(&[make_conf("c"), make_conf("b")], working_dir.clone())
+            .unwrap();
+        drop_tx(&dispatcher);
+        assert_eq!(
+            recv_urls(3),
+            [
+                format!("/c/{}", PATH_NEW_OBSERVER),
+                format!("/c/{}", PATH_MEMPOOL_TX_DROP),
+                format!("/b/{}", PATH_MEMPOOL_TX_DROP),
+            ]
+        );
+        assert_eq!(
+            dispatcher
+                .registered_observers
+                .iter()
+                .map(|observer| observer.endpoint.clone())
+                .collect::<Vec<_>>(),
+            [
+                format!("127.0.0.1:{}/c", port),
+                format!("127.0.0.1:{}/b", port)
+            ]
+        );
+
+        // a replacement with an invalid observer leaves the observers as they were
+        let invalid_conf = EventObserverConfig {
+            strict_block_ordering: true,
+            parallel_connections: 2,
+            ..make_conf("d")
+        };
+        assert!(dispatcher
+            .replace_observers(&[make_conf("a"), invalid_conf], working_dir.clone())
+            .is_err());
+        dispatcher.set_max_observers(1);
+        assert!(dispatcher
+            .replace_observers(&[make_conf("a"), make_conf("b")], working_dir.clone())
+            .is_err());
+        drop_tx(&dispatcher);
+        assert_eq!(
+            recv_urls(2),
+            [
+                format!("/c/{}", PATH_MEMPOOL_TX_DROP),
+                format!("/b/{}", PATH_MEMPOOL_TX_DROP),
+            ]
+        );
+
+        // replacing the observers with none stops all events
+        dispatcher.replace_observers(&[], working_dir).unwrap();
+        drop_tx(&dispatcher);
+        assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+    }
+
+    #[test]
+    fn test_microblock_tip_event() {
+        use stacks::chainstate::stacks::StacksMicroblockHeader;
+
+        let port = get_random_port();
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+        let num_events = 3;
+
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            for _ in 0..num_events {
+                let mut request = server.recv().unwrap();
+                let mut payload = String::new();
+                request.as_reader().read_to_string(&mut payload).unwrap();
+                let url = request.url().to_string();
+                request.respond(Response::empty(200)).unwrap();
+                tx.send((url, payload)).unwrap();
+            }
+        });
+
+        let mut dispatcher = EventDispatcher::new();
+        for (path, events_keys) in [
+            ("all", vec![EventKeyType::AnyEvent]),
+            ("tip", vec![EventKeyType::MicroblockTip]),
+        ] {
+            dispatcher
+                .register_observer(
+                    &EventObserverConfig {
+                        endpoint: format!("127.0.0.1:{}/{}", port, path),
+                        events_keys,
+                        timeout_ms: 3_000,
+                        parallel_connections: 1,
+                        ..EventObserverConfig::default()
+                    },
+                    working_dir.clone(),
+                )
+                .unwrap();
+        }
+
+        dispatcher
+            .process_new_microblocks(StacksBlockId([2; 32]), ProcessedUnconfirmedState::default());
+
+        let first = StacksMicroblockHeader::first_unsigned(
+            &BlockHeaderHash([1; 32]),
+            &Sha512Trunc256Sum([0; 32]),
+        );
+        let second =
+            StacksMicroblockHeader::from_parent_unsigned(&first, &Sha512Trunc256Sum([0; 32]))
+                .unwrap();
+        let processed_unconfirmed_state = ProcessedUnconfirmedState {
+            receipts: vec![
+                (0, first, make_stx_transfer_receipts(1, 1)),
+                (1, second.clone(), make_stx_transfer_receipts(1, 1)),
+            ],
+            ..ProcessedUnconfirmedState::default()
+        };
+        dispatcher.process_new_microblocks(StacksBlockId([3; 32]), processed_unconfirmed_state);
+
+        let received: Vec<(String, serde_json::Value)> = (0..num_events)
+            .map(|_| {
+                let (url, payload) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+                (url, serde_json::from_str(&payload).unwrap())
+            })
+            .collect();
+        // `*` observers only get the full payloads, and there is no tip without microblocks
+        assert_eq!(received[0].0, format!("/all/{}", PATH_MICROBLOCK_SUBMIT));
+        assert_eq!(received[0].1["transactions"], json!([]));
+        assert_eq!(
+            received[1],
+            (
+                format!("/tip/{}", PATH_MICROBLOCK_TIP),
+                json!({
+                    "parent_index_block_hash": format!("0x{}", StacksBlockId([3; 32])),
+                    "microblock_sequence": 1,
+                    "microblock_hash": format!("0x{}", second.block_hash()),
+                })
+            )
+        );
+        assert_eq!(received[2].0, format!("/all/{}", PATH_MICROBLOCK_SUBMIT));
+        assert_eq!(received[2].1["transactions"].as_array().unwrap().len(), 2);
+        // events are numbered across the whole stream, in the order they happened
+        let event_indexes: Vec<_> = received[2].1["events"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|event| event["event_index"].as_u64().unwrap())
+            .collect();
+        assert_eq!(event_indexes, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_block_fork_event() {
+        let port = get_random_port();
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            for _ in 0..5 {
+                let mut request = server.recv().unwrap();
+                let mut payload = String::new();
+                request.as_reader().read_to_string(&mut payload).unwrap();
+                let url = request.url().to_string();
+                request.respond(Response::empty(200)).unwrap();
+                tx.send((url, payload)).unwrap();
+            }
+        });
+
+        let mut dispatcher = EventDispatcher::new();
+        for (path, events_keys) in [
+            ("blocks", vec![EventKeyType::AnyEvent]),
+            ("forks", vec![EventKeyType::BlockFork]),
+        ] {
+            dispatcher
+                .register_observer(
+                    &EventObserverConfig {
+                        endpoint: format!("127.0.0.1:{}/{}", port, path),
+                        events_keys,
+                        timeout_ms: 3_000,
+                        ..EventObserverConfig::default()
+                    },
+                    working_dir.clone(),
+                )
+                .unwrap();
+        }
+
+        // two blocks in the same tenure, at the same height
+        let parent_block_id = StacksBlockId([0x01; 32]);
+        let mut header_a = NakamotoBlockHeader::empty();
+        header_a.chain_length = 10;
+        header_a.parent_block_id = parent_block_id.clone();
+        let mut header_b = header_a.clone();
+        header_b.timestamp += 1;
+        for header in [&header_a, &header_b] {
+            let block = NakamotoBlock {
+                header: header.clone(),
+                txs: vec![],
+            };
+            let mut metadata = StacksHeaderInfo::regtest_genesis();
+            metadata.stacks_block_height = header.chain_length;
+            metadata.anchored_header = StacksBlockHeaderTypes::Nakamoto(header.clone());
+            dispatcher.process_chain_tip(
+                &StacksBlockEventData::from((block, BlockHeaderHash([0; 32]))),
+                &metadata,
+                &[],
+                &parent_block_id,
+                Txid([0; 32]),
+                &[],
+                None,
+                BurnchainHeaderHash([0; 32]),
+                0,
+                0,
+                &ExecutionCost::zero(),
+                &ExecutionCost::zero(),
+                &PoxConstants::testnet_default(),
+                &None,
+                &None,
+                None,
+                0,
+                &BlockProcessingTimings::default(),
+            );
+        }
+
+        let received: Vec<(String, serde_json::Value)> = (0..4)
+            .map(|_| {
+                let (url, payload) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+                (url, serde_json::from_str(&payload).unwrap())
+            })
+            .collect();
+        let urls: Vec<_> = received.iter().map(|(url, _)| url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                format!("/blocks/{}", PATH_BLOCK_PROCESSED),
+                format!("/forks/{}", PATH_BLOCK_PROCESSED),
+                format!("/blocks/{}", PATH_BLOCK_PROCESSED),
+                format!("/forks/{}", PATH_BLOCK_PROCESSED),
+            ]
+        );
+        // the fork event follows the second block's `new_block` event
+        let (url, payload) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(url, format!("/forks/{}", PATH_BLOCK_FORK));
+        let payload: serde_json::Value = serde_json::from_str(&payload).unwrap();
+        assert_eq!(
+            payload,
+            json!({
+                "consensus_hash": format!("0x{}", header_b.consensus_hash),
+                "block_height": 10,
+                "block_id": format!("0x{}", header_b.block_id()),
+                "sibling_block_id": format!("0x{}", header_a.block_id()),
+                // there's no sortition DB to find the canonical tip in
+                "canonical_block_id": null,
+            })
+        );
+    }
+
+    #[test]
+    fn test_raw_header() {
+        use stacks::chainstate::stacks::StacksBlockHeader;
+        use stacks_common::util::hash::hex_bytes;
+
+        let port = get_random_port();
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            for _ in 0..4 {
+                let mut request = server.recv().unwrap();
+                let mut payload = String::new();
+                request.as_reader().read_to_string(&mut payload).unwrap();
+                let url = request.url().to_string();
+                request.respond(Response::empty(200)).unwrap();
+                tx.send((url, payload)).unwrap();
+            }
+        });
+
+        let mut dispatcher = EventDispatcher::new();
+        for (path, include_raw_header) in [("raw", true), ("plain", false)] {
+            dispatcher
+                .register_observer(
+                    &EventObserverConfig {
+                        endpoint: format!("127.0.0.1:{}/{}", port, path),
+                        events_keys: vec![EventKeyType::AnyEvent],
+                        timeout_ms: 3_000,
+                        include_raw_header,
+                        ..EventObserverConfig::default()
+                    },
+                    working_dir.clone(),
+                )
+                .unwrap();
+        }
+
+        let mut nakamoto_metadata = StacksHeaderInfo::regtest_genesis();
+        let mut nakamoto_header = NakamotoBlockHeader::empty();
+        nakamoto_header.chain_length = 10;
+        nakamoto_header.consensus_hash = nakamoto_metadata.consensus_hash.clone();
+        nakamoto_header.timestamp = 123456;
+        nakamoto_header.miner_signature = MessageSignature::from_bytes(&[0x01; 65]).unwrap();
+        nakamoto_header.signer_signature = vec![MessageSignature::from_bytes(&[0x02; 65]).unwrap()];
+        nakamoto_metadata.stacks_block_height = nakamoto_header.chain_length;
+        nakamoto_metadata.anchored_header =
+            StacksBlockHeaderTypes::Nakamoto(nakamoto_header.clone());
+        let nakamoto_block = StacksBlockEventData::from((
+            NakamotoBlock {
+                header: nakamoto_header.clone(),
+                txs: vec![],
+            },
+            BlockHeaderHash([0; 32]),
+        ));
+
+        let mut epoch2_metadata = StacksHeaderInfo::regtest_genesis();
+        let mut epoch2_header = StacksBlockHeader::genesis_block_header();
+        epoch2_header.parent_block = BlockHeaderHash([0x03; 32]);
+        epoch2_header.parent_microblock_sequence = 4;
+        epoch2_metadata.stacks_block_height = 5;
+        epoch2_metadata.anchored_header = StacksBlockHeaderTypes::Epoch2(epoch2_header.clone());
+        let epoch2_block = StacksBlockEventData::from(StacksBlock {
+            header: epoch2_header.clone(),
+            txs: vec![],
+        });
+
+        for (block, metadata) in [
+            (&nakamoto_block, &nakamoto_metadata),
+            (&epoch2_block, &epoch2_metadata),
+        ] {
+            dispatcher.process_chain_tip(
+                block,
+                metadata,
+                &[],
+                &StacksBlockId([0; 32]),
+                Txid([0; 32]),
+                &[],
+                None,
+                BurnchainHeaderHash([0; 32]),
+                0,
+                0,
+                &ExecutionCost::zero(),
+                &ExecutionCost::zero(),
+                &PoxConstants::testnet_default(),
+                &None,
+                &None,
+                None,
+                0,
+                &BlockProcessingTimings::default(),
+            );
+        }
+
+        let mut received: Vec<(String, serde_json::Value)> = (0..4)
+            .map(|_| {
+                let (url, payload) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+                (url, serde_json::from_str(&payload).unwrap())
+            })
+            .collect();
+        // only the observer that asked for it gets the raw header
+        for (url, payload) in received.iter() {
+            assert_eq!(
+                payload.get("raw_header").is_some(),
+                url.starts_with("/raw/"),
+                "{}",
+                url
+            );
+        }
+        received.retain(|(url, _)| url.starts_with("/raw/"));
+        let raw_header = |payload: &serde_json::Value| {
+            let hex = payload["raw_header"].as_str().unwrap();
+            hex_bytes(hex.strip_prefix("0x").unwrap()).unwrap()
+        };
+
+        // the raw header deserializes back to the block's header, which agrees with the
+        // payload's decoded fields
+        let payload = &received[0].1;
+        let header =
+            NakamotoBlockHeader::consensus_deserialize(&mut &raw_header(payload)[..]).unwrap();
+        assert_eq!(header, nakamoto_header);
+        assert_eq!(payload["block_hash"], format!("0x{}", header.block_hash()));
+        assert_eq!(
+            payload["index_block_hash"],
+            format!("0x{}", header.block_id())
+        );
+        assert_eq!(payload["block_height"], header.chain_length);
+        assert_eq!(
+            payload["miner_signature"],
+            format!("0x{}", header.miner_signature)
+        );
+        assert_eq!(
+            payload["signer_signature_hash"],
+            format!("0x{}", header.signer_signature_hash())
+        );
+
+        let payload = &received[1].1;
+        let header =
+            StacksBlockHeader::consensus_deserialize(&mut &raw_header(payload)[..]).unwrap();
+        assert_eq!(header, epoch2_header);
+        assert_eq!(payload["block_hash"], format!("0x{}", header.block_hash()));
+        assert_eq!(
+            payload["parent_block_hash"],
+            format!("0x{}", header.parent_block)
+        );
+        assert_eq!(
+            payload["parent_microblock"],
+            format!("0x{}", header.parent_microblock)
+        );
+        assert_eq!(
+            payload["parent_microblock_sequence"],
+            header.parent_microblock_sequence
+        );
+    }
+
+    #[test]
+    fn test_stackerdb_equivocation_event() {
+        let port = get_random_port();
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            for _ in 0..2 {
+                let mut request = server.recv().unwrap();
+                let mut payload = String::new();
+                request.as_reader().read_to_string(&mut payload).unwrap();
+                let url = request.url().to_string();
+                request.respond(Response::empty(200)).unwrap();
+                tx.send((url, payload)).unwrap();
+            }
+        });
+
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher
+            .register_observer(
+                &EventObserverConfig {
+                    endpoint: format!("127.0.0.1:{}", port),
+                    events_keys: vec![EventKeyType::StackerDBEquivocation],
+                    timeout_ms: 3_000,
+                    ..EventObserverConfig::default()
+                },
+                working_dir,
+            )
+            .unwrap();
+
+        let contract_id = boot_code_id("signers-0-0", false);
+        let stored = StackerDBChunkData::new(0, 1, vec![1]);
+        let conflicting = StackerDBChunkData::new(0, 1, vec![2]);
+        dispatcher.process_new_stackerdb_chunks(contract_id.clone(), vec![stored.clone()]);
+
+        // the same chunk again, and chunks at versions that weren't stored, are not equivocation
+        dispatcher.stale_stackerdb_chunks(
+            contract_id.clone(),
+            vec![
+                stored.clone(),
+                StackerDBChunkData::new(0, 2, vec![2]),
+                StackerDBChunkData::new(1, 1, vec![2]),
+            ],
+        );
+        dispatcher.stale_stackerdb_chunks(contract_id.clone(), vec![conflicting.clone()]);
+
+        let (url, payload) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(url, format!("/{}", PATH_STACKERDB_EQUIVOCATION));
+        let payload: serde_json::Value = serde_json::from_str(&payload).unwrap();
+        assert_eq!(
+            payload,
+            json!({
+                "contract_id": contract_id.to_string(),
+                "slot_id": 0,
+                "slot_version": 1,
+                "stored_chunk": {
+                    "data_hash": Sha512Trunc256Sum::from_data(&stored.data).to_hex(),
+                    "signature": stored.sig,
+                },
+                "conflicting_chunk": {
+                    "slot_id": 0,
+                    "slot_version": 1,
+                    "signature": conflicting.sig,
+                    "data": "02",
+                    "data_hash": Sha512Trunc256Sum::from_data(&conflicting.data).to_hex(),
+                },
+            })
+        );
+        // nothing else was sent
+        assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+    }
+
+    #[test]
+    fn test_block_processed_event_nakamoto() {
+        let observer = EventObserver::new(None, "nowhere".to_string(), Duration::from_secs(3));
+
+        let filtered_events = vec![];
+        let mut block_header = NakamotoBlockHeader::empty();
+        let signer_signature = vec![
+            MessageSignature::from_bytes(&[0; 65]).unwrap(),
+            MessageSignature::from_bytes(&[1; 65]).unwrap(),
+        ];
+        block_header.signer_signature = signer_signature.clone();
+        let block = NakamotoBlock {
+            header: block_header.clone(),
+            txs: vec![],
+        };
+        let mut metadata = StacksHeaderInfo::regtest_genesis();
+        metadata.anchored_header = StacksBlockHeaderTypes::Nakamoto(block_header.clone());
+        let receipts = vec![];
+        let parent_index_hash = StacksBlockId([0; 32]);
+        let winner_txid = Txid([0; 32]);
+        let mature_rewards = serde_json::Value::Array(vec![]);
+        let parent_burn_block_hash = BurnchainHeaderHash([0; 32]);
+        let parent_burn_block_height = 0;
+        let parent_burn_block_timestamp = 0;
+        let anchored_consumed = ExecutionCost::zero();
+        let mblock_confirmed_consumed = ExecutionCost::zero();
+        let pox_constants = PoxConstants::testnet_default();
+        let signer_bitvec = BitVec::zeros(2).expect("Failed to create BitVec with length 2");
+        let block_timestamp = Some(123456);
+        let coinbase_height = 1234;
+        let processed_at = metadata.burn_header_timestamp + 42;
+
+        let payload = observer.make_new_block_processed_payload(&TestNewBlock {
+            filtered_events,
+            block: StacksBlockEventData::from((block, BlockHeaderHash([0; 32]))),
+            metadata,
+            receipts: &receipts,
+            parent_index_hash,
+            winner_txid,
+            mature_rewards,
+            parent_burn_block_hash,
+            parent_burn_block_height,
+            parent_burn_block_timestamp,
+            anchored_consumed,
+            mblock_confirmed_consumed,
+            pox_constants,
+            signer_bitvec: Some(signer_bitvec),
+            block_timestamp,
+            coinbase_height,
+            processed_at,
+            ..TestNewBlock::default()
+        });
+
+        let event_signer_signature = payload
+            .get("signer_signature")
+            .unwrap()
+            .as_array()
+            .expect("Expected signer_signature to be an array")
+            .iter()
+            .cloned()
+            .map(serde_json::from_value::<MessageSignature>)
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Unable to deserialize array of MessageSignature");
+        assert_eq!(event_signer_signature, signer_signature);
+    }
+
+    #[test]
+    fn test_stackerdb_channel_deactivates_on_dropped_receiver() {
+        let stackerdb_channel = StackerDBChannel::new();
+        let contract_id = boot_code_id(SIGNERS_NAME, false);
+        let make_event = || StackerDBChunksEvent {
+            contract_id: contract_id.clone(),
+            modified_slots: vec![],
+        };
+
+        let (receiver, replaced_other) = stackerdb_channel.register_miner_coordinator();
+        assert!(!replaced_other);
+
+        // simulate the miner thread exiting
+        drop(receiver);
+
+        let sender = stackerdb_channel
+            .is_active(&contract_id)
+            .expect("Channel should be active");
+        let SendError(event) = sender.send(make_event()).unwrap_err();
+        assert!(stackerdb_channel.deactivate_if_disconnected(event));
+        assert!(stackerdb_channel.is_active(&contract_id).is_none());
+
+        // nothing left to deactivate
+        assert!(!stackerdb_channel.deactivate_if_disconnected(make_event()));
+
+        // a receiver that registered in the meantime gets the event instead
+        let (receiver, _) = stackerdb_channel.register_miner_coordinator();
+        assert!(!stackerdb_channel.deactivate_if_disconnected(make_event()));
+        assert!(stackerdb_channel.is_active(&contract_id).is_some());
+        assert_eq!(receiver.try_recv().unwrap().contract_id, contract_id);
+    }
+
+    #[test]
+    fn test_canonical_json_bytes() {
+        let payload_a: serde_json::Value = serde_json::from_str(
+            r#"{"z":{"b":1.0,"a":[2e0,"x",-0]},"amt":340282366920938463463374607431768211455,"f":0.25}"#,
+        )
+        .unwrap();
+        let payload_b: serde_json::Value = serde_json::from_str(
+            r#"{"f":2.5e-1,"amt":340282366920938463463374607431768211455,"z":{"a":[2,"x",0],"b":1}}"#,
+        )
+        .unwrap();
+
+        let expected =
+            r#"{"amt":340282366920938463463374607431768211455,"f":0.25,"z":{"a":[2,"x",0],"b":1}}"#;
+        assert_eq!(
+            std::str::from_utf8(&canonical_json_bytes(&payload_a)).unwrap(),
+            expected
+        );
+        assert_eq!(
+            canonical_json_bytes(&payload_a),
+            canonical_json_bytes(&payload_b)
+        );
+
+        // canonicalization is idempotent, the bytes are those of the canonical form, and
+        // repeated runs give identical bytes
+        let canonical = canonicalize_payload(&payload_a);
+        assert_eq!(canonicalize_payload(&canonical), canonical);
+        assert_eq!(
+            canonical_json_bytes(&payload_a),
+            serde_json::to_vec(&canonical).unwrap()
+        );
+        for _ in 0..10 {
+            assert_eq!(
+                canonical_json_bytes(&payload_b).as_slice(),
+                expected.as_bytes()
+            );
+        }
+    }
+
+    #[test]
+    fn test_block_txs_payload_raw_burnchain_op() {
+        use stacks::chainstate::burn::operations::TransferStxOp;
+        use stacks_common::types::chainstate::StacksAddress;
+
+        let op = BlockstackOperationType::TransferStx(TransferStxOp {
+            sender: StacksAddress::burn_address(false),
+            recipient: StacksAddress::burn_address(true),
+            transfered_ustx: 123,
+            memo: vec![0x01, 0x02],
+            txid: Txid([0x11; 32]),
+            vtxindex: 4,
+            block_height: 100,
+            burn_header_hash: BurnchainHeaderHash([0x22; 32]),
+        });
+        let receipt = StacksTransactionReceipt {
+            transaction: TransactionOrigin::Burn(op),
+            events: vec![],
+            post_condition_aborted: false,
+            result: Value::okay_true(),
+            stx_burned: 0,
+            contract_analysis: None,
+            execution_cost: ExecutionCost::zero(),
+            microblock_header: None,
+            tx_index: 0,
+            vm_error: None,
+        };
+
+        let observer = EventObserver::new(None, "127.0.0.1:3700".into(), Duration::from_secs(1));
+        let payload = observer.make_observer_block_txs_payload(&receipt, 0);
+        assert_eq!(payload["raw_tx"], json!("0x00"));
+        assert!(payload.get("raw_burnchain_op").is_none());
+
+        let observer = observer.with_raw_burnchain_op(true);
+        let payload = observer.make_observer_block_txs_payload(&receipt, 0);
+        // opcode '$', then the big-endian amount and the memo
+        let mut expected = vec![b'$'];
+        expected.extend(123u128.to_be_bytes());
+        expected.extend([0x01, 0x02]);
+        assert_eq!(
+            payload["raw_burnchain_op"],
+            json!(format!("0x{}", bytes_to_hex(&expected)))
+        );
+        assert_eq!(
+            payload["burnchain_op"]["transfer_stx"]["burn_txid"],
+            json!(Txid([0x11; 32]))
+        );
+
+        // Stacks transactions carry their bytes in `raw_tx` already
+        let receipt = make_stx_transfer_receipts(1, 0).pop().unwrap();
+        let payload = observer.make_observer_block_txs_payload(&receipt, 0);
+        assert!(payload.get("raw_burnchain_op").is_none());
+    }
+
+    #[test]
+    fn test_block_txs_payload_sponsor() {
+        use clarity::vm::types::PrincipalData;
+        use stacks::chainstate::stacks::{TokenTransferMemo, TransactionAuth, TransactionVersion};
+        use stacks_common::types::chainstate::StacksPrivateKey;
+
+        let origin_auth = TransactionAuth::from_p2pkh(&StacksPrivateKey::new()).unwrap();
+        let sponsor_auth = TransactionAuth::from_p2pkh(&StacksPrivateKey::new()).unwrap();
+        let recipient = PrincipalData::from(boot_code_id("pox-4", false).issuer);
+        let mut tx = StacksTransaction::new(
+            TransactionVersion::Testnet,
+            origin_auth.into_sponsored(sponsor_auth).unwrap(),
+            TransactionPayload::TokenTransfer(recipient, 1, TokenTransferMemo([0u8; 34])),
+        );
+        tx.set_tx_fee(180);
+        let sponsor_address = tx.sponsor_address().unwrap();
+
+        let receipt = StacksTransactionReceipt::from_stx_transfer(
+            tx,
+            vec![],
+            Value::okay_true(),
+            ExecutionCost::zero(),
+        );
+        let payload = EventObserver::make_new_block_txs_payload(&receipt, 0);
+        assert_eq!(payload["sponsored"], json!(true));
+        assert_eq!(
+            payload["sponsor_address"],
+            json!(sponsor_address.to_string())
+        );
+        assert_eq!(payload["sponsor_fee"], json!(180));
+
+        // a standard transaction has no sponsor
+        let receipt = make_stx_transfer_receipts(1, 0).pop().unwrap();
+        let payload = EventObserver::make_new_block_txs_payload(&receipt, 0);
+        assert_eq!(payload["sponsored"], json!(false));
+        assert_eq!(payload["sponsor_address"], json!(null));
+        assert_eq!(payload["sponsor_fee"], json!(null));
+    }
+
+    #[test]
+    fn test_block_txs_payload_post_condition_failure() {
+        use stacks::chainstate::stacks::{
+            AssetInfo, FungibleConditionCode, PostConditionPrincipal, TransactionPostCondition,
+        };
+
+        let mut receipt = make_stx_transfer_receipts(1, 0).pop().unwrap();
+        let payload = EventObserver::make_new_block_txs_payload(&receipt, 0);
+        assert_eq!(payload["status"], json!("success"));
+        assert_eq!(payload["post_condition_failure"], json!(null));
+
+        let TransactionOrigin::Stacks(tx) = &mut receipt.transaction else {
+            panic!("Expected a Stacks transaction");
+        };
+        let contract_address = tx.origin_address();
+        tx.post_condition_mode = TransactionPostConditionMode::Deny;
+        tx.post_conditions = vec![
+            TransactionPostCondition::STX(
+                PostConditionPrincipal::Origin,
+                FungibleConditionCode::SentEq,
+                1,
+            ),
+            TransactionPostCondition::Fungible(
+                PostConditionPrincipal::Origin,
+                AssetInfo {
+                    contract_address,
+                    contract_name: "token".into(),
+                    asset_name: "token".into(),
+                },
+                FungibleConditionCode::SentGe,
+                10,
+            ),
+        ];
+        receipt.post_condition_aborted = true;
+        receipt.result = Value::err_none();
+
+        let payload = EventObserver::make_new_block_txs_payload(&receipt, 0);
+        assert_eq!(payload["status"], json!("abort_by_post_condition"));
+        assert_eq!(
+            payload["post_condition_failure"],
+            json!({ "post_condition_mode": "deny", "post_condition_count": 2 })
+        );
+    }
+
+    #[test]
+    fn test_send_request_connect_timeout() {
+        let timeout_duration = Duration::from_secs(3);
+
+        // Start measuring time
+        let start_time = Instant::now();
+
+        let host = "10.255.255.1"; // non-routable IP for timeout
+        let port = 80;
+
+        let peerhost: PeerHost = format!("{host}:{port}")
+            .parse()
+            .unwrap_or(PeerHost::DNS(host.to_string(), port));
+        let mut request = StacksHttpRequest::new_for_peer(
+            peerhost,
+            "POST".into(),
+            "/".into(),
+            HttpRequestContents::new().payload_json(serde_json::from_slice(b"{}").unwrap()),
+        )
+        .unwrap_or_else(|_| panic!("FATAL: failed to encode infallible data as HTTP request"));
+        request.add_header("Connection".into(), "close".into());
+
+        // Attempt to send a request with a timeout
+        let result = send_http_request(host, port, request, timeout_duration);
+
+        // Measure the elapsed time
+        let elapsed_time = start_time.elapsed();
+
+        // Assert that the connection attempt timed out
+        assert!(
+            result.is_err(),
+            "Expected a timeout error, but got {:?}",
+            result
+        );
+        match result.unwrap_err() {
+            SendRequestError::Connect(e) => assert_eq!(
+                e.kind(),
+                std::io::ErrorKind::TimedOut,
+                "Expected a TimedOut error"
+            ),
+            e => panic!("Expected a connection error, but got {:?}", e),
+        }
+
+        // Assert that the elapsed time is within an acceptable range
+        assert!(
+            elapsed_time >= timeout_duration,
+            "Timeout occurred too quickly"
+        );
+        assert!(
+            elapsed_time < timeout_duration + Duration::from_secs(1),
+            "Timeout took too long"
+        );
+    }
+
+    fn get_random_port() -> u16 {
+        // Bind to a random port by specifying port 0, then retrieve the port assigned by the OS
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind to a random port");
+        listener.local_addr().unwrap().port()
+    }
+
+    #[test]
+    fn test_init_db() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_init_db.sqlite");
+        let db_path_str = db_path.to_str().unwrap();
+
+        // Call init_db
+        let conn_result = EventObserver::init_db(db_path_str);
+        assert!(conn_result.is_ok(), "Failed to initialize the database");
+
+        // Check that the database file exists
+        assert!(db_path.exists(), "Database file was not created");
+
+        // Check that the table exists
+        let conn = conn_result.unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT name FROM sqlite_master WHERE type='table' AND name='pending_payloads'",
+            )
+            .unwrap();
+        let table_exists = stmt.exists([]).unwrap();
+        assert!(table_exists, "Table 'pending_payloads' does not exist");
+
+        // a database from before payloads had a delivery state is migrated, and its payloads
+        // are still pending
+        let old_db_path = dir.path().join("test_init_old_db.sqlite");
+        let old_db_path_str = old_db_path.to_str().unwrap();
+        Connection::open(old_db_path_str)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE pending_payloads (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    url TEXT NOT NULL,
+                    payload TEXT NOT NULL,
+                    timeout INTEGER NOT NULL
+                );
+                INSERT INTO pending_payloads (url, payload, timeout)
+                    VALUES ('http://example.com/api', '{}', 1000);",
+            )
+            .unwrap();
+        let conn = EventObserver::init_db(old_db_path_str).unwrap();
+        assert_eq!(EventObserver::get_pending_payloads(&conn).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_insert_and_get_pending_payloads() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_payloads.sqlite");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let conn = EventObserver::init_db(db_path_str).expect("Failed to initialize the database");
+
+        let url = "http://example.com/api";
+        let payload = json!({"key": "value"});
+        let timeout = Duration::from_secs(5);
+
+        // Insert payload
+        let insert_result =
+            EventObserver::insert_payload(&conn, "http://example.com", url, &payload, timeout);
+        assert!(insert_result.is_ok(), "Failed to insert payload");
+
+        // Get pending payloads
+        let pending_payloads =
+            EventObserver::get_pending_payloads(&conn).expect("Failed to get pending payloads");
+        assert_eq!(pending_payloads.len(), 1, "Expected one pending payload");
+
+        let (_id, retrieved_url, retrieved_payload, timeout_ms) = &pending_payloads[0];
+        assert_eq!(retrieved_url, url, "URL does not match");
+        assert_eq!(retrieved_payload, &payload, "Payload does not match");
+        assert_eq!(
+            *timeout_ms,
+            timeout.as_millis() as u64,
+            "Timeout does not match"
+        );
+    }
+
+    #[test]
+    fn test_delete_payload() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_delete_payload.sqlite");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let conn = EventObserver::init_db(db_path_str).expect("Failed to initialize the database");
+
+        let url = "http://example.com/api";
+        let payload = json!({"key": "value"});
+        let timeout = Duration::from_secs(5);
+
+        // Insert payload
+        EventObserver::insert_payload(&conn, "http://example.com", url, &payload, timeout)
+            .expect("Failed to insert payload");
+
+        // Get pending payloads
+        let pending_payloads =
+            EventObserver::get_pending_payloads(&conn).expect("Failed to get pending payloads");
+        assert_eq!(pending_payloads.len(), 1, "Expected one pending payload");
+
+        let (id, _, _, _) = pending_payloads[0];
+
+        // Delete payload
+        let delete_result = EventObserver::delete_payload(&conn, id);
+        assert!(delete_result.is_ok(), "Failed to delete payload");
+
+        // Verify that the pending payloads list is empty
+        let pending_payloads =
+            EventObserver::get_pending_payloads(&conn).expect("Failed to get pending payloads");
+        assert_eq!(pending_payloads.len(), 0, "Expected no pending payloads");
+    }
+
+    #[test]
+    fn test_process_pending_payloads() {
+        use mockito::Matcher;
+
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_process_payloads.sqlite");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let conn = EventObserver::init_db(db_path_str).expect("Failed to initialize the database");
+
+        let payload = json!({"key": "value"});
+        let timeout = Duration::from_secs(5);
+
+        // Create a mock server
+        let mut server = mockito::Server::new();
+        let _m = server
+            .mock("POST", "/api")
+            .match_header("content-type", Matcher::Regex("application/json.*".into()))
+            .match_body(Matcher::Json(payload.clone()))
+            .with_status(200)
+            .create();
+
+        let url = &format!("{}/api", &server.url());
+
+        // Insert payload
+        EventObserver::insert_payload(&conn, &server.url(), url, &payload, timeout)
+            .expect("Failed to insert payload");
+
+        // Process pending payloads
+        EventObserver::process_pending_payloads(
+            &conn,
+            &server.url(),
+            1,
+            None,
+            &DeliverySettings::default(),
+        );
+
+        // Verify that the pending payloads list is empty
+        let pending_payloads =
+            EventObserver::get_pending_payloads(&conn).expect("Failed to get pending payloads");
+        assert_eq!(pending_payloads.len(), 0, "Expected no pending payloads");
+
+        // Verify that the mock was called
+        _m.assert();
+    }
+
+    #[test]
+    fn test_legacy_pending_payloads() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_legacy_payloads.sqlite");
+        let conn = EventObserver::init_db(db_path.to_str().unwrap()).unwrap();
+
+        // payloads spooled before they recorded their observer are matched to one by URL, and
+        // an observer whose endpoint is under another's doesn't take the other's payloads
+        let timeout = Duration::from_secs(5);
+        for url in [
+            "http://h:1/new_block",
+            "http://h:1/attachments/new",
+            "http://h:1/sub/new_block",
+            "http://h:10/new_block",
+        ] {
+            EventObserver::insert_payload(&conn, "", url, &json!({}), timeout).unwrap();
+        }
+        conn.execute("UPDATE pending_payloads SET observer = NULL", [])
+            .unwrap();
+
+        let urls = |observer: &str| -> Vec<String> {
+            EventObserver::get_observer_pending_payloads(&conn, observer)
+                .unwrap()
+                .into_iter()
+                .map(|(_, url, ..)| url)
+                .collect()
+        };
+        assert_eq!(
+            urls("http://h:1"),
+            vec!["http://h:1/new_block", "http://h:1/attachments/new"]
+        );
+        assert_eq!(urls("http://h:1/sub"), vec!["http://h:1/sub/new_block"]);
+        assert_eq!(urls("http://h:10"), vec!["http://h:10/new_block"]);
+        assert_eq!(
+            EventObserver::count_pending_payloads(&conn, "http://h:1").unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_process_pending_payloads_in_parallel() {
+        use mockito::Matcher;
+
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_process_payloads_in_parallel.sqlite");
+        let db_path_str = db_path.to_str().unwrap();
+
+        let conn = EventObserver::init_db(db_path_str).expect("Failed to initialize the database");
+
+        let timeout = Duration::from_secs(5);
+
+        let mut server = mockito::Server::new();
+        let parallel_mock = server
+            .mock("POST", "/parallel/api")
+            .match_header("content-type", Matcher::Regex("application/json.*".into()))
+            .with_status(200)
+            .expect(10)
+            .create();
+        let serial_mock = server
+            .mock("POST", "/serial/api")
+            .match_body(Matcher::Json(json!({"serial": true})))
+            .with_status(200)
+            .expect(1)
+            .create();
+
+        // payloads for another observer are interleaved with the parallel observer's payloads
+        let parallel_observer = format!("{}/parallel", &server.url());
+        let serial_observer = format!("{}/serial", &server.url());
+        for i in 0..10 {
+            EventObserver::insert_payload(
+                &conn,
+                &parallel_observer,
+                &format!("{}/api", parallel_observer),
+                &json!({ "i": i }),
+                timeout,
+            )
+            .expect("Failed to insert payload");
+            if i == 5 {
+                EventObserver::insert_payload(
+                    &conn,
+                    &serial_observer,
+                    &format!("{}/api", serial_observer),
+                    &json!({"serial": true}),
+                    timeout,
+                )
+                .expect("Failed to insert payload");
+            }
+        }
+
+        // the parallel observer only sends its own payloads
+        EventObserver::process_pending_payloads(
+            &conn,
+            &parallel_observer,
+            4,
+            None,
+            &DeliverySettings::default(),
+        );
+        let pending_payloads =
+            EventObserver::get_pending_payloads(&conn).expect("Failed to get pending payloads");
+        assert_eq!(pending_payloads.len(), 1, "Expected one pending payload");
+        assert_eq!(pending_payloads[0].2, json!({"serial": true}));
+
+        EventObserver::process_pending_payloads(
+            &conn,
+            &serial_observer,
+            1,
+            None,
+            &DeliverySettings::default(),
+        );
+        let pending_payloads =
+            EventObserver::get_pending_payloads(&conn).expect("Failed to get pending payloads");
+        assert_eq!(pending_payloads.len(), 0, "Expected no pending payloads");
+
+        parallel_mock.assert();
+        serial_mock.assert();
+    }
+
+    #[test]
+    fn test_resume_interrupted_payload_drain() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_resume_drain.sqlite");
+        let db_path_str = db_path.to_str().unwrap();
+        let timeout = Duration::from_secs(5);
+
+        let port = get_random_port();
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            for _ in 0..4 {
+                let mut request = server.recv().unwrap();
+                let key = request
+                    .headers()
+                    .iter()
+                    .find(|header| header.field.equiv("Idempotency-Key"))
+                    .map(|header| header.value.to_string())
+                    .unwrap();
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body).unwrap();
+                request.respond(Response::empty(200)).unwrap();
+                tx.send((
+                    key,
+                    serde_json::from_str::<serde_json::Value>(&body).unwrap(),
+                ))
+                .unwrap();
+            }
+        });
+
+        let observer = format!("http://127.0.0.1:{}", port);
+        let url = format!("{}/test", observer);
+        let conn = EventObserver::init_db(db_path_str).unwrap();
+        for i in 0..3 {
+            EventObserver::insert_payload(&conn, &observer, &url, &json!({ "i": i }), timeout)
+                .unwrap();
+        }
+        let ids: Vec<_> = EventObserver::get_pending_payloads(&conn)
+            .unwrap()
+            .into_iter()
+            .map(|(id, ..)| id)
+            .collect();
+
+        // the node sends the first two payloads, and only records the second one's
+        // acknowledgement before it crashes
+        EventObserver::mark_payloads_in_flight(&conn, &ids[0..2]).unwrap();
+        for i in 0..2 {
+            assert_eq!(
+                EventObserver::send_payload_directly(
+                    &json!({ "i": i }),
+                    &url,
+                    timeout,
+                    None,
+                    None,
+                    None,
+                    &DeliverySettings::default(),
+                ),
+                DeliveryOutcome::Delivered
+            );
+        }
+        EventObserver::confirm_payload(&conn, ids[1]).unwrap();
+        assert_eq!(
+            EventObserver::count_pending_payloads(&conn, &observer).unwrap(),
+            2
+        );
+        drop(conn);
+
+        // on restart, only the unacknowledged payloads are sent again
+        let conn = EventObserver::init_db(db_path_str).unwrap();
+        let pending: Vec<_> = EventObserver::get_pending_payloads(&conn)
+            .unwrap()
+            .into_iter()
+            .map(|(id, _, payload, _)| (id, payload))
+            .collect();
+        assert_eq!(
+            pending,
+            vec![(ids[0], json!({"i": 0})), (ids[2], json!({"i": 2}))]
+        );
+        EventObserver::process_pending_payloads(
+            &conn,
+            &observer,
+            1,
+            None,
+            &DeliverySettings::default(),
+        );
+        assert!(EventObserver::get_pending_payloads(&conn)
+            .unwrap()
+            .is_empty());
+        let remaining: i64 = conn
+            .query_row("SELECT COUNT(*) FROM pending_payloads", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(remaining, 0);
+
+        let deliveries: Vec<(String, serde_json::Value)> = (0..4)
+            .map(|_| rx.recv_timeout(Duration::from_secs(5)).unwrap())
+            .collect();
+        let bodies: Vec<_> = deliveries
+            .iter()
+            .map(|(_, body)| body["i"].clone())
+            .collect();
+        assert_eq!(bodies, vec![json!(0), json!(1), json!(0), json!(2)]);
+
+        // the payload that was in flight during the crash is sent again with the same key, so
+        // an observer that deduplicates by key takes every event exactly once
+        assert_eq!(deliveries[0].0, deliveries[2].0);
+        let keys: HashSet<_> = deliveries.iter().map(|(key, _)| key.clone()).collect();
+        assert_eq!(keys.len(), 3);
+    }
+
+    #[test]
+    fn test_host_delivery_limits() {
+        let dir = tempdir().unwrap();
+
+        // observers of one host share its limit
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher.set_host_delivery_limits(2, HashMap::from([("localhost".into(), 1)]));
+        for endpoint in ["127.0.0.1:3700", "127.0.0.1:3701", "localhost:3700"] {
+            dispatcher
+                .register_observer(
+                    &EventObserverConfig {
+                        endpoint: endpoint.into(),
+                        events_keys: vec![EventKeyType::BurnchainBlocks],
+                        timeout_ms: 1_000,
+                        ..EventObserverConfig::default()
+                    },
+                    dir.path().to_path_buf(),
+                )
+                .unwrap();
+        }
+        let limits: Vec<_> = dispatcher
+            .registered_observers
+            .iter()
+            .map(|observer| observer.delivery.host_limit.clone().unwrap())
+            .collect();
+        assert!(Arc::ptr_eq(&limits[0], &limits[1]));
+        assert!(!Arc::ptr_eq(&limits[0], &limits[2]));
+        assert_eq!(limits[0].max_in_flight(), 2);
+        assert_eq!(limits[2].max_in_flight(), 1);
+
+        // a slow backend that records how many requests it is handling at once
+        let port = get_random_port();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let most_in_flight = Arc::new(AtomicUsize::new(0));
+        let handled = Arc::new(AtomicUsize::new(0));
+        let server_thread = {
+            let in_flight = in_flight.clone();
+            let most_in_flight = most_in_flight.clone();
+            let handled = handled.clone();
+            thread::spawn(move || {
+                while handled.load(Ordering::SeqCst) < 16 {
+                    let Ok(Some(request)) = server.recv_timeout(Duration::from_secs(10)) else {
+                        return;
+                    };
+                    let in_flight = in_flight.clone();
+                    let most_in_flight = most_in_flight.clone();
+                    handled.fetch_add(1, Ordering::SeqCst);
+                    thread::spawn(move || {
+                        let now_in_flight = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                        most_in_flight.fetch_max(now_in_flight, Ordering::SeqCst);
+                        thread::sleep(Duration::from_millis(20));
+                        // done before responding, so the client can't send its next request
+                        // before this one is counted out
+                        in_flight.fetch_sub(1, Ordering::SeqCst);
+                        request.respond(Response::empty(200)).unwrap();
+                    });
+                }
+            })
+        };
+
+        // two observers of the host, each with 4 connections, send their queues at once
+        let limit = limits[0].clone();
+        thread::scope(|s| {
+            for observer in ["a", "b"] {
+                let db_path = dir.path().join(format!("host_limits_{}.sqlite", observer));
+                let conn = EventObserver::init_db(db_path.to_str().unwrap()).unwrap();
+                let observer = format!("http://127.0.0.1:{}/{}", port, observer);
+                for i in 0..8 {
+                    EventObserver::insert_payload(
+                        &conn,
+                        &observer,
+                        &format!("{}/api", observer),
+                        &json!({ "i": i }),
+                        Duration::from_secs(5),
+                    )
+                    .unwrap();
+                }
+                let settings = DeliverySettings {
+                    host_limit: Some(limit.clone()),
+                    ..DeliverySettings::default()
+                };
+                s.spawn(move || {
+                    EventObserver::process_pending_payloads(&conn, &observer, 4, None, &settings);
+                    assert!(EventObserver::get_pending_payloads(&conn)
+                        .unwrap()
+                        .is_empty());
+                });
+            }
+        });
+        server_thread.join().unwrap();
+
+        // every payload was delivered, but never more than 2 at a time
+        assert_eq!(handled.load(Ordering::SeqCst), 16);
+        assert!(most_in_flight.load(Ordering::SeqCst) <= 2);
+    }
+
+    #[test]
+    fn test_fallback_transport() {
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+        let fallback_path = dir.path().join("fallback.jsonl");
+
+        // nothing is listening on this port yet
+        let port = get_random_port();
+        let fallback = ObserverFallback::new(FallbackTransport::File(fallback_path.clone()), 2);
+        let observer = EventObserver::new(
+            Some(working_dir),
+            format!("127.0.0.1:{}", port),
+            Duration::from_secs(1),
+        )
+        .with_fallback(Some(fallback.clone()));
+
+        // the primary's retries are exhausted, so the payloads go to the fallback instead of
+        // staying in the database
+        observer.send_payload(&json!({ "i": 0 }), "/test");
+        observer.send_payload(&json!({ "i": 1 }), "/test");
+        assert!(fallback.is_active());
+        let conn = Connection::open(observer.db_path.as_ref().unwrap()).unwrap();
+        assert!(EventObserver::get_pending_payloads(&conn)
+            .unwrap()
+            .is_empty());
+
+        let written: Vec<serde_json::Value> = std::fs::read_to_string(&fallback_path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let url = format!("http://127.0.0.1:{}/test", port);
+        assert_eq!(
+            written,
+            vec![
+                json!({ "url": url, "payload": { "i": 0 } }),
+                json!({ "url": url, "payload": { "i": 1 } }),
+            ]
+        );
+
+        // once the primary is back, it gets the payloads again
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        let server_thread = thread::spawn(move || {
+            let mut request = server.recv().unwrap();
+            let mut payload = String::new();
+            request.as_reader().read_to_string(&mut payload).unwrap();
+            request.respond(Response::empty(200)).unwrap();
+            serde_json::from_str::<serde_json::Value>(&payload).unwrap()
+        });
+        observer.send_payload(&json!({ "i": 2 }), "/test");
+        assert_eq!(server_thread.join().unwrap(), json!({ "i": 2 }));
+        assert!(!fallback.is_active());
+        assert_eq!(
+            std::fs::read_to_string(&fallback_path)
+                .unwrap()
+                .lines()
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_success_body_marker() {
+        let port = get_random_port();
+        let observer =
+            EventObserver::new(None, format!("127.0.0.1:{}", port), Duration::from_secs(1))
+                .with_success_body_marker(Some(r#""ok":true"#.into()));
+
+        // a `200` without the marker is retried, until one with the marker arrives
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        let server_thread = thread::spawn(move || {
+            for body in [r#"{"ok":false}"#, "accepted", r#"{"ok":true}"#] {
+                let request = server.recv().unwrap();
+                let content_type =
+                    tiny_http::Header::from_bytes("Content-Type", "application/json").unwrap();
+                request
+                    .respond(Response::from_string(body).with_header(content_type))
+                    .unwrap();
+            }
+        });
+        observer.send_payload(&json!({ "i": 0 }), "/test");
+        server_thread.join().unwrap();
+
+        let health = observer.delivery_health();
+        assert_eq!(health.consecutive_failures, 0);
+        assert!(health.last_success_time.is_some());
+
+        assert!(response_body_contains(
+            &HttpResponsePayload::JSON(json!({ "ok": true, "n": 1 })),
+            r#""ok":true"#
+        ));
+        assert!(response_body_contains(
+            &HttpResponsePayload::Text("status: ok".into()),
+            "ok"
+        ));
+        assert!(!response_body_contains(
+            &HttpResponsePayload::Bytes(b"nope".to_vec()),
+            "ok"
+        ));
+        assert!(!response_body_contains(&HttpResponsePayload::Empty, "ok"));
+    }
+
+    /// Answer one request per entry of `statuses` with that status, sending each request's body
+    /// down the returned channel before it is answered
+    fn serve_statuses(port: u16, statuses: Vec<u16>) -> Receiver<serde_json::Value> {
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            for status in statuses {
+                let Ok(Some(mut request)) = server.recv_timeout(Duration::from_secs(10)) else {
+                    return;
+                };
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body).unwrap();
+                tx.send(serde_json::from_str(&body).unwrap()).unwrap();
+                request.respond(Response::empty(status)).unwrap();
+            }
+        });
+        rx
+    }
+
+    #[test]
+    fn test_delivery_policy_max_attempts() {
+        let dir = tempdir().unwrap();
+        let port = get_random_port();
+        let dead_letter_port = get_random_port();
+        let observer = EventObserver::new(
+            Some(dir.path().to_path_buf()),
+            format!("127.0.0.1:{}", port),
+            Duration::from_secs(1),
+        )
+        .with_delivery_policy(
+            DeliveryPolicy::from_config(&DeliveryPolicyConfig {
+                max_attempts: Some(2),
+                backoff_ms: Some(10),
+                dead_letter_endpoint: Some(format!("127.0.0.1:{}", dead_letter_port)),
+                ..DeliveryPolicyConfig::default()
+            })
+            .unwrap(),
+        );
+        let conn = Connection::open(observer.db_path.as_ref().unwrap()).unwrap();
+
+        // neither endpoint is up, so the payload that is given up on stays queued
+        observer.send_payload(&json!({ "i": 0 }), "/test");
+        assert_eq!(EventObserver::get_pending_payloads(&conn).unwrap().len(), 1);
+
+        // each payload gets 2 attempts, and is then handed to the dead-letter endpoint
+        let primary = serve_statuses(port, vec![503; 4]);
+        let dead_letters = serve_statuses(dead_letter_port, vec![200; 2]);
+        observer.send_payload(&json!({ "i": 1 }), "/test");
+        assert!(EventObserver::get_pending_payloads(&conn)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            primary.try_iter().collect::<Vec<_>>(),
+            vec![
+                json!({ "i": 0 }),
+                json!({ "i": 0 }),
+                json!({ "i": 1 }),
+                json!({ "i": 1 })
+            ]
+        );
+        let url = format!("http://127.0.0.1:{}/test", port);
+        assert_eq!(
+            dead_letters.try_iter().collect::<Vec<_>>(),
+            vec![
+                json!({
+                    "url": url,
+                    "reason": "delivery failed after 2 attempts",
+                    "payload": { "i": 0 },
+                }),
+                json!({
+                    "url": url,
+                    "reason": "delivery failed after 2 attempts",
+                    "payload": { "i": 1 },
+                }),
+            ]
+        );
+
+        // without a dead-letter endpoint, a payload that is given up on is dropped
+        let policy = DeliveryPolicy {
+            dead_letter_url: None,
+            ..observer.delivery.policy.clone()
+        };
+        let observer = observer.with_delivery_policy(policy);
+        observer.send_payload(&json!({ "i": 2 }), "/test");
+        assert!(EventObserver::get_pending_payloads(&conn)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_delivery_policy_is_per_observer() {
+        let dir = tempdir().unwrap();
+        let port_a = get_random_port();
+        let port_b = get_random_port();
+
+        // observer a gives up on a payload after one failed attempt, and has no dead-letter
+        // endpoint, while observer b retries its payloads until they are delivered
+        let observer_a = EventObserver::new(
+            Some(dir.path().to_path_buf()),
+            format!("127.0.0.1:{}", port_a),
+            Duration::from_secs(1),
+        )
+        .with_delivery_policy(
+            DeliveryPolicy::from_config(&DeliveryPolicyConfig {
+                max_attempts: Some(1),
+                ..DeliveryPolicyConfig::default()
+            })
+            .unwrap(),
+        );
+        let observer_b = EventObserver::new(
+            Some(dir.path().to_path_buf()),
+            format!("127.0.0.1:{}", port_b),
+            Duration::from_secs(1),
+        );
+        let conn = Connection::open(observer_a.db_path.as_ref().unwrap()).unwrap();
+
+        // observer b is down, so its payload stays in the database
+        observer_b.send_payload_with_retry_deadline(
+            &json!({ "for": "b" }),
+            "/test",
+            Some(Instant::now() + Duration::from_millis(300)),
+        );
+
+        // observer a's policy drops its own failed payload, but not observer b's
+        let primary_a = serve_statuses(port_a, vec![503]);
+        observer_a.send_payload(&json!({ "for": "a" }), "/test");
+        assert_eq!(
+            primary_a.try_iter().collect::<Vec<_>>(),
+            vec![json!({ "for": "a" })]
+        );
+        let pending: Vec<_> = EventObserver::get_pending_payloads(&conn)
+            .unwrap()
+            .into_iter()
+            .map(|(_, _, payload, _)| payload)
+            .collect();
+        assert_eq!(pending, vec![json!({ "for": "b" })]);
+
+        // observer b's backlog is delivered once it is back
+        let primary_b = serve_statuses(port_b, vec![200; 2]);
+        observer_b.send_payload(&json!({ "for": "b", "n": 1 }), "/test");
+        assert_eq!(
+            primary_b.try_iter().collect::<Vec<_>>(),
+            vec![json!({ "for": "b" }), json!({ "for": "b", "n": 1 })]
+        );
+        assert!(EventObserver::get_pending_payloads(&conn)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_failure_grace_period() {
+        let dir = tempdir().unwrap();
+        let port = get_random_port();
+        let policy = DeliveryPolicy::from_config(&DeliveryPolicyConfig {
+            max_attempts: Some(1),
+            backoff_ms: Some(10),
+            max_backoff_ms: Some(10),
+            failure_grace_period_ms: Some(5_000),
+            ..DeliveryPolicyConfig::default()
+        })
+        .unwrap();
+        let observer = EventObserver::new(
+            Some(dir.path().to_path_buf()),
+            format!("127.0.0.1:{}", port),
+            Duration::from_secs(1),
+        )
+        .with_delivery_policy(policy.clone());
+        let conn = Connection::open(observer.db_path.as_ref().unwrap()).unwrap();
+
+        // a blip: the payload outlasts its max_attempts, and is delivered once the observer is back
+        let primary = serve_statuses(port, vec![503, 503, 200]);
+        observer.send_payload(&json!({ "i": 0 }), "/test");
+        assert_eq!(
+            primary.try_iter().collect::<Vec<_>>(),
+            vec![json!({ "i": 0 }); 3]
+        );
+        assert_eq!(observer.delivery.health.failing_for(), Duration::ZERO);
+        assert!(EventObserver::get_pending_payloads(&conn)
+            .unwrap()
+            .is_empty());
+
+        // a sustained outage: the payload is given up on once the grace period is over
+        let observer = observer.with_delivery_policy(DeliveryPolicy {
+            failure_grace_period: Some(Duration::from_millis(300)),
+            ..policy
+        });
+        let primary = serve_statuses(port, vec![503; 1_000]);
+        let start = Instant::now();
+        observer.send_payload(&json!({ "i": 1 }), "/test");
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert!(primary.try_iter().count() > 1);
+        assert!(observer.delivery.health.failing_for() >= Duration::from_millis(300));
+        assert!(EventObserver::get_pending_payloads(&conn)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_failure_grace_period_before_fallback() {
+        let dir = tempdir().unwrap();
+        let fallback_path = dir.path().join("fallback.jsonl");
+        let port = get_random_port();
+        let fallback = ObserverFallback::new(FallbackTransport::File(fallback_path.clone()), 1);
+        let policy = DeliveryPolicy {
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Some(Duration::from_millis(10)),
+            failure_grace_period: Some(Duration::from_secs(5)),
+            ..DeliveryPolicy::default()
+        };
+        let observer = EventObserver::new(
+            Some(dir.path().to_path_buf()),
+            format!("127.0.0.1:{}", port),
+            Duration::from_secs(1),
+        )
+        .with_fallback(Some(fallback.clone()))
+        .with_delivery_policy(policy.clone());
+
+        // a blip doesn't send the payload to the fallback transport
+        let primary = serve_statuses(port, vec![503, 200]);
+        observer.send_payload(&json!({ "i": 0 }), "/test");
+        assert_eq!(primary.try_iter().count(), 2);
+        assert!(!fallback.is_active());
+        assert!(!fallback_path.exists());
+
+        // a sustained outage does, once the grace period is over
+        let observer = observer.with_delivery_policy(DeliveryPolicy {
+            failure_grace_period: Some(Duration::from_millis(300)),
+            ..policy
+        });
+        let start = Instant::now();
+        observer.send_payload(&json!({ "i": 1 }), "/test");
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        assert!(fallback.is_active());
+        let url = format!("http://127.0.0.1:{}/test", port);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(
+                &std::fs::read_to_string(&fallback_path).unwrap()
+            )
+            .unwrap(),
+            json!({ "url": url, "payload": { "i": 1 } })
+        );
+    }
+
+    #[test]
+    fn test_delivery_policy_retryable_statuses() {
+        let port = get_random_port();
+        let primary = serve_statuses(port, vec![503, 400, 200]);
+        let observer =
+            EventObserver::new(None, format!("127.0.0.1:{}", port), Duration::from_secs(1))
+                .with_delivery_policy(
+                    DeliveryPolicy::from_config(&DeliveryPolicyConfig {
+                        backoff_ms: Some(10),
+                        retryable_statuses: Some(vec![503]),
+                        ..DeliveryPolicyConfig::default()
+                    })
+                    .unwrap(),
+                );
+
+        // a 503 is retried, but a 400 isn't, so the payload is given up on
+        observer.send_payload(&json!({ "i": 0 }), "/test");
+        assert_eq!(primary.try_iter().count(), 2);
+        assert_eq!(observer.delivery_health().consecutive_failures, 2);
+
+        // which doesn't hold up the next payload
+        observer.send_payload(&json!({ "i": 1 }), "/test");
+        assert_eq!(
+            primary.try_iter().collect::<Vec<_>>(),
+            vec![json!({ "i": 1 })]
+        );
+    }
+
+    #[test]
+    fn test_delivery_policy_max_event_age() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("test_max_event_age.sqlite");
+        let conn = EventObserver::init_db(db_path.to_str().unwrap()).unwrap();
+        let port = get_random_port();
+        let primary = serve_statuses(port, vec![503; 20]);
+        let observer = format!("http://127.0.0.1:{}", port);
+        let url = format!("{}/test", &observer);
+        let settings = DeliverySettings {
+            policy: DeliveryPolicy::from_config(&DeliveryPolicyConfig {
+                backoff_ms: Some(50),
+                max_backoff_ms: Some(50),
+                max_event_age_ms: Some(500),
+                ..DeliveryPolicyConfig::default()
+            })
+            .unwrap(),
+            ..DeliverySettings::default()
+        };
+
+        // a payload that is already too old is given up on without being sent
+        EventObserver::insert_payload(
+            &conn,
+            &observer,
+            &url,
+            &json!({ "i": 0 }),
+            Duration::from_secs(1),
+        )
+        .unwrap();
+        conn.execute("UPDATE pending_payloads SET created_at = 0", [])
+            .unwrap();
+        EventObserver::process_pending_payloads(&conn, &observer, 1, None, &settings);
+        assert!(EventObserver::get_pending_payloads(&conn)
+            .unwrap()
+            .is_empty());
+        assert_eq!(primary.try_iter().count(), 0);
+
+        // a new payload is retried until it gets too old
+        EventObserver::insert_payload(
+            &conn,
+            &observer,
+            &url,
+            &json!({ "i": 1 }),
+            Duration::from_secs(1),
+        )
+        .unwrap();
+        let start = Instant::now();
+        EventObserver::process_pending_payloads(&conn, &observer, 1, None, &settings);
+        let elapsed = start.elapsed();
+        assert!(EventObserver::get_pending_payloads(&conn)
+            .unwrap()
+            .is_empty());
+        assert!(primary.try_iter().count() > 1);
+        assert!(elapsed >= Duration::from_millis(400));
+        assert!(elapsed < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_delivery_policy_backoff() {
+        let port = get_random_port();
+        let primary = serve_statuses(port, vec![500, 500, 500, 200]);
+        let timeout = Duration::from_secs(1);
+        let policy = DeliveryPolicy::from_config(&DeliveryPolicyConfig {
+            backoff_ms: Some(200),
+            max_backoff_ms: Some(200),
+            ..DeliveryPolicyConfig::default()
+        })
+        .unwrap();
+
+        // each of the 3 retries waits 200ms, since the backoff can't grow past its cap, whatever
+        // the jitter
+        let mut backoff = policy.initial_backoff;
+        for jitter_ms in [0, 99, 99] {
+            assert_eq!(backoff, Duration::from_millis(200));
+            backoff = policy.next_backoff(backoff, Duration::from_millis(jitter_ms), timeout);
+        }
+
+        // the schedule is checked above, so the wall clock only needs to show the waits happened
+        let observer = EventObserver::new(None, format!("127.0.0.1:{}", port), timeout)
+            .with_delivery_policy(policy);
+        let start = Instant::now();
+        observer.send_payload(&json!({ "i": 0 }), "/test");
+        let elapsed = start.elapsed();
+        assert_eq!(primary.try_iter().count(), 4);
+        assert!(elapsed >= Duration::from_millis(600));
+        assert!(elapsed < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_delivery_policy_backoff_multiplier() {
+        let port = get_random_port();
+        let primary = serve_statuses(port, vec![500, 500, 500, 200]);
+        let timeout = Duration::from_secs(1);
+        let policy = DeliveryPolicy::from_config(&DeliveryPolicyConfig {
+            backoff_ms: Some(100),
+            backoff_multiplier: Some(3),
+            max_backoff_ms: Some(10_000),
+            ..DeliveryPolicyConfig::default()
+        })
+        .unwrap();
+
+        // the 3 retries wait 100ms, then 300ms, then 900ms, plus each wait's jitter, which is
+        // multiplied along with it
+        let schedule = |jitter: Duration| {
+            let mut backoff = policy.initial_backoff;
+            let mut waits = vec![];
+            for _ in 0..3 {
+                waits.push(backoff);
+                backoff = policy.next_backoff(backoff, jitter, timeout);
+            }
+            waits
+        };
+        assert_eq!(
+            schedule(Duration::ZERO),
+            vec![
+                Duration::from_millis(100),
+                Duration::from_millis(300),
+                Duration::from_millis(900)
+            ]
+        );
+        assert_eq!(
+            schedule(Duration::from_millis(99)),
+            vec![
+                Duration::from_millis(100),
+                Duration::from_millis(399),
+                Duration::from_millis(1_296)
+            ]
+        );
+
+        // the schedule is checked above, so the wall clock only needs to show the waits happened
+        let observer = EventObserver::new(None, format!("127.0.0.1:{}", port), timeout)
+            .with_delivery_policy(policy);
+        let start = Instant::now();
+        observer.send_payload(&json!({ "i": 0 }), "/test");
+        let elapsed = start.elapsed();
+        assert_eq!(primary.try_iter().count(), 4);
+        assert!(elapsed >= Duration::from_millis(1_300));
+        assert!(elapsed < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_event_delivery_health() {
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+        let fallback_path = dir.path().join("fallback.jsonl");
+
+        // nothing is listening on this port yet
+        let port = get_random_port();
+        let fallback = ObserverFallback::new(FallbackTransport::File(fallback_path), 2);
+        let observer = EventObserver::new(
+            Some(working_dir),
+            format!("127.0.0.1:{}", port),
+            Duration::from_secs(1),
+        )
+        .with_fallback(Some(fallback));
+
+        let health = observer.delivery_health();
+        assert_eq!(health.endpoint, format!("127.0.0.1:{}", port));
+        assert_eq!(health.queue_depth, 0);
+        assert_eq!(health.last_success_time, None);
+        assert_eq!(health.delivery_state, EventDeliveryState::Ok);
+
+        // payloads queued for this observer count towards its queue depth, others' don't
+        let conn = Connection::open(observer.db_path.as_ref().unwrap()).unwrap();
+        let url = format!("http://127.0.0.1:{}/test", port);
+        EventObserver::insert_payload_with_retry(
+            &conn,
+            &observer_base_url(&observer.endpoint),
+            &url,
+            &json!({}),
+            observer.timeout,
+        );
+        EventObserver::insert_payload_with_retry(
+            &conn,
+            "http://127.0.0.1:1",
+            "http://127.0.0.1:1/test",
+            &json!({}),
+            observer.timeout,
+        );
+        assert_eq!(observer.delivery_health().queue_depth, 1);
+        conn.execute("DELETE FROM pending_payloads", []).unwrap();
+
+        // each failed attempt counts, and the payload goes to the fallback
+        observer.send_payload(&json!({ "i": 0 }), "/test");
+        let health = observer.delivery_health();
+        assert_eq!(health.consecutive_failures, 2);
+        assert_eq!(health.fallback_deliveries, 1);
+        assert_eq!(health.queue_depth, 0);
+        assert_eq!(health.delivery_state, EventDeliveryState::Fallback);
+
+        // once the primary is back, the failures are cleared
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        let server_thread = thread::spawn(move || {
+            let request = server.recv().unwrap();
+            request.respond(Response::empty(200)).unwrap();
+        });
+        observer.send_payload(&json!({ "i": 1 }), "/test");
+        server_thread.join().unwrap();
+
+        let dispatcher_health = RPCEventHealthData::new(vec![observer.delivery_health()]);
+        let health = &dispatcher_health.observers[0];
+        assert_eq!(health.consecutive_failures, 0);
+        assert_eq!(health.fallback_deliveries, 1);
+        assert!(health.last_success_time.is_some());
+        assert_eq!(health.delivery_state, EventDeliveryState::Ok);
+        assert_eq!(dispatcher_health.total_fallback_deliveries, 1);
+    }
+
+    #[test]
+    fn test_new_event_observer_with_db() {
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+
+        let endpoint = "http://example.com".to_string();
+        let timeout = Duration::from_secs(5);
+
+        let observer = EventObserver::new(Some(working_dir.clone()), endpoint.clone(), timeout);
+
+        // Verify fields
+        assert_eq!(observer.endpoint, endpoint);
+        assert_eq!(observer.timeout, timeout);
+
+        // Verify that the database was initialized
+        let mut db_path = working_dir;
+        db_path.push("event_observers.sqlite");
+        assert!(db_path.exists(), "Database file was not created");
+    }
+
+    #[test]
+    fn test_new_event_observer_without_db() {
+        let endpoint = "http://example.com".to_string();
+        let timeout = Duration::from_secs(5);
+
+        let observer = EventObserver::new(None, endpoint.clone(), timeout);
+
+        // Verify fields
+        assert_eq!(observer.endpoint, endpoint);
+        assert_eq!(observer.timeout, timeout);
+        assert!(observer.db_path.is_none(), "Expected db_path to be None");
+    }
+
+    #[test]
+    fn test_send_payload_with_db() {
+        use mockito::Matcher;
+
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+        let payload = json!({"key": "value"});
+
+        // Create a mock server
+        let mut server = mockito::Server::new();
+        let _m = server
+            .mock("POST", "/test")
+            .match_header("content-type", Matcher::Regex("application/json.*".into()))
+            .match_body(Matcher::Json(payload.clone()))
+            .with_status(200)
+            .create();
+
+        let endpoint = server.url().strip_prefix("http://").unwrap().to_string();
+        let timeout = Duration::from_secs(5);
+
+        let observer = EventObserver::new(Some(working_dir.clone()), endpoint, timeout);
+
+        // Call send_payload
+        observer.send_payload(&payload, "/test");
+
+        // Verify that the payload was sent and database is empty
+        _m.assert();
+
+        // Verify that the database is empty
+        let db_path = observer.db_path.unwrap();
+        let db_path_str = db_path.to_str().unwrap();
+        let conn = Connection::open(db_path_str).expect("Failed to open database");
+        let pending_payloads =
+            EventObserver::get_pending_payloads(&conn).expect("Failed to get pending payloads");
+        assert_eq!(pending_payloads.len(), 0, "Expected no pending payloads");
+    }
+
+    #[test]
+    fn test_send_payload_with_projection() {
+        use mockito::Matcher;
+
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+        let payload = json!({
+            "block_height": 100,
+            "events": [{"txid": "0xaa"}],
+            "transactions": [{"txid": "0xaa", "raw_tx": "0x00"}],
+        });
+
+        let mut server = mockito::Server::new();
+        let _m = server
+            .mock("POST", "/test")
+            .match_body(Matcher::Json(json!({
+                "block_height": 100,
+                "transactions": [{"txid": "0xaa"}],
+            })))
+            .with_status(200)
+            .create();
+
+        let endpoint = server.url().strip_prefix("http://").unwrap().to_string();
+        let projection =
+            PayloadProjection::new(&["block_height", "transactions[].txid"], &[]).unwrap();
+        let observer =
+            EventObserver::new(Some(working_dir.clone()), endpoint, Duration::from_secs(5))
+                .with_payload_projection(projection);
+
+        observer.send_payload(&payload, "/test");
+
+        _m.assert();
+    }
+
+    #[test]
+    fn test_send_payload_with_transform() {
+        use mockito::Matcher;
+
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+        let payload = json!({
+            "block_height": 100,
+            "transactions": [{"txid": "0xaa", "raw_tx": "0x00"}],
+        });
+
+        // the transform sees the full payload, before the observer's projection
+        let mut server = mockito::Server::new();
+        let _m = server
+            .mock("POST", "/test")
+            .match_body(Matcher::Json(json!({
+                "block_height": 100,
+                "transactions": [{"txid": "0xaa", "raw_tx": "redacted"}],
+            })))
+            .with_status(200)
+            .create();
+
+        let endpoint = server.url().strip_prefix("http://").unwrap().to_string();
+        let redact = PayloadTransform::new(|path, mut payload| {
+            assert_eq!(path, "/test");
+            payload["transactions"][0]["raw_tx"] = json!("redacted");
+            payload["tx_count"] = json!(1);
+            payload
+        });
+        let projection = PayloadProjection::new(&["block_height", "transactions"], &[]).unwrap();
+        let observer = EventObserver::new(
+            Some(working_dir.clone()),
+            endpoint.clone(),
+            Duration::from_secs(5),
+        )
+        .with_payload_projection(projection)
+        .with_payload_transform(Some(redact));
+
+        observer.send_payload(&payload, "/test");
+        _m.assert();
+
+        // a payload whose transform panics is not sent at all
+        let _m = server.mock("POST", "/test").expect(0).create();
+        let observer = EventObserver::new(Some(working_dir), endpoint, Duration::from_secs(5))
+            .with_payload_transform(Some(PayloadTransform::new(|_, _| panic!("bad transform"))));
+        observer.send_payload(&payload, "/test");
+        _m.assert();
+    }
+
+    #[test]
+    fn test_send_payload_retry_deadline() {
+        use mockito::Matcher;
+
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+        let timeout = Duration::from_secs(1);
+
+        // nothing listens on this port, so deliveries to it always fail
+        let dead_observer = EventObserver::new(
+            Some(working_dir.clone()),
+            format!("127.0.0.1:{}", get_random_port()),
+            timeout,
+        );
+
+        let live_payload = json!({"key": "live"});
+        let mut server = mockito::Server::new();
+        let _m = server
+            .mock("POST", "/test")
+            .match_body(Matcher::Json(live_payload.clone()))
+            .with_status(200)
+            .expect(1)
+            .create();
+        let live_observer = EventObserver::new(
+            Some(working_dir.clone()),
+            server.url().strip_prefix("http://").unwrap().to_string(),
+            timeout,
+        );
+
+        let start = Instant::now();
+        let retry_deadline = Some(start + Duration::from_millis(500));
+        dead_observer.send_payload_with_retry_deadline(
+            &json!({"key": "dead"}),
+            "/test",
+            retry_deadline,
+        );
+        // the live observer's payload is still attempted, and isn't held up by the
+        // dead observer's
+        live_observer.send_payload_with_retry_deadline(&live_payload, "/test", retry_deadline);
+        assert!(start.elapsed() < Duration::from_secs(3));
+        _m.assert();
+
+        // the dead observer's payload was deferred
+        let conn = Connection::open(dead_observer.db_path.as_ref().unwrap()).unwrap();
+        let pending_payloads = EventObserver::get_pending_payloads(&conn).unwrap();
+        assert_eq!(pending_payloads.len(), 1);
+        assert_eq!(pending_payloads[0].2, json!({"key": "dead"}));
+    }
+
+    #[test]
+    fn test_send_payload_ipv6() {
+        let listener = TcpListener::bind("[::1]:0").expect("Failed to bind to IPv6 loopback");
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+        let server = Server::http(format!("[::1]:{}", port)).unwrap();
+
+        let (tx, rx) = channel();
+        thread::spawn(move || {
+            let mut request = server.recv().unwrap();
+            let mut payload = String::new();
+            request.as_reader().read_to_string(&mut payload).unwrap();
+            let url = request.url().to_string();
+            request.respond(Response::empty(200)).unwrap();
+            tx.send((url, payload)).unwrap();
+        });
+
+        let observer = EventObserver::new(None, format!("[::1]:{}", port), Duration::from_secs(3));
+        observer.send_payload(&json!({"key": "value"}), "/test");
+
+        let (url, payload) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(url, "/test");
+        assert_eq!(payload, r#"{"key":"value"}"#);
+    }
+
+    #[test]
+    fn test_send_payload_https() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (tx, rx) = channel();
+        spawn_test_tls_server(listener, tx);
+
+        // the test server's certificate isn't signed by a public CA, so it is only accepted if
+        // verification is skipped
+        let dir = tempdir().unwrap();
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher
+            .register_observer(
+                &EventObserverConfig {
+                    endpoint: format!("https://localhost:{}/tls", port),
+                    events_keys: vec![EventKeyType::AnyEvent],
+                    timeout_ms: 3_000,
+                    tls_skip_verify: true,
+                    ..EventObserverConfig::default()
+                },
+                dir.path().to_path_buf(),
+            )
+            .unwrap();
+        dispatcher.registered_observers[0].send_payload(&json!({"key": "value"}), "/test");
+        let (request_line, body) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(request_line, "POST /tls/test HTTP/1.1");
+        assert_eq!(body, br#"{"key":"value"}"#);
+        let conn =
+            Connection::open(dispatcher.registered_observers[0].db_path.as_ref().unwrap()).unwrap();
+        assert!(EventObserver::get_pending_payloads(&conn)
+            .unwrap()
+            .is_empty());
+
+        // an observer that verifies certificates never hands the payload over
+        let observer = EventObserver::new(
+            None,
+            format!("https://localhost:{}/tls", port),
+            Duration::from_secs(3),
+        )
+        .with_tls(Some(TlsClientConfig::new()))
+        .with_delivery_policy(DeliveryPolicy {
+            max_attempts: Some(1),
+            ..DeliveryPolicy::default()
+        });
+        observer.send_payload(&json!({"key": "value"}), "/test");
+        assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+    }
+
+    #[test]
+    fn test_spooled_https_payloads_keep_their_observers_tls() {
+        let dir = tempdir().unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let tls_port = listener.local_addr().unwrap().port();
+        let plain_port = get_random_port();
+
+        // the https observer trusts the test CA, which the Mozilla roots don't include
+        let tls_observer = EventObserver::new(
+            Some(dir.path().to_path_buf()),
+            format!("https://localhost:{}", tls_port),
+            Duration::from_secs(3),
+        )
+        .with_tls(Some(
+            TlsClientConfig::with_root_certificates(&[TEST_CA_CERT.to_vec()]).unwrap(),
+        ));
+        let plain_observer = EventObserver::new(
+            Some(dir.path().to_path_buf()),
+            format!("127.0.0.1:{}", plain_port),
+            Duration::from_secs(3),
+        );
+
+        // the https observer was down when its payload was produced
+        let conn = Connection::open(tls_observer.db_path.as_ref().unwrap()).unwrap();
+        let tls_url = observer_base_url(&tls_observer.endpoint);
+        EventObserver::insert_payload(
+            &conn,
+            &tls_url,
+            &format!("{}/test", tls_url),
+            &json!({ "n": 0 }),
+            Duration::from_secs(3),
+        )
+        .unwrap();
+
+        // the other observer's drain leaves it alone, rather than sending it without the CA
+        let plain = serve_statuses(plain_port, vec![200]);
+        plain_observer.send_payload(&json!({ "n": 1 }), "/test");
+        assert_eq!(
+            plain.try_iter().collect::<Vec<_>>(),
+            vec![json!({ "n": 1 })]
+        );
+        assert_eq!(
+            EventObserver::count_pending_payloads(&conn, &tls_url).unwrap(),
+            1
+        );
+
+        // and the https observer sends it with its own TLS config once it's back
+        let (tx, rx) = channel();
+        spawn_test_tls_server(listener, tx);
+        tls_observer.replay_pending_payloads();
+        let (request_line, body) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(request_line, "POST /test HTTP/1.1");
+        assert_eq!(body, br#"{"n":0}"#);
+        assert!(EventObserver::get_pending_payloads(&conn)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_send_payload_https_custom_ca_and_pinned_certificate() {
+        let dir = tempdir().unwrap();
+        let ca_file = dir.path().join("ca.pem").to_str().unwrap().to_string();
+        std::fs::write(&ca_file, TEST_CA_CERT_PEM).unwrap();
+        let (tx, rx) = channel();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let ca_issued_port = listener.local_addr().unwrap().port();
+        spawn_test_tls_server(listener, tx.clone());
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let self_signed_port = listener.local_addr().unwrap().port();
+        spawn_test_tls_server_with_config(listener, make_self_signed_server_config(), tx);
+
+        let conf =
+            |port: u16, tls_ca_file: Option<&str>, pinned: Option<&[u8]>| EventObserverConfig {
+                endpoint: format!("https://localhost:{}/tls", port),
+                events_keys: vec![EventKeyType::AnyEvent],
+                timeout_ms: 3_000,
+                tls_ca_file: tls_ca_file.map(String::from),
+                tls_pinned_cert_sha256: pinned.map(|cert| Sha256Sum::from_data(cert).to_hex()),
+                ..EventObserverConfig::default()
+            };
+        // a single attempt, so that an observer that fails verification doesn't retry forever
+        let send_once = |conf: &EventObserverConfig, payload: &serde_json::Value| {
+            EventObserver::send_payload_directly(
+                payload,
+                &format!("{}/test", conf.endpoint),
+                Duration::from_millis(conf.timeout_ms),
+                None,
+                Some(1),
+                None,
+                &DeliverySettings {
+                    tls: make_observer_tls_config(conf).unwrap(),
+                    ..DeliverySettings::default()
+                },
+            ) == DeliveryOutcome::Delivered
+        };
+
+        // the internal CA's certificates are trusted, but not the self-signed one
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher
+            .register_observer(
+                &conf(ca_issued_port, Some(&ca_file), None),
+                dir.path().to_path_buf(),
+            )
+            .unwrap();
+        dispatcher.registered_observers[0].send_payload(&json!({"key": "value"}), "/test");
+        let (request_line, body) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(request_line, "POST /tls/test HTTP/1.1");
+        assert_eq!(body, br#"{"key":"value"}"#);
+        assert!(!send_once(
+            &conf(self_signed_port, Some(&ca_file), None),
+            &json!({})
+        ));
+        assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+
+        // the pinned self-signed certificate is trusted on its own
+        assert!(send_once(
+            &conf(self_signed_port, None, Some(TEST_SELF_SIGNED_CERT)),
+            &json!({"key": "value"})
+        ));
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(5)).unwrap().1,
+            br#"{"key":"value"}"#
+        );
+
+        // and a pinned certificate must be issued by the internal CA, if there is one
+        assert!(send_once(
+            &conf(ca_issued_port, Some(&ca_file), Some(TEST_SERVER_CERT)),
+            &json!({"n": 1})
+        ));
+        assert_eq!(
+            rx.recv_timeout(Duration::from_secs(5)).unwrap().1,
+            br#"{"n":1}"#
+        );
+        assert!(!send_once(
+            &conf(
+                self_signed_port,
+                Some(&ca_file),
+                Some(TEST_SELF_SIGNED_CERT)
+            ),
+            &json!({"n": 2})
+        ));
+        assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+
+        // a CA file without certificates is refused when the observer is registered
+        let bad_ca_file = dir.path().join("bad.pem").to_str().unwrap().to_string();
+        std::fs::write(&bad_ca_file, "not a certificate").unwrap();
+        let err = dispatcher
+            .register_observer(
+                &conf(ca_issued_port, Some(&bad_ca_file), None),
+                dir.path().to_path_buf(),
+            )
+            .unwrap_err();
+        assert!(err.contains(&bad_ca_file), "{}", err);
+    }
+
+    #[test]
+    fn test_https_endpoint_options() {
+        let dir = tempdir().unwrap();
+        let mut dispatcher = EventDispatcher::new();
+        let conf = |endpoint: &str| EventObserverConfig {
+            endpoint: endpoint.into(),
+            events_keys: vec![EventKeyType::AnyEvent],
+            timeout_ms: 1_000,
+            ..EventObserverConfig::default()
+        };
+
+        // the HTTP/2 client only speaks cleartext HTTP/2
+        let err = dispatcher
+            .register_observer(
+                &EventObserverConfig {
+                    http2: true,
+                    ..conf("https://127.0.0.1:3700")
+                },
+                dir.path().to_path_buf(),
+            )
+            .unwrap_err();
+        assert!(err.contains("http2 cannot be used"), "{}", err);
+
+        // there's no certificate to verify, or skip verifying, without TLS
+        let err = dispatcher
+            .register_observer(
+                &EventObserverConfig {
+                    tls_skip_verify: true,
+                    ..conf("127.0.0.1:3700")
+                },
+                dir.path().to_path_buf(),
+            )
+            .unwrap_err();
+        assert!(err.contains("tls_skip_verify"), "{}", err);
+        let err = dispatcher
+            .register_observer(
+                &EventObserverConfig {
+                    tls_pinned_cert_sha256: Some(Sha256Sum::from_data(TEST_CA_CERT).to_hex()),
+                    ..conf("127.0.0.1:3700")
+                },
+                dir.path().to_path_buf(),
+            )
+            .unwrap_err();
+        assert!(err.contains("tls_pinned_cert_sha256"), "{}", err);
+        assert!(dispatcher.registered_observers.is_empty());
+
+        // certificates are verified by default
+        dispatcher
+            .register_observer(&conf("https://127.0.0.1:3700"), dir.path().to_path_buf())
+            .unwrap();
+        let tls = dispatcher.registered_observers[0]
+            .delivery
+            .tls
+            .as_ref()
+            .unwrap();
+        assert!(tls.verifies_certificates());
+        dispatcher
+            .register_observer(&conf("127.0.0.1:3701"), dir.path().to_path_buf())
+            .unwrap();
+        assert!(dispatcher.registered_observers[1].delivery.tls.is_none());
+    }
+
+    #[test]
+    fn test_send_payload_without_db() {
+        use mockito::Matcher;
+
+        let timeout = Duration::from_secs(5);
+        let payload = json!({"key": "value"});
+
+        // Create a mock server
+        let mut server = mockito::Server::new();
+        let _m = server
+            .mock("POST", "/test")
+            .match_header("content-type", Matcher::Regex("application/json.*".into()))
+            .match_body(Matcher::Json(payload.clone()))
+            .with_status(200)
+            .create();
+
+        let endpoint = server.url().strip_prefix("http://").unwrap().to_string();
+
+        let observer = EventObserver::new(None, endpoint, timeout);
+
+        // Call send_payload
+        observer.send_payload(&payload, "/test");
+
+        // Verify that the payload was sent
+        _m.assert();
+    }
+
+    #[test]
+    fn test_send_payload_success() {
+        let port = get_random_port();
+
+        // Set up a channel to notify when the server has processed the request
+        let (tx, rx) = channel();
+
+        // Start a mock server in a separate thread
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            let request = server.recv().unwrap();
+            assert_eq!(request.url(), "/test");
+            assert_eq!(request.method(), &Method::Post);
+
+            // Simulate a successful response
+            let response = Response::from_string("HTTP/1.1 200 OK");
+            request.respond(response).unwrap();
+
+            // Notify the test that the request was processed
+            tx.send(()).unwrap();
+        });
+
+        let observer =
+            EventObserver::new(None, format!("127.0.0.1:{}", port), Duration::from_secs(3));
+
+        let payload = json!({"key": "value"});
+
+        observer.send_payload(&payload, "/test");
+
+        // Wait for the server to process the request
+        rx.recv_timeout(Duration::from_secs(5))
+            .expect("Server did not receive request in time");
     }
 
-    pub fn register_observer(&mut self, conf: &EventObserverConfig, working_dir: PathBuf) {
-        info!("Registering event observer at: {}", conf.endpoint);
-        let event_observer = EventObserver::new(
-            Some(working_dir),
-            conf.endpoint.clone(),
-            Duration::from_millis(conf.timeout_ms),
-        );
+    #[test]
+    fn test_send_payload_retry() {
+        let port = get_random_port();
 
-        let observer_index = self.registered_observers.len() as u16;
+        // Set up a channel to notify when the server has processed the request
+        let (tx, rx) = channel();
 
-        for event_key_type in conf.events_keys.iter() {
-            match event_key_type {
-                EventKeyType::SmartContractEvent(event_key) => {
-                    match self
-                        .contract_events_observers_lookup
-                        .entry(event_key.clone())
-                    {
-                        Entry::Occupied(observer_indexes) => {
-                            observer_indexes.into_mut().insert(observer_index);
-                        }
-                        Entry::Vacant(v) => {
-                            let mut observer_indexes = HashSet::new();
-                            observer_indexes.insert(observer_index);
-                            v.insert(observer_indexes);
-                        }
-                    };
-                }
-                EventKeyType::BurnchainBlocks => {
-                    self.burn_block_observers_lookup.insert(observer_index);
-                }
-                EventKeyType::MemPoolTransactions => {
-                    self.mempool_observers_lookup.insert(observer_index);
-                }
-                EventKeyType::Microblocks => {
-                    self.microblock_observers_lookup.insert(observer_index);
-                }
-                EventKeyType::STXEvent => {
-                    self.stx_observers_lookup.insert(observer_index);
-                }
-                EventKeyType::AssetEvent(event_key) => {
-                    match self.assets_observers_lookup.entry(event_key.clone()) {
-                        Entry::Occupied(observer_indexes) => {
-                            observer_indexes.into_mut().insert(observer_index);
-                        }
-                        Entry::Vacant(v) => {
-                            let mut observer_indexes = HashSet::new();
-                            observer_indexes.insert(observer_index);
-                            v.insert(observer_indexes);
-                        }
-                    };
-                }
-                EventKeyType::AnyEvent => {
-                    self.any_event_observers_lookup.insert(observer_index);
-                }
-                EventKeyType::MinedBlocks => {
-                    self.miner_observers_lookup.insert(observer_index);
-                }
-                EventKeyType::MinedMicroblocks => {
-                    self.mined_microblocks_observers_lookup
-                        .insert(observer_index);
-                }
-                EventKeyType::StackerDBChunks => {
-                    self.stackerdb_observers_lookup.insert(observer_index);
-                }
-                EventKeyType::BlockProposal => {
-                    self.block_proposal_observers_lookup.insert(observer_index);
+        // Start a mock server in a separate thread
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            let mut attempt = 0;
+            while let Ok(request) = server.recv() {
+                attempt += 1;
+                if attempt == 1 {
+                    debug!("Mock server received request attempt 1");
+                    // Simulate a failure on the first attempt
+                    let response = Response::new(
+                        StatusCode(500),
+                        vec![],
+                        "Internal Server Error".as_bytes(),
+                        Some(21),
+                        None,
+                    );
+                    request.respond(response).unwrap();
+                } else {
+                    debug!("Mock server received request attempt 2");
+                    // Simulate a successful response on the second attempt
+                    let response = Response::from_string("HTTP/1.1 200 OK");
+                    request.respond(response).unwrap();
+
+                    // Notify the test that the request was processed successfully
+                    tx.send(()).unwrap();
+                    break;
                 }
             }
-        }
+        });
 
-        self.registered_observers.push(event_observer);
-    }
-}
+        let observer =
+            EventObserver::new(None, format!("127.0.0.1:{}", port), Duration::from_secs(3));
 
-#[cfg(test)]
-mod test {
-    use std::net::TcpListener;
-    use std::thread;
-    use std::time::Instant;
+        let payload = json!({"key": "value"});
 
-    use clarity::vm::costs::ExecutionCost;
-    use stacks::burnchains::{PoxConstants, Txid};
-    use stacks::chainstate::nakamoto::{NakamotoBlock, NakamotoBlockHeader};
-    use stacks::chainstate::stacks::db::{StacksBlockHeaderTypes, StacksHeaderInfo};
-    use stacks::chainstate::stacks::events::StacksBlockEventData;
-    use stacks::chainstate::stacks::StacksBlock;
-    use stacks::types::chainstate::BlockHeaderHash;
-    use stacks::util::secp256k1::MessageSignature;
-    use stacks_common::bitvec::BitVec;
-    use stacks_common::types::chainstate::{BurnchainHeaderHash, StacksBlockId};
-    use tempfile::tempdir;
-    use tiny_http::{Method, Response, Server, StatusCode};
+        observer.send_payload(&payload, "/test");
 
-    use super::*;
+        // Wait for the server to process the request
+        rx.recv_timeout(Duration::from_secs(5))
+            .expect("Server did not receive request in time");
+    }
 
     #[test]
-    fn build_block_processed_event() {
-        let observer = EventObserver::new(None, "nowhere".to_string(), Duration::from_secs(3));
+    fn test_send_payload_idempotency_key() {
+        let port = get_random_port();
 
-        let filtered_events = vec![];
-        let block = StacksBlock::genesis_block();
-        let metadata = StacksHeaderInfo::regtest_genesis();
-        let receipts = vec![];
-        let parent_index_hash = StacksBlockId([0; 32]);
-        let winner_txid = Txid([0; 32]);
-        let mature_rewards = serde_json::Value::Array(vec![]);
-        let parent_burn_block_hash = BurnchainHeaderHash([0; 32]);
-        let parent_burn_block_height = 0;
-        let parent_burn_block_timestamp = 0;
-        let anchored_consumed = ExecutionCost::zero();
-        let mblock_confirmed_consumed = ExecutionCost::zero();
-        let pox_constants = PoxConstants::testnet_default();
-        let signer_bitvec = BitVec::zeros(2).expect("Failed to create BitVec with length 2");
-        let block_timestamp = Some(123456);
-        let coinbase_height = 1234;
+        let (tx, rx) = channel();
 
-        let payload = observer.make_new_block_processed_payload(
-            filtered_events,
-            &block.into(),
-            &metadata,
-            &receipts,
-            &parent_index_hash,
-            &winner_txid,
-            &mature_rewards,
-            parent_burn_block_hash,
-            parent_burn_block_height,
-            parent_burn_block_timestamp,
-            &anchored_consumed,
-            &mblock_confirmed_consumed,
-            &pox_constants,
-            &None,
-            &Some(signer_bitvec.clone()),
-            block_timestamp,
-            coinbase_height,
-        );
+        // Fail the first attempt at each event, so that it is retried
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            let mut attempt = 0;
+            while let Ok(request) = server.recv() {
+                attempt += 1;
+                let key = request
+                    .headers()
+                    .iter()
+                    .find(|header| header.field.equiv("Idempotency-Key"))
+                    .map(|header| header.value.to_string());
+                tx.send(key).unwrap();
+                let status = if attempt % 2 == 1 { 500 } else { 200 };
+                request
+                    .respond(Response::empty(StatusCode(status)))
+                    .unwrap();
+                if attempt == 4 {
+                    break;
+                }
+            }
+        });
+
+        let observer =
+            EventObserver::new(None, format!("127.0.0.1:{}", port), Duration::from_secs(3));
+
+        let payload = json!({"key": "value", "block_height": 1});
+        observer.send_payload(&payload, "/test");
+        let first_attempt = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        let retry = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(first_attempt, Some(idempotency_key("/test", &payload)));
+        assert_eq!(first_attempt, retry);
+
+        observer.send_payload(&json!({"key": "value", "block_height": 2}), "/test");
+        let other_event = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(other_event.is_some());
+        assert_ne!(first_attempt, other_event);
         assert_eq!(
-            payload
-                .get("pox_v1_unlock_height")
-                .unwrap()
-                .as_u64()
-                .unwrap(),
-            pox_constants.v1_unlock_height as u64
+            other_event,
+            rx.recv_timeout(Duration::from_secs(5)).unwrap()
         );
 
-        let expected_bitvec_str = serde_json::to_value(signer_bitvec)
-            .unwrap_or_default()
-            .as_str()
-            .unwrap()
-            .to_string();
-        assert_eq!(
-            payload.get("signer_bitvec").unwrap().as_str().unwrap(),
-            expected_bitvec_str
+        // the same payload on another path is another event
+        assert_ne!(
+            idempotency_key("/test", &payload),
+            idempotency_key("/other", &payload)
         );
     }
 
     #[test]
-    fn test_block_processed_event_nakamoto() {
-        let observer = EventObserver::new(None, "nowhere".to_string(), Duration::from_secs(3));
+    fn test_send_payload_keeps_its_serialization() {
+        let port = get_random_port();
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            let mut request = server.recv().unwrap();
+            let key = request
+                .headers()
+                .iter()
+                .find(|header| header.field.equiv("Idempotency-Key"))
+                .map(|header| header.value.to_string())
+                .unwrap();
+            let mut body = String::new();
+            request.as_reader().read_to_string(&mut body).unwrap();
+            request.respond(Response::empty(200)).unwrap();
+            tx.send((key, body)).unwrap();
+        });
 
-        let filtered_events = vec![];
-        let mut block_header = NakamotoBlockHeader::empty();
-        let signer_signature = vec![
-            MessageSignature::from_bytes(&[0; 65]).unwrap(),
-            MessageSignature::from_bytes(&[1; 65]).unwrap(),
-        ];
-        block_header.signer_signature = signer_signature.clone();
-        let block = NakamotoBlock {
-            header: block_header.clone(),
-            txs: vec![],
-        };
-        let mut metadata = StacksHeaderInfo::regtest_genesis();
-        metadata.anchored_header = StacksBlockHeaderTypes::Nakamoto(block_header.clone());
-        let receipts = vec![];
-        let parent_index_hash = StacksBlockId([0; 32]);
-        let winner_txid = Txid([0; 32]);
-        let mature_rewards = serde_json::Value::Array(vec![]);
-        let parent_burn_block_hash = BurnchainHeaderHash([0; 32]);
-        let parent_burn_block_height = 0;
-        let parent_burn_block_timestamp = 0;
-        let anchored_consumed = ExecutionCost::zero();
-        let mblock_confirmed_consumed = ExecutionCost::zero();
-        let pox_constants = PoxConstants::testnet_default();
-        let signer_bitvec = BitVec::zeros(2).expect("Failed to create BitVec with length 2");
-        let block_timestamp = Some(123456);
-        let coinbase_height = 1234;
+        // the body is sent as the payload serializes, and only its idempotency key is computed
+        // over the canonical form
+        let payload: serde_json::Value = serde_json::from_str(r#"{"amount":1.50}"#).unwrap();
+        let observer =
+            EventObserver::new(None, format!("127.0.0.1:{}", port), Duration::from_secs(3));
+        observer.send_payload(&payload, "/test");
+        let (key, body) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(body, r#"{"amount":1.50}"#);
+        assert_eq!(key, idempotency_key("/test", &json!({ "amount": 1.5 })));
+    }
+
+    #[test]
+    fn test_send_payload_partition_key() {
+        let port = get_random_port();
+
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            for _ in 0..3 {
+                let request = server.recv().unwrap();
+                let key = request
+                    .headers()
+                    .iter()
+                    .find(|header| header.field.equiv(PARTITION_KEY_HEADER))
+                    .map(|header| header.value.to_string());
+                request.respond(Response::empty(200)).unwrap();
+                tx.send(key).unwrap();
+            }
+        });
 
-        let payload = observer.make_new_block_processed_payload(
-            filtered_events,
-            &StacksBlockEventData::from((block, BlockHeaderHash([0; 32]))),
-            &metadata,
-            &receipts,
-            &parent_index_hash,
-            &winner_txid,
-            &mature_rewards,
-            parent_burn_block_hash,
-            parent_burn_block_height,
-            parent_burn_block_timestamp,
-            &anchored_consumed,
-            &mblock_confirmed_consumed,
-            &pox_constants,
-            &None,
-            &Some(signer_bitvec.clone()),
-            block_timestamp,
-            coinbase_height,
+        let partitioner = PayloadPartitioner::parse("by_block:4").unwrap();
+        let observer =
+            EventObserver::new(None, format!("127.0.0.1:{}", port), Duration::from_secs(3))
+                .with_partition(Some(partitioner.clone()));
+
+        let block = json!({"index_block_hash": "0x01", "block_height": 1});
+        let header = json!({"index_block_hash": "0x01"});
+        observer.send_payload(&block, PATH_BLOCK_PROCESSED);
+        observer.send_payload(&header, PATH_BLOCK_HEADER);
+        let block_key = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        let header_key = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(
+            block_key,
+            Some(partitioner.partition_key("/new_block", &block).to_string())
         );
+        assert_eq!(block_key, header_key);
 
-        let event_signer_signature = payload
-            .get("signer_signature")
-            .unwrap()
-            .as_array()
-            .expect("Expected signer_signature to be an array")
-            .iter()
-            .cloned()
-            .map(serde_json::from_value::<MessageSignature>)
-            .collect::<Result<Vec<_>, _>>()
-            .expect("Unable to deserialize array of MessageSignature");
-        assert_eq!(event_signer_signature, signer_signature);
+        // observers without a partition get no header
+        let observer =
+            EventObserver::new(None, format!("127.0.0.1:{}", port), Duration::from_secs(3));
+        observer.send_payload(&block, PATH_BLOCK_PROCESSED);
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), None);
     }
 
     #[test]
-    fn test_send_request_connect_timeout() {
-        let timeout_duration = Duration::from_secs(3);
-
-        // Start measuring time
-        let start_time = Instant::now();
+    fn test_send_payload_static_headers() {
+        let port = get_random_port();
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            // the first attempt fails, so that the retry is seen too
+            let mut attempt = 0;
+            while let Ok(request) = server.recv() {
+                attempt += 1;
+                let authorization = request
+                    .headers()
+                    .iter()
+                    .find(|header| header.field.equiv("Authorization"))
+                    .map(|header| header.value.to_string());
+                let status = if attempt == 1 { 500 } else { 200 };
+                request.respond(Response::empty(status)).unwrap();
+                tx.send(authorization).unwrap();
+            }
+        });
 
-        let host = "10.255.255.1"; // non-routable IP for timeout
-        let port = 80;
+        let dir = tempdir().unwrap();
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher
+            .register_observer(
+                &EventObserverConfig {
+                    endpoint: format!("127.0.0.1:{}", port),
+                    events_keys: vec![EventKeyType::AnyEvent],
+                    timeout_ms: 3_000,
+                    delivery_policy: DeliveryPolicyConfig {
+                        backoff_ms: Some(10),
+                        ..DeliveryPolicyConfig::default()
+                    },
+                    headers: BTreeMap::from([(
+                        "Authorization".to_string(),
+                        "Bearer hunter2".to_string(),
+                    )]),
+                    ..EventObserverConfig::default()
+                },
+                dir.path().to_path_buf(),
+            )
+            .unwrap();
+        dispatcher.registered_observers[0].send_payload(&json!({"key": "value"}), "/test");
 
-        let peerhost: PeerHost = format!("{host}:{port}")
-            .parse()
-            .unwrap_or(PeerHost::DNS(host.to_string(), port));
-        let mut request = StacksHttpRequest::new_for_peer(
-            peerhost,
-            "POST".into(),
-            "/".into(),
-            HttpRequestContents::new().payload_json(serde_json::from_slice(b"{}").unwrap()),
-        )
-        .unwrap_or_else(|_| panic!("FATAL: failed to encode infallible data as HTTP request"));
-        request.add_header("Connection".into(), "close".into());
+        for _ in 0..2 {
+            assert_eq!(
+                rx.recv_timeout(Duration::from_secs(5)).unwrap().as_deref(),
+                Some("Bearer hunter2")
+            );
+        }
 
-        // Attempt to send a request with a timeout
-        let result = send_http_request(host, port, request, timeout_duration);
+        // a header that the dispatcher sets itself can't be overridden
+        let err = dispatcher
+            .register_observer(
+                &EventObserverConfig {
+                    endpoint: format!("127.0.0.1:{}", port),
+                    events_keys: vec![EventKeyType::AnyEvent],
+                    timeout_ms: 3_000,
+                    headers: BTreeMap::from([("Idempotency-Key".to_string(), "abcd".to_string())]),
+                    ..EventObserverConfig::default()
+                },
+                dir.path().to_path_buf(),
+            )
+            .unwrap_err();
+        assert!(err.contains("Idempotency-Key"), "{}", err);
+    }
 
-        // Measure the elapsed time
-        let elapsed_time = start_time.elapsed();
+    #[test]
+    fn test_spooled_payloads_keep_their_observers_headers() {
+        fn serve_authorizations(port: u16) -> Receiver<(String, Option<String>)> {
+            let (tx, rx) = channel();
+            let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+            thread::spawn(move || {
+                while let Ok(request) = server.recv() {
+                    let authorization = request
+                        .headers()
+                        .iter()
+                        .find(|header| header.field.equiv("Authorization"))
+                        .map(|header| header.value.to_string());
+                    let url = request.url().to_string();
+                    request.respond(Response::empty(200)).unwrap();
+                    if tx.send((url, authorization)).is_err() {
+                        return;
+                    }
+                }
+            });
+            rx
+        }
 
-        // Assert that the connection attempt timed out
-        assert!(
-            result.is_err(),
-            "Expected a timeout error, but got {:?}",
-            result
+        // two observers share the node's pending-payload database
+        let dir = tempdir().unwrap();
+        let port_a = get_random_port();
+        let port_b = get_random_port();
+        let mut dispatcher = EventDispatcher::new();
+        for (port, token) in [(port_a, "Bearer a"), (port_b, "Bearer b")] {
+            dispatcher
+                .register_observer(
+                    &EventObserverConfig {
+                        endpoint: format!("127.0.0.1:{}", port),
+                        events_keys: vec![EventKeyType::AnyEvent],
+                        timeout_ms: 500,
+                        headers: BTreeMap::from([("Authorization".to_string(), token.to_string())]),
+                        ..EventObserverConfig::default()
+                    },
+                    dir.path().to_path_buf(),
+                )
+                .unwrap();
+        }
+        let observer_a = &dispatcher.registered_observers[0];
+        let observer_b = &dispatcher.registered_observers[1];
+
+        // observer a is down, so its payload stays in the database
+        observer_a.send_payload_with_retry_deadline(
+            &json!({ "for": "a" }),
+            "/test",
+            Some(Instant::now() + Duration::from_millis(300)),
         );
+        let conn = Connection::open(observer_a.db_path.as_ref().unwrap()).unwrap();
+        let observer_a_url = format!("http://127.0.0.1:{}", port_a);
         assert_eq!(
-            result.unwrap_err().kind(),
-            std::io::ErrorKind::TimedOut,
-            "Expected a TimedOut error"
+            EventObserver::count_pending_payloads(&conn, &observer_a_url).unwrap(),
+            1
         );
 
-        // Assert that the elapsed time is within an acceptable range
-        assert!(
-            elapsed_time >= timeout_duration,
-            "Timeout occurred too quickly"
+        // observer b's drain only sends its own payload, with its own token, and isn't held up
+        // by observer a's
+        let rx_b = serve_authorizations(port_b);
+        observer_b.send_payload(&json!({ "for": "b" }), "/test");
+        assert_eq!(
+            rx_b.recv_timeout(Duration::from_secs(5)).unwrap(),
+            ("/test".to_string(), Some("Bearer b".to_string()))
         );
-        assert!(
-            elapsed_time < timeout_duration + Duration::from_secs(1),
-            "Timeout took too long"
+        assert!(rx_b.recv_timeout(Duration::from_millis(200)).is_err());
+        assert_eq!(
+            EventObserver::count_pending_payloads(&conn, &observer_a_url).unwrap(),
+            1
         );
-    }
 
-    fn get_random_port() -> u16 {
-        // Bind to a random port by specifying port 0, then retrieve the port assigned by the OS
-        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind to a random port");
-        listener.local_addr().unwrap().port()
+        // once observer a is back, its backlog goes to it with its own token
+        let rx_a = serve_authorizations(port_a);
+        observer_a.send_payload(&json!({ "for": "a" }), "/test");
+        for _ in 0..2 {
+            assert_eq!(
+                rx_a.recv_timeout(Duration::from_secs(5)).unwrap(),
+                ("/test".to_string(), Some("Bearer a".to_string()))
+            );
+        }
+        assert!(EventObserver::get_pending_payloads(&conn)
+            .unwrap()
+            .is_empty());
     }
 
     #[test]
-    fn test_init_db() {
+    fn test_send_payload_wire_capture() {
         let dir = tempdir().unwrap();
-        let db_path = dir.path().join("test_init_db.sqlite");
-        let db_path_str = db_path.to_str().unwrap();
-
-        // Call init_db
-        let conn_result = EventObserver::init_db(db_path_str);
-        assert!(conn_result.is_ok(), "Failed to initialize the database");
+        let capture_path = dir.path().join("wire.log");
+        let port = get_random_port();
 
-        // Check that the database file exists
-        assert!(db_path.exists(), "Database file was not created");
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            let mut request = server.recv().unwrap();
+            let key = request
+                .headers()
+                .iter()
+                .find(|header| header.field.equiv("Idempotency-Key"))
+                .map(|header| header.value.to_string())
+                .unwrap();
+            let mut body = vec![];
+            request.as_reader().read_to_end(&mut body).unwrap();
+            request.respond(Response::empty(200)).unwrap();
+            tx.send((key, body)).unwrap();
+        });
 
-        // Check that the table exists
-        let conn = conn_result.unwrap();
-        let mut stmt = conn
-            .prepare(
-                "SELECT name FROM sqlite_master WHERE type='table' AND name='pending_payloads'",
-            )
-            .unwrap();
-        let table_exists = stmt.exists([]).unwrap();
-        assert!(table_exists, "Table 'pending_payloads' does not exist");
+        let observer =
+            EventObserver::new(None, format!("127.0.0.1:{}", port), Duration::from_secs(3))
+                .with_payload_envelope(true)
+                .with_wire_capture(Some(WireCapture::File(capture_path.clone())));
+        observer.send_payload(&json!({ "b": 1, "a": "x" }), "/test");
+        let (key, body) = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        // the capture holds the request as it was sent, envelope and all
+        let captured = std::fs::read(&capture_path).unwrap();
+        let header_end = captured.iter().position(|byte| *byte == b'\n').unwrap();
+        let request = &captured[header_end + 1..captured.len() - 1];
+        assert_eq!(
+            String::from_utf8_lossy(&captured[..header_end]),
+            format!("### http://127.0.0.1:{}/test {} bytes", port, request.len())
+        );
+        let request = String::from_utf8(request.to_vec()).unwrap();
+        assert!(request.starts_with("POST /test HTTP/1.1\r\n"));
+        assert!(request.contains(&format!("idempotency-key: {}\r\n", key)));
+        assert!(request.ends_with(&format!("\r\n\r\n{}", String::from_utf8(body).unwrap())));
+        assert!(request.contains(r#""type":"test""#));
     }
 
     #[test]
-    fn test_insert_and_get_pending_payloads() {
-        let dir = tempdir().unwrap();
-        let db_path = dir.path().join("test_payloads.sqlite");
-        let db_path_str = db_path.to_str().unwrap();
+    fn test_send_payload_timeout() {
+        let port = get_random_port();
+        let timeout = Duration::from_secs(3);
 
-        let conn = EventObserver::init_db(db_path_str).expect("Failed to initialize the database");
+        // Set up a channel to notify when the server has processed the request
+        let (tx, rx) = channel();
+
+        // Start a mock server in a separate thread
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            let mut attempt = 0;
+            let mut _request_holder = None;
+            while let Ok(request) = server.recv() {
+                attempt += 1;
+                if attempt == 1 {
+                    debug!("Mock server received request attempt 1");
+                    // Do not reply, forcing the sender to timeout and retry,
+                    // but don't drop the request or it will receive a 500 error,
+                    _request_holder = Some(request);
+                } else {
+                    debug!("Mock server received request attempt 2");
+                    // Simulate a successful response on the second attempt
+                    let response = Response::from_string("HTTP/1.1 200 OK");
+                    request.respond(response).unwrap();
+
+                    // Notify the test that the request was processed successfully
+                    tx.send(()).unwrap();
+                    break;
+                }
+            }
+        });
+
+        let observer = EventObserver::new(None, format!("127.0.0.1:{}", port), timeout);
 
-        let url = "http://example.com/api";
         let payload = json!({"key": "value"});
-        let timeout = Duration::from_secs(5);
 
-        // Insert payload
-        let insert_result = EventObserver::insert_payload(&conn, url, &payload, timeout);
-        assert!(insert_result.is_ok(), "Failed to insert payload");
+        // Record the time before sending the payload
+        let start_time = Instant::now();
 
-        // Get pending payloads
-        let pending_payloads =
-            EventObserver::get_pending_payloads(&conn).expect("Failed to get pending payloads");
-        assert_eq!(pending_payloads.len(), 1, "Expected one pending payload");
+        // Call the function being tested
+        observer.send_payload(&payload, "/test");
 
-        let (_id, retrieved_url, retrieved_payload, timeout_ms) = &pending_payloads[0];
-        assert_eq!(retrieved_url, url, "URL does not match");
-        assert_eq!(retrieved_payload, &payload, "Payload does not match");
-        assert_eq!(
-            *timeout_ms,
-            timeout.as_millis() as u64,
-            "Timeout does not match"
+        // Record the time after the function returns
+        let elapsed_time = start_time.elapsed();
+
+        println!("Elapsed time: {:?}", elapsed_time);
+        assert!(
+            elapsed_time >= timeout,
+            "Expected a timeout, but the function returned too quickly"
+        );
+
+        assert!(
+            elapsed_time < timeout + Duration::from_secs(1),
+            "Expected a timeout, but the function took too long"
         );
+
+        // Wait for the server to process the request
+        rx.recv_timeout(Duration::from_secs(5))
+            .expect("Server did not receive request in time");
     }
 
     #[test]
-    fn test_delete_payload() {
+    fn test_send_payload_with_db_force_restart() {
+        let port = get_random_port();
+        let timeout = Duration::from_secs(3);
         let dir = tempdir().unwrap();
-        let db_path = dir.path().join("test_delete_payload.sqlite");
-        let db_path_str = db_path.to_str().unwrap();
-
-        let conn = EventObserver::init_db(db_path_str).expect("Failed to initialize the database");
-
-        let url = "http://example.com/api";
-        let payload = json!({"key": "value"});
-        let timeout = Duration::from_secs(5);
+        let working_dir = dir.path().to_path_buf();
 
-        // Insert payload
-        EventObserver::insert_payload(&conn, url, &payload, timeout)
-            .expect("Failed to insert payload");
+        // Set up a channel to notify when the server has processed the request
+        let (tx, rx) = channel();
 
-        // Get pending payloads
-        let pending_payloads =
-            EventObserver::get_pending_payloads(&conn).expect("Failed to get pending payloads");
-        assert_eq!(pending_payloads.len(), 1, "Expected one pending payload");
+        info!("Starting mock server on port {}", port);
+        // Start a mock server in a separate thread
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || {
+            let mut attempt = 0;
+            let mut _request_holder = None;
+            while let Ok(mut request) = server.recv() {
+                attempt += 1;
+                match attempt {
+                    1 => {
+                        debug!("Mock server received request attempt 1");
+                        // Do not reply, forcing the sender to timeout and retry,
+                        // but don't drop the request or it will receive a 500 error,
+                        _request_holder = Some(request);
+                    }
+                    2 => {
+                        debug!("Mock server received request attempt 2");
 
-        let (id, _, _, _) = pending_payloads[0];
+                        // Verify the payload
+                        let mut payload = String::new();
+                        request.as_reader().read_to_string(&mut payload).unwrap();
+                        let expected_payload = r#"{"key":"value"}"#;
+                        assert_eq!(payload, expected_payload);
 
-        // Delete payload
-        let delete_result = EventObserver::delete_payload(&conn, id);
-        assert!(delete_result.is_ok(), "Failed to delete payload");
+                        // Simulate a successful response on the second attempt
+                        let response = Response::from_string("HTTP/1.1 200 OK");
+                        request.respond(response).unwrap();
+                    }
+                    3 => {
+                        debug!("Mock server received request attempt 3");
 
-        // Verify that the pending payloads list is empty
-        let pending_payloads =
-            EventObserver::get_pending_payloads(&conn).expect("Failed to get pending payloads");
-        assert_eq!(pending_payloads.len(), 0, "Expected no pending payloads");
-    }
+                        // Verify the payload
+                        let mut payload = String::new();
+                        request.as_reader().read_to_string(&mut payload).unwrap();
+                        let expected_payload = r#"{"key":"value2"}"#;
+                        assert_eq!(payload, expected_payload);
 
-    #[test]
-    fn test_process_pending_payloads() {
-        use mockito::Matcher;
+                        // Simulate a successful response on the second attempt
+                        let response = Response::from_string("HTTP/1.1 200 OK");
+                        request.respond(response).unwrap();
 
-        let dir = tempdir().unwrap();
-        let db_path = dir.path().join("test_process_payloads.sqlite");
-        let db_path_str = db_path.to_str().unwrap();
+                        // When we receive attempt 3 (message 1, re-sent message 1, message 2),
+                        // notify the test that the request was processed successfully
+                        tx.send(()).unwrap();
+                        break;
+                    }
+                    _ => panic!("Unexpected request attempt"),
+                }
+            }
+        });
 
-        let conn = EventObserver::init_db(db_path_str).expect("Failed to initialize the database");
+        let observer = EventObserver::new(
+            Some(working_dir.clone()),
+            format!("127.0.0.1:{}", port),
+            timeout,
+        );
 
         let payload = json!({"key": "value"});
-        let timeout = Duration::from_secs(5);
+        let payload2 = json!({"key": "value2"});
 
-        // Create a mock server
-        let mut server = mockito::Server::new();
-        let _m = server
-            .mock("POST", "/api")
-            .match_header("content-type", Matcher::Regex("application/json.*".into()))
-            .match_body(Matcher::Json(payload.clone()))
-            .with_status(200)
-            .create();
+        // Disable retrying so that it sends the payload only once
+        // and that payload will be ignored by the test server.
+        TEST_EVENT_OBSERVER_SKIP_RETRY.lock().unwrap().replace(true);
 
-        let url = &format!("{}/api", &server.url());
+        info!("Sending payload 1");
 
-        // Insert payload
-        EventObserver::insert_payload(&conn, url, &payload, timeout)
-            .expect("Failed to insert payload");
+        // Send the payload
+        observer.send_payload(&payload, "/test");
 
-        // Process pending payloads
-        EventObserver::process_pending_payloads(&conn);
+        // Re-enable retrying
+        TEST_EVENT_OBSERVER_SKIP_RETRY
+            .lock()
+            .unwrap()
+            .replace(false);
 
-        // Verify that the pending payloads list is empty
-        let pending_payloads =
-            EventObserver::get_pending_payloads(&conn).expect("Failed to get pending payloads");
-        assert_eq!(pending_payloads.len(), 0, "Expected no pending payloads");
+        info!("Sending payload 2");
 
-        // Verify that the mock was called
-        _m.assert();
+        // Send another payload
+        observer.send_payload(&payload2, "/test");
+
+        // Wait for the server to process the requests
+        rx.recv_timeout(Duration::from_secs(5))
+            .expect("Server did not receive request in time");
     }
 
     #[test]
-    fn test_new_event_observer_with_db() {
+    fn test_replay_pending_payloads_on_register() {
+        let port = get_random_port();
         let dir = tempdir().unwrap();
         let working_dir = dir.path().to_path_buf();
+        let conf = EventObserverConfig {
+            endpoint: format!("127.0.0.1:{}/replayed", port),
+            events_keys: vec![EventKeyType::MemPoolTransactions],
+            timeout_ms: 500,
+            parallel_connections: 1,
+            ..EventObserverConfig::default()
+        };
+        let observer = format!("http://127.0.0.1:{}/replayed", port);
 
-        let endpoint = "http://example.com".to_string();
-        let timeout = Duration::from_secs(5);
+        // the node stopped before the observer took these
+        let mut db_path = working_dir.clone();
+        db_path.push("event_observers.sqlite");
+        let conn = EventObserver::init_db(db_path.to_str().unwrap()).unwrap();
+        for n in 0..3 {
+            EventObserver::insert_payload(
+                &conn,
+                &observer,
+                &format!("{}/test", observer),
+                &json!({ "n": n }),
+                Duration::from_millis(500),
+            )
+            .unwrap();
+        }
 
-        let observer = EventObserver::new(Some(working_dir.clone()), endpoint.clone(), timeout);
+        // an observer that is still down doesn't hold up its registration, and keeps its backlog
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher
+            .register_observer(&conf, working_dir.clone())
+            .unwrap();
+        assert_eq!(
+            EventObserver::count_pending_payloads(&conn, &observer).unwrap(),
+            3
+        );
+        drop(dispatcher);
 
-        // Verify fields
-        assert_eq!(observer.endpoint, endpoint);
-        assert_eq!(observer.timeout, timeout);
+        let (tx, rx) = channel();
+        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
+        thread::spawn(move || loop {
+            let mut request = server.recv().unwrap();
+            let mut payload = String::new();
+            request.as_reader().read_to_string(&mut payload).unwrap();
+            let url = request.url().to_string();
+            request.respond(Response::empty(200)).unwrap();
+            let payload: serde_json::Value = serde_json::from_str(&payload).unwrap();
+            if tx.send((url, payload)).is_err() {
+                return;
+            }
+        });
 
-        // Verify that the database was initialized
-        let mut db_path = working_dir;
-        db_path.push("event_observers.sqlite");
-        assert!(db_path.exists(), "Database file was not created");
+        // once it's back, registering it again drains the backlog in order, before any new event
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher.register_observer(&conf, working_dir).unwrap();
+        assert_eq!(
+            EventObserver::count_pending_payloads(&conn, &observer).unwrap(),
+            0
+        );
+        dispatcher.process_dropped_mempool_txs(
+            vec![Txid([1; 32])],
+            MemPoolDropReason::STALE_COLLECT,
+            None,
+        );
+        let received: Vec<_> = (0..4)
+            .map(|_| rx.recv_timeout(Duration::from_secs(5)).unwrap())
+            .collect();
+        let urls: Vec<_> = received.iter().map(|(url, _)| url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                "/replayed/test".to_string(),
+                "/replayed/test".to_string(),
+                "/replayed/test".to_string(),
+                format!("/replayed/{}", PATH_MEMPOOL_TX_DROP),
+            ]
+        );
+        for (n, (_, payload)) in received.iter().take(3).enumerate() {
+            assert_eq!(payload, &json!({ "n": n }));
+        }
     }
 
-    #[test]
-    fn test_new_event_observer_without_db() {
-        let endpoint = "http://example.com".to_string();
-        let timeout = Duration::from_secs(5);
-
-        let observer = EventObserver::new(None, endpoint.clone(), timeout);
-
-        // Verify fields
-        assert_eq!(observer.endpoint, endpoint);
-        assert_eq!(observer.timeout, timeout);
-        assert!(observer.db_path.is_none(), "Expected db_path to be None");
+    /// Make `num_receipts` STX transfer receipts with `events_per_receipt` events each
+    fn make_stx_transfer_receipts(
+        num_receipts: usize,
+        events_per_receipt: usize,
+    ) -> Vec<StacksTransactionReceipt> {
+        use clarity::vm::events::STXTransferEventData;
+        use clarity::vm::types::{BuffData, PrincipalData};
+        use stacks::chainstate::stacks::{TokenTransferMemo, TransactionAuth, TransactionVersion};
+        use stacks_common::types::chainstate::StacksPrivateKey;
+
+        let privk = StacksPrivateKey::new();
+        let sender = PrincipalData::from(boot_code_id("pox-4", false).issuer);
+        let tx = StacksTransaction::new(
+            TransactionVersion::Testnet,
+            TransactionAuth::from_p2pkh(&privk).unwrap(),
+            TransactionPayload::TokenTransfer(sender.clone(), 1, TokenTransferMemo([0u8; 34])),
+        );
+        let event = StacksTransactionEvent::STXEvent(STXEventType::STXTransferEvent(
+            STXTransferEventData {
+                sender: sender.clone(),
+                recipient: sender,
+                amount: 1,
+                memo: BuffData { data: vec![] },
+            },
+        ));
+        (0..num_receipts)
+            .map(|_| {
+                StacksTransactionReceipt::from_stx_transfer(
+                    tx.clone(),
+                    vec![event.clone(); events_per_receipt],
+                    Value::okay_true(),
+                    ExecutionCost::zero(),
+                )
+            })
+            .collect()
     }
 
     #[test]
-    fn test_send_payload_with_db() {
-        use mockito::Matcher;
-
-        let dir = tempdir().unwrap();
-        let working_dir = dir.path().to_path_buf();
-        let payload = json!({"key": "value"});
-
-        // Create a mock server
-        let mut server = mockito::Server::new();
-        let _m = server
-            .mock("POST", "/test")
-            .match_header("content-type", Matcher::Regex("application/json.*".into()))
-            .match_body(Matcher::Json(payload.clone()))
-            .with_status(200)
-            .create();
-
-        let endpoint = server.url().strip_prefix("http://").unwrap().to_string();
-        let timeout = Duration::from_secs(5);
+    fn test_winner_commit_payload() {
+        use stacks::burnchains::BurnchainSigner;
+        use stacks_common::types::chainstate::VRFSeed;
+        use stacks_common::util::vrf::VRFPublicKey;
 
-        let observer = EventObserver::new(Some(working_dir.clone()), endpoint, timeout);
-
-        // Call send_payload
-        observer.send_payload(&payload, "/test");
-
-        // Verify that the payload was sent and database is empty
-        _m.assert();
+        let public_key = VRFPublicKey::from_hex(
+            "a366b51292bef4edd64063d9145c617fec373bceb0758e98cd72becd84d54c7a",
+        )
+        .unwrap();
+        let leader_key = LeaderKeyRegisterOp {
+            consensus_hash: ConsensusHash([0x01; 20]),
+            public_key: public_key.clone(),
+            memo: vec![],
+            txid: Txid([0x02; 32]),
+            vtxindex: 3,
+            block_height: 100,
+            burn_header_hash: BurnchainHeaderHash([0x03; 32]),
+        };
+        let commit = LeaderBlockCommitOp {
+            block_header_hash: BlockHeaderHash([0x04; 32]),
+            new_seed: VRFSeed([0x05; 32]),
+            parent_block_ptr: 101,
+            parent_vtxindex: 1,
+            key_block_ptr: 100,
+            key_vtxindex: 3,
+            memo: vec![],
+            burn_fee: 12345,
+            input: (Txid([0x06; 32]), 0),
+            burn_parent_modulus: 0,
+            apparent_sender: BurnchainSigner("miner".into()),
+            commit_outs: vec![],
+            treatment: vec![],
+            sunset_burn: 0,
+            txid: Txid([0x07; 32]),
+            vtxindex: 2,
+            block_height: 102,
+            burn_header_hash: BurnchainHeaderHash([0x08; 32]),
+        };
 
-        // Verify that the database is empty
-        let db_path = observer.db_path.unwrap();
-        let db_path_str = db_path.to_str().unwrap();
-        let conn = Connection::open(db_path_str).expect("Failed to open database");
-        let pending_payloads =
-            EventObserver::get_pending_payloads(&conn).expect("Failed to get pending payloads");
-        assert_eq!(pending_payloads.len(), 0, "Expected no pending payloads");
+        let winner_commit =
+            EventObserver::make_winner_commit_payload(&commit, 20000, Some(&leader_key));
+        assert_eq!(
+            winner_commit,
+            json!({
+                "txid": format!("0x{}", Txid([0x07; 32])),
+                "burn_block_height": 102,
+                "vtxindex": 2,
+                "burn_fee": 12345,
+                "sunset_burn": 0,
+                "sortition_burn": 20000,
+                "apparent_sender": "miner",
+                "key_block_ptr": 100,
+                "key_vtxindex": 3,
+                "vrf_public_key": format!("0x{}", public_key.to_hex()),
+            })
+        );
+        let without_key = EventObserver::make_winner_commit_payload(&commit, 20000, None);
+        assert_eq!(without_key["vrf_public_key"], serde_json::Value::Null);
+
+        // only added to the payload if given
+        let observer = EventObserver::new(None, "nowhere".to_string(), Duration::from_secs(3))
+            .with_winner_commit(true);
+        let make_payload = |winner_commit: Option<&serde_json::Value>| {
+            observer.make_new_block_processed_payload(&TestNewBlock {
+                winner_commit: winner_commit.cloned(),
+                ..TestNewBlock::default()
+            })
+        };
+        assert_eq!(
+            make_payload(Some(&winner_commit))["winner_commit"],
+            winner_commit
+        );
+        assert!(make_payload(None).get("winner_commit").is_none());
     }
 
     #[test]
-    fn test_send_payload_without_db() {
-        use mockito::Matcher;
-
-        let timeout = Duration::from_secs(5);
-        let payload = json!({"key": "value"});
-
-        // Create a mock server
-        let mut server = mockito::Server::new();
-        let _m = server
-            .mock("POST", "/test")
-            .match_header("content-type", Matcher::Regex("application/json.*".into()))
-            .match_body(Matcher::Json(payload.clone()))
-            .with_status(200)
-            .create();
-
-        let endpoint = server.url().strip_prefix("http://").unwrap().to_string();
+    fn test_sortition_info_payload() {
+        use stacks::core::{StacksEpoch, StacksEpochExtension};
+        use stacks_common::types::chainstate::VRFSeed;
 
-        let observer = EventObserver::new(None, endpoint, timeout);
+        let dir = tempdir().unwrap();
+        let sortdb_path = dir.path().join("sortdb").to_str().unwrap().to_string();
+        let pox_constants = PoxConstants::testnet_default();
+        let sortdb = SortitionDB::connect(
+            &sortdb_path,
+            0,
+            &BurnchainHeaderHash([0x01; 32]),
+            0,
+            &StacksEpoch::all(0, 0, 0),
+            pox_constants.clone(),
+            None,
+            true,
+        )
+        .unwrap();
+        let snapshot = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn()).unwrap();
+
+        // the lookup finds the sortition record for the tenure's consensus hash
+        let mut dispatcher = EventDispatcher::new();
+        assert!(dispatcher
+            .get_sortition_info(&snapshot.consensus_hash, &pox_constants)
+            .is_none());
+        dispatcher.set_sortdb_path(sortdb_path);
+        let sortition_info = dispatcher
+            .get_sortition_info(&snapshot.consensus_hash, &pox_constants)
+            .unwrap();
+        assert_eq!(
+            sortition_info,
+            json!({
+                "sortition_id": format!("0x{}", &snapshot.sortition_id),
+                // no block-commit won the first sortition
+                "vrf_seed": null,
+            })
+        );
+        assert!(dispatcher
+            .get_sortition_info(&ConsensusHash([0x02; 20]), &pox_constants)
+            .is_none());
 
-        // Call send_payload
-        observer.send_payload(&payload, "/test");
+        let with_seed =
+            EventObserver::make_sortition_info_payload(&snapshot, Some(&VRFSeed([0x03; 32])));
+        assert_eq!(
+            with_seed["vrf_seed"],
+            json!(format!("0x{}", VRFSeed([0x03; 32]).to_hex()))
+        );
 
-        // Verify that the payload was sent
-        _m.assert();
+        // only added to the payload if given
+        let observer = EventObserver::new(None, "nowhere".to_string(), Duration::from_secs(3))
+            .with_sortition_info(true);
+        let make_payload = |sortition_info: Option<&serde_json::Value>| {
+            observer.make_new_block_processed_payload(&TestNewBlock {
+                pox_constants: pox_constants.clone(),
+                sortition_info: sortition_info.cloned(),
+                ..TestNewBlock::default()
+            })
+        };
+        let payload = make_payload(Some(&with_seed));
+        assert_eq!(payload["sortition_id"], with_seed["sortition_id"]);
+        assert_eq!(payload["vrf_seed"], with_seed["vrf_seed"]);
+        let payload = make_payload(None);
+        assert!(payload.get("sortition_id").is_none());
+        assert!(payload.get("vrf_seed").is_none());
     }
 
     #[test]
-    fn test_send_payload_success() {
-        let port = get_random_port();
-
-        // Set up a channel to notify when the server has processed the request
-        let (tx, rx) = channel();
-
-        // Start a mock server in a separate thread
-        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
-        thread::spawn(move || {
-            let request = server.recv().unwrap();
-            assert_eq!(request.url(), "/test");
-            assert_eq!(request.method(), &Method::Post);
-
-            // Simulate a successful response
-            let response = Response::from_string("HTTP/1.1 200 OK");
-            request.respond(response).unwrap();
-
-            // Notify the test that the request was processed
-            tx.send(()).unwrap();
-        });
+    fn test_miner_signature_valid() {
+        use stacks_common::types::chainstate::{StacksPrivateKey, StacksPublicKey};
+        use stacks_common::util::vrf::VRFPublicKey;
+
+        let miner_privkey = StacksPrivateKey::new();
+        let mut leader_key = LeaderKeyRegisterOp {
+            consensus_hash: ConsensusHash([0x01; 20]),
+            public_key: VRFPublicKey::from_hex(
+                "a366b51292bef4edd64063d9145c617fec373bceb0758e98cd72becd84d54c7a",
+            )
+            .unwrap(),
+            memo: vec![],
+            txid: Txid([0x02; 32]),
+            vtxindex: 3,
+            block_height: 100,
+            burn_header_hash: BurnchainHeaderHash([0x03; 32]),
+        };
 
-        let observer =
-            EventObserver::new(None, format!("127.0.0.1:{}", port), Duration::from_secs(3));
+        let mut header = NakamotoBlockHeader::empty();
+        header.sign_miner(&miner_privkey).unwrap();
 
-        let payload = json!({"key": "value"});
+        // a leader key without a signing key can't tell
+        assert_eq!(
+            EventObserver::check_miner_signature(&header, &leader_key),
+            None
+        );
 
-        observer.send_payload(&payload, "/test");
+        leader_key.set_nakamoto_signing_key(&Hash160::from_node_public_key(
+            &StacksPublicKey::from_private(&miner_privkey),
+        ));
+        assert_eq!(
+            EventObserver::check_miner_signature(&header, &leader_key),
+            Some(true)
+        );
 
-        // Wait for the server to process the request
-        rx.recv_timeout(Duration::from_secs(5))
-            .expect("Server did not receive request in time");
-    }
+        // signed by some other key
+        let mut other_header = NakamotoBlockHeader::empty();
+        other_header.sign_miner(&StacksPrivateKey::new()).unwrap();
+        assert_eq!(
+            EventObserver::check_miner_signature(&other_header, &leader_key),
+            Some(false)
+        );
 
-    #[test]
-    fn test_send_payload_retry() {
-        let port = get_random_port();
+        // signed by the miner, but over a different block
+        header.chain_length += 1;
+        assert_eq!(
+            EventObserver::check_miner_signature(&header, &leader_key),
+            Some(false)
+        );
 
-        // Set up a channel to notify when the server has processed the request
-        let (tx, rx) = channel();
+        // without a signature at all
+        assert_eq!(
+            EventObserver::check_miner_signature(&NakamotoBlockHeader::empty(), &leader_key),
+            Some(false)
+        );
 
-        // Start a mock server in a separate thread
-        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
-        thread::spawn(move || {
-            let mut attempt = 0;
-            while let Ok(request) = server.recv() {
-                attempt += 1;
-                if attempt == 1 {
-                    debug!("Mock server received request attempt 1");
-                    // Simulate a failure on the first attempt
-                    let response = Response::new(
-                        StatusCode(500),
-                        vec![],
-                        "Internal Server Error".as_bytes(),
-                        Some(21),
-                        None,
-                    );
-                    request.respond(response).unwrap();
-                } else {
-                    debug!("Mock server received request attempt 2");
-                    // Simulate a successful response on the second attempt
-                    let response = Response::from_string("HTTP/1.1 200 OK");
-                    request.respond(response).unwrap();
+        // without a sortition DB, there's no leader key to check against
+        let dispatcher = EventDispatcher::new();
+        assert!(dispatcher
+            .get_miner_signature_valid(&header, &PoxConstants::testnet_default())
+            .is_none());
+
+        // only added to the payload if given
+        let observer = EventObserver::new(None, "nowhere".to_string(), Duration::from_secs(3))
+            .with_miner_signature_valid(true);
+        let make_payload = |miner_signature_valid| {
+            observer.make_new_block_processed_payload(&TestNewBlock {
+                miner_signature_valid,
+                ..TestNewBlock::default()
+            })
+        };
+        assert_eq!(
+            make_payload(Some(true))["miner_signature_valid"],
+            json!(true)
+        );
+        assert_eq!(
+            make_payload(Some(false))["miner_signature_valid"],
+            json!(false)
+        );
+        assert!(make_payload(None).get("miner_signature_valid").is_none());
+    }
 
-                    // Notify the test that the request was processed successfully
-                    tx.send(()).unwrap();
-                    break;
-                }
-            }
-        });
+    #[test]
+    fn test_parent_tenure_consensus_hash() {
+        use stacks::burnchains::Burnchain;
+        use stacks::chainstate::stacks::db::ChainStateBootData;
 
-        let observer =
-            EventObserver::new(None, format!("127.0.0.1:{}", port), Duration::from_secs(3));
+        let dir = tempdir().unwrap();
+        let chainstate_path = dir.path().join("chainstate").to_str().unwrap().to_string();
+        let burnchain = Burnchain::regtest(dir.path().join("burnchain").to_str().unwrap());
+        let mut boot_data = ChainStateBootData::new(&burnchain, vec![], None);
+        let (mut chainstate, _) = StacksChainState::open_and_exec(
+            false,
+            0x80000000,
+            &chainstate_path,
+            Some(&mut boot_data),
+            None,
+        )
+        .unwrap();
 
-        let payload = json!({"key": "value"});
+        // store a parent block, mined in some tenure
+        let mut parent = StacksHeaderInfo::regtest_genesis();
+        parent.consensus_hash = ConsensusHash([0x11; 20]);
+        let parent_id = parent.index_block_hash();
+        let tx = chainstate.db_tx_begin().unwrap();
+        StacksChainState::insert_stacks_block_header(
+            &tx,
+            &StacksBlockId([0; 32]),
+            &parent,
+            &ExecutionCost::zero(),
+            0,
+        )
+        .unwrap();
+        tx.commit().unwrap();
 
-        observer.send_payload(&payload, "/test");
+        // without a chainstate, the parent's tenure is unknown
+        let mut dispatcher = EventDispatcher::new();
+        assert!(dispatcher
+            .get_parent_tenure_consensus_hash(&parent_id)
+            .is_none());
 
-        // Wait for the server to process the request
-        rx.recv_timeout(Duration::from_secs(5))
-            .expect("Server did not receive request in time");
+        dispatcher.set_chainstate_path(chainstate_path);
+        assert_eq!(
+            dispatcher.get_parent_tenure_consensus_hash(&parent_id),
+            Some(ConsensusHash([0x11; 20]))
+        );
+        assert!(dispatcher
+            .get_parent_tenure_consensus_hash(&StacksBlockId([0x22; 32]))
+            .is_none());
+
+        let observer = EventObserver::new(None, "nowhere".to_string(), Duration::from_secs(3));
+        let make_payload =
+            |metadata: &StacksHeaderInfo, parent_tenure_consensus_hash: Option<&ConsensusHash>| {
+                observer.make_new_block_processed_payload(&TestNewBlock {
+                    metadata: metadata.clone(),
+                    parent_index_hash: parent_id,
+                    parent_tenure_consensus_hash: parent_tenure_consensus_hash.cloned(),
+                    ..TestNewBlock::default()
+                })
+            };
+
+        // Nakamoto blocks report their parent's tenure, or null if it couldn't be looked up
+        let mut metadata = StacksHeaderInfo::regtest_genesis();
+        metadata.anchored_header = StacksBlockHeaderTypes::Nakamoto(NakamotoBlockHeader::empty());
+        let parent_tenure_consensus_hash = dispatcher.get_parent_tenure_consensus_hash(&parent_id);
+        let payload = make_payload(&metadata, parent_tenure_consensus_hash.as_ref());
+        assert_eq!(
+            payload["parent_tenure_consensus_hash"],
+            json!(format!("0x{}", parent.consensus_hash))
+        );
+        assert!(make_payload(&metadata, None)["parent_tenure_consensus_hash"].is_null());
+
+        // epoch 2.x blocks don't have the field
+        let payload = make_payload(
+            &StacksHeaderInfo::regtest_genesis(),
+            parent_tenure_consensus_hash.as_ref(),
+        );
+        assert!(payload.get("parent_tenure_consensus_hash").is_none());
     }
 
     #[test]
-    fn test_send_payload_timeout() {
-        let port = get_random_port();
-        let timeout = Duration::from_secs(3);
+    fn test_dispatch_matrix_fast_path() {
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
+        let receipts = make_stx_transfer_receipts(3, 2);
+
+        let mut dispatcher = EventDispatcher::new();
+        for events_keys in [
+            vec![EventKeyType::MemPoolTransactions],
+            vec![EventKeyType::BurnchainBlocks],
+        ] {
+            dispatcher
+                .register_observer(
+                    &EventObserverConfig {
+                        endpoint: "localhost:3700".into(),
+                        events_keys,
+                        timeout_ms: 1_000,
+                        parallel_connections: 1,
+                        ..EventObserverConfig::default()
+                    },
+                    working_dir.clone(),
+                )
+                .unwrap();
+        }
 
-        // Set up a channel to notify when the server has processed the request
-        let (tx, rx) = channel();
+        // no observer gets transaction events, so the events are not walked, but every
+        // observer still gets a row
+        assert!(!dispatcher.has_transaction_event_observers());
+        let dispatch_matrix = dispatcher.make_dispatch_matrix(&receipts, None);
+        assert_eq!(dispatch_matrix, vec![BTreeSet::new(), BTreeSet::new()]);
 
-        // Start a mock server in a separate thread
-        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
-        thread::spawn(move || {
-            let mut attempt = 0;
-            let mut _request_holder = None;
-            while let Ok(request) = server.recv() {
-                attempt += 1;
-                if attempt == 1 {
-                    debug!("Mock server received request attempt 1");
-                    // Do not reply, forcing the sender to timeout and retry,
-                    // but don't drop the request or it will receive a 500 error,
-                    _request_holder = Some(request);
-                } else {
-                    debug!("Mock server received request attempt 2");
-                    // Simulate a successful response on the second attempt
-                    let response = Response::from_string("HTTP/1.1 200 OK");
-                    request.respond(response).unwrap();
+        // the slow path assigns the same (empty) events to each observer
+        let (dispatch_matrix, events) =
+            dispatcher.create_dispatch_matrix_and_event_vector(&receipts, None);
+        assert_eq!(dispatch_matrix, vec![BTreeSet::new(), BTreeSet::new()]);
+        assert_eq!(events.len(), 6);
+
+        dispatcher
+            .register_observer(
+                &EventObserverConfig {
+                    endpoint: "localhost:3701".into(),
+                    events_keys: vec![EventKeyType::STXEvent],
+                    timeout_ms: 1_000,
+                    parallel_connections: 1,
+                    ..EventObserverConfig::default()
+                },
+                working_dir,
+            )
+            .unwrap();
+        assert!(dispatcher.has_transaction_event_observers());
+        let dispatch_matrix = dispatcher.make_dispatch_matrix(&receipts, None);
+        assert_eq!(
+            dispatch_matrix,
+            vec![BTreeSet::new(), BTreeSet::new(), (0..6).collect()]
+        );
+    }
 
-                    // Notify the test that the request was processed successfully
-                    tx.send(()).unwrap();
-                    break;
-                }
-            }
-        });
+    #[test]
+    fn test_stx_supply_delta() {
+        use clarity::vm::events::{STXBurnEventData, STXLockEventData, STXMintEventData};
+        use clarity::vm::types::{PrincipalData, QualifiedContractIdentifier};
 
-        let observer = EventObserver::new(None, format!("127.0.0.1:{}", port), timeout);
+        let dir = tempdir().unwrap();
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher
+            .register_observer(
+                &EventObserverConfig {
+                    endpoint: "localhost:3700".into(),
+                    events_keys: vec![EventKeyType::BurnchainBlocks],
+                    timeout_ms: 1_000,
+                    include_stx_supply_delta: true,
+                    ..EventObserverConfig::default()
+                },
+                dir.path().to_path_buf(),
+            )
+            .unwrap();
+        assert!(!dispatcher.has_transaction_event_observers());
+
+        let principal = PrincipalData::from(boot_code_id("pox-4", false).issuer);
+        let mint = |amount| {
+            StacksTransactionEvent::STXEvent(STXEventType::STXMintEvent(STXMintEventData {
+                recipient: principal.clone(),
+                amount,
+            }))
+        };
+        let mut receipts = make_stx_transfer_receipts(3, 1);
+        receipts[0].events.push(mint(1_000));
+        receipts[1].events.push(StacksTransactionEvent::STXEvent(
+            STXEventType::STXBurnEvent(STXBurnEventData {
+                sender: principal.clone(),
+                amount: 100,
+            }),
+        ));
+        receipts[1].events.push(StacksTransactionEvent::STXEvent(
+            STXEventType::STXLockEvent(STXLockEventData {
+                locked_amount: 50,
+                unlock_height: 200,
+                locked_address: principal.clone(),
+                contract_identifier: QualifiedContractIdentifier::transient(),
+            }),
+        ));
+        // a transaction that failed its post-conditions minted nothing
+        receipts[2].events.push(mint(500));
+        receipts[2].post_condition_aborted = true;
+
+        // the events are walked for the supply delta, even though no observer gets them
+        let mut stx_supply_delta = StxSupplyDelta::default();
+        let dispatch_matrix =
+            dispatcher.make_dispatch_matrix(&receipts, Some(&mut stx_supply_delta));
+        assert_eq!(dispatch_matrix, vec![BTreeSet::new()]);
+        assert_eq!(
+            stx_supply_delta,
+            StxSupplyDelta {
+                minted: 1_000,
+                burned: 100,
+                locked: 50,
+            }
+        );
 
-        let payload = json!({"key": "value"});
+        // without a supply delta to compute, they aren't, and the observer gets no events
+        let dispatch_matrix = dispatcher.make_dispatch_matrix(&receipts, None);
+        assert_eq!(dispatch_matrix, vec![BTreeSet::new()]);
+    }
 
-        // Record the time before sending the payload
-        let start_time = Instant::now();
+    #[test]
+    fn test_dispatch_matrix_deployer_events() {
+        use clarity::vm::events::{FTMintEventData, SmartContractEventData};
+        use clarity::vm::types::PrincipalData;
+        use stacks::chainstate::stacks::{TokenTransferMemo, TransactionAuth, TransactionVersion};
+        use stacks_common::types::chainstate::StacksPrivateKey;
 
-        // Call the function being tested
-        observer.send_payload(&payload, "/test");
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
 
-        // Record the time after the function returns
-        let elapsed_time = start_time.elapsed();
+        let deployer =
+            PrincipalData::parse_standard_principal("ST2CY5V39NHDPWSXMW9QDT3HC3GD6Q6XX4CFRK9AG")
+                .unwrap();
+        let other =
+            PrincipalData::parse_standard_principal("ST2JHG361ZXG51QTKY2NQCVBPPRRE2KZB1HR05NNC")
+                .unwrap();
+        let print_event = |issuer: &StandardPrincipalData, name: &str| {
+            StacksTransactionEvent::SmartContractEvent(SmartContractEventData {
+                key: (
+                    QualifiedContractIdentifier::new(issuer.clone(), name.into()),
+                    "print".into(),
+                ),
+                value: Value::okay_true(),
+            })
+        };
+        let mint_event = |issuer: &StandardPrincipalData, name: &str| {
+            StacksTransactionEvent::FTEvent(FTEventType::FTMintEvent(FTMintEventData {
+                asset_identifier: AssetIdentifier {
+                    contract_identifier: QualifiedContractIdentifier::new(
+                        issuer.clone(),
+                        name.into(),
+                    ),
+                    asset_name: "token".into(),
+                },
+                recipient: PrincipalData::from(other.clone()),
+                amount: 1,
+            }))
+        };
 
-        println!("Elapsed time: {:?}", elapsed_time);
-        assert!(
-            elapsed_time >= timeout,
-            "Expected a timeout, but the function returned too quickly"
+        let privk = StacksPrivateKey::new();
+        let tx = StacksTransaction::new(
+            TransactionVersion::Testnet,
+            TransactionAuth::from_p2pkh(&privk).unwrap(),
+            TransactionPayload::TokenTransfer(
+                PrincipalData::from(other.clone()),
+                1,
+                TokenTransferMemo([0u8; 34]),
+            ),
         );
+        let receipts = vec![StacksTransactionReceipt::from_stx_transfer(
+            tx,
+            vec![
+                print_event(&deployer, "protocol-core"),
+                print_event(&other, "unrelated"),
+                mint_event(&deployer, "protocol-token"),
+                print_event(&deployer, "protocol-vault"),
+                mint_event(&other, "other-token"),
+            ],
+            Value::okay_true(),
+            ExecutionCost::zero(),
+        )];
+
+        let mut dispatcher = EventDispatcher::new();
+        for events_keys in [
+            vec![EventKeyType::DeployerEvent(deployer.clone())],
+            vec![EventKeyType::SmartContractEvent((
+                QualifiedContractIdentifier::new(deployer.clone(), "protocol-core".into()),
+                "print".into(),
+            ))],
+            vec![EventKeyType::DeployerEvent(other.clone())],
+        ] {
+            dispatcher
+                .register_observer(
+                    &EventObserverConfig {
+                        endpoint: "localhost:3700".into(),
+                        events_keys,
+                        timeout_ms: 1_000,
+                        parallel_connections: 1,
+                        ..EventObserverConfig::default()
+                    },
+                    working_dir.clone(),
+                )
+                .unwrap();
+        }
 
-        assert!(
-            elapsed_time < timeout + Duration::from_secs(1),
-            "Expected a timeout, but the function took too long"
+        // the deployer's observer gets every event of each of its contracts, whether or not
+        // they were known when it subscribed
+        assert!(dispatcher.has_transaction_event_observers());
+        let dispatch_matrix = dispatcher.make_dispatch_matrix(&receipts, None);
+        assert_eq!(EventDispatcher::receipt_events(&receipts).count(), 5);
+        assert_eq!(
+            dispatch_matrix,
+            vec![
+                BTreeSet::from([0, 2, 3]),
+                BTreeSet::from([0]),
+                BTreeSet::from([1, 4]),
+            ]
         );
-
-        // Wait for the server to process the request
-        rx.recv_timeout(Duration::from_secs(5))
-            .expect("Server did not receive request in time");
     }
 
     #[test]
-    fn test_send_payload_with_db_force_restart() {
-        let port = get_random_port();
-        let timeout = Duration::from_secs(3);
+    fn test_dispatch_matrix_boot_contract_events() {
+        use clarity::vm::events::{FTMintEventData, STXMintEventData, SmartContractEventData};
+        use clarity::vm::types::PrincipalData;
+        use stacks::chainstate::stacks::{TokenTransferMemo, TransactionAuth, TransactionVersion};
+        use stacks_common::types::chainstate::StacksPrivateKey;
+
         let dir = tempdir().unwrap();
         let working_dir = dir.path().to_path_buf();
 
-        // Set up a channel to notify when the server has processed the request
-        let (tx, rx) = channel();
+        let deployer =
+            PrincipalData::parse_standard_principal("ST2CY5V39NHDPWSXMW9QDT3HC3GD6Q6XX4CFRK9AG")
+                .unwrap();
+        let pox = boot_code_id("pox-4", false);
+        let signers = boot_code_id("signers", false);
+        let protocol = QualifiedContractIdentifier::new(deployer.clone(), "protocol".into());
+        assert!(pox.is_boot() && signers.is_boot() && !protocol.is_boot());
+
+        let print_event = |contract_identifier: &QualifiedContractIdentifier| {
+            StacksTransactionEvent::SmartContractEvent(SmartContractEventData {
+                key: (contract_identifier.clone(), "print".into()),
+                value: Value::okay_true(),
+            })
+        };
+        let mint_event = |contract_identifier: &QualifiedContractIdentifier| {
+            StacksTransactionEvent::FTEvent(FTEventType::FTMintEvent(FTMintEventData {
+                asset_identifier: AssetIdentifier {
+                    contract_identifier: contract_identifier.clone(),
+                    asset_name: "token".into(),
+                },
+                recipient: PrincipalData::from(deployer.clone()),
+                amount: 1,
+            }))
+        };
 
-        info!("Starting mock server on port {}", port);
-        // Start a mock server in a separate thread
-        let server = Server::http(format!("127.0.0.1:{}", port)).unwrap();
-        thread::spawn(move || {
-            let mut attempt = 0;
-            let mut _request_holder = None;
-            while let Ok(mut request) = server.recv() {
-                attempt += 1;
-                match attempt {
-                    1 => {
-                        debug!("Mock server received request attempt 1");
-                        // Do not reply, forcing the sender to timeout and retry,
-                        // but don't drop the request or it will receive a 500 error,
-                        _request_holder = Some(request);
-                    }
-                    2 => {
-                        debug!("Mock server received request attempt 2");
+        let privk = StacksPrivateKey::new();
+        let tx = StacksTransaction::new(
+            TransactionVersion::Testnet,
+            TransactionAuth::from_p2pkh(&privk).unwrap(),
+            TransactionPayload::TokenTransfer(
+                PrincipalData::from(deployer.clone()),
+                1,
+                TokenTransferMemo([0u8; 34]),
+            ),
+        );
+        let receipts = vec![StacksTransactionReceipt::from_stx_transfer(
+            tx,
+            vec![
+                print_event(&pox),
+                print_event(&protocol),
+                mint_event(&signers),
+                mint_event(&protocol),
+                StacksTransactionEvent::STXEvent(STXEventType::STXMintEvent(STXMintEventData {
+                    recipient: PrincipalData::from(deployer.clone()),
+                    amount: 1,
+                })),
+                print_event(&signers),
+            ],
+            Value::okay_true(),
+            ExecutionCost::zero(),
+        )];
+
+        let mut dispatcher = EventDispatcher::new();
+        for (endpoint, events_keys) in [
+            ("boot:3700", vec![EventKeyType::BootContractEvent]),
+            (
+                "deployer:3700",
+                vec![EventKeyType::DeployerEvent(deployer.clone())],
+            ),
+        ] {
+            dispatcher
+                .register_observer(
+                    &EventObserverConfig {
+                        endpoint: endpoint.into(),
+                        events_keys,
+                        timeout_ms: 1_000,
+                        parallel_connections: 1,
+                        ..EventObserverConfig::default()
+                    },
+                    working_dir.clone(),
+                )
+                .unwrap();
+        }
 
-                        // Verify the payload
-                        let mut payload = String::new();
-                        request.as_reader().read_to_string(&mut payload).unwrap();
-                        let expected_payload = r#"{"key":"value"}"#;
-                        assert_eq!(payload, expected_payload);
+        // the boot contracts' observer gets their contract and asset events, but not the events
+        // of other contracts, nor STX events
+        assert!(dispatcher.has_transaction_event_observers());
+        let dispatch_matrix = dispatcher.make_dispatch_matrix(&receipts, None);
+        assert_eq!(EventDispatcher::receipt_events(&receipts).count(), 6);
+        assert_eq!(
+            dispatch_matrix,
+            vec![BTreeSet::from([0, 2, 5]), BTreeSet::from([1, 3])]
+        );
 
-                        // Simulate a successful response on the second attempt
-                        let response = Response::from_string("HTTP/1.1 200 OK");
-                        request.respond(response).unwrap();
-                    }
-                    3 => {
-                        debug!("Mock server received request attempt 3");
+        assert_eq!(
+            dispatcher.observers_for(&EventKeyType::SmartContractEvent((pox, "print".into()))),
+            vec!["boot:3700"]
+        );
+        assert_eq!(
+            dispatcher.observers_for(&EventKeyType::SmartContractEvent((
+                protocol,
+                "print".into()
+            ))),
+            vec!["deployer:3700"]
+        );
+        assert_eq!(
+            dispatcher.observers_for(&EventKeyType::BootContractEvent),
+            vec!["boot:3700"]
+        );
+        assert_eq!(
+            EventKeyType::from_key_string("boot_contracts"),
+            Some(EventKeyType::BootContractEvent)
+        );
+        assert_eq!(
+            EventKeyType::BootContractEvent.to_key_string(),
+            "boot_contracts"
+        );
+    }
 
-                        // Verify the payload
-                        let mut payload = String::new();
-                        request.as_reader().read_to_string(&mut payload).unwrap();
-                        let expected_payload = r#"{"key":"value2"}"#;
-                        assert_eq!(payload, expected_payload);
+    #[test]
+    fn test_observers_for() {
+        use clarity::vm::types::PrincipalData;
 
-                        // Simulate a successful response on the second attempt
-                        let response = Response::from_string("HTTP/1.1 200 OK");
-                        request.respond(response).unwrap();
+        let dir = tempdir().unwrap();
+        let working_dir = dir.path().to_path_buf();
 
-                        // When we receive attempt 3 (message 1, re-sent message 1, message 2),
-                        // notify the test that the request was processed successfully
-                        tx.send(()).unwrap();
-                        break;
-                    }
-                    _ => panic!("Unexpected request attempt"),
-                }
-            }
-        });
+        let deployer =
+            PrincipalData::parse_standard_principal("ST2CY5V39NHDPWSXMW9QDT3HC3GD6Q6XX4CFRK9AG")
+                .unwrap();
+        let other =
+            PrincipalData::parse_standard_principal("ST2JHG361ZXG51QTKY2NQCVBPPRRE2KZB1HR05NNC")
+                .unwrap();
+        let core = QualifiedContractIdentifier::new(deployer.clone(), "protocol-core".into());
+        let token = AssetIdentifier {
+            contract_identifier: QualifiedContractIdentifier::new(
+                deployer.clone(),
+                "protocol-token".into(),
+            ),
+            asset_name: "token".into(),
+        };
+        let other_token = AssetIdentifier {
+            contract_identifier: QualifiedContractIdentifier::new(
+                other.clone(),
+                "other-token".into(),
+            ),
+            asset_name: "token".into(),
+        };
 
-        let observer = EventObserver::new(
-            Some(working_dir.clone()),
-            format!("127.0.0.1:{}", port),
-            timeout,
+        let mut dispatcher = EventDispatcher::new();
+        for (endpoint, events_keys) in [
+            (
+                "contract:3700",
+                vec![EventKeyType::SmartContractEvent((
+                    core.clone(),
+                    "print".into(),
+                ))],
+            ),
+            ("asset:3700", vec![EventKeyType::AssetEvent(token.clone())]),
+            (
+                "deployer:3700",
+                vec![EventKeyType::DeployerEvent(deployer.clone())],
+            ),
+            ("any:3700", vec![EventKeyType::AnyEvent]),
+            (
+                "miner:3700",
+                vec![EventKeyType::MinedBlocks, EventKeyType::BurnchainBlocks],
+            ),
+        ] {
+            dispatcher
+                .register_observer(
+                    &EventObserverConfig {
+                        endpoint: endpoint.into(),
+                        events_keys,
+                        timeout_ms: 1_000,
+                        ..EventObserverConfig::default()
+                    },
+                    working_dir.clone(),
+                )
+                .unwrap();
+        }
+
+        // contract and asset events also reach the deployer's and the any-event observers
+        assert_eq!(
+            dispatcher.observers_for(&EventKeyType::SmartContractEvent((
+                core.clone(),
+                "print".into()
+            ))),
+            vec!["contract:3700", "deployer:3700", "any:3700"]
+        );
+        assert_eq!(
+            dispatcher.observers_for(&EventKeyType::SmartContractEvent((core, "other".into()))),
+            vec!["deployer:3700", "any:3700"]
+        );
+        assert_eq!(
+            dispatcher.observers_for(&EventKeyType::AssetEvent(token)),
+            vec!["asset:3700", "deployer:3700", "any:3700"]
+        );
+        assert_eq!(
+            dispatcher.observers_for(&EventKeyType::AssetEvent(other_token)),
+            vec!["any:3700"]
+        );
+        assert_eq!(
+            dispatcher.observers_for(&EventKeyType::DeployerEvent(other)),
+            vec!["any:3700"]
         );
 
-        let payload = json!({"key": "value"});
-        let payload2 = json!({"key": "value2"});
+        // `*` covers burn blocks, but not mined blocks
+        assert_eq!(
+            dispatcher.observers_for(&EventKeyType::BurnchainBlocks),
+            vec!["any:3700", "miner:3700"]
+        );
+        assert_eq!(
+            dispatcher.observers_for(&EventKeyType::MinedBlocks),
+            vec!["miner:3700"]
+        );
+        assert_eq!(
+            dispatcher.observers_for(&EventKeyType::AnyEvent),
+            vec!["any:3700"]
+        );
+        assert!(dispatcher
+            .observers_for(&EventKeyType::StackerDBChunks)
+            .is_empty());
+    }
 
-        // Disable retrying so that it sends the payload only once
-        // and that payload will be ignored by the test server.
-        TEST_EVENT_OBSERVER_SKIP_RETRY.lock().unwrap().replace(true);
+    /// Compare the cost of building the dispatch matrix with and without the fast path for a
+    /// node whose observers do not receive transaction events.
+    /// Run with `cargo test --release bench_dispatch_matrix_fast_path -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_dispatch_matrix_fast_path() {
+        let dir = tempdir().unwrap();
+        let receipts = make_stx_transfer_receipts(1_000, 10);
+
+        let mut dispatcher = EventDispatcher::new();
+        dispatcher
+            .register_observer(
+                &EventObserverConfig {
+                    endpoint: "localhost:3700".into(),
+                    events_keys: vec![
+                        EventKeyType::MemPoolTransactions,
+                        EventKeyType::BurnchainBlocks,
+                    ],
+                    timeout_ms: 1_000,
+                    parallel_connections: 1,
+                    ..EventObserverConfig::default()
+                },
+                dir.path().to_path_buf(),
+            )
+            .unwrap();
 
-        info!("Sending payload 1");
+        let iterations = 1_000;
+        let start_time = Instant::now();
+        for _ in 0..iterations {
+            let (dispatch_matrix, events) =
+                dispatcher.create_dispatch_matrix_and_event_vector(&receipts, None);
+            assert_eq!(dispatch_matrix.len() + events.len(), 10_001);
+        }
+        let full = start_time.elapsed();
 
-        // Send the payload
-        observer.send_payload(&payload, "/test");
+        let start_time = Instant::now();
+        for _ in 0..iterations {
+            let dispatch_matrix = dispatcher.make_dispatch_matrix(&receipts, None);
+            assert_eq!(dispatch_matrix.len(), 1);
+        }
+        let fast = start_time.elapsed();
 
-        // Re-enable retrying
-        TEST_EVENT_OBSERVER_SKIP_RETRY
-            .lock()
-            .unwrap()
-            .replace(false);
+        println!(
+            "Dispatch matrix for 1,000 receipts with 10 events each, {} iterations: full {:?}, fast path {:?}",
+            iterations, full, fast
+        );
+        assert!(fast < full);
+    }
 
-        info!("Sending payload 2");
+    /// Compare serializing a large block's `new_block` payloads for many observers one observer
+    /// at a time, from parts shared by all observers, and from shared parts on several threads.
+    /// Run with `cargo test --release bench_parallel_block_payload_serialization -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_parallel_block_payload_serialization() {
+        let receipts = make_stx_transfer_receipts(2_000, 5);
+        // every observer receives every event
+        let filtered_events: Vec<_> = EventDispatcher::receipt_events(&receipts)
+            .enumerate()
+            .collect();
+        let event_ids: Vec<_> = (0..filtered_events.len()).collect();
+        let observer_ids: Vec<_> = (0..32).collect();
 
-        // Send another payload
-        observer.send_payload(&payload2, "/test");
+        let block: StacksBlockEventData = StacksBlock::genesis_block().into();
+        let metadata = StacksHeaderInfo::regtest_genesis();
+        let pox_constants = PoxConstants::testnet_default();
+        let make_parts = |threads| {
+            let common = EventObserver::make_new_block_common_payload(
+                &block,
+                &metadata,
+                &StacksBlockId([0; 32]),
+                &Txid([0; 32]),
+                &json!([]),
+                BurnchainHeaderHash([0; 32]),
+                0,
+                0,
+                &ExecutionCost::zero(),
+                &ExecutionCost::zero(),
+                &pox_constants,
+                &None,
+                &None,
+                None,
+                1,
+                0,
+                None,
+            );
+            EventObserver::make_new_block_payload_parts(
+                common,
+                &filtered_events,
+                &receipts,
+                false,
+                threads,
+            )
+        };
 
-        // Wait for the server to process the requests
-        rx.recv_timeout(Duration::from_secs(5))
-            .expect("Server did not receive request in time");
+        let start_time = Instant::now();
+        let per_observer: Vec<_> = observer_ids
+            .iter()
+            .map(|_| make_parts(1).assemble(&event_ids, false, None, None, None))
+            .collect();
+        let per_observer_time = start_time.elapsed();
+
+        let mut shared_times = vec![];
+        for threads in [1, 2, 4, 8] {
+            let start_time = Instant::now();
+            let parts = make_parts(threads);
+            let payloads = parallel_map(&observer_ids, threads, |_, _| {
+                parts.assemble(&event_ids, false, None, None, None)
+            });
+            shared_times.push((threads, start_time.elapsed()));
+            assert_eq!(payloads, per_observer);
+        }
+
+        println!(
+            "new_block payloads for 32 observers of 2,000 receipts with 5 events each: one observer at a time {:?}, from shared parts on (threads, time) {:?}",
+            per_observer_time, shared_times
+        );
+        assert!(shared_times[0].1 < per_observer_time);
     }
 }
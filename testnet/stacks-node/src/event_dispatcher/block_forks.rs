@@ -0,0 +1,138 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, HashMap};
+
+use stacks_common::types::chainstate::{ConsensusHash, StacksBlockId};
+
+/// How many of the most recent Stacks block heights to remember. Forks whose blocks are
+/// further apart than this are not detected.
+const MAX_TRACKED_BLOCK_HEIGHTS: u64 = 256;
+
+/// The Nakamoto blocks processed at recent heights, to find blocks that compete for the same
+/// slot in a tenure
+#[derive(Default)]
+pub struct BlockForkTracker {
+    /// The blocks processed at each height, with their tenures
+    by_height: BTreeMap<u64, Vec<(ConsensusHash, StacksBlockId)>>,
+    /// The parent and height of each tracked block
+    parents: HashMap<StacksBlockId, (StacksBlockId, u64)>,
+}
+
+impl BlockForkTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the processing of `block_id`, the block at `height` in the tenure with
+    /// `consensus_hash`. Returns the blocks already processed for the same tenure and height.
+    pub fn record(
+        &mut self,
+        consensus_hash: &ConsensusHash,
+        height: u64,
+        block_id: &StacksBlockId,
+        parent_block_id: &StacksBlockId,
+    ) -> Vec<StacksBlockId> {
+        self.parents
+            .insert(block_id.clone(), (parent_block_id.clone(), height));
+        let blocks = self.by_height.entry(height).or_default();
+        let siblings = blocks
+            .iter()
+            .filter(|(ch, id)| ch == consensus_hash && id != block_id)
+            .map(|(_, id)| id.clone())
+            .collect();
+        if !blocks.iter().any(|(_, id)| id == block_id) {
+            blocks.push((consensus_hash.clone(), block_id.clone()));
+        }
+
+        let max_height = self.by_height.last_key_value().map_or(0, |(h, _)| *h);
+        let min_height = max_height.saturating_sub(MAX_TRACKED_BLOCK_HEIGHTS);
+        while let Some(entry) = self.by_height.first_entry() {
+            if *entry.key() > min_height {
+                break;
+            }
+            for (_, id) in entry.remove() {
+                self.parents.remove(&id);
+            }
+        }
+        siblings
+    }
+
+    /// Find the ancestor at `height` of `tip`, a block at `tip_height`. Returns `None` if the
+    /// path from `tip` leaves the tracked blocks before reaching `height`.
+    pub fn ancestor_at(
+        &self,
+        tip: &StacksBlockId,
+        tip_height: u64,
+        height: u64,
+    ) -> Option<StacksBlockId> {
+        let mut block_id = tip.clone();
+        let mut block_height = tip_height;
+        while block_height > height {
+            let (parent, _) = self.parents.get(&block_id)?;
+            block_id = parent.clone();
+            block_height -= 1;
+        }
+        (block_height == height).then_some(block_id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_block_fork_tracker() {
+        let mut tracker = BlockForkTracker::new();
+        let tenure = ConsensusHash([0x01; 20]);
+        let other_tenure = ConsensusHash([0x02; 20]);
+        let parent = StacksBlockId([0x00; 32]);
+        let block_a = StacksBlockId([0x0a; 32]);
+        let block_b = StacksBlockId([0x0b; 32]);
+        let block_c = StacksBlockId([0x0c; 32]);
+        let child_a = StacksBlockId([0xaa; 32]);
+
+        assert!(tracker.record(&tenure, 10, &block_a, &parent).is_empty());
+        assert!(tracker.record(&tenure, 11, &child_a, &block_a).is_empty());
+        // re-processing the same block is not a fork
+        assert!(tracker.record(&tenure, 10, &block_a, &parent).is_empty());
+        assert_eq!(
+            tracker.record(&tenure, 10, &block_b, &parent),
+            vec![block_a.clone()]
+        );
+        // a block of another tenure at the same height doesn't compete for the same slot
+        assert!(tracker
+            .record(&other_tenure, 10, &block_c, &parent)
+            .is_empty());
+
+        assert_eq!(tracker.ancestor_at(&child_a, 11, 10), Some(block_a.clone()));
+        assert_eq!(tracker.ancestor_at(&block_b, 10, 10), Some(block_b.clone()));
+        assert_eq!(tracker.ancestor_at(&child_a, 11, 9), Some(parent));
+        assert_eq!(tracker.ancestor_at(&child_a, 11, 8), None);
+
+        // old heights are forgotten
+        let mut prev = child_a.clone();
+        for height in 12..(12 + MAX_TRACKED_BLOCK_HEIGHTS) {
+            let mut bytes = [0xff; 32];
+            bytes[..8].copy_from_slice(&height.to_be_bytes());
+            let block = StacksBlockId(bytes);
+            tracker.record(&tenure, height, &block, &prev);
+            prev = block;
+        }
+        assert!(tracker.record(&tenure, 10, &block_c, &parent).is_empty());
+        assert!(tracker.parents.get(&block_a).is_none());
+    }
+}
@@ -0,0 +1,162 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Strict block ordering for an observer.
+//!
+//! While a block's `new_block` event is being delivered to an observer with
+//! `strict_block_ordering` set, that observer's events of every other topic (e.g.
+//! `stackerdb_chunks` or `proposal_response`) are held back, whichever thread sends them. They
+//! are released once the `new_block` delivery is confirmed, or once they have waited for the
+//! node's block retry budget, if it has one.
+
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+/// Holds back an observer's non-block events while one of its blocks is being delivered
+#[derive(Debug, Default)]
+pub struct BlockOrderGate {
+    /// How many blocks are being delivered to the observer
+    blocks_in_flight: Mutex<usize>,
+    released: Condvar,
+    /// How long a held-back event waits for the blocks before it's sent anyway. `None` waits
+    /// until they are delivered.
+    max_wait: Mutex<Option<Duration>>,
+}
+
+/// Keeps a `BlockOrderGate` closed until dropped
+pub struct BlockOrderHold<'a> {
+    gate: &'a BlockOrderGate,
+}
+
+impl BlockOrderGate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Close the gate until the returned hold is dropped, i.e. until the block's delivery is
+    /// confirmed
+    pub fn hold(&self) -> BlockOrderHold<'_> {
+        let mut blocks_in_flight = self
+            .blocks_in_flight
+            .lock()
+            .expect("FATAL: block order gate lock poisoned");
+        *blocks_in_flight += 1;
+        BlockOrderHold { gate: self }
+    }
+
+    /// Stop waiting for the blocks after `max_wait`, or never, if it's `None`
+    pub fn set_max_wait(&self, max_wait: Option<Duration>) {
+        *self
+            .max_wait
+            .lock()
+            .expect("FATAL: block order gate lock poisoned") = max_wait;
+    }
+
+    pub fn max_wait(&self) -> Option<Duration> {
+        *self
+            .max_wait
+            .lock()
+            .expect("FATAL: block order gate lock poisoned")
+    }
+
+    /// Block until no block is being delivered, or for at most `max_wait`. Returns `false` if it
+    /// stopped waiting while a block was still being delivered.
+    pub fn wait_for_blocks(&self) -> bool {
+        let max_wait = self.max_wait();
+        let blocks_in_flight = self
+            .blocks_in_flight
+            .lock()
+            .expect("FATAL: block order gate lock poisoned");
+        let Some(max_wait) = max_wait else {
+            let _blocks_in_flight = self
+                .released
+                .wait_while(blocks_in_flight, |blocks_in_flight| *blocks_in_flight > 0)
+                .expect("FATAL: block order gate lock poisoned");
+            return true;
+        };
+        let (_blocks_in_flight, result) = self
+            .released
+            .wait_timeout_while(blocks_in_flight, max_wait, |blocks_in_flight| {
+                *blocks_in_flight > 0
+            })
+            .expect("FATAL: block order gate lock poisoned");
+        !result.timed_out()
+    }
+}
+
+impl Drop for BlockOrderHold<'_> {
+    fn drop(&mut self) {
+        let mut blocks_in_flight = self
+            .gate
+            .blocks_in_flight
+            .lock()
+            .expect("FATAL: block order gate lock poisoned");
+        *blocks_in_flight -= 1;
+        if *blocks_in_flight == 0 {
+            self.gate.released.notify_all();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::mpsc::channel;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_block_order_gate() {
+        let gate = Arc::new(BlockOrderGate::new());
+
+        // an open gate doesn't wait
+        assert!(gate.wait_for_blocks());
+
+        let first = gate.hold();
+        let second = gate.hold();
+        let (tx, rx) = channel();
+        let waiter = {
+            let gate = gate.clone();
+            thread::spawn(move || {
+                assert!(gate.wait_for_blocks());
+                tx.send(()).unwrap();
+            })
+        };
+
+        // held back until every block in flight is delivered
+        assert!(rx.recv_timeout(Duration::from_millis(200)).is_err());
+        drop(first);
+        assert!(rx.recv_timeout(Duration::from_millis(200)).is_err());
+        drop(second);
+        rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        waiter.join().unwrap();
+    }
+
+    #[test]
+    fn test_block_order_gate_max_wait() {
+        let gate = BlockOrderGate::new();
+        gate.set_max_wait(Some(Duration::from_millis(100)));
+
+        // gives up on a block that isn't delivered in time
+        let hold = gate.hold();
+        assert!(!gate.wait_for_blocks());
+
+        drop(hold);
+        assert!(gate.wait_for_blocks());
+    }
+}
@@ -0,0 +1,174 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use serde_json::Value;
+
+/// The arrays of a `new_block` payload that are split across parts, in the order they are filled
+const SPLIT_ARRAYS: [&str; 2] = ["transactions", "events"];
+
+/// The size of `value` as it is sent, in bytes
+fn encoded_len(value: &Value) -> u64 {
+    serde_json::to_vec(value).map_or(0, |bytes| bytes.len() as u64)
+}
+
+/// Split a `new_block` payload whose encoding is larger than `max_bytes` into parts.
+/// Each part repeats every field of the payload except `transactions` and `events`, which are
+/// divided between the parts in order, and adds `part` (1-based) and `total_parts`. A part is
+/// only larger than `max_bytes` if it holds a single item that doesn't fit on its own.
+/// Returns None if the payload fits as-is, or has no arrays to split.
+pub fn split_block_payload(payload: &Value, max_bytes: u64) -> Option<Vec<Value>> {
+    if encoded_len(payload) <= max_bytes {
+        return None;
+    }
+    let Value::Object(fields) = payload else {
+        return None;
+    };
+
+    let mut base = fields.clone();
+    let mut items = vec![];
+    for name in SPLIT_ARRAYS {
+        if let Some(Value::Array(array)) = base.get_mut(name) {
+            items.extend(array.drain(..).map(|item| (name, item)));
+        }
+    }
+    if items.is_empty() {
+        return None;
+    }
+
+    // size the part indicator for the worst case, so that every part is sized the same way
+    base.insert("part".into(), Value::from(u32::MAX));
+    base.insert("total_parts".into(), Value::from(u32::MAX));
+    let base_len = encoded_len(&Value::Object(base.clone()));
+
+    // greedily fill each part, in order. Each item also costs a separating comma.
+    let mut parts: Vec<Vec<(&str, Value)>> = vec![];
+    let mut part_len = base_len;
+    for (name, item) in items.into_iter() {
+        let item_len = encoded_len(&item) + 1;
+        match parts.last_mut() {
+            Some(part) if part_len.saturating_add(item_len) <= max_bytes => {
+                part_len += item_len;
+                part.push((name, item));
+            }
+            _ => {
+                part_len = base_len.saturating_add(item_len);
+                parts.push(vec![(name, item)]);
+            }
+        }
+    }
+
+    let total_parts = parts.len();
+    let parts = parts
+        .into_iter()
+        .enumerate()
+        .map(|(i, part_items)| {
+            let mut part = base.clone();
+            part.insert("part".into(), Value::from(i + 1));
+            part.insert("total_parts".into(), Value::from(total_parts));
+            for (name, item) in part_items.into_iter() {
+                if let Some(Value::Array(array)) = part.get_mut(name) {
+                    array.push(item);
+                }
+            }
+            Value::Object(part)
+        })
+        .collect();
+    Some(parts)
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn make_block_payload(num_txs: usize, num_events: usize) -> Value {
+        let transactions: Vec<_> = (0..num_txs)
+            .map(|i| json!({ "txid": format!("0x{:064x}", i), "tx_index": i, "raw_tx": "0x00" }))
+            .collect();
+        let events: Vec<_> = (0..num_events)
+            .map(|i| json!({ "txid": format!("0x{:064x}", i), "event_index": i, "type": "stx_transfer_event" }))
+            .collect();
+        json!({
+            "block_hash": format!("0x{}", "11".repeat(32)),
+            "index_block_hash": format!("0x{}", "22".repeat(32)),
+            "block_height": 123,
+            "transactions": transactions,
+            "events": events,
+            "matured_miner_rewards": [],
+        })
+    }
+
+    /// Put a split payload back together, checking the part indicators along the way
+    fn reassemble(parts: &[Value]) -> Value {
+        let mut reassembled = parts[0].as_object().unwrap().clone();
+        reassembled.remove("part");
+        reassembled.remove("total_parts");
+        for name in SPLIT_ARRAYS {
+            reassembled.insert(name.into(), json!([]));
+        }
+        for (i, part) in parts.iter().enumerate() {
+            assert_eq!(part["part"], json!(i + 1));
+            assert_eq!(part["total_parts"], json!(parts.len()));
+            for (key, value) in part.as_object().unwrap().iter() {
+                if SPLIT_ARRAYS.contains(&key.as_str()) {
+                    let items = value.as_array().unwrap().clone();
+                    reassembled[key].as_array_mut().unwrap().extend(items);
+                } else if key != "part" && key != "total_parts" {
+                    // header fields repeat on every part
+                    assert_eq!(&reassembled[key], value);
+                }
+            }
+        }
+        Value::Object(reassembled)
+    }
+
+    #[test]
+    fn test_split_block_payload_reassembles() {
+        let payload = make_block_payload(20, 50);
+        let size = encoded_len(&payload);
+
+        // fits as-is
+        assert!(split_block_payload(&payload, size).is_none());
+
+        for max_bytes in [size - 1, size / 2, size / 7, 600] {
+            let parts = split_block_payload(&payload, max_bytes).unwrap();
+            assert!(parts.len() > 1);
+            for part in parts.iter() {
+                assert!(encoded_len(part) <= max_bytes);
+                assert!(
+                    !part["transactions"].as_array().unwrap().is_empty()
+                        || !part["events"].as_array().unwrap().is_empty()
+                );
+            }
+            assert_eq!(reassemble(&parts), payload);
+        }
+
+        // items that are too big on their own get a part each
+        let parts = split_block_payload(&payload, 1).unwrap();
+        assert_eq!(parts.len(), 70);
+        assert_eq!(reassemble(&parts), payload);
+    }
+
+    #[test]
+    fn test_split_block_payload_without_arrays() {
+        let payload = json!({ "block_hash": format!("0x{}", "11".repeat(32)) });
+        assert!(split_block_payload(&payload, 1).is_none());
+
+        let payload = json!({ "block_hash": "0x00", "transactions": [], "events": [] });
+        assert!(split_block_payload(&payload, 1).is_none());
+    }
+}
@@ -0,0 +1,120 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+
+use stacks_common::types::chainstate::BurnchainHeaderHash;
+use stacks_common::util::hash::Sha512Trunc256Sum;
+
+/// How many of the most recent burn block heights to remember. Burnchain reorgs deeper than
+/// this are not detected.
+const MAX_TRACKED_BURN_BLOCKS: usize = 256;
+
+/// How an announced burn block relates to the burn blocks announced before it
+#[derive(Debug, PartialEq, Clone)]
+pub enum BurnBlockAnnouncement {
+    /// No burn block was announced at this height yet
+    New,
+    /// The same burn block, with the same payload, was already announced at this height
+    Duplicate,
+    /// The same burn block was already announced at this height, but with a different
+    /// payload (e.g. after a PoX anchor block reorg changed its reward recipients)
+    Updated,
+    /// A different burn block was announced at this height, which this one replaces
+    Reorg(BurnchainHeaderHash),
+}
+
+/// The burn block (and a digest of its `new_burn_block` payload) last announced at each
+/// recent height
+#[derive(Default)]
+pub struct BurnBlockHistory {
+    announced: BTreeMap<u64, (BurnchainHeaderHash, Sha512Trunc256Sum)>,
+}
+
+impl BurnBlockHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the announcement of `burn_block` at `burn_block_height`, whose payload bytes are
+    /// `payload`, and classify it against the previous announcement at that height
+    pub fn record(
+        &mut self,
+        burn_block: &BurnchainHeaderHash,
+        burn_block_height: u64,
+        payload: &[u8],
+    ) -> BurnBlockAnnouncement {
+        let digest = Sha512Trunc256Sum::from_data(payload);
+        let previous = self
+            .announced
+            .insert(burn_block_height, (burn_block.clone(), digest.clone()));
+        while self.announced.len() > MAX_TRACKED_BURN_BLOCKS {
+            self.announced.pop_first();
+        }
+        match previous {
+            None => BurnBlockAnnouncement::New,
+            Some((previous_block, _)) if &previous_block != burn_block => {
+                BurnBlockAnnouncement::Reorg(previous_block)
+            }
+            Some((_, previous_digest)) if previous_digest == digest => {
+                BurnBlockAnnouncement::Duplicate
+            }
+            Some(_) => BurnBlockAnnouncement::Updated,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_burn_block_history() {
+        let mut history = BurnBlockHistory::new();
+        let block_a = BurnchainHeaderHash([0x0a; 32]);
+        let block_b = BurnchainHeaderHash([0x0b; 32]);
+
+        assert_eq!(
+            history.record(&block_a, 100, b"a"),
+            BurnBlockAnnouncement::New
+        );
+        assert_eq!(
+            history.record(&block_a, 100, b"a"),
+            BurnBlockAnnouncement::Duplicate
+        );
+        assert_eq!(
+            history.record(&block_a, 100, b"a2"),
+            BurnBlockAnnouncement::Updated
+        );
+        assert_eq!(
+            history.record(&block_b, 100, b"b"),
+            BurnBlockAnnouncement::Reorg(block_a.clone())
+        );
+        assert_eq!(
+            history.record(&block_a, 100, b"a"),
+            BurnBlockAnnouncement::Reorg(block_b)
+        );
+
+        // old heights are forgotten
+        for height in 101..(101 + MAX_TRACKED_BURN_BLOCKS as u64) {
+            history.record(&block_a, height, b"a");
+        }
+        assert_eq!(
+            history.record(&block_a, 100, b"a"),
+            BurnBlockAnnouncement::New
+        );
+    }
+}
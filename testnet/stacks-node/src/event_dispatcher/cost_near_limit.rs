@@ -0,0 +1,113 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use clarity::vm::costs::ExecutionCost;
+use stacks::burnchains::Txid;
+use stacks::chainstate::stacks::events::StacksTransactionReceipt;
+
+fn serialize_txid<S>(txid: &Txid, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(&format!("0x{}", txid))
+}
+
+/// A transaction whose execution cost came close to the block limit in at least one dimension
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct NearLimitTransaction {
+    #[serde(serialize_with = "serialize_txid")]
+    pub txid: Txid,
+    pub execution_cost: ExecutionCost,
+    /// The cost dimensions (e.g. `runtime`, `read_count`) that exceeded the threshold, in the
+    /// order they appear in `execution_cost`
+    pub near_limit_dimensions: Vec<&'static str>,
+}
+
+/// The cost dimensions of `cost` that exceed `threshold` (a fraction) of `block_limit`
+fn near_limit_dimensions(
+    cost: &ExecutionCost,
+    block_limit: &ExecutionCost,
+    threshold: f64,
+) -> Vec<&'static str> {
+    [
+        ("write_length", cost.write_length, block_limit.write_length),
+        ("write_count", cost.write_count, block_limit.write_count),
+        ("read_length", cost.read_length, block_limit.read_length),
+        ("read_count", cost.read_count, block_limit.read_count),
+        ("runtime", cost.runtime, block_limit.runtime),
+    ]
+    .into_iter()
+    .filter(|(_, cost, limit)| (*cost as f64) > (*limit as f64) * threshold)
+    .map(|(dimension, _, _)| dimension)
+    .collect()
+}
+
+/// Find the transactions in a block whose execution cost exceeds `threshold` (a fraction) of
+/// `block_limit` in any dimension
+pub fn find_near_limit_transactions(
+    receipts: &[StacksTransactionReceipt],
+    block_limit: &ExecutionCost,
+    threshold: f64,
+) -> Vec<NearLimitTransaction> {
+    receipts
+        .iter()
+        .filter_map(|receipt| {
+            let dimensions = near_limit_dimensions(&receipt.execution_cost, block_limit, threshold);
+            if dimensions.is_empty() {
+                return None;
+            }
+            Some(NearLimitTransaction {
+                txid: receipt.transaction.txid(),
+                execution_cost: receipt.execution_cost.clone(),
+                near_limit_dimensions: dimensions,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cost(write_length: u64, read_count: u64, runtime: u64) -> ExecutionCost {
+        ExecutionCost {
+            write_length,
+            write_count: 0,
+            read_length: 0,
+            read_count,
+            runtime,
+        }
+    }
+
+    #[test]
+    fn test_near_limit_dimensions() {
+        let block_limit = cost(1000, 100, 10_000);
+
+        assert!(near_limit_dimensions(&ExecutionCost::zero(), &block_limit, 0.9).is_empty());
+        // exactly at the threshold is not over it
+        assert!(near_limit_dimensions(&cost(900, 90, 9_000), &block_limit, 0.9).is_empty());
+        assert_eq!(
+            near_limit_dimensions(&cost(901, 10, 9_001), &block_limit, 0.9),
+            vec!["write_length", "runtime"]
+        );
+        assert_eq!(
+            near_limit_dimensions(&cost(0, 51, 0), &block_limit, 0.5),
+            vec!["read_count"]
+        );
+        // a zero-cost dimension never exceeds a zero limit
+        assert!(near_limit_dimensions(&cost(0, 0, 0), &ExecutionCost::zero(), 0.9).is_empty());
+    }
+}
@@ -0,0 +1,142 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use stacks::net::api::geteventhealth::{EventDeliveryState, RPCEventObserverHealth};
+use stacks::util::{get_epoch_time_ms, get_epoch_time_secs};
+
+/// Running event delivery statistics for one observer. Shared by every copy of the observer,
+/// and only ever updated with atomics, so that reading them never waits on a delivery.
+#[derive(Debug, Default)]
+pub struct ObserverHealth {
+    /// Unix time of the last successful delivery, or 0 if there was none yet
+    last_success_time: AtomicU64,
+    consecutive_failures: AtomicU64,
+    /// Unix time, in milliseconds, of the first of the consecutive failures, or 0 if the last
+    /// delivery succeeded
+    failing_since_ms: AtomicU64,
+    fallback_deliveries: AtomicU64,
+}
+
+impl ObserverHealth {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Note that the observer's endpoint took a payload
+    pub fn record_success(&self) {
+        self.last_success_time
+            .store(get_epoch_time_secs(), Ordering::SeqCst);
+        self.consecutive_failures.store(0, Ordering::SeqCst);
+        self.failing_since_ms.store(0, Ordering::SeqCst);
+    }
+
+    /// Note that an attempt to deliver a payload to the observer's endpoint failed
+    pub fn record_failure(&self) {
+        self.consecutive_failures.fetch_add(1, Ordering::SeqCst);
+        let _ = self.failing_since_ms.compare_exchange(
+            0,
+            Self::now_ms(),
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        );
+    }
+
+    /// How long every delivery to the observer's endpoint has been failing, or zero if the last
+    /// one succeeded
+    pub fn failing_for(&self) -> Duration {
+        match self.failing_since_ms.load(Ordering::SeqCst) {
+            0 => Duration::ZERO,
+            failing_since_ms => {
+                Duration::from_millis(Self::now_ms().saturating_sub(failing_since_ms))
+            }
+        }
+    }
+
+    fn now_ms() -> u64 {
+        u64::try_from(get_epoch_time_ms()).unwrap_or(u64::MAX)
+    }
+
+    /// Note that a payload went to the observer's fallback transport instead
+    pub fn record_fallback_delivery(&self) {
+        self.fallback_deliveries.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Report these statistics for the observer at `endpoint` (which must already be redacted)
+    pub fn report(
+        &self,
+        endpoint: String,
+        queue_depth: u64,
+        fallback_active: bool,
+    ) -> RPCEventObserverHealth {
+        let last_success_time = self.last_success_time.load(Ordering::SeqCst);
+        let consecutive_failures = self.consecutive_failures.load(Ordering::SeqCst);
+        let delivery_state = if fallback_active {
+            EventDeliveryState::Fallback
+        } else if consecutive_failures > 0 {
+            EventDeliveryState::Failing
+        } else {
+            EventDeliveryState::Ok
+        };
+        RPCEventObserverHealth {
+            endpoint,
+            queue_depth,
+            last_success_time: (last_success_time > 0).then_some(last_success_time),
+            consecutive_failures,
+            delivery_state,
+            fallback_deliveries: self.fallback_deliveries.load(Ordering::SeqCst),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_observer_health() {
+        let health = ObserverHealth::new();
+        let report = health.report("localhost:3700".into(), 0, false);
+        assert_eq!(report.last_success_time, None);
+        assert_eq!(report.delivery_state, EventDeliveryState::Ok);
+        assert_eq!(health.failing_for(), Duration::ZERO);
+
+        health.record_failure();
+        std::thread::sleep(Duration::from_millis(20));
+        health.record_failure();
+        // counted from the first failure
+        assert!(health.failing_for() >= Duration::from_millis(20));
+        let report = health.report("localhost:3700".into(), 2, false);
+        assert_eq!(report.consecutive_failures, 2);
+        assert_eq!(report.queue_depth, 2);
+        assert_eq!(report.delivery_state, EventDeliveryState::Failing);
+
+        health.record_fallback_delivery();
+        let report = health.report("localhost:3700".into(), 1, true);
+        assert_eq!(report.fallback_deliveries, 1);
+        assert_eq!(report.delivery_state, EventDeliveryState::Fallback);
+
+        health.record_success();
+        let report = health.report("localhost:3700".into(), 0, false);
+        assert_eq!(report.consecutive_failures, 0);
+        assert_eq!(health.failing_for(), Duration::ZERO);
+        assert_eq!(report.fallback_deliveries, 1);
+        assert!(report.last_success_time.is_some());
+        assert_eq!(report.delivery_state, EventDeliveryState::Ok);
+    }
+}
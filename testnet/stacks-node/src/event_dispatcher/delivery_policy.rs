@@ -0,0 +1,307 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! An observer's delivery policy: how often its payloads are retried, and what becomes of a
+//! payload that it gives up on.
+//!
+//! By default, a payload is retried until the observer takes it, whatever the observer
+//! answers. A policy can instead give up on a payload after a number of attempts, as soon as
+//! the observer answers with a status that isn't worth retrying, or once the payload is too
+//! old to be of use. A payload that is given up on is POSTed once to the policy's dead-letter
+//! endpoint, if it has one, and is otherwise dropped.
+//!
+//! A policy with a failure grace period doesn't count a brief outage against the observer: its
+//! payloads are neither given up on after `max_attempts`, nor handed to its fallback transport,
+//! until it has been failing for the whole grace period.
+
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+use stacks::util::get_epoch_time_ms;
+
+use super::{observer_base_url, parse_observer_url};
+use crate::config::DeliveryPolicyConfig;
+
+/// Wait before the first retry of a payload, if the policy doesn't set `backoff_ms`
+pub const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Factor that the wait between retries grows by, if the policy doesn't set
+/// `backoff_multiplier`
+pub const DEFAULT_BACKOFF_MULTIPLIER: u32 = 2;
+
+/// Path that payloads are POSTed to at a dead-letter endpoint
+pub const DEAD_LETTER_PATH: &str = "/dead_letter";
+
+/// What became of an attempt to deliver a payload
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeliveryOutcome {
+    /// The observer took the payload
+    Delivered,
+    /// The payload wasn't delivered, but may be tried again later
+    Deferred,
+    /// The delivery policy gave up on the payload, for this reason
+    GaveUp(String),
+}
+
+/// How an observer's payloads are retried, parsed from its `delivery_policy`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeliveryPolicy {
+    /// Most attempts at delivering each payload
+    pub max_attempts: Option<u32>,
+    /// Wait before the first retry, which is multiplied by `backoff_multiplier` (plus some
+    /// jitter) for each retry after it
+    pub initial_backoff: Duration,
+    /// Factor that the wait grows by from each retry to the next
+    pub backoff_multiplier: u32,
+    /// Longest wait between retries. Without one, it is 3 times the observer's timeout.
+    pub max_backoff: Option<Duration>,
+    /// Failed response statuses that are worth retrying. Without them, all of them are.
+    /// Connection failures are always retried.
+    pub retryable_statuses: Option<Vec<u16>>,
+    /// URL that the payloads this policy gives up on are POSTed to
+    pub dead_letter_url: Option<String>,
+    /// Oldest a payload can be, since it was produced, and still be delivered
+    pub max_event_age: Option<Duration>,
+    /// How long the observer must have been failing before its payloads are given up on after
+    /// `max_attempts`, or handed to its fallback transport
+    pub failure_grace_period: Option<Duration>,
+}
+
+impl Default for DeliveryPolicy {
+    /// Retry every payload until it is delivered
+    fn default() -> Self {
+        Self {
+            max_attempts: None,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            backoff_multiplier: DEFAULT_BACKOFF_MULTIPLIER,
+            max_backoff: None,
+            retryable_statuses: None,
+            dead_letter_url: None,
+            max_event_age: None,
+            failure_grace_period: None,
+        }
+    }
+}
+
+impl DeliveryPolicy {
+    /// Parse an observer's `delivery_policy`
+    pub fn from_config(conf: &DeliveryPolicyConfig) -> Result<Self, String> {
+        if conf.max_attempts == Some(0) {
+            return Err("Invalid delivery_policy: max_attempts must be at least 1".into());
+        }
+        if conf.backoff_multiplier == Some(0) {
+            return Err("Invalid delivery_policy: backoff_multiplier must be at least 1".into());
+        }
+        if let Some(status) = conf
+            .retryable_statuses
+            .iter()
+            .flatten()
+            .find(|status| !(100..=599).contains(*status))
+        {
+            return Err(format!(
+                "Invalid delivery_policy: retryable status {} is not an HTTP status",
+                status
+            ));
+        }
+        let dead_letter_url = conf
+            .dead_letter_endpoint
+            .as_ref()
+            .map(|endpoint| {
+                let url = format!("{}{}", observer_base_url(endpoint), DEAD_LETTER_PATH);
+                parse_observer_url(&url).map(|_| url).map_err(|e| {
+                    format!(
+                        "Invalid delivery_policy: dead_letter_endpoint '{}' is not a valid endpoint: {}",
+                        endpoint, e
+                    )
+                })
+            })
+            .transpose()?;
+        Ok(Self {
+            max_attempts: conf.max_attempts,
+            initial_backoff: conf
+                .backoff_ms
+                .map(Duration::from_millis)
+                .unwrap_or(DEFAULT_INITIAL_BACKOFF),
+            backoff_multiplier: conf
+                .backoff_multiplier
+                .unwrap_or(DEFAULT_BACKOFF_MULTIPLIER),
+            max_backoff: conf.max_backoff_ms.map(Duration::from_millis),
+            retryable_statuses: conf.retryable_statuses.clone(),
+            dead_letter_url,
+            max_event_age: conf.max_event_age_ms.map(Duration::from_millis),
+            failure_grace_period: conf.failure_grace_period_ms.map(Duration::from_millis),
+        })
+    }
+
+    /// Whether a failed response with `status_code` is worth retrying
+    pub fn is_retryable(&self, status_code: u16) -> bool {
+        self.retryable_statuses
+            .as_ref()
+            .map_or(true, |statuses| statuses.contains(&status_code))
+    }
+
+    /// Whether the policy gives up on a payload once `attempts` deliveries of it have failed
+    pub fn is_exhausted(&self, attempts: u32) -> bool {
+        self.max_attempts
+            .is_some_and(|max_attempts| attempts >= max_attempts)
+    }
+
+    /// Whether an observer that has been failing for `failing_for` is still within the policy's
+    /// grace period, in which its failed payloads are retried however many attempts they took
+    pub fn is_in_grace_period(&self, failing_for: Duration) -> bool {
+        self.failure_grace_period
+            .is_some_and(|grace_period| failing_for < grace_period)
+    }
+
+    /// Longest wait between retries to an observer with `timeout`
+    pub fn max_backoff(&self, timeout: Duration) -> Duration {
+        self.max_backoff.unwrap_or(timeout.saturating_mul(3))
+    }
+
+    /// Wait before the retry after one that waited `backoff`, plus `jitter`, to an observer
+    /// with `timeout`
+    pub fn next_backoff(&self, backoff: Duration, jitter: Duration, timeout: Duration) -> Duration {
+        std::cmp::min(
+            backoff.saturating_mul(self.backoff_multiplier) + jitter,
+            self.max_backoff(timeout),
+        )
+    }
+
+    /// When a payload produced at `created_at_ms` (in milliseconds since the epoch) becomes too
+    /// old to deliver, if the policy has a `max_event_age`. A payload of unknown age is
+    /// taken to be new.
+    pub fn expires_at(&self, created_at_ms: Option<u64>) -> Option<Instant> {
+        let max_event_age = self.max_event_age?;
+        let now_ms = u64::try_from(get_epoch_time_ms()).unwrap_or(u64::MAX);
+        let age = Duration::from_millis(now_ms.saturating_sub(created_at_ms.unwrap_or(now_ms)));
+        Some(Instant::now() + max_event_age.saturating_sub(age))
+    }
+}
+
+/// The body POSTed to a dead-letter endpoint for a payload meant for `url`, which was given up
+/// on for `reason`
+pub fn make_dead_letter_payload(
+    url: &str,
+    payload: &serde_json::Value,
+    reason: &str,
+) -> serde_json::Value {
+    json!({
+        "url": url,
+        "reason": reason,
+        "payload": payload,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_delivery_policy_from_config() {
+        // the default policy retries everything, forever
+        let policy = DeliveryPolicy::from_config(&DeliveryPolicyConfig::default()).unwrap();
+        assert_eq!(policy, DeliveryPolicy::default());
+        assert!(!policy.is_exhausted(u32::MAX));
+        assert!(policy.is_retryable(400) && policy.is_retryable(503));
+        assert_eq!(
+            policy.max_backoff(Duration::from_secs(2)),
+            Duration::from_secs(6)
+        );
+        assert_eq!(
+            policy.next_backoff(
+                Duration::from_millis(100),
+                Duration::ZERO,
+                Duration::from_secs(2)
+            ),
+            Duration::from_millis(200)
+        );
+        assert!(policy.expires_at(Some(0)).is_none());
+        assert!(!policy.is_in_grace_period(Duration::ZERO));
+
+        let policy = DeliveryPolicy::from_config(&DeliveryPolicyConfig {
+            max_attempts: Some(3),
+            backoff_ms: Some(10),
+            backoff_multiplier: Some(3),
+            max_backoff_ms: Some(50),
+            retryable_statuses: Some(vec![429, 503]),
+            dead_letter_endpoint: Some("localhost:3701".into()),
+            max_event_age_ms: Some(60_000),
+            failure_grace_period_ms: Some(5_000),
+        })
+        .unwrap();
+        assert!(!policy.is_exhausted(2));
+        assert!(policy.is_exhausted(3));
+        assert_eq!(policy.initial_backoff, Duration::from_millis(10));
+        assert_eq!(
+            policy.max_backoff(Duration::from_secs(2)),
+            Duration::from_millis(50)
+        );
+        // the wait grows geometrically, up to its cap
+        let timeout = Duration::from_secs(2);
+        let backoff = policy.next_backoff(policy.initial_backoff, Duration::ZERO, timeout);
+        assert_eq!(backoff, Duration::from_millis(30));
+        let backoff = policy.next_backoff(backoff, Duration::ZERO, timeout);
+        assert_eq!(backoff, Duration::from_millis(50));
+        assert!(policy.is_retryable(503));
+        assert!(!policy.is_retryable(400));
+        assert_eq!(
+            policy.dead_letter_url.as_deref(),
+            Some("http://localhost:3701/dead_letter")
+        );
+
+        // a dead-letter endpoint can be a full URL, like an observer's
+        let url_policy = DeliveryPolicy::from_config(&DeliveryPolicyConfig {
+            dead_letter_endpoint: Some("https://dlq.example.com:3701/events".into()),
+            ..DeliveryPolicyConfig::default()
+        })
+        .unwrap();
+        assert_eq!(
+            url_policy.dead_letter_url.as_deref(),
+            Some("https://dlq.example.com:3701/events/dead_letter")
+        );
+
+        // a payload's expiry counts from when it was produced
+        let now_ms = u64::try_from(get_epoch_time_ms()).unwrap();
+        assert!(policy.expires_at(Some(now_ms - 120_000)).unwrap() <= Instant::now());
+        assert!(policy.expires_at(None).unwrap() > Instant::now() + Duration::from_secs(50));
+
+        // failures only count once the observer has been failing for the grace period
+        assert!(policy.is_in_grace_period(Duration::ZERO));
+        assert!(policy.is_in_grace_period(Duration::from_millis(4_999)));
+        assert!(!policy.is_in_grace_period(Duration::from_secs(5)));
+
+        for conf in [
+            DeliveryPolicyConfig {
+                max_attempts: Some(0),
+                ..DeliveryPolicyConfig::default()
+            },
+            DeliveryPolicyConfig {
+                backoff_multiplier: Some(0),
+                ..DeliveryPolicyConfig::default()
+            },
+            DeliveryPolicyConfig {
+                retryable_statuses: Some(vec![503, 1000]),
+                ..DeliveryPolicyConfig::default()
+            },
+            DeliveryPolicyConfig {
+                dead_letter_endpoint: Some("not a host:port".into()),
+                ..DeliveryPolicyConfig::default()
+            },
+        ] {
+            assert!(DeliveryPolicy::from_config(&conf).is_err());
+        }
+    }
+}
@@ -0,0 +1,325 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Non-blocking delivery to an observer.
+//!
+//! By default, sending a payload to an observer blocks until the observer takes it (or the
+//! delivery policy gives up on it), so a slow observer holds up block processing. An observer
+//! with a `delivery_queue` instead gets a bounded queue and a thread of its own, which delivers
+//! the queued payloads one at a time, in the order they were queued. Sending a payload to it
+//! only queues the payload. What happens to a payload sent while the queue is full is up to the
+//! queue's `QueueFullPolicy`.
+//!
+//! Queued payloads are only in memory: those that haven't been handed to the observer's
+//! delivery thread when the node stops are lost. An observer that is dropped while the node
+//! runs, such as one replaced by `EventDispatcher::replace_observers()`, doesn't lose them: its
+//! delivery thread keeps going until its queue is empty.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::config::DeliveryQueueConfig;
+
+/// Most payloads a delivery queue holds, if its config doesn't set `capacity`
+pub const DEFAULT_QUEUE_CAPACITY: usize = 1024;
+
+/// How long a delivery thread waits for a payload before checking whether its queue is still in use
+const QUEUE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// What happens to a payload sent to an observer whose delivery queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueFullPolicy {
+    /// The sender waits until there's room, as it would without a queue
+    Block,
+    /// The oldest queued payload is dropped to make room
+    DropOldest,
+    /// The payload is dropped
+    DropNewest,
+}
+
+impl FromStr for QueueFullPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "block" => Ok(Self::Block),
+            "drop_oldest" => Ok(Self::DropOldest),
+            "drop_newest" => Ok(Self::DropNewest),
+            _ => Err(format!(
+                "Invalid delivery queue when_full '{}': expected block, drop_oldest or drop_newest",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for QueueFullPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Block => write!(f, "block"),
+            Self::DropOldest => write!(f, "drop_oldest"),
+            Self::DropNewest => write!(f, "drop_newest"),
+        }
+    }
+}
+
+/// A payload waiting in an observer's delivery queue
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedPayload {
+    pub payload: serde_json::Value,
+    pub path: String,
+    /// If set, the payload's failed deliveries are not retried past this time
+    pub retry_deadline: Option<Instant>,
+}
+
+/// The payloads waiting to be delivered to an observer by its delivery thread
+#[derive(Debug)]
+pub struct DeliveryQueue {
+    capacity: usize,
+    when_full: QueueFullPolicy,
+    payloads: Mutex<VecDeque<QueuedPayload>>,
+    /// Signalled when a payload is queued
+    queued: Condvar,
+    /// Signalled when a payload is taken off the queue
+    taken: Condvar,
+    /// How many payloads have been dropped because the queue was full
+    dropped: AtomicU64,
+}
+
+impl DeliveryQueue {
+    /// Hold up to `capacity` payloads. At least 1 is always held.
+    pub fn new(capacity: usize, when_full: QueueFullPolicy) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            when_full,
+            payloads: Mutex::new(VecDeque::new()),
+            queued: Condvar::new(),
+            taken: Condvar::new(),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Parse an observer's `delivery_queue` table
+    pub fn from_config(config: &DeliveryQueueConfig) -> Result<Self, String> {
+        let capacity = config.capacity.unwrap_or(DEFAULT_QUEUE_CAPACITY);
+        if capacity == 0 {
+            return Err("Delivery queue capacity must be at least 1".into());
+        }
+        let when_full = config
+            .when_full
+            .as_deref()
+            .map(QueueFullPolicy::from_str)
+            .transpose()?
+            .unwrap_or(QueueFullPolicy::Block);
+        Ok(Self::new(capacity, when_full))
+    }
+
+    /// Queue a payload for delivery. If the queue is full, this waits for room, drops the
+    /// oldest payload, or drops this one, as the queue's policy says. Returns the payload that
+    /// was dropped, if any.
+    pub fn push(&self, queued: QueuedPayload) -> Option<QueuedPayload> {
+        let mut payloads = self
+            .payloads
+            .lock()
+            .expect("FATAL: failed to lock delivery queue");
+        let mut dropped = None;
+        if payloads.len() >= self.capacity {
+            match self.when_full {
+                QueueFullPolicy::Block => {
+                    payloads = self
+                        .taken
+                        .wait_while(payloads, |payloads| payloads.len() >= self.capacity)
+                        .expect("FATAL: failed to lock delivery queue");
+                }
+                QueueFullPolicy::DropOldest => {
+                    self.dropped.fetch_add(1, Ordering::SeqCst);
+                    dropped = payloads.pop_front();
+                }
+                QueueFullPolicy::DropNewest => {
+                    self.dropped.fetch_add(1, Ordering::SeqCst);
+                    return Some(queued);
+                }
+            }
+        }
+        payloads.push_back(queued);
+        self.queued.notify_one();
+        dropped
+    }
+
+    /// Take the oldest payload off the queue, waiting up to `timeout` for one to be queued
+    pub fn pop(&self, timeout: Duration) -> Option<QueuedPayload> {
+        let payloads = self
+            .payloads
+            .lock()
+            .expect("FATAL: failed to lock delivery queue");
+        let (mut payloads, _) = self
+            .queued
+            .wait_timeout_while(payloads, timeout, |payloads| payloads.is_empty())
+            .expect("FATAL: failed to lock delivery queue");
+        let queued = payloads.pop_front()?;
+        self.taken.notify_all();
+        Some(queued)
+    }
+
+    /// How many payloads have been dropped because the queue was full
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::SeqCst)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn when_full(&self) -> QueueFullPolicy {
+        self.when_full
+    }
+
+    /// Body of an observer's delivery thread: `deliver` each queued payload, in order, until
+    /// the queue is empty and every observer that owns it has been dropped
+    pub fn run(queue: Arc<DeliveryQueue>, mut deliver: impl FnMut(QueuedPayload)) {
+        loop {
+            if let Some(queued) = queue.pop(QUEUE_POLL_INTERVAL) {
+                deliver(queued);
+                continue;
+            }
+            // the queue was empty, and if no observer owns it, nothing can be queued anymore
+            if Arc::strong_count(&queue) == 1 {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::mpsc::channel;
+    use std::thread;
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn make_payload(n: u64) -> QueuedPayload {
+        QueuedPayload {
+            payload: json!({ "n": n }),
+            path: "test".into(),
+            retry_deadline: None,
+        }
+    }
+
+    fn drain(queue: &DeliveryQueue) -> Vec<QueuedPayload> {
+        let mut payloads = vec![];
+        while let Some(queued) = queue.pop(Duration::ZERO) {
+            payloads.push(queued);
+        }
+        payloads
+    }
+
+    #[test]
+    fn test_delivery_queue_when_full() {
+        let queue = DeliveryQueue::new(2, QueueFullPolicy::DropOldest);
+        for n in 0..4 {
+            queue.push(make_payload(n));
+        }
+        assert_eq!(queue.dropped(), 2);
+        assert_eq!(drain(&queue), vec![make_payload(2), make_payload(3)]);
+
+        let queue = DeliveryQueue::new(2, QueueFullPolicy::DropNewest);
+        for n in 0..4 {
+            queue.push(make_payload(n));
+        }
+        assert_eq!(queue.dropped(), 2);
+        assert_eq!(drain(&queue), vec![make_payload(0), make_payload(1)]);
+
+        // a blocked sender resumes once the delivery thread takes a payload
+        let queue = Arc::new(DeliveryQueue::new(2, QueueFullPolicy::Block));
+        queue.push(make_payload(0));
+        queue.push(make_payload(1));
+        let (tx, rx) = channel();
+        let sender = {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                queue.push(make_payload(2));
+                tx.send(()).unwrap();
+            })
+        };
+        assert!(rx.recv_timeout(Duration::from_millis(200)).is_err());
+        assert_eq!(queue.pop(Duration::ZERO), Some(make_payload(0)));
+        rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        sender.join().unwrap();
+        assert_eq!(queue.dropped(), 0);
+        assert_eq!(drain(&queue), vec![make_payload(1), make_payload(2)]);
+    }
+
+    #[test]
+    fn test_delivery_queue_from_config() {
+        let queue = DeliveryQueue::from_config(&DeliveryQueueConfig::default()).unwrap();
+        assert_eq!(queue.capacity(), DEFAULT_QUEUE_CAPACITY);
+        assert_eq!(queue.when_full(), QueueFullPolicy::Block);
+
+        let queue = DeliveryQueue::from_config(&DeliveryQueueConfig {
+            capacity: Some(10),
+            when_full: Some("drop_oldest".into()),
+        })
+        .unwrap();
+        assert_eq!(queue.capacity(), 10);
+        assert_eq!(queue.when_full(), QueueFullPolicy::DropOldest);
+
+        assert!(DeliveryQueue::from_config(&DeliveryQueueConfig {
+            capacity: Some(0),
+            when_full: None,
+        })
+        .is_err());
+        assert!(DeliveryQueue::from_config(&DeliveryQueueConfig {
+            capacity: None,
+            when_full: Some("drop_random".into()),
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_delivery_queue_run() {
+        let queue = Arc::new(DeliveryQueue::new(10, QueueFullPolicy::Block));
+        let (tx, rx) = channel();
+        let runner = {
+            let queue = queue.clone();
+            thread::spawn(move || DeliveryQueue::run(queue, |queued| tx.send(queued).unwrap()))
+        };
+        for n in 0..5 {
+            queue.push(make_payload(n));
+        }
+        for n in 0..5 {
+            assert_eq!(
+                rx.recv_timeout(Duration::from_secs(5)).unwrap(),
+                make_payload(n)
+            );
+        }
+
+        // once the queue is dropped, the thread delivers what's left in it, then exits
+        for n in 5..8 {
+            queue.push(make_payload(n));
+        }
+        drop(queue);
+        runner.join().unwrap();
+        for n in 5..8 {
+            assert_eq!(rx.recv().unwrap(), make_payload(n));
+        }
+    }
+}
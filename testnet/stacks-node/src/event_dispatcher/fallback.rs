@@ -0,0 +1,220 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use serde_json::json;
+
+use super::redis_stream::RedisStream;
+
+/// Number of attempts an observer's primary endpoint gets for each payload before the payload
+/// is handed to its fallback transport, if `fallback_after_attempts` is not set
+pub const DEFAULT_FALLBACK_AFTER_ATTEMPTS: u32 = 5;
+
+/// Endpoint of an event observer that has its events written to the node's stdout instead of
+/// POSTed to it. Meant for development, so it is not allowed on mainnet.
+pub const STDOUT_ENDPOINT: &str = "stdout://";
+
+/// Where an observer's payloads go once its HTTP endpoint has failed to take them
+#[derive(Debug, Clone, PartialEq)]
+pub enum FallbackTransport {
+    /// Append each payload to a local file, as one JSON object per line
+    File(PathBuf),
+    /// Write each payload to the node's stdout, as one JSON object per line. Only used by
+    /// `stdout://` observers, in place of HTTP.
+    Stdout,
+    /// Add each payload to the Redis stream of its event. Only used by `redis://` observers, in
+    /// place of HTTP.
+    Redis(Arc<RedisStream>),
+}
+
+impl FallbackTransport {
+    /// Parse a fallback transport from an observer's config, e.g. `file:/var/stacks/events.jsonl`
+    pub fn parse(transport: &str) -> Result<Self, String> {
+        match transport.split_once(':') {
+            Some(("file", path)) if !path.is_empty() => Ok(Self::File(PathBuf::from(path))),
+            _ => Err(format!(
+                "Unsupported fallback transport '{}': expected 'file:<path>'",
+                transport
+            )),
+        }
+    }
+
+    /// Deliver a payload that was meant for `url`
+    pub fn send(&self, url: &str, payload: &serde_json::Value) -> Result<(), String> {
+        self.write_line(&json!({ "url": url, "payload": payload }))
+    }
+
+    /// Deliver a payload for the event at `path` (e.g. `/new_block`), for an observer that
+    /// has no URL
+    pub fn send_event(&self, path: &str, payload: &serde_json::Value) -> Result<(), String> {
+        if let Self::Redis(stream) = self {
+            return stream.add(path, payload);
+        }
+        self.write_line(&json!({ "path": path, "payload": payload }))
+    }
+
+    /// Write `line` as one line of JSON, in a single write
+    fn write_line(&self, line: &serde_json::Value) -> Result<(), String> {
+        let mut line = serde_json::to_vec(line)
+            .map_err(|e| format!("Failed to serialize payload: {:?}", &e))?;
+        line.push(b'\n');
+        match self {
+            Self::File(path) => {
+                let mut file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .map_err(|e| format!("Failed to open {}: {:?}", path.display(), &e))?;
+                file.write_all(&line)
+                    .map_err(|e| format!("Failed to write to {}: {:?}", path.display(), &e))
+            }
+            Self::Stdout => {
+                let mut stdout = std::io::stdout().lock();
+                stdout
+                    .write_all(&line)
+                    .and_then(|_| stdout.flush())
+                    .map_err(|e| format!("Failed to write to stdout: {:?}", &e))
+            }
+            Self::Redis(_) => Err("Redis streams take fields, not lines".into()),
+        }
+    }
+}
+
+impl std::fmt::Display for FallbackTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "file:{}", path.display()),
+            Self::Stdout => write!(f, "{}", STDOUT_ENDPOINT),
+            Self::Redis(stream) => write!(f, "{}", stream.endpoint()),
+        }
+    }
+}
+
+/// An observer's fallback transport, and whether the observer is currently using it
+#[derive(Debug, Clone)]
+pub struct ObserverFallback {
+    pub transport: FallbackTransport,
+    /// Number of attempts the primary endpoint gets for each payload
+    pub after_attempts: u32,
+    /// Whether the last payload went to the fallback transport. Shared by every copy of the
+    /// observer, so that each transition is logged once.
+    active: Arc<AtomicBool>,
+}
+
+impl ObserverFallback {
+    pub fn new(transport: FallbackTransport, after_attempts: u32) -> Self {
+        Self {
+            transport,
+            after_attempts: after_attempts.max(1),
+            active: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Whether payloads are currently going to the fallback transport
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    /// Hand over a payload that the primary endpoint failed to take.
+    /// Returns true if the fallback transport took it.
+    pub fn send(&self, url: &str, payload: &serde_json::Value) -> bool {
+        if let Err(e) = self.transport.send(url, payload) {
+            error!(
+                "Event observer: fallback transport failed, keeping payload";
+                "url" => url, "fallback" => %self.transport, "error" => e
+            );
+            return false;
+        }
+        if !self.active.swap(true, Ordering::SeqCst) {
+            warn!(
+                "Event observer: primary endpoint unreachable, switching to fallback transport";
+                "url" => url, "fallback" => %self.transport
+            );
+        }
+        true
+    }
+
+    /// Note that the primary endpoint took a payload
+    pub fn primary_delivered(&self, url: &str) {
+        if self.active.swap(false, Ordering::SeqCst) {
+            info!(
+                "Event observer: primary endpoint reachable again, switching back from fallback transport";
+                "url" => url, "fallback" => %self.transport
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_fallback_transport() {
+        assert_eq!(
+            FallbackTransport::parse("file:/tmp/events.jsonl").unwrap(),
+            FallbackTransport::File(PathBuf::from("/tmp/events.jsonl"))
+        );
+        assert_eq!(
+            FallbackTransport::parse("file:/tmp/events.jsonl")
+                .unwrap()
+                .to_string(),
+            "file:/tmp/events.jsonl"
+        );
+        assert!(FallbackTransport::parse("file:").is_err());
+        assert!(FallbackTransport::parse("/tmp/events.jsonl").is_err());
+        assert!(FallbackTransport::parse("kafka:events").is_err());
+        // stdout is only for observers that have no HTTP endpoint
+        assert!(FallbackTransport::parse(STDOUT_ENDPOINT).is_err());
+    }
+
+    #[test]
+    fn test_send_event_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.jsonl");
+        let transport = FallbackTransport::File(path.clone());
+        transport
+            .send_event("/new_block", &json!({ "block_height": 1 }))
+            .unwrap();
+        transport
+            .send(
+                "http://localhost:3700/new_burn_block",
+                &json!({ "burn_block_height": 2 }),
+            )
+            .unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<serde_json::Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                json!({ "path": "/new_block", "payload": { "block_height": 1 } }),
+                json!({
+                    "url": "http://localhost:3700/new_burn_block",
+                    "payload": { "burn_block_height": 2 }
+                }),
+            ]
+        );
+    }
+}
@@ -0,0 +1,123 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Flattening of execution costs in event payloads.
+//!
+//! An `ExecutionCost` is sent as a nested object, e.g.
+//! `"anchored_cost": {"runtime": 1, "read_count": 2, ...}`. An observer that sets
+//! `flatten_execution_costs` receives each one as prefixed fields of the object that holds it
+//! instead, e.g. `"anchored_cost_runtime": 1, "anchored_cost_read_count": 2, ...`, which is
+//! easier to load into stores that don't handle nested JSON.
+
+use serde_json::{Map, Value};
+
+/// The fields of a serialized `ExecutionCost`
+pub const EXECUTION_COST_FIELDS: &[&str] = &[
+    "read_count",
+    "read_length",
+    "runtime",
+    "write_count",
+    "write_length",
+];
+
+/// Is `value` a serialized `ExecutionCost`, i.e. an object with exactly its fields?
+fn is_execution_cost(value: &Value) -> bool {
+    let Value::Object(map) = value else {
+        return false;
+    };
+    map.len() == EXECUTION_COST_FIELDS.len()
+        && EXECUTION_COST_FIELDS
+            .iter()
+            .all(|field| map.get(*field).is_some_and(Value::is_number))
+}
+
+/// Replace every execution cost object in `payload`, at any level of nesting, with one field
+/// per cost dimension, named `<key>_<dimension>`
+pub fn flatten_execution_costs(payload: &Value) -> Value {
+    match payload {
+        Value::Object(map) => {
+            let mut flattened = Map::with_capacity(map.len());
+            for (key, value) in map.iter() {
+                if is_execution_cost(value) {
+                    for field in EXECUTION_COST_FIELDS {
+                        flattened.insert(format!("{key}_{field}"), value[*field].clone());
+                    }
+                } else {
+                    flattened.insert(key.clone(), flatten_execution_costs(value));
+                }
+            }
+            Value::Object(flattened)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(flatten_execution_costs).collect()),
+        _ => payload.clone(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use clarity::vm::costs::ExecutionCost;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_flatten_execution_costs() {
+        let cost = ExecutionCost {
+            write_length: 1,
+            write_count: 2,
+            read_length: 3,
+            read_count: 4,
+            runtime: 5,
+        };
+        let payload = json!({
+            "block_height": 100,
+            "anchored_cost": cost,
+            "confirmed_microblocks_cost": ExecutionCost::zero(),
+            "transactions": [
+                { "txid": "0x01", "execution_cost": cost },
+            ],
+            // not a whole execution cost, so left alone
+            "partial_cost": { "runtime": 5 },
+        });
+        assert_eq!(
+            flatten_execution_costs(&payload),
+            json!({
+                "block_height": 100,
+                "anchored_cost_read_count": 4,
+                "anchored_cost_read_length": 3,
+                "anchored_cost_runtime": 5,
+                "anchored_cost_write_count": 2,
+                "anchored_cost_write_length": 1,
+                "confirmed_microblocks_cost_read_count": 0,
+                "confirmed_microblocks_cost_read_length": 0,
+                "confirmed_microblocks_cost_runtime": 0,
+                "confirmed_microblocks_cost_write_count": 0,
+                "confirmed_microblocks_cost_write_length": 0,
+                "transactions": [
+                    {
+                        "txid": "0x01",
+                        "execution_cost_read_count": 4,
+                        "execution_cost_read_length": 3,
+                        "execution_cost_runtime": 5,
+                        "execution_cost_write_count": 2,
+                        "execution_cost_write_length": 1,
+                    },
+                ],
+                "partial_cost": { "runtime": 5 },
+            })
+        );
+    }
+}
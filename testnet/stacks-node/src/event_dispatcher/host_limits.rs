@@ -0,0 +1,174 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Limits on the event deliveries in flight to each observer host.
+//!
+//! Several observers often point at one backend (e.g. one per topic), and each observer can
+//! deliver over several connections at once, so a burst of events can open a storm of
+//! connections to that backend. Every observer of a host shares that host's limit: a delivery
+//! attempt beyond it waits for another attempt to the host to finish, rather than dialing right
+//! away. Retry backoffs don't hold a slot.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex};
+
+/// Default number of delivery attempts that may be in flight to one observer host
+pub const DEFAULT_MAX_IN_FLIGHT_PER_HOST: usize = 64;
+
+/// The delivery attempts in flight to one observer host
+#[derive(Debug)]
+pub struct HostDeliveryLimit {
+    max_in_flight: usize,
+    in_flight: Mutex<usize>,
+    released: Condvar,
+}
+
+/// A slot for one delivery attempt to a host, given back when dropped
+pub struct HostDeliveryPermit<'a> {
+    limit: &'a HostDeliveryLimit,
+}
+
+impl HostDeliveryLimit {
+    /// Allow up to `max_in_flight` delivery attempts at once. At least 1 is always allowed.
+    pub fn new(max_in_flight: usize) -> Self {
+        Self {
+            max_in_flight: max_in_flight.max(1),
+            in_flight: Mutex::new(0),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Wait for a free slot, and take it
+    pub fn acquire(&self) -> HostDeliveryPermit<'_> {
+        let mut in_flight = self
+            .in_flight
+            .lock()
+            .expect("FATAL: failed to lock host delivery limit");
+        while *in_flight >= self.max_in_flight {
+            in_flight = self
+                .released
+                .wait(in_flight)
+                .expect("FATAL: failed to lock host delivery limit");
+        }
+        *in_flight += 1;
+        HostDeliveryPermit { limit: self }
+    }
+
+    #[cfg(test)]
+    pub fn max_in_flight(&self) -> usize {
+        self.max_in_flight
+    }
+}
+
+impl Drop for HostDeliveryPermit<'_> {
+    fn drop(&mut self) {
+        let mut in_flight = self
+            .limit
+            .in_flight
+            .lock()
+            .expect("FATAL: failed to lock host delivery limit");
+        *in_flight = in_flight.saturating_sub(1);
+        self.limit.released.notify_one();
+    }
+}
+
+/// The delivery limits of every observer host, made on first use
+#[derive(Debug)]
+pub struct HostDeliveryLimits {
+    /// Limit of the hosts without one of their own
+    default_max_in_flight: usize,
+    /// Limits of specific hosts, keyed by host name or address
+    host_max_in_flight: HashMap<String, usize>,
+    limits: Mutex<HashMap<String, Arc<HostDeliveryLimit>>>,
+}
+
+impl HostDeliveryLimits {
+    pub fn new(default_max_in_flight: usize, host_max_in_flight: HashMap<String, usize>) -> Self {
+        Self {
+            default_max_in_flight,
+            host_max_in_flight: host_max_in_flight
+                .into_iter()
+                .map(|(host, max_in_flight)| (host.to_ascii_lowercase(), max_in_flight))
+                .collect(),
+            limits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The limit shared by every observer of `host`
+    pub fn limit_for(&self, host: &str) -> Arc<HostDeliveryLimit> {
+        let host = host.to_ascii_lowercase();
+        let mut limits = self
+            .limits
+            .lock()
+            .expect("FATAL: failed to lock host delivery limits");
+        let max_in_flight = self
+            .host_max_in_flight
+            .get(&host)
+            .copied()
+            .unwrap_or(self.default_max_in_flight);
+        limits
+            .entry(host)
+            .or_insert_with(|| Arc::new(HostDeliveryLimit::new(max_in_flight)))
+            .clone()
+    }
+}
+
+impl Default for HostDeliveryLimits {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_IN_FLIGHT_PER_HOST, HashMap::new())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_host_delivery_limit() {
+        let limits = HostDeliveryLimits::new(8, HashMap::from([("Observers.local".into(), 3)]));
+
+        // hosts share one limit however their observers spell them
+        let limit = limits.limit_for("observers.local");
+        assert!(Arc::ptr_eq(&limit, &limits.limit_for("Observers.Local")));
+        assert_eq!(limit.max_in_flight(), 3);
+        assert_eq!(limits.limit_for("127.0.0.1").max_in_flight(), 8);
+        assert!(!Arc::ptr_eq(&limit, &limits.limit_for("127.0.0.1")));
+        assert_eq!(HostDeliveryLimit::new(0).max_in_flight(), 1);
+
+        // many concurrent senders never have more than 3 attempts in flight
+        let in_flight = AtomicUsize::new(0);
+        let most_in_flight = AtomicUsize::new(0);
+        thread::scope(|s| {
+            for _ in 0..12 {
+                s.spawn(|| {
+                    for _ in 0..5 {
+                        let _permit = limit.acquire();
+                        let now_in_flight = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                        most_in_flight.fetch_max(now_in_flight, Ordering::SeqCst);
+                        thread::sleep(Duration::from_millis(2));
+                        in_flight.fetch_sub(1, Ordering::SeqCst);
+                    }
+                });
+            }
+        });
+        assert!(most_in_flight.load(Ordering::SeqCst) <= 3);
+        assert_eq!(*limit.in_flight.lock().unwrap(), 0);
+    }
+}
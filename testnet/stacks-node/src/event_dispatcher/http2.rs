@@ -0,0 +1,263 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! HTTP/2 delivery of event payloads.
+//!
+//! Observers with `http2` set are sent their payloads over cleartext HTTP/2 (with prior
+//! knowledge, i.e. without an HTTP/1.1 upgrade), instead of one HTTP/1.1 connection per
+//! request. All of an observer's concurrent deliveries share one connection. This needs the
+//! `http2-observers` feature; without it, registering such an observer fails.
+
+use std::time::Duration;
+
+use stacks::net::http::HttpResponsePayload;
+use url::Url;
+
+/// An observer's HTTP/2 client. Deliveries from any number of threads are multiplexed over one
+/// connection per observer host, which is opened on first use and reopened if it drops.
+#[cfg(feature = "http2-observers")]
+#[derive(Debug)]
+pub struct Http2Client {
+    /// Drives the connection in the background, between the blocking `post()` calls
+    runtime: tokio::runtime::Runtime,
+    client: hyper::Client<hyper::client::HttpConnector, hyper::Body>,
+}
+
+#[cfg(feature = "http2-observers")]
+impl Http2Client {
+    pub fn new() -> Result<Self, String> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("event-observer-http2")
+            .enable_all()
+            .build()
+            .map_err(|e| format!("Failed to start HTTP/2 runtime: {:?}", &e))?;
+        let client = hyper::Client::builder().http2_only(true).build_http();
+        Ok(Self { runtime, client })
+    }
+
+    /// POST `payload` to `url`, with `extra_headers`, and return the response's status code and
+    /// body. Like `send_http_request()`, the whole exchange, including connecting, must finish
+    /// within `timeout`.
+    pub fn post(
+        &self,
+        url: &Url,
+        payload: &serde_json::Value,
+        idempotency_key: &str,
+        extra_headers: &[(&str, String)],
+        timeout: Duration,
+    ) -> Result<(u16, HttpResponsePayload), String> {
+        let body = serde_json::to_vec(payload)
+            .map_err(|e| format!("Failed to serialize payload: {:?}", &e))?;
+        let mut request = hyper::Request::post(url.as_str())
+            .header(hyper::header::CONTENT_TYPE, "application/json")
+            .header("Idempotency-Key", idempotency_key);
+        for (name, value) in extra_headers.iter() {
+            request = request.header(*name, value.as_str());
+        }
+        let request = request
+            .body(hyper::Body::from(body))
+            .map_err(|e| format!("Failed to build request: {:?}", &e))?;
+
+        self.runtime.block_on(async {
+            let exchange = async {
+                let response = self
+                    .client
+                    .request(request)
+                    .await
+                    .map_err(|e| format!("Request failed: {:?}", &e))?;
+                let status_code = response.status().as_u16();
+                let is_json = response
+                    .headers()
+                    .get(hyper::header::CONTENT_TYPE)
+                    .and_then(|content_type| content_type.to_str().ok())
+                    .is_some_and(|content_type| content_type.starts_with("application/json"));
+                let bytes = hyper::body::to_bytes(response.into_body())
+                    .await
+                    .map_err(|e| format!("Failed to read response body: {:?}", &e))?;
+                Ok((status_code, response_payload(&bytes, is_json)))
+            };
+            tokio::time::timeout(timeout, exchange)
+                .await
+                .map_err(|_| format!("Request timed out after {:?}", timeout))?
+        })
+    }
+}
+
+/// Without the `http2-observers` feature, there is no HTTP/2 client to make
+#[cfg(not(feature = "http2-observers"))]
+#[derive(Debug)]
+pub struct Http2Client {}
+
+#[cfg(not(feature = "http2-observers"))]
+impl Http2Client {
+    pub fn new() -> Result<Self, String> {
+        Err("HTTP/2 delivery needs a stacks-node built with the `http2-observers` feature".into())
+    }
+
+    pub fn post(
+        &self,
+        _url: &Url,
+        _payload: &serde_json::Value,
+        _idempotency_key: &str,
+        _extra_headers: &[(&str, String)],
+        _timeout: Duration,
+    ) -> Result<(u16, HttpResponsePayload), String> {
+        unreachable!("Http2Client cannot be constructed without the `http2-observers` feature")
+    }
+}
+
+/// Decode a response body the way the HTTP/1.1 client does, so that success markers match the
+/// same way over either transport
+#[cfg_attr(not(feature = "http2-observers"), allow(dead_code))]
+fn response_payload(bytes: &[u8], is_json: bool) -> HttpResponsePayload {
+    if bytes.is_empty() {
+        return HttpResponsePayload::Empty;
+    }
+    if is_json {
+        if let Ok(json) = serde_json::from_slice(bytes) {
+            return HttpResponsePayload::JSON(json);
+        }
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) => HttpResponsePayload::Text(text.to_string()),
+        Err(_) => HttpResponsePayload::Bytes(bytes.to_vec()),
+    }
+}
+
+#[cfg(all(test, feature = "http2-observers"))]
+mod test {
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use std::sync::mpsc::{channel, Sender};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Request, Response, Server, Version};
+    use serde_json::json;
+
+    use super::*;
+
+    /// Serve HTTP/2 (only) on a background thread, answering each request with `{"ok":true}`
+    /// and reporting its HTTP version, idempotency key, and body, and the address of the
+    /// connection it came in on
+    fn start_http2_server(
+        requests: Sender<(Version, String, serde_json::Value, SocketAddr)>,
+    ) -> SocketAddr {
+        let (addr_tx, addr_rx) = channel();
+        thread::spawn(move || {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(async move {
+                let requests = Arc::new(Mutex::new(requests));
+                let make_service =
+                    make_service_fn(move |conn: &hyper::server::conn::AddrStream| {
+                        let remote_addr = conn.remote_addr();
+                        let requests = requests.clone();
+                        async move {
+                            Ok::<_, Infallible>(service_fn(move |request: Request<Body>| {
+                                let requests = requests.clone();
+                                async move {
+                                    let version = request.version();
+                                    let key = request.headers()["Idempotency-Key"]
+                                        .to_str()
+                                        .unwrap()
+                                        .to_string();
+                                    let body =
+                                        hyper::body::to_bytes(request.into_body()).await.unwrap();
+                                    let payload = serde_json::from_slice(&body).unwrap();
+                                    requests
+                                        .lock()
+                                        .unwrap()
+                                        .send((version, key, payload, remote_addr))
+                                        .unwrap();
+                                    Ok::<_, Infallible>(
+                                        Response::builder()
+                                            .header("Content-Type", "application/json")
+                                            .body(Body::from(r#"{ "ok": true }"#))
+                                            .unwrap(),
+                                    )
+                                }
+                            }))
+                        }
+                    });
+                let server = Server::bind(&"127.0.0.1:0".parse().unwrap())
+                    .http2_only(true)
+                    .serve(make_service);
+                addr_tx.send(server.local_addr()).unwrap();
+                server.await.unwrap();
+            });
+        });
+        addr_rx.recv().unwrap()
+    }
+
+    #[test]
+    fn test_post_over_one_connection() {
+        let (tx, rx) = channel();
+        let addr = start_http2_server(tx);
+        let client = Arc::new(Http2Client::new().unwrap());
+        let url = Url::parse(&format!("http://{}/new_block", addr)).unwrap();
+
+        // concurrent payloads share the client's connection
+        thread::scope(|s| {
+            for i in 0..4 {
+                let client = client.clone();
+                let url = url.clone();
+                s.spawn(move || {
+                    let (status_code, body) = client
+                        .post(
+                            &url,
+                            &json!({ "i": i }),
+                            &format!("key-{}", i),
+                            &[],
+                            Duration::from_secs(5),
+                        )
+                        .unwrap();
+                    assert_eq!(status_code, 200);
+                    assert_eq!(body, HttpResponsePayload::JSON(json!({"ok": true})));
+                });
+            }
+        });
+
+        let mut received: Vec<_> = (0..4)
+            .map(|_| rx.recv_timeout(Duration::from_secs(5)).unwrap())
+            .collect();
+        received.sort_by_key(|(_, key, _, _)| key.clone());
+        let connection = received[0].3;
+        for (i, (version, key, payload, remote_addr)) in received.into_iter().enumerate() {
+            assert_eq!(version, Version::HTTP_2);
+            assert_eq!(key, format!("key-{}", i));
+            assert_eq!(payload, json!({ "i": i }));
+            assert_eq!(remote_addr, connection);
+        }
+    }
+
+    #[test]
+    fn test_post_timeout() {
+        // a listener that never answers
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!(
+            "http://{}/new_block",
+            listener.local_addr().unwrap()
+        ))
+        .unwrap();
+        let client = Http2Client::new().unwrap();
+        let err = client
+            .post(&url, &json!({}), "key", &[], Duration::from_millis(200))
+            .unwrap_err();
+        assert!(err.contains("timed out"), "{}", err);
+    }
+}
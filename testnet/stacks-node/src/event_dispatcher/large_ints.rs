@@ -0,0 +1,105 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Stringification of potentially-large integer fields in event payloads.
+//!
+//! JavaScript parses JSON numbers as doubles, so integers above 2^53 silently lose precision.
+//! An observer that sets `stringify_large_ints` receives the u64 and u128 fields listed in
+//! `LARGE_INT_FIELDS` as decimal strings instead, the way `serialize_u128_as_string` already
+//! sends `stacked_amt`.
+
+use serde_json::Value;
+
+/// The payload fields that hold u64 or u128 values, at any level of nesting.
+/// Fields that are already strings (e.g. `coinbase_amount`) are unaffected.
+pub const LARGE_INT_FIELDS: &[&str] = &[
+    "amt",
+    "block_height",
+    "burn_amount",
+    "burn_block_height",
+    "burn_fee",
+    "delegated_ustx",
+    "max_amount",
+    "parent_burn_block_height",
+    "sortition_burn",
+    "sponsor_fee",
+    "stacked_ustx",
+    "stacks_height",
+    "sunset_burn",
+    "target_burn_height",
+    "tenure_height",
+    "transfered_ustx",
+    "until_burn_height",
+];
+
+/// Rewrite every integer-valued `LARGE_INT_FIELDS` field of `payload` as a decimal string
+pub fn stringify_large_ints(payload: &Value) -> Value {
+    match payload {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        Value::Number(number)
+                            if !number.is_f64() && LARGE_INT_FIELDS.contains(&key.as_str()) =>
+                        {
+                            Value::String(number.to_string())
+                        }
+                        _ => stringify_large_ints(value),
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(stringify_large_ints).collect()),
+        _ => payload.clone(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_stringify_large_ints() {
+        let payload = json!({
+            "block_height": 100,
+            "burn_block_height": u64::MAX,
+            "tx_index": 3,
+            "coinbase_amount": "1000",
+            "reward_recipients": [{"recipient": "addr", "amt": 9007199254740993u64}],
+            "transactions": [{
+                "burnchain_op": {"transfer_stx": {"transfered_ustx": u128::MAX, "burn_block_height": null}},
+            }],
+            "metadata": {"tenure_height": 7, "ratio": 0.5},
+        });
+        assert_eq!(
+            stringify_large_ints(&payload),
+            json!({
+                "block_height": "100",
+                "burn_block_height": u64::MAX.to_string(),
+                "tx_index": 3,
+                "coinbase_amount": "1000",
+                "reward_recipients": [{"recipient": "addr", "amt": "9007199254740993"}],
+                "transactions": [{
+                    "burnchain_op": {"transfer_stx": {"transfered_ustx": u128::MAX.to_string(), "burn_block_height": null}},
+                }],
+                "metadata": {"tenure_height": "7", "ratio": 0.5},
+            })
+        );
+    }
+}
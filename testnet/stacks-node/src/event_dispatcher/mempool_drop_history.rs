@@ -0,0 +1,169 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A bounded history of the transactions dropped from the mempool.
+//!
+//! `/drop_mempool_tx` events are sent once, to the observers subscribed at the time, so an
+//! operator looking into why a transaction disappeared usually has nothing to go on. This keeps
+//! the most recent drops, with their reason and time, so that they can be listed with
+//! `GET /v2/debug/mempool_drops`, or sent again to an observer that subscribed later.
+
+use std::collections::VecDeque;
+
+use serde_json::json;
+use stacks::burnchains::Txid;
+use stacks::core::mempool::MemPoolDropReason;
+use stacks::net::api::getmempooldrops::{RPCMempoolDrop, RPCMempoolDropsData};
+
+/// The most recently dropped mempool transactions, oldest first
+pub struct MempoolDropHistory {
+    capacity: usize,
+    drops: VecDeque<RPCMempoolDrop>,
+}
+
+impl MempoolDropHistory {
+    /// Remember up to `capacity` dropped transactions. A capacity of 0 remembers none.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            drops: VecDeque::new(),
+        }
+    }
+
+    /// Record that `txids` were dropped at `timestamp` for `reason`, forgetting the oldest
+    /// drops once there are more than `capacity`
+    pub fn record(&mut self, txids: &[Txid], reason: &MemPoolDropReason, timestamp: u64) {
+        if self.capacity == 0 {
+            return;
+        }
+        let reason = reason.to_string();
+        // only the last `capacity` of a large drop can be kept anyway
+        let skip = txids.len().saturating_sub(self.capacity);
+        for txid in txids.iter().skip(skip) {
+            self.drops.push_back(RPCMempoolDrop {
+                txid: txid.clone(),
+                reason: reason.clone(),
+                timestamp,
+            });
+        }
+        while self.drops.len() > self.capacity {
+            self.drops.pop_front();
+        }
+    }
+
+    /// The remembered drops, for `GET /v2/debug/mempool_drops`
+    pub fn snapshot(&self) -> RPCMempoolDropsData {
+        RPCMempoolDropsData {
+            drops: self.drops.iter().cloned().collect(),
+            capacity: self.capacity as u64,
+        }
+    }
+
+    /// Rebuild the `/drop_mempool_tx` payloads of the remembered drops, oldest first. Drops
+    /// made at the same time for the same reason are sent together, as they originally were.
+    /// Each payload also carries the time of the drop, and is marked as a replay.
+    pub fn make_replay_payloads(&self) -> Vec<serde_json::Value> {
+        let mut payloads = vec![];
+        let mut drops = self.drops.iter().peekable();
+        while let Some(first) = drops.next() {
+            let mut dropped_txids = vec![json!(format!("0x{}", first.txid))];
+            while let Some(next) = drops
+                .next_if(|next| next.reason == first.reason && next.timestamp == first.timestamp)
+            {
+                dropped_txids.push(json!(format!("0x{}", next.txid)));
+            }
+            payloads.push(json!({
+                "dropped_txids": dropped_txids,
+                "reason": first.reason,
+                "dropped_at": first.timestamp,
+                "replayed": true,
+            }));
+        }
+        payloads
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_mempool_drop_history() {
+        let mut history = MempoolDropHistory::new(4);
+        history.record(
+            &[Txid([0x01; 32]), Txid([0x02; 32])],
+            &MemPoolDropReason::REPLACE_BY_FEE,
+            100,
+        );
+        history.record(&[Txid([0x03; 32])], &MemPoolDropReason::STALE_COLLECT, 100);
+        history.record(&[Txid([0x04; 32])], &MemPoolDropReason::STALE_COLLECT, 160);
+
+        let snapshot = history.snapshot();
+        assert_eq!(snapshot.capacity, 4);
+        assert_eq!(
+            snapshot.drops.first(),
+            Some(&RPCMempoolDrop {
+                txid: Txid([0x01; 32]),
+                reason: "ReplaceByFee".into(),
+                timestamp: 100,
+            })
+        );
+
+        assert_eq!(
+            history.make_replay_payloads(),
+            vec![
+                json!({
+                    "dropped_txids": [
+                        format!("0x{}", Txid([0x01; 32])),
+                        format!("0x{}", Txid([0x02; 32])),
+                    ],
+                    "reason": "ReplaceByFee",
+                    "dropped_at": 100,
+                    "replayed": true,
+                }),
+                json!({
+                    "dropped_txids": [format!("0x{}", Txid([0x03; 32]))],
+                    "reason": "StaleGarbageCollect",
+                    "dropped_at": 100,
+                    "replayed": true,
+                }),
+                json!({
+                    "dropped_txids": [format!("0x{}", Txid([0x04; 32]))],
+                    "reason": "StaleGarbageCollect",
+                    "dropped_at": 160,
+                    "replayed": true,
+                }),
+            ]
+        );
+
+        // the oldest drops are forgotten, even within one large drop
+        let txids: Vec<_> = (5..12).map(|i| Txid([i; 32])).collect();
+        history.record(&txids, &MemPoolDropReason::TOO_EXPENSIVE, 200);
+        let snapshot = history.snapshot();
+        let remembered: Vec<_> = snapshot
+            .drops
+            .iter()
+            .map(|drop| drop.txid.clone())
+            .collect();
+        assert_eq!(remembered, txids[3..].to_vec());
+
+        // a history without capacity remembers nothing
+        let mut history = MempoolDropHistory::new(0);
+        history.record(&txids, &MemPoolDropReason::TOO_EXPENSIVE, 200);
+        assert!(history.snapshot().drops.is_empty());
+        assert!(history.make_replay_payloads().is_empty());
+    }
+}
@@ -0,0 +1,188 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Local archive of the blocks this node has mined.
+//!
+//! Each mined-block payload is appended to a gzipped file of newline-delimited JSON, one file
+//! per target burn block height, as `{"path": ..., "payload": ...}`. Each append is its own
+//! gzip member, so a file stays readable (e.g. with `zcat`) even if the node stops mid-write
+//! of a later block.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use libflate::gzip::Encoder;
+use serde_json::json;
+
+const FILE_PREFIX: &str = "mined-blocks-";
+const FILE_SUFFIX: &str = ".ndjson.gz";
+
+/// Where this node's mined-block payloads are archived, and for how long
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinedBlockArchive {
+    dir: PathBuf,
+    /// If set, only the files of this many most recent burn block heights are kept
+    retention_burn_blocks: Option<u64>,
+}
+
+impl MinedBlockArchive {
+    pub fn new(dir: PathBuf, retention_burn_blocks: Option<u64>) -> Self {
+        Self {
+            dir,
+            retention_burn_blocks: retention_burn_blocks.map(|retention| retention.max(1)),
+        }
+    }
+
+    /// The file holding the blocks mined for `burn_height`. Heights are zero-padded so that the
+    /// files sort in height order.
+    pub fn file_path(&self, burn_height: u64) -> PathBuf {
+        self.dir
+            .join(format!("{}{:010}{}", FILE_PREFIX, burn_height, FILE_SUFFIX))
+    }
+
+    /// Append the payload of the mined-block event at `path` to the file for
+    /// `target_burn_height`. Starting a new file drops the files that have fallen out of the
+    /// retention window.
+    pub fn archive(
+        &self,
+        path: &str,
+        target_burn_height: u64,
+        payload: &serde_json::Value,
+    ) -> Result<(), String> {
+        let mut line = serde_json::to_vec(&json!({ "path": path, "payload": payload }))
+            .map_err(|e| format!("Failed to serialize payload: {:?}", &e))?;
+        line.push(b'\n');
+
+        let mut encoder = Encoder::new(Vec::new())
+            .map_err(|e| format!("Failed to start compressing payload: {:?}", &e))?;
+        encoder
+            .write_all(&line)
+            .map_err(|e| format!("Failed to compress payload: {:?}", &e))?;
+        let compressed = encoder
+            .finish()
+            .into_result()
+            .map_err(|e| format!("Failed to compress payload: {:?}", &e))?;
+
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create {}: {:?}", self.dir.display(), &e))?;
+        let file_path = self.file_path(target_burn_height);
+        let is_new_file = !file_path.exists();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&file_path)
+            .map_err(|e| format!("Failed to open {}: {:?}", file_path.display(), &e))?;
+        file.write_all(&compressed)
+            .map_err(|e| format!("Failed to write to {}: {:?}", file_path.display(), &e))?;
+
+        if is_new_file {
+            self.prune(target_burn_height)?;
+        }
+        Ok(())
+    }
+
+    /// Remove the files of burn heights that are `retention_burn_blocks` or more below
+    /// `burn_height`
+    fn prune(&self, burn_height: u64) -> Result<(), String> {
+        let Some(retention) = self.retention_burn_blocks else {
+            return Ok(());
+        };
+        let Some(oldest_kept) = burn_height.checked_sub(retention - 1) else {
+            return Ok(());
+        };
+        let entries = fs::read_dir(&self.dir)
+            .map_err(|e| format!("Failed to list {}: {:?}", self.dir.display(), &e))?;
+        for entry in entries.flatten() {
+            let file_name = entry.file_name();
+            let Some(height) = file_name
+                .to_str()
+                .and_then(|name| name.strip_prefix(FILE_PREFIX))
+                .and_then(|name| name.strip_suffix(FILE_SUFFIX))
+                .and_then(|height| height.parse::<u64>().ok())
+            else {
+                continue;
+            };
+            if height < oldest_kept {
+                fs::remove_file(entry.path()).map_err(|e| {
+                    format!("Failed to remove {}: {:?}", entry.path().display(), &e)
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Read;
+
+    use libflate::gzip::MultiDecoder;
+    use tempfile::tempdir;
+
+    use super::*;
+
+    fn read_archive_file(path: &PathBuf) -> Vec<serde_json::Value> {
+        let mut decoder = MultiDecoder::new(fs::File::open(path).unwrap()).unwrap();
+        let mut contents = String::new();
+        decoder.read_to_string(&mut contents).unwrap();
+        contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_archive_and_prune() {
+        let dir = tempdir().unwrap();
+        let archive = MinedBlockArchive::new(dir.path().join("mined"), Some(2));
+
+        archive
+            .archive("/mined_block", 100, &json!({"stacks_height": 1}))
+            .unwrap();
+        archive
+            .archive("/mined_block", 100, &json!({"stacks_height": 2}))
+            .unwrap();
+        assert_eq!(
+            read_archive_file(&archive.file_path(100)),
+            vec![
+                json!({"path": "/mined_block", "payload": {"stacks_height": 1}}),
+                json!({"path": "/mined_block", "payload": {"stacks_height": 2}}),
+            ]
+        );
+
+        // an unrelated file in the directory is left alone
+        fs::write(dir.path().join("mined").join("notes.txt"), "keep").unwrap();
+
+        archive
+            .archive("/mined_nakamoto_block", 101, &json!({"stacks_height": 3}))
+            .unwrap();
+        assert!(archive.file_path(100).exists());
+
+        // only the last 2 burn heights are kept
+        archive
+            .archive("/mined_nakamoto_block", 102, &json!({"stacks_height": 4}))
+            .unwrap();
+        assert!(!archive.file_path(100).exists());
+        assert!(archive.file_path(101).exists());
+        assert!(archive.file_path(102).exists());
+        assert!(dir.path().join("mined").join("notes.txt").exists());
+        assert_eq!(
+            read_archive_file(&archive.file_path(102)),
+            vec![json!({"path": "/mined_nakamoto_block", "payload": {"stacks_height": 4}})]
+        );
+    }
+}
@@ -0,0 +1,219 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Serialization of a block's `new_block` payloads for many observers.
+//!
+//! Most of a `new_block` payload is the same for every observer: the block's header fields, and
+//! its transactions, whose contract ABIs are slow to build in contract-heavy blocks. Only the
+//! events that an observer subscribed to, and a few optional fields, differ. So each shared part
+//! is serialized once, and each observer's payload is assembled from the serialized parts. Both
+//! steps can be spread over `node.event_serialization_threads` threads, so that the block
+//! processing thread doesn't do all of the work on its own.
+
+use std::collections::HashMap;
+use std::panic::resume_unwind;
+use std::thread;
+
+use serde_json::{Map, Value};
+
+/// The parts of a block's `new_block` payloads, serialized once for all observers
+pub struct NewBlockPayloadParts {
+    /// Every field that is the same in each observer's payload
+    pub common: Map<String, Value>,
+    /// Serialized events, keyed by event index. Only the events that some observer receives
+    /// are serialized.
+    pub events: HashMap<usize, Value>,
+    /// Serialized transactions, in block order
+    pub transactions: Vec<Value>,
+    /// Serialized burnchain operations of burn-origin transactions, keyed by transaction index.
+    /// Only made if some observer sets `include_raw_burnchain_op`.
+    pub raw_burnchain_ops: HashMap<usize, Value>,
+}
+
+impl NewBlockPayloadParts {
+    /// Assemble an observer's payload, with the events at `event_ids` in the order given.
+    /// Every event in `event_ids` must have been serialized.
+    pub fn assemble<'a>(
+        &self,
+        event_ids: impl IntoIterator<Item = &'a usize>,
+        include_raw_burnchain_op: bool,
+        winner_commit: Option<&Value>,
+        sortition_info: Option<&Value>,
+        miner_signature_valid: Option<bool>,
+    ) -> Value {
+        let mut payload = self.common.clone();
+
+        let events = event_ids
+            .into_iter()
+            .map(|event_id| self.events[event_id].clone())
+            .collect();
+        payload.insert("events".into(), Value::Array(events));
+
+        let transactions = self
+            .transactions
+            .iter()
+            .enumerate()
+            .map(|(tx_index, tx)| {
+                let mut tx = tx.clone();
+                if let Some(op) = self
+                    .raw_burnchain_ops
+                    .get(&tx_index)
+                    .filter(|_| include_raw_burnchain_op)
+                {
+                    tx["raw_burnchain_op"] = op.clone();
+                }
+                tx
+            })
+            .collect();
+        payload.insert("transactions".into(), Value::Array(transactions));
+
+        if let Some(winner_commit) = winner_commit {
+            payload.insert("winner_commit".into(), winner_commit.clone());
+        }
+
+        if let Some(Value::Object(sortition_info)) = sortition_info {
+            for (key, value) in sortition_info.iter() {
+                payload.insert(key.clone(), value.clone());
+            }
+        }
+
+        if let Some(miner_signature_valid) = miner_signature_valid {
+            payload.insert("miner_signature_valid".into(), miner_signature_valid.into());
+        }
+
+        Value::Object(payload)
+    }
+}
+
+/// Map `f` over `items`, which it is given with their indexes, on up to `threads` threads.
+/// The results are in the same order as `items`. With one thread, or at most one item, this
+/// runs on the calling thread. A panic in `f` is passed on to the caller.
+pub fn parallel_map<T, R, F>(items: &[T], threads: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(usize, &T) -> R + Sync,
+{
+    if threads <= 1 || items.len() <= 1 {
+        return items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| f(index, item))
+            .collect();
+    }
+
+    let chunk_size = items.len().div_ceil(threads);
+    let f = &f;
+    thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .enumerate()
+            .map(|(chunk_index, chunk)| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .enumerate()
+                        .map(|(i, item)| f(chunk_index * chunk_size + i, item))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap_or_else(|e| resume_unwind(e)))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_parallel_map_preserves_order() {
+        let items: Vec<u64> = (0..103).collect();
+        let expected: Vec<_> = items.iter().map(|item| item * 2).collect();
+        for threads in [0, 1, 2, 3, 8, 200] {
+            let results = parallel_map(&items, threads, |index, item| {
+                assert_eq!(index as u64, *item);
+                item * 2
+            });
+            assert_eq!(results, expected, "threads = {}", threads);
+        }
+        assert!(parallel_map(&Vec::<u64>::new(), 4, |_, item| *item).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "serialization failed")]
+    fn test_parallel_map_passes_on_panics() {
+        let items: Vec<u64> = (0..10).collect();
+        parallel_map(&items, 4, |_, item| {
+            if *item == 7 {
+                panic!("serialization failed");
+            }
+            *item
+        });
+    }
+
+    #[test]
+    fn test_assemble_new_block_payload() {
+        let parts = NewBlockPayloadParts {
+            common: json!({ "block_height": 1, "sortition_id": "0x00" })
+                .as_object()
+                .unwrap()
+                .clone(),
+            events: HashMap::from([
+                (0, json!({"event_index": 0})),
+                (2, json!({"event_index": 2})),
+            ]),
+            transactions: vec![json!({"tx_index": 0}), json!({"tx_index": 1})],
+            raw_burnchain_ops: HashMap::from([(1, json!("0x24"))]),
+        };
+
+        assert_eq!(
+            parts.assemble(&[], false, None, None, None),
+            json!({
+                "block_height": 1,
+                "sortition_id": "0x00",
+                "events": [],
+                "transactions": [{"tx_index": 0}, {"tx_index": 1}],
+            })
+        );
+
+        let winner_commit = json!({ "txid": "0x11" });
+        let sortition_info = json!({ "sortition_id": "0x22", "vrf_seed": null });
+        assert_eq!(
+            parts.assemble(
+                &[2, 0],
+                true,
+                Some(&winner_commit),
+                Some(&sortition_info),
+                Some(false)
+            ),
+            json!({
+                "block_height": 1,
+                "sortition_id": "0x22",
+                "vrf_seed": null,
+                "events": [{"event_index": 2}, {"event_index": 0}],
+                "transactions": [{"tx_index": 0}, {"tx_index": 1, "raw_burnchain_op": "0x24"}],
+                "winner_commit": {"txid": "0x11"},
+                "miner_signature_valid": false,
+            })
+        );
+    }
+}
@@ -0,0 +1,254 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Partition keys, for sharded event consumers.
+//!
+//! An observer with a `partition` option gets an `X-Partition-Key` header with each payload,
+//! holding a partition number below its partition count. The number is a hash of one value
+//! taken from the payload (an address, a contract, or a block), so payloads that share that
+//! value always get the same partition, and a load balancer or the consumer can route them to
+//! the same shard. A payload without such a value is partitioned by its event path instead, so
+//! that each of those topics stays on one shard.
+
+use serde_json::Value;
+use stacks::chainstate::stacks::StacksTransaction;
+use stacks_common::codec::StacksMessageCodec;
+use stacks_common::util::hash::{hex_bytes, Sha512Trunc256Sum};
+
+/// Name of the header that carries a payload's partition
+pub const PARTITION_KEY_HEADER: &str = "X-Partition-Key";
+
+/// The payload fields that identify a block, in order of preference
+const BLOCK_FIELDS: [&str; 5] = [
+    "index_block_hash",
+    "block_hash",
+    "burn_block_hash",
+    "parent_index_block_hash",
+    "consensus_hash",
+];
+
+/// The payload fields that identify a contract
+const CONTRACT_FIELDS: [&str; 2] = ["contract_identifier", "contract_id"];
+
+/// Which value of a payload its partition is computed from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PartitionFunction {
+    /// The origin address of the payload's first transaction
+    ByAddress,
+    /// The first contract identifier found in the payload
+    ByContract,
+    /// The block the payload is about
+    ByBlock,
+}
+
+/// Computes the partition of each of an observer's payloads
+#[derive(Debug, Clone, PartialEq)]
+pub struct PayloadPartitioner {
+    function: PartitionFunction,
+    partitions: u64,
+}
+
+impl PayloadPartitioner {
+    /// Parse a partition option from an observer's config, e.g. `by_address:16`
+    pub fn parse(partition: &str) -> Result<Self, String> {
+        let err = || {
+            format!(
+                "Unsupported partition '{}': expected '<by_address|by_contract|by_block>:<partitions>', with at least 1 partition",
+                partition
+            )
+        };
+        let (function, partitions) = partition.split_once(':').ok_or_else(err)?;
+        let function = match function {
+            "by_address" => PartitionFunction::ByAddress,
+            "by_contract" => PartitionFunction::ByContract,
+            "by_block" => PartitionFunction::ByBlock,
+            _ => return Err(err()),
+        };
+        let partitions: u64 = partitions.parse().map_err(|_| err())?;
+        if partitions == 0 {
+            return Err(err());
+        }
+        Ok(Self {
+            function,
+            partitions,
+        })
+    }
+
+    /// The partition, below the partition count, of a payload sent to `path`. Enveloped
+    /// payloads are partitioned by the payload they hold.
+    pub fn partition_key(&self, path: &str, payload: &Value) -> u64 {
+        let payload = match (payload.get("type"), payload.get("data")) {
+            (Some(Value::String(_)), Some(data)) => data,
+            _ => payload,
+        };
+        let value = match self.function {
+            PartitionFunction::ByAddress => first_origin_address(payload),
+            PartitionFunction::ByContract => first_string_field(payload, &CONTRACT_FIELDS),
+            PartitionFunction::ByBlock => BLOCK_FIELDS
+                .iter()
+                .find_map(|field| payload.get(field)?.as_str().map(String::from)),
+        };
+        let value = value.unwrap_or_else(|| path.to_string());
+        let digest = Sha512Trunc256Sum::from_data(value.as_bytes());
+        let mut prefix = [0u8; 8];
+        prefix.copy_from_slice(&digest.as_bytes()[0..8]);
+        u64::from_be_bytes(prefix) % self.partitions
+    }
+}
+
+/// Decode a hex-encoded transaction, with or without a `0x` prefix, and get its origin address
+fn origin_address(raw_tx: &str) -> Option<String> {
+    let bytes = hex_bytes(raw_tx.strip_prefix("0x").unwrap_or(raw_tx)).ok()?;
+    let tx = StacksTransaction::consensus_deserialize(&mut &bytes[..]).ok()?;
+    Some(tx.origin_address().to_string())
+}
+
+/// The origin address of the first transaction in a payload: its `raw_tx`, the first of its
+/// `transactions`, or the first transaction of a `new_mempool_tx` array
+fn first_origin_address(payload: &Value) -> Option<String> {
+    if let Some(raw_tx) = payload.get("raw_tx").and_then(Value::as_str) {
+        return origin_address(raw_tx);
+    }
+    let raw_tx = match payload {
+        Value::Array(raw_txs) => raw_txs.first()?.as_str()?,
+        _ => payload
+            .get("transactions")?
+            .as_array()?
+            .first()?
+            .get("raw_tx")?
+            .as_str()?,
+    };
+    origin_address(raw_tx)
+}
+
+/// The first string value of any of `fields`, searching the payload depth-first
+fn first_string_field(payload: &Value, fields: &[&str]) -> Option<String> {
+    match payload {
+        Value::Object(object) => {
+            if let Some(value) = fields.iter().find_map(|field| object.get(*field)?.as_str()) {
+                return Some(value.to_string());
+            }
+            object
+                .values()
+                .find_map(|value| first_string_field(value, fields))
+        }
+        Value::Array(values) => values
+            .iter()
+            .find_map(|value| first_string_field(value, fields)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use clarity::vm::types::PrincipalData;
+    use serde_json::json;
+    use stacks::types::chainstate::StacksAddress;
+    use stacks::util::hash::to_hex;
+    use stacks::util::secp256k1::Secp256k1PrivateKey;
+
+    use super::*;
+    use crate::tests::{make_stacks_transfer, to_addr};
+
+    /// A hex-encoded transfer from the address of `seed`, and that address
+    fn make_raw_tx(seed: u8) -> (String, StacksAddress) {
+        let sender = Secp256k1PrivateKey::from_seed(&[seed]);
+        let recipient: PrincipalData = StacksAddress::burn_address(false).into();
+        let raw_tx = make_stacks_transfer(&sender, 0, 180, 0x80000000, &recipient, 1);
+        (format!("0x{}", to_hex(&raw_tx)), to_addr(&sender))
+    }
+
+    #[test]
+    fn test_parse_partition() {
+        assert_eq!(
+            PayloadPartitioner::parse("by_address:16").unwrap(),
+            PayloadPartitioner {
+                function: PartitionFunction::ByAddress,
+                partitions: 16
+            }
+        );
+        assert!(PayloadPartitioner::parse("by_block:1").is_ok());
+        assert!(PayloadPartitioner::parse("by_contract").is_err());
+        assert!(PayloadPartitioner::parse("by_contract:0").is_err());
+        assert!(PayloadPartitioner::parse("by_contract:-1").is_err());
+        assert!(PayloadPartitioner::parse("by_txid:4").is_err());
+    }
+
+    #[test]
+    fn test_partition_key() {
+        let by_address = PayloadPartitioner::parse("by_address:1000000000").unwrap();
+        let (raw_tx_a, origin_a) = make_raw_tx(1);
+        let (raw_tx_b, _) = make_raw_tx(2);
+        assert_eq!(
+            first_origin_address(&json!([raw_tx_a])),
+            Some(origin_a.to_string())
+        );
+
+        // a mempool transaction and a block led by a transaction from the same address
+        // land in the same partition, however they are enveloped
+        let mempool_key = by_address.partition_key("/new_mempool_tx", &json!([raw_tx_a]));
+        let block = json!({
+            "index_block_hash": "0x01",
+            "transactions": [{"raw_tx": raw_tx_a}, {"raw_tx": raw_tx_b}],
+        });
+        assert_eq!(by_address.partition_key("/new_block", &block), mempool_key);
+        assert_eq!(
+            by_address.partition_key(
+                "/new_block",
+                &json!({"type": "/new_block", "data": block.clone()})
+            ),
+            mempool_key
+        );
+        assert_ne!(
+            by_address.partition_key("/new_mempool_tx", &json!([raw_tx_b])),
+            mempool_key
+        );
+
+        let by_contract = PayloadPartitioner::parse("by_contract:1000000000").unwrap();
+        let contract_event = json!({
+            "events": [
+                {"stx_transfer_event": {"sender": "ST000000000000000000002AMW42H"}},
+                {"contract_event": {"contract_identifier": "ST000000000000000000002AMW42H.pox-4"}},
+            ],
+        });
+        let chunks = json!({
+            "contract_id": "ST000000000000000000002AMW42H.pox-4",
+            "modified_slots": [],
+        });
+        assert_eq!(
+            by_contract.partition_key("/new_block", &contract_event),
+            by_contract.partition_key("/stackerdb_chunks", &chunks)
+        );
+
+        // payloads without the value are partitioned by their path
+        let by_block = PayloadPartitioner::parse("by_block:1000000000").unwrap();
+        assert_eq!(
+            by_block.partition_key("/drop_mempool_tx", &json!({"dropped_txids": ["0x01"]})),
+            by_block.partition_key("/drop_mempool_tx", &json!({"dropped_txids": ["0x02"]}))
+        );
+        assert_eq!(
+            by_block.partition_key("/new_block", &block),
+            by_block.partition_key("/new_block_header", &json!({"index_block_hash": "0x01"}))
+        );
+
+        // every key is below the partition count
+        let by_block = PayloadPartitioner::parse("by_block:3").unwrap();
+        for height in 0..100 {
+            let payload = json!({ "index_block_hash": format!("0x{:064x}", height) });
+            assert!(by_block.partition_key("/new_block", &payload) < 3);
+        }
+    }
+}
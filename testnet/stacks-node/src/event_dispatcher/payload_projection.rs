@@ -0,0 +1,316 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Per-observer projection of event payloads.
+//!
+//! An observer can list the payload fields it wants (`include_fields`) and/or the ones it
+//! doesn't (`exclude_fields`).  Fields are named by dot-separated paths, where a `[]` suffix
+//! applies the rest of the path to every item of an array, e.g. `transactions[].txid`.
+
+use serde_json::{Map, Value};
+
+/// One step of a field path
+#[derive(Debug, Clone, PartialEq)]
+struct PathSegment {
+    key: String,
+    /// Whether this field is an array whose items the rest of the path applies to
+    each_item: bool,
+}
+
+/// A path to a field of an event payload, e.g. `transactions[].txid`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldPath {
+    segments: Vec<PathSegment>,
+}
+
+impl FieldPath {
+    pub fn parse(path: &str) -> Result<FieldPath, String> {
+        let segments = path
+            .split('.')
+            .map(|part| {
+                let (key, each_item) = match part.strip_suffix("[]") {
+                    Some(key) => (key, true),
+                    None => (part, false),
+                };
+                if key.is_empty() || key.contains(['[', ']']) {
+                    return Err(format!("Invalid payload field path '{}'", path));
+                }
+                Ok(PathSegment {
+                    key: key.to_string(),
+                    each_item,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(FieldPath { segments })
+    }
+}
+
+/// Extract the part of `src` named by `segments`, or `None` if `src` doesn't have it.
+/// Array items that don't have the rest of the path are kept as `{}` (or `null`, if they are
+/// not objects), so that projected items stay aligned with the original array.
+fn extract_path(src: &Value, segments: &[PathSegment]) -> Option<Value> {
+    let Some((segment, rest)) = segments.split_first() else {
+        return Some(src.clone());
+    };
+    let Value::Object(src_map) = src else {
+        return None;
+    };
+    let src_field = src_map.get(&segment.key)?;
+    let projected = if segment.each_item {
+        let Value::Array(src_items) = src_field else {
+            return None;
+        };
+        let items = src_items
+            .iter()
+            .map(|item| {
+                extract_path(item, rest).unwrap_or_else(|| match item {
+                    Value::Object(_) => Value::Object(Map::new()),
+                    _ => Value::Null,
+                })
+            })
+            .collect();
+        Value::Array(items)
+    } else {
+        extract_path(src_field, rest)?
+    };
+    let mut map = Map::new();
+    map.insert(segment.key.clone(), projected);
+    Some(Value::Object(map))
+}
+
+/// Merge an extracted field into a projected payload.
+/// Objects are merged key by key, and equal-length arrays item by item.
+fn merge_into(dst: &mut Value, src: Value) {
+    match (dst, src) {
+        (Value::Object(dst_map), Value::Object(src_map)) => {
+            for (key, src_value) in src_map.into_iter() {
+                match dst_map.get_mut(&key) {
+                    Some(dst_value) => merge_into(dst_value, src_value),
+                    None => {
+                        dst_map.insert(key, src_value);
+                    }
+                }
+            }
+        }
+        (Value::Array(dst_items), Value::Array(src_items))
+            if dst_items.len() == src_items.len() =>
+        {
+            for (dst_item, src_item) in dst_items.iter_mut().zip(src_items.into_iter()) {
+                merge_into(dst_item, src_item);
+            }
+        }
+        (dst, src) => *dst = src,
+    }
+}
+
+/// Remove the part of `value` named by `segments`, if it exists
+fn remove_path(value: &mut Value, segments: &[PathSegment]) {
+    let Some((segment, rest)) = segments.split_first() else {
+        return;
+    };
+    let Value::Object(map) = value else {
+        return;
+    };
+    if rest.is_empty() {
+        map.remove(&segment.key);
+        return;
+    }
+    let Some(field) = map.get_mut(&segment.key) else {
+        return;
+    };
+    if !segment.each_item {
+        remove_path(field, rest);
+        return;
+    }
+    if let Value::Array(items) = field {
+        for item in items.iter_mut() {
+            remove_path(item, rest);
+        }
+    }
+}
+
+/// The fields of event payloads that an observer wants delivered.
+/// Only JSON object payloads are projected; any other payload (e.g. the array of raw
+/// transactions in `new_mempool_tx`) is delivered as-is.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PayloadProjection {
+    /// If non-empty, only these fields are kept
+    include: Vec<FieldPath>,
+    /// These fields are removed, after `include` is applied
+    exclude: Vec<FieldPath>,
+}
+
+impl PayloadProjection {
+    pub fn new<S: AsRef<str>>(include: &[S], exclude: &[S]) -> Result<PayloadProjection, String> {
+        let parse_all = |paths: &[S]| {
+            paths
+                .iter()
+                .map(|path| FieldPath::parse(path.as_ref()))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(PayloadProjection {
+            include: parse_all(include)?,
+            exclude: parse_all(exclude)?,
+        })
+    }
+
+    /// Does this projection leave every payload unchanged?
+    pub fn is_identity(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Apply this projection to an assembled payload
+    pub fn apply(&self, payload: &Value) -> Value {
+        if !payload.is_object() {
+            return payload.clone();
+        }
+        let mut projected = if self.include.is_empty() {
+            payload.clone()
+        } else {
+            let mut projected = Value::Object(Map::new());
+            for path in self.include.iter() {
+                if let Some(field) = extract_path(payload, &path.segments) {
+                    merge_into(&mut projected, field);
+                }
+            }
+            projected
+        };
+        for path in self.exclude.iter() {
+            remove_path(&mut projected, &path.segments);
+        }
+        projected
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn block_payload() -> Value {
+        json!({
+            "block_hash": "0x01",
+            "block_height": 100,
+            "events": [{"txid": "0xaa", "type": "stx_transfer_event"}],
+            "metadata": {"cost": {"runtime": 1, "read_count": 2}, "tenure_height": 3},
+            "transactions": [
+                {"txid": "0xaa", "raw_tx": "0x00", "execution_cost": {"runtime": 4}},
+                {"txid": "0xbb", "raw_tx": "0x01", "execution_cost": {"runtime": 5}},
+                {"raw_tx": "0x02"},
+            ],
+        })
+    }
+
+    #[test]
+    fn test_parse_field_path() {
+        assert!(FieldPath::parse("block_height").is_ok());
+        assert!(FieldPath::parse("transactions[].txid").is_ok());
+        assert!(FieldPath::parse("a[].b[].c").is_ok());
+        assert!(FieldPath::parse("").is_err());
+        assert!(FieldPath::parse("a..b").is_err());
+        assert!(FieldPath::parse("[]").is_err());
+        assert!(FieldPath::parse("a[0].b").is_err());
+        assert!(FieldPath::parse("a[]]").is_err());
+    }
+
+    #[test]
+    fn test_include_fields() {
+        let projection =
+            PayloadProjection::new(&["block_height", "events", "transactions[].txid"], &[])
+                .unwrap();
+        assert_eq!(
+            projection.apply(&block_payload()),
+            json!({
+                "block_height": 100,
+                "events": [{"txid": "0xaa", "type": "stx_transfer_event"}],
+                "transactions": [{"txid": "0xaa"}, {"txid": "0xbb"}, {}],
+            })
+        );
+
+        // nested objects, overlapping paths, and missing fields
+        let projection = PayloadProjection::new(
+            &[
+                "metadata.cost.runtime",
+                "transactions[].execution_cost.runtime",
+                "transactions[].txid",
+                "transactions",
+                "no_such_field",
+                "block_height.not_an_object",
+                "block_hash[].not_an_array",
+            ],
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            projection.apply(&block_payload()),
+            json!({
+                "metadata": {"cost": {"runtime": 1}},
+                "transactions": block_payload()["transactions"],
+            })
+        );
+    }
+
+    #[test]
+    fn test_exclude_fields() {
+        let projection = PayloadProjection::new(
+            &[],
+            &[
+                "events",
+                "metadata.cost.read_count",
+                "transactions[].raw_tx",
+                "no_such_field.nested",
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            projection.apply(&block_payload()),
+            json!({
+                "block_hash": "0x01",
+                "block_height": 100,
+                "metadata": {"cost": {"runtime": 1}, "tenure_height": 3},
+                "transactions": [
+                    {"txid": "0xaa", "execution_cost": {"runtime": 4}},
+                    {"txid": "0xbb", "execution_cost": {"runtime": 5}},
+                    {},
+                ],
+            })
+        );
+
+        // exclusions apply after inclusions
+        let projection =
+            PayloadProjection::new(&["transactions"], &["transactions[].execution_cost"]).unwrap();
+        assert_eq!(
+            projection.apply(&block_payload()),
+            json!({
+                "transactions": [
+                    {"txid": "0xaa", "raw_tx": "0x00"},
+                    {"txid": "0xbb", "raw_tx": "0x01"},
+                    {"raw_tx": "0x02"},
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_non_object_payloads_unchanged() {
+        let projection = PayloadProjection::new(&["txid"], &["raw_tx"]).unwrap();
+        let payload = json!(["0x00", "0x01"]);
+        assert_eq!(projection.apply(&payload), payload);
+        assert!(!projection.is_identity());
+        assert!(PayloadProjection::default().is_identity());
+    }
+}
@@ -0,0 +1,100 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Programmatic payload transforms, for embedders that run the node as a library.
+//!
+//! A `PayloadTransform` wraps a closure that is given every payload, with the path it is sent
+//! to, before any of the observer's own payload options (`include_fields`,
+//! `stringify_large_ints`, `payload_envelope`, ...) are applied. It can redact or enrich the
+//! payload as it likes. The closure runs on whichever thread sends the event, often the block
+//! processing thread, so it should be quick, and it must not panic: a panicking closure is
+//! caught, and the payload it was given is dropped rather than sent untransformed, since it may
+//! hold data the closure was meant to redact.
+
+use std::fmt;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+
+use serde_json::Value;
+
+/// A closure applied to every event payload before it is sent
+#[derive(Clone)]
+pub struct PayloadTransform {
+    transform: Arc<dyn Fn(&str, Value) -> Value + Send + Sync>,
+}
+
+impl fmt::Debug for PayloadTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PayloadTransform")
+    }
+}
+
+impl PayloadTransform {
+    /// Wrap `transform`, which is given each payload's path (e.g. `/new_block`) and the payload,
+    /// and returns the payload to send
+    pub fn new<F>(transform: F) -> Self
+    where
+        F: Fn(&str, Value) -> Value + Send + Sync + 'static,
+    {
+        Self {
+            transform: Arc::new(transform),
+        }
+    }
+
+    /// Transform a payload that will be sent to `path`. Returns `None` if the closure panicked,
+    /// in which case the payload must not be sent.
+    pub fn apply(&self, path: &str, payload: &Value) -> Option<Value> {
+        match catch_unwind(AssertUnwindSafe(|| (self.transform)(path, payload.clone()))) {
+            Ok(transformed) => Some(transformed),
+            Err(_) => {
+                error!(
+                    "Event dispatcher: payload transform panicked, dropping payload";
+                    "path" => path
+                );
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_payload_transform() {
+        let redact = PayloadTransform::new(|path, mut payload| {
+            if let Some(object) = payload.as_object_mut() {
+                object.remove("memo");
+                object.insert("path".into(), json!(path));
+            }
+            payload
+        });
+        assert_eq!(
+            redact.apply("/new_block", &json!({"memo": "secret", "block_height": 1})),
+            Some(json!({"path": "/new_block", "block_height": 1}))
+        );
+
+        // a panicking transform drops the payload, rather than taking the thread down with it
+        let panicking = PayloadTransform::new(|_, _| panic!("transform failed"));
+        assert_eq!(
+            panicking.apply("/new_block", &json!({"memo": "secret"})),
+            None
+        );
+    }
+}
@@ -0,0 +1,160 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use stacks_common::util::hash::Sha512Trunc256Sum;
+
+/// Whether a throttled `proposal_response` may be sent to an observer
+#[derive(Debug, PartialEq, Clone)]
+pub enum ProposalResponseSlot {
+    /// Send it now
+    Send,
+    /// Check again after this long
+    Wait(Duration),
+    /// A later response for the same block proposal replaced it, so drop it
+    Superseded,
+}
+
+/// Spaces out the `proposal_response` events sent to each observer, and drops responses that
+/// a later response for the same block proposal replaces before they are sent
+pub struct ProposalResponseThrottle {
+    /// Minimum time between two `proposal_response` events to the same observer
+    min_interval: Duration,
+    /// Sequence number of the latest response for each block proposal that is still being
+    /// delivered, keyed by the proposal's signer signature hash
+    latest: HashMap<Sha512Trunc256Sum, u64>,
+    next_seq: u64,
+    /// When each observer, keyed by endpoint, was last sent a `proposal_response`
+    last_sent: HashMap<String, Instant>,
+}
+
+impl ProposalResponseThrottle {
+    pub fn new(min_interval: Duration) -> Self {
+        Self {
+            min_interval,
+            latest: HashMap::new(),
+            next_seq: 0,
+            last_sent: HashMap::new(),
+        }
+    }
+
+    /// Note a new response for the proposal with `signer_signature_hash`, superseding any
+    /// earlier response for it that is not yet sent. Returns the response's sequence number.
+    pub fn begin(&mut self, signer_signature_hash: &Sha512Trunc256Sum) -> u64 {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.latest.insert(signer_signature_hash.clone(), seq);
+        seq
+    }
+
+    /// Check whether response `seq` for `signer_signature_hash` may be sent to `endpoint` at
+    /// `now`. If so, the send is recorded against the observer's interval.
+    pub fn try_send(
+        &mut self,
+        signer_signature_hash: &Sha512Trunc256Sum,
+        seq: u64,
+        endpoint: &str,
+        now: Instant,
+    ) -> ProposalResponseSlot {
+        if self.latest.get(signer_signature_hash) != Some(&seq) {
+            return ProposalResponseSlot::Superseded;
+        }
+        if let Some(last_sent) = self.last_sent.get(endpoint) {
+            let due = *last_sent + self.min_interval;
+            if due > now {
+                return ProposalResponseSlot::Wait(due - now);
+            }
+        }
+        self.last_sent.insert(endpoint.to_string(), now);
+        ProposalResponseSlot::Send
+    }
+
+    /// Note that response `seq` for `signer_signature_hash` is done being delivered
+    pub fn finish(&mut self, signer_signature_hash: &Sha512Trunc256Sum, seq: u64) {
+        if self.latest.get(signer_signature_hash) == Some(&seq) {
+            self.latest.remove(signer_signature_hash);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_proposal_response_throttle() {
+        let mut throttle = ProposalResponseThrottle::new(Duration::from_millis(100));
+        let hash_a = Sha512Trunc256Sum([0x0a; 32]);
+        let hash_b = Sha512Trunc256Sum([0x0b; 32]);
+        let start = Instant::now();
+
+        let seq_a = throttle.begin(&hash_a);
+        assert_eq!(
+            throttle.try_send(&hash_a, seq_a, "observer-1", start),
+            ProposalResponseSlot::Send
+        );
+        assert_eq!(
+            throttle.try_send(&hash_a, seq_a, "observer-2", start),
+            ProposalResponseSlot::Send
+        );
+        throttle.finish(&hash_a, seq_a);
+
+        // each observer waits out its own interval
+        let seq_b = throttle.begin(&hash_b);
+        assert_eq!(
+            throttle.try_send(
+                &hash_b,
+                seq_b,
+                "observer-1",
+                start + Duration::from_millis(40)
+            ),
+            ProposalResponseSlot::Wait(Duration::from_millis(60))
+        );
+        assert_eq!(
+            throttle.try_send(&hash_b, seq_b, "observer-3", start),
+            ProposalResponseSlot::Send
+        );
+
+        // a later response for the same proposal replaces the one that is waiting
+        let seq_b2 = throttle.begin(&hash_b);
+        assert_eq!(
+            throttle.try_send(
+                &hash_b,
+                seq_b,
+                "observer-1",
+                start + Duration::from_millis(100)
+            ),
+            ProposalResponseSlot::Superseded
+        );
+        assert_eq!(
+            throttle.try_send(
+                &hash_b,
+                seq_b2,
+                "observer-1",
+                start + Duration::from_millis(100)
+            ),
+            ProposalResponseSlot::Send
+        );
+
+        // finishing a superseded response leaves the latest one in place
+        throttle.finish(&hash_b, seq_b);
+        assert_eq!(throttle.latest.get(&hash_b), Some(&seq_b2));
+        throttle.finish(&hash_b, seq_b2);
+        assert!(throttle.latest.is_empty());
+    }
+}
@@ -0,0 +1,276 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Delivery of event payloads to Redis streams.
+//!
+//! An observer whose endpoint is a `redis://` URL, such as `redis://:password@host:6379/0`,
+//! has each of its payloads added (with `XADD`) to a Redis stream instead of POSTed to it.
+//! Each event path goes to its own stream, named by the observer's `redis_stream_prefix`
+//! followed by the path, e.g. `stacks:new_block`. Each top-level field of the payload becomes
+//! a field of the stream entry, with its value as JSON, and a payload that isn't a JSON object
+//! becomes a single `payload` field. Redis orders the entries of each stream as they are added;
+//! each entry's `seq` field, which counts up across all of the observer's streams, orders
+//! entries from different streams.
+//!
+//! A connection that fails is reopened, with a growing backoff, and the entry is added again
+//! until Redis takes it. This needs the `redis-observers` feature; without it, registering a
+//! `redis://` observer fails.
+
+use std::time::Duration;
+
+/// Prefix of the names of the streams of a `redis://` observer, if it doesn't set
+/// `redis_stream_prefix`
+pub const DEFAULT_REDIS_STREAM_PREFIX: &str = "stacks:";
+
+/// Longest wait between attempts at reconnecting to Redis
+#[cfg_attr(not(feature = "redis-observers"), allow(dead_code))]
+const MAX_REDIS_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Whether an observer's endpoint is a Redis server, rather than an HTTP one
+pub fn is_redis_endpoint(endpoint: &str) -> bool {
+    endpoint.starts_with("redis://")
+}
+
+/// The name of the stream that the payloads of the event at `path` (e.g. `/new_block`) go to
+#[cfg_attr(not(feature = "redis-observers"), allow(dead_code))]
+pub fn redis_stream_name(prefix: &str, path: &str) -> String {
+    format!("{}{}", prefix, path.trim_start_matches('/'))
+}
+
+/// The fields of the stream entry for `payload`, the `seq`th payload sent to the observer
+#[cfg_attr(not(feature = "redis-observers"), allow(dead_code))]
+pub fn redis_stream_fields(payload: &serde_json::Value, seq: u64) -> Vec<(String, String)> {
+    let mut fields = vec![("seq".to_string(), seq.to_string())];
+    match payload.as_object() {
+        Some(object) => fields.extend(
+            object
+                .iter()
+                // the entry's own `seq` takes the place of any in the payload
+                .filter(|(name, _)| name.as_str() != "seq")
+                .map(|(name, value)| (name.clone(), value.to_string())),
+        ),
+        None => fields.push(("payload".to_string(), payload.to_string())),
+    }
+    fields
+}
+
+#[cfg(feature = "redis-observers")]
+mod client {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Mutex;
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    use super::{redis_stream_fields, redis_stream_name, MAX_REDIS_BACKOFF};
+    use crate::event_dispatcher::observer_registry::redact_endpoint;
+
+    /// Whether `e` is a failure of the connection to Redis, rather than an error from Redis
+    fn is_connection_error(e: &redis::RedisError) -> bool {
+        e.is_io_error() || e.is_connection_dropped() || e.is_timeout()
+    }
+
+    /// The streams of a `redis://` observer, and its connection to Redis, which is opened on
+    /// first use and reopened if it fails
+    pub struct RedisStream {
+        /// The observer's endpoint, with its password redacted
+        endpoint: String,
+        client: redis::Client,
+        prefix: String,
+        /// How long connecting to Redis, and each command, may take
+        timeout: Duration,
+        connection: Mutex<Option<redis::Connection>>,
+        /// Number of entries added so far, across all of the observer's streams
+        seq: AtomicU64,
+    }
+
+    impl RedisStream {
+        pub fn new(endpoint: &str, prefix: &str, timeout: Duration) -> Result<Self, String> {
+            let client = redis::Client::open(endpoint)
+                .map_err(|e| format!("Invalid Redis endpoint: {}", e))?;
+            Ok(Self {
+                endpoint: redact_endpoint(endpoint),
+                client,
+                prefix: prefix.to_string(),
+                // a zero timeout means no timeout to the connection, but is an error to set
+                timeout: timeout.max(Duration::from_millis(1)),
+                connection: Mutex::new(None),
+                seq: AtomicU64::new(0),
+            })
+        }
+
+        /// The observer's endpoint, with its password redacted
+        pub fn endpoint(&self) -> &str {
+            &self.endpoint
+        }
+
+        pub fn prefix(&self) -> &str {
+            &self.prefix
+        }
+
+        /// Add `payload` to the stream of the event at `path`. If the connection to Redis
+        /// fails, it is reopened and the entry is added again, until Redis takes it. An error
+        /// from Redis itself, such as the stream's key holding something else, is not retried.
+        pub fn add(&self, path: &str, payload: &serde_json::Value) -> Result<(), String> {
+            let stream = redis_stream_name(&self.prefix, path);
+            let fields = redis_stream_fields(payload, self.seq.fetch_add(1, Ordering::SeqCst));
+            let mut backoff = Duration::from_millis(100);
+            let mut attempts = 0u64;
+            loop {
+                let e = match self.try_add(&stream, &fields) {
+                    Ok(()) => {
+                        if attempts > 0 {
+                            info!(
+                                "Event observer: reconnected to Redis";
+                                "endpoint" => &self.endpoint, "attempts" => attempts
+                            );
+                        }
+                        return Ok(());
+                    }
+                    Err(e) => e,
+                };
+                if !is_connection_error(&e) {
+                    return Err(format!("Failed to add to Redis stream {}: {}", &stream, e));
+                }
+                warn!(
+                    "Event observer: failed to reach Redis";
+                    "endpoint" => &self.endpoint, "stream" => &stream, "error" => %e,
+                    "backoff" => ?backoff, "attempts" => attempts
+                );
+                sleep(backoff);
+                backoff = std::cmp::min(backoff.saturating_mul(2), MAX_REDIS_BACKOFF);
+                attempts = attempts.saturating_add(1);
+            }
+        }
+
+        fn try_add(&self, stream: &str, fields: &[(String, String)]) -> redis::RedisResult<()> {
+            let mut connection = self
+                .connection
+                .lock()
+                .expect("FATAL: failed to lock Redis connection");
+            if connection.is_none() {
+                let new_connection = self.client.get_connection_with_timeout(self.timeout)?;
+                new_connection.set_read_timeout(Some(self.timeout))?;
+                new_connection.set_write_timeout(Some(self.timeout))?;
+                *connection = Some(new_connection);
+            }
+            let mut cmd = redis::cmd("XADD");
+            cmd.arg(stream).arg("*");
+            for (name, value) in fields.iter() {
+                cmd.arg(name).arg(value);
+            }
+            let res = cmd.query::<String>(
+                connection
+                    .as_mut()
+                    .expect("FATAL: Redis connection was just opened"),
+            );
+            if let Err(e) = res.as_ref() {
+                if is_connection_error(e) {
+                    // the connection may be half-used, so the next attempt starts a new one
+                    *connection = None;
+                }
+            }
+            res.map(|_| ())
+        }
+    }
+}
+
+/// Without the `redis-observers` feature, there is no Redis client to make
+#[cfg(not(feature = "redis-observers"))]
+mod client {
+    use std::time::Duration;
+
+    pub struct RedisStream {}
+
+    impl RedisStream {
+        pub fn new(_endpoint: &str, _prefix: &str, _timeout: Duration) -> Result<Self, String> {
+            Err(
+                "Redis delivery needs a stacks-node built with the `redis-observers` feature"
+                    .into(),
+            )
+        }
+
+        pub fn endpoint(&self) -> &str {
+            unreachable!("RedisStream cannot be constructed without the `redis-observers` feature")
+        }
+
+        pub fn prefix(&self) -> &str {
+            unreachable!("RedisStream cannot be constructed without the `redis-observers` feature")
+        }
+
+        pub fn add(&self, _path: &str, _payload: &serde_json::Value) -> Result<(), String> {
+            unreachable!("RedisStream cannot be constructed without the `redis-observers` feature")
+        }
+    }
+}
+
+pub use self::client::RedisStream;
+
+impl std::fmt::Debug for RedisStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisStream")
+            .field("endpoint", &self.endpoint())
+            .field("prefix", &self.prefix())
+            .finish()
+    }
+}
+
+/// Copies of an observer share its stream, so a stream is only equal to itself
+impl PartialEq for RedisStream {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_redis_stream_fields() {
+        assert!(is_redis_endpoint("redis://:hunter2@localhost:6379/0"));
+        assert!(!is_redis_endpoint("localhost:6379"));
+        assert_eq!(
+            redis_stream_name(DEFAULT_REDIS_STREAM_PREFIX, "/new_block"),
+            "stacks:new_block"
+        );
+        assert_eq!(redis_stream_name("", "new_burn_block"), "new_burn_block");
+
+        let payload = json!({
+            "block_height": 100,
+            "index_block_hash": "0x1234",
+            "events": [{ "txid": "0xaa" }],
+        });
+        assert_eq!(
+            redis_stream_fields(&payload, 7),
+            vec![
+                ("seq".to_string(), "7".to_string()),
+                ("block_height".to_string(), "100".to_string()),
+                ("events".to_string(), r#"[{"txid":"0xaa"}]"#.to_string()),
+                ("index_block_hash".to_string(), r#""0x1234""#.to_string()),
+            ]
+        );
+        // a payload that isn't an object, such as a list of mempool transactions, is one field
+        assert_eq!(
+            redis_stream_fields(&json!(["0x00", "0x01"]), 8),
+            vec![
+                ("seq".to_string(), "8".to_string()),
+                ("payload".to_string(), r#"["0x00","0x01"]"#.to_string()),
+            ]
+        );
+    }
+}
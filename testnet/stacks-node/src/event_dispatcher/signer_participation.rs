@@ -0,0 +1,320 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+
+use stacks::chainstate::burn::ConsensusHash;
+use stacks::chainstate::nakamoto::NakamotoBlockHeader;
+use stacks::chainstate::stacks::boot::{NakamotoSignerEntry, RewardSetData};
+use stacks_common::types::chainstate::StacksPublicKey;
+use stacks_common::util::hash::MerkleHashFunc;
+
+use super::hex_serialize;
+
+/// How many announced reward sets to remember. A reward set is announced during the prepare
+/// phase of the cycle before the one it applies to, so at most two can be relevant at once.
+const MAX_TRACKED_REWARD_SETS: usize = 2;
+
+/// One signer's participation over a tenure
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct SignerParticipationEntry {
+    #[serde(serialize_with = "hex_serialize")]
+    pub signing_key: [u8; 33],
+    /// The signer's weight in the tenure's reward set, or `None` if the signer is not in it (or
+    /// the reward set is not known to this node).
+    pub weight: Option<u32>,
+    /// Number of the tenure's blocks that carry this signer's signature
+    pub blocks_signed: u64,
+}
+
+/// Summary of how often each signer signed the blocks of one tenure.
+/// This is sent to `signer_participation` observers once the tenure is complete, i.e. when
+/// the first block of the next tenure is processed.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct SignerParticipationSummary {
+    pub consensus_hash: String,
+    /// Reward cycle of the reward set the signers were drawn from, if known
+    pub cycle_number: Option<u64>,
+    pub first_block_height: u64,
+    pub last_block_height: u64,
+    pub total_blocks: u64,
+    pub signers: Vec<SignerParticipationEntry>,
+}
+
+/// The tenure currently being accumulated
+struct TenureParticipation {
+    consensus_hash: ConsensusHash,
+    cycle_number: Option<u64>,
+    first_block_height: u64,
+    last_block_height: u64,
+    total_blocks: u64,
+    /// Blocks signed, keyed by compressed signing key
+    blocks_signed: BTreeMap<[u8; 33], u64>,
+}
+
+/// Tracks signer signatures across the blocks of a tenure.
+/// Signer public keys are recovered from each Nakamoto block's `signer_signature` and matched
+/// against the signer set of the most recently announced reward sets. Since this tracker only
+/// learns of a reward set from the block that announces it, signers are reported without
+/// weights until the node has processed a reward set announcement.
+#[derive(Default)]
+pub struct SignerParticipationTracker {
+    /// Recently announced reward-set signers, keyed by reward cycle
+    reward_sets: BTreeMap<u64, Vec<NakamotoSignerEntry>>,
+    current_tenure: Option<TenureParticipation>,
+}
+
+impl SignerParticipationTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Recover the compressed public keys of the signers who signed this block.
+    /// Signatures that do not recover to a key are skipped.
+    fn recover_signers(header: &NakamotoBlockHeader) -> Vec<[u8; 33]> {
+        let sighash = header.signer_signature_hash();
+        header
+            .signer_signature
+            .iter()
+            .filter_map(|signature| {
+                let Ok(pubkey) = StacksPublicKey::recover_to_pubkey(sighash.bits(), signature)
+                else {
+                    debug!(
+                        "Failed to recover signer public key from block signature";
+                        "block_id" => %header.block_id(),
+                        "signature" => %signature.to_hex(),
+                    );
+                    return None;
+                };
+                let mut key = [0u8; 33];
+                key.copy_from_slice(&pubkey.to_bytes_compressed());
+                Some(key)
+            })
+            .collect()
+    }
+
+    /// Find the reward cycle whose signer set recognizes the most of the given signers.
+    /// Ties go to the older cycle, since a newly-announced reward set only takes effect at the
+    /// start of its cycle.
+    fn find_reward_cycle(&self, signers: &[[u8; 33]]) -> Option<u64> {
+        let mut best: Option<(u64, usize)> = None;
+        for (cycle, entries) in self.reward_sets.iter() {
+            let recognized = signers
+                .iter()
+                .filter(|key| entries.iter().any(|entry| &entry.signing_key == *key))
+                .count();
+            if recognized == 0 {
+                continue;
+            }
+            if best.map_or(true, |(_, best_count)| recognized > best_count) {
+                best = Some((*cycle, recognized));
+            }
+        }
+        best.map(|(cycle, _)| cycle)
+    }
+
+    /// Build the summary for a completed tenure
+    fn summarize(&self, tenure: TenureParticipation) -> SignerParticipationSummary {
+        let mut blocks_signed = tenure.blocks_signed;
+        let mut signers = vec![];
+        if let Some(entries) = tenure
+            .cycle_number
+            .and_then(|cycle| self.reward_sets.get(&cycle))
+        {
+            for entry in entries.iter() {
+                signers.push(SignerParticipationEntry {
+                    signing_key: entry.signing_key,
+                    weight: Some(entry.weight),
+                    blocks_signed: blocks_signed.remove(&entry.signing_key).unwrap_or(0),
+                });
+            }
+        }
+        // anyone left over was not in the reward set
+        for (signing_key, count) in blocks_signed.into_iter() {
+            signers.push(SignerParticipationEntry {
+                signing_key,
+                weight: None,
+                blocks_signed: count,
+            });
+        }
+
+        SignerParticipationSummary {
+            consensus_hash: format!("0x{}", &tenure.consensus_hash),
+            cycle_number: tenure.cycle_number,
+            first_block_height: tenure.first_block_height,
+            last_block_height: tenure.last_block_height,
+            total_blocks: tenure.total_blocks,
+            signers,
+        }
+    }
+
+    /// Account for a newly-processed Nakamoto block.
+    /// Returns the summary of the previous tenure if this block starts a new one.
+    pub fn record_block(
+        &mut self,
+        header: &NakamotoBlockHeader,
+        block_height: u64,
+        reward_set_data: &Option<RewardSetData>,
+    ) -> Option<SignerParticipationSummary> {
+        let block_signers = Self::recover_signers(header);
+
+        let completed = match self.current_tenure.as_ref() {
+            Some(tenure) if tenure.consensus_hash == header.consensus_hash => None,
+            _ => self.current_tenure.take(),
+        };
+        let summary = completed.map(|tenure| self.summarize(tenure));
+
+        if self.current_tenure.is_none() {
+            self.current_tenure = Some(TenureParticipation {
+                consensus_hash: header.consensus_hash.clone(),
+                cycle_number: self.find_reward_cycle(&block_signers),
+                first_block_height: block_height,
+                last_block_height: block_height,
+                total_blocks: 0,
+                blocks_signed: BTreeMap::new(),
+            });
+        }
+        let tenure = self
+            .current_tenure
+            .as_mut()
+            .expect("FATAL: no tenure to track");
+        tenure.last_block_height = block_height;
+        tenure.total_blocks += 1;
+        for signer in block_signers.into_iter() {
+            *tenure.blocks_signed.entry(signer).or_insert(0) += 1;
+        }
+
+        if let Some(data) = reward_set_data {
+            if let Some(signers) = data.reward_set.signers.as_ref() {
+                self.reward_sets.insert(data.cycle_number, signers.clone());
+                while self.reward_sets.len() > MAX_TRACKED_REWARD_SETS {
+                    self.reward_sets.pop_first();
+                }
+            }
+        }
+
+        summary
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use stacks::chainstate::stacks::boot::RewardSet;
+    use stacks_common::types::chainstate::StacksPrivateKey;
+    use stacks_common::types::PrivateKey;
+    use stacks_common::util::secp256k1::MessageSignature;
+
+    use super::*;
+
+    fn make_signed_header(
+        consensus_hash: ConsensusHash,
+        chain_length: u64,
+        signers: &[&StacksPrivateKey],
+    ) -> NakamotoBlockHeader {
+        let mut header = NakamotoBlockHeader::empty();
+        header.consensus_hash = consensus_hash;
+        header.chain_length = chain_length;
+        let sighash = header.signer_signature_hash();
+        header.signer_signature = signers
+            .iter()
+            .map(|privk| privk.sign(sighash.bits()).unwrap())
+            .collect();
+        header
+    }
+
+    fn signing_key(privk: &StacksPrivateKey) -> [u8; 33] {
+        let mut key = [0u8; 33];
+        key.copy_from_slice(&StacksPublicKey::from_private(privk).to_bytes_compressed());
+        key
+    }
+
+    #[test]
+    fn test_signer_participation_summary() {
+        let privks: Vec<_> = (0..3).map(|_| StacksPrivateKey::new()).collect();
+        let outsider = StacksPrivateKey::new();
+
+        let mut reward_set = RewardSet::empty();
+        reward_set.signers = Some(
+            privks
+                .iter()
+                .enumerate()
+                .map(|(i, privk)| NakamotoSignerEntry {
+                    signing_key: signing_key(privk),
+                    stacked_amt: 1000,
+                    weight: i as u32 + 1,
+                })
+                .collect(),
+        );
+        let reward_set_data = Some(RewardSetData {
+            reward_set,
+            cycle_number: 5,
+        });
+
+        let mut tracker = SignerParticipationTracker::new();
+
+        // the announcing block belongs to a tenure whose reward set is unknown
+        let ch_0 = ConsensusHash([0x00; 20]);
+        let header = make_signed_header(ch_0.clone(), 1, &[&privks[0]]);
+        assert!(tracker.record_block(&header, 1, &reward_set_data).is_none());
+
+        // next tenure: signer 2 misses a block, and an unknown signer signs one
+        let ch_1 = ConsensusHash([0x01; 20]);
+        let header = make_signed_header(ch_1.clone(), 2, &[&privks[0], &privks[1], &privks[2]]);
+        let summary = tracker.record_block(&header, 2, &None).unwrap();
+        assert_eq!(summary.consensus_hash, format!("0x{}", &ch_0));
+        assert_eq!(summary.cycle_number, None);
+        assert_eq!(summary.total_blocks, 1);
+        assert_eq!(
+            summary.signers,
+            vec![SignerParticipationEntry {
+                signing_key: signing_key(&privks[0]),
+                weight: None,
+                blocks_signed: 1,
+            }]
+        );
+
+        let header = make_signed_header(ch_1.clone(), 3, &[&privks[0], &privks[1], &outsider]);
+        assert!(tracker.record_block(&header, 3, &None).is_none());
+
+        // a block with a bad signature still counts towards the tenure
+        let mut header = make_signed_header(ch_1.clone(), 4, &[&privks[0]]);
+        header.signer_signature.push(MessageSignature::empty());
+        assert!(tracker.record_block(&header, 4, &None).is_none());
+
+        let header = make_signed_header(ConsensusHash([0x02; 20]), 5, &[&privks[0]]);
+        let summary = tracker.record_block(&header, 5, &None).unwrap();
+        assert_eq!(summary.consensus_hash, format!("0x{}", &ch_1));
+        assert_eq!(summary.cycle_number, Some(5));
+        assert_eq!(summary.first_block_height, 2);
+        assert_eq!(summary.last_block_height, 4);
+        assert_eq!(summary.total_blocks, 3);
+
+        let counts: Vec<_> = summary
+            .signers
+            .iter()
+            .map(|entry| (entry.signing_key, entry.weight, entry.blocks_signed))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                (signing_key(&privks[0]), Some(1), 3),
+                (signing_key(&privks[1]), Some(2), 2),
+                (signing_key(&privks[2]), Some(3), 1),
+                (signing_key(&outsider), None, 1),
+            ]
+        );
+    }
+}
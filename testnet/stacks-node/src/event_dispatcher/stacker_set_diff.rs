@@ -0,0 +1,271 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use stacks::chainstate::stacks::address::PoxAddress;
+use stacks::chainstate::stacks::boot::{RewardSet, RewardSetData};
+
+use super::{hex_serialize, serialize_optional_u128_as_string, serialize_pox_addresses};
+
+/// How many computed reward sets to remember. Only the most recent one is needed to compute
+/// the next diff, but a reward set can be announced again (e.g. on a fork), in which case it
+/// is compared against the set before it.
+const MAX_TRACKED_REWARD_SETS: usize = 2;
+
+fn serialize_i128_as_string<S>(value: &i128, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(&value.to_string())
+}
+
+/// A signer whose stacked amount differs between two reward sets
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct SignerStackedAmountChange {
+    #[serde(serialize_with = "hex_serialize")]
+    pub signing_key: [u8; 33],
+    /// Amount stacked in the previous cycle, or `None` if the signer entered the set
+    #[serde(serialize_with = "serialize_optional_u128_as_string")]
+    pub previous_stacked_amt: Option<u128>,
+    /// Amount stacked in this cycle, or `None` if the signer left the set
+    #[serde(serialize_with = "serialize_optional_u128_as_string")]
+    pub stacked_amt: Option<u128>,
+    #[serde(serialize_with = "serialize_i128_as_string")]
+    pub stacked_amt_delta: i128,
+}
+
+/// The changes to the stacker set between two reward cycles.
+/// This is sent to `stacker_set_diff` observers whenever a new reward set is computed.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct StackerSetDiff {
+    pub cycle_number: u64,
+    pub previous_cycle_number: u64,
+    /// PoX addresses that are in this cycle's reward set, but not in the previous one
+    #[serde(serialize_with = "serialize_pox_addresses")]
+    pub added_reward_addresses: Vec<PoxAddress>,
+    /// PoX addresses that were in the previous cycle's reward set, but not in this one
+    #[serde(serialize_with = "serialize_pox_addresses")]
+    pub removed_reward_addresses: Vec<PoxAddress>,
+    /// Signers that entered or left the set, or whose stacked amount changed.
+    /// Empty for pre-Nakamoto reward sets, which have no signers.
+    pub signers: Vec<SignerStackedAmountChange>,
+}
+
+/// Retains recently computed reward sets, so each new one can be compared with its
+/// predecessor
+#[derive(Default)]
+pub struct StackerSetDiffTracker {
+    reward_sets: BTreeMap<u64, RewardSet>,
+}
+
+impl StackerSetDiffTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn signer_amounts(reward_set: &RewardSet) -> BTreeMap<[u8; 33], u128> {
+        let mut amounts = BTreeMap::new();
+        for entry in reward_set.signers.iter().flatten() {
+            *amounts.entry(entry.signing_key).or_insert(0u128) += entry.stacked_amt;
+        }
+        amounts
+    }
+
+    fn diff(
+        previous_cycle_number: u64,
+        previous: &RewardSet,
+        cycle_number: u64,
+        current: &RewardSet,
+    ) -> StackerSetDiff {
+        let previous_addresses: BTreeSet<_> = previous.rewarded_addresses.iter().collect();
+        let current_addresses: BTreeSet<_> = current.rewarded_addresses.iter().collect();
+
+        let previous_amounts = Self::signer_amounts(previous);
+        let current_amounts = Self::signer_amounts(current);
+        let signing_keys: BTreeSet<_> = previous_amounts
+            .keys()
+            .chain(current_amounts.keys())
+            .collect();
+        let signers = signing_keys
+            .into_iter()
+            .filter_map(|signing_key| {
+                let previous_stacked_amt = previous_amounts.get(signing_key).copied();
+                let stacked_amt = current_amounts.get(signing_key).copied();
+                if previous_stacked_amt == stacked_amt {
+                    return None;
+                }
+                // stacked amounts are bounded by the total STX supply, so they fit in an i128
+                let stacked_amt_delta = i128::try_from(stacked_amt.unwrap_or(0))
+                    .expect("FATAL: stacked amount exceeds i128::MAX")
+                    - i128::try_from(previous_stacked_amt.unwrap_or(0))
+                        .expect("FATAL: stacked amount exceeds i128::MAX");
+                Some(SignerStackedAmountChange {
+                    signing_key: *signing_key,
+                    previous_stacked_amt,
+                    stacked_amt,
+                    stacked_amt_delta,
+                })
+            })
+            .collect();
+
+        StackerSetDiff {
+            cycle_number,
+            previous_cycle_number,
+            added_reward_addresses: current_addresses
+                .difference(&previous_addresses)
+                .map(|addr| (*addr).clone())
+                .collect(),
+            removed_reward_addresses: previous_addresses
+                .difference(&current_addresses)
+                .map(|addr| (*addr).clone())
+                .collect(),
+            signers,
+        }
+    }
+
+    /// Account for a newly-computed reward set.
+    /// Returns its diff against the latest earlier reward set this tracker has seen, or `None`
+    /// if there is no such set (e.g. this is the first reward set since the node started).
+    pub fn record_reward_set(&mut self, reward_set_data: &RewardSetData) -> Option<StackerSetDiff> {
+        let cycle_number = reward_set_data.cycle_number;
+        let diff = self.reward_sets.range(..cycle_number).next_back().map(
+            |(previous_cycle_number, previous)| {
+                Self::diff(
+                    *previous_cycle_number,
+                    previous,
+                    cycle_number,
+                    &reward_set_data.reward_set,
+                )
+            },
+        );
+
+        self.reward_sets
+            .insert(cycle_number, reward_set_data.reward_set.clone());
+        while self.reward_sets.len() > MAX_TRACKED_REWARD_SETS {
+            self.reward_sets.pop_first();
+        }
+        diff
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use stacks::chainstate::stacks::boot::NakamotoSignerEntry;
+    use stacks_common::types::chainstate::StacksAddress;
+    use stacks_common::util::hash::Hash160;
+
+    use super::*;
+
+    fn pox_addr(byte: u8) -> PoxAddress {
+        PoxAddress::Standard(
+            StacksAddress {
+                version: 26,
+                bytes: Hash160([byte; 20]),
+            },
+            None,
+        )
+    }
+
+    fn make_reward_set_data(
+        cycle_number: u64,
+        addresses: &[u8],
+        signers: &[(u8, u128)],
+    ) -> RewardSetData {
+        let mut reward_set = RewardSet::empty();
+        reward_set.rewarded_addresses = addresses.iter().map(|byte| pox_addr(*byte)).collect();
+        reward_set.signers = Some(
+            signers
+                .iter()
+                .map(|(key, stacked_amt)| NakamotoSignerEntry {
+                    signing_key: [*key; 33],
+                    stacked_amt: *stacked_amt,
+                    weight: 1,
+                })
+                .collect(),
+        );
+        RewardSetData {
+            reward_set,
+            cycle_number,
+        }
+    }
+
+    #[test]
+    fn test_stacker_set_diff() {
+        let mut tracker = StackerSetDiffTracker::new();
+
+        // nothing to compare the first reward set with
+        let data = make_reward_set_data(5, &[1, 2, 2, 3], &[(1, 100), (2, 200)]);
+        assert!(tracker.record_reward_set(&data).is_none());
+
+        let data = make_reward_set_data(6, &[2, 3, 4], &[(2, 250), (3, 300)]);
+        let diff = tracker.record_reward_set(&data).unwrap();
+        assert_eq!(
+            diff,
+            StackerSetDiff {
+                cycle_number: 6,
+                previous_cycle_number: 5,
+                added_reward_addresses: vec![pox_addr(4)],
+                removed_reward_addresses: vec![pox_addr(1)],
+                signers: vec![
+                    SignerStackedAmountChange {
+                        signing_key: [1; 33],
+                        previous_stacked_amt: Some(100),
+                        stacked_amt: None,
+                        stacked_amt_delta: -100,
+                    },
+                    SignerStackedAmountChange {
+                        signing_key: [2; 33],
+                        previous_stacked_amt: Some(200),
+                        stacked_amt: Some(250),
+                        stacked_amt_delta: 50,
+                    },
+                    SignerStackedAmountChange {
+                        signing_key: [3; 33],
+                        previous_stacked_amt: None,
+                        stacked_amt: Some(300),
+                        stacked_amt_delta: 300,
+                    },
+                ],
+            }
+        );
+
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(json["signers"][0]["stacked_amt_delta"], "-100");
+        assert_eq!(json["signers"][0]["stacked_amt"], serde_json::Value::Null);
+        assert_eq!(json["signers"][1]["previous_stacked_amt"], "200");
+        assert_eq!(
+            json["added_reward_addresses"][0],
+            serde_json::Value::String(pox_addr(4).to_b58())
+        );
+
+        // re-announcing a cycle's reward set compares it against the cycle before it
+        let data = make_reward_set_data(6, &[2, 3], &[(2, 200), (3, 300)]);
+        let diff = tracker.record_reward_set(&data).unwrap();
+        assert_eq!(diff.previous_cycle_number, 5);
+        assert_eq!(diff.added_reward_addresses, vec![]);
+        assert_eq!(diff.removed_reward_addresses, vec![pox_addr(1)]);
+        assert_eq!(diff.signers.len(), 2);
+
+        // an unchanged set has an empty diff
+        let data = make_reward_set_data(7, &[3, 2], &[(3, 300), (2, 200)]);
+        let diff = tracker.record_reward_set(&data).unwrap();
+        assert_eq!(diff.previous_cycle_number, 6);
+        assert!(diff.added_reward_addresses.is_empty());
+        assert!(diff.removed_reward_addresses.is_empty());
+        assert!(diff.signers.is_empty());
+    }
+}
@@ -0,0 +1,134 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use clarity::vm::types::QualifiedContractIdentifier;
+use serde_json::json;
+
+/// A `stackerdb_chunks` payload that is collecting the slots of later events for the same
+/// contract
+#[derive(Debug)]
+struct PendingBatch {
+    contract_id: serde_json::Value,
+    modified_slots: Vec<serde_json::Value>,
+}
+
+/// Merges the `stackerdb_chunks` payloads for each StackerDB contract that arrive within a
+/// window into one payload, so that bursts of small chunk events are sent in one POST
+#[derive(Debug)]
+pub struct StackerDBChunkBatcher {
+    /// How long a batch collects events, from its first event until it is sent
+    window: Duration,
+    pending: Mutex<HashMap<QualifiedContractIdentifier, PendingBatch>>,
+}
+
+impl StackerDBChunkBatcher {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Add a `stackerdb_chunks` payload for `contract_id` to its contract's batch. Returns
+    /// `true` if this started a new batch, which the caller must `take()` once the window has
+    /// passed.
+    pub fn add(
+        &self,
+        contract_id: &QualifiedContractIdentifier,
+        payload: &serde_json::Value,
+    ) -> bool {
+        let modified_slots = payload
+            .get("modified_slots")
+            .and_then(|slots| slots.as_array())
+            .cloned()
+            .unwrap_or_default();
+        let mut pending = self
+            .pending
+            .lock()
+            .expect("FATAL: failed to lock StackerDB chunk batches");
+        if let Some(batch) = pending.get_mut(contract_id) {
+            batch.modified_slots.extend(modified_slots);
+            return false;
+        }
+        pending.insert(
+            contract_id.clone(),
+            PendingBatch {
+                contract_id: payload["contract_id"].clone(),
+                modified_slots,
+            },
+        );
+        true
+    }
+
+    /// Remove the batch for `contract_id`, and return it as one `stackerdb_chunks` payload
+    /// with the modified slots of all of its events, in the order they arrived
+    pub fn take(&self, contract_id: &QualifiedContractIdentifier) -> Option<serde_json::Value> {
+        let batch = self
+            .pending
+            .lock()
+            .expect("FATAL: failed to lock StackerDB chunk batches")
+            .remove(contract_id)?;
+        Some(json!({
+            "contract_id": batch.contract_id,
+            "modified_slots": batch.modified_slots,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_stackerdb_chunk_batcher() {
+        let batcher = StackerDBChunkBatcher::new(Duration::from_millis(100));
+        let contract_a = QualifiedContractIdentifier::transient();
+        let contract_b =
+            QualifiedContractIdentifier::parse("ST000000000000000000002AMW42H.signers-0-0")
+                .unwrap();
+        let payload = |contract_id: &QualifiedContractIdentifier, slot_id: u32| {
+            json!({
+                "contract_id": contract_id,
+                "modified_slots": [{ "slot_id": slot_id }],
+            })
+        };
+
+        assert!(batcher.add(&contract_a, &payload(&contract_a, 1)));
+        assert!(batcher.add(&contract_b, &payload(&contract_b, 2)));
+        assert!(!batcher.add(&contract_a, &payload(&contract_a, 3)));
+
+        assert_eq!(
+            batcher.take(&contract_a),
+            Some(json!({
+                "contract_id": contract_a,
+                "modified_slots": [{ "slot_id": 1 }, { "slot_id": 3 }],
+            }))
+        );
+        assert_eq!(batcher.take(&contract_a), None);
+        assert_eq!(batcher.take(&contract_b), Some(payload(&contract_b, 2)));
+
+        // the next event starts a new batch
+        assert!(batcher.add(&contract_a, &payload(&contract_a, 4)));
+    }
+}
@@ -0,0 +1,192 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Detection of StackerDB equivocation.
+//!
+//! A StackerDB replica keeps one chunk per slot, and only replaces it with a chunk of a higher
+//! slot version, so a second, different chunk signed for a slot version that the replica
+//! already has is refused. Since a slot's chunks are signed by its signer, two different chunks
+//! for the same slot version mean that the signer equivocated. This remembers the chunks the
+//! replica accepted at recent slot versions, so that refused chunks can be checked against them.
+
+use std::collections::{HashMap, VecDeque};
+
+use clarity::vm::types::QualifiedContractIdentifier;
+use stacks::libstackerdb::StackerDBChunkData;
+use stacks_common::util::hash::Sha512Trunc256Sum;
+use stacks_common::util::secp256k1::MessageSignature;
+
+/// How many accepted chunks to remember for each StackerDB contract. At about 120 bytes per
+/// chunk, this bounds the tracker to roughly 500 KB for each contract that the node replicates.
+pub const MAX_TRACKED_SLOT_VERSIONS: usize = 4096;
+
+/// A chunk the replica accepted
+#[derive(Debug, Clone, PartialEq)]
+pub struct AcceptedChunk {
+    pub data_hash: Sha512Trunc256Sum,
+    pub sig: MessageSignature,
+}
+
+/// The chunks accepted at recent slot versions of one contract
+#[derive(Default)]
+struct ContractChunks {
+    /// Accepted chunks, keyed by (slot ID, slot version)
+    chunks: HashMap<(u32, u32), AcceptedChunk>,
+    /// The keys of `chunks`, oldest first, for eviction
+    order: VecDeque<(u32, u32)>,
+}
+
+/// The chunks accepted at recent slot versions of each StackerDB contract
+#[derive(Default)]
+pub struct StackerDBEquivocationTracker {
+    contracts: HashMap<QualifiedContractIdentifier, ContractChunks>,
+}
+
+impl StackerDBEquivocationTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a chunk that the replica of `contract_id` accepted. Since the replica only accepts
+    /// increasing slot versions, an accepted chunk whose version isn't above the ones remembered
+    /// for its slot means that the slot has been reset (e.g. for a new reward cycle), so those
+    /// are forgotten.
+    pub fn record_accepted(
+        &mut self,
+        contract_id: &QualifiedContractIdentifier,
+        chunk: &StackerDBChunkData,
+    ) {
+        let contract = self.contracts.entry(contract_id.clone()).or_default();
+        let is_current =
+            |(id, version): &(u32, u32)| *id != chunk.slot_id || *version < chunk.slot_version;
+        contract.chunks.retain(|key, _| is_current(key));
+        contract.order.retain(is_current);
+
+        let key = (chunk.slot_id, chunk.slot_version);
+        contract.chunks.insert(
+            key,
+            AcceptedChunk {
+                data_hash: Sha512Trunc256Sum::from_data(&chunk.data),
+                sig: chunk.sig.clone(),
+            },
+        );
+        contract.order.push_back(key);
+        while contract.order.len() > MAX_TRACKED_SLOT_VERSIONS {
+            if let Some(evicted) = contract.order.pop_front() {
+                contract.chunks.remove(&evicted);
+            }
+        }
+    }
+
+    /// Check a chunk that the replica of `contract_id` refused because it already had a chunk at
+    /// the same slot version. Returns the accepted chunk if it differs from `chunk`, i.e. if the
+    /// slot's signer equivocated. Returns `None` if they are the same chunk, or if the accepted
+    /// chunk is no longer remembered.
+    pub fn check_refused(
+        &self,
+        contract_id: &QualifiedContractIdentifier,
+        chunk: &StackerDBChunkData,
+    ) -> Option<AcceptedChunk> {
+        let accepted = self
+            .contracts
+            .get(contract_id)?
+            .chunks
+            .get(&(chunk.slot_id, chunk.slot_version))?;
+        let data_hash = Sha512Trunc256Sum::from_data(&chunk.data);
+        (accepted.data_hash != data_hash).then(|| accepted.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn make_chunk(slot_id: u32, slot_version: u32, data: &[u8]) -> StackerDBChunkData {
+        StackerDBChunkData {
+            slot_id,
+            slot_version,
+            sig: MessageSignature::empty(),
+            data: data.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_stackerdb_equivocation_tracker() {
+        let contract_id =
+            QualifiedContractIdentifier::parse("ST000000000000000000002AMW42H.signers-0-1")
+                .unwrap();
+        let other_contract_id =
+            QualifiedContractIdentifier::parse("ST000000000000000000002AMW42H.signers-1-1")
+                .unwrap();
+        let mut tracker = StackerDBEquivocationTracker::new();
+
+        tracker.record_accepted(&contract_id, &make_chunk(0, 1, b"hello"));
+        tracker.record_accepted(&contract_id, &make_chunk(0, 2, b"world"));
+
+        // the same chunk again is not equivocation
+        assert_eq!(
+            tracker.check_refused(&contract_id, &make_chunk(0, 1, b"hello")),
+            None
+        );
+        // a different chunk at an accepted slot version is
+        assert_eq!(
+            tracker.check_refused(&contract_id, &make_chunk(0, 2, b"other")),
+            Some(AcceptedChunk {
+                data_hash: Sha512Trunc256Sum::from_data(b"world"),
+                sig: MessageSignature::empty(),
+            })
+        );
+        // other slots, versions and contracts are tracked separately
+        assert_eq!(
+            tracker.check_refused(&contract_id, &make_chunk(1, 2, b"other")),
+            None
+        );
+        assert_eq!(
+            tracker.check_refused(&contract_id, &make_chunk(0, 3, b"other")),
+            None
+        );
+        assert_eq!(
+            tracker.check_refused(&other_contract_id, &make_chunk(0, 2, b"other")),
+            None
+        );
+
+        // a reset slot forgets the versions it is reset below
+        tracker.record_accepted(&contract_id, &make_chunk(0, 1, b"reset"));
+        assert_eq!(
+            tracker.check_refused(&contract_id, &make_chunk(0, 2, b"other")),
+            None
+        );
+        assert!(tracker
+            .check_refused(&contract_id, &make_chunk(0, 1, b"hello"))
+            .is_some());
+
+        // only the most recent chunks are remembered
+        for version in 2..(MAX_TRACKED_SLOT_VERSIONS as u32 + 2) {
+            tracker.record_accepted(&contract_id, &make_chunk(1, version, b"spam"));
+        }
+        assert_eq!(
+            tracker.check_refused(&contract_id, &make_chunk(0, 1, b"hello")),
+            None
+        );
+        assert!(tracker
+            .check_refused(&contract_id, &make_chunk(1, 3, b"other"))
+            .is_some());
+        assert_eq!(
+            tracker.contracts[&contract_id].chunks.len(),
+            MAX_TRACKED_SLOT_VERSIONS
+        );
+    }
+}
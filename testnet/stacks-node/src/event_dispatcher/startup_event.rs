@@ -0,0 +1,123 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The `new_observer` event, sent to an observer with `send_startup_event` when it is
+//! registered.
+//!
+//! Real events may not flow for a while after a node starts (e.g. until its next block), so
+//! an operator wiring up a new observer has no quick way to tell whether the node can reach
+//! it. This event is sent right away, and says which node sent it and what the observer is
+//! subscribed to, so the observer can also record the configuration it is being fed with.
+
+use serde_json::json;
+use stacks::chainstate::stacks::StacksPublicKey;
+use stacks_common::util::hash::Hash160;
+use stacks_common::util::secp256k1::Secp256k1PrivateKey;
+
+use super::observer_registry::ObserverSnapshot;
+use crate::config::{Config, EventObserverConfig};
+
+/// Who the node is, as told to observers in `new_observer` events
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeIdentity {
+    /// Hash of the node's p2p public key, as reported in `/v2/info`'s `node_public_key_hash`
+    pub node_id: Hash160,
+    /// The node's version string, as reported in `/v2/info`'s `server_version`
+    pub version: String,
+    /// `mainnet` or `testnet`
+    pub network: String,
+    pub chain_id: u32,
+}
+
+impl NodeIdentity {
+    /// The identity of this node, run with `config`
+    pub fn from_config(config: &Config) -> Self {
+        let node_privkey = Secp256k1PrivateKey::from_seed(&config.node.local_peer_seed);
+        Self {
+            node_id: Hash160::from_node_public_key(&StacksPublicKey::from_private(&node_privkey)),
+            version: stacks::version_string(
+                "stacks-node",
+                option_env!("STACKS_NODE_VERSION")
+                    .or(option_env!("CARGO_PKG_VERSION"))
+                    .unwrap_or("0.0.0.0"),
+            ),
+            network: if config.is_mainnet() {
+                "mainnet".into()
+            } else {
+                "testnet".into()
+            },
+            chain_id: config.burnchain.chain_id,
+        }
+    }
+}
+
+/// The `new_observer` payload for the observer registered with `conf`. The node's identity
+/// fields are `null` if it isn't known.
+pub fn make_startup_payload(
+    identity: Option<&NodeIdentity>,
+    conf: &EventObserverConfig,
+) -> serde_json::Value {
+    json!({
+        "node_id": identity.map(|identity| format!("0x{}", identity.node_id)),
+        "version": identity.map(|identity| identity.version.clone()),
+        "network": identity.map(|identity| identity.network.clone()),
+        "chain_id": identity.map(|identity| identity.chain_id),
+        "observer": ObserverSnapshot::from_config(conf),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::EventKeyType;
+
+    #[test]
+    fn test_make_startup_payload() {
+        let mut config = Config::default();
+        config.node.local_peer_seed = vec![0x01; 32];
+        let identity = NodeIdentity::from_config(&config);
+        assert!(identity.version.starts_with("stacks-node"));
+        assert_eq!(identity.network, "testnet");
+        assert_eq!(identity.chain_id, config.burnchain.chain_id);
+
+        let conf = EventObserverConfig {
+            endpoint: "user:secret@localhost:3700".into(),
+            events_keys: vec![EventKeyType::BurnchainBlocks],
+            timeout_ms: 1_000,
+            send_startup_event: true,
+            ..EventObserverConfig::default()
+        };
+        let payload = make_startup_payload(Some(&identity), &conf);
+        assert_eq!(payload["node_id"], json!(format!("0x{}", identity.node_id)));
+        assert_eq!(payload["version"], json!(identity.version));
+        assert_eq!(payload["network"], json!("testnet"));
+        assert_eq!(payload["chain_id"], json!(config.burnchain.chain_id));
+
+        // the subscription summary is the observer's registry entry, so credentials are redacted
+        assert_eq!(
+            payload["observer"]["endpoint"],
+            json!("REDACTED@localhost:3700")
+        );
+        assert_eq!(payload["observer"]["events_keys"], json!(["burn_blocks"]));
+        assert_eq!(payload["observer"]["send_startup_event"], json!(true));
+
+        // without an identity, only the observer is described
+        let payload = make_startup_payload(None, &conf);
+        assert!(payload["node_id"].is_null());
+        assert!(payload["chain_id"].is_null());
+        assert_eq!(payload["observer"]["events_keys"], json!(["burn_blocks"]));
+    }
+}
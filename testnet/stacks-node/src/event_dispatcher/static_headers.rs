@@ -0,0 +1,163 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Static headers, such as a bearer token, that are sent with every request to an observer.
+//!
+//! An observer with a `headers` table gets each of its headers with every attempt at
+//! delivering each of its payloads, including retries, over HTTP/1.1 or HTTP/2. The values
+//! are often credentials, so they are never logged: `StaticHeaders` prints with its values
+//! redacted, and wire captures redact them too.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use super::observer_registry::REDACTED;
+
+/// Headers that the event dispatcher sets itself, in lowercase, which an observer's `headers`
+/// cannot override
+pub const RESERVED_HEADERS: [&str; 7] = [
+    "host",
+    "content-type",
+    "content-length",
+    "transfer-encoding",
+    "connection",
+    "idempotency-key",
+    "x-partition-key",
+];
+
+/// The headers sent with every request to an observer, in the order they were configured
+#[derive(Clone, Default, PartialEq)]
+pub struct StaticHeaders(Vec<(String, String)>);
+
+impl fmt::Debug for StaticHeaders {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.0.iter().map(|(name, _)| (name, REDACTED)))
+            .finish()
+    }
+}
+
+/// Whether `name` can be sent as a header name (an HTTP token)
+fn is_valid_header_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+/// Whether `value` can be sent as a header value, without control characters other than tab
+fn is_valid_header_value(value: &str) -> bool {
+    value
+        .bytes()
+        .all(|b| b == b'\t' || (b >= 0x20 && b != 0x7f))
+}
+
+impl StaticHeaders {
+    /// Parse an observer's `headers` table. Fails if a name or value can't be sent, if a name
+    /// is one of the `RESERVED_HEADERS`, or if two names differ only in case.
+    pub fn parse(headers: &BTreeMap<String, String>) -> Result<Self, String> {
+        let mut names = HashSet::new();
+        for (name, value) in headers.iter() {
+            if !is_valid_header_name(name) {
+                return Err(format!("Invalid header name '{}'", name));
+            }
+            if !is_valid_header_value(value) {
+                return Err(format!("Invalid value for header '{}'", name));
+            }
+            let lowercase_name = name.to_lowercase();
+            if RESERVED_HEADERS.contains(&lowercase_name.as_str()) {
+                return Err(format!(
+                    "Header '{}' is set by the event dispatcher, and cannot be overridden",
+                    name
+                ));
+            }
+            if !names.insert(lowercase_name) {
+                return Err(format!("Header '{}' is given more than once", name));
+            }
+        }
+        Ok(Self(
+            headers
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+        ))
+    }
+
+    /// The headers' names and values
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Whether `name` is one of the headers, whatever its case
+    pub fn contains(&self, name: &str) -> bool {
+        self.0
+            .iter()
+            .any(|(header, _)| header.eq_ignore_ascii_case(name))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_static_headers_parse() {
+        let headers = StaticHeaders::parse(&BTreeMap::from([
+            ("Authorization".to_string(), "Bearer hunter2".to_string()),
+            ("X-Service".to_string(), "indexer".to_string()),
+        ]))
+        .unwrap();
+        assert_eq!(
+            headers.iter().collect::<Vec<_>>(),
+            vec![
+                ("Authorization", "Bearer hunter2"),
+                ("X-Service", "indexer")
+            ]
+        );
+        // the values never make it into logs
+        let printed = format!("{:?}", headers);
+        assert!(!printed.contains("hunter2"), "{}", printed);
+        assert!(printed.contains("Authorization"), "{}", printed);
+        assert!(headers.contains("authorization") && !headers.contains("X-Other"));
+        assert!(StaticHeaders::parse(&BTreeMap::new()).unwrap().is_empty());
+
+        for (name, value) in [
+            ("", "value"),
+            ("Bad Name", "value"),
+            ("X-Injected", "value\r\nX-Other: 1"),
+            ("Content-Type", "text/plain"),
+            ("idempotency-key", "abcd"),
+        ] {
+            assert!(
+                StaticHeaders::parse(&BTreeMap::from([(name.to_string(), value.to_string())]))
+                    .is_err(),
+                "{}",
+                name
+            );
+        }
+        assert!(StaticHeaders::parse(&BTreeMap::from([
+            ("X-Token".to_string(), "a".to_string()),
+            ("x-token".to_string(), "b".to_string()),
+        ]))
+        .is_err());
+    }
+}
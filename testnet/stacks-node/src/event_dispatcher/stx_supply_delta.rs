@@ -0,0 +1,124 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2024 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The net change in STX supply made by a block, for `include_stx_supply_delta` observers.
+//!
+//! Economics trackers otherwise have to sum every STX event of every block themselves, and it
+//! is easy to get subtly wrong: e.g. the events of a transaction that failed its post-conditions
+//! are still reported, but none of them took effect. The totals are taken from the same STX
+//! events that the dispatch matrix routes to observers, as the matrix is built.
+
+use clarity::vm::events::STXEventType;
+use serde_json::json;
+
+/// The STX minted, burned and locked by a block's committed transactions, in microSTX
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StxSupplyDelta {
+    pub minted: u128,
+    pub burned: u128,
+    pub locked: u128,
+}
+
+impl StxSupplyDelta {
+    /// Count an STX event of a transaction that took effect. Transfers move STX around, but
+    /// don't change the supply.
+    pub fn add_event(&mut self, event: &STXEventType) {
+        match event {
+            STXEventType::STXMintEvent(event_data) => {
+                self.minted = self.minted.saturating_add(event_data.amount);
+            }
+            STXEventType::STXBurnEvent(event_data) => {
+                self.burned = self.burned.saturating_add(event_data.amount);
+            }
+            STXEventType::STXLockEvent(event_data) => {
+                self.locked = self.locked.saturating_add(event_data.locked_amount);
+            }
+            STXEventType::STXTransferEvent(_) => {}
+        }
+    }
+
+    /// The `stx_supply_delta` object of a `new_block` payload. Amounts are strings, as they are
+    /// in STX events, since they may not fit in a JSON number.
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "minted": self.minted.to_string(),
+            "burned": self.burned.to_string(),
+            "locked": self.locked.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use clarity::vm::events::{
+        STXBurnEventData, STXLockEventData, STXMintEventData, STXTransferEventData,
+    };
+    use clarity::vm::types::{BuffData, PrincipalData, QualifiedContractIdentifier};
+
+    use super::*;
+
+    #[test]
+    fn test_stx_supply_delta() {
+        let principal = PrincipalData::parse("ST000000000000000000002AMW42H").unwrap();
+        let mut delta = StxSupplyDelta::default();
+        for event in [
+            STXEventType::STXMintEvent(STXMintEventData {
+                recipient: principal.clone(),
+                amount: 1_000,
+            }),
+            STXEventType::STXMintEvent(STXMintEventData {
+                recipient: principal.clone(),
+                amount: u128::MAX,
+            }),
+            STXEventType::STXBurnEvent(STXBurnEventData {
+                sender: principal.clone(),
+                amount: 300,
+            }),
+            STXEventType::STXLockEvent(STXLockEventData {
+                locked_amount: 50,
+                unlock_height: 100,
+                locked_address: principal.clone(),
+                contract_identifier: QualifiedContractIdentifier::transient(),
+            }),
+            STXEventType::STXTransferEvent(STXTransferEventData {
+                sender: principal.clone(),
+                recipient: principal.clone(),
+                amount: 7,
+                memo: BuffData::empty(),
+            }),
+        ] {
+            delta.add_event(&event);
+        }
+
+        // totals saturate rather than wrap
+        assert_eq!(
+            delta,
+            StxSupplyDelta {
+                minted: u128::MAX,
+                burned: 300,
+                locked: 50,
+            }
+        );
+        assert_eq!(
+            delta.to_json(),
+            json!({
+                "minted": u128::MAX.to_string(),
+                "burned": "300",
+                "locked": "50",
+            })
+        );
+    }
+}
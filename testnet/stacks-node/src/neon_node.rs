@@ -4577,6 +4577,7 @@ impl PeerThread {
                 cost_estimator: Some(cost_estimator.as_ref()),
                 cost_metric: Some(cost_metric.as_ref()),
                 fee_estimator: fee_estimator.map(|boxed_estimator| boxed_estimator.as_ref()),
+                archival_block_source: p2p_thread.config.make_archival_block_source(),
                 ..RPCHandlerArgs::default()
             };
             p2p_thread.with_network(|_, net| {
@@ -46,6 +46,7 @@ use stacks_common::util::vrf::VRFPublicKey;
 
 use super::{BurnchainController, BurnchainTip, Config, EventDispatcher, Keychain, Tenure};
 use crate::burnchains::make_bitcoin_indexer;
+use crate::event_dispatcher::{MinedBlockArchive, NodeIdentity};
 use crate::genesis_data::USE_TEST_GENESIS_CHAINSTATE;
 use crate::run_loop;
 use crate::run_loop::RegisteredKey;
@@ -339,9 +340,37 @@ impl Node {
 
         let mut event_dispatcher = EventDispatcher::new();
 
+        event_dispatcher.set_max_observers(config.node.max_event_observers);
+        event_dispatcher.set_node_identity(NodeIdentity::from_config(&config));
+        event_dispatcher.set_host_delivery_limits(
+            config.node.event_observer_max_in_flight_per_host,
+            config.node.event_observer_host_limits.clone(),
+        );
         for observer in &config.events_observers {
-            event_dispatcher.register_observer(observer, config.get_working_dir());
+            event_dispatcher
+                .register_observer(observer, config.get_working_dir())
+                .expect("FATAL: failed to register event observer");
         }
+        event_dispatcher.set_sortdb_path(config.get_burn_db_file_path());
+        event_dispatcher.set_chainstate_path(config.get_chainstate_path_str());
+        event_dispatcher.set_block_retry_budget(
+            config
+                .node
+                .block_event_retry_budget_ms
+                .map(time::Duration::from_millis),
+        );
+        event_dispatcher.set_cost_near_limit_threshold(config.node.cost_near_limit_threshold);
+        event_dispatcher.set_event_serialization_threads(config.node.event_serialization_threads);
+        event_dispatcher.set_mempool_drop_history_size(config.node.mempool_drop_history_size);
+        event_dispatcher.set_proposal_response_min_interval(
+            config
+                .node
+                .proposal_response_min_interval_ms
+                .map(time::Duration::from_millis),
+        );
+        event_dispatcher.set_mined_block_archive(config.node.mined_block_archive_dir.as_ref().map(
+            |dir| MinedBlockArchive::new(dir.into(), config.node.mined_block_archive_retention),
+        ));
 
         let burnchain_config = config.get_burnchain();
 
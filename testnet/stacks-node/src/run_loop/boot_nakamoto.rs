@@ -186,6 +186,11 @@ impl BootRunLoop {
             Some(counters),
             monitoring_thread,
         );
+        // the nakamoto node has its own event dispatcher, which shouldn't tell observers that
+        //  the node caught up to the chain tip if the neon node already did
+        if neon_loop.get_event_dispatcher().is_node_synced() {
+            naka.get_event_dispatcher().set_node_synced();
+        }
         let new_coord_channels = naka
             .get_coordinator_channel()
             .expect("FATAL: should have coordinator channel in newly instantiated runloop");
@@ -6,7 +6,7 @@ pub mod neon;
 use clarity::vm::costs::ExecutionCost;
 use clarity::vm::database::BurnStateDB;
 use stacks::burnchains::{PoxConstants, Txid};
-use stacks::chainstate::stacks::db::StacksChainState;
+use stacks::chainstate::stacks::db::{BlockProcessingTimings, StacksChainState};
 use stacks::chainstate::stacks::events::StacksTransactionReceipt;
 use stacks::chainstate::stacks::{
     StacksBlock, TransactionAuth, TransactionPayload, TransactionSpendingCondition,
@@ -199,5 +199,6 @@ pub fn announce_boot_receipts(
         &None,
         None,
         0,
+        &BlockProcessingTimings::default(),
     );
 }
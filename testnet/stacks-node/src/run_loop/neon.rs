@@ -31,6 +31,7 @@ use stx_genesis::GenesisData;
 
 use super::RunLoopCallbacks;
 use crate::burnchains::{make_bitcoin_indexer, Error};
+use crate::event_dispatcher::{MinedBlockArchive, NodeIdentity};
 use crate::globals::NeonGlobals as Globals;
 use crate::monitoring::{start_serving_monitoring_metrics, MonitoringError};
 use crate::neon_node::{
@@ -235,9 +236,37 @@ impl RunLoop {
         )));
 
         let mut event_dispatcher = EventDispatcher::new();
+        event_dispatcher.set_max_observers(config.node.max_event_observers);
+        event_dispatcher.set_node_identity(NodeIdentity::from_config(&config));
+        event_dispatcher.set_host_delivery_limits(
+            config.node.event_observer_max_in_flight_per_host,
+            config.node.event_observer_host_limits.clone(),
+        );
         for observer in config.events_observers.iter() {
-            event_dispatcher.register_observer(observer, config.get_working_dir());
+            event_dispatcher
+                .register_observer(observer, config.get_working_dir())
+                .expect("FATAL: failed to register event observer");
         }
+        event_dispatcher.set_sortdb_path(config.get_burn_db_file_path());
+        event_dispatcher.set_chainstate_path(config.get_chainstate_path_str());
+        event_dispatcher.set_block_retry_budget(
+            config
+                .node
+                .block_event_retry_budget_ms
+                .map(std::time::Duration::from_millis),
+        );
+        event_dispatcher.set_cost_near_limit_threshold(config.node.cost_near_limit_threshold);
+        event_dispatcher.set_event_serialization_threads(config.node.event_serialization_threads);
+        event_dispatcher.set_mempool_drop_history_size(config.node.mempool_drop_history_size);
+        event_dispatcher.set_proposal_response_min_interval(
+            config
+                .node
+                .proposal_response_min_interval_ms
+                .map(std::time::Duration::from_millis),
+        );
+        event_dispatcher.set_mined_block_archive(config.node.mined_block_archive_dir.as_ref().map(
+            |dir| MinedBlockArchive::new(dir.into(), config.node.mined_block_archive_retention),
+        ));
 
         Self {
             config,
@@ -1397,6 +1426,8 @@ impl RunLoop {
                     //  this prevents a possible corner case in the event of a PoX fork.
                     mine_start = 0;
                     globals.set_start_mining_height_if_zero(sortition_db_height);
+                    self.event_dispatcher
+                        .process_node_synced(canonical_stacks_tip_height, sortition_db_height);
 
                     // at tip, and not downloading. proceed to mine.
                     if last_tenure_sortition_height != sortition_db_height {
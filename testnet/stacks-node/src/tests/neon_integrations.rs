@@ -638,6 +638,8 @@ pub mod test_observer {
             endpoint: format!("localhost:{EVENT_OBSERVER_PORT}"),
             events_keys: event_keys.to_vec(),
             timeout_ms: 1000,
+            parallel_connections: 1,
+            ..EventObserverConfig::default()
         });
     }
 
@@ -7595,6 +7597,8 @@ fn atlas_integration_test() {
             endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
             events_keys: vec![EventKeyType::AnyEvent],
             timeout_ms: 1000,
+            parallel_connections: 1,
+            ..EventObserverConfig::default()
         });
 
     conf_follower_node.node.always_use_affirmation_maps = false;
@@ -8141,6 +8145,8 @@ fn antientropy_integration_test() {
             endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
             events_keys: vec![EventKeyType::AnyEvent],
             timeout_ms: 1000,
+            parallel_connections: 1,
+            ..EventObserverConfig::default()
         });
 
     conf_follower_node.node.mine_microblocks = true;
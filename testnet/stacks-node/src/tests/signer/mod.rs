@@ -651,6 +651,8 @@ fn setup_stx_btc_node<G: FnMut(&mut NeonConfig) -> ()>(
                 EventKeyType::BurnchainBlocks,
             ],
             timeout_ms: 1000,
+            parallel_connections: 1,
+            ..EventObserverConfig::default()
         });
     }
 
@@ -666,6 +668,8 @@ fn setup_stx_btc_node<G: FnMut(&mut NeonConfig) -> ()>(
             EventKeyType::BurnchainBlocks,
         ],
         timeout_ms: 1000,
+        parallel_connections: 1,
+        ..EventObserverConfig::default()
     });
 
     // The signers need some initial balances in order to pay for epoch 2.5 transaction votes
@@ -3027,6 +3027,8 @@ fn signer_set_rollover() {
                         EventKeyType::BurnchainBlocks,
                     ],
                     timeout_ms: 1000,
+                    parallel_connections: 1,
+                    ..EventObserverConfig::default()
                 });
             }
             naka_conf.node.rpc_bind = rpc_bind.clone();